@@ -15,6 +15,7 @@
 use std::borrow::Cow;
 use std::cell::OnceCell;
 use std::collections::BTreeMap;
+use std::collections::HashMap;
 use std::collections::HashSet;
 use std::env;
 use std::ffi::OsString;
@@ -41,6 +42,7 @@ use clap::builder::TypedValueParser;
 use clap::builder::ValueParserFactory;
 use clap::error::ContextKind;
 use clap::error::ContextValue;
+use clap::parser::ValueSource;
 use clap::ArgAction;
 use clap::ArgMatches;
 use clap::Command;
@@ -51,6 +53,7 @@ use indexmap::IndexMap;
 use indexmap::IndexSet;
 use indoc::writedoc;
 use itertools::Itertools;
+use jj_lib::backend::BackendError;
 use jj_lib::backend::BackendResult;
 use jj_lib::backend::ChangeId;
 use jj_lib::backend::CommitId;
@@ -64,14 +67,20 @@ use jj_lib::config::ConfigMigrationRule;
 use jj_lib::config::ConfigNamePathBuf;
 use jj_lib::config::ConfigSource;
 use jj_lib::config::StackedConfig;
+use jj_lib::conflicts::extract_as_single_hunk;
+use jj_lib::conflicts::is_binary_conflict;
 use jj_lib::conflicts::ConflictMarkerStyle;
+use jj_lib::eol::EolConversionMode;
 use jj_lib::fileset;
+use jj_lib::fileset::FilesetAliasesMap;
 use jj_lib::fileset::FilesetDiagnostics;
 use jj_lib::fileset::FilesetExpression;
 use jj_lib::gitignore::GitIgnoreError;
 use jj_lib::gitignore::GitIgnoreFile;
 use jj_lib::id_prefix::IdPrefixContext;
+use jj_lib::local_working_copy::FileStates;
 use jj_lib::matchers::Matcher;
+use jj_lib::matchers::NothingMatcher;
 use jj_lib::merge::MergedTreeValue;
 use jj_lib::merged_tree::MergedTree;
 use jj_lib::object_id::ObjectId;
@@ -113,12 +122,16 @@ use jj_lib::revset::UserRevsetExpression;
 use jj_lib::rewrite::restore_tree;
 use jj_lib::settings::HumanByteSize;
 use jj_lib::settings::UserSettings;
+use jj_lib::str_util::GlobPattern;
 use jj_lib::str_util::StringPattern;
 use jj_lib::transaction::Transaction;
 use jj_lib::view::View;
 use jj_lib::working_copy;
 use jj_lib::working_copy::CheckoutOptions;
+use jj_lib::working_copy::CheckoutSkipReason;
 use jj_lib::working_copy::CheckoutStats;
+use jj_lib::working_copy::CloneMode;
+use jj_lib::working_copy::ExecutableBitMode;
 use jj_lib::working_copy::SnapshotOptions;
 use jj_lib::working_copy::SnapshotStats;
 use jj_lib::working_copy::UntrackedReason;
@@ -134,6 +147,7 @@ use jj_lib::workspace::Workspace;
 use jj_lib::workspace::WorkspaceLoadError;
 use jj_lib::workspace::WorkspaceLoader;
 use jj_lib::workspace::WorkspaceLoaderFactory;
+use pollster::FutureExt as _;
 use tracing::instrument;
 use tracing_chrome::ChromeLayerBuilder;
 use tracing_subscriber::prelude::*;
@@ -155,7 +169,9 @@ use crate::config::parse_config_args;
 use crate::config::ConfigArgKind;
 use crate::config::ConfigEnv;
 use crate::config::RawConfig;
+use crate::config_schema::check_config_unknown_keys;
 use crate::description_util::TextEditor;
+use crate::diff_stat_cache::DiffStatCache;
 use crate::diff_util;
 use crate::diff_util::DiffFormat;
 use crate::diff_util::DiffFormatArgs;
@@ -182,6 +198,9 @@ use crate::ui::Ui;
 
 const SHORT_CHANGE_ID_TEMPLATE_TEXT: &str = "format_short_change_id(self.change_id())";
 
+/// Name of the directory in the repo where computed data is cached.
+const CACHE_DIR_NAME: &str = "cache";
+
 #[derive(Clone)]
 struct ChromeTracingFlushGuard {
     _inner: Option<Rc<tracing_chrome::FlushGuard>>,
@@ -428,14 +447,39 @@ impl CommandHelper {
     pub fn workspace_helper_with_stats(
         &self,
         ui: &Ui,
+    ) -> Result<(WorkspaceCommandHelper, SnapshotStats), CommandError> {
+        self.workspace_helper_with_stats_impl(ui, false)
+    }
+
+    /// Like [`Self::workspace_helper_with_stats()`], but also reports the
+    /// untracked paths which are ignored.
+    #[instrument(skip(self, ui))]
+    pub fn workspace_helper_with_ignored_paths(
+        &self,
+        ui: &Ui,
+    ) -> Result<(WorkspaceCommandHelper, SnapshotStats), CommandError> {
+        self.workspace_helper_with_stats_impl(ui, true)
+    }
+
+    fn workspace_helper_with_stats_impl(
+        &self,
+        ui: &Ui,
+        report_ignored_paths: bool,
     ) -> Result<(WorkspaceCommandHelper, SnapshotStats), CommandError> {
         let mut workspace_command = self.workspace_helper_no_snapshot(ui)?;
+        // Update the recorded root path in case the workspace has been moved.
+        // This is best-effort since the repo might be read-only.
+        if let Err(err) = workspace_command.workspace().update_recorded_path() {
+            tracing::warn!(?err, "failed to update recorded workspace path");
+        }
 
-        let (workspace_command, stats) = match workspace_command.maybe_snapshot_impl(ui) {
+        let (workspace_command, stats) = match workspace_command
+            .maybe_snapshot_impl(ui, report_ignored_paths)
+        {
             Ok(stats) => (workspace_command, stats),
             Err(SnapshotWorkingCopyError::Command(err)) => return Err(err),
             Err(SnapshotWorkingCopyError::StaleWorkingCopy(err)) => {
-                let auto_update_stale = self.settings().get_bool("snapshot.auto-update-stale")?;
+                let auto_update_stale = self.settings().get_bool("workspace.auto-update-stale")?;
                 if !auto_update_stale {
                     return Err(err);
                 }
@@ -531,7 +575,7 @@ impl CommandHelper {
                 // merged repo wouldn't change because the old one wins, but it's probably
                 // fine if we picked the new wc_commit_id.
                 let stats = workspace_command
-                    .maybe_snapshot_impl(ui)
+                    .maybe_snapshot_impl(ui, false)
                     .map_err(|err| err.into_command_error())?;
 
                 let wc_commit_id = workspace_command.get_wc_commit_id().unwrap();
@@ -557,13 +601,18 @@ impl CommandHelper {
                     }
                     WorkingCopyFreshness::WorkingCopyStale
                     | WorkingCopyFreshness::SiblingOperation => {
-                        let stats = update_stale_working_copy(
-                            locked_ws,
-                            repo.op_id().clone(),
-                            &stale_wc_commit,
-                            &desired_wc_commit,
-                            &checkout_options,
-                        )?;
+                        let stats = {
+                            let mut options = checkout_options;
+                            let progress = crate::progress::checkout_progress(ui);
+                            options.progress = progress.as_ref().map(|x| x as _);
+                            update_stale_working_copy(
+                                locked_ws,
+                                repo.op_id().clone(),
+                                &stale_wc_commit,
+                                &desired_wc_commit,
+                                &options,
+                            )?
+                        };
 
                         // TODO: Share this code with new/checkout somehow.
                         if let Some(mut formatter) = ui.status_formatter() {
@@ -573,7 +622,12 @@ impl CommandHelper {
                             })?;
                             writeln!(formatter)?;
                         }
-                        print_checkout_stats(ui, stats, &desired_wc_commit)?;
+                        print_checkout_stats(
+                            ui,
+                            stats,
+                            &desired_wc_commit,
+                            workspace_command.path_converter(),
+                        )?;
 
                         writeln!(
                             ui.status(),
@@ -642,6 +696,13 @@ impl CommandHelper {
                 repo_loader.op_heads_store().as_ref(),
                 repo_loader.op_store(),
                 |op_heads| {
+                    if !repo_loader.settings().get_bool("op.auto-merge")? {
+                        return Err(user_error_with_hint(
+                            "Concurrent modification detected",
+                            "Use `jj op heads` to list the divergent operations, and `jj op \
+                             merge` to merge them.",
+                        ));
+                    }
                     writeln!(
                         ui.status(),
                         "Concurrent modification detected, resolving automatically.",
@@ -780,11 +841,19 @@ pub struct WorkspaceCommandEnvironment {
     settings: UserSettings,
     revset_aliases_map: RevsetAliasesMap,
     template_aliases_map: TemplateAliasesMap,
+    fileset_aliases_map: FilesetAliasesMap,
     path_converter: RepoPathUiConverter,
     workspace_id: WorkspaceId,
     immutable_heads_expression: Rc<UserRevsetExpression>,
     short_prefixes_expression: Option<Rc<UserRevsetExpression>>,
     conflict_marker_style: ConflictMarkerStyle,
+    create_symlinks: bool,
+    eol_conversion_mode: EolConversionMode,
+    executable_bit_mode: ExecutableBitMode,
+    clone_mode: CloneMode,
+    binary_conflict_side: usize,
+    diff_prefetch_concurrency: Option<usize>,
+    diff_stat_cache: DiffStatCache,
 }
 
 impl WorkspaceCommandEnvironment {
@@ -793,6 +862,7 @@ impl WorkspaceCommandEnvironment {
         let settings = workspace.settings();
         let revset_aliases_map = revset_util::load_revset_aliases(ui, settings.config())?;
         let template_aliases_map = load_template_aliases(ui, settings.config())?;
+        let fileset_aliases_map = load_fileset_aliases(ui, settings.config())?;
         let path_converter = RepoPathUiConverter::Fs {
             cwd: command.cwd().to_owned(),
             base: workspace.workspace_root().to_owned(),
@@ -802,11 +872,22 @@ impl WorkspaceCommandEnvironment {
             settings: settings.clone(),
             revset_aliases_map,
             template_aliases_map,
+            fileset_aliases_map,
             path_converter,
             workspace_id: workspace.workspace_id().to_owned(),
             immutable_heads_expression: RevsetExpression::root(),
             short_prefixes_expression: None,
             conflict_marker_style: settings.get("ui.conflict-marker-style")?,
+            create_symlinks: settings.get_bool("working-copy.symlinks")?,
+            eol_conversion_mode: settings.get("working-copy.eol-conversion")?,
+            executable_bit_mode: settings.get("working-copy.executable-bit")?,
+            clone_mode: settings.get("working-copy.clone-mode")?,
+            binary_conflict_side: settings.get("working-copy.binary-conflict-side")?,
+            diff_prefetch_concurrency: diff_util::prefetch_concurrency_from_settings(settings)?,
+            diff_stat_cache: DiffStatCache::new(
+                workspace.repo_path().join(CACHE_DIR_NAME),
+                settings.get("diff.stat-cache-max-entries")?,
+            ),
         };
         env.immutable_heads_expression = env.load_immutable_heads_expression(ui)?;
         env.short_prefixes_expression = env.load_short_prefixes_expression(ui)?;
@@ -817,6 +898,10 @@ impl WorkspaceCommandEnvironment {
         &self.path_converter
     }
 
+    pub(crate) fn fileset_aliases_map(&self) -> &FilesetAliasesMap {
+        &self.fileset_aliases_map
+    }
+
     pub fn workspace_id(&self) -> &WorkspaceId {
         &self.workspace_id
     }
@@ -824,6 +909,7 @@ impl WorkspaceCommandEnvironment {
     pub(crate) fn revset_parse_context(&self) -> RevsetParseContext {
         let workspace_context = RevsetWorkspaceContext {
             path_converter: &self.path_converter,
+            fileset_aliases_map: &self.fileset_aliases_map,
             workspace_id: &self.workspace_id,
         };
         let now = if let Some(timestamp) = self.settings.commit_timestamp() {
@@ -869,6 +955,31 @@ impl WorkspaceCommandEnvironment {
         self.conflict_marker_style
     }
 
+    /// Whether to create symlinks when updating the working copy
+    pub fn create_symlinks(&self) -> bool {
+        self.create_symlinks
+    }
+
+    /// User-configured line-ending conversion for the working copy
+    pub fn eol_conversion_mode(&self) -> EolConversionMode {
+        self.eol_conversion_mode
+    }
+
+    /// User-configured handling of the executable bit in the working copy
+    pub fn executable_bit_mode(&self) -> ExecutableBitMode {
+        self.executable_bit_mode
+    }
+
+    /// User-configured way to write file contents to the working copy
+    pub fn clone_mode(&self) -> CloneMode {
+        self.clone_mode
+    }
+
+    /// User-configured side of binary conflicts to write to the working copy
+    pub fn binary_conflict_side(&self) -> usize {
+        self.binary_conflict_side
+    }
+
     fn load_immutable_heads_expression(
         &self,
         ui: &Ui,
@@ -971,11 +1082,13 @@ impl WorkspaceCommandEnvironment {
         CommitTemplateLanguage::new(
             repo,
             &self.path_converter,
+            &self.fileset_aliases_map,
             &self.workspace_id,
             self.revset_parse_context(),
             id_prefix_context,
             self.immutable_expression(),
             self.conflict_marker_style,
+            &self.diff_stat_cache,
             &self.command.data.commit_template_extensions,
         )
     }
@@ -1048,8 +1161,26 @@ impl WorkspaceCommandHelper {
         // Parse commit_summary template early to report error before starting
         // mutable operation.
         helper.parse_operation_template(ui, &helper.op_summary_template_text)?;
-        helper.parse_commit_template(ui, &helper.commit_summary_template_text)?;
-        helper.parse_commit_template(ui, SHORT_CHANGE_ID_TEMPLATE_TEXT)?;
+        // Validate commit templates without the short-prefixes disambiguation
+        // so that the index isn't loaded until a commit is actually rendered.
+        {
+            let id_prefix_context =
+                IdPrefixContext::new(helper.env.command.revset_extensions().clone());
+            let language = helper
+                .env
+                .commit_template_language(helper.repo().as_ref(), &id_prefix_context);
+            for template_text in [
+                helper.commit_summary_template_text.as_str(),
+                SHORT_CHANGE_ID_TEMPLATE_TEXT,
+            ] {
+                helper.parse_template(
+                    ui,
+                    &language,
+                    template_text,
+                    CommitTemplateLanguage::wrap_commit,
+                )?;
+            }
+        }
         Ok(helper)
     }
 
@@ -1078,7 +1209,11 @@ impl WorkspaceCommandHelper {
     /// call [`print_snapshot_stats`] with the [`SnapshotStats`] returned by
     /// this function to present possible untracked files to the user.
     #[instrument(skip_all)]
-    fn maybe_snapshot_impl(&mut self, ui: &Ui) -> Result<SnapshotStats, SnapshotWorkingCopyError> {
+    fn maybe_snapshot_impl(
+        &mut self,
+        ui: &Ui,
+        report_ignored_paths: bool,
+    ) -> Result<SnapshotStats, SnapshotWorkingCopyError> {
         if !self.may_update_working_copy {
             return Ok(SnapshotStats::default());
         }
@@ -1091,7 +1226,7 @@ impl WorkspaceCommandHelper {
         // pointing to the new working-copy commit might not be exported.
         // In that situation, the ref would be conflicted anyway, so export
         // failure is okay.
-        let stats = self.snapshot_working_copy(ui)?;
+        let stats = self.snapshot_working_copy(ui, report_ignored_paths)?;
 
         // import_git_refs() can rebase the working-copy commit.
         #[cfg(feature = "git")]
@@ -1106,7 +1241,7 @@ impl WorkspaceCommandHelper {
     #[instrument(skip_all)]
     pub fn maybe_snapshot(&mut self, ui: &Ui) -> Result<(), CommandError> {
         let stats = self
-            .maybe_snapshot_impl(ui)
+            .maybe_snapshot_impl(ui, false)
             .map_err(|err| err.into_command_error())?;
         print_snapshot_stats(ui, &stats, self.env().path_converter())?;
         Ok(())
@@ -1216,6 +1351,12 @@ impl WorkspaceCommandHelper {
         self.workspace.repo_path()
     }
 
+    /// Directory where data computed from the repo, such as diff stats, is
+    /// cached.
+    pub fn cache_dir(&self) -> PathBuf {
+        self.workspace.repo_path().join(CACHE_DIR_NAME)
+    }
+
     pub fn workspace(&self) -> &Workspace {
         &self.workspace
     }
@@ -1228,9 +1369,14 @@ impl WorkspaceCommandHelper {
         &self.env
     }
 
-    pub fn checkout_options(&self) -> CheckoutOptions {
+    pub fn checkout_options(&self) -> CheckoutOptions<'static> {
         CheckoutOptions {
             conflict_marker_style: self.env.conflict_marker_style(),
+            create_symlinks: self.env.create_symlinks(),
+            eol_conversion_mode: self.env.eol_conversion_mode(),
+            clone_mode: self.env.clone_mode(),
+            binary_conflict_side: self.env.binary_conflict_side(),
+            progress: None,
         }
     }
 
@@ -1289,7 +1435,7 @@ to the current parents may contain changes from multiple commits.
         locked_ws.finish(repo.op_id().clone())?;
         self.user_repo = ReadonlyUserRepo::new(repo);
 
-        self.maybe_snapshot_impl(ui)
+        self.maybe_snapshot_impl(ui, false)
             .map_err(|err| err.into_command_error())
     }
 
@@ -1335,6 +1481,24 @@ to the current parents may contain changes from multiple commits.
         }
     }
 
+    /// Parses the given fileset expressions, and excludes files matching any
+    /// of the `--exclude` filesets from them. If no `values` are given, the
+    /// files are excluded from all files.
+    pub fn parse_file_patterns_excluding(
+        &self,
+        ui: &Ui,
+        values: &[String],
+        exclude_args: &ExcludeArgs,
+    ) -> Result<FilesetExpression, CommandError> {
+        let expression = self.parse_file_patterns(ui, values)?;
+        if exclude_args.exclude.is_empty() {
+            Ok(expression)
+        } else {
+            let excluded = self.parse_union_filesets(ui, &exclude_args.exclude)?;
+            Ok(expression.difference(excluded))
+        }
+    }
+
     /// Parses the given fileset expressions and concatenates them all.
     pub fn parse_union_filesets(
         &self,
@@ -1344,7 +1508,14 @@ to the current parents may contain changes from multiple commits.
         let mut diagnostics = FilesetDiagnostics::new();
         let expressions: Vec<_> = file_args
             .iter()
-            .map(|arg| fileset::parse_maybe_bare(&mut diagnostics, arg, self.path_converter()))
+            .map(|arg| {
+                fileset::parse_maybe_bare(
+                    &mut diagnostics,
+                    arg,
+                    self.env.fileset_aliases_map(),
+                    self.path_converter(),
+                )
+            })
             .try_collect()?;
         print_parse_diagnostics(ui, "In fileset expression", &diagnostics)?;
         Ok(FilesetExpression::union_all(expressions))
@@ -1356,18 +1527,30 @@ to the current parents may contain changes from multiple commits.
         let expression = fileset::parse(
             &mut diagnostics,
             &pattern,
+            self.env.fileset_aliases_map(),
             &RepoPathUiConverter::Fs {
                 cwd: "".into(),
                 base: "".into(),
             },
         )?;
         print_parse_diagnostics(ui, "In `snapshot.auto-track`", &diagnostics)?;
-        Ok(expression.to_matcher())
+        Ok(expression.to_matcher()?)
     }
 
     pub fn snapshot_options_with_start_tracking_matcher<'a>(
         &self,
         start_tracking_matcher: &'a dyn Matcher,
+    ) -> Result<SnapshotOptions<'a>, CommandError> {
+        self.snapshot_options_with_tracking_matchers(start_tracking_matcher, &NothingMatcher)
+    }
+
+    /// Snapshot options which start tracking new files matching
+    /// `start_tracking_matcher`, and new files matching
+    /// `force_tracking_matcher` even if they're ignored.
+    pub fn snapshot_options_with_tracking_matchers<'a>(
+        &self,
+        start_tracking_matcher: &'a dyn Matcher,
+        force_tracking_matcher: &'a dyn Matcher,
     ) -> Result<SnapshotOptions<'a>, CommandError> {
         let base_ignores = self.base_ignores()?;
         let fsmonitor_settings = self.settings().fsmonitor_settings()?;
@@ -1383,8 +1566,12 @@ to the current parents may contain changes from multiple commits.
             fsmonitor_settings,
             progress: None,
             start_tracking_matcher,
+            force_tracking_matcher,
+            report_ignored_paths: false,
             max_new_file_size,
             conflict_marker_style,
+            eol_conversion_mode: self.env.eol_conversion_mode(),
+            executable_bit_mode: self.env.executable_bit_mode(),
         })
     }
 
@@ -1392,14 +1579,27 @@ to the current parents may contain changes from multiple commits.
         self.env.path_converter()
     }
 
-    #[cfg(not(feature = "git"))]
+    /// Returns the ignore patterns which apply on top of the in-tree
+    /// `.gitignore` files. In order of increasing precedence, these are the
+    /// global Git excludes file, `.git/info/exclude`, and the workspace-local
+    /// `.jj/working-copy-ignore` file.
     pub fn base_ignores(&self) -> Result<Arc<GitIgnoreFile>, GitIgnoreError> {
+        let workspace_ignore_path = self
+            .workspace_root()
+            .join(".jj")
+            .join("working-copy-ignore");
+        self.git_excludes()?
+            .chain_with_file("", workspace_ignore_path)
+    }
+
+    #[cfg(not(feature = "git"))]
+    fn git_excludes(&self) -> Result<Arc<GitIgnoreFile>, GitIgnoreError> {
         Ok(GitIgnoreFile::empty())
     }
 
     #[cfg(feature = "git")]
     #[instrument(skip_all)]
-    pub fn base_ignores(&self) -> Result<Arc<GitIgnoreFile>, GitIgnoreError> {
+    fn git_excludes(&self) -> Result<Arc<GitIgnoreFile>, GitIgnoreError> {
         let get_excludes_file_path = |config: &gix::config::File| -> Option<PathBuf> {
             // TODO: maybe use path() and interpolate(), which can process non-utf-8
             // path on Unix.
@@ -1430,8 +1630,11 @@ to the current parents may contain changes from multiple commits.
             if let Some(excludes_file_path) = get_excludes_file_path(&git_repo.config_snapshot()) {
                 git_ignores = git_ignores.chain_with_file("", excludes_file_path)?;
             }
-            git_ignores = git_ignores
-                .chain_with_file("", git_backend.git_repo_path().join("info").join("exclude"))?;
+            // info/exclude is shared by all worktrees
+            git_ignores = git_ignores.chain_with_file(
+                "",
+                git_backend.git_common_dir().join("info").join("exclude"),
+            )?;
         } else if let Ok(git_config) = gix::config::File::from_globals() {
             if let Some(excludes_file_path) = get_excludes_file_path(&git_config) {
                 git_ignores = git_ignores.chain_with_file("", excludes_file_path)?;
@@ -1448,6 +1651,8 @@ to the current parents may contain changes from multiple commits.
             self.env.conflict_marker_style(),
             formats,
         )
+        .with_diff_stat_cache(&self.env.diff_stat_cache)
+        .with_prefetch_concurrency(self.env.diff_prefetch_concurrency)
     }
 
     /// Loads textual diff renderer from the settings and command arguments.
@@ -1568,6 +1773,48 @@ to the current parents may contain changes from multiple commits.
         )
     }
 
+    /// Resolve a revset to a single revision in the repo as it was at the given
+    /// operation. The revision may be hidden at the current operation.
+    pub fn resolve_single_rev_at_operation(
+        &self,
+        ui: &Ui,
+        op_str: &str,
+        revision_arg: &RevisionArg,
+    ) -> Result<Commit, CommandError> {
+        let op = self.resolve_single_op(op_str)?;
+        let repo = self.workspace.repo_loader().load_at(&op)?;
+        let mut diagnostics = RevsetDiagnostics::new();
+        let context = self.revset_parse_context();
+        let expression = revset::parse(&mut diagnostics, revision_arg.as_ref(), &context)?;
+        print_parse_diagnostics(ui, "In revset expression", &diagnostics)?;
+        let id_prefix_context = self.env.new_id_prefix_context();
+        let expression = RevsetExpressionEvaluator::new(
+            repo.as_ref(),
+            self.env.command.revset_extensions().clone(),
+            &id_prefix_context,
+            expression,
+        );
+        let commit_id = revset_util::evaluate_revset_to_single_commit_id(
+            revision_arg.as_ref(),
+            &expression,
+            || self.commit_summary_template(),
+            false,
+        )?;
+        repo.store()
+            .get_commit(&commit_id)
+            .map_err(|err| match err {
+                BackendError::ObjectNotFound { .. } => user_error_with_hint(
+                    format!(
+                        "Commit {} from operation {} is no longer available",
+                        short_commit_hash(&commit_id),
+                        short_operation_hash(op.id())
+                    ),
+                    "It was probably removed by `jj util gc`.",
+                ),
+                err => err.into(),
+            })
+    }
+
     /// Evaluates revset expressions to non-empty set of commits. The returned
     /// set preserves the order of the input expressions.
     ///
@@ -1812,6 +2059,15 @@ to the current parents may contain changes from multiple commits.
         self.commit_summary_template().format(commit, formatter)
     }
 
+    /// Returns whether the commit can be rewritten, i.e. it isn't in the
+    /// immutable set or `--ignore-immutable` is specified.
+    pub fn is_rewritable(&self, commit_id: &CommitId) -> Result<bool, CommandError> {
+        let immutable_commit = self
+            .env
+            .find_immutable_commit(self.repo().as_ref(), [commit_id])?;
+        Ok(immutable_commit.is_none())
+    }
+
     pub fn check_rewritable<'a>(
         &self,
         commits: impl IntoIterator<Item = &'a CommitId>,
@@ -1845,6 +2101,7 @@ to the current parents may contain changes from multiple commits.
     fn snapshot_working_copy(
         &mut self,
         ui: &Ui,
+        report_ignored_paths: bool,
     ) -> Result<SnapshotStats, SnapshotWorkingCopyError> {
         let workspace_id = self.workspace_id().to_owned();
         let get_wc_commit = |repo: &ReadonlyRepo| -> Result<Option<_>, _> {
@@ -1927,6 +2184,7 @@ See https://jj-vcs.github.io/jj/latest/working-copy/#stale-working-copy \
         self.user_repo = ReadonlyUserRepo::new(repo);
         let (new_tree_id, stats) = {
             let mut options = options;
+            options.report_ignored_paths = report_ignored_paths;
             let progress = crate::progress::snapshot_progress(ui);
             options.progress = progress.as_ref().map(|x| x as _);
             locked_ws
@@ -1985,14 +2243,18 @@ See https://jj-vcs.github.io/jj/latest/working-copy/#stale-working-copy \
         new_commit: &Commit,
     ) -> Result<(), CommandError> {
         assert!(self.may_update_working_copy);
-        let checkout_options = self.checkout_options();
-        let stats = update_working_copy(
-            &self.user_repo.repo,
-            &mut self.workspace,
-            maybe_old_commit,
-            new_commit,
-            &checkout_options,
-        )?;
+        let stats = {
+            let mut options = self.checkout_options();
+            let progress = crate::progress::checkout_progress(ui);
+            options.progress = progress.as_ref().map(|x| x as _);
+            update_working_copy(
+                &self.user_repo.repo,
+                &mut self.workspace,
+                maybe_old_commit,
+                new_commit,
+                &options,
+            )?
+        };
         if Some(new_commit) != maybe_old_commit {
             if let Some(mut formatter) = ui.status_formatter() {
                 let template = self.commit_summary_template();
@@ -2008,7 +2270,7 @@ See https://jj-vcs.github.io/jj/latest/working-copy/#stale-working-copy \
                 }
             }
         }
-        print_checkout_stats(ui, stats, new_commit)?;
+        print_checkout_stats(ui, stats, new_commit, self.path_converter())?;
         if Some(new_commit) != maybe_old_commit {
             if let Some(mut formatter) = ui.status_formatter() {
                 if new_commit.has_conflict()? {
@@ -2017,7 +2279,7 @@ See https://jj-vcs.github.io/jj/latest/working-copy/#stale-working-copy \
                         formatter.labeled("warning").with_heading("Warning: "),
                         "There are unresolved conflicts at these paths:"
                     )?;
-                    print_conflicted_paths(conflicts, formatter.as_mut(), self)?;
+                    print_conflicted_paths(conflicts, formatter.as_mut(), self, None)?;
                 }
             }
         }
@@ -2373,6 +2635,11 @@ impl WorkspaceCommandTransaction<'_> {
         self.tx.repo_mut()
     }
 
+    /// Records a key-value pair in the metadata of the operation.
+    pub fn set_tag(&mut self, key: String, value: String) {
+        self.tx.set_tag(key, value);
+    }
+
     pub fn check_out(&mut self, commit: &Commit) -> Result<Commit, CheckOutCommitError> {
         let workspace_id = self.helper.workspace_id().to_owned();
         self.id_prefix_context.take(); // invalidate
@@ -2567,10 +2834,15 @@ fn update_stale_working_copy(
 }
 
 #[instrument(skip_all)]
+/// Prints the conflicted paths and the kinds of the conflicts.
+///
+/// If the conflicts are materialized in the working copy, its `file_states`
+/// tell binary conflicts apart without reading the file contents.
 pub fn print_conflicted_paths(
     conflicts: Vec<(RepoPathBuf, BackendResult<MergedTreeValue>)>,
     formatter: &mut dyn Formatter,
     workspace_command: &WorkspaceCommandHelper,
+    file_states: Option<FileStates>,
 ) -> Result<(), CommandError> {
     let formatted_paths = conflicts
         .iter()
@@ -2581,11 +2853,22 @@ pub fn print_conflicted_paths(
         .into_iter()
         .map(|p| format!("{:width$}", p, width = max_path_len.min(32) + 3));
 
-    for ((_, conflict), formatted_path) in std::iter::zip(conflicts, formatted_paths) {
+    for ((path, conflict), formatted_path) in std::iter::zip(conflicts, formatted_paths) {
         // TODO: Display the error for the path instead of failing the whole command if
         // `conflict` is an error?
         let conflict = conflict?.simplify();
         let sides = conflict.num_sides();
+        let is_binary = if let Some(file_states) = file_states {
+            file_states
+                .get(&path)
+                .is_some_and(|state| state.is_binary_conflict())
+        } else if let Some(file_merge) = conflict.to_file_merge() {
+            let store = workspace_command.repo().store();
+            let contents = extract_as_single_hunk(&file_merge, store, &path).block_on()?;
+            is_binary_conflict(&contents)
+        } else {
+            false
+        };
         let n_adds = conflict.adds().flatten().count();
         let deletions = sides - n_adds;
 
@@ -2634,6 +2917,10 @@ pub fn print_conflicted_paths(
                     if sides > 2 { "difficult" } else { "normal" },
                 ),
             )?;
+            if is_binary {
+                write!(formatter, " ")?;
+                write!(formatter.labeled("difficult"), "binary")?;
+            }
             write!(formatter, " conflict")?;
 
             if !seen_objects.is_empty() {
@@ -2661,7 +2948,7 @@ pub fn print_conflicted_paths(
 }
 
 /// Build human-readable messages explaining why the file was not tracked
-fn build_untracked_reason_message(reason: &UntrackedReason) -> Option<String> {
+pub fn build_untracked_reason_message(reason: &UntrackedReason) -> Option<String> {
     match reason {
         UntrackedReason::FileTooLarge { size, max_size } => {
             // Show both exact and human bytes sizes to avoid something
@@ -2730,6 +3017,35 @@ pub fn print_snapshot_stats(
             "
         )?;
     }
+
+    if !stats.ignored_tracked_paths.is_empty() {
+        writeln!(
+            ui.warning_default(),
+            "Some tracked files match ignore patterns and will still be snapshotted:"
+        )?;
+        let mut formatter = ui.stderr_formatter();
+        for path in &stats.ignored_tracked_paths {
+            let ui_path = path_converter.format_file_path(path);
+            writeln!(formatter, "  {ui_path}")?;
+        }
+        drop(formatter);
+        writeln!(
+            ui.hint_default(),
+            "Run `jj file untrack --matching-ignores` to stop tracking them."
+        )?;
+    }
+
+    if !stats.mixed_eol_paths.is_empty() {
+        writeln!(
+            ui.warning_default(),
+            "Line endings of some files weren't converted because they contain both LF and CRLF:"
+        )?;
+        let mut formatter = ui.stderr_formatter();
+        for path in &stats.mixed_eol_paths {
+            let ui_path = path_converter.format_file_path(path);
+            writeln!(formatter, "  {ui_path}")?;
+        }
+    }
     Ok(())
 }
 
@@ -2737,23 +3053,46 @@ pub fn print_checkout_stats(
     ui: &Ui,
     stats: CheckoutStats,
     new_commit: &Commit,
+    path_converter: &RepoPathUiConverter,
 ) -> Result<(), std::io::Error> {
     if stats.added_files > 0 || stats.updated_files > 0 || stats.removed_files > 0 {
-        writeln!(
+        write!(
             ui.status(),
             "Added {} files, modified {} files, removed {} files",
             stats.added_files,
             stats.updated_files,
             stats.removed_files
         )?;
+        if stats.skipped_files != 0 {
+            write!(
+                ui.status(),
+                ", skipped {} files (see below)",
+                stats.skipped_files
+            )?;
+        }
+        writeln!(ui.status())?;
     }
     if stats.skipped_files != 0 {
         writeln!(
             ui.warning_default(),
             "{} of those updates were skipped because there were conflicting changes in the \
-             working copy.",
+             working copy:",
             stats.skipped_files
         )?;
+        if let Some(mut formatter) = ui.status_formatter() {
+            for (path, reason) in &stats.skipped_paths {
+                let ui_path = path_converter.format_file_path(path);
+                let message = match reason {
+                    CheckoutSkipReason::UntrackedPathExists => {
+                        "an untracked file or directory is in the way"
+                    }
+                    CheckoutSkipReason::ParentNotDirectory => {
+                        "an untracked file is in place of a parent directory"
+                    }
+                };
+                writeln!(formatter, "  {ui_path}: {message}")?;
+            }
+        }
         writeln!(
             ui.hint_default(),
             "Inspect the changes compared to the intended target with `jj diff --from {}`.
@@ -2762,6 +3101,20 @@ Discard the conflicting changes with `jj restore --from {}`.",
             short_commit_hash(new_commit.id())
         )?;
     }
+    if stats.fake_symlinks != 0 {
+        if stats.fake_symlinks == 1 {
+            writeln!(
+                ui.warning_default(),
+                "1 symlink was written as a regular file containing the link target."
+            )?;
+        } else {
+            writeln!(
+                ui.warning_default(),
+                "{} symlinks were written as regular files containing the link target.",
+                stats.fake_symlinks
+            )?;
+        }
+    }
     Ok(())
 }
 
@@ -2789,6 +3142,18 @@ pub fn print_unmatched_explicit_paths<'a>(
         ui.warning_default(),
         "No matching entries for paths: {ui_paths}"
     )?;
+    // The path might have been meant as a glob pattern
+    let glob_like_path = explicit_paths
+        .iter()
+        .map(|&path| workspace_command.format_file_path(path))
+        .find(|ui_path| GlobPattern::escape(ui_path) != *ui_path);
+    if let Some(ui_path) = glob_like_path {
+        writeln!(
+            ui.hint_default(),
+            "Paths are matched literally. Use glob:{ui_path:?} to match {ui_path} as a glob \
+             pattern."
+        )?;
+    }
     Ok(())
 }
 
@@ -2834,6 +3199,19 @@ pub fn update_working_copy(
     options: &CheckoutOptions,
 ) -> Result<CheckoutStats, CommandError> {
     let old_tree_id = old_commit.map(|commit| commit.tree_id().clone());
+    // Download file contents at once if the repo is a partial clone.
+    #[cfg(feature = "git")]
+    {
+        let store = repo.store();
+        let old_tree = match &old_tree_id {
+            Some(tree_id) => store.get_root_tree(tree_id)?,
+            None => store.get_root_tree(&store.empty_merged_tree_id())?,
+        };
+        let new_tree = new_commit.tree()?;
+        let sparse_patterns = workspace.working_copy().sparse_patterns()?;
+        let matcher = jj_lib::working_copy::sparse_patterns_matcher(sparse_patterns);
+        jj_lib::git::fetch_missing_blobs_for_diff(store, &old_tree, &new_tree, matcher.as_ref())?;
+    }
     // TODO: CheckoutError::ConcurrentCheckout should probably just result in a
     // warning for most commands (but be an error for the checkout command)
     let stats = workspace
@@ -2889,6 +3267,43 @@ pub fn load_template_aliases(
     Ok(aliases_map)
 }
 
+pub fn load_fileset_aliases(
+    ui: &Ui,
+    stacked_config: &StackedConfig,
+) -> Result<FilesetAliasesMap, CommandError> {
+    let table_name = ConfigNamePathBuf::from_iter(["fileset-aliases"]);
+    let mut aliases_map = FilesetAliasesMap::new();
+    // Load from all config layers in order. 'f(x)' in default layer should be
+    // overridden by 'f(a)' in user.
+    for layer in stacked_config.layers() {
+        let table = match layer.look_up_table(&table_name) {
+            Ok(Some(table)) => table,
+            Ok(None) => continue,
+            Err(item) => {
+                return Err(ConfigGetError::Type {
+                    name: table_name.to_string(),
+                    error: format!("Expected a table, but is {}", item.type_name()).into(),
+                    source_path: layer.path.clone(),
+                }
+                .into());
+            }
+        };
+        for (decl, item) in table.iter() {
+            let r = item
+                .as_str()
+                .ok_or_else(|| format!("Expected a string, but is {}", item.type_name()))
+                .and_then(|v| aliases_map.insert(decl, v).map_err(|e| e.to_string()));
+            if let Err(s) = r {
+                writeln!(
+                    ui.warning_default(),
+                    "Failed to load `{table_name}.{decl}`: {s}"
+                )?;
+            }
+        }
+    }
+    Ok(aliases_map)
+}
+
 /// Helper to reformat content of log-like commands.
 #[derive(Clone, Debug)]
 pub struct LogContentFormat {
@@ -3122,11 +3537,30 @@ pub struct GlobalArgs {
     /// Enable debug logging
     #[arg(long, global = true)]
     pub debug: bool,
+    /// Ignore the default arguments configured in `command-defaults`
+    #[arg(long, global = true)]
+    pub no_defaults: bool,
 
     #[command(flatten)]
     pub early_args: EarlyArgs,
 }
 
+/// `--exclude` option of commands which take fileset arguments.
+#[derive(clap::Args, Clone, Debug)]
+pub struct ExcludeArgs {
+    /// Exclude files matching these filesets
+    ///
+    /// The files are excluded from the files matching the positional
+    /// filesets, or from all files if no filesets are given.
+    #[arg(
+        long,
+        short = 'X',
+        value_name = "FILESETS",
+        value_hint = clap::ValueHint::AnyPath,
+    )]
+    pub exclude: Vec<String>,
+}
+
 #[derive(clap::Args, Clone, Debug)]
 pub struct EarlyArgs {
     /// When to colorize output
@@ -3358,6 +3792,158 @@ fn resolve_aliases(
     }
 }
 
+/// Inserts the default arguments configured in `command-defaults` right after
+/// the subcommand name, so the arguments specified by the user come later.
+///
+/// Default arguments which would be specified twice, or which conflict with
+/// the arguments specified by the user, are dropped. Invalid default arguments
+/// are dropped with a warning.
+fn resolve_command_defaults(
+    ui: &Ui,
+    config: &StackedConfig,
+    app: &Command,
+    mut string_args: Vec<String>,
+) -> Result<Vec<String>, CommandError> {
+    // Avoid parsing the arguments if no defaults are configured.
+    let has_defaults = config
+        .get_table("command-defaults")
+        .optional()?
+        .is_some_and(|table| !table.is_empty());
+    if !has_defaults {
+        return Ok(string_args);
+    }
+    let Ok(matches) = app.clone().try_get_matches_from(&string_args) else {
+        // The error will be reported later.
+        return Ok(string_args);
+    };
+    if matches.get_flag("no_defaults") {
+        return Ok(string_args);
+    }
+    let name = command_name(&matches);
+    if name.is_empty() {
+        return Ok(string_args);
+    }
+    let Some(default_args) = config
+        .get::<Vec<String>>(["command-defaults", &name])
+        .optional()?
+    else {
+        return Ok(string_args);
+    };
+    let Some(mut insert_index) = subcommand_end_index(app, &matches, &string_args) else {
+        return Ok(string_args);
+    };
+
+    let mut arg_counts = explicit_arg_counts(&matches);
+    let user_arg_ids: HashSet<_> = arg_counts.keys().cloned().collect();
+    for group in split_arg_groups(&default_args) {
+        let mut new_args = string_args.clone();
+        new_args.splice(insert_index..insert_index, group.iter().cloned());
+        let new_matches = match app.clone().try_get_matches_from(&new_args) {
+            Ok(new_matches) => new_matches,
+            // The argument was specified by the user, or conflicts with the
+            // user arguments.
+            Err(err) if err.kind() == clap::error::ErrorKind::ArgumentConflict => continue,
+            Err(_) => {
+                writeln!(
+                    ui.warning_default(),
+                    "Ignored invalid default argument `{}` for `jj {name}`",
+                    group.join(" ")
+                )?;
+                continue;
+            }
+        };
+        let new_arg_counts = explicit_arg_counts(&new_matches);
+        // Repeatable arguments specified by the user override the defaults.
+        let overridden = new_arg_counts.iter().any(|(id, count)| {
+            user_arg_ids.contains(id) && arg_counts.get(id).map_or(true, |old| count > old)
+        });
+        if overridden {
+            continue;
+        }
+        string_args = new_args;
+        insert_index += group.len();
+        arg_counts = new_arg_counts;
+    }
+    Ok(string_args)
+}
+
+/// Returns the index next to the (possibly nested) subcommand name in `args`.
+fn subcommand_end_index(app: &Command, mut matches: &ArgMatches, args: &[String]) -> Option<usize> {
+    let mut commands = vec![app];
+    let mut index = 0;
+    while let Some((name, sub_matches)) = matches.subcommand() {
+        let command = commands.last().unwrap().find_subcommand(name)?;
+        let names = iter::once(command.get_name())
+            .chain(command.get_all_aliases())
+            .collect_vec();
+        // Skip option values which happen to be the subcommand name.
+        index = (index + 1..args.len()).find(|&i| {
+            names.contains(&args[i].as_str()) && !option_takes_value(&commands, &args[i - 1])
+        })?;
+        commands.push(command);
+        matches = sub_matches;
+    }
+    Some(index + 1)
+}
+
+/// Returns true if `arg` is an option of one of the `commands` and its value
+/// is the next argument.
+fn option_takes_value(commands: &[&Command], arg: &str) -> bool {
+    let matches_arg = |def: &clap::Arg| {
+        if let Some(long) = arg.strip_prefix("--") {
+            def.get_long_and_visible_aliases()
+                .is_some_and(|names| names.contains(&long))
+        } else if let Some(short) = arg.strip_prefix('-') {
+            let mut chars = short.chars();
+            let (Some(c), None) = (chars.next(), chars.next()) else {
+                return false;
+            };
+            def.get_short_and_visible_aliases()
+                .is_some_and(|names| names.contains(&c))
+        } else {
+            false
+        }
+    };
+    commands
+        .iter()
+        .flat_map(|command| command.get_arguments())
+        .any(|def| matches_arg(def) && def.get_action().takes_values())
+}
+
+/// Returns the number of occurrences of the arguments of the leaf subcommand
+/// specified in the command line.
+fn explicit_arg_counts(mut matches: &ArgMatches) -> HashMap<String, usize> {
+    while let Some((_, sub_matches)) = matches.subcommand() {
+        matches = sub_matches;
+    }
+    matches
+        .ids()
+        .filter(|id| matches.value_source(id.as_str()) == Some(ValueSource::CommandLine))
+        .map(|id| {
+            let count = matches
+                .indices_of(id.as_str())
+                .map_or(0, |indices| indices.len());
+            (id.to_string(), count)
+        })
+        .collect()
+}
+
+/// Splits arguments into groups of a flag and the following values.
+fn split_arg_groups(args: &[String]) -> Vec<&[String]> {
+    let mut groups = vec![];
+    let mut start = 0;
+    for (i, arg) in args.iter().enumerate() {
+        if i > start && arg.starts_with('-') && arg.len() > 1 {
+            groups.push(&args[start..i]);
+            start = i;
+        }
+    }
+    if start < args.len() {
+        groups.push(&args[start..]);
+    }
+    groups
+}
+
 /// Parse args that must be interpreted early, e.g. before printing help.
 fn parse_early_args(
     app: &Command,
@@ -3724,6 +4310,7 @@ impl CliRunner {
         }
 
         let string_args = expand_args(ui, &self.app, env::args_os(), &config)?;
+        let string_args = resolve_command_defaults(ui, &config, &self.app, string_args)?;
         let (args, config_layers) = parse_early_args(&self.app, &string_args)?;
         if !config_layers.is_empty() {
             raw_config.as_mut().extend_layers(config_layers);
@@ -3761,7 +4348,9 @@ impl CliRunner {
         };
 
         // Apply workspace configs, --config arguments, and --when.commands.
-        config = config_env.resolve_config(&raw_config)?;
+        let (new_config, config_scope_warnings) =
+            config_env.resolve_config_with_warnings(&raw_config)?;
+        config = new_config;
         migrate_config(&mut config)?;
         ui.reset(&config)?;
 
@@ -3769,10 +4358,25 @@ impl CliRunner {
         for desc in &last_config_migration_descriptions {
             writeln!(ui.warning_default(), "Deprecated config: {desc}")?;
         }
+        for warning in &config_scope_warnings {
+            writeln!(ui.warning_default(), "{warning}")?;
+        }
+        // Unknown keys are usually typos. Don't nag while the config is being
+        // edited; `jj config list --check` reports all the problems.
+        if matches.subcommand_name() != Some("config") {
+            for problem in check_config_unknown_keys(&config) {
+                writeln!(ui.warning_default(), "{problem}")?;
+                if let Some(hint) = problem.hint() {
+                    writeln!(ui.hint_default(), "{hint}")?;
+                }
+            }
+        }
 
         // If -R or --config* is specified, check if the expanded arguments differ.
         if args.global_args.repository.is_some() || args.global_args.early_args.has_config_args() {
-            let new_string_args = expand_args(ui, &self.app, env::args_os(), &config).ok();
+            let new_string_args = expand_args(ui, &self.app, env::args_os(), &config)
+                .and_then(|args| resolve_command_defaults(ui, &config, &self.app, args))
+                .ok();
             if new_string_args.as_ref() != Some(&string_args) {
                 writeln!(
                     ui.warning_default(),
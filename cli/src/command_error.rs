@@ -28,7 +28,9 @@ use jj_lib::config::ConfigGetError;
 use jj_lib::config::ConfigLoadError;
 use jj_lib::config::ConfigMigrateError;
 use jj_lib::dsl_util::Diagnostics;
+use jj_lib::fileset;
 use jj_lib::fileset::FilePatternParseError;
+use jj_lib::fileset::FilesetEvaluationError;
 use jj_lib::fileset::FilesetParseError;
 use jj_lib::fileset::FilesetParseErrorKind;
 use jj_lib::gitignore::GitIgnoreError;
@@ -54,6 +56,7 @@ use jj_lib::working_copy::ResetError;
 use jj_lib::working_copy::SnapshotError;
 use jj_lib::working_copy::WorkingCopyStateError;
 use jj_lib::workspace::WorkspaceInitError;
+use jj_lib::workspace_store::WorkspaceStoreError;
 use thiserror::Error;
 
 use crate::cli_util::short_operation_hash;
@@ -310,10 +313,18 @@ impl From<RenameWorkspaceError> for CommandError {
     }
 }
 
+impl From<WorkspaceStoreError> for CommandError {
+    fn from(err: WorkspaceStoreError) -> Self {
+        internal_error_with_message("Failed to access the workspace store", err)
+    }
+}
+
 impl From<BackendError> for CommandError {
     fn from(err: BackendError) -> Self {
         match &err {
             BackendError::Unsupported(_) => user_error(err),
+            #[cfg(feature = "git")]
+            _ if git::is_fetch_missing_objects_error(&err) => user_error(err),
             _ => internal_error_with_message("Unexpected error from backend", err),
         }
     }
@@ -350,6 +361,9 @@ impl From<WorkspaceInitError> for CommandError {
                 internal_error_with_message("Failed to access the repository", err)
             }
             WorkspaceInitError::SignInit(err) => user_error(err),
+            WorkspaceInitError::WorkspaceStore(err) => {
+                internal_error_with_message("Failed to record the workspace path", err)
+            }
         }
     }
 }
@@ -452,6 +466,7 @@ impl From<MergeToolConfigError> for CommandError {
                     ),
                 )
             }
+            MergeToolConfigError::NotADiffEditor { .. } => user_error(err),
             _ => user_error_with_message("Failed to load tool configuration", err),
         }
     }
@@ -485,9 +500,21 @@ mod git {
     use jj_lib::git::GitPushError;
     use jj_lib::git::GitRemoteManagementError;
     use jj_lib::git::UnexpectedGitBackendError;
+    use jj_lib::git_backend::GitBackendError;
 
     use super::*;
 
+    /// Returns true if the `err` was caused by failure of fetching missing
+    /// objects from the promisor remote, which is usually a network problem.
+    pub(super) fn is_fetch_missing_objects_error(err: &(dyn error::Error + 'static)) -> bool {
+        iter::successors(Some(err), |err| err.source()).any(|err| {
+            matches!(
+                err.downcast_ref::<GitBackendError>(),
+                Some(GitBackendError::FetchMissingObjects { .. })
+            )
+        })
+    }
+
     impl From<git2::Error> for CommandError {
         fn from(err: git2::Error) -> Self {
             user_error_with_message("Git operation failed", err)
@@ -637,6 +664,15 @@ impl From<FilesetParseError> for CommandError {
     }
 }
 
+impl From<FilesetEvaluationError> for CommandError {
+    fn from(err: FilesetEvaluationError) -> Self {
+        match err {
+            FilesetEvaluationError::TreeRequired(_) => user_error(err),
+            FilesetEvaluationError::Backend(err) => err.into(),
+        }
+    }
+}
+
 impl From<RecoverWorkspaceError> for CommandError {
     fn from(err: RecoverWorkspaceError) -> Self {
         match err {
@@ -776,18 +812,38 @@ fn file_pattern_parse_error_hint(err: &FilePatternParseError) -> Option<String>
 }
 
 fn fileset_parse_error_hint(err: &FilesetParseError) -> Option<String> {
-    match err.kind() {
+    // Only for the bottom error, which is usually the root cause
+    let bottom_err = iter::successors(Some(err), |e| e.origin()).last().unwrap();
+    match bottom_err.kind() {
         FilesetParseErrorKind::SyntaxError => Some(String::from(
             "See https://jj-vcs.github.io/jj/latest/filesets/ for filesets syntax, or for how to \
              match file paths.",
         )),
+        FilesetParseErrorKind::NoSuchPatternKind {
+            kind: _,
+            candidates,
+        } => {
+            let kinds = fileset::FILE_PATTERN_KINDS
+                .iter()
+                .map(|kind| format!("`{kind}:`"))
+                .join(", ");
+            let candidates = candidates
+                .iter()
+                .map(|kind| format!("{kind}:"))
+                .collect_vec();
+            let similarity_hint = format_similarity_hint(&candidates)
+                .map(|hint| hint + " ")
+                .unwrap_or_default();
+            Some(format!("{similarity_hint}Valid pattern kinds are {kinds}"))
+        }
         FilesetParseErrorKind::NoSuchFunction {
             name: _,
             candidates,
         } => format_similarity_hint(candidates),
         FilesetParseErrorKind::InvalidArguments { .. } | FilesetParseErrorKind::Expression(_) => {
-            find_source_parse_error_hint(&err)
+            find_source_parse_error_hint(bottom_err)
         }
+        _ => None,
     }
 }
 
@@ -20,6 +20,7 @@ use itertools::Itertools as _;
 use jj_lib::backend::CommitId;
 use jj_lib::commit::CommitIteratorExt;
 use jj_lib::object_id::ObjectId;
+use jj_lib::op_store::AbandonReason;
 use jj_lib::refs::diff_named_ref_targets;
 use jj_lib::repo::Repo as _;
 use jj_lib::rewrite::RewriteRefsOptions;
@@ -59,7 +60,9 @@ pub(crate) struct AbandonArgs {
     summary: bool,
     /// Do not delete bookmarks pointing to the revisions to abandon
     ///
-    /// Bookmarks will be moved to the parent revisions instead.
+    /// Bookmarks will be moved to the parent revisions instead. If the parents
+    /// are abandoned too, the bookmarks are moved to their closest ancestors
+    /// which are not abandoned.
     #[arg(long)]
     retain_bookmarks: bool,
     /// Do not modify the content of the children of the abandoned commits
@@ -97,8 +100,12 @@ pub(crate) fn cmd_abandon(
     tx.repo_mut().transform_descendants_with_options(
         to_abandon_set.iter().copied().cloned().collect(),
         &options,
-        |rewriter| {
+        |mut rewriter| {
             if to_abandon_set.contains(rewriter.old_commit().id()) {
+                let commit_id = rewriter.old_commit().id().clone();
+                rewriter
+                    .mut_repo()
+                    .set_abandon_reason(commit_id, AbandonReason::Explicit);
                 rewriter.abandon();
             } else if args.restore_descendants {
                 rewriter.reparent().write()?;
@@ -111,6 +118,13 @@ pub(crate) fn cmd_abandon(
         },
     )?;
 
+    let deleted_bookmarks = diff_named_ref_targets(
+        tx.base_repo().view().local_bookmarks(),
+        tx.repo().view().local_bookmarks(),
+    )
+    .filter(|(_, (_old, new))| new.is_absent())
+    .map(|(name, _)| name)
+    .collect_vec();
     if let Some(mut formatter) = ui.status_formatter() {
         if to_abandon.len() == 1 {
             write!(formatter, "Abandoned commit ")?;
@@ -128,13 +142,6 @@ pub(crate) fn cmd_abandon(
         } else {
             writeln!(formatter, "Abandoned {} commits.", to_abandon.len())?;
         }
-        let deleted_bookmarks = diff_named_ref_targets(
-            tx.base_repo().view().local_bookmarks(),
-            tx.repo().view().local_bookmarks(),
-        )
-        .filter(|(_, (_old, new))| new.is_absent())
-        .map(|(name, _)| name)
-        .collect_vec();
         if !deleted_bookmarks.is_empty() {
             writeln!(
                 formatter,
@@ -157,6 +164,13 @@ pub(crate) fn cmd_abandon(
             }
         }
     }
+    if !deleted_bookmarks.is_empty() {
+        writeln!(
+            ui.hint_default(),
+            "Run `jj undo` to restore the deleted bookmarks, or use `--retain-bookmarks` to move \
+             them to the parent revisions instead."
+        )?;
+    }
     let transaction_description = if to_abandon.len() == 1 {
         format!("abandon commit {}", to_abandon[0].id().hex())
     } else {
@@ -50,6 +50,12 @@ pub(crate) struct BackoutArgs {
         add = ArgValueCandidates::new(complete::all_revisions),
     )]
     destination: Vec<RevisionArg>,
+    /// If true, revisions whose reverse would be empty are skipped
+    ///
+    /// This is the case if the changes were already reverted in the
+    /// destination.
+    #[arg(long)]
+    skip_emptied: bool,
 }
 
 #[instrument(skip_all)]
@@ -102,6 +108,7 @@ pub(crate) fn cmd_backout(
     };
     let mut tx = workspace_command.start_transaction();
     let mut new_base_tree = merge_commit_trees(tx.repo(), &parents)?;
+    let mut num_skipped = 0;
 
     for (commit_to_back_out, new_commit_description) in
         commits_to_back_out_with_new_commit_descriptions
@@ -109,6 +116,10 @@ pub(crate) fn cmd_backout(
         let old_base_tree = commit_to_back_out.parent_tree(tx.repo())?;
         let old_tree = commit_to_back_out.tree()?;
         let new_tree = new_base_tree.merge(&old_tree, &old_base_tree)?;
+        if args.skip_emptied && new_tree.id() == new_base_tree.id() {
+            num_skipped += 1;
+            continue;
+        }
         let new_parent_ids = parents.iter().map(|commit| commit.id().clone()).collect();
         let new_commit = tx
             .repo_mut()
@@ -118,6 +129,12 @@ pub(crate) fn cmd_backout(
         parents = vec![new_commit];
         new_base_tree = new_tree;
     }
+    if num_skipped > 0 {
+        writeln!(
+            ui.status(),
+            "Skipped {num_skipped} commits whose reverse would be empty"
+        )?;
+    }
     tx.finish(ui, transaction_description)?;
 
     Ok(())
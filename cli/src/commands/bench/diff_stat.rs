@@ -0,0 +1,96 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use itertools::Itertools as _;
+use jj_lib::backend::BackendResult;
+use jj_lib::copies::CopyRecords;
+use jj_lib::matchers::EverythingMatcher;
+use jj_lib::repo::Repo as _;
+use pollster::FutureExt as _;
+
+use super::run_bench;
+use super::CriterionArgs;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::CommandError;
+use crate::diff_stat_cache::DiffStatCache;
+use crate::diff_util::DiffStatOptions;
+use crate::diff_util::DiffStats;
+use crate::ui::Ui;
+
+/// Calculate the diff stats of the revisions with and without the cache
+///
+/// The cached benchmark loads the cache file on each iteration, like a
+/// repeated `jj log --stat` would.
+#[derive(clap::Args, Clone, Debug)]
+pub struct BenchDiffStatArgs {
+    #[arg(default_value = "@")]
+    revisions: Vec<RevisionArg>,
+    #[command(flatten)]
+    criterion: CriterionArgs,
+}
+
+pub fn cmd_bench_diff_stat(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &BenchDiffStatArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let repo = workspace_command.repo();
+    let commits: Vec<_> = workspace_command
+        .parse_union_revsets(ui, &args.revisions)?
+        .evaluate_to_commits()?
+        .try_collect()?;
+    let trees: Vec<_> = commits
+        .iter()
+        .map(|commit| Ok((commit.parent_tree(repo.as_ref())?, commit.tree()?)))
+        .try_collect::<_, _, CommandError>()?;
+    let store = repo.store();
+    let options = DiffStatOptions::default();
+    let conflict_marker_style = workspace_command.env().conflict_marker_style();
+    let copy_records = CopyRecords::default();
+    let max_entries = workspace_command
+        .settings()
+        .get("diff.stat-cache-max-entries")?;
+    let cache_dir = tempfile::tempdir()?;
+
+    let calculate = |cache: Option<&DiffStatCache>| -> BackendResult<(usize, usize)> {
+        let mut total = (0, 0);
+        for (from_tree, to_tree) in &trees {
+            let tree_diff =
+                from_tree.diff_stream_with_copies(to_tree, &EverythingMatcher, &copy_records);
+            let stats = DiffStats::calculate(
+                store,
+                tree_diff,
+                &options,
+                conflict_marker_style,
+                cache,
+                None,
+            )
+            .block_on()?;
+            total.0 += stats.count_total_added();
+            total.1 += stats.count_total_removed();
+        }
+        Ok(total)
+    };
+    let uncached = || calculate(None);
+    run_bench(ui, "diff-stat-uncached", &args.criterion, uncached)?;
+
+    // Populate the cache file
+    let new_cache = || DiffStatCache::new(cache_dir.path().to_owned(), max_entries);
+    calculate(Some(&new_cache()))?;
+    let cached = || calculate(Some(&new_cache()));
+    run_bench(ui, "diff-stat-cached", &args.criterion, cached)?;
+    Ok(())
+}
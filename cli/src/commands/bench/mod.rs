@@ -13,7 +13,9 @@
 // limitations under the License.
 
 mod common_ancestors;
+mod diff_stat;
 mod is_ancestor;
+mod resolve_change_prefix;
 mod resolve_prefix;
 mod revset;
 
@@ -26,8 +28,12 @@ use criterion::Criterion;
 
 use self::common_ancestors::cmd_bench_common_ancestors;
 use self::common_ancestors::BenchCommonAncestorsArgs;
+use self::diff_stat::cmd_bench_diff_stat;
+use self::diff_stat::BenchDiffStatArgs;
 use self::is_ancestor::cmd_bench_is_ancestor;
 use self::is_ancestor::BenchIsAncestorArgs;
+use self::resolve_change_prefix::cmd_bench_resolve_change_prefix;
+use self::resolve_change_prefix::BenchResolveChangePrefixArgs;
 use self::resolve_prefix::cmd_bench_resolve_prefix;
 use self::resolve_prefix::BenchResolvePrefixArgs;
 use self::revset::cmd_bench_revset;
@@ -41,7 +47,9 @@ use crate::ui::Ui;
 #[command(hide = true)]
 pub enum BenchCommand {
     CommonAncestors(BenchCommonAncestorsArgs),
+    DiffStat(BenchDiffStatArgs),
     IsAncestor(BenchIsAncestorArgs),
+    ResolveChangePrefix(BenchResolveChangePrefixArgs),
     ResolvePrefix(BenchResolvePrefixArgs),
     Revset(BenchRevsetArgs),
 }
@@ -53,7 +61,11 @@ pub(crate) fn cmd_bench(
 ) -> Result<(), CommandError> {
     match subcommand {
         BenchCommand::CommonAncestors(args) => cmd_bench_common_ancestors(ui, command, args),
+        BenchCommand::DiffStat(args) => cmd_bench_diff_stat(ui, command, args),
         BenchCommand::IsAncestor(args) => cmd_bench_is_ancestor(ui, command, args),
+        BenchCommand::ResolveChangePrefix(args) => {
+            cmd_bench_resolve_change_prefix(ui, command, args)
+        }
         BenchCommand::ResolvePrefix(args) => cmd_bench_resolve_prefix(ui, command, args),
         BenchCommand::Revset(args) => cmd_bench_revset(ui, command, args),
     }
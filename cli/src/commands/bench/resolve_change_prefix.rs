@@ -0,0 +1,75 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use jj_lib::hex_util::to_forward_hex;
+use jj_lib::object_id::HexPrefix;
+use jj_lib::object_id::PrefixResolution;
+use jj_lib::repo::Repo as _;
+
+use super::run_bench;
+use super::CriterionArgs;
+use crate::cli_util::CommandHelper;
+use crate::command_error::user_error;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Resolve a change ID prefix and compute the shortest unique prefix of its
+/// commits
+///
+/// The change ID index is built from the view's heads in each iteration, as
+/// it would be by a fresh command.
+#[derive(clap::Args, Clone, Debug)]
+pub struct BenchResolveChangePrefixArgs {
+    prefix: String,
+    #[command(flatten)]
+    criterion: CriterionArgs,
+}
+
+pub fn cmd_bench_resolve_change_prefix(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &BenchResolveChangePrefixArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let prefix = to_forward_hex(&args.prefix)
+        .as_deref()
+        .and_then(HexPrefix::new)
+        .ok_or_else(|| user_error(format!("Invalid change ID prefix: {}", args.prefix)))?;
+    let repo = workspace_command.repo();
+    let change_id = match repo.resolve_change_id_prefix(&prefix) {
+        PrefixResolution::SingleMatch(commit_ids) => {
+            repo.store().get_commit(&commit_ids[0])?.change_id().clone()
+        }
+        PrefixResolution::NoMatch | PrefixResolution::AmbiguousMatch => {
+            return Err(user_error(format!(
+                "Change ID prefix doesn't resolve to a single change: {}",
+                args.prefix
+            )));
+        }
+    };
+    let index = repo.readonly_index();
+    let routine = || {
+        let change_id_index = index.change_id_index(&mut repo.view().heads().iter());
+        let commit_ids = change_id_index.resolve_prefix(&prefix);
+        let prefix_len = change_id_index.shortest_unique_prefix_len(&change_id);
+        (commit_ids, prefix_len)
+    };
+    run_bench(
+        ui,
+        &format!("resolve-change-prefix-{}", args.prefix),
+        &args.criterion,
+        routine,
+    )?;
+    Ok(())
+}
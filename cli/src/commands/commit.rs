@@ -13,18 +13,21 @@
 // limitations under the License.
 
 use clap_complete::ArgValueCompleter;
+use itertools::Itertools as _;
 use jj_lib::backend::Signature;
 use jj_lib::object_id::ObjectId;
 use jj_lib::repo::Repo;
 use tracing::instrument;
 
 use crate::cli_util::CommandHelper;
+use crate::cli_util::ExcludeArgs;
 use crate::command_error::user_error;
 use crate::command_error::CommandError;
 use crate::complete;
 use crate::description_util::description_template;
 use crate::description_util::edit_description;
 use crate::description_util::join_message_paragraphs;
+use crate::description_util::new_commit_description;
 use crate::text_util::parse_author;
 use crate::ui::Ui;
 
@@ -47,6 +50,8 @@ pub(crate) struct CommitArgs {
         add = ArgValueCompleter::new(complete::modified_files),
     )]
     paths: Vec<String>,
+    #[command(flatten)]
+    exclude_args: ExcludeArgs,
     /// Reset the author to the configured user
     ///
     /// This resets the author name, email, and timestamp.
@@ -81,9 +86,10 @@ pub(crate) fn cmd_commit(
         .get_wc_commit_id()
         .ok_or_else(|| user_error("This command requires a working copy"))?;
     let commit = workspace_command.repo().store().get_commit(commit_id)?;
+    let has_paths = !args.paths.is_empty() || !args.exclude_args.exclude.is_empty();
     let matcher = workspace_command
-        .parse_file_patterns(ui, &args.paths)?
-        .to_matcher();
+        .parse_file_patterns_excluding(ui, &args.paths, &args.exclude_args)?
+        .to_matcher()?;
     let advanceable_bookmarks = workspace_command.get_advanceable_bookmarks(commit.parent_ids())?;
     let diff_selector =
         workspace_command.diff_selector(ui, args.tool.as_deref(), args.interactive)?;
@@ -108,11 +114,22 @@ new working-copy commit.
         matcher.as_ref(),
         format_instructions,
     )?;
-    if !args.paths.is_empty() && tree_id == base_tree.id() {
+    if tree_id == base_tree.id() && commit.tree_id() != &tree_id {
+        // There were changes, but none of them were selected.
+        if diff_selector.is_interactive() {
+            return Err(user_error("No changes selected"));
+        } else if has_paths {
+            return Err(user_error(format!(
+                "The given paths do not match any changes: {}",
+                format_path_args(args)
+            )));
+        }
+    }
+    if has_paths && tree_id == base_tree.id() {
         writeln!(
             ui.warning_default(),
             "The given paths do not match any file: {}",
-            args.paths.join(" ")
+            format_path_args(args)
         )?;
     }
 
@@ -134,7 +151,7 @@ new working-copy commit.
         join_message_paragraphs(&args.message_paragraphs)
     } else {
         if commit_builder.description().is_empty() {
-            commit_builder.set_description(tx.settings().get_string("ui.default-description")?);
+            commit_builder.set_description(new_commit_description(ui, &tx, &commit)?);
         }
         let temp_commit = commit_builder.write_hidden()?;
         let template = description_template(ui, &tx, "", &temp_commit)?;
@@ -160,3 +177,13 @@ new working-copy commit.
     tx.finish(ui, format!("commit {}", commit.id().hex()))?;
     Ok(())
 }
+
+/// Formats the path arguments as they were specified on the command line.
+fn format_path_args(args: &CommitArgs) -> String {
+    let excludes = args
+        .exclude_args
+        .exclude
+        .iter()
+        .map(|value| format!("--exclude {value}"));
+    args.paths.iter().cloned().chain(excludes).join(" ")
+}
@@ -12,14 +12,20 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::error;
 use std::io::Write as _;
 
 use clap_complete::ArgValueCandidates;
+use itertools::Itertools as _;
+use jj_lib::config::ConfigGetError;
 use jj_lib::config::ConfigNamePathBuf;
 use jj_lib::config::ConfigValue;
+use serde::de::IntoDeserializer as _;
+use serde::Deserialize as _;
 use tracing::instrument;
 
 use crate::cli_util::CommandHelper;
+use crate::command_error::user_error_with_message;
 use crate::command_error::CommandError;
 use crate::complete;
 use crate::ui::Ui;
@@ -33,11 +39,35 @@ use crate::ui::Ui;
 /// user.name="Martin von Zweigbergk"
 /// $ jj config get user.name
 /// Martin von Zweigbergk
+///
+/// Strings are printed without quotes, and arrays are printed one item per
+/// line. If multiple names are given, each value is printed as `name=value`.
+/// If any of the options isn't set, nothing is printed and the command fails
+/// unless `--default` is given.
 #[derive(clap::Args, Clone, Debug)]
 #[command(verbatim_doc_comment)]
 pub struct ConfigGetArgs {
     #[arg(required = true, add = ArgValueCandidates::new(complete::leaf_config_keys))]
-    name: ConfigNamePathBuf,
+    names: Vec<ConfigNamePathBuf>,
+    /// Value to print if the config option isn't set
+    #[arg(long, value_name = "VALUE")]
+    default: Option<String>,
+    /// Check that the value is of the given type
+    #[arg(long = "type", value_name = "TYPE")]
+    value_type: Option<ConfigValueType>,
+}
+
+/// Type of config value to be printed.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Eq, PartialEq)]
+enum ConfigValueType {
+    /// String printed without quotes
+    String,
+    /// Integer, or string which can be parsed as an integer
+    Int,
+    /// Boolean, or string `true` or `false`
+    Bool,
+    /// Any value printed in JSON syntax
+    Json,
 }
 
 #[instrument(skip_all)]
@@ -46,24 +76,118 @@ pub fn cmd_config_get(
     command: &CommandHelper,
     args: &ConfigGetArgs,
 ) -> Result<(), CommandError> {
-    let stringified = command
-        .settings()
-        .get_value_with(&args.name, |value| match value {
-            // Remove extra formatting from a string value
-            ConfigValue::String(v) => Ok(v.into_value()),
-            // Print other values in TOML syntax (but whitespace trimmed)
-            ConfigValue::Integer(_)
-            | ConfigValue::Float(_)
-            | ConfigValue::Boolean(_)
-            | ConfigValue::Datetime(_) => Ok(value.decorated("", "").to_string()),
-            // TODO: maybe okay to just print array or table in TOML syntax?
-            ConfigValue::Array(_) => {
-                Err("Expected a value convertible to a string, but is an array")
-            }
-            ConfigValue::InlineTable(_) => {
-                Err("Expected a value convertible to a string, but is a table")
+    let default_lines = args
+        .default
+        .as_ref()
+        .map(|default| format_default(default, args.value_type))
+        .transpose()
+        .map_err(|err| user_error_with_message("Invalid --default value", err))?;
+    // Look up all values first so nothing is printed on error.
+    let mut values = Vec::with_capacity(args.names.len());
+    for name in &args.names {
+        let result = command
+            .settings()
+            .get_value_with(name, |value| format_value(value, args.value_type));
+        let lines = match (result, &default_lines) {
+            (Ok(lines), _) => lines,
+            (Err(ConfigGetError::NotFound { .. }), Some(default_lines)) => default_lines.clone(),
+            (Err(err), _) => return Err(err.into()),
+        };
+        values.push((name, lines));
+    }
+
+    let mut stdout = ui.stdout();
+    if let [(_, lines)] = values.as_slice() {
+        for line in lines {
+            writeln!(stdout, "{line}")?;
+        }
+    } else {
+        for (name, lines) in &values {
+            for line in lines {
+                writeln!(stdout, "{name}={line}")?;
             }
-        })?;
-    writeln!(ui.stdout(), "{stringified}")?;
+        }
+    }
     Ok(())
 }
+
+/// Formats `value` as lines of text.
+fn format_value(
+    value: ConfigValue,
+    value_type: Option<ConfigValueType>,
+) -> Result<Vec<String>, Box<dyn error::Error + Send + Sync>> {
+    match value_type {
+        None => match value {
+            ConfigValue::Array(array) => array.into_iter().map(format_scalar).try_collect(),
+            value => Ok(vec![format_scalar(value)?]),
+        },
+        Some(ConfigValueType::String) => match value {
+            ConfigValue::String(v) => Ok(vec![v.into_value()]),
+            value => Err(type_error("a string", &value)),
+        },
+        Some(ConfigValueType::Int) => match value {
+            ConfigValue::Integer(v) => Ok(vec![v.into_value().to_string()]),
+            ConfigValue::String(v) => match v.value().trim().parse::<i64>() {
+                Ok(n) => Ok(vec![n.to_string()]),
+                Err(_) => Err(format!("Expected an integer, but is {:?}", v.value()).into()),
+            },
+            value => Err(type_error("an integer", &value)),
+        },
+        Some(ConfigValueType::Bool) => match value {
+            ConfigValue::Boolean(v) => Ok(vec![v.into_value().to_string()]),
+            ConfigValue::String(v) => match v.value().as_str() {
+                "true" | "false" => Ok(vec![v.into_value()]),
+                _ => Err(format!("Expected a boolean, but is {:?}", v.value()).into()),
+            },
+            value => Err(type_error("a boolean", &value)),
+        },
+        Some(ConfigValueType::Json) => {
+            let json = serde_json::Value::deserialize(value.into_deserializer())?;
+            Ok(vec![serde_json::to_string(&json)?])
+        }
+    }
+}
+
+/// Formats the `--default` string as lines of text, checking that it's of the
+/// given type. JSON values are parsed.
+fn format_default(
+    default: &str,
+    value_type: Option<ConfigValueType>,
+) -> Result<Vec<String>, Box<dyn error::Error + Send + Sync>> {
+    match value_type {
+        Some(ConfigValueType::Json) => {
+            let json: serde_json::Value = serde_json::from_str(default)?;
+            Ok(vec![serde_json::to_string(&json)?])
+        }
+        _ => format_value(ConfigValue::from(default), value_type),
+    }
+}
+
+/// Formats scalar `value` without extra formatting.
+fn format_scalar(value: ConfigValue) -> Result<String, Box<dyn error::Error + Send + Sync>> {
+    match value {
+        // Remove extra formatting from a string value
+        ConfigValue::String(v) => Ok(v.into_value()),
+        // Print other values in TOML syntax (but whitespace trimmed)
+        ConfigValue::Integer(_)
+        | ConfigValue::Float(_)
+        | ConfigValue::Boolean(_)
+        | ConfigValue::Datetime(_) => Ok(value.decorated("", "").to_string()),
+        ConfigValue::Array(_) | ConfigValue::InlineTable(_) => {
+            Err(type_error("a value convertible to a string", &value))
+        }
+    }
+}
+
+fn type_error(expected: &str, value: &ConfigValue) -> Box<dyn error::Error + Send + Sync> {
+    let actual = match value {
+        ConfigValue::String(_) => "a string",
+        ConfigValue::Integer(_) => "an integer",
+        ConfigValue::Float(_) => "a float",
+        ConfigValue::Boolean(_) => "a boolean",
+        ConfigValue::Datetime(_) => "a datetime",
+        ConfigValue::Array(_) => "an array",
+        ConfigValue::InlineTable(_) => "a table",
+    };
+    format!("Expected {expected}, but is {actual}").into()
+}
@@ -20,15 +20,24 @@ use tracing::instrument;
 
 use super::ConfigLevelArgs;
 use crate::cli_util::CommandHelper;
+use crate::command_error::config_error;
 use crate::command_error::CommandError;
 use crate::complete;
 use crate::config::resolved_config_values;
 use crate::config::AnnotatedValue;
+use crate::config_schema::check_config;
 use crate::generic_templater::GenericTemplateLanguage;
 use crate::template_builder::TemplateLanguage as _;
 use crate::templater::TemplatePropertyExt as _;
 use crate::ui::Ui;
 
+const CONFIG_LIST_WITH_ORIGIN_TEMPLATE: &str = r##"
+if(overridden,
+  label("overridden", indent("# ", name ++ " = " ++ value)),
+  name ++ " = " ++ value,
+) ++ "  # " ++ source ++ if(path, " " ++ path) ++ if(scope, ", " ++ scope) ++ "\n"
+"##;
+
 /// List variables set in config file, along with their values.
 #[derive(clap::Args, Clone, Debug)]
 #[command(mut_group("config_level", |g| g.required(false)))]
@@ -42,9 +51,24 @@ pub struct ConfigListArgs {
     /// Allow printing overridden values.
     #[arg(long)]
     pub include_overridden: bool,
+    /// Annotate each variable with where it is defined.
+    ///
+    /// The origin is `default` for built-in default values, `env` for values
+    /// derived from environment variables, `user` or `repo` followed by the
+    /// config file path, and `cli` for command-line arguments.
+    #[arg(long, conflicts_with = "template")]
+    pub include_origin: bool,
+    /// Check the config files for unknown keys and values of wrong types
+    /// instead of listing variables.
+    ///
+    /// Keys prefixed with `x-` aren't checked.
+    #[arg(
+        long,
+        conflicts_with_all = ["name", "include_defaults", "include_overridden", "include_origin", "template"]
+    )]
+    pub check: bool,
     #[command(flatten)]
     pub level: ConfigLevelArgs,
-    // TODO(#1047): Support --show-origin using StackedConfig.
     /// Render each variable using the given template
     ///
     /// The following keywords are available in the [template expression]:
@@ -52,6 +76,12 @@ pub struct ConfigListArgs {
     /// * `name: String`: Config name.
     /// * `value: ConfigValue`: Value to be formatted in TOML syntax.
     /// * `overridden: Boolean`: True if the value is shadowed by other.
+    /// * `source: String`: Kind of the source where the value is defined:
+    ///   `default`, `env`, `user`, `repo`, or `cli`.
+    /// * `path: String`: Path to the config file where the value is defined, or
+    ///   empty if the value isn't defined in a file.
+    /// * `scope: String`: Conditions of the `[[--scope]]` table the value is
+    ///   defined in, or empty if the value isn't scoped.
     ///
     /// [template expression]:
     ///     https://jj-vcs.github.io/jj/latest/templates/
@@ -69,10 +99,15 @@ pub fn cmd_config_list(
     command: &CommandHelper,
     args: &ConfigListArgs,
 ) -> Result<(), CommandError> {
+    if args.check {
+        return check_config_problems(ui, command, args);
+    }
+
     let template = {
         let language = config_template_language(command.settings());
         let text = match &args.template {
             Some(value) => value.to_owned(),
+            None if args.include_origin => CONFIG_LIST_WITH_ORIGIN_TEMPLATE.to_owned(),
             None => command.settings().get_string("templates.config_list")?,
         };
         command
@@ -111,6 +146,31 @@ pub fn cmd_config_list(
     Ok(())
 }
 
+fn check_config_problems(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &ConfigListArgs,
+) -> Result<(), CommandError> {
+    let mut problems = check_config(command.settings().config());
+    if let Some(target_source) = args.level.get_source_kind() {
+        problems.retain(|problem| problem.source == target_source);
+    }
+    if problems.is_empty() {
+        writeln!(ui.status(), "No problems found in config")?;
+        return Ok(());
+    }
+    for problem in &problems {
+        writeln!(ui.warning_default(), "{problem}")?;
+        if let Some(hint) = problem.hint() {
+            writeln!(ui.hint_default(), "{hint}")?;
+        }
+    }
+    Err(config_error(format!(
+        "Found {} problem(s) in config",
+        problems.len()
+    )))
+}
+
 // AnnotatedValue will be cloned internally in the templater. If the cloning
 // cost matters, wrap it with Rc.
 fn config_template_language(
@@ -131,5 +191,24 @@ fn config_template_language(
         let out_property = self_property.map(|annotated| annotated.is_overridden);
         Ok(L::wrap_boolean(out_property))
     });
+    language.add_keyword("source", |self_property| {
+        let out_property = self_property.map(|annotated| annotated.source.to_string());
+        Ok(L::wrap_string(out_property))
+    });
+    language.add_keyword("path", |self_property| {
+        let out_property = self_property.map(|annotated| {
+            annotated
+                .path
+                .as_ref()
+                .map(|path| path.display().to_string())
+                .unwrap_or_default()
+        });
+        Ok(L::wrap_string(out_property))
+    });
+    language.add_keyword("scope", |self_property| {
+        let out_property =
+            self_property.map(|annotated| annotated.scope.clone().unwrap_or_default());
+        Ok(L::wrap_string(out_property))
+    });
     language
 }
@@ -37,15 +37,17 @@ pub fn cmd_debug_fileset(
 ) -> Result<(), CommandError> {
     let workspace_command = command.workspace_helper(ui)?;
     let path_converter = workspace_command.path_converter();
+    let aliases_map = workspace_command.env().fileset_aliases_map();
 
     let mut diagnostics = FilesetDiagnostics::new();
-    let expression = fileset::parse_maybe_bare(&mut diagnostics, &args.path, path_converter)?;
+    let expression =
+        fileset::parse_maybe_bare(&mut diagnostics, &args.path, aliases_map, path_converter)?;
     print_parse_diagnostics(ui, "In fileset expression", &diagnostics)?;
     writeln!(ui.stdout(), "-- Parsed:")?;
     writeln!(ui.stdout(), "{expression:#?}")?;
     writeln!(ui.stdout())?;
 
-    let matcher = expression.to_matcher();
+    let matcher = expression.to_matcher()?;
     writeln!(ui.stdout(), "-- Matcher:")?;
     writeln!(ui.stdout(), "{matcher:#?}")?;
     Ok(())
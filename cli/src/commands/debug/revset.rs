@@ -29,6 +29,12 @@ use crate::ui::Ui;
 #[derive(clap::Args, Clone, Debug)]
 pub struct DebugRevsetArgs {
     revision: String,
+    /// Limit number of commit IDs to evaluate and print
+    #[arg(long, short = 'n')]
+    limit: Option<usize>,
+    /// Print the number of times the filter predicates were evaluated
+    #[arg(long)]
+    stats: bool,
 }
 
 pub fn cmd_debug_revset(
@@ -73,8 +79,18 @@ pub fn cmd_debug_revset(
     writeln!(ui.stdout())?;
 
     writeln!(ui.stdout(), "-- Commit IDs:")?;
-    for commit_id in revset.iter() {
+    for commit_id in revset.iter().take(args.limit.unwrap_or(usize::MAX)) {
         writeln!(ui.stdout(), "{}", commit_id?.hex())?;
     }
+
+    if args.stats {
+        writeln!(ui.stdout())?;
+        writeln!(ui.stdout(), "-- Stats:")?;
+        writeln!(
+            ui.stdout(),
+            "Predicate evaluations: {}",
+            revset.num_predicate_evaluations()
+        )?;
+    }
     Ok(())
 }
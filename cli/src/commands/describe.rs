@@ -32,6 +32,7 @@ use crate::description_util::description_template;
 use crate::description_util::edit_description;
 use crate::description_util::edit_multiple_descriptions;
 use crate::description_util::join_message_paragraphs;
+use crate::description_util::new_commit_description;
 use crate::description_util::ParsedBulkEditMessage;
 use crate::text_util::parse_author;
 use crate::ui::Ui;
@@ -59,7 +60,8 @@ pub(crate) struct DescribeArgs {
     /// The change description to use (don't open editor)
     ///
     /// If multiple revisions are specified, the same description will be used
-    /// for all of them.
+    /// for all of them. On a terminal, you'll be asked for confirmation first
+    /// unless `--yes` is given.
     #[arg(
         long = "message",
         short,
@@ -104,6 +106,10 @@ pub(crate) struct DescribeArgs {
         value_parser = parse_author
     )]
     author: Option<(String, String)>,
+    /// Don't ask for confirmation when using `--message` with multiple
+    /// revisions
+    #[arg(long, short)]
+    yes: bool,
 }
 
 #[instrument(skip_all)]
@@ -126,6 +132,16 @@ pub(crate) fn cmd_describe(
         return Ok(());
     }
     workspace_command.check_rewritable(commits.iter().ids())?;
+    if commits.len() > 1
+        && !args.message_paragraphs.is_empty()
+        && !(args.yes || args.edit)
+        && Ui::can_prompt()
+    {
+        let prompt = format!("Set the same description on {} commits?", commits.len());
+        if !ui.prompt_yes_no(&prompt, Some(false))? {
+            return Err(user_error("Describe aborted"));
+        }
+    }
     let text_editor = workspace_command.text_editor()?;
 
     let mut tx = workspace_command.start_transaction();
@@ -174,8 +190,7 @@ pub(crate) fn cmd_describe(
                 if let Some(description) = &shared_description {
                     commit_builder.set_description(description);
                 } else if commit_builder.description().is_empty() {
-                    commit_builder
-                        .set_description(tx.settings().get_string("ui.default-description")?);
+                    commit_builder.set_description(new_commit_description(ui, &tx, commit)?);
                 }
                 if args.reset_author {
                     let new_author = commit_builder.committer().clone();
@@ -252,6 +267,7 @@ pub(crate) fn cmd_describe(
         .map(|(commit, new_description)| (commit.id(), new_description))
         .collect();
 
+    let defer_rebase = tx.repo().defers_descendant_rebase();
     let mut num_described = 0;
     let mut num_reparented = 0;
     // Even though `MutRepo::rewrite_commit` and `MutRepo::rebase_descendants` can
@@ -266,6 +282,10 @@ pub(crate) fn cmd_describe(
             .collect_vec(),
         |rewriter| {
             let old_commit_id = rewriter.old_commit().id().clone();
+            if defer_rebase && !commit_descriptions.contains_key(&old_commit_id) {
+                // Left in place until the deferred rewrites are resolved.
+                return Ok(());
+            }
             let mut commit_builder = rewriter.reparent();
             if let Some(description) = commit_descriptions.get(&old_commit_id) {
                 commit_builder = commit_builder.set_description(description);
@@ -22,6 +22,7 @@ use tracing::instrument;
 
 use crate::cli_util::print_unmatched_explicit_paths;
 use crate::cli_util::CommandHelper;
+use crate::cli_util::ExcludeArgs;
 use crate::cli_util::RevisionArg;
 use crate::command_error::CommandError;
 use crate::complete;
@@ -82,6 +83,8 @@ pub(crate) struct DiffArgs {
     )]
     paths: Vec<String>,
     #[command(flatten)]
+    exclude_args: ExcludeArgs,
+    #[command(flatten)]
     format: DiffFormatArgs,
 }
 
@@ -93,33 +96,36 @@ pub(crate) fn cmd_diff(
 ) -> Result<(), CommandError> {
     let workspace_command = command.workspace_helper(ui)?;
     let repo = workspace_command.repo();
-    let fileset_expression = workspace_command.parse_file_patterns(ui, &args.paths)?;
-    let matcher = fileset_expression.to_matcher();
+    let fileset_expression =
+        workspace_command.parse_file_patterns_excluding(ui, &args.paths, &args.exclude_args)?;
     let resolve_revision = |r: &Option<RevisionArg>| {
         workspace_command.resolve_single_rev(ui, r.as_ref().unwrap_or(&RevisionArg::AT))
     };
 
     let from_tree;
     let to_tree;
-    let mut copy_records = CopyRecords::default();
+    let copy_sources;
+    let to;
     if args.from.is_some() || args.to.is_some() {
         let from = resolve_revision(&args.from)?;
-        let to = resolve_revision(&args.to)?;
+        to = resolve_revision(&args.to)?;
         from_tree = from.tree()?;
         to_tree = to.tree()?;
-
-        let records = get_copy_records(repo.store(), from.id(), to.id(), &matcher)?;
-        copy_records.add_records(records)?;
+        copy_sources = vec![from];
     } else {
-        let to = resolve_revision(&args.revision)?;
+        to = resolve_revision(&args.revision)?;
         let parents: Vec<_> = to.parents().try_collect()?;
         from_tree = merge_commit_trees(repo.as_ref(), &parents)?;
         to_tree = to.tree()?;
+        copy_sources = parents;
+    }
 
-        for p in &parents {
-            let records = get_copy_records(repo.store(), p.id(), to.id(), &matcher)?;
-            copy_records.add_records(records)?;
-        }
+    // File predicates such as size() are evaluated against the new tree.
+    let matcher = fileset_expression.to_matcher_in_tree(&to_tree)?;
+    let mut copy_records = CopyRecords::default();
+    for source in &copy_sources {
+        let records = get_copy_records(repo.store(), source.id(), to.id(), &matcher)?;
+        copy_records.add_records(records)?;
     }
 
     let diff_renderer = workspace_command.diff_renderer_for(&args.format)?;
@@ -15,10 +15,11 @@
 use std::io::Write;
 
 use clap_complete::ArgValueCandidates;
+use clap_complete::ArgValueCompleter;
 use itertools::Itertools;
-use jj_lib::matchers::EverythingMatcher;
 use jj_lib::object_id::ObjectId;
 use jj_lib::rewrite::merge_commit_trees;
+use jj_lib::rewrite::restore_tree;
 use tracing::instrument;
 
 use crate::cli_util::CommandHelper;
@@ -43,6 +44,13 @@ use crate::ui::Ui;
 /// updated. Unless `--restore-descendants` is used, descendants will be
 /// rebased on top as usual, which may result in conflicts.
 ///
+/// If paths are specified, only changes to those paths are shown in the diff
+/// editor. With `--reset`, the diff editor isn't started at all; the specified
+/// paths are instead reset to their contents on the left side of the diff. For
+/// example, `jj diffedit --from Y --to X --reset --restore-descendants <paths>`
+/// makes the paths in X match Y without changing the contents of X's
+/// descendants.
+///
 /// See `jj restore` if you want to move entire files from one revision to
 /// another. For moving changes between revisions, see `jj squash -i`.
 #[derive(clap::Args, Clone, Debug)]
@@ -88,6 +96,17 @@ pub(crate) struct DiffeditArgs {
     /// is preserved instead of preserving the diff.
     #[arg(long)]
     restore_descendants: bool,
+    /// Reset the specified paths to the left side of the diff instead of
+    /// starting the diff editor
+    #[arg(long, requires = "paths", conflicts_with = "tool")]
+    reset: bool,
+    /// Edit only changes to these paths (instead of all paths)
+    #[arg(
+        value_name = "FILESETS",
+        value_hint = clap::ValueHint::AnyPath,
+        add = ArgValueCompleter::new(complete::modified_revision_or_range_files),
+    )]
+    paths: Vec<String>,
 }
 
 #[instrument(skip_all)]
@@ -116,7 +135,14 @@ pub(crate) fn cmd_diffedit(
     };
     workspace_command.check_rewritable([target_commit.id()])?;
 
-    let diff_editor = workspace_command.diff_editor(ui, args.tool.as_deref())?;
+    let matcher = workspace_command
+        .parse_file_patterns(ui, &args.paths)?
+        .to_matcher()?;
+    let diff_editor = if args.reset {
+        None
+    } else {
+        Some(workspace_command.diff_editor(ui, args.tool.as_deref())?)
+    };
     let mut tx = workspace_command.start_transaction();
     let format_instructions = || {
         format!(
@@ -132,7 +158,11 @@ don't make any changes, then the operation will be aborted.",
     };
     let base_tree = merge_commit_trees(tx.repo(), base_commits.as_slice())?;
     let tree = target_commit.tree()?;
-    let tree_id = diff_editor.edit(&base_tree, &tree, &EverythingMatcher, format_instructions)?;
+    let tree_id = if let Some(diff_editor) = &diff_editor {
+        diff_editor.edit(&base_tree, &tree, matcher.as_ref(), format_instructions)?
+    } else {
+        restore_tree(&base_tree, &tree, matcher.as_ref())?
+    };
     if tree_id == *target_commit.tree_id() {
         writeln!(ui.status(), "Nothing changed.")?;
     } else {
@@ -16,8 +16,10 @@ use std::io::Write;
 
 use clap_complete::ArgValueCandidates;
 use jj_lib::object_id::ObjectId;
+use jj_lib::repo::Repo as _;
 use tracing::instrument;
 
+use crate::cli_util::short_commit_hash;
 use crate::cli_util::CommandHelper;
 use crate::cli_util::RevisionArg;
 use crate::command_error::CommandError;
@@ -31,6 +33,11 @@ use crate::ui::Ui;
 ///
 /// [generally recommended]:
 ///     https://jj-vcs.github.io/jj/latest/FAQ#how-do-i-resume-working-on-an-existing-change
+///
+/// With `--clone`, an immutable revision is duplicated onto its parents and
+/// the copy is edited instead, e.g. to experiment on top of an old release.
+/// Bookmarks stay on the original revision. A mutable revision is edited as
+/// usual.
 #[derive(clap::Args, Clone, Debug)]
 pub(crate) struct EditArgs {
     /// The commit to edit
@@ -39,6 +46,9 @@ pub(crate) struct EditArgs {
     /// Ignored (but lets you pass `-r` for consistency with other commands)
     #[arg(short = 'r', hide = true)]
     unused_revision: bool,
+    /// Edit a copy of the revision if it is immutable
+    #[arg(long)]
+    clone: bool,
 }
 
 #[instrument(skip_all)]
@@ -49,6 +59,32 @@ pub(crate) fn cmd_edit(
 ) -> Result<(), CommandError> {
     let mut workspace_command = command.workspace_helper(ui)?;
     let new_commit = workspace_command.resolve_single_rev(ui, &args.revision)?;
+    if args.clone
+        && new_commit.id() != workspace_command.repo().store().root_commit_id()
+        && !workspace_command.is_rewritable(new_commit.id())?
+    {
+        let mut tx = workspace_command.start_transaction();
+        let cloned_commit = tx
+            .repo_mut()
+            .rewrite_commit(&new_commit)
+            .generate_new_change_id()
+            .write()?;
+        if let Some(mut formatter) = ui.status_formatter() {
+            write!(
+                formatter,
+                "Duplicated immutable commit {} as ",
+                short_commit_hash(new_commit.id())
+            )?;
+            tx.write_commit_summary(formatter.as_mut(), &cloned_commit)?;
+            writeln!(formatter)?;
+        }
+        tx.edit(&cloned_commit)?;
+        tx.finish(
+            ui,
+            format!("edit duplicate of commit {}", new_commit.id().hex()),
+        )?;
+        return Ok(());
+    }
     workspace_command.check_rewritable([new_commit.id()])?;
     if workspace_command.get_wc_commit_id() == Some(new_commit.id()) {
         writeln!(ui.status(), "Already editing that commit")?;
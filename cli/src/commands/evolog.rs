@@ -12,7 +12,11 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
 use std::convert::Infallible;
+use std::io;
+use std::io::Write as _;
+use std::slice;
 
 use clap_complete::ArgValueCandidates;
 use itertools::Itertools;
@@ -23,7 +27,16 @@ use jj_lib::dag_walk::topo_order_reverse_ok;
 use jj_lib::graph::reverse_graph;
 use jj_lib::graph::GraphEdge;
 use jj_lib::graph::GraphNode;
+use jj_lib::index::ReadonlyIndex;
 use jj_lib::matchers::EverythingMatcher;
+use jj_lib::op_store::AbandonReason;
+use jj_lib::op_store::OpStoreResult;
+use jj_lib::op_store::OperationId;
+use jj_lib::op_walk;
+use jj_lib::operation::Operation;
+use jj_lib::repo::ReadonlyRepo;
+use jj_lib::repo::Repo as _;
+use jj_lib::repo::RepoLoader;
 use tracing::instrument;
 
 use super::log::get_node_template;
@@ -31,18 +44,24 @@ use crate::cli_util::format_template;
 use crate::cli_util::CommandHelper;
 use crate::cli_util::LogContentFormat;
 use crate::cli_util::RevisionArg;
+use crate::command_error::internal_error;
 use crate::command_error::CommandError;
 use crate::commit_templater::CommitTemplateLanguage;
 use crate::complete;
 use crate::diff_util::DiffFormatArgs;
+use crate::formatter::Formatter;
 use crate::graphlog::get_graphlog;
 use crate::graphlog::GraphStyle;
+use crate::operation_templater::OperationTemplateLanguage;
 use crate::ui::Ui;
 
 /// Show how a change has evolved over time
 ///
 /// Lists the previous commits which a change has pointed to. The current commit
 /// of a change evolves when the change is updated, rebased, etc.
+///
+/// With `--operations`, each version is followed by the operation which
+/// created it, so it can be looked up in `jj op log` or passed to `jj op show`.
 #[derive(clap::Args, Clone, Debug)]
 pub(crate) struct EvologArgs {
     #[arg(
@@ -85,6 +104,11 @@ pub(crate) struct EvologArgs {
     /// contaminated by unrelated changes.
     #[arg(long, short = 'p')]
     patch: bool,
+    /// Show the operation which created each version of the change
+    ///
+    /// The operation is rendered using the `templates.op_summary` template.
+    #[arg(long)]
+    operations: bool,
     #[command(flatten)]
     diff_format: DiffFormatArgs,
 }
@@ -98,6 +122,16 @@ pub(crate) fn cmd_evolog(
     let workspace_command = command.workspace_helper(ui)?;
 
     let start_commit = workspace_command.resolve_single_rev(ui, &args.revision)?;
+    let repo = workspace_command.repo();
+    if repo.resolve_change_id(start_commit.change_id()).is_none() {
+        // No visible commits are left in the change, so it was abandoned.
+        let reason = find_abandon_reason(repo, &start_commit)?;
+        writeln!(
+            ui.status(),
+            "The change was abandoned: {}",
+            reason.map_or("unknown", |reason| reason.as_str())
+        )?;
+    }
 
     let diff_renderer = workspace_command.diff_renderer_for_log(&args.diff_format, args.patch)?;
     let graph_style = GraphStyle::from_settings(workspace_command.settings())?;
@@ -128,6 +162,28 @@ pub(crate) fn cmd_evolog(
             )?
             .labeled("node");
     }
+    let op_summary_template = if args.operations {
+        let language = OperationTemplateLanguage::new(
+            repo.loader(),
+            Some(repo.op_id()),
+            workspace_command.env().operation_template_extensions(),
+        );
+        let text = workspace_command
+            .settings()
+            .get_string("templates.op_summary")?;
+        Some(
+            workspace_command
+                .parse_template(
+                    ui,
+                    &language,
+                    &text,
+                    OperationTemplateLanguage::wrap_operation,
+                )?
+                .labeled("operation"),
+        )
+    } else {
+        None
+    };
 
     ui.request_pager();
     let mut formatter = ui.stdout_formatter();
@@ -155,6 +211,22 @@ pub(crate) fn cmd_evolog(
     if let Some(n) = args.limit {
         commits.truncate(n);
     }
+    let creating_ops = if op_summary_template.is_some() {
+        find_creating_operations(repo, &commits)?
+    } else {
+        HashMap::new()
+    };
+    let write_operation = |formatter: &mut dyn Formatter, commit: &Commit| -> io::Result<()> {
+        let Some(template) = &op_summary_template else {
+            return Ok(());
+        };
+        if let Some(op) = creating_ops.get(commit.id()) {
+            write!(formatter, "-- operation ")?;
+            template.format(op, formatter)?;
+            writeln!(formatter)?;
+        }
+        Ok(())
+    };
     if !args.no_graph {
         let mut raw_output = formatter.raw()?;
         let mut graph = get_graphlog(graph_style, raw_output.as_mut());
@@ -188,6 +260,7 @@ pub(crate) fn cmd_evolog(
             if !buffer.ends_with(b"\n") {
                 buffer.push(b'\n');
             }
+            write_operation(ui.new_formatter(&mut buffer).as_mut(), &commit)?;
             if let Some(renderer) = &diff_renderer {
                 let predecessors: Vec<_> = commit.predecessors().try_collect()?;
                 let mut formatter = ui.new_formatter(&mut buffer);
@@ -216,6 +289,7 @@ pub(crate) fn cmd_evolog(
         for commit in commits {
             with_content_format
                 .write(formatter, |formatter| template.format(&commit, formatter))?;
+            write_operation(formatter, &commit)?;
             if let Some(renderer) = &diff_renderer {
                 let predecessors: Vec<_> = commit.predecessors().try_collect()?;
                 let width = ui.term_width();
@@ -233,3 +307,95 @@ pub(crate) fn cmd_evolog(
 
     Ok(())
 }
+
+/// Looks up the reason why the given commit was abandoned, as recorded by the
+/// operation which abandoned it.
+///
+/// Operations which finished before the commit was created can't have
+/// abandoned it, so the search stops there.
+fn find_abandon_reason(
+    repo: &ReadonlyRepo,
+    commit: &Commit,
+) -> OpStoreResult<Option<AbandonReason>> {
+    let created = commit.committer().timestamp.timestamp;
+    for op in op_walk::walk_ancestors(slice::from_ref(repo.operation())) {
+        let op = op?;
+        if op.metadata().end_time.timestamp < created {
+            break;
+        }
+        if let Some(reason) = op.store_operation().abandoned_commits.get(commit.id()) {
+            return Ok(Some(*reason));
+        }
+    }
+    Ok(None)
+}
+
+/// Finds the operation which created each of the given commits.
+///
+/// A commit was created by the operation whose index contains it but whose
+/// parent operations' indexes don't. Commits created before the oldest
+/// operation which is still available are omitted.
+fn find_creating_operations<'a>(
+    repo: &ReadonlyRepo,
+    commits: impl IntoIterator<Item = &'a Commit>,
+) -> Result<HashMap<CommitId, Operation>, CommandError> {
+    let repo_loader = repo.loader();
+    let root_op_id = repo_loader.op_store().root_operation_id();
+    let mut remaining: HashMap<&CommitId, &Commit> = commits
+        .into_iter()
+        .map(|commit| (commit.id(), commit))
+        .collect();
+    let mut indexes = HashMap::new();
+    let mut creating_ops = HashMap::new();
+    for op in op_walk::walk_ancestors(slice::from_ref(repo.operation())) {
+        let op = op?;
+        let index = load_index_at_op(&mut indexes, repo_loader, &op)?;
+        let mut created = remaining
+            .keys()
+            .copied()
+            .filter(|id| index.as_index().has_id(id))
+            .collect_vec();
+        // The indexes of the ancestor operations can't contain the commits
+        // either, so there's no need to walk further.
+        if created.is_empty() {
+            break;
+        }
+        for parent_op in op.parents() {
+            let parent_op = parent_op?;
+            let parent_index = load_index_at_op(&mut indexes, repo_loader, &parent_op)?;
+            created.retain(|id| !parent_index.as_index().has_id(id));
+            // If the preceding operations were abandoned, the commits they
+            // created are indexed by their oldest remaining descendant. Those
+            // commits are older than the operation.
+            if parent_op.id() == root_op_id {
+                let start_time = op.metadata().start_time.timestamp;
+                created.retain(|id| remaining[id].committer().timestamp.timestamp >= start_time);
+            }
+        }
+        for id in created {
+            remaining.remove(id);
+            creating_ops.insert(id.clone(), op.clone());
+        }
+        if remaining.is_empty() {
+            break;
+        }
+        // Only the parents of the operations yet to be visited are needed.
+        indexes.remove(op.id());
+    }
+    Ok(creating_ops)
+}
+
+fn load_index_at_op<'a>(
+    indexes: &'a mut HashMap<OperationId, Box<dyn ReadonlyIndex>>,
+    repo_loader: &RepoLoader,
+    op: &Operation,
+) -> Result<&'a dyn ReadonlyIndex, CommandError> {
+    if !indexes.contains_key(op.id()) {
+        let index = repo_loader
+            .index_store()
+            .get_index_at_op(op, repo_loader.store())
+            .map_err(internal_error)?;
+        indexes.insert(op.id().clone(), index);
+    }
+    Ok(indexes[op.id()].as_ref())
+}
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use clap_complete::ArgValueCandidates;
+use jj_lib::repo_path::RepoPathUiConverter;
 use tracing::instrument;
 
 use crate::cli_util::CommandHelper;
@@ -48,6 +49,14 @@ pub(crate) struct FileListArgs {
     #[arg(long, short = 'T')]
     template: Option<String>,
 
+    /// Print paths relative to the current working directory (default)
+    #[arg(long, conflicts_with = "repo_root")]
+    cwd: bool,
+
+    /// Print paths relative to the workspace root
+    #[arg(long)]
+    repo_root: bool,
+
     /// Only list files matching these prefixes (instead of all files)
     #[arg(value_name = "FILESETS", value_hint = clap::ValueHint::AnyPath)]
     paths: Vec<String>,
@@ -64,9 +73,16 @@ pub(crate) fn cmd_file_list(
     let tree = commit.tree()?;
     let matcher = workspace_command
         .parse_file_patterns(ui, &args.paths)?
-        .to_matcher();
+        .to_matcher_in_tree(&tree)?;
+    let root_path_converter = RepoPathUiConverter::Fs {
+        cwd: workspace_command.workspace_root().to_owned(),
+        base: workspace_command.workspace_root().to_owned(),
+    };
     let template = {
-        let language = workspace_command.commit_template_language();
+        let mut language = workspace_command.commit_template_language();
+        if args.repo_root {
+            language.set_path_converter(&root_path_converter);
+        }
         let text = match &args.template {
             Some(value) => value.to_owned(),
             None => workspace_command.settings().get("templates.file_list")?,
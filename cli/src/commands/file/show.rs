@@ -89,7 +89,7 @@ pub(crate) fn cmd_file_show(
         }
     }
 
-    let matcher = fileset_expression.to_matcher();
+    let matcher = fileset_expression.to_matcher_in_tree(&tree)?;
     ui.request_pager();
     write_tree_entries(
         ui,
@@ -106,7 +106,7 @@ fn get_single_path(expression: &FilesetExpression) -> Option<&RepoPath> {
             // Not using pattern.as_path() because files-in:<path> shouldn't
             // select the literal <path> itself.
             FilePattern::FilePath(path) | FilePattern::PrefixPath(path) => Some(path),
-            FilePattern::FileGlob { .. } => None,
+            FilePattern::FileGlob { .. } | FilePattern::FileGlobI { .. } => None,
         },
         _ => None,
     }
@@ -17,6 +17,8 @@ use std::io::Write;
 
 use indoc::writedoc;
 use itertools::Itertools;
+use jj_lib::matchers::Matcher;
+use jj_lib::matchers::NothingMatcher;
 use jj_lib::repo_path::RepoPathUiConverter;
 use jj_lib::working_copy::SnapshotStats;
 use jj_lib::working_copy::UntrackedReason;
@@ -41,6 +43,14 @@ pub(crate) struct FileTrackArgs {
     /// Paths to track
     #[arg(required = true, value_name = "FILESETS", value_hint = clap::ValueHint::AnyPath)]
     paths: Vec<String>,
+    /// Track the paths even if they're ignored
+    ///
+    /// Paths can be ignored by `.gitignore` files, `.git/info/exclude`, the
+    /// global Git excludes file, or the workspace-local
+    /// `.jj/working-copy-ignore` file. Once tracked, the files stay tracked
+    /// until they're untracked with `jj file untrack`.
+    #[arg(long)]
+    include_ignored: bool,
 }
 
 #[instrument(skip_all)]
@@ -52,8 +62,14 @@ pub(crate) fn cmd_file_track(
     let (mut workspace_command, auto_stats) = command.workspace_helper_with_stats(ui)?;
     let matcher = workspace_command
         .parse_file_patterns(ui, &args.paths)?
-        .to_matcher();
-    let options = workspace_command.snapshot_options_with_start_tracking_matcher(&matcher)?;
+        .to_matcher()?;
+    let force_tracking_matcher: &dyn Matcher = if args.include_ignored {
+        matcher.as_ref()
+    } else {
+        &NothingMatcher
+    };
+    let options = workspace_command
+        .snapshot_options_with_tracking_matchers(matcher.as_ref(), force_tracking_matcher)?;
 
     let mut tx = workspace_command.start_transaction().into_inner();
     let (mut locked_ws, _wc_commit) = workspace_command.start_working_copy_mutation()?;
@@ -12,13 +12,21 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
 use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
 
 use clap_complete::ArgValueCompleter;
 use itertools::Itertools;
+use jj_lib::gitignore::GitIgnoreError;
+use jj_lib::gitignore::GitIgnoreFile;
+use jj_lib::matchers::FilesMatcher;
 use jj_lib::merge::Merge;
 use jj_lib::merged_tree::MergedTreeBuilder;
 use jj_lib::repo::Repo;
+use jj_lib::repo_path::RepoPath;
+use jj_lib::repo_path::RepoPathBuf;
 use tracing::instrument;
 
 use crate::cli_util::print_snapshot_stats;
@@ -33,15 +41,20 @@ use crate::ui::Ui;
 pub(crate) struct FileUntrackArgs {
     /// Paths to untrack. They must already be ignored.
     ///
-    /// The paths could be ignored via a .gitignore or .git/info/exclude (in
-    /// colocated repos).
+    /// The paths could be ignored via a .gitignore, .git/info/exclude (in
+    /// colocated repos), or .jj/working-copy-ignore.
     #[arg(
-        required = true,
+        required_unless_present = "matching_ignores",
         value_name = "FILESETS",
         value_hint = clap::ValueHint::AnyPath,
         add = ArgValueCompleter::new(complete::all_revision_files),
     )]
     paths: Vec<String>,
+    /// Untrack all tracked files which match ignore patterns
+    ///
+    /// If paths are specified, only the matching files in them are untracked.
+    #[arg(long)]
+    matching_ignores: bool,
 }
 
 #[instrument(skip_all)]
@@ -50,11 +63,42 @@ pub(crate) fn cmd_file_untrack(
     command: &CommandHelper,
     args: &FileUntrackArgs,
 ) -> Result<(), CommandError> {
-    let mut workspace_command = command.workspace_helper(ui)?;
+    let (mut workspace_command, mut auto_stats) = command.workspace_helper_with_stats(ui)?;
     let store = workspace_command.repo().store().clone();
-    let matcher = workspace_command
+    let mut matcher = workspace_command
         .parse_file_patterns(ui, &args.paths)?
-        .to_matcher();
+        .to_matcher()?;
+    if args.matching_ignores {
+        let wc_tree = workspace_command
+            .get_wc_commit_id()
+            .map(|id| store.get_commit(id)?.tree())
+            .transpose()?;
+        let tracked_paths = wc_tree
+            .iter()
+            .flat_map(|tree| tree.entries_matching(matcher.as_ref()))
+            .map(|(path, _value)| path);
+        let ignored_paths = find_ignored_paths(
+            workspace_command.workspace_root(),
+            &workspace_command.base_ignores()?,
+            tracked_paths,
+        )?;
+        if ignored_paths.is_empty() {
+            print_snapshot_stats(ui, &auto_stats, workspace_command.env().path_converter())?;
+            writeln!(ui.status(), "No tracked files match ignore patterns.")?;
+            return Ok(());
+        }
+        writeln!(ui.status(), "Untracking {} files:", ignored_paths.len())?;
+        for path in &ignored_paths {
+            let ui_path = workspace_command.format_file_path(path);
+            writeln!(ui.status(), "  {ui_path}")?;
+        }
+        matcher = Box::new(FilesMatcher::new(ignored_paths));
+    }
+    // Don't suggest untracking the files which are about to be untracked.
+    auto_stats
+        .ignored_tracked_paths
+        .retain(|path| !matcher.matches(path));
+    print_snapshot_stats(ui, &auto_stats, workspace_command.env().path_converter())?;
     let auto_tracking_matcher = workspace_command.auto_tracking_matcher(ui)?;
     let options =
         workspace_command.snapshot_options_with_start_tracking_matcher(&auto_tracking_matcher)?;
@@ -97,7 +141,7 @@ pub(crate) fn cmd_file_untrack(
             return Err(user_error_with_hint(
                 message,
                 "Files that are not ignored will be added back by the next command.
-Make sure they're ignored, then try again.",
+Make sure they're ignored or excluded from `snapshot.auto-track`, then try again.",
             ));
         } else {
             // This means there were some concurrent changes made in the working copy. We
@@ -114,3 +158,58 @@ Make sure they're ignored, then try again.",
     print_snapshot_stats(ui, &stats, workspace_command.env().path_converter())?;
     Ok(())
 }
+
+/// Returns the paths which match the ignore patterns found on disk. Files in
+/// ignored directories are considered ignored.
+fn find_ignored_paths(
+    workspace_root: &Path,
+    base_ignores: &Arc<GitIgnoreFile>,
+    paths: impl IntoIterator<Item = RepoPathBuf>,
+) -> Result<Vec<RepoPathBuf>, GitIgnoreError> {
+    let mut dir_ignores = HashMap::new();
+    let mut ignored_paths = vec![];
+    for path in paths {
+        let dir = path.parent().expect("file path shouldn't be the root");
+        let is_ignored = match dir_git_ignore(workspace_root, base_ignores, dir, &mut dir_ignores)?
+        {
+            Some(git_ignore) => git_ignore.matches(path.as_internal_file_string()),
+            None => true,
+        };
+        if is_ignored {
+            ignored_paths.push(path);
+        }
+    }
+    Ok(ignored_paths)
+}
+
+/// Loads the ignore patterns which apply to the files in `dir`, or returns
+/// `None` if the directory itself is ignored. Results are cached in
+/// `dir_ignores`.
+fn dir_git_ignore(
+    workspace_root: &Path,
+    base_ignores: &Arc<GitIgnoreFile>,
+    dir: &RepoPath,
+    dir_ignores: &mut HashMap<RepoPathBuf, Option<Arc<GitIgnoreFile>>>,
+) -> Result<Option<Arc<GitIgnoreFile>>, GitIgnoreError> {
+    if let Some(git_ignore) = dir_ignores.get(dir) {
+        return Ok(git_ignore.clone());
+    }
+    let parent_git_ignore = match dir.parent() {
+        Some(parent) => dir_git_ignore(workspace_root, base_ignores, parent, dir_ignores)?,
+        None => Some(base_ignores.clone()),
+    };
+    let git_ignore = match parent_git_ignore {
+        // .gitignore files in ignored directories are disregarded
+        Some(parent) if !dir.is_root() && parent.matches(&dir.to_internal_dir_string()) => None,
+        Some(parent) => {
+            let disk_dir = dir.to_fs_path_unchecked(workspace_root);
+            Some(
+                parent
+                    .chain_with_file(&dir.to_internal_dir_string(), disk_dir.join(".gitignore"))?,
+            )
+        }
+        None => None,
+    };
+    dir_ignores.insert(dir.to_owned(), git_ignore.clone());
+    Ok(git_ignore)
+}
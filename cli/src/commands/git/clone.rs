@@ -29,6 +29,7 @@ use crate::cli_util::CommandHelper;
 use crate::cli_util::WorkspaceCommandHelper;
 use crate::command_error::cli_error;
 use crate::command_error::user_error;
+use crate::command_error::user_error_with_hint;
 use crate::command_error::user_error_with_message;
 use crate::command_error::CommandError;
 use crate::commands::git::maybe_add_gitignore;
@@ -63,6 +64,13 @@ pub struct GitCloneArgs {
     /// Create a shallow clone of the given depth
     #[arg(long)]
     depth: Option<NonZeroU32>,
+    /// Create a partial clone omitting objects that match the given filter
+    ///
+    /// For example, `--filter=blob:none` omits all file contents. Missing
+    /// contents are fetched from the remote when a command needs them. This
+    /// requires `git.subprocess = true`.
+    #[arg(long, value_name = "FILTER_SPEC")]
+    filter: Option<String>,
 }
 
 fn clone_destination_for_source(source: &str) -> Option<&str> {
@@ -97,6 +105,12 @@ pub fn cmd_git_clone(
         .or_else(|| clone_destination_for_source(&source))
         .ok_or_else(|| user_error("No destination specified and wasn't able to guess it"))?;
     let wc_path = command.cwd().join(wc_path_str);
+    if args.filter.is_some() && !command.settings().git_settings()?.subprocess {
+        return Err(user_error_with_hint(
+            "Partial clones are not supported by the built-in Git client",
+            "Set `git.subprocess = true` to use the git executable.",
+        ));
+    }
 
     let wc_path_existed = wc_path.exists();
     if wc_path_existed && !is_empty_dir(&wc_path) {
@@ -116,8 +130,14 @@ pub fn cmd_git_clone(
 
     let clone_result = (|| -> Result<_, CommandError> {
         let workspace_command = init_workspace(ui, command, &canonical_wc_path, args.colocate)?;
-        let mut workspace_command =
-            configure_remote(ui, command, workspace_command, remote_name, &source)?;
+        let mut workspace_command = configure_remote(
+            ui,
+            command,
+            workspace_command,
+            remote_name,
+            &source,
+            args.filter.as_deref(),
+        )?;
         let default_branch = fetch_new_remote(ui, &mut workspace_command, remote_name, args.depth)?;
         Ok((workspace_command, default_branch))
     })();
@@ -194,9 +214,13 @@ fn configure_remote(
     workspace_command: WorkspaceCommandHelper,
     remote_name: &str,
     source: &str,
+    filter: Option<&str>,
 ) -> Result<WorkspaceCommandHelper, CommandError> {
     let git_repo = get_git_repo(workspace_command.repo().store())?;
     git::add_remote(&git_repo, remote_name, source)?;
+    if let Some(filter) = filter {
+        git::set_remote_partial_clone_filter(&git_repo, remote_name, filter)?;
+    }
     // Reload workspace to apply new remote configuration to
     // gix::ThreadSafeRepository behind the store.
     let workspace = command.load_workspace_at(
@@ -224,11 +248,12 @@ fn fetch_new_remote(
     let git_settings = workspace_command.settings().git_settings()?;
     let mut fetch_tx = workspace_command.start_transaction();
     let mut git_fetch = GitFetch::new(fetch_tx.repo_mut(), &git_settings)?;
-    with_remote_git_callbacks(ui, |cb| {
+    with_remote_git_callbacks(ui, &git_settings, |cb| {
         git_fetch.fetch(remote_name, &[StringPattern::everything()], cb, depth)
     })?;
-    let default_branch =
-        with_remote_git_callbacks(ui, |cb| git_fetch.get_default_branch(remote_name, cb))?;
+    let default_branch = with_remote_git_callbacks(ui, &git_settings, |cb| {
+        git_fetch.get_default_branch(remote_name, cb)
+    })?;
     let import_stats = git_fetch.import_refs()?;
     print_git_import_stats(ui, fetch_tx.repo(), &import_stats, true)?;
     fetch_tx.finish(ui, "fetch from git remote into empty repo")?;
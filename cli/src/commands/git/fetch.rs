@@ -12,12 +12,19 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::path::Path;
+
 use clap_complete::ArgValueCandidates;
 use itertools::Itertools;
+use jj_lib::config::ConfigFile;
 use jj_lib::config::ConfigGetResultExt as _;
+use jj_lib::config::ConfigSource;
 use jj_lib::git;
 use jj_lib::git::GitFetch;
+use jj_lib::git::GitImportStats;
+use jj_lib::git::RefName;
 use jj_lib::repo::Repo;
+use jj_lib::revset;
 use jj_lib::str_util::StringPattern;
 
 use crate::cli_util::CommandHelper;
@@ -25,6 +32,7 @@ use crate::cli_util::WorkspaceCommandHelper;
 use crate::cli_util::WorkspaceCommandTransaction;
 use crate::command_error::CommandError;
 use crate::commands::git::get_single_remote;
+use crate::commands::git::write_repository_level_trunk_alias;
 use crate::complete;
 use crate::git_util::print_git_import_stats;
 use crate::git_util::with_remote_git_callbacks;
@@ -121,15 +129,43 @@ fn do_git_fetch(
     branch_names: &[StringPattern],
 ) -> Result<(), CommandError> {
     let git_settings = tx.settings().git_settings()?;
+    let repo_path = tx.base_workspace_helper().repo_path().to_owned();
+    let trunk_alias = load_repository_level_trunk_alias(&repo_path)?;
     let mut git_fetch = GitFetch::new(tx.repo_mut(), &git_settings)?;
 
+    let mut new_default_branch = None;
     for remote_name in remotes {
-        with_remote_git_callbacks(ui, |callbacks| {
+        with_remote_git_callbacks(ui, &git_settings, |callbacks| {
             git_fetch.fetch(remote_name, branch_names, callbacks, None)
         })?;
+        // Follow the remote's default branch only if trunk() was set up to track
+        // it (e.g. by "jj git clone".)
+        let Some(old_branch) = trunk_alias
+            .as_deref()
+            .and_then(|expr| parse_trunk_alias_bookmark(expr, remote_name))
+        else {
+            continue;
+        };
+        let default_branch = with_remote_git_callbacks(ui, &git_settings, |callbacks| {
+            git_fetch.get_default_branch(remote_name, callbacks)
+        })?;
+        if let Some(branch) = default_branch.filter(|branch| *branch != old_branch) {
+            new_default_branch = Some((remote_name, old_branch, branch));
+        }
     }
     let import_stats = git_fetch.import_refs()?;
     print_git_import_stats(ui, tx.repo(), &import_stats, true)?;
+    if git_settings.prune_on_fetch {
+        print_pruned_remote_bookmarks(ui, &import_stats, remotes, branch_names)?;
+    }
+    if let Some((remote_name, old_branch, branch)) = new_default_branch {
+        writeln!(
+            ui.status(),
+            "The default branch of remote `{remote_name}` changed from `{old_branch}` to \
+             `{branch}`",
+        )?;
+        write_repository_level_trunk_alias(ui, &repo_path, remote_name, &branch)?;
+    }
     warn_if_branches_not_found(
         ui,
         tx,
@@ -138,6 +174,65 @@ fn do_git_fetch(
     )
 }
 
+/// Prints the number of remote bookmarks deleted because they are no longer
+/// present in the fetched remotes. The bookmarks themselves are listed by the
+/// import stats.
+fn print_pruned_remote_bookmarks(
+    ui: &Ui,
+    import_stats: &GitImportStats,
+    remotes: &[String],
+    branch_names: &[StringPattern],
+) -> Result<(), CommandError> {
+    // Remote bookmarks may also be deleted if the Git refs were removed by
+    // other means, which shouldn't be reported as pruned.
+    let num_pruned = import_stats
+        .changed_remote_refs
+        .iter()
+        .filter(|(ref_name, (old_remote_ref, new_target))| match ref_name {
+            RefName::RemoteBranch { branch, remote } => {
+                old_remote_ref.is_present()
+                    && new_target.is_absent()
+                    && remotes.contains(remote)
+                    && branch_names.iter().any(|pattern| pattern.matches(branch))
+            }
+            _ => false,
+        })
+        .count();
+    if num_pruned > 0 {
+        writeln!(
+            ui.status(),
+            "Pruned {num_pruned} remote {} that no longer {} on the remote.",
+            if num_pruned == 1 {
+                "bookmark"
+            } else {
+                "bookmarks"
+            },
+            if num_pruned == 1 { "exists" } else { "exist" },
+        )?;
+    }
+    Ok(())
+}
+
+/// Loads the `trunk()` alias defined in the repository-level config file.
+fn load_repository_level_trunk_alias(repo_path: &Path) -> Result<Option<String>, CommandError> {
+    let file = ConfigFile::load_or_empty(ConfigSource::Repo, repo_path.join("config.toml"))?;
+    let expr = file
+        .layer()
+        .look_up_item(["revset-aliases", "trunk()"])
+        .ok()
+        .flatten()
+        .and_then(|item| item.as_str())
+        .map(|expr| expr.to_owned());
+    Ok(expr)
+}
+
+/// Returns the bookmark name if the `trunk()` alias `expr` points to a
+/// bookmark of the given `remote`.
+fn parse_trunk_alias_bookmark(expr: &str, remote: &str) -> Option<String> {
+    let name = expr.strip_suffix(&format!("@{}", revset::format_symbol(remote)))?;
+    revset::parse_symbol(name).ok()
+}
+
 fn warn_if_branches_not_found(
     ui: &mut Ui,
     tx: &WorkspaceCommandTransaction,
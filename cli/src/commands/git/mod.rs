@@ -19,6 +19,7 @@ mod import;
 mod init;
 mod push;
 mod remote;
+mod root;
 mod submodule;
 
 use std::path::Path;
@@ -45,6 +46,8 @@ use self::push::cmd_git_push;
 use self::push::GitPushArgs;
 use self::remote::cmd_git_remote;
 use self::remote::RemoteCommand;
+use self::root::cmd_git_root;
+use self::root::GitRootArgs;
 use self::submodule::cmd_git_submodule;
 use self::submodule::GitSubmoduleCommand;
 use crate::cli_util::CommandHelper;
@@ -72,6 +75,7 @@ pub enum GitCommand {
     Push(GitPushArgs),
     #[command(subcommand)]
     Remote(RemoteCommand),
+    Root(GitRootArgs),
     #[command(subcommand, hide = true)]
     Submodule(GitSubmoduleCommand),
 }
@@ -89,6 +93,7 @@ pub fn cmd_git(
         GitCommand::Init(args) => cmd_git_init(ui, command, args),
         GitCommand::Push(args) => cmd_git_push(ui, command, args),
         GitCommand::Remote(args) => cmd_git_remote(ui, command, args),
+        GitCommand::Root(args) => cmd_git_root(ui, command, args),
         GitCommand::Submodule(args) => cmd_git_submodule(ui, command, args),
     }
 }
@@ -17,7 +17,9 @@ use std::collections::HashSet;
 use std::fmt;
 use std::io;
 use std::io::Write;
+use std::slice;
 
+use bstr::ByteVec as _;
 use clap::ArgGroup;
 use clap_complete::ArgValueCandidates;
 use indexmap::IndexSet;
@@ -30,10 +32,12 @@ use jj_lib::git;
 use jj_lib::git::GitBranchPushTargets;
 use jj_lib::object_id::ObjectId;
 use jj_lib::op_store::RefTarget;
+use jj_lib::op_walk;
 use jj_lib::refs::classify_bookmark_push_action;
 use jj_lib::refs::BookmarkPushAction;
 use jj_lib::refs::BookmarkPushUpdate;
 use jj_lib::refs::LocalAndRemoteRef;
+use jj_lib::repo::ReadonlyRepo;
 use jj_lib::repo::Repo;
 use jj_lib::revset::RevsetExpression;
 use jj_lib::settings::UserSettings;
@@ -48,10 +52,12 @@ use crate::cli_util::RevisionArg;
 use crate::cli_util::WorkspaceCommandHelper;
 use crate::cli_util::WorkspaceCommandTransaction;
 use crate::command_error::user_error;
+use crate::command_error::user_error_with_hint;
 use crate::command_error::CommandError;
 use crate::commands::git::get_single_remote;
 use crate::complete;
 use crate::formatter::Formatter;
+use crate::formatter::PlainTextFormatter;
 use crate::git_util::with_remote_git_callbacks;
 use crate::ui::Ui;
 
@@ -155,7 +161,9 @@ pub struct GitPushArgs {
     ///
     /// The created bookmark will be tracked automatically. Use the
     /// `git.push-bookmark-prefix` setting to change the prefix for generated
-    /// names.
+    /// names, or the `templates.git_push_bookmark` setting to generate names
+    /// from a template. Once created, the same bookmark is reused for the
+    /// change.
     #[arg(
         long,
         short,
@@ -245,9 +253,7 @@ pub fn cmd_git_push(
         let mut seen_bookmarks: HashSet<&str> = HashSet::new();
 
         // Process --change bookmarks first because matching bookmarks can be moved.
-        let bookmark_prefix = tx.settings().get_string("git.push-bookmark-prefix")?;
-        let change_bookmark_names =
-            update_change_bookmarks(ui, &mut tx, &args.change, &bookmark_prefix)?;
+        let change_bookmark_names = update_change_bookmarks(ui, &mut tx, &args.change)?;
         let change_bookmarks = change_bookmark_names.iter().map(|bookmark_name| {
             let targets = LocalAndRemoteRef {
                 local_target: tx.repo().view().get_local_bookmark(bookmark_name),
@@ -369,7 +375,7 @@ pub fn cmd_git_push(
         branch_updates: bookmark_updates,
     };
     let git_settings = tx.settings().git_settings()?;
-    with_remote_git_callbacks(ui, |cb| {
+    with_remote_git_callbacks(ui, &git_settings, |cb| {
         git::push_branches(tx.repo_mut(), &git_settings, &remote, &targets, cb)
     })?;
     tx.finish(ui, tx_description)?;
@@ -673,11 +679,15 @@ fn classify_bookmark_update(
 }
 
 /// Creates or moves bookmarks based on the change IDs.
+///
+/// If `templates.git_push_bookmark` is set, bookmark names are generated from
+/// the template, and recorded in the operation metadata. If the bookmark
+/// generated by a previous push still points to the change, it will be reused
+/// even if the template would now produce a different name.
 fn update_change_bookmarks(
     ui: &Ui,
     tx: &mut WorkspaceCommandTransaction,
     changes: &[RevisionArg],
-    bookmark_prefix: &str,
 ) -> Result<Vec<String>, CommandError> {
     if changes.is_empty() {
         // NOTE: we don't want resolve_some_revsets_default_single to fail if the
@@ -685,32 +695,75 @@ fn update_change_bookmarks(
         return Ok(vec![]);
     }
 
-    let mut bookmark_names = Vec::new();
     let workspace_command = tx.base_workspace_helper();
     let all_commits = workspace_command.resolve_some_revsets_default_single(ui, changes)?;
-
+    let settings = workspace_command.settings();
+    let bookmark_prefix = settings.get_string("git.push-bookmark-prefix")?;
+    let template = settings
+        .get_string("templates.git_push_bookmark")
+        .optional()?
+        .map(|text| workspace_command.parse_commit_template(ui, &text))
+        .transpose()?;
+
+    let mut commits_and_names = Vec::new();
+    let mut generated_bookmark_tags = Vec::new();
     for commit in all_commits {
-        let workspace_command = tx.base_workspace_helper();
         let short_change_id = short_change_hash(commit.change_id());
-        let mut bookmark_name = format!("{bookmark_prefix}{}", commit.change_id().hex());
         let view = tx.base_repo().view();
-        if view.get_local_bookmark(&bookmark_name).is_absent() {
-            // A local bookmark with the full change ID doesn't exist already, so use the
-            // short ID if it's not ambiguous (which it shouldn't be most of the time).
-            if workspace_command
-                .resolve_single_rev(ui, &RevisionArg::from(short_change_id.clone()))
-                .is_ok()
-            {
-                // Short change ID is not ambiguous, so update the bookmark name to use it.
-                bookmark_name = format!("{bookmark_prefix}{short_change_id}");
-            };
-        }
+        let bookmark_name = if let Some(template) = &template {
+            let tag_key = generated_bookmark_tag_key(&commit);
+            if let Some(name) = find_generated_bookmark(tx.base_repo(), &commit)? {
+                generated_bookmark_tags.push((tag_key, name.clone()));
+                commits_and_names.push((commit, name));
+                continue;
+            }
+            let mut output = Vec::new();
+            template
+                .format(&commit, &mut PlainTextFormatter::new(&mut output))
+                .expect("write() to vec backed formatter should never fail");
+            let name = output.into_string_lossy();
+            validate_bookmark_name(&name).map_err(|reason| {
+                user_error_with_hint(
+                    format!(
+                        "Invalid bookmark name `{name}` generated for revision {short_change_id}: \
+                         {reason}"
+                    ),
+                    "Check the `templates.git_push_bookmark` setting.",
+                )
+            })?;
+            generated_bookmark_tags.push((tag_key, name.clone()));
+            name
+        } else {
+            let mut name = format!("{bookmark_prefix}{}", commit.change_id().hex());
+            if view.get_local_bookmark(&name).is_absent() {
+                // A local bookmark with the full change ID doesn't exist already, so use the
+                // short ID if it's not ambiguous (which it shouldn't be most of the time).
+                if workspace_command
+                    .resolve_single_rev(ui, &RevisionArg::from(short_change_id.clone()))
+                    .is_ok()
+                {
+                    // Short change ID is not ambiguous, so update the bookmark name to use it.
+                    name = format!("{bookmark_prefix}{short_change_id}");
+                };
+            }
+            name
+        };
         if view.get_local_bookmark(&bookmark_name).is_absent() {
             writeln!(
                 ui.status(),
                 "Creating bookmark {bookmark_name} for revision {short_change_id}",
             )?;
         }
+        commits_and_names.push((commit, bookmark_name));
+    }
+    // The template borrows the transaction.
+    drop(template);
+
+    for (key, name) in generated_bookmark_tags {
+        tx.set_tag(key, name);
+    }
+    let mut bookmark_names = Vec::new();
+    for (commit, bookmark_name) in commits_and_names {
         tx.repo_mut()
             .set_local_bookmark_target(&bookmark_name, RefTarget::normal(commit.id().clone()));
         bookmark_names.push(bookmark_name);
@@ -718,6 +771,82 @@ fn update_change_bookmarks(
     Ok(bookmark_names)
 }
 
+/// Checks that the bookmark name can be used as a Git branch name. Returns
+/// the reason if it can't.
+fn validate_bookmark_name(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("the name is empty".to_owned());
+    }
+    if let Some(c) = name
+        .chars()
+        .find(|&c| c.is_ascii_control() || " ~^:?*[\\".contains(c))
+    {
+        return Err(format!("the name contains invalid character {c:?}"));
+    }
+    if name == "@" || name.contains("@{") {
+        return Err("the name contains `@{` or is `@`".to_owned());
+    }
+    if name.contains("..") {
+        return Err("the name contains `..`".to_owned());
+    }
+    if name.ends_with('.') {
+        return Err("the name ends with `.`".to_owned());
+    }
+    for component in name.split('/') {
+        if component.is_empty() {
+            return Err("the name contains an empty path component".to_owned());
+        }
+        if component.starts_with('.') {
+            return Err(format!("the path component `{component}` starts with `.`"));
+        }
+        if component.ends_with(".lock") {
+            return Err(format!(
+                "the path component `{component}` ends with `.lock`"
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Prefix of the operation tags which record the bookmark names generated by
+/// `templates.git_push_bookmark`. The change ID follows the prefix.
+const GENERATED_BOOKMARK_TAG_PREFIX: &str = "git-push-bookmark:";
+
+fn generated_bookmark_tag_key(commit: &Commit) -> String {
+    format!(
+        "{GENERATED_BOOKMARK_TAG_PREFIX}{}",
+        commit.change_id().hex()
+    )
+}
+
+/// Looks up the bookmark generated for the change by a previous push.
+///
+/// Only the local bookmarks pointing to the commit are candidates, so the
+/// operation log is searched back to where they were created at most.
+fn find_generated_bookmark(
+    repo: &ReadonlyRepo,
+    commit: &Commit,
+) -> Result<Option<String>, CommandError> {
+    let mut candidates: HashSet<&str> = repo
+        .view()
+        .local_bookmarks_for_commit(commit.id())
+        .map(|(name, _)| name)
+        .collect();
+    let tag_key = generated_bookmark_tag_key(commit);
+    for op in op_walk::walk_ancestors(slice::from_ref(repo.operation())) {
+        if candidates.is_empty() {
+            break;
+        }
+        let op = op?;
+        if let Some(name) = op.metadata().tags.get(&tag_key) {
+            return Ok(candidates.contains(name.as_str()).then(|| name.clone()));
+        }
+        let view = op.view()?;
+        candidates.retain(|name| view.get_local_bookmark(name).is_present());
+    }
+    Ok(None)
+}
+
 fn find_bookmarks_to_push<'a>(
     view: &'a View,
     bookmark_patterns: &[StringPattern],
@@ -0,0 +1,51 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write as _;
+use std::path::PathBuf;
+
+use jj_lib::git;
+use tracing::instrument;
+
+use crate::cli_util::CommandHelper;
+use crate::command_error::user_error;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Show the underlying Git directory of a repository using the Git backend
+///
+/// If the repo is colocated with a linked Git worktree, this is the
+/// worktree-specific Git directory (like `git rev-parse --absolute-git-dir`.)
+#[derive(clap::Args, Clone, Debug)]
+pub struct GitRootArgs {}
+
+#[instrument(skip_all)]
+pub fn cmd_git_root(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    GitRootArgs {}: &GitRootArgs,
+) -> Result<(), CommandError> {
+    // The backend can be obtained without loading the repo, which would
+    // resolve the operation and load the index.
+    let workspace = command.load_workspace()?;
+    let git_backend = git::get_git_backend(workspace.repo_loader().store())?;
+    // The path of a linked worktree may have a trailing slash, which is
+    // removed by collecting the components.
+    let root: PathBuf = git_backend.git_repo_path().components().collect();
+    let root = root
+        .to_str()
+        .ok_or_else(|| user_error("The Git directory path is not valid UTF-8"))?;
+    writeln!(ui.stdout(), "{root}")?;
+    Ok(())
+}
@@ -32,6 +32,7 @@ use tracing::instrument;
 
 use crate::cli_util::format_template;
 use crate::cli_util::CommandHelper;
+use crate::cli_util::ExcludeArgs;
 use crate::cli_util::LogContentFormat;
 use crate::cli_util::RevisionArg;
 use crate::command_error::CommandError;
@@ -84,6 +85,8 @@ pub(crate) struct LogArgs {
         add = ArgValueCompleter::new(complete::log_files),
     )]
     paths: Vec<String>,
+    #[command(flatten)]
+    exclude_args: ExcludeArgs,
     /// Limit number of revisions to show
     ///
     /// Applied after revisions are filtered and reordered topologically, but
@@ -127,10 +130,12 @@ pub(crate) fn cmd_log(
     let workspace_command = command.workspace_helper(ui)?;
     let settings = workspace_command.settings();
 
-    let fileset_expression = workspace_command.parse_file_patterns(ui, &args.paths)?;
+    let fileset_expression =
+        workspace_command.parse_file_patterns_excluding(ui, &args.paths, &args.exclude_args)?;
+    let has_paths = !args.paths.is_empty() || !args.exclude_args.exclude.is_empty();
     let revset_expression = {
         // only use default revset if neither revset nor path are specified
-        let mut expression = if args.revisions.is_empty() && args.paths.is_empty() {
+        let mut expression = if args.revisions.is_empty() && !has_paths {
             let revset_string = settings.get_string("revsets.log")?;
             workspace_command.parse_revset(ui, &RevisionArg::from(revset_string))?
         } else if !args.revisions.is_empty() {
@@ -139,7 +144,7 @@ pub(crate) fn cmd_log(
             // a path was specified so we use all() and add path filter later
             workspace_command.attach_revset_evaluator(RevsetExpression::all())
         };
-        if !args.paths.is_empty() {
+        if has_paths {
             // Beware that args.paths = ["root:."] is not identical to []. The
             // former will filter out empty commits.
             let predicate = RevsetFilterPredicate::File(fileset_expression.clone());
@@ -149,7 +154,7 @@ pub(crate) fn cmd_log(
     };
 
     let repo = workspace_command.repo();
-    let matcher = fileset_expression.to_matcher();
+    let matcher = fileset_expression.to_matcher()?;
     let revset = revset_expression.evaluate()?;
 
     let store = repo.store();
@@ -315,7 +320,11 @@ pub(crate) fn cmd_log(
 
     // Check to see if the user might have specified a path when they intended
     // to specify a revset.
-    if let ([], [only_path]) = (args.revisions.as_slice(), args.paths.as_slice()) {
+    if let ([], [only_path], []) = (
+        args.revisions.as_slice(),
+        args.paths.as_slice(),
+        args.exclude_args.exclude.as_slice(),
+    ) {
         if only_path == "." && workspace_command.parse_file_path(only_path)?.is_root() {
             // For users of e.g. Mercurial, where `.` indicates the current commit.
             writeln!(
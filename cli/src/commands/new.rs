@@ -26,6 +26,7 @@ use jj_lib::revset::ResolvedRevsetExpression;
 use jj_lib::revset::RevsetExpression;
 use jj_lib::revset::RevsetIteratorExt;
 use jj_lib::rewrite::merge_commit_trees;
+use jj_lib::rewrite::merge_commit_trees_following_renames;
 use jj_lib::rewrite::rebase_commit;
 use tracing::instrument;
 
@@ -36,6 +37,7 @@ use crate::command_error::user_error;
 use crate::command_error::CommandError;
 use crate::complete;
 use crate::description_util::join_message_paragraphs;
+use crate::description_util::new_commit_description;
 use crate::ui::Ui;
 
 /// Create a new, empty change and (by default) edit it in the working copy
@@ -65,6 +67,13 @@ pub(crate) struct NewArgs {
     /// The change description to use
     #[arg(long = "message", short, value_name = "MESSAGE")]
     message_paragraphs: Vec<String>,
+    /// Use the description generated by the `templates.new_commit_description`
+    /// template
+    ///
+    /// By default, the new change has no description, so the placeholder text
+    /// in the template won't be committed accidentally.
+    #[arg(long, conflicts_with = "message_paragraphs")]
+    use_template_description: bool,
     /// Do not edit the newly created change
     #[arg(long, conflicts_with = "_edit")]
     no_edit: bool,
@@ -191,13 +200,42 @@ pub(crate) fn cmd_new(
 
     let parent_commit_ids_set: HashSet<CommitId> = parent_commit_ids.iter().cloned().collect();
 
+    let follow_renames = workspace_command
+        .settings()
+        .get_bool("merge.follow-renames")?;
     let mut tx = workspace_command.start_transaction();
-    let merged_tree = merge_commit_trees(tx.repo(), &parent_commits)?;
-    let new_commit = tx
+    let (merged_tree, followed_renames) = if follow_renames {
+        merge_commit_trees_following_renames(tx.repo(), &parent_commits)?
+    } else {
+        (merge_commit_trees(tx.repo(), &parent_commits)?, vec![])
+    };
+    for rename in &followed_renames {
+        let workspace_command = tx.base_workspace_helper();
+        let source = workspace_command.format_file_path(&rename.source);
+        let target = workspace_command.format_file_path(&rename.target);
+        if rename.resolved {
+            writeln!(
+                ui.status(),
+                "Merged changes to {source} into {target}, where it was renamed"
+            )?;
+        } else {
+            writeln!(
+                ui.status(),
+                "Moved conflicting changes to {source} to {target}, where it was renamed"
+            )?;
+        }
+    }
+    let mut commit_builder = tx
         .repo_mut()
         .new_commit(parent_commit_ids, merged_tree.id())
-        .set_description(join_message_paragraphs(&args.message_paragraphs))
-        .write()?;
+        .detach();
+    if args.use_template_description {
+        let temp_commit = commit_builder.write_hidden()?;
+        commit_builder.set_description(new_commit_description(ui, &tx, &temp_commit)?);
+    } else {
+        commit_builder.set_description(join_message_paragraphs(&args.message_paragraphs));
+    }
+    let new_commit = commit_builder.write(tx.repo_mut())?;
 
     let mut num_rebased = 0;
     for child_commit in children_commits {
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::convert::Infallible;
 use std::sync::Arc;
@@ -26,6 +27,7 @@ use jj_lib::dag_walk;
 use jj_lib::graph::GraphEdge;
 use jj_lib::graph::TopoGroupedGraphIterator;
 use jj_lib::matchers::EverythingMatcher;
+use jj_lib::op_store::AbandonReason;
 use jj_lib::op_store::RefTarget;
 use jj_lib::op_store::RemoteRef;
 use jj_lib::op_store::RemoteRefState;
@@ -154,6 +156,7 @@ pub fn cmd_op_diff(
         (!args.no_graph).then_some(graph_style),
         &with_content_format,
         diff_renderer.as_ref(),
+        None,
     )
 }
 
@@ -172,8 +175,10 @@ pub fn show_op_diff(
     graph_style: Option<GraphStyle>,
     with_content_format: &LogContentFormat,
     diff_renderer: Option<&DiffRenderer>,
+    max_changes: Option<usize>,
 ) -> Result<(), CommandError> {
     let changes = compute_operation_commits_diff(current_repo, from_repo, to_repo)?;
+    let abandon_reasons = &to_repo.operation().store_operation().abandoned_commits;
 
     let commit_id_change_id_map: HashMap<CommitId, ChangeId> = changes
         .iter()
@@ -195,11 +200,14 @@ pub fn show_op_diff(
         .collect();
 
     // Order changes in reverse topological order.
-    let ordered_change_ids = dag_walk::topo_order_reverse(
+    let mut ordered_change_ids = dag_walk::topo_order_reverse(
         changes.keys().cloned().collect_vec(),
         |change_id: &ChangeId| change_id.clone(),
         |change_id: &ChangeId| change_parents.get(change_id).unwrap().clone(),
     );
+    let num_elided_changes =
+        max_changes.map_or(0, |max| ordered_change_ids.len().saturating_sub(max));
+    ordered_change_ids.truncate(ordered_change_ids.len() - num_elided_changes);
 
     if !ordered_change_ids.is_empty() {
         writeln!(formatter)?;
@@ -217,6 +225,11 @@ pub fn show_op_diff(
                         change_id.clone(),
                         parent_change_ids
                             .iter()
+                            // Don't draw edges to elided changes
+                            .filter(|parent_change_id| {
+                                num_elided_changes == 0
+                                    || ordered_change_ids.contains(parent_change_id)
+                            })
                             .map(|parent_change_id| GraphEdge::direct(parent_change_id.clone()))
                             .collect_vec(),
                     ))
@@ -234,6 +247,7 @@ pub fn show_op_diff(
                         formatter,
                         commit_summary_template,
                         modified_change,
+                        abandon_reasons,
                     )
                 })?;
                 if !buffer.ends_with(b"\n") {
@@ -267,6 +281,7 @@ pub fn show_op_diff(
                         formatter,
                         commit_summary_template,
                         modified_change,
+                        abandon_reasons,
                     )
                 })?;
                 if let Some(diff_renderer) = &diff_renderer {
@@ -275,6 +290,11 @@ pub fn show_op_diff(
                 }
             }
         }
+        if num_elided_changes > 0 {
+            with_content_format.write(formatter, |formatter| {
+                writeln!(formatter, "… and {num_elided_changes} more")
+            })?;
+        }
     }
 
     let changed_local_bookmarks = diff_named_ref_targets(
@@ -387,6 +407,7 @@ fn write_modified_change_summary(
     formatter: &mut dyn Formatter,
     commit_summary_template: &TemplateRenderer<Commit>,
     modified_change: &ModifiedChange,
+    abandon_reasons: &BTreeMap<CommitId, AbandonReason>,
 ) -> Result<(), std::io::Error> {
     for commit in &modified_change.added_commits {
         formatter.with_label("diff", |formatter| write!(formatter.labeled("added"), "+"))?;
@@ -400,6 +421,9 @@ fn write_modified_change_summary(
         })?;
         write!(formatter, " ")?;
         commit_summary_template.format(commit, formatter)?;
+        if let Some(reason) = abandon_reasons.get(commit.id()) {
+            write!(formatter, " (abandoned: {})", reason.as_str())?;
+        }
         writeln!(formatter)?;
     }
     Ok(())
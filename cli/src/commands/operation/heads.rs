@@ -0,0 +1,88 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use clap_complete::ArgValueCandidates;
+use jj_lib::op_walk;
+
+use crate::cli_util::CommandHelper;
+use crate::command_error::CommandError;
+use crate::complete;
+use crate::operation_templater::OperationTemplateLanguage;
+use crate::ui::Ui;
+
+/// List the current operation heads
+///
+/// There is usually a single operation head. Concurrent commands can create
+/// divergent operation heads, which are merged automatically by the next
+/// command. Use `jj op merge` to merge them explicitly.
+///
+/// Unlike most commands, this doesn't merge divergent operation heads.
+#[derive(clap::Args, Clone, Debug)]
+pub struct OperationHeadsArgs {
+    /// Render each operation using the given template
+    ///
+    /// You can specify arbitrary [template expressions] using the
+    /// [built-in keywords].
+    ///
+    /// [template expression]:
+    ///     https://jj-vcs.github.io/jj/latest/templates/
+    ///
+    /// [built-in keywords]:
+    ///     https://jj-vcs.github.io/jj/latest/templates/#operation-keywords
+    #[arg(long, short = 'T', add = ArgValueCandidates::new(complete::template_aliases))]
+    template: Option<String>,
+}
+
+pub fn cmd_op_heads(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &OperationHeadsArgs,
+) -> Result<(), CommandError> {
+    // Don't load the repo so that the divergent operations aren't merged.
+    let workspace = command.load_workspace()?;
+    let workspace_env = command.workspace_environment(ui, &workspace)?;
+    let repo_loader = workspace.repo_loader();
+    let head_ops = op_walk::get_current_head_ops(
+        repo_loader.op_store(),
+        repo_loader.op_heads_store().as_ref(),
+    )?;
+
+    let template = {
+        let language = OperationTemplateLanguage::new(
+            repo_loader,
+            None,
+            workspace_env.operation_template_extensions(),
+        );
+        let text = match &args.template {
+            Some(value) => value.to_owned(),
+            None => repo_loader.settings().get_string("templates.op_summary")?,
+        };
+        workspace_env
+            .parse_template(
+                ui,
+                &language,
+                &text,
+                OperationTemplateLanguage::wrap_operation,
+            )?
+            .labeled("operation")
+    };
+
+    ui.request_pager();
+    let mut formatter = ui.stdout_formatter();
+    for op in head_ops.iter().rev() {
+        template.format(op, formatter.as_mut())?;
+        writeln!(formatter)?;
+    }
+    Ok(())
+}
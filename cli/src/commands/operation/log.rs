@@ -21,6 +21,7 @@ use jj_lib::config::ConfigGetResultExt as _;
 use jj_lib::graph::reverse_graph;
 use jj_lib::graph::GraphEdge;
 use jj_lib::op_store::OpStoreError;
+use jj_lib::op_store::OperationId;
 use jj_lib::op_walk;
 use jj_lib::operation::Operation;
 use jj_lib::repo::RepoLoader;
@@ -62,6 +63,12 @@ pub struct OperationLogArgs {
     /// Don't show the graph, show a flat list of operations
     #[arg(long)]
     no_graph: bool,
+    /// Hide operations which only snapshotted the working copy
+    ///
+    /// The graph edges of the remaining operations skip over the hidden
+    /// operations.
+    #[arg(long)]
+    no_snapshots: bool,
     /// Render each operation using the given template
     ///
     /// You can specify arbitrary [template expressions] using the
@@ -155,6 +162,7 @@ fn do_op_log(
     let diff_formats = diff_formats_for_log(settings, &args.diff_format, args.patch)?;
     let maybe_show_op_diff = if args.op_diff || !diff_formats.is_empty() {
         let template_text = settings.get_string("templates.commit_summary")?;
+        let max_changes = settings.get::<usize>("ui.op-log-max-changes").optional()?;
         let show = move |ui: &Ui,
                          formatter: &mut dyn Formatter,
                          op: &Operation,
@@ -196,6 +204,7 @@ fn do_op_log(
                 (!args.no_graph).then_some(graph_style),
                 with_content_format,
                 diff_renderer.as_ref(),
+                max_changes,
             )
         };
         Some(show)
@@ -206,16 +215,21 @@ fn do_op_log(
     ui.request_pager();
     let mut formatter = ui.stdout_formatter();
     let formatter = formatter.as_mut();
-    let iter =
-        op_walk::walk_ancestors(slice::from_ref(current_op)).take(args.limit.unwrap_or(usize::MAX));
+    let iter = op_walk::walk_ancestors(slice::from_ref(current_op))
+        .filter_ok(|op| !(args.no_snapshots && op.metadata().is_snapshot))
+        .take(args.limit.unwrap_or(usize::MAX));
 
     if !args.no_graph {
         let mut raw_output = formatter.raw()?;
         let mut graph = get_graphlog(graph_style, raw_output.as_mut());
         let iter = iter.map(|op| -> Result<_, OpStoreError> {
             let op = op?;
-            let ids = op.parent_ids();
-            let edges = ids.iter().cloned().map(GraphEdge::direct).collect();
+            let edges = if args.no_snapshots {
+                non_snapshot_parent_edges(&op)?
+            } else {
+                let ids = op.parent_ids();
+                ids.iter().cloned().map(GraphEdge::direct).collect()
+            };
             Ok((op, edges))
         });
         let iter_nodes: Box<dyn Iterator<Item = _>> = if args.reversed {
@@ -263,6 +277,29 @@ fn do_op_log(
     Ok(())
 }
 
+/// Returns edges to the nearest ancestors of `op` which aren't snapshot
+/// operations. Edges skipping over snapshot operations are indirect.
+fn non_snapshot_parent_edges(op: &Operation) -> Result<Vec<GraphEdge<OperationId>>, OpStoreError> {
+    let mut edges: Vec<GraphEdge<OperationId>> = vec![];
+    let mut to_visit: Vec<(Operation, bool)> =
+        op.parents().map_ok(|op| (op, false)).try_collect()?;
+    to_visit.reverse();
+    while let Some((parent, skipped)) = to_visit.pop() {
+        if parent.metadata().is_snapshot {
+            let grand_parents: Vec<_> = parent.parents().try_collect()?;
+            to_visit.extend(grand_parents.into_iter().rev().map(|op| (op, true)));
+        } else if !edges.iter().any(|edge| edge.target == *parent.id()) {
+            let id = parent.id().clone();
+            edges.push(if skipped {
+                GraphEdge::indirect(id)
+            } else {
+                GraphEdge::direct(id)
+            });
+        }
+    }
+    Ok(edges)
+}
+
 fn get_node_template(style: GraphStyle, settings: &UserSettings) -> Result<String, ConfigGetError> {
     let symbol = settings.get_string("templates.op_log_node").optional()?;
     let default = if style.is_ascii() {
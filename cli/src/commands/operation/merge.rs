@@ -0,0 +1,183 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write as _;
+
+use clap_complete::ArgValueCandidates;
+use itertools::Itertools as _;
+use jj_lib::op_heads_store;
+use jj_lib::op_walk;
+use jj_lib::repo::Repo as _;
+
+use crate::cli_util::short_operation_hash;
+use crate::cli_util::start_repo_transaction;
+use crate::cli_util::CommandHelper;
+use crate::command_error::cli_error;
+use crate::command_error::user_error;
+use crate::command_error::CommandError;
+use crate::complete;
+use crate::operation_templater::OperationTemplateLanguage;
+use crate::ui::Ui;
+
+/// Merge divergent operation heads
+///
+/// Concurrent commands can create divergent operation heads. They are usually
+/// merged automatically by the next command, unless `op.auto-merge` is
+/// disabled. This command merges them explicitly, and reports the bookmarks
+/// which became conflicted by the merge.
+///
+/// Use `jj op heads` to list the divergent operations.
+#[derive(clap::Args, Clone, Debug)]
+pub struct OperationMergeArgs {
+    /// Keep the repo state of this operation head, and discard the changes
+    /// made by the other heads
+    ///
+    /// The discarded operations are still recorded as parents of the new
+    /// operation, so they can be restored later.
+    #[arg(long, value_name = "OPERATION", add = ArgValueCandidates::new(complete::operations))]
+    keep: Option<String>,
+}
+
+pub fn cmd_op_merge(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &OperationMergeArgs,
+) -> Result<(), CommandError> {
+    // Don't load the repo so that the divergent operations aren't merged
+    // automatically.
+    let workspace = command.load_workspace()?;
+    let workspace_env = command.workspace_environment(ui, &workspace)?;
+    let repo_loader = workspace.repo_loader();
+    let op_store = repo_loader.op_store();
+    let op_heads_store = repo_loader.op_heads_store();
+    if command.global_args().at_operation.is_some() {
+        return Err(cli_error("--at-op is not respected"));
+    }
+    let current_head_ops = op_walk::get_current_head_ops(op_store, op_heads_store.as_ref())?;
+    let keep_op = args
+        .keep
+        .as_ref()
+        .map(|op_str| op_walk::resolve_op_at(op_store, &current_head_ops, op_str))
+        .transpose()?;
+    if let Some(op) = &keep_op {
+        if !current_head_ops.contains(op) {
+            return Err(user_error(format!(
+                "Operation {} is not an operation head",
+                short_operation_hash(op.id())
+            )));
+        }
+    }
+
+    let op_summary_template = {
+        let language = OperationTemplateLanguage::new(
+            repo_loader,
+            None,
+            workspace_env.operation_template_extensions(),
+        );
+        let text = repo_loader.settings().get_string("templates.op_summary")?;
+        workspace_env.parse_template(
+            ui,
+            &language,
+            &text,
+            OperationTemplateLanguage::wrap_operation,
+        )?
+    };
+
+    let mut merged = false;
+    op_heads_store::resolve_op_heads(
+        op_heads_store.as_ref(),
+        op_store,
+        |op_heads| -> Result<_, CommandError> {
+            merged = true;
+            let base_op = keep_op.as_ref().unwrap_or(&op_heads[0]);
+            if let Some(mut formatter) = ui.status_formatter() {
+                if keep_op.is_some() {
+                    write!(formatter, "Keeping operation: ")?;
+                    op_summary_template.format(base_op, formatter.as_mut())?;
+                    writeln!(formatter)?;
+                    writeln!(formatter, "Discarding changes from operations:")?;
+                } else {
+                    writeln!(formatter, "Merging operations:")?;
+                }
+                for op in op_heads
+                    .iter()
+                    .filter(|op| keep_op.is_none() || *op != base_op)
+                {
+                    write!(formatter, "  ")?;
+                    op_summary_template.format(op, formatter.as_mut())?;
+                    writeln!(formatter)?;
+                }
+            }
+
+            let base_repo = repo_loader.load_at(base_op)?;
+            let mut tx = start_repo_transaction(&base_repo, command.string_args());
+            for other_op_head in op_heads.iter().filter(|op| *op != base_op) {
+                tx.merge_operation(other_op_head.clone())?;
+                let num_rebased = tx.repo_mut().rebase_descendants()?;
+                if num_rebased > 0 && keep_op.is_none() {
+                    writeln!(
+                        ui.status(),
+                        "Rebased {num_rebased} descendant commits onto commits rewritten by other \
+                         operation"
+                    )?;
+                }
+            }
+            if keep_op.is_some() {
+                // The other operations are recorded as parents, but their changes
+                // are discarded.
+                tx.repo_mut()
+                    .set_view(base_repo.view().store_view().clone());
+            }
+
+            let conflicted_bookmarks = tx
+                .repo()
+                .view()
+                .local_bookmarks()
+                .filter(|(name, target)| {
+                    // Only report the conflicts introduced by the merge
+                    target.has_conflict()
+                        && !base_repo.view().get_local_bookmark(name).has_conflict()
+                })
+                .map(|(name, _)| name)
+                .collect_vec();
+            if !conflicted_bookmarks.is_empty() {
+                writeln!(
+                    ui.warning_default(),
+                    "The merged operation has conflicted bookmarks: {}",
+                    conflicted_bookmarks.join(", ")
+                )?;
+                writeln!(
+                    ui.hint_default(),
+                    "Use `jj bookmark list` to see details. Use `jj bookmark set <name> -r <rev>` \
+                     to resolve."
+                )?;
+            }
+
+            let description = if keep_op.is_some() {
+                "discard divergent operations"
+            } else {
+                "reconcile divergent operations"
+            };
+            Ok(tx
+                .write(description)
+                .leave_unpublished()
+                .operation()
+                .clone())
+        },
+    )?;
+    if !merged {
+        writeln!(ui.status(), "Nothing changed.")?;
+    }
+    Ok(())
+}
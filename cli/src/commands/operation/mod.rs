@@ -14,18 +14,35 @@
 
 mod abandon;
 mod diff;
+mod heads;
 mod log;
+mod merge;
 mod restore;
 mod show;
 pub mod undo;
 
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
 use abandon::cmd_op_abandon;
 use abandon::OperationAbandonArgs;
 use clap::Subcommand;
 use diff::cmd_op_diff;
+use diff::show_op_diff;
 use diff::OperationDiffArgs;
+use heads::cmd_op_heads;
+use heads::OperationHeadsArgs;
+use itertools::Itertools as _;
+use jj_lib::op_store::RemoteView;
+use jj_lib::refs::diff_named_ref_targets;
+use jj_lib::refs::diff_named_remote_refs;
+use jj_lib::repo::ReadonlyRepo;
+use jj_lib::repo::Repo as _;
+use jj_lib::str_util::StringPattern;
 use log::cmd_op_log;
 use log::OperationLogArgs;
+use merge::cmd_op_merge;
+use merge::OperationMergeArgs;
 use restore::cmd_op_restore;
 use restore::OperationRestoreArgs;
 use show::cmd_op_show;
@@ -34,7 +51,12 @@ use undo::cmd_op_undo;
 use undo::OperationUndoArgs;
 
 use crate::cli_util::CommandHelper;
+use crate::cli_util::LogContentFormat;
+use crate::cli_util::WorkspaceCommandHelper;
+use crate::command_error::internal_error;
 use crate::command_error::CommandError;
+use crate::commit_templater::CommitTemplateLanguage;
+use crate::graphlog::GraphStyle;
 use crate::ui::Ui;
 
 /// Commands for working with the operation log
@@ -47,7 +69,9 @@ use crate::ui::Ui;
 pub enum OperationCommand {
     Abandon(OperationAbandonArgs),
     Diff(OperationDiffArgs),
+    Heads(OperationHeadsArgs),
     Log(OperationLogArgs),
+    Merge(OperationMergeArgs),
     Restore(OperationRestoreArgs),
     Show(OperationShowArgs),
     Undo(OperationUndoArgs),
@@ -61,7 +85,9 @@ pub fn cmd_operation(
     match subcommand {
         OperationCommand::Abandon(args) => cmd_op_abandon(ui, command, args),
         OperationCommand::Diff(args) => cmd_op_diff(ui, command, args),
+        OperationCommand::Heads(args) => cmd_op_heads(ui, command, args),
         OperationCommand::Log(args) => cmd_op_log(ui, command, args),
+        OperationCommand::Merge(args) => cmd_op_merge(ui, command, args),
         OperationCommand::Restore(args) => cmd_op_restore(ui, command, args),
         OperationCommand::Show(args) => cmd_op_show(ui, command, args),
         OperationCommand::Undo(args) => cmd_op_undo(ui, command, args),
@@ -75,34 +101,205 @@ enum UndoWhatToRestore {
     /// The remote-tracking bookmarks. Do not restore these if you'd like to
     /// push after the undo
     RemoteTracking,
+    /// The local bookmarks only, leaving commits and their visibility alone
+    LocalBookmarks,
 }
 
 const DEFAULT_UNDO_WHAT: [UndoWhatToRestore; 2] =
     [UndoWhatToRestore::Repo, UndoWhatToRestore::RemoteTracking];
 
 /// Restore only the portions of the view specified by the `what` argument
+///
+/// If `bookmark_patterns` is non-empty, only the local and remote-tracking
+/// bookmarks matching any of the patterns are restored. The commits, tags,
+/// and working-copy commits are left as they are in that case.
 fn view_with_desired_portions_restored(
     view_being_restored: &jj_lib::op_store::View,
     current_view: &jj_lib::op_store::View,
     what: &[UndoWhatToRestore],
+    bookmark_patterns: &[StringPattern],
 ) -> jj_lib::op_store::View {
-    let repo_source = if what.contains(&UndoWhatToRestore::Repo) {
+    let repo_source = if what.contains(&UndoWhatToRestore::Repo) && bookmark_patterns.is_empty() {
         view_being_restored
     } else {
         current_view
     };
-    let remote_source = if what.contains(&UndoWhatToRestore::RemoteTracking) {
-        view_being_restored
+    let is_selected = |name: &str| {
+        bookmark_patterns.is_empty() || bookmark_patterns.iter().any(|p| p.matches(name))
+    };
+    let local_bookmarks = if what.contains(&UndoWhatToRestore::Repo)
+        || what.contains(&UndoWhatToRestore::LocalBookmarks)
+    {
+        restore_selected_bookmarks(
+            &view_being_restored.local_bookmarks,
+            &current_view.local_bookmarks,
+            is_selected,
+        )
     } else {
-        current_view
+        current_view.local_bookmarks.clone()
     };
+    let remote_views = if !what.contains(&UndoWhatToRestore::RemoteTracking) {
+        current_view.remote_views.clone()
+    } else if bookmark_patterns.is_empty() {
+        view_being_restored.remote_views.clone()
+    } else {
+        let remote_names = itertools::merge(
+            view_being_restored.remote_views.keys(),
+            current_view.remote_views.keys(),
+        )
+        .dedup();
+        remote_names
+            .map(|remote_name| {
+                let restored = view_being_restored.remote_views.get(remote_name);
+                let current = current_view.remote_views.get(remote_name);
+                let bookmarks = restore_selected_bookmarks(
+                    restored.map_or(&BTreeMap::new(), |view| &view.bookmarks),
+                    current.map_or(&BTreeMap::new(), |view| &view.bookmarks),
+                    is_selected,
+                );
+                (remote_name.clone(), RemoteView { bookmarks })
+            })
+            .collect()
+    };
+    let mut head_ids = repo_source.head_ids.clone();
+    if !bookmark_patterns.is_empty() {
+        // Make the commits of the restored bookmarks visible.
+        head_ids.extend(
+            local_bookmarks
+                .iter()
+                .filter(|(name, _)| is_selected(name))
+                .flat_map(|(_, target)| target.added_ids())
+                .cloned(),
+        );
+    }
     jj_lib::op_store::View {
-        head_ids: repo_source.head_ids.clone(),
-        local_bookmarks: repo_source.local_bookmarks.clone(),
+        head_ids,
+        local_bookmarks,
         tags: repo_source.tags.clone(),
-        remote_views: remote_source.remote_views.clone(),
+        remote_views,
         git_refs: current_view.git_refs.clone(),
         git_head: current_view.git_head.clone(),
         wc_commit_ids: repo_source.wc_commit_ids.clone(),
+        deferred_rewrites: repo_source.deferred_rewrites.clone(),
+    }
+}
+
+/// Takes the selected bookmarks from `restored` and the others from `current`.
+fn restore_selected_bookmarks<V: Clone>(
+    restored: &BTreeMap<String, V>,
+    current: &BTreeMap<String, V>,
+    is_selected: impl Fn(&str) -> bool,
+) -> BTreeMap<String, V> {
+    let restored = restored.iter().filter(|(name, _)| is_selected(name));
+    let kept = current.iter().filter(|(name, _)| !is_selected(name));
+    restored
+        .chain(kept)
+        .map(|(name, value)| (name.clone(), value.clone()))
+        .collect()
+}
+
+/// Returns the names of the local and remote-tracking bookmarks which differ
+/// between the two views.
+fn restored_bookmark_names(
+    old_view: &jj_lib::op_store::View,
+    new_view: &jj_lib::op_store::View,
+) -> Vec<String> {
+    let local_names = diff_named_ref_targets(&old_view.local_bookmarks, &new_view.local_bookmarks)
+        .map(|(name, _)| name.clone());
+    let remote_names = itertools::merge(old_view.remote_views.keys(), new_view.remote_views.keys())
+        .dedup()
+        .flat_map(|remote_name| {
+            let old_bookmarks = old_view
+                .remote_views
+                .get(remote_name)
+                .map(|view| &view.bookmarks);
+            let new_bookmarks = new_view
+                .remote_views
+                .get(remote_name)
+                .map(|view| &view.bookmarks);
+            diff_named_remote_refs(
+                old_bookmarks.into_iter().flatten(),
+                new_bookmarks.into_iter().flatten(),
+            )
+            .map(move |(name, _)| format!("{name}@{remote_name}"))
+            .collect_vec()
+        });
+    local_names.chain(remote_names).collect()
+}
+
+/// Loads the current repo with its view replaced by `new_view`, without
+/// writing an operation. The current index must contain all commits the view
+/// refers to.
+fn repo_with_view(
+    repo: &ReadonlyRepo,
+    new_view: &jj_lib::op_store::View,
+) -> Result<Arc<ReadonlyRepo>, CommandError> {
+    let repo_loader = repo.loader();
+    let index = repo_loader
+        .index_store()
+        .get_index_at_op(repo.operation(), repo_loader.store())
+        .map_err(internal_error)?;
+    Ok(repo_loader.create_from(
+        repo.operation().clone(),
+        jj_lib::view::View::new(new_view.clone()),
+        index,
+    ))
+}
+
+/// Prints the changes that replacing the current repo with `new_repo` would
+/// make. Warns if the working-copy commit would become hidden.
+fn print_restore_preview(
+    ui: &Ui,
+    workspace_command: &WorkspaceCommandHelper,
+    new_repo: &Arc<ReadonlyRepo>,
+) -> Result<(), CommandError> {
+    let repo = workspace_command.repo();
+    let workspace_env = workspace_command.env();
+    let settings = workspace_command.settings();
+    let id_prefix_context = workspace_env.new_id_prefix_context();
+    let commit_summary_template = {
+        let language =
+            workspace_env.commit_template_language(new_repo.as_ref(), &id_prefix_context);
+        let text = settings.get_string("templates.commit_summary")?;
+        workspace_env.parse_template(ui, &language, &text, CommitTemplateLanguage::wrap_commit)?
+    };
+    let graph_style = GraphStyle::from_settings(settings)?;
+    let with_content_format = LogContentFormat::new(ui, settings)?;
+    let mut formatter = ui.stdout_formatter();
+    writeln!(formatter, "The following changes would be made:")?;
+    show_op_diff(
+        ui,
+        formatter.as_mut(),
+        new_repo.as_ref(),
+        repo,
+        new_repo,
+        &commit_summary_template,
+        Some(graph_style),
+        &with_content_format,
+        None,
+        None,
+    )?;
+    drop(formatter);
+
+    if let Some(wc_commit_id) = workspace_command.get_wc_commit_id() {
+        let index = new_repo.index();
+        let is_visible = new_repo
+            .view()
+            .heads()
+            .iter()
+            .any(|head_id| index.is_ancestor(wc_commit_id, head_id));
+        if !is_visible {
+            writeln!(
+                ui.warning_default(),
+                "The current working-copy commit would become hidden:"
+            )?;
+            if let Some(mut formatter) = ui.status_formatter() {
+                let wc_commit = repo.store().get_commit(wc_commit_id)?;
+                write!(formatter, "  ")?;
+                workspace_command.write_commit_summary(formatter.as_mut(), &wc_commit)?;
+                writeln!(formatter)?;
+            }
+        }
     }
+    Ok(())
 }
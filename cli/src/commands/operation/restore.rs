@@ -12,13 +12,20 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::io::Write as _;
+
 use clap_complete::ArgValueCandidates;
 use jj_lib::object_id::ObjectId;
+use jj_lib::str_util::StringPattern;
 
+use super::print_restore_preview;
+use super::repo_with_view;
+use super::restored_bookmark_names;
 use super::view_with_desired_portions_restored;
 use super::UndoWhatToRestore;
 use super::DEFAULT_UNDO_WHAT;
 use crate::cli_util::CommandHelper;
+use crate::command_error::user_error;
 use crate::command_error::CommandError;
 use crate::complete;
 use crate::ui::Ui;
@@ -42,6 +49,33 @@ pub struct OperationRestoreArgs {
     /// This option is EXPERIMENTAL.
     #[arg(long, value_enum, default_values_t = DEFAULT_UNDO_WHAT)]
     what: Vec<UndoWhatToRestore>,
+
+    /// Only restore the local and remote-tracking bookmarks matching the given
+    /// name patterns (can be repeated)
+    ///
+    /// Other bookmarks are left as they are. The names of the restored
+    /// bookmarks are printed.
+    ///
+    /// By default, the specified name matches exactly. Use `glob:` prefix to
+    /// select bookmarks by [wildcard pattern].
+    ///
+    /// [wildcard pattern]:
+    ///     https://jj-vcs.github.io/jj/latest/revsets/#string-patterns
+    #[arg(
+        long = "bookmark",
+        value_name = "BOOKMARK",
+        value_parser = StringPattern::parse,
+        add = ArgValueCandidates::new(complete::bookmarks),
+    )]
+    bookmarks: Vec<StringPattern>,
+
+    /// Only show what would change, without creating an operation
+    #[arg(long, conflicts_with = "interactive")]
+    dry_run: bool,
+
+    /// Show what would change, and ask for confirmation before restoring
+    #[arg(long, short)]
+    interactive: bool,
 }
 
 pub fn cmd_op_restore(
@@ -51,12 +85,30 @@ pub fn cmd_op_restore(
 ) -> Result<(), CommandError> {
     let mut workspace_command = command.workspace_helper(ui)?;
     let target_op = workspace_command.resolve_single_op(&args.operation)?;
-    let mut tx = workspace_command.start_transaction();
     let new_view = view_with_desired_portions_restored(
         target_op.view()?.store_view(),
-        tx.base_repo().view().store_view(),
+        workspace_command.repo().view().store_view(),
         &args.what,
+        &args.bookmarks,
     );
+    let restored_bookmarks =
+        restored_bookmark_names(workspace_command.repo().view().store_view(), &new_view);
+    if !args.bookmarks.is_empty() && restored_bookmarks.is_empty() {
+        writeln!(ui.warning_default(), "No matching bookmarks to restore")?;
+        return Ok(());
+    }
+    if args.dry_run || args.interactive {
+        let new_repo = repo_with_view(workspace_command.repo(), &new_view)?;
+        print_restore_preview(ui, &workspace_command, &new_repo)?;
+        if args.dry_run {
+            writeln!(ui.status(), "Dry-run requested, not restoring.")?;
+            return Ok(());
+        }
+        if !ui.prompt_yes_no("Restore to this operation?", Some(false))? {
+            return Err(user_error("Restore aborted"));
+        }
+    }
+    let mut tx = workspace_command.start_transaction();
     tx.repo_mut().set_view(new_view);
     if let Some(mut formatter) = ui.status_formatter() {
         write!(formatter, "Restored to operation: ")?;
@@ -64,6 +116,13 @@ pub fn cmd_op_restore(
         template.format(&target_op, formatter.as_mut())?;
         writeln!(formatter)?;
     }
+    if !args.bookmarks.is_empty() {
+        writeln!(
+            ui.status(),
+            "Restored bookmarks: {}",
+            restored_bookmarks.join(", ")
+        )?;
+    }
     tx.finish(ui, format!("restore to operation {}", target_op.id().hex()))?;
 
     Ok(())
@@ -36,6 +36,18 @@ pub struct OperationShowArgs {
     /// Don't show the graph, show a flat list of modified changes
     #[arg(long)]
     no_graph: bool,
+    /// Render the operation using the given template
+    ///
+    /// You can specify arbitrary [template expressions] using the
+    /// [built-in keywords].
+    ///
+    /// [template expression]:
+    ///     https://jj-vcs.github.io/jj/latest/templates/
+    ///
+    /// [built-in keywords]:
+    ///     https://jj-vcs.github.io/jj/latest/templates/#operation-keywords
+    #[arg(long, short = 'T', add = ArgValueCandidates::new(complete::template_aliases))]
+    template: Option<String>,
     /// Show patch of modifications to changes
     ///
     /// If the previous version has different parents, it will be temporarily
@@ -85,9 +97,11 @@ pub fn cmd_op_show(
         })
     };
 
-    // TODO: Should we make this customizable via clap arg?
     let template = {
-        let text = settings.get_string("templates.op_log")?;
+        let text = match &args.template {
+            Some(value) => value.to_owned(),
+            None => settings.get_string("templates.op_log")?,
+        };
         workspace_command
             .parse_operation_template(ui, &text)?
             .labeled("operation")
@@ -107,5 +121,6 @@ pub fn cmd_op_show(
         (!args.no_graph).then_some(graph_style),
         &with_content_format,
         diff_renderer.as_ref(),
+        None,
     )
 }
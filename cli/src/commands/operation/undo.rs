@@ -12,13 +12,19 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::io::Write as _;
+
 use clap_complete::ArgValueCandidates;
 use itertools::Itertools;
 use jj_lib::object_id::ObjectId;
 use jj_lib::op_store::OpStoreError;
 use jj_lib::operation::Operation;
 use jj_lib::repo::Repo;
+use jj_lib::str_util::StringPattern;
 
+use super::print_restore_preview;
+use super::repo_with_view;
+use super::restored_bookmark_names;
 use super::view_with_desired_portions_restored;
 use super::UndoWhatToRestore;
 use super::DEFAULT_UNDO_WHAT;
@@ -45,6 +51,41 @@ pub struct OperationUndoArgs {
     /// This option is EXPERIMENTAL.
     #[arg(long, value_enum, default_values_t = DEFAULT_UNDO_WHAT)]
     what: Vec<UndoWhatToRestore>,
+
+    /// Only restore the local and remote-tracking bookmarks matching the given
+    /// name patterns (can be repeated)
+    ///
+    /// Other bookmarks are left as they are. The names of the restored
+    /// bookmarks are printed.
+    ///
+    /// By default, the specified name matches exactly. Use `glob:` prefix to
+    /// select bookmarks by [wildcard pattern].
+    ///
+    /// [wildcard pattern]:
+    ///     https://jj-vcs.github.io/jj/latest/revsets/#string-patterns
+    #[arg(
+        long = "bookmark",
+        value_name = "BOOKMARK",
+        value_parser = StringPattern::parse,
+        add = ArgValueCandidates::new(complete::bookmarks),
+    )]
+    bookmarks: Vec<StringPattern>,
+
+    /// Only show what would change, without creating an operation
+    #[arg(long, conflicts_with = "interactive")]
+    dry_run: bool,
+
+    /// Show what would change, and ask for confirmation before undoing
+    #[arg(long, short)]
+    interactive: bool,
+
+    /// Don't ask for confirmation when undoing an 'undo' operation
+    ///
+    /// Running `jj undo` twice redoes the first undo instead of going further
+    /// back. When that happens on a terminal, `jj undo` asks for confirmation
+    /// unless this flag is given.
+    #[arg(long, short)]
+    yes: bool,
 }
 
 fn is_undo(op: &Operation, parent_op: &Operation) -> Result<bool, OpStoreError> {
@@ -70,17 +111,74 @@ pub fn cmd_op_undo(
     if parent_ops.next().is_some() {
         return Err(user_error("Cannot undo a merge operation"));
     }
+    let undoing_undo = args.operation == "@" && is_undo(&bad_op, &parent_op)?;
+    if undoing_undo && !(args.yes || args.dry_run || args.interactive) && Ui::can_prompt() {
+        writeln!(
+            ui.warning_default(),
+            "The operation to undo is itself an 'undo' operation. Undoing it will redo the \
+             previously undone operation."
+        )?;
+        writeln!(
+            ui.hint_default(),
+            "To go further back, use `jj op log` to see past states, and `jj op restore` to \
+             restore one of these states."
+        )?;
+        if !ui.prompt_yes_no("Undo the 'undo' operation?", Some(false))? {
+            return Err(user_error("Undo aborted"));
+        }
+    }
 
     let mut tx = workspace_command.start_transaction();
     let repo_loader = tx.base_repo().loader();
     let bad_repo = repo_loader.load_at(&bad_op)?;
     let parent_repo = repo_loader.load_at(&parent_op)?;
-    tx.repo_mut().merge(&bad_repo, &parent_repo)?;
-    let new_view = view_with_desired_portions_restored(
-        tx.repo().view().store_view(),
-        tx.base_repo().view().store_view(),
-        &args.what,
-    );
+    let new_view = if args.bookmarks.is_empty() {
+        tx.repo_mut().merge(&bad_repo, &parent_repo)?;
+        view_with_desired_portions_restored(
+            tx.repo().view().store_view(),
+            tx.base_repo().view().store_view(),
+            &args.what,
+            &args.bookmarks,
+        )
+    } else {
+        // Merge the inverse in a separate transaction. Otherwise, the commits
+        // rewritten by the operation would be recorded as rewritten back, and
+        // their descendants would be rebased.
+        let mut merge_tx = tx.base_repo().start_transaction();
+        merge_tx.repo_mut().merge(&bad_repo, &parent_repo)?;
+        view_with_desired_portions_restored(
+            merge_tx.repo().view().store_view(),
+            tx.base_repo().view().store_view(),
+            &args.what,
+            &args.bookmarks,
+        )
+    };
+    let restored_bookmarks = restored_bookmark_names(tx.base_repo().view().store_view(), &new_view);
+    if !args.bookmarks.is_empty() && restored_bookmarks.is_empty() {
+        writeln!(ui.warning_default(), "No matching bookmarks to restore")?;
+        return Ok(());
+    }
+    if args.dry_run || args.interactive {
+        let new_repo = if args.bookmarks.is_empty() {
+            // The commits removed by the merge stay in the view until the
+            // descendants are rebased, so preview the rebased result.
+            let mut preview_tx = tx.base_repo().start_transaction();
+            preview_tx.repo_mut().merge(&bad_repo, &parent_repo)?;
+            preview_tx.repo_mut().set_view(new_view.clone());
+            preview_tx.repo_mut().rebase_descendants()?;
+            preview_tx.write("preview undo").leave_unpublished()
+        } else {
+            repo_with_view(tx.base_repo(), &new_view)?
+        };
+        print_restore_preview(ui, tx.base_workspace_helper(), &new_repo)?;
+        if args.dry_run {
+            writeln!(ui.status(), "Dry-run requested, not undoing.")?;
+            return Ok(());
+        }
+        if !ui.prompt_yes_no("Undo this operation?", Some(false))? {
+            return Err(user_error("Undo aborted"));
+        }
+    }
     tx.repo_mut().set_view(new_view);
     if let Some(mut formatter) = ui.status_formatter() {
         write!(formatter, "Undid operation: ")?;
@@ -88,9 +186,16 @@ pub fn cmd_op_undo(
         template.format(&bad_op, formatter.as_mut())?;
         writeln!(formatter)?;
     }
+    if !args.bookmarks.is_empty() {
+        writeln!(
+            ui.status(),
+            "Restored bookmarks: {}",
+            restored_bookmarks.join(", ")
+        )?;
+    }
     tx.finish(ui, format!("undo operation {}", bad_op.id().hex()))?;
 
-    if args.operation == "@" && is_undo(&bad_op, &parent_op)? {
+    if undoing_undo {
         writeln!(
             ui.hint_default(),
             "This action reverted an 'undo' operation. The repository is now in the same state as \
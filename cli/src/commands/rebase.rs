@@ -12,19 +12,31 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
+use std::collections::HashSet;
 use std::io::Write;
 use std::rc::Rc;
 use std::sync::Arc;
 
 use clap::ArgGroup;
 use clap_complete::ArgValueCandidates;
+use futures::StreamExt as _;
+use futures::TryStreamExt as _;
 use itertools::Itertools;
+use jj_lib::backend::BackendResult;
 use jj_lib::backend::CommitId;
 use jj_lib::commit::Commit;
 use jj_lib::commit::CommitIteratorExt;
+use jj_lib::git::REMOTE_NAME_FOR_LOCAL_GIT_REPO;
+use jj_lib::matchers::EverythingMatcher;
+use jj_lib::matchers::FilesMatcher;
+use jj_lib::matchers::Matcher;
+use jj_lib::merge::MergedTreeValue;
+use jj_lib::merged_tree::TreeDiffEntry;
 use jj_lib::object_id::ObjectId;
 use jj_lib::repo::ReadonlyRepo;
 use jj_lib::repo::Repo;
+use jj_lib::repo_path::RepoPathBuf;
 use jj_lib::revset::ResolvedRevsetExpression;
 use jj_lib::revset::RevsetExpression;
 use jj_lib::revset::RevsetIteratorExt;
@@ -33,15 +45,19 @@ use jj_lib::rewrite::EmptyBehaviour;
 use jj_lib::rewrite::MoveCommitsStats;
 use jj_lib::rewrite::MoveCommitsTarget;
 use jj_lib::rewrite::RebaseOptions;
+use jj_lib::rewrite::RebasedCommit;
 use jj_lib::rewrite::RewriteRefsOptions;
+use pollster::FutureExt as _;
 use tracing::instrument;
 
 use crate::cli_util::short_commit_hash;
 use crate::cli_util::CommandHelper;
 use crate::cli_util::RevisionArg;
 use crate::cli_util::WorkspaceCommandHelper;
+use crate::cli_util::WorkspaceCommandTransaction;
 use crate::command_error::cli_error;
 use crate::command_error::user_error;
+use crate::command_error::user_error_with_hint;
 use crate::command_error::CommandError;
 use crate::complete;
 use crate::ui::Ui;
@@ -70,6 +86,12 @@ use crate::ui::Ui;
 ///   parents and to rebase the targets and their descendants onto the rebased
 ///   revisions
 ///
+/// If none of these is specified, `--branch/-b` (including the default `-b @`)
+/// rebases the branch onto the immutable tracked remote bookmark the branch was
+/// forked from. For example, if `main` is tracking `main@origin`, then
+/// `jj rebase` rebases the current branch onto `main@origin` after it was
+/// updated by `jj git fetch`.
+///
 /// See the sections below for details about the different ways of specifying
 /// which revisions to rebase where.
 ///
@@ -295,6 +317,7 @@ pub(crate) struct RebaseArgs {
         long,
         short,
         value_name = "REVSETS",
+        requires = "RebaseDestinationArgs",
         add = ArgValueCandidates::new(complete::mutable_revisions)
     )]
     source: Vec<RevisionArg>,
@@ -309,6 +332,7 @@ pub(crate) struct RebaseArgs {
         long,
         short,
         value_name = "REVSETS",
+        requires = "RebaseDestinationArgs",
         add = ArgValueCandidates::new(complete::mutable_revisions)
     )]
     revisions: Vec<RevisionArg>,
@@ -323,13 +347,41 @@ pub(crate) struct RebaseArgs {
     /// If true, when rebasing would produce an empty commit, the commit is
     /// abandoned. It will not be abandoned if it was already empty before the
     /// rebase. Will never skip merge commits with multiple non-empty
-    /// parents.
+    /// parents, nor working-copy commits.
     #[arg(long)]
     skip_emptied: bool,
+
+    /// Abandon revisions whose changes already exist in the destination
+    ///
+    /// A revision is considered a duplicate if it makes exactly the same
+    /// changes as one of the destination's ancestors which isn't an ancestor
+    /// of the revisions being rebased, for example because it was already
+    /// landed upstream. Only the resulting file contents are compared, so a
+    /// change which was landed with modifications or with different context
+    /// isn't detected.
+    #[arg(long)]
+    skip_duplicates: bool,
+
+    /// Rebase the descendants of all commits rewritten while rebasing was
+    /// deferred
+    ///
+    /// See the `rewrite.defer-descendant-rebase` setting.
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "branch",
+            "source",
+            "revisions",
+            "destination",
+            "insert_after",
+            "insert_before",
+            "skip_duplicates",
+        ],
+    )]
+    resolve_deferred: bool,
 }
 
 #[derive(clap::Args, Clone, Debug)]
-#[group(required = true)]
 pub struct RebaseDestinationArgs {
     /// The revision(s) to rebase onto (can be repeated to create a merge
     /// commit)
@@ -364,6 +416,12 @@ pub struct RebaseDestinationArgs {
     insert_before: Option<Vec<RevisionArg>>,
 }
 
+impl RebaseDestinationArgs {
+    fn is_empty(&self) -> bool {
+        self.destination.is_none() && self.insert_after.is_none() && self.insert_before.is_none()
+    }
+}
+
 #[instrument(skip_all)]
 pub(crate) fn cmd_rebase(
     ui: &mut Ui,
@@ -387,13 +445,16 @@ pub(crate) fn cmd_rebase(
         simplify_ancestor_merge: false,
     };
     let mut workspace_command = command.workspace_helper(ui)?;
-    if !args.revisions.is_empty() {
+    if args.resolve_deferred {
+        resolve_deferred_rebases(ui, &mut workspace_command, &rebase_options)?;
+    } else if !args.revisions.is_empty() {
         rebase_revisions(
             ui,
             &mut workspace_command,
             &args.revisions,
             &args.destination,
             &rebase_options,
+            args.skip_duplicates,
         )?;
     } else if !args.source.is_empty() {
         rebase_source(
@@ -402,6 +463,7 @@ pub(crate) fn cmd_rebase(
             &args.source,
             &args.destination,
             &rebase_options,
+            args.skip_duplicates,
         )?;
     } else {
         rebase_branch(
@@ -410,6 +472,7 @@ pub(crate) fn cmd_rebase(
             &args.branch,
             &args.destination,
             &rebase_options,
+            args.skip_duplicates,
         )?;
     }
     Ok(())
@@ -421,6 +484,7 @@ fn rebase_revisions(
     revisions: &[RevisionArg],
     rebase_destination: &RebaseDestinationArgs,
     rebase_options: &RebaseOptions,
+    skip_duplicates: bool,
 ) -> Result<(), CommandError> {
     let target_commits: Vec<_> = workspace_command
         .parse_union_revsets(ui, revisions)?
@@ -447,6 +511,7 @@ fn rebase_revisions(
         &new_children,
         target_commits,
         rebase_options,
+        skip_duplicates,
     )
 }
 
@@ -456,6 +521,7 @@ fn rebase_source(
     source: &[RevisionArg],
     rebase_destination: &RebaseDestinationArgs,
     rebase_options: &RebaseOptions,
+    skip_duplicates: bool,
 ) -> Result<(), CommandError> {
     let source_commits = workspace_command
         .resolve_some_revsets_default_single(ui, source)?
@@ -478,15 +544,37 @@ fn rebase_source(
         &new_children,
         source_commits,
         rebase_options,
+        skip_duplicates,
     )
 }
 
+fn resolve_deferred_rebases(
+    ui: &mut Ui,
+    workspace_command: &mut WorkspaceCommandHelper,
+    rebase_options: &RebaseOptions,
+) -> Result<(), CommandError> {
+    let mut tx = workspace_command.start_transaction();
+    tx.repo_mut().set_defer_descendant_rebase(false);
+    let mut stats = MoveCommitsStats::default();
+    tx.repo_mut().rebase_descendants_with_options(
+        rebase_options,
+        |_old_commit, rebased_commit| match rebased_commit {
+            RebasedCommit::Rewritten(_) => stats.num_rebased_descendants += 1,
+            RebasedCommit::Abandoned { .. } => stats.num_abandoned += 1,
+        },
+    )?;
+    print_move_commits_stats(ui, &stats)?;
+    tx.finish(ui, "resolve deferred rebases")?;
+    Ok(())
+}
+
 fn rebase_branch(
     ui: &mut Ui,
     workspace_command: &mut WorkspaceCommandHelper,
     branch: &[RevisionArg],
     rebase_destination: &RebaseDestinationArgs,
     rebase_options: &RebaseOptions,
+    skip_duplicates: bool,
 ) -> Result<(), CommandError> {
     let branch_commits: Vec<_> = if branch.is_empty() {
         vec![workspace_command.resolve_single_rev(ui, &RevisionArg::AT)?]
@@ -498,8 +586,12 @@ fn rebase_branch(
             .collect_vec()
     };
 
-    let (new_parents, new_children) =
-        compute_rebase_destination(ui, workspace_command, rebase_destination)?;
+    let (new_parents, new_children) = if rebase_destination.is_empty() {
+        let destination = default_branch_destination(ui, workspace_command, &branch_commits)?;
+        (vec![destination], vec![])
+    } else {
+        compute_rebase_destination(ui, workspace_command, rebase_destination)?
+    };
     let new_parent_ids = new_parents.iter().ids().cloned().collect_vec();
     let branch_commit_ids = branch_commits.iter().ids().cloned().collect_vec();
     let roots_expression = RevsetExpression::commits(new_parent_ids.clone())
@@ -512,7 +604,9 @@ fn rebase_branch(
         .commits(workspace_command.repo().store())
         .try_collect()?;
     workspace_command.check_rewritable(root_commits.iter().ids())?;
-    if rebase_destination.destination.is_some() && new_children.is_empty() {
+    if (rebase_destination.destination.is_some() || rebase_destination.is_empty())
+        && new_children.is_empty()
+    {
         for commit in &root_commits {
             check_rebase_destinations(workspace_command.repo(), &new_parents, commit)?;
         }
@@ -525,9 +619,91 @@ fn rebase_branch(
         &new_children,
         root_commits,
         rebase_options,
+        skip_duplicates,
     )
 }
 
+/// Finds the destination of `jj rebase -b` when no destination is given: the
+/// immutable tracked remote bookmarks descending from the commits the branch
+/// was forked from.
+fn default_branch_destination(
+    ui: &Ui,
+    workspace_command: &WorkspaceCommandHelper,
+    branch_commits: &[Commit],
+) -> Result<Commit, CommandError> {
+    let repo = workspace_command.repo();
+    let tracked_remote_targets = repo
+        .view()
+        .bookmarks()
+        .flat_map(|(name, targets)| {
+            targets
+                .remote_refs
+                .into_iter()
+                .filter(|(remote_name, remote_ref)| {
+                    *remote_name != REMOTE_NAME_FOR_LOCAL_GIT_REPO && remote_ref.is_tracking()
+                })
+                .filter_map(move |(remote_name, remote_ref)| {
+                    let id = remote_ref.target.as_normal()?;
+                    Some((format!("{name}@{remote_name}"), id.clone()))
+                })
+        })
+        .collect_vec();
+    // The local bookmarks may have been moved by `jj git fetch`, so the fork
+    // point is found from the remote bookmarks. Mutable remote bookmarks, such
+    // as a pushed bookmark of the branch itself, aren't candidates.
+    let immutable = workspace_command.env().immutable_expression();
+    let targets_expression = RevsetExpression::commits(
+        tracked_remote_targets
+            .iter()
+            .map(|(_, id)| id.clone())
+            .collect(),
+    );
+    let fork_points = RevsetExpression::commits(branch_commits.iter().ids().cloned().collect())
+        .ancestors()
+        .intersection(&targets_expression.ancestors())
+        .intersection(&immutable)
+        .heads();
+    let candidate_ids: HashSet<CommitId> = workspace_command
+        .attach_revset_evaluator(
+            targets_expression
+                .intersection(&immutable)
+                .intersection(&fork_points.descendants()),
+        )
+        .evaluate_to_commit_ids()?
+        .try_collect()?;
+
+    let (remote_names, destination_ids): (Vec<_>, Vec<_>) = tracked_remote_targets
+        .into_iter()
+        .filter(|(_, id)| candidate_ids.contains(id))
+        .unzip();
+    let hint = "Use `--destination` to specify where to rebase the revisions.";
+    let destination_id = match destination_ids.iter().unique().collect_vec()[..] {
+        [] => {
+            return Err(user_error_with_hint(
+                "No immutable tracked remote bookmark was found upstream of the branch",
+                hint,
+            ));
+        }
+        [id] => id.clone(),
+        _ => {
+            return Err(user_error_with_hint(
+                format!(
+                    "Cannot choose a destination between the tracked remote bookmarks {}",
+                    remote_names.join(", ")
+                ),
+                hint,
+            ));
+        }
+    };
+    let destination = repo.store().get_commit(&destination_id)?;
+    if let Some(mut formatter) = ui.status_formatter() {
+        write!(formatter, "Rebasing onto {}: ", remote_names.join(", "))?;
+        workspace_command.write_commit_summary(formatter.as_mut(), &destination)?;
+        writeln!(formatter)?;
+    }
+    Ok(destination)
+}
+
 fn rebase_descendants_transaction(
     ui: &mut Ui,
     workspace_command: &mut WorkspaceCommandHelper,
@@ -535,11 +711,25 @@ fn rebase_descendants_transaction(
     new_children: &[Commit],
     target_roots: Vec<Commit>,
     rebase_options: &RebaseOptions,
+    skip_duplicates: bool,
 ) -> Result<(), CommandError> {
     if target_roots.is_empty() {
         writeln!(ui.status(), "Nothing changed.")?;
         return Ok(());
     }
+    let duplicates = if skip_duplicates {
+        let repo = workspace_command.repo().as_ref();
+        let target_commits: Vec<_> =
+            RevsetExpression::commits(target_roots.iter().ids().cloned().collect())
+                .descendants()
+                .evaluate(repo)?
+                .iter()
+                .commits(repo.store())
+                .try_collect()?;
+        find_duplicate_commits(repo, &target_commits, new_parent_ids)?
+    } else {
+        vec![]
+    };
 
     let mut tx = workspace_command.start_transaction();
     let tx_description = if target_roots.len() == 1 {
@@ -562,6 +752,7 @@ fn rebase_descendants_transaction(
         rebase_options,
     )?;
     print_move_commits_stats(ui, &stats)?;
+    abandon_rebased_duplicates(ui, &mut tx, &duplicates, &stats.rebased_commits)?;
     tx.finish(ui, tx_description)
 }
 
@@ -641,11 +832,21 @@ fn rebase_revisions_transaction(
     new_children: &[Commit],
     target_commits: Vec<Commit>,
     rebase_options: &RebaseOptions,
+    skip_duplicates: bool,
 ) -> Result<(), CommandError> {
     if target_commits.is_empty() {
         writeln!(ui.status(), "Nothing changed.")?;
         return Ok(());
     }
+    let duplicates = if skip_duplicates {
+        find_duplicate_commits(
+            workspace_command.repo().as_ref(),
+            &target_commits,
+            new_parent_ids,
+        )?
+    } else {
+        vec![]
+    };
 
     let mut tx = workspace_command.start_transaction();
     let tx_description = if target_commits.len() == 1 {
@@ -666,9 +867,135 @@ fn rebase_revisions_transaction(
         rebase_options,
     )?;
     print_move_commits_stats(ui, &stats)?;
+    abandon_rebased_duplicates(ui, &mut tx, &duplicates, &stats.rebased_commits)?;
     tx.finish(ui, tx_description)
 }
 
+/// The maximum number of commits in the destination which are compared with
+/// the rebased commits by `--skip-duplicates`.
+const MAX_DUPLICATE_CANDIDATES: usize = 1000;
+
+/// Returns the `target_commits` which make the same changes as a commit in
+/// `target_commits..new_parents`.
+///
+/// Only the most recent [`MAX_DUPLICATE_CANDIDATES`] commits in the range are
+/// compared, and only at the paths changed by the `target_commits`.
+fn find_duplicate_commits(
+    repo: &dyn Repo,
+    target_commits: &[Commit],
+    new_parent_ids: &[CommitId],
+) -> Result<Vec<Commit>, CommandError> {
+    let mut target_changes = vec![];
+    for commit in target_commits {
+        if let Some(changes) = commit_changes(repo, commit, &EverythingMatcher)? {
+            target_changes.push((commit, changes));
+        }
+    }
+    if target_changes.is_empty() {
+        return Ok(vec![]);
+    }
+    let matcher = FilesMatcher::new(
+        target_changes
+            .iter()
+            .flat_map(|(_, changes)| changes.iter().map(|(path, _, _)| path)),
+    );
+    let candidates: Vec<_> =
+        RevsetExpression::commits(target_commits.iter().ids().cloned().collect())
+            .range(&RevsetExpression::commits(new_parent_ids.to_vec()))
+            .evaluate(repo)?
+            .iter()
+            .commits(repo.store())
+            .take(MAX_DUPLICATE_CANDIDATES)
+            .try_collect()?;
+    let mut candidate_changes: HashMap<_, Vec<&Commit>> = HashMap::new();
+    for commit in &candidates {
+        if let Some(changes) = commit_changes(repo, commit, &matcher)? {
+            candidate_changes.entry(changes).or_default().push(commit);
+        }
+    }
+    let mut duplicates = vec![];
+    for (commit, changes) in target_changes {
+        let Some(matched) = candidate_changes.get(&changes) else {
+            continue;
+        };
+        // The candidate may also change paths the commit doesn't touch.
+        for candidate in matched {
+            if commit_changes(repo, candidate, &EverythingMatcher)?.as_ref() == Some(&changes) {
+                duplicates.push(commit.clone());
+                break;
+            }
+        }
+    }
+    Ok(duplicates)
+}
+
+/// Returns the `(path, before, after)` values changed by the commit at the
+/// paths matching `matcher`, or `None` if the commit is a merge or makes no
+/// such changes.
+fn commit_changes(
+    repo: &dyn Repo,
+    commit: &Commit,
+    matcher: &dyn Matcher,
+) -> BackendResult<Option<Vec<(RepoPathBuf, MergedTreeValue, MergedTreeValue)>>> {
+    if commit.parent_ids().len() != 1 {
+        return Ok(None);
+    }
+    let changes: Vec<_> = commit
+        .parent_tree(repo)?
+        .diff_stream(&commit.tree()?, matcher)
+        .map(|TreeDiffEntry { path, values }| values.map(|(before, after)| (path, before, after)))
+        .try_collect()
+        .block_on()?;
+    Ok((!changes.is_empty()).then_some(changes))
+}
+
+/// Abandons the rebased versions of the `duplicates`, moving their descendants
+/// onto their parents.
+///
+/// `rebased_commits` maps the ids of the commits rebased by the transaction
+/// to the results. Duplicates which were already in place are abandoned as
+/// they are.
+fn abandon_rebased_duplicates(
+    ui: &Ui,
+    tx: &mut WorkspaceCommandTransaction,
+    duplicates: &[Commit],
+    rebased_commits: &HashMap<CommitId, RebasedCommit>,
+) -> Result<(), CommandError> {
+    let mut abandoned = vec![];
+    for commit in duplicates {
+        let new_commit = match rebased_commits.get(commit.id()) {
+            Some(RebasedCommit::Rewritten(new_commit)) => new_commit,
+            // Already abandoned because it became empty
+            Some(RebasedCommit::Abandoned { .. }) => continue,
+            None => commit,
+        };
+        tx.repo_mut().record_abandoned_commit(new_commit);
+        abandoned.push(commit);
+    }
+    if abandoned.is_empty() {
+        return Ok(());
+    }
+    tx.repo_mut().rebase_descendants()?;
+    if let Some(mut formatter) = ui.status_formatter() {
+        writeln!(
+            formatter,
+            "Skipped {} {} whose changes are already in the destination:",
+            abandoned.len(),
+            if abandoned.len() == 1 {
+                "commit"
+            } else {
+                "commits"
+            },
+        )?;
+        for commit in abandoned {
+            write!(formatter, "  ")?;
+            tx.write_commit_summary(formatter.as_mut(), commit)?;
+            writeln!(formatter)?;
+        }
+    }
+    Ok(())
+}
+
 /// Ensure that there is no possible cycle between the potential children and
 /// parents of rebased commits.
 fn ensure_no_commit_loop(
@@ -719,6 +1046,7 @@ fn print_move_commits_stats(ui: &Ui, stats: &MoveCommitsStats) -> std::io::Resul
         num_rebased_descendants,
         num_skipped_rebases,
         num_abandoned,
+        rebased_commits: _,
     } = stats;
     if num_skipped_rebases > 0 {
         writeln!(
@@ -0,0 +1,198 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::io::Write as _;
+
+use clap_complete::ArgValueCandidates;
+use itertools::Itertools as _;
+use jj_lib::commit::Commit;
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::op_store::AbandonReason;
+use jj_lib::revset::RevsetExpression;
+use jj_lib::rewrite::CommitRewriter;
+use jj_lib::rewrite::RebaseOptions;
+use jj_lib::rewrite::RewriteRefsOptions;
+use tracing::instrument;
+
+use crate::cli_util::short_change_hash;
+use crate::cli_util::short_commit_hash;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::user_error;
+use crate::command_error::user_error_with_hint;
+use crate::command_error::CommandError;
+use crate::complete;
+use crate::ui::Ui;
+
+/// Reorder a linear stack of revisions in the text editor
+///
+/// Opens the text editor with one line per revision in the stack starting at
+/// `--source`, oldest first. Each line starts with the change id and commit id
+/// of the revision, so divergent revisions can be told apart. Reorder the lines
+/// to reorder the revisions, or delete a line to abandon the revision. The
+/// stack is rewritten in a single operation, so `jj undo` restores the original
+/// order.
+///
+/// Revisions which end up conflicted in the new order are left conflicted.
+///
+/// Only linear stacks are supported: the source and each of its descendants
+/// must have a single parent and at most one child.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct ReorderArgs {
+    /// The first revision of the stack to reorder, which is reordered along
+    /// with all its descendants
+    #[arg(
+        long,
+        short,
+        value_name = "REVSET",
+        add = ArgValueCandidates::new(complete::mutable_revisions)
+    )]
+    source: RevisionArg,
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_reorder(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &ReorderArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let source = workspace_command.resolve_single_rev(ui, &args.source)?;
+    let base_commit_ids = source.parent_ids().to_vec();
+    if base_commit_ids.len() != 1 {
+        return Err(user_error(format!(
+            "Cannot reorder a stack starting at merge commit {}",
+            short_change_hash(source.change_id())
+        )));
+    }
+    let stack_expression = RevsetExpression::commits(vec![source.id().clone()]).descendants();
+    let mut stack: Vec<Commit> = workspace_command
+        .attach_revset_evaluator(stack_expression)
+        .evaluate_to_commits()?
+        .try_collect()?;
+    // Order the stack oldest first.
+    stack.reverse();
+    for (parent, child) in stack.iter().tuple_windows() {
+        if child.parent_ids() != [parent.id().clone()] {
+            return Err(user_error_with_hint(
+                "Cannot reorder a non-linear stack of revisions",
+                "Each revision in the stack must have a single parent and at most one child.",
+            ));
+        }
+    }
+    workspace_command.check_rewritable(stack.iter().map(|commit| commit.id()))?;
+
+    // Key the lines by both ids since divergent revisions share a change id.
+    let mut commits_by_hash: HashMap<String, &Commit> = HashMap::new();
+    let mut template = String::new();
+    for commit in &stack {
+        let hash = format!(
+            "{} {}",
+            short_change_hash(commit.change_id()),
+            short_commit_hash(commit.id())
+        );
+        let first_line = commit
+            .description()
+            .lines()
+            .next()
+            .unwrap_or("(no description set)");
+        template.push_str(&format!("{hash} {first_line}\n"));
+        commits_by_hash.insert(hash, commit);
+    }
+    template.push_str(
+        "\nJJ: Reorder the lines to reorder the revisions. The first line is the bottom of the \
+         stack.\nJJ: Delete a line to abandon the revision.\nJJ: Lines starting with \"JJ:\" \
+         (like this one) will be removed.\n",
+    );
+    let text_editor = workspace_command.text_editor()?;
+    let edited = text_editor
+        .edit_str(template, Some(".jjreorder"))
+        .map_err(|err| err.with_name("reorder list"))?;
+
+    let mut new_order: Vec<&Commit> = vec![];
+    let mut seen = HashSet::new();
+    for line in edited.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("JJ:") {
+            continue;
+        }
+        let hash = line.split_whitespace().take(2).join(" ");
+        let Some(&commit) = commits_by_hash.get(&hash) else {
+            return Err(user_error(format!(
+                "Revision {hash} is not in the stack being reordered"
+            )));
+        };
+        if !seen.insert(commit.id()) {
+            return Err(user_error(format!(
+                "Revision {hash} is listed more than once"
+            )));
+        }
+        new_order.push(commit);
+    }
+    let abandoned = stack
+        .iter()
+        .filter(|commit| !seen.contains(commit.id()))
+        .collect_vec();
+
+    let mut tx = workspace_command.start_transaction();
+    let mut new_parent_id = base_commit_ids[0].clone();
+    let mut num_reordered = 0;
+    for commit in &new_order {
+        let rewriter = CommitRewriter::new(
+            tx.repo_mut(),
+            (*commit).clone(),
+            vec![new_parent_id.clone()],
+        );
+        if rewriter.parents_changed() {
+            new_parent_id = rewriter.rebase()?.write()?.id().clone();
+            num_reordered += 1;
+        } else {
+            new_parent_id = commit.id().clone();
+        }
+    }
+    for commit in &abandoned {
+        tx.repo_mut().record_abandoned_commit(commit);
+        tx.repo_mut()
+            .set_abandon_reason(commit.id().clone(), AbandonReason::Explicit);
+    }
+    // Like `jj abandon`, delete the bookmarks pointing to the dropped revisions.
+    let options = RebaseOptions {
+        rewrite_refs: RewriteRefsOptions {
+            delete_abandoned_bookmarks: true,
+        },
+        ..Default::default()
+    };
+    tx.repo_mut()
+        .rebase_descendants_with_options(&options, |_, _| {})?;
+
+    if let Some(mut formatter) = ui.status_formatter() {
+        if num_reordered > 0 {
+            writeln!(formatter, "Rebased {num_reordered} commits")?;
+        }
+        if !abandoned.is_empty() {
+            writeln!(formatter, "Abandoned {} commits", abandoned.len())?;
+        }
+    }
+    tx.finish(
+        ui,
+        format!(
+            "reorder {} commits starting at {}",
+            stack.len(),
+            source.id().hex()
+        ),
+    )?;
+    Ok(())
+}
@@ -12,18 +12,44 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::env;
+use std::io::Write as _;
+use std::path::Path;
+use std::path::PathBuf;
+
 use clap_complete::ArgValueCandidates;
 use clap_complete::ArgValueCompleter;
 use itertools::Itertools;
+use jj_lib::backend::BackendResult;
+use jj_lib::config::ConfigGetResultExt as _;
+use jj_lib::config::ConfigNamePathBuf;
+use jj_lib::fileset;
+use jj_lib::fileset::FilesetAliasesMap;
+use jj_lib::fileset::FilesetDiagnostics;
+use jj_lib::fileset::FilesetExpression;
+use jj_lib::matchers::Matcher;
+use jj_lib::merge::MergedTreeValue;
 use jj_lib::object_id::ObjectId;
+use jj_lib::repo::Repo as _;
+use jj_lib::repo_path::RepoPathBuf;
+use jj_lib::repo_path::RepoPathUiConverter;
+use jj_lib::settings::UserSettings;
 use tracing::instrument;
 
 use crate::cli_util::print_conflicted_paths;
 use crate::cli_util::CommandHelper;
 use crate::cli_util::RevisionArg;
+use crate::cli_util::WorkspaceCommandHelper;
 use crate::command_error::cli_error;
+use crate::command_error::print_parse_diagnostics;
 use crate::command_error::CommandError;
+use crate::commit_templater::conflict_involves_deletion;
+use crate::commit_templater::conflict_involves_modes;
+use crate::commit_templater::CommitTemplateLanguage;
+use crate::commit_templater::TreeEntry;
 use crate::complete;
+use crate::merge_tools::get_external_tool_config;
+use crate::merge_tools::MergeToolPartialResolutionError;
 use crate::ui::Ui;
 
 /// Resolve conflicted files with an external merge tool
@@ -34,6 +60,15 @@ use crate::ui::Ui;
 /// resolved. To stop resolving conflicts, exit the merge tool without making
 /// any changes.
 ///
+/// Unless `--tool` is specified, each file is resolved with the first tool
+/// whose `merge-tools.<name>.patterns` matches it, or with the default merge
+/// tool (`ui.merge-editor`) if no patterns match. The patterns are filesets
+/// relative to the workspace root. Use `--list-tools` to see the configured
+/// tools and their patterns.
+///
+/// Conflicts in binary files aren't passed to the merge tool. Instead, you're
+/// asked which side of the conflict to keep.
+///
 /// Note that conflicts can also be resolved without using this command. You may
 /// edit the conflict markers in the conflicted file directly with a text
 /// editor.
@@ -58,9 +93,53 @@ pub(crate) struct ResolveArgs {
     // `diff --summary`, but should be more verbose.
     #[arg(long, short)]
     list: bool,
+    /// Render each conflict listed by `--list` using the given template
+    ///
+    /// All 0-argument methods of the [`TreeEntry` type] are available as
+    /// keywords in the [template expression]. `sides`, `involves_deletion`,
+    /// and `involves_modes` describe the conflict.
+    ///
+    /// [template expression]:
+    ///     https://jj-vcs.github.io/jj/latest/templates/
+    ///
+    /// [`TreeEntry` type]:
+    ///     https://jj-vcs.github.io/jj/latest/templates/#treeentry-type
+    #[arg(long, short = 'T', requires = "list")]
+    template: Option<String>,
+    /// Print the conflicts listed by `--list` as JSON
+    ///
+    /// The output is an array with an object per conflicted path, with the
+    /// fields `path`, `sides`, `involves_deletion`, and `involves_modes`.
+    /// Paths are relative to the workspace root and use `/` as separator.
+    #[arg(long, requires = "list", conflicts_with = "template")]
+    json: bool,
     /// Specify 3-way merge tool to be used
+    ///
+    /// This overrides the tools selected by `merge-tools.<name>.patterns`.
+    ///
+    /// The built-in tools `:ours` and `:theirs` resolve each conflicted hunk by
+    /// picking the first or second side respectively, and `:union` keeps the
+    /// lines from both sides. In binary files, `:ours` and `:theirs` pick the
+    /// whole side.
     #[arg(long, conflicts_with = "list", value_name = "NAME")]
     tool: Option<String>,
+    /// Instead of resolving conflicts, list the configured merge tools
+    ///
+    /// Shows whether the program of each tool was found, and the patterns of
+    /// the files it's used for.
+    #[arg(long, conflicts_with_all = ["list", "tool"])]
+    list_tools: bool,
+    /// Invoke an external merge tool once for all the conflicted files
+    ///
+    /// The `$base`, `$left`, `$right`, and `$output` arguments are replaced
+    /// with directories containing the conflicted files. Files which aren't
+    /// saved in the `$output` directory are left conflicted.
+    #[arg(long, conflicts_with_all = ["list", "list_tools"])]
+    dir_mode: bool,
+    /// With `--dir-mode`, also copy the other files in the directories of the
+    /// conflicted files, read-only
+    #[arg(long, requires = "dir_mode")]
+    include_context: bool,
     /// Only resolve conflicts in these paths. You can use the `--list` argument
     /// to find paths to use here.
     #[arg(
@@ -77,10 +156,13 @@ pub(crate) fn cmd_resolve(
     command: &CommandHelper,
     args: &ResolveArgs,
 ) -> Result<(), CommandError> {
+    if args.list_tools {
+        return print_merge_tools(ui, command.settings());
+    }
     let mut workspace_command = command.workspace_helper(ui)?;
     let matcher = workspace_command
         .parse_file_patterns(ui, &args.paths)?
-        .to_matcher();
+        .to_matcher()?;
     let commit = workspace_command.resolve_single_rev(ui, &args.revision)?;
     let tree = commit.tree()?;
     let conflicts = tree
@@ -95,10 +177,16 @@ pub(crate) fn cmd_resolve(
         }));
     }
     if args.list {
+        if args.json {
+            return print_conflicts_json(ui, conflicts);
+        } else if let Some(template_text) = &args.template {
+            return print_conflicts_with_template(ui, &workspace_command, conflicts, template_text);
+        }
         return print_conflicted_paths(
             conflicts,
             ui.stdout_formatter().as_mut(),
             &workspace_command,
+            None,
         );
     };
 
@@ -107,14 +195,69 @@ pub(crate) fn cmd_resolve(
         .map(|(path, _)| path.as_ref())
         .collect_vec();
     workspace_command.check_rewritable([commit.id()])?;
-    let merge_editor = workspace_command.merge_editor(ui, args.tool.as_deref())?;
+    let pattern_tools = if args.tool.is_some() {
+        vec![]
+    } else {
+        load_pattern_merge_tools(
+            ui,
+            workspace_command.settings(),
+            workspace_command.env().fileset_aliases_map(),
+        )?
+    };
+    // Group the paths by tool. The last group is for the default tool.
+    let mut paths_by_tool = vec![vec![]; pattern_tools.len() + 1];
+    for path in repo_paths {
+        let index = pattern_tools
+            .iter()
+            .position(|(_, matcher)| matcher.matches(path))
+            .unwrap_or(pattern_tools.len());
+        paths_by_tool[index].push(path);
+    }
+    let tool_names = pattern_tools
+        .iter()
+        .map(|(name, _)| Some(name.as_str()))
+        .chain([args.tool.as_deref()]);
+
     let mut tx = workspace_command.start_transaction();
-    let (new_tree_id, partial_resolution_error) =
-        merge_editor.edit_files(ui, &tree, &repo_paths)?;
+    let mut new_tree = tree;
+    let mut resolved_count = 0;
+    let mut partial_resolution_error = None;
+    for (tool_name, paths) in tool_names.zip(&paths_by_tool) {
+        if paths.is_empty() {
+            continue;
+        }
+        let merge_editor = tx.base_workspace_helper().merge_editor(ui, tool_name)?;
+        let result = if args.dir_mode {
+            merge_editor.edit_files_in_dir(ui, &new_tree, paths, args.include_context)
+        } else {
+            merge_editor.edit_files(ui, &new_tree, paths)
+        };
+        match result {
+            Ok((new_tree_id, err)) => {
+                new_tree = tx.repo().store().get_root_tree(&new_tree_id)?;
+                if let Some(err) = err {
+                    partial_resolution_error = Some(MergeToolPartialResolutionError {
+                        source: err.source,
+                        resolved_count: resolved_count + err.resolved_count,
+                    });
+                    break;
+                }
+                resolved_count += paths.len();
+            }
+            Err(err) if resolved_count == 0 => return Err(err.into()),
+            Err(err) => {
+                partial_resolution_error = Some(MergeToolPartialResolutionError {
+                    source: err,
+                    resolved_count,
+                });
+                break;
+            }
+        }
+    }
     let new_commit = tx
         .repo_mut()
         .rewrite_commit(&commit)
-        .set_tree_id(new_tree_id)
+        .set_tree_id(new_tree.id())
         .write()?;
     tx.finish(
         ui,
@@ -133,7 +276,12 @@ pub(crate) fn cmd_resolve(
                     formatter.labeled("warning").with_heading("Warning: "),
                     "After this operation, some files at this revision still have conflicts:"
                 )?;
-                print_conflicted_paths(new_conflicts, formatter.as_mut(), &workspace_command)?;
+                print_conflicted_paths(
+                    new_conflicts,
+                    formatter.as_mut(),
+                    &workspace_command,
+                    None,
+                )?;
             }
         }
     }
@@ -143,3 +291,135 @@ pub(crate) fn cmd_resolve(
     }
     Ok(())
 }
+
+fn print_conflicts_with_template(
+    ui: &mut Ui,
+    workspace_command: &WorkspaceCommandHelper,
+    conflicts: Vec<(RepoPathBuf, BackendResult<MergedTreeValue>)>,
+    template_text: &str,
+) -> Result<(), CommandError> {
+    let language = workspace_command.commit_template_language();
+    let template = workspace_command
+        .parse_template(
+            ui,
+            &language,
+            template_text,
+            CommitTemplateLanguage::wrap_tree_entry,
+        )?
+        .labeled("conflict_list");
+    ui.request_pager();
+    let mut formatter = ui.stdout_formatter();
+    for (path, value) in conflicts {
+        let entry = TreeEntry {
+            path,
+            value: value?,
+        };
+        template.format(&entry, formatter.as_mut())?;
+    }
+    Ok(())
+}
+
+fn print_conflicts_json(
+    ui: &mut Ui,
+    conflicts: Vec<(RepoPathBuf, BackendResult<MergedTreeValue>)>,
+) -> Result<(), CommandError> {
+    let entries: Vec<_> = conflicts
+        .into_iter()
+        .map(|(path, value)| {
+            let value = value?;
+            Ok::<_, CommandError>(serde_json::json!({
+                "path": path.as_internal_file_string(),
+                "sides": value.clone().simplify().num_sides(),
+                "involves_deletion": conflict_involves_deletion(&value),
+                "involves_modes": conflict_involves_modes(&value),
+            }))
+        })
+        .try_collect()?;
+    let text = serde_json::to_string_pretty(&entries).unwrap();
+    writeln!(ui.stdout(), "{text}")?;
+    Ok(())
+}
+
+type PatternMergeTools = Vec<(String, Box<dyn Matcher>)>;
+
+/// Loads the merge tools which have `merge-tools.<name>.patterns` configured,
+/// in the order they are defined.
+fn load_pattern_merge_tools(
+    ui: &Ui,
+    settings: &UserSettings,
+    aliases_map: &FilesetAliasesMap,
+) -> Result<PatternMergeTools, CommandError> {
+    let path_converter = RepoPathUiConverter::Fs {
+        cwd: "".into(),
+        base: "".into(),
+    };
+    let mut tools = vec![];
+    for name in settings.table_keys("merge-tools") {
+        let Some(patterns) = get_merge_tool_patterns(settings, name)? else {
+            continue;
+        };
+        let mut diagnostics = FilesetDiagnostics::new();
+        let expressions: Vec<_> = patterns
+            .iter()
+            .map(|pattern| fileset::parse(&mut diagnostics, pattern, aliases_map, &path_converter))
+            .try_collect()?;
+        print_parse_diagnostics(
+            ui,
+            &format!("In `merge-tools.{name}.patterns`"),
+            &diagnostics,
+        )?;
+        let matcher = FilesetExpression::union_all(expressions).to_matcher()?;
+        tools.push((name.to_owned(), matcher));
+    }
+    Ok(tools)
+}
+
+fn get_merge_tool_patterns(
+    settings: &UserSettings,
+    name: &str,
+) -> Result<Option<Vec<String>>, CommandError> {
+    let key = ConfigNamePathBuf::from_iter(["merge-tools", name, "patterns"]);
+    let patterns: Option<Vec<String>> = settings.get(&key).optional()?;
+    Ok(patterns.filter(|patterns| !patterns.is_empty()))
+}
+
+fn print_merge_tools(ui: &mut Ui, settings: &UserSettings) -> Result<(), CommandError> {
+    ui.request_pager();
+    let mut formatter = ui.stdout_formatter();
+    for name in settings.table_keys("merge-tools") {
+        // Built-in tools like `:theirs` can also be configured with patterns.
+        if name.starts_with(':') {
+            write!(formatter, "{name}: (built-in)")?;
+        } else {
+            let Some(tool) = get_external_tool_config(settings, name)? else {
+                continue;
+            };
+            write!(formatter, "{name}: {} ", tool.program)?;
+            if find_program(&tool.program).is_some() {
+                write!(formatter.labeled("found"), "(found)")?;
+            } else {
+                write!(formatter.labeled("not-found"), "(not found)")?;
+            }
+        }
+        if let Some(patterns) = get_merge_tool_patterns(settings, name)? {
+            write!(formatter, ", patterns: {}", patterns.join(" "))?;
+        }
+        writeln!(formatter)?;
+    }
+    Ok(())
+}
+
+/// Looks up the program in `PATH` unless it is a path.
+fn find_program(program: &str) -> Option<PathBuf> {
+    let path = Path::new(program);
+    if path.components().count() > 1 {
+        return path.is_file().then(|| path.to_owned());
+    }
+    let file_names = [
+        program.to_owned(),
+        format!("{program}{}", env::consts::EXE_SUFFIX),
+    ];
+    env::split_paths(&env::var_os("PATH")?)
+        .flat_map(|dir| file_names.iter().map(move |name| dir.join(name)))
+        .find(|candidate| candidate.is_file())
+}
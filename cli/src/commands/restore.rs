@@ -42,8 +42,13 @@ use crate::ui::Ui;
 /// to `jj abandon`, except that it leaves an empty revision with its
 /// description and other metadata preserved.
 ///
-/// See `jj diffedit` if you'd like to restore portions of files rather than
-/// entire files.
+/// Use `--interactive` (or `--tool`) to restore portions of files rather than
+/// entire files. The diff editor then shows the changes being restored, and
+/// only the selected hunks are restored.
+///
+/// With `--from-op`, the source revision is resolved as of the given
+/// operation, so the contents of a revision which has since been rewritten or
+/// abandoned can be restored without `jj op restore`.
 #[derive(clap::Args, Clone, Debug)]
 pub(crate) struct RestoreArgs {
     /// Restore only these paths (instead of all paths)
@@ -61,6 +66,15 @@ pub(crate) struct RestoreArgs {
         add = ArgValueCandidates::new(complete::all_revisions)
     )]
     from: Option<RevisionArg>,
+    /// Resolve the source revision (`--from`, or the working copy) as of this
+    /// operation
+    #[arg(
+        long,
+        value_name = "OPERATION",
+        conflicts_with = "changes_in",
+        add = ArgValueCandidates::new(complete::operations)
+    )]
+    from_op: Option<String>,
     /// Revision to restore into (destination)
     #[arg(
         long, short = 't',
@@ -119,11 +133,15 @@ pub(crate) fn cmd_restore(
              revision,\nuse `--into` or `--changes-in`.",
         ));
     }
-    if args.from.is_some() || args.into.is_some() {
+    if args.from.is_some() || args.into.is_some() || args.from_op.is_some() {
         to_commit = workspace_command
             .resolve_single_rev(ui, args.into.as_ref().unwrap_or(&RevisionArg::AT))?;
-        let from_commit = workspace_command
-            .resolve_single_rev(ui, args.from.as_ref().unwrap_or(&RevisionArg::AT))?;
+        let from_revision = args.from.as_ref().unwrap_or(&RevisionArg::AT);
+        let from_commit = if let Some(op_str) = &args.from_op {
+            workspace_command.resolve_single_rev_at_operation(ui, op_str, from_revision)?
+        } else {
+            workspace_command.resolve_single_rev(ui, from_revision)?
+        };
         from_tree = from_commit.tree()?;
         from_commits = vec![from_commit];
     } else {
@@ -136,7 +154,7 @@ pub(crate) fn cmd_restore(
 
     let matcher = workspace_command
         .parse_file_patterns(ui, &args.paths)?
-        .to_matcher();
+        .to_matcher()?;
     let diff_selector =
         workspace_command.diff_selector(ui, args.tool.as_deref(), args.interactive)?;
     let to_tree = to_commit.tree()?;
@@ -0,0 +1,194 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashSet;
+
+use clap_complete::ArgValueCandidates;
+use itertools::Itertools as _;
+use jj_lib::commit::Commit;
+use jj_lib::commit::CommitIteratorExt as _;
+use jj_lib::repo::Repo as _;
+use jj_lib::signing::SignBehavior;
+use tracing::instrument;
+
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::cli_util::WorkspaceCommandHelper;
+use crate::command_error::user_error_with_hint;
+use crate::command_error::CommandError;
+use crate::complete;
+use crate::ui::Ui;
+
+/// Cryptographically sign revisions
+///
+/// The revisions are rewritten to add signatures made with the configured
+/// signing backend. Revisions which are already signed are skipped unless
+/// `--force` is specified.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct SignArgs {
+    /// The revision(s) to sign
+    #[arg(
+        long, short,
+        default_value = "@",
+        value_name = "REVSETS",
+        add = ArgValueCandidates::new(complete::mutable_revisions),
+    )]
+    revisions: Vec<RevisionArg>,
+    /// The key used for signing (overrides `signing.key`)
+    #[arg(long)]
+    key: Option<String>,
+    /// Re-sign revisions which are already signed
+    #[arg(long, short)]
+    force: bool,
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_sign(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &SignArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    if !workspace_command.repo().store().signer().can_sign() {
+        return Err(user_error_with_hint(
+            "No signing backend is configured",
+            "Set `signing.backend` to the backend to sign commits with",
+        ));
+    }
+    let commits: Vec<Commit> = workspace_command
+        .parse_union_revsets(ui, &args.revisions)?
+        .evaluate_to_commits()?
+        .try_collect()?;
+    let change = SignatureChange::Sign {
+        key: args.key.as_deref(),
+    };
+    rewrite_signatures(ui, workspace_command, commits, change, |commit| {
+        args.force || !commit.is_signed()
+    })
+}
+
+/// How [`rewrite_signatures()`] changes the signatures of the commits.
+pub(crate) enum SignatureChange<'a> {
+    /// Sign the commits, with the given key if specified.
+    Sign { key: Option<&'a str> },
+    /// Drop the signatures of the commits.
+    Drop,
+}
+
+impl SignatureChange<'_> {
+    fn verb(&self) -> &'static str {
+        match self {
+            SignatureChange::Sign { .. } => "sign",
+            SignatureChange::Drop => "unsign",
+        }
+    }
+
+    fn past_tense(&self) -> &'static str {
+        match self {
+            SignatureChange::Sign { .. } => "Signed",
+            SignatureChange::Drop => "Unsigned",
+        }
+    }
+
+    fn skipped_message(&self, num_skipped: usize) -> String {
+        let reason = match (self, num_skipped) {
+            (SignatureChange::Sign { .. }, 1) => "which is already signed",
+            (SignatureChange::Sign { .. }, _) => "which are already signed",
+            (SignatureChange::Drop, 1) => "which isn't signed",
+            (SignatureChange::Drop, _) => "which aren't signed",
+        };
+        format!("Skipped {} {reason}", num_commits(num_skipped))
+    }
+}
+
+fn num_commits(n: usize) -> String {
+    if n == 1 {
+        format!("{n} commit")
+    } else {
+        format!("{n} commits")
+    }
+}
+
+/// Rewrites the `commits` for which `needs_rewrite` returns true with the
+/// signature `change`, rebases their descendants, and reports the numbers of
+/// rewritten and skipped commits.
+pub(crate) fn rewrite_signatures(
+    ui: &mut Ui,
+    mut workspace_command: WorkspaceCommandHelper,
+    commits: Vec<Commit>,
+    change: SignatureChange,
+    needs_rewrite: impl Fn(&Commit) -> bool,
+) -> Result<(), CommandError> {
+    workspace_command.check_rewritable(commits.iter().ids())?;
+    let (to_rewrite, skipped): (Vec<_>, Vec<_>) = commits
+        .into_iter()
+        .partition(|commit| needs_rewrite(commit));
+    let num_skipped = skipped.len();
+    if to_rewrite.is_empty() {
+        if num_skipped > 0 {
+            writeln!(ui.status(), "{}", change.skipped_message(num_skipped))?;
+        }
+        writeln!(ui.status(), "Nothing changed.")?;
+        return Ok(());
+    }
+
+    let mut tx = workspace_command.start_transaction();
+    let to_rewrite_ids: HashSet<_> = to_rewrite.iter().ids().cloned().collect();
+    let mut num_rewritten = 0;
+    let mut num_rebased = 0;
+    tx.repo_mut().transform_descendants(
+        to_rewrite.iter().ids().cloned().collect(),
+        |rewriter| {
+            if to_rewrite_ids.contains(rewriter.old_commit().id()) {
+                let commit_builder = rewriter.reparent();
+                let commit_builder = match &change {
+                    SignatureChange::Sign { key } => {
+                        let commit_builder = commit_builder.set_sign_behavior(SignBehavior::Force);
+                        match key {
+                            Some(key) => commit_builder.set_sign_key(Some(key.to_string())),
+                            None => commit_builder,
+                        }
+                    }
+                    SignatureChange::Drop => commit_builder.set_sign_behavior(SignBehavior::Drop),
+                };
+                commit_builder.write()?;
+                num_rewritten += 1;
+            } else {
+                rewriter.reparent().write()?;
+                num_rebased += 1;
+            }
+            Ok(())
+        },
+    )?;
+
+    if let Some(mut formatter) = ui.status_formatter() {
+        writeln!(
+            formatter,
+            "{} {}",
+            change.past_tense(),
+            num_commits(num_rewritten)
+        )?;
+        if num_skipped > 0 {
+            writeln!(formatter, "{}", change.skipped_message(num_skipped))?;
+        }
+        if num_rebased > 0 {
+            writeln!(formatter, "Rebased {num_rebased} descendant commits")?;
+        }
+    }
+    tx.finish(
+        ui,
+        format!("{} {}", change.verb(), num_commits(num_rewritten)),
+    )?;
+    Ok(())
+}
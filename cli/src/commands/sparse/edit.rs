@@ -17,8 +17,10 @@ use std::path::Path;
 
 use itertools::Itertools;
 use jj_lib::repo_path::RepoPathBuf;
+use jj_lib::working_copy::SparsePattern;
 use tracing::instrument;
 
+use super::normalize_sparse_patterns;
 use super::update_sparse_patterns_with;
 use crate::cli_util::CommandHelper;
 use crate::command_error::internal_error;
@@ -28,6 +30,9 @@ use crate::description_util::TextEditor;
 use crate::ui::Ui;
 
 /// Start an editor to update the patterns that are present in the working copy
+///
+/// Patterns are listed one per line. Lines starting with `!` exclude the
+/// files matching the rest of the line.
 #[derive(clap::Args, Clone, Debug)]
 pub struct SparseEditArgs {}
 
@@ -40,19 +45,18 @@ pub fn cmd_sparse_edit(
     let mut workspace_command = command.workspace_helper(ui)?;
     let editor = workspace_command.text_editor()?;
     update_sparse_patterns_with(ui, &mut workspace_command, |_ui, old_patterns| {
-        let mut new_patterns = edit_sparse(&editor, old_patterns)?;
-        new_patterns.sort_unstable();
-        new_patterns.dedup();
-        Ok(new_patterns)
+        let new_patterns = edit_sparse(&editor, old_patterns)?;
+        Ok(normalize_sparse_patterns(new_patterns))
     })
 }
 
 fn edit_sparse(
     editor: &TextEditor,
-    sparse: &[RepoPathBuf],
-) -> Result<Vec<RepoPathBuf>, CommandError> {
+    sparse: &[SparsePattern],
+) -> Result<Vec<SparsePattern>, CommandError> {
     let mut content = String::new();
-    for sparse_path in sparse {
+    for pattern in sparse {
+        let sparse_path = pattern.path();
         // Invalid path shouldn't block editing. Edited paths will be validated.
         let workspace_relative_sparse_path = sparse_path.to_fs_path_unchecked(Path::new(""));
         let path_string = workspace_relative_sparse_path.to_str().ok_or_else(|| {
@@ -61,7 +65,8 @@ fn edit_sparse(
                 workspace_relative_sparse_path.display()
             ))
         })?;
-        writeln!(&mut content, "{path_string}").unwrap();
+        let prefix = if pattern.is_exclude() { "!" } else { "" };
+        writeln!(&mut content, "{prefix}{path_string}").unwrap();
     }
 
     let content = editor
@@ -74,8 +79,17 @@ fn edit_sparse(
         .map(|line| line.trim())
         .filter(|line| !line.is_empty())
         .map(|line| {
-            RepoPathBuf::from_relative_path(line).map_err(|err| {
+            let (path, exclude) = match line.strip_prefix('!') {
+                Some(rest) => (rest.trim_start(), true),
+                None => (line, false),
+            };
+            let path = RepoPathBuf::from_relative_path(path).map_err(|err| {
                 user_error_with_message(format!("Failed to parse sparse pattern: {line}"), err)
+            })?;
+            Ok::<_, CommandError>(if exclude {
+                SparsePattern::Exclude(path)
+            } else {
+                SparsePattern::Include(path)
             })
         })
         .try_collect()
@@ -25,7 +25,7 @@ use crate::ui::Ui;
 ///
 /// By default, a newly cloned or initialized repo will have have a pattern
 /// matching all files from the repo root. That pattern is rendered as `.` (a
-/// single period).
+/// single period). Patterns excluding files are prefixed with `!`.
 #[derive(clap::Args, Clone, Debug)]
 pub struct SparseListArgs {}
 
@@ -36,11 +36,12 @@ pub fn cmd_sparse_list(
     _args: &SparseListArgs,
 ) -> Result<(), CommandError> {
     let workspace_command = command.workspace_helper(ui)?;
-    for path in workspace_command.working_copy().sparse_patterns()? {
+    for pattern in workspace_command.working_copy().sparse_patterns()? {
+        let prefix = if pattern.is_exclude() { "!" } else { "" };
         writeln!(
             ui.stdout(),
-            "{}",
-            path.to_fs_path_unchecked(Path::new("")).display()
+            "{prefix}{}",
+            pattern.path().to_fs_path_unchecked(Path::new("")).display()
         )?;
     }
     Ok(())
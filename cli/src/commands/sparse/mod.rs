@@ -18,7 +18,8 @@ mod reset;
 mod set;
 
 use clap::Subcommand;
-use jj_lib::repo_path::RepoPathBuf;
+use itertools::Itertools as _;
+use jj_lib::working_copy::SparsePattern;
 use tracing::instrument;
 
 use self::edit::cmd_sparse_edit;
@@ -63,17 +64,36 @@ pub(crate) fn cmd_sparse(
 fn update_sparse_patterns_with(
     ui: &mut Ui,
     workspace_command: &mut WorkspaceCommandHelper,
-    f: impl FnOnce(&mut Ui, &[RepoPathBuf]) -> Result<Vec<RepoPathBuf>, CommandError>,
+    f: impl FnOnce(&mut Ui, &[SparsePattern]) -> Result<Vec<SparsePattern>, CommandError>,
 ) -> Result<(), CommandError> {
     let checkout_options = workspace_command.checkout_options();
     let (mut locked_ws, wc_commit) = workspace_command.start_working_copy_mutation()?;
     let new_patterns = f(ui, locked_ws.locked_wc().sparse_patterns()?)?;
-    let stats = locked_ws
-        .locked_wc()
-        .set_sparse_patterns(new_patterns, &checkout_options)
-        .map_err(|err| internal_error_with_message("Failed to update working copy paths", err))?;
+    let stats = {
+        let mut options = checkout_options;
+        let progress = crate::progress::checkout_progress(ui);
+        options.progress = progress.as_ref().map(|x| x as _);
+        locked_ws
+            .locked_wc()
+            .set_sparse_patterns(new_patterns, &options)
+            .map_err(|err| {
+                internal_error_with_message("Failed to update working copy paths", err)
+            })?
+    };
     let operation_id = locked_ws.locked_wc().old_operation_id().clone();
     locked_ws.finish(operation_id)?;
-    print_checkout_stats(ui, stats, &wc_commit)?;
+    print_checkout_stats(ui, stats, &wc_commit, workspace_command.path_converter())?;
     Ok(())
 }
+
+/// Sorts and deduplicates runs of consecutive include or exclude patterns.
+///
+/// Since the last matching pattern wins, the order within such a run doesn't
+/// matter.
+fn normalize_sparse_patterns(patterns: Vec<SparsePattern>) -> Vec<SparsePattern> {
+    let mut normalized = Vec::with_capacity(patterns.len());
+    for (_, chunk) in &patterns.into_iter().chunk_by(SparsePattern::is_exclude) {
+        normalized.extend(chunk.sorted_unstable().dedup());
+    }
+    normalized
+}
@@ -12,12 +12,11 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::HashSet;
-
-use itertools::Itertools as _;
 use jj_lib::repo_path::RepoPathBuf;
+use jj_lib::working_copy::SparsePattern;
 use tracing::instrument;
 
+use super::normalize_sparse_patterns;
 use super::update_sparse_patterns_with;
 use crate::cli_util::CommandHelper;
 use crate::command_error::CommandError;
@@ -28,6 +27,12 @@ use crate::ui::Ui;
 /// For example, if all you need is the `README.md` and the `lib/`
 /// directory, use `jj sparse set --clear --add README.md --add lib`.
 /// If you no longer need the `lib` directory, use `jj sparse set --remove lib`.
+/// To leave out `lib/generated/` but keep the rest of `lib/`, use
+/// `jj sparse set --exclude lib/generated`.
+///
+/// Patterns are evaluated in order, and the last pattern matching a path wins.
+/// Added patterns are placed after the existing patterns, and exclusions are
+/// placed after them.
 #[derive(clap::Args, Clone, Debug)]
 pub struct SparseSetArgs {
     /// Patterns to add to the working copy
@@ -37,7 +42,16 @@ pub struct SparseSetArgs {
         value_parser = |s: &str| RepoPathBuf::from_relative_path(s),
     )]
     add: Vec<RepoPathBuf>,
+    /// Patterns to exclude from the working copy
+    #[arg(
+        long,
+        value_hint = clap::ValueHint::AnyPath,
+        value_parser = |s: &str| RepoPathBuf::from_relative_path(s),
+    )]
+    exclude: Vec<RepoPathBuf>,
     /// Patterns to remove from the working copy
+    ///
+    /// Both included and excluded patterns with the given path are removed.
     #[arg(
         long,
         conflicts_with = "clear",
@@ -58,16 +72,28 @@ pub fn cmd_sparse_set(
 ) -> Result<(), CommandError> {
     let mut workspace_command = command.workspace_helper(ui)?;
     update_sparse_patterns_with(ui, &mut workspace_command, |_ui, old_patterns| {
-        let mut new_patterns = HashSet::new();
+        let mut new_patterns = vec![];
         if !args.clear {
-            new_patterns.extend(old_patterns.iter().cloned());
-            for path in &args.remove {
-                new_patterns.remove(path);
-            }
+            new_patterns.extend(
+                old_patterns
+                    .iter()
+                    .filter(|pattern| {
+                        !args
+                            .remove
+                            .iter()
+                            .any(|path| path.as_ref() == pattern.path())
+                    })
+                    .cloned(),
+            );
         }
         for path in &args.add {
-            new_patterns.insert(path.to_owned());
+            new_patterns.retain(|pattern| pattern.path() != path.as_ref());
+            new_patterns.push(SparsePattern::Include(path.clone()));
+        }
+        for path in &args.exclude {
+            new_patterns.retain(|pattern| pattern.path() != path.as_ref());
+            new_patterns.push(SparsePattern::Exclude(path.clone()));
         }
-        Ok(new_patterns.into_iter().sorted_unstable().collect())
+        Ok(normalize_sparse_patterns(new_patterns))
     })
 }
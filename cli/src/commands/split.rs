@@ -13,19 +13,26 @@
 // limitations under the License.
 use std::io::Write;
 
+use bstr::ByteVec as _;
 use clap_complete::ArgValueCandidates;
 use clap_complete::ArgValueCompleter;
+use jj_lib::commit::Commit;
+use jj_lib::config::ConfigGetResultExt as _;
 use jj_lib::object_id::ObjectId;
 use jj_lib::repo::Repo;
 use tracing::instrument;
 
 use crate::cli_util::CommandHelper;
+use crate::cli_util::ExcludeArgs;
 use crate::cli_util::RevisionArg;
+use crate::cli_util::WorkspaceCommandHelper;
 use crate::command_error::user_error_with_hint;
 use crate::command_error::CommandError;
 use crate::complete;
 use crate::description_util::description_template;
 use crate::description_util::edit_description;
+use crate::description_util::join_message_paragraphs;
+use crate::formatter::PlainTextFormatter;
 use crate::ui::Ui;
 
 /// Split a revision in two
@@ -43,13 +50,20 @@ use crate::ui::Ui;
 /// description, the second part will not get a description, and you will be
 /// asked for a description only for the first part.
 ///
+/// If a message is given with `-m`, it's used for the first commit, and the
+/// second commit keeps the description of the revision being split. The
+/// descriptions can also be generated from the revision being split by the
+/// `templates.split_first_description` and `templates.split_second_description`
+/// config variables. The editor is only opened for the commits which don't get
+/// their description from a message or a template.
+///
 /// Splitting an empty commit is not supported because the same effect can be
 /// achieved with `jj new`.
 #[derive(clap::Args, Clone, Debug)]
 pub(crate) struct SplitArgs {
     /// Interactively choose which parts to split
     ///
-    /// This is the default if no filesets are provided.
+    /// This is the default if no filesets or `--exclude` are provided.
     #[arg(long, short)]
     interactive: bool,
     /// Specify diff editor to be used (implies --interactive)
@@ -67,6 +81,24 @@ pub(crate) struct SplitArgs {
     /// child
     #[arg(long, short)]
     parallel: bool,
+    /// The change description to use for the first commit (don't open editor)
+    #[arg(
+        long = "message",
+        short,
+        visible_alias = "message-first",
+        value_name = "MESSAGE"
+    )]
+    message_paragraphs: Vec<String>,
+    /// The change description to use for the second commit (don't open
+    /// editor)
+    #[arg(long = "message-second", value_name = "MESSAGE")]
+    message_second_paragraphs: Vec<String>,
+    /// Which of the two commits the bookmarks pointing to the revision should
+    /// be moved to
+    ///
+    /// Defaults to the `split.legacy-bookmark-behavior` setting.
+    #[arg(long, value_enum, value_name = "PART")]
+    bookmarks_to: Option<SplitPart>,
     /// Files matching any of these filesets are put in the first commit
     #[arg(
         value_name = "FILESETS",
@@ -74,6 +106,36 @@ pub(crate) struct SplitArgs {
         add = ArgValueCompleter::new(complete::modified_revision_files),
     )]
     paths: Vec<String>,
+    #[command(flatten)]
+    exclude_args: ExcludeArgs,
+}
+
+/// Renders the description template configured at `key` against the commit
+/// being split, or returns `None` if the template isn't configured.
+fn render_split_description(
+    ui: &Ui,
+    workspace_command: &WorkspaceCommandHelper,
+    key: &'static str,
+    commit: &Commit,
+) -> Result<Option<String>, CommandError> {
+    let Some(template_text) = workspace_command.settings().get_string(key).optional()? else {
+        return Ok(None);
+    };
+    let template = workspace_command.parse_commit_template(ui, &template_text)?;
+    let mut output = Vec::new();
+    template
+        .format(commit, &mut PlainTextFormatter::new(&mut output))
+        .expect("write() to vec backed formatter should never fail");
+    // Template output is usually UTF-8, but it can contain file content.
+    Ok(Some(output.into_string_lossy()))
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, clap::ValueEnum)]
+enum SplitPart {
+    /// The first commit, which keeps the change id of the revision
+    First,
+    /// The second commit, which contains the remaining changes
+    Second,
 }
 
 #[instrument(skip_all)]
@@ -93,14 +155,26 @@ pub(crate) fn cmd_split(
 
     workspace_command.check_rewritable([commit.id()])?;
     let matcher = workspace_command
-        .parse_file_patterns(ui, &args.paths)?
-        .to_matcher();
+        .parse_file_patterns_excluding(ui, &args.paths, &args.exclude_args)?
+        .to_matcher()?;
     let diff_selector = workspace_command.diff_selector(
         ui,
         args.tool.as_deref(),
-        args.interactive || args.paths.is_empty(),
+        args.interactive || (args.paths.is_empty() && args.exclude_args.exclude.is_empty()),
     )?;
     let text_editor = workspace_command.text_editor()?;
+    let first_template_description = render_split_description(
+        ui,
+        &workspace_command,
+        "templates.split_first_description",
+        &commit,
+    )?;
+    let second_template_description = render_split_description(
+        ui,
+        &workspace_command,
+        "templates.split_second_description",
+        &commit,
+    )?;
     let mut tx = workspace_command.start_transaction();
     let end_tree = commit.tree()?;
     let base_tree = commit.parent_tree(tx.repo())?;
@@ -121,6 +195,12 @@ The remainder will be in the second commit.
     // Prompt the user to select the changes they want for the first commit.
     let selected_tree_id =
         diff_selector.select(&base_tree, &end_tree, matcher.as_ref(), format_instructions)?;
+    if selected_tree_id == base_tree.id() && !diff_selector.is_interactive() {
+        return Err(user_error_with_hint(
+            "No changes have been selected by the given paths",
+            "Use `jj new --insert-before` if you want to insert an empty commit.",
+        ));
+    }
     if &selected_tree_id == commit.tree_id() {
         // The user selected everything from the original commit.
         writeln!(
@@ -140,17 +220,23 @@ The remainder will be in the second commit.
     let first_commit = {
         let mut commit_builder = tx.repo_mut().rewrite_commit(&commit).detach();
         commit_builder.set_tree_id(selected_tree_id);
-        if commit_builder.description().is_empty() {
-            commit_builder.set_description(tx.settings().get_string("ui.default-description")?);
-        }
-        let temp_commit = commit_builder.write_hidden()?;
-        let template = description_template(
-            ui,
-            &tx,
-            "Enter a description for the first commit.",
-            &temp_commit,
-        )?;
-        let description = edit_description(&text_editor, &template)?;
+        let description = if !args.message_paragraphs.is_empty() {
+            join_message_paragraphs(&args.message_paragraphs)
+        } else if let Some(description) = first_template_description {
+            description
+        } else {
+            if commit_builder.description().is_empty() {
+                commit_builder.set_description(tx.settings().get_string("ui.default-description")?);
+            }
+            let temp_commit = commit_builder.write_hidden()?;
+            let template = description_template(
+                ui,
+                &tx,
+                "Enter a description for the first commit.",
+                &temp_commit,
+            )?;
+            edit_description(&text_editor, &template)?
+        };
         commit_builder.set_description(description);
         commit_builder.write(tx.repo_mut())?
     };
@@ -178,10 +264,18 @@ The remainder will be in the second commit.
             // Generate a new change id so that the commit being split doesn't
             // become divergent.
             .generate_new_change_id();
-        let description = if commit.description().is_empty() {
+        let description = if !args.message_second_paragraphs.is_empty() {
+            join_message_paragraphs(&args.message_second_paragraphs)
+        } else if let Some(description) = second_template_description {
+            description
+        } else if commit.description().is_empty() {
             // If there was no description before, don't ask for one for the
             // second commit.
             "".to_string()
+        } else if !args.message_paragraphs.is_empty() {
+            // The first commit was given a new description, so the second
+            // commit keeps the original one.
+            commit.description().to_owned()
         } else {
             let temp_commit = commit_builder.write_hidden()?;
             let template = description_template(
@@ -196,7 +290,11 @@ The remainder will be in the second commit.
         commit_builder.write(tx.repo_mut())?
     };
 
-    let legacy_bookmark_behavior = read_legacy_bookmark_behavior_setting(tx.settings(), ui)?;
+    let legacy_bookmark_behavior = match args.bookmarks_to {
+        Some(SplitPart::First) => false,
+        Some(SplitPart::Second) => true,
+        None => read_legacy_bookmark_behavior_setting(tx.settings(), ui)?,
+    };
     if legacy_bookmark_behavior {
         // Mark the commit being split as rewritten to the second commit. This
         // moves any bookmarks pointing to the target commit to the second
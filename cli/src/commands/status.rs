@@ -13,20 +13,36 @@
 // limitations under the License.
 
 use std::io;
+use std::iter;
+use std::path;
 
 use itertools::Itertools;
+use jj_lib::backend::BackendResult;
+use jj_lib::commit::Commit;
 use jj_lib::copies::CopyRecords;
+use jj_lib::fileset::FilesetExpression;
+use jj_lib::local_working_copy::LocalWorkingCopy;
+use jj_lib::matchers::Matcher;
+use jj_lib::merged_tree::MergedTree;
 use jj_lib::repo::Repo;
+use jj_lib::repo_path::RepoPath;
+use jj_lib::repo_path::RepoPathUiConverter;
 use jj_lib::revset::RevsetExpression;
 use jj_lib::revset::RevsetFilterPredicate;
+use jj_lib::working_copy::IgnoredPathKind;
+use jj_lib::working_copy::UntrackedReason;
 use tracing::instrument;
 
+use crate::cli_util::build_untracked_reason_message;
 use crate::cli_util::print_conflicted_paths;
 use crate::cli_util::print_snapshot_stats;
 use crate::cli_util::CommandHelper;
+use crate::cli_util::ExcludeArgs;
+use crate::cli_util::WorkspaceCommandHelper;
 use crate::command_error::CommandError;
 use crate::diff_util::get_copy_records;
 use crate::diff_util::DiffFormat;
+use crate::formatter::Formatter;
 use crate::ui::Ui;
 
 /// Show high-level repo status
@@ -35,6 +51,11 @@ use crate::ui::Ui;
 ///
 ///  * The working copy commit and its (first) parent, and a summary of the
 ///    changes between them
+///  * Untracked paths. Directories which contain no tracked files are listed as
+///    a single entry.
+///  * New files which weren't snapshotted because they are larger than
+///    `snapshot.max-new-file-size`
+///  * Conflicted files, and the commits whose changes conflict in them
 ///  * [Conflicted bookmarks]
 ///
 /// [Conflicted bookmarks]:
@@ -45,6 +66,11 @@ pub(crate) struct StatusArgs {
     /// Restrict the status display to these paths
     #[arg(value_name = "FILESETS", value_hint = clap::ValueHint::AnyPath)]
     paths: Vec<String>,
+    #[command(flatten)]
+    exclude_args: ExcludeArgs,
+    /// Also list paths ignored by `.gitignore`
+    #[arg(long)]
+    ignored: bool,
 }
 
 #[instrument(skip_all)]
@@ -53,7 +79,11 @@ pub(crate) fn cmd_status(
     command: &CommandHelper,
     args: &StatusArgs,
 ) -> Result<(), CommandError> {
-    let (workspace_command, snapshot_stats) = command.workspace_helper_with_stats(ui)?;
+    let (workspace_command, snapshot_stats) = if args.ignored {
+        command.workspace_helper_with_ignored_paths(ui)?
+    } else {
+        command.workspace_helper_with_stats(ui)?
+    };
     print_snapshot_stats(
         ui,
         &snapshot_stats,
@@ -65,8 +95,8 @@ pub(crate) fn cmd_status(
         .map(|id| repo.store().get_commit(id))
         .transpose()?;
     let matcher = workspace_command
-        .parse_file_patterns(ui, &args.paths)?
-        .to_matcher();
+        .parse_file_patterns_excluding(ui, &args.paths, &args.exclude_args)?
+        .to_matcher()?;
     ui.request_pager();
     let mut formatter = ui.stdout_formatter();
     let formatter = formatter.as_mut();
@@ -75,8 +105,34 @@ pub(crate) fn cmd_status(
         let parent_tree = wc_commit.parent_tree(repo.as_ref())?;
         let tree = wc_commit.tree()?;
 
+        let mut untracked_paths: Vec<&RepoPath> = vec![];
+        let mut large_files = vec![];
+        for (path, reason) in &snapshot_stats.untracked_paths {
+            if !matcher.matches(path) {
+                continue;
+            }
+            match reason {
+                UntrackedReason::FileNotAutoTracked => untracked_paths.push(path),
+                UntrackedReason::FileTooLarge { .. } => large_files.push((path, reason)),
+            }
+        }
+        let untracked_paths = collapse_untracked_paths(&untracked_paths, &tree, &matcher)?;
+        let ignored_paths = if args.ignored {
+            snapshot_stats
+                .ignored_paths
+                .iter()
+                .filter(|(path, kind)| match kind {
+                    IgnoredPathKind::File => matcher.matches(path),
+                    IgnoredPathKind::Directory => !matcher.visit(path).is_nothing(),
+                })
+                .collect_vec()
+        } else {
+            vec![]
+        };
+
         let wc_has_changes = tree.id() != parent_tree.id();
-        let wc_has_untracked = !snapshot_stats.untracked_paths.is_empty();
+        let wc_has_untracked =
+            !untracked_paths.is_empty() || !large_files.is_empty() || !ignored_paths.is_empty();
         if !wc_has_changes && !wc_has_untracked {
             writeln!(formatter, "The working copy has no changes.")?;
         } else {
@@ -100,18 +156,43 @@ pub(crate) fn cmd_status(
                 )?;
             }
 
-            if wc_has_untracked {
-                // TODO: make sure this always display all untracked non-ignored files, even
-                // when using watchman. See https://github.com/jj-vcs/jj/commit/168c7979feab40d58f49fe19683975697a7bc089 for details.
+            let path_converter = workspace_command.path_converter();
+            // TODO: make sure this always display all untracked non-ignored files, even
+            // when using watchman. See https://github.com/jj-vcs/jj/commit/168c7979feab40d58f49fe19683975697a7bc089 for details.
+            if !untracked_paths.is_empty() {
                 writeln!(formatter, "Untracked paths:")?;
                 formatter.with_label("diff", |formatter| {
-                    for path in snapshot_stats.untracked_paths.keys() {
-                        let ui_path = workspace_command.path_converter().format_file_path(path);
+                    for (path, is_dir) in &untracked_paths {
+                        let ui_path = format_path(path_converter, path, *is_dir);
                         writeln!(formatter.labeled("untracked"), "? {ui_path}")?;
                     }
                     io::Result::Ok(())
                 })?;
             }
+
+            if !large_files.is_empty() {
+                writeln!(formatter, "Untracked paths too large to snapshot:")?;
+                formatter.with_label("diff", |formatter| {
+                    for (path, reason) in &large_files {
+                        let ui_path = path_converter.format_file_path(path);
+                        let message = build_untracked_reason_message(reason).unwrap();
+                        writeln!(formatter.labeled("untracked"), "? {ui_path}: {message}")?;
+                    }
+                    io::Result::Ok(())
+                })?;
+            }
+
+            if !ignored_paths.is_empty() {
+                writeln!(formatter, "Ignored paths:")?;
+                formatter.with_label("diff", |formatter| {
+                    for (path, kind) in &ignored_paths {
+                        let is_dir = **kind == IgnoredPathKind::Directory;
+                        let ui_path = format_path(path_converter, path, is_dir);
+                        writeln!(formatter.labeled("ignored"), "! {ui_path}")?;
+                    }
+                    io::Result::Ok(())
+                })?;
+            }
         }
 
         let template = workspace_command.commit_summary_template();
@@ -129,11 +210,24 @@ pub(crate) fn cmd_status(
             // TODO: Conflicts should also be filtered by the `matcher`. See the related
             // TODO on `MergedTree::conflicts()`.
             let conflicts = wc_commit.tree()?.conflicts().collect_vec();
+            // Tracing the origins is expensive, so it's only done for the first
+            // few paths.
+            let conflicted_paths = conflicts
+                .iter()
+                .take(MAX_CONFLICT_ORIGINS)
+                .map(|(path, _)| path.clone())
+                .collect_vec();
             writeln!(
                 formatter.labeled("warning").with_heading("Warning: "),
                 "There are unresolved conflicts at these paths:"
             )?;
-            print_conflicted_paths(conflicts, formatter, &workspace_command)?;
+            let local_wc: Option<&LocalWorkingCopy> =
+                workspace_command.working_copy().as_any().downcast_ref();
+            let file_states = local_wc.map(|wc| wc.file_states()).transpose()?;
+            print_conflicted_paths(conflicts, formatter, &workspace_command, file_states)?;
+            for path in &conflicted_paths {
+                print_conflict_origin(formatter, &workspace_command, wc_commit, path)?;
+            }
 
             let wc_revset = RevsetExpression::commit(wc_commit.id().clone());
 
@@ -149,6 +243,13 @@ pub(crate) fn cmd_status(
                 .evaluate_to_commit_ids()?
                 .try_collect()?;
 
+            if ancestors_conflicts.is_empty() {
+                writeln!(
+                    formatter.labeled("hint").with_heading("Hint: "),
+                    "To resolve the conflicts, use `jj resolve`, or edit the conflict markers in \
+                     the file directly."
+                )?;
+            }
             workspace_command.report_repo_conflicts(formatter, repo, ancestors_conflicts)?;
         } else {
             for parent in wc_commit.parents() {
@@ -215,3 +316,145 @@ pub(crate) fn cmd_status(
 
     Ok(())
 }
+
+/// Maximum number of conflicted paths whose origins are printed by `jj status`.
+const MAX_CONFLICT_ORIGINS: usize = 3;
+
+/// Prints the commits whose changes conflict at `path` in `wc_commit`.
+///
+/// Conflicts don't record which commits their sides came from, so they are
+/// derived from the history: the conflict is traced back through the ancestors
+/// to the commit which introduced it, and the sides are the commits which last
+/// modified the file on each of its parents. If the conflict was introduced by
+/// rebasing a commit, the commit itself is one of the sides.
+fn print_conflict_origin(
+    formatter: &mut dyn Formatter,
+    workspace_command: &WorkspaceCommandHelper,
+    wc_commit: &Commit,
+    path: &RepoPath,
+) -> Result<(), CommandError> {
+    let is_conflicted = |commit: &Commit| -> BackendResult<bool> {
+        Ok(!commit.tree()?.path_value(path)?.is_resolved())
+    };
+    // Find the closest ancestor which introduced the conflict.
+    let mut origin = wc_commit.clone();
+    loop {
+        let parents: Vec<_> = origin.parents().try_collect()?;
+        let mut conflicted_parent = None;
+        for parent in parents {
+            if is_conflicted(&parent)? {
+                conflicted_parent = Some(parent);
+                break;
+            }
+        }
+        match conflicted_parent {
+            Some(parent) => origin = parent,
+            None => break,
+        }
+    }
+
+    let last_modifying_commit = |commit: &Commit| -> Result<Option<Commit>, CommandError> {
+        let expression = RevsetExpression::commit(commit.id().clone())
+            .ancestors()
+            .filtered(RevsetFilterPredicate::File(FilesetExpression::file_path(
+                path.to_owned(),
+            )));
+        Ok(workspace_command
+            .attach_revset_evaluator(expression)
+            .evaluate_to_commits()?
+            .next()
+            .transpose()?)
+    };
+    let parents: Vec<_> = origin.parents().try_collect()?;
+    let mut sides = vec![];
+    for parent in &parents {
+        sides.extend(last_modifying_commit(parent)?);
+    }
+    let mut rebased_onto = None;
+    if let [parent] = parents.as_slice() {
+        // A commit with a single parent only has a conflict of its own if it
+        // was created with one, or if it was rebased onto conflicting changes.
+        sides.push(origin.clone());
+        for predecessor in origin.predecessors() {
+            let predecessor = predecessor?;
+            if predecessor.parent_ids() != origin.parent_ids() && !is_conflicted(&predecessor)? {
+                rebased_onto = Some(parent);
+                break;
+            }
+        }
+    }
+    let sides = sides
+        .into_iter()
+        .unique_by(|commit| commit.id().clone())
+        .collect_vec();
+    if sides.is_empty() {
+        return Ok(());
+    }
+
+    let ui_path = workspace_command.format_file_path(path);
+    writeln!(formatter, "Conflicting changes to {ui_path} come from:")?;
+    let template = workspace_command.commit_summary_template();
+    for commit in &sides {
+        write!(formatter, "  ")?;
+        template.format(commit, formatter)?;
+        writeln!(formatter)?;
+    }
+    if let Some(parent) = rebased_onto {
+        let template = workspace_command.short_change_id_template();
+        formatter.with_label("hint", |formatter| {
+            write!(formatter.labeled("heading"), "Hint: ")?;
+            write!(
+                formatter,
+                "The conflict at {ui_path} was introduced by rebasing "
+            )?;
+            template.format(&origin, formatter)?;
+            write!(formatter, " onto ")?;
+            template.format(parent, formatter)?;
+            writeln!(formatter, ".")
+        })?;
+    }
+    Ok(())
+}
+
+/// Collapses untracked paths into their topmost ancestor directories which
+/// contain no tracked files. Returns sorted `(path, is_dir)` pairs.
+///
+/// Only directories matched by the `matcher` are collapsed so that paths
+/// outside of the filter aren't listed.
+fn collapse_untracked_paths<'a>(
+    paths: &[&'a RepoPath],
+    tree: &MergedTree,
+    matcher: &dyn Matcher,
+) -> BackendResult<Vec<(&'a RepoPath, bool)>> {
+    let mut collapsed: Vec<(&RepoPath, bool)> = vec![];
+    for &path in paths {
+        if let Some(&(dir, true)) = collapsed.last() {
+            if path.starts_with(dir) {
+                continue;
+            }
+        }
+        // Look up ancestors from the top, excluding the root directory.
+        let ancestors = iter::successors(path.parent(), |dir| dir.parent()).collect_vec();
+        let mut untracked_dir = None;
+        for &dir in ancestors.iter().rev().skip(1) {
+            if matcher.matches(dir) && tree.sub_tree_recursive(dir)?.is_none() {
+                untracked_dir = Some(dir);
+                break;
+            }
+        }
+        match untracked_dir {
+            Some(dir) => collapsed.push((dir, true)),
+            None => collapsed.push((path, false)),
+        }
+    }
+    Ok(collapsed)
+}
+
+fn format_path(path_converter: &RepoPathUiConverter, path: &RepoPath, is_dir: bool) -> String {
+    let ui_path = path_converter.format_file_path(path);
+    if is_dir {
+        format!("{ui_path}{}", path::MAIN_SEPARATOR)
+    } else {
+        ui_path
+    }
+}
@@ -16,6 +16,7 @@ use std::slice;
 
 use jj_lib::matchers::EverythingMatcher;
 use jj_lib::object_id::ObjectId;
+use jj_lib::op_store::AbandonReason;
 use tracing::instrument;
 
 use crate::cli_util::CommandHelper;
@@ -119,6 +120,8 @@ aborted.
     // case).
     if new_parent_tree_id == parent_base_tree.id() {
         tx.repo_mut().record_abandoned_commit(&parent);
+        tx.repo_mut()
+            .set_abandon_reason(parent.id().clone(), AbandonReason::SquashedInto);
         let description = combine_messages(&text_editor, slice::from_ref(&parent), &commit)?;
         // Commit the new child on top of the parent's parents.
         tx.repo_mut()
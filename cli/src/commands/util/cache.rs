@@ -0,0 +1,71 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fs;
+use std::io;
+use std::io::Write as _;
+
+use clap::Subcommand;
+
+use crate::cli_util::CommandHelper;
+use crate::command_error::user_error_with_message;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Manage the caches of data computed from the repo
+///
+/// The cached data, such as the stats of the diffs shown by `jj log --stat`,
+/// is keyed by the contents it was computed from, so it never goes out of
+/// date. The least recently used entries are evicted when a cache grows too
+/// large.
+#[derive(Subcommand, Clone, Debug)]
+pub(crate) enum UtilCacheCommand {
+    Clear(UtilCacheClearArgs),
+}
+
+/// Remove all cached data of the repo
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct UtilCacheClearArgs {}
+
+pub(crate) fn cmd_util_cache(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    subcommand: &UtilCacheCommand,
+) -> Result<(), CommandError> {
+    match subcommand {
+        UtilCacheCommand::Clear(args) => cmd_util_cache_clear(ui, command, args),
+    }
+}
+
+fn cmd_util_cache_clear(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    _args: &UtilCacheClearArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper_no_snapshot(ui)?;
+    let cache_dir = workspace_command.cache_dir();
+    match fs::remove_dir_all(&cache_dir) {
+        Ok(()) => writeln!(ui.status(), "Removed cached data.")?,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            writeln!(ui.status(), "Nothing to remove.")?;
+        }
+        Err(err) => {
+            return Err(user_error_with_message(
+                format!("Failed to remove {}", cache_dir.display()),
+                err,
+            ));
+        }
+    }
+    Ok(())
+}
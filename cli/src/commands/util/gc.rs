@@ -12,21 +12,43 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::fs;
+use std::io::Write as _;
+use std::iter;
+use std::path::Path;
 use std::slice;
 use std::time::Duration;
 use std::time::SystemTime;
 
+use chrono::TimeZone as _;
+use itertools::Itertools as _;
+use jj_lib::backend::MillisSinceEpoch;
+use jj_lib::config::ConfigGetResultExt as _;
+use jj_lib::op_walk;
 use jj_lib::repo::Repo as _;
+use jj_lib::settings::HumanByteSize;
+use jj_lib::simple_op_store::SimpleOpStore;
+use jj_lib::time_util::DatePattern;
 
 use crate::cli_util::CommandHelper;
 use crate::command_error::user_error;
+use crate::command_error::user_error_with_message;
 use crate::command_error::CommandError;
 use crate::ui::Ui;
 
 /// Run backend-dependent garbage collection.
 ///
-/// To garbage-collect old operations and the commits/objects referenced by
-/// then, run `jj op abandon ..<some old operation>` before `jj util gc`.
+/// If `--expire` or the `gc.operation-expiry` setting is given, operations
+/// older than the expiry time are abandoned first, as if by `jj op abandon`.
+/// The current operation is always kept. Then the operations, commits, and
+/// other objects which are no longer reachable are removed, and the remaining
+/// operations are packed into fewer files. For the Git backend,
+/// this runs `git gc` on the backing Git repository.
+///
+/// The working-copy commits of all workspaces are kept since they're
+/// referenced by the current operation. Other workspaces whose working copy
+/// refers to an abandoned operation can be recovered by `jj workspace
+/// update-stale`.
 ///
 /// Previous versions of a change that are reachable via the evolution log are
 /// not garbage-collected.
@@ -34,12 +56,11 @@ use crate::ui::Ui;
 pub struct UtilGcArgs {
     /// Time threshold
     ///
-    /// By default, only obsolete objects and operations older than 2 weeks are
-    /// pruned.
-    ///
-    /// Only the string "now" can be passed to this parameter. Support for
-    /// arbitrary absolute and relative timestamps will come in a subsequent
-    /// release.
+    /// Operations and obsolete objects older than this are pruned. Accepts
+    /// "now", an absolute date such as "2024-01-31", or a relative date such as
+    /// "2 weeks ago". Defaults to the `gc.operation-expiry` setting. If neither
+    /// is set, no operations are abandoned, and only obsolete objects older
+    /// than 2 weeks are pruned.
     #[arg(long)]
     expire: Option<String>,
 }
@@ -54,16 +75,123 @@ pub fn cmd_util_gc(
             "Cannot garbage collect from a non-head operation",
         ));
     }
-    let keep_newer = match args.expire.as_deref() {
-        None => SystemTime::now() - Duration::from_secs(14 * 86400),
-        Some("now") => SystemTime::now() - Duration::ZERO,
-        _ => return Err(user_error("--expire only accepts 'now'")),
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let settings = workspace_command.settings();
+    let expire = match &args.expire {
+        Some(expire) => Some(expire.clone()),
+        None => settings.get_string("gc.operation-expiry").optional()?,
     };
-    let workspace_command = command.workspace_helper(ui)?;
+    let (expiry, keep_newer) = if let Some(expire) = &expire {
+        // Operation timestamps may be overridden, so relative dates are
+        // resolved against the same clock.
+        let now = if let Some(timestamp) = settings.operation_timestamp() {
+            chrono::Local
+                .timestamp_millis_opt(timestamp.timestamp.0)
+                .single()
+                .ok_or_else(|| user_error("Operation timestamp is out of range"))?
+        } else {
+            chrono::Local::now()
+        };
+        let expiry = if expire == "now" {
+            DatePattern::Before(MillisSinceEpoch(now.timestamp_millis()))
+        } else {
+            DatePattern::from_str_kind(expire, "before", now)
+                .map_err(|err| user_error_with_message("Invalid --expire date", err))?
+        };
+        // Files are compared by modification time, which is real clock time.
+        let (DatePattern::Before(cutoff) | DatePattern::AtOrAfter(cutoff)) = expiry;
+        let age = u64::try_from(now.timestamp_millis() - cutoff.0).unwrap_or(0);
+        let keep_newer = SystemTime::now() - Duration::from_millis(age);
+        (Some((expire, expiry)), keep_newer)
+    } else {
+        (None, SystemTime::now() - Duration::from_secs(14 * 86400))
+    };
+
+    let repo_loader = workspace_command.repo().loader().clone();
+    let op_store = repo_loader.op_store();
+    let op_heads_store = repo_loader.op_heads_store();
+    let store_paths = {
+        let repo_path = workspace_command.workspace().repo_path().to_owned();
+        #[cfg(feature = "git")]
+        let git_repo_path = jj_lib::git::get_git_backend(workspace_command.repo().store())
+            .ok()
+            .map(|git_backend| git_backend.git_repo_path().to_owned())
+            .filter(|path| !path.starts_with(&repo_path));
+        #[cfg(not(feature = "git"))]
+        let git_repo_path = None;
+        iter::once(repo_path).chain(git_repo_path).collect_vec()
+    };
+    let size_before: u64 = store_paths.iter().map(|path| dir_size(path)).sum();
+
+    // Abandon expired operations under the op heads lock so that concurrent
+    // commands can't add descendants to them in the meantime.
+    if let Some((expire, expiry)) = &expiry {
+        let _lock = op_heads_store.lock()?;
+        let current_head_ops = op_walk::get_current_head_ops(op_store, op_heads_store.as_ref())?;
+        let root_op = repo_loader.root_operation();
+        let expired_ops: Vec<_> = op_walk::walk_ancestors(&current_head_ops)
+            .filter_ok(|op| {
+                op.id() != root_op.id()
+                    && !current_head_ops.contains(op)
+                    && expiry.matches(&op.metadata().end_time)
+            })
+            .try_collect()?;
+        if !expired_ops.is_empty() {
+            let stats = op_walk::reparent_range(
+                op_store.as_ref(),
+                &expired_ops,
+                &current_head_ops,
+                &root_op,
+            )?;
+            let reparented_head_ops = || iter::zip(&current_head_ops, &stats.new_head_ids);
+            for (old, new_id) in reparented_head_ops().filter(|&(old, new_id)| old.id() != new_id) {
+                op_heads_store.update_op_heads(slice::from_ref(old.id()), new_id)?;
+            }
+            if !command.global_args().ignore_working_copy {
+                let (mut locked_ws, _) =
+                    workspace_command.unchecked_start_working_copy_mutation()?;
+                let old_op_id = locked_ws.locked_wc().old_operation_id();
+                if let Some((_, new_id)) =
+                    reparented_head_ops().find(|(old, _)| old.id() == old_op_id)
+                {
+                    locked_ws.finish(new_id.clone())?;
+                }
+            }
+            writeln!(
+                ui.status(),
+                "Abandoned {} operations older than {expire}.",
+                stats.unreachable_count,
+            )?;
+        }
+    }
 
-    let repo = workspace_command.repo();
-    repo.op_store()
-        .gc(slice::from_ref(repo.op_id()), keep_newer)?;
+    // Reload the repo so the index no longer includes the commits only
+    // reachable from the abandoned operations.
+    let repo = repo_loader.load_at_head()?;
+    op_store.gc(slice::from_ref(repo.op_id()), keep_newer)?;
+    if let Some(simple_op_store) = op_store.as_any().downcast_ref::<SimpleOpStore>() {
+        simple_op_store.pack()?;
+    }
     repo.store().gc(repo.index(), keep_newer)?;
+
+    let size_after: u64 = store_paths.iter().map(|path| dir_size(path)).sum();
+    if let Some(reclaimed) = size_before.checked_sub(size_after).filter(|&size| size > 0) {
+        writeln!(ui.status(), "Reclaimed {}.", HumanByteSize(reclaimed))?;
+    }
     Ok(())
 }
+
+/// Returns the total size of the files in the given directory.
+fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .filter_map(Result::ok)
+        .map(|entry| match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => dir_size(&entry.path()),
+            Ok(_) => entry.metadata().map_or(0, |metadata| metadata.len()),
+            Err(_) => 0,
+        })
+        .sum()
+}
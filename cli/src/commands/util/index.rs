@@ -0,0 +1,232 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write as _;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use clap::Subcommand;
+use jj_lib::default_index::AsCompositeIndex as _;
+use jj_lib::default_index::DefaultIndexStore;
+use jj_lib::default_index::DefaultReadonlyIndex;
+use jj_lib::index::IndexStore as _;
+use jj_lib::repo::RepoLoader;
+
+use crate::cli_util::CommandHelper;
+use crate::command_error::internal_error;
+use crate::command_error::user_error;
+use crate::command_error::user_error_with_hint;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Inspect and maintain the commit index
+///
+/// The commit index is stored in segment files. A new segment is usually
+/// added by each operation, and small segments are merged as new ones are
+/// added. The index is rebuilt automatically if a segment file is found to be
+/// corrupted.
+#[derive(Subcommand, Clone, Debug)]
+pub(crate) enum UtilIndexCommand {
+    Compact(UtilIndexCompactArgs),
+    Rebuild(UtilIndexRebuildArgs),
+    Stats(UtilIndexStatsArgs),
+    Verify(UtilIndexVerifyArgs),
+}
+
+/// Merge the index segments of the current operation into one file
+///
+/// Other processes can keep reading the index while it's compacted. The
+/// segment files replaced by the compaction are kept for two weeks, and then
+/// removed by a later compaction unless they are used by other operations.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct UtilIndexCompactArgs {}
+
+/// Discard the index and build it again from the operation log
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct UtilIndexRebuildArgs {}
+
+/// Show the numbers and sizes of the index segment files
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct UtilIndexStatsArgs {}
+
+/// Check that the index segments of the current operation aren't corrupted
+///
+/// Unlike other commands, the index isn't rebuilt if it's corrupted.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct UtilIndexVerifyArgs {}
+
+pub(crate) fn cmd_util_index(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    subcommand: &UtilIndexCommand,
+) -> Result<(), CommandError> {
+    match subcommand {
+        UtilIndexCommand::Compact(args) => cmd_util_index_compact(ui, command, args),
+        UtilIndexCommand::Rebuild(args) => cmd_util_index_rebuild(ui, command, args),
+        UtilIndexCommand::Stats(args) => cmd_util_index_stats(ui, command, args),
+        UtilIndexCommand::Verify(args) => cmd_util_index_verify(ui, command, args),
+    }
+}
+
+fn default_index_store<'a>(
+    repo_loader: &'a RepoLoader,
+    action: &str,
+) -> Result<&'a DefaultIndexStore, CommandError> {
+    let index_store = repo_loader.index_store();
+    index_store
+        .as_any()
+        .downcast_ref::<DefaultIndexStore>()
+        .ok_or_else(|| {
+            user_error(format!(
+                "Cannot {action} indexes of type '{}'",
+                index_store.name()
+            ))
+        })
+}
+
+fn cmd_util_index_compact(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    _args: &UtilIndexCompactArgs,
+) -> Result<(), CommandError> {
+    // Resolve the operation without loading the repo, which would update the
+    // index.
+    let workspace = command.load_workspace()?;
+    let repo_loader = workspace.repo_loader();
+    let op = command.resolve_operation(ui, repo_loader)?;
+    let index_store = default_index_store(repo_loader, "compact")?;
+    let index = index_store
+        .get_index_at_op(&op, repo_loader.store())
+        .map_err(internal_error)?;
+    let index = index
+        .as_any()
+        .downcast_ref::<DefaultReadonlyIndex>()
+        .expect("default index store should return default index");
+    let num_segments = index.as_composite().stats().levels.len();
+    if num_segments <= 1 {
+        writeln!(ui.status(), "The index is already compact.")?;
+        return Ok(());
+    }
+    // Same as the default expiry of obsolete objects in `jj util gc`
+    let keep_newer = SystemTime::now() - Duration::from_secs(14 * 86400);
+    let index = index_store
+        .compact_index_at_operation(&op, repo_loader.store(), keep_newer)
+        .map_err(internal_error)?;
+    writeln!(
+        ui.status(),
+        "Compacted {num_segments} index segments into one with {} commits.",
+        index.as_composite().num_commits()
+    )?;
+    Ok(())
+}
+
+fn cmd_util_index_rebuild(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    _args: &UtilIndexRebuildArgs,
+) -> Result<(), CommandError> {
+    // Resolve the operation without loading the repo. The index might have to
+    // be rebuilt while loading the repo.
+    let workspace = command.load_workspace()?;
+    let repo_loader = workspace.repo_loader();
+    let op = command.resolve_operation(ui, repo_loader)?;
+    let index_store = default_index_store(repo_loader, "rebuild")?;
+    index_store.reinit().map_err(internal_error)?;
+    let index = index_store
+        .build_index_at_operation(&op, repo_loader.store())
+        .map_err(internal_error)?;
+    writeln!(
+        ui.status(),
+        "Finished indexing {} commits.",
+        index.as_composite().num_commits()
+    )?;
+    Ok(())
+}
+
+fn cmd_util_index_stats(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    _args: &UtilIndexStatsArgs,
+) -> Result<(), CommandError> {
+    // Resolve the operation without loading the repo, so this command won't
+    // update the index.
+    let workspace = command.load_workspace()?;
+    let repo_loader = workspace.repo_loader();
+    let op = command.resolve_operation(ui, repo_loader)?;
+    let index_store = default_index_store(repo_loader, "get stats for")?;
+    let store_stats = index_store.stats().map_err(internal_error)?;
+    let index = index_store
+        .get_index_at_op(&op, repo_loader.store())
+        .map_err(internal_error)?;
+    let index = index
+        .as_any()
+        .downcast_ref::<DefaultReadonlyIndex>()
+        .expect("default index store should return default index");
+    let stats = index.as_composite().stats();
+    let mut formatter = ui.stdout_formatter();
+    writeln!(
+        formatter,
+        "Number of segment files: {}",
+        store_stats.num_segment_files
+    )?;
+    writeln!(
+        formatter,
+        "Total size of segment files: {} bytes",
+        store_stats.segment_files_size
+    )?;
+    writeln!(
+        formatter,
+        "Number of indexed operations: {}",
+        store_stats.num_indexed_operations
+    )?;
+    writeln!(formatter, "Number of commits: {}", stats.num_commits)?;
+    writeln!(formatter, "Segments at the operation:")?;
+    for level in &stats.levels {
+        writeln!(
+            formatter,
+            "  Number of commits in {}: {}",
+            level.name.as_deref().unwrap_or("(unnamed)"),
+            level.num_commits
+        )?;
+    }
+    Ok(())
+}
+
+fn cmd_util_index_verify(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    _args: &UtilIndexVerifyArgs,
+) -> Result<(), CommandError> {
+    // Resolve the operation without loading the repo, which would update the
+    // index.
+    let workspace = command.load_workspace()?;
+    let repo_loader = workspace.repo_loader();
+    let op = command.resolve_operation(ui, repo_loader)?;
+    let index_store = default_index_store(repo_loader, "verify")?;
+    let index = index_store
+        .verify_index_at_operation(&op, repo_loader.store())
+        .map_err(|err| {
+            user_error_with_hint(
+                err,
+                "Run `jj util index rebuild` to rebuild the index from the operation log",
+            )
+        })?;
+    writeln!(
+        ui.status(),
+        "Verified {} commits in {} index segments.",
+        index.as_composite().num_commits(),
+        index.as_composite().stats().levels.len()
+    )?;
+    Ok(())
+}
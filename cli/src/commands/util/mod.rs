@@ -12,16 +12,20 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod cache;
 mod completion;
 mod config_schema;
 mod exec;
 mod gc;
+mod index;
 mod install_man_pages;
 mod markdown_help;
 
 use clap::Subcommand;
 use tracing::instrument;
 
+use self::cache::cmd_util_cache;
+use self::cache::UtilCacheCommand;
 use self::completion::cmd_util_completion;
 use self::completion::UtilCompletionArgs;
 use self::config_schema::cmd_util_config_schema;
@@ -30,6 +34,8 @@ use self::exec::cmd_util_exec;
 use self::exec::UtilExecArgs;
 use self::gc::cmd_util_gc;
 use self::gc::UtilGcArgs;
+use self::index::cmd_util_index;
+use self::index::UtilIndexCommand;
 use self::install_man_pages::cmd_util_install_man_pages;
 use self::install_man_pages::UtilInstallManPagesArgs;
 use self::markdown_help::cmd_util_markdown_help;
@@ -41,10 +47,14 @@ use crate::ui::Ui;
 /// Infrequently used commands such as for generating shell completions
 #[derive(Subcommand, Clone, Debug)]
 pub(crate) enum UtilCommand {
+    #[command(subcommand)]
+    Cache(UtilCacheCommand),
     Completion(UtilCompletionArgs),
     ConfigSchema(UtilConfigSchemaArgs),
     Exec(UtilExecArgs),
     Gc(UtilGcArgs),
+    #[command(subcommand)]
+    Index(UtilIndexCommand),
     InstallManPages(UtilInstallManPagesArgs),
     MarkdownHelp(UtilMarkdownHelp),
 }
@@ -56,10 +66,12 @@ pub(crate) fn cmd_util(
     subcommand: &UtilCommand,
 ) -> Result<(), CommandError> {
     match subcommand {
+        UtilCommand::Cache(subcommand) => cmd_util_cache(ui, command, subcommand),
         UtilCommand::Completion(args) => cmd_util_completion(ui, command, args),
         UtilCommand::ConfigSchema(args) => cmd_util_config_schema(ui, command, args),
         UtilCommand::Exec(args) => cmd_util_exec(ui, command, args),
         UtilCommand::Gc(args) => cmd_util_gc(ui, command, args),
+        UtilCommand::Index(subcommand) => cmd_util_index(ui, command, subcommand),
         UtilCommand::InstallManPages(args) => cmd_util_install_man_pages(ui, command, args),
         UtilCommand::MarkdownHelp(args) => cmd_util_markdown_help(ui, command, args),
     }
@@ -18,8 +18,10 @@ use itertools::Itertools;
 use jj_lib::commit::CommitIteratorExt;
 use jj_lib::file_util;
 use jj_lib::file_util::IoResultExt;
+use jj_lib::op_store::RefTarget;
 use jj_lib::op_store::WorkspaceId;
 use jj_lib::repo::Repo;
+use jj_lib::revset;
 use jj_lib::rewrite::merge_commit_trees;
 use jj_lib::workspace::Workspace;
 use tracing::instrument;
@@ -70,6 +72,12 @@ pub struct WorkspaceAddArgs {
     /// new r1 r2 r3 ...`.
     #[arg(long, short, value_name = "REVSETS")]
     revision: Vec<RevisionArg>,
+    /// Create a local bookmark with this name pointing to the parent of the
+    /// new working-copy commit
+    ///
+    /// This requires the working-copy commit to have a single parent.
+    #[arg(long, short, value_parser = revset::parse_symbol)]
+    bookmark: Option<String>,
     /// How to handle sparse patterns when creating a new workspace.
     #[arg(long, value_enum, default_value_t = SparseInheritance::Copy)]
     sparse_patterns: SparseInheritance,
@@ -82,6 +90,43 @@ pub fn cmd_workspace_add(
     args: &WorkspaceAddArgs,
 ) -> Result<(), CommandError> {
     let old_workspace_command = command.workspace_helper(ui)?;
+    let repo = old_workspace_command.repo();
+
+    // If no parent revisions are specified, create a working-copy commit based
+    // on the parent of the current working-copy commit.
+    let parents = if args.revision.is_empty() {
+        // Check out parents of the current workspace's working-copy commit, or the
+        // root if there is no working-copy commit in the current workspace.
+        if let Some(old_wc_commit_id) = repo
+            .view()
+            .get_wc_commit_id(old_workspace_command.workspace_id())
+        {
+            repo.store()
+                .get_commit(old_wc_commit_id)?
+                .parents()
+                .try_collect()?
+        } else {
+            vec![repo.store().root_commit()]
+        }
+    } else {
+        old_workspace_command
+            .resolve_some_revsets_default_single(ui, &args.revision)?
+            .into_iter()
+            .collect_vec()
+    };
+    if let Some(bookmark_name) = &args.bookmark {
+        if repo.view().get_local_bookmark(bookmark_name).is_present() {
+            return Err(user_error(format!(
+                "Bookmark already exists: {bookmark_name}"
+            )));
+        }
+        if parents.len() != 1 {
+            return Err(user_error(
+                "Cannot create a bookmark for a working-copy commit with multiple parents",
+            ));
+        }
+    }
+
     let destination_path = command.cwd().join(&args.destination);
     if destination_path.exists() {
         return Err(user_error("Workspace already exists"));
@@ -99,7 +144,6 @@ pub fn cmd_workspace_add(
             .to_string()
     };
     let workspace_id = WorkspaceId::new(name.clone());
-    let repo = old_workspace_command.repo();
     if repo.view().get_wc_commit_id(&workspace_id).is_some() {
         return Err(user_error(format!(
             "Workspace named '{name}' already exists"
@@ -158,37 +202,15 @@ pub fn cmd_workspace_add(
     }
 
     let mut tx = new_workspace_command.start_transaction();
-
-    // If no parent revisions are specified, create a working-copy commit based
-    // on the parent of the current working-copy commit.
-    let parents = if args.revision.is_empty() {
-        // Check out parents of the current workspace's working-copy commit, or the
-        // root if there is no working-copy commit in the current workspace.
-        if let Some(old_wc_commit_id) = tx
-            .base_repo()
-            .view()
-            .get_wc_commit_id(old_workspace_command.workspace_id())
-        {
-            tx.repo()
-                .store()
-                .get_commit(old_wc_commit_id)?
-                .parents()
-                .try_collect()?
-        } else {
-            vec![tx.repo().store().root_commit()]
-        }
-    } else {
-        old_workspace_command
-            .resolve_some_revsets_default_single(ui, &args.revision)?
-            .into_iter()
-            .collect_vec()
-    };
-
     let tree = merge_commit_trees(tx.repo(), &parents)?;
     let parent_ids = parents.iter().ids().cloned().collect_vec();
     let new_wc_commit = tx.repo_mut().new_commit(parent_ids, tree.id()).write()?;
 
     tx.edit(&new_wc_commit)?;
+    if let Some(bookmark_name) = &args.bookmark {
+        tx.repo_mut()
+            .set_local_bookmark_target(bookmark_name, RefTarget::normal(parents[0].id().clone()));
+    }
     tx.finish(
         ui,
         format!("create initial working-copy commit in workspace {name}"),
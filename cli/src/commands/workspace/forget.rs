@@ -12,13 +12,19 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::io::Write as _;
+
 use clap_complete::ArgValueCandidates;
 use itertools::Itertools;
+use jj_lib::commit::Commit;
 use jj_lib::op_store::WorkspaceId;
+use jj_lib::repo::Repo as _;
+use jj_lib::str_util::StringPattern;
 use tracing::instrument;
 
 use crate::cli_util::CommandHelper;
 use crate::command_error::user_error;
+use crate::command_error::user_error_with_hint;
 use crate::command_error::CommandError;
 use crate::complete;
 use crate::ui::Ui;
@@ -31,8 +37,30 @@ use crate::ui::Ui;
 pub struct WorkspaceForgetArgs {
     /// Names of the workspaces to forget. By default, forgets only the current
     /// workspace.
-    #[arg(add = ArgValueCandidates::new(complete::workspaces))]
-    workspaces: Vec<String>,
+    ///
+    /// By default, the specified name matches exactly. Use `glob:` prefix to
+    /// select workspaces by [wildcard pattern]. Patterns other than exact
+    /// names never match the current workspace.
+    ///
+    /// [wildcard pattern]:
+    ///     https://jj-vcs.github.io/jj/latest/revsets/#string-patterns
+    #[arg(
+        value_parser = StringPattern::parse,
+        add = ArgValueCandidates::new(complete::workspaces),
+    )]
+    workspaces: Vec<StringPattern>,
+
+    /// Also abandon the working-copy commits of the forgotten workspaces
+    ///
+    /// A working-copy commit is abandoned only if it has no children and isn't
+    /// the working-copy commit of another workspace. Working-copy commits
+    /// with changes aren't abandoned unless `--force` is specified.
+    #[arg(long)]
+    abandon_working_copy: bool,
+
+    /// Abandon working-copy commits even if they have changes
+    #[arg(long, requires = "abandon_working_copy")]
+    force: bool,
 }
 
 #[instrument(skip_all)]
@@ -42,25 +70,57 @@ pub fn cmd_workspace_forget(
     args: &WorkspaceForgetArgs,
 ) -> Result<(), CommandError> {
     let mut workspace_command = command.workspace_helper(ui)?;
+    let repo = workspace_command.repo().clone();
 
     let wss: Vec<WorkspaceId> = if args.workspaces.is_empty() {
         vec![workspace_command.workspace_id().clone()]
     } else {
-        args.workspaces
-            .iter()
-            .map(|ws| WorkspaceId::new(ws.to_string()))
-            .collect()
+        find_workspaces(
+            repo.view().wc_commit_ids().keys(),
+            workspace_command.workspace_id(),
+            &args.workspaces,
+        )?
     };
 
-    for ws in &wss {
-        if workspace_command
-            .repo()
+    let mut to_abandon: Vec<Commit> = vec![];
+    if args.abandon_working_copy {
+        let remaining_wc_commit_ids = repo
             .view()
-            .get_wc_commit_id(ws)
-            .is_none()
-        {
-            return Err(user_error(format!("No such workspace: {}", ws.as_str())));
+            .wc_commit_ids()
+            .iter()
+            .filter(|(ws, _)| !wss.contains(ws))
+            .map(|(_, id)| id)
+            .collect_vec();
+        for ws in &wss {
+            let wc_commit_id = repo.view().get_wc_commit_id(ws).unwrap();
+            if remaining_wc_commit_ids.contains(&wc_commit_id)
+                || to_abandon.iter().any(|commit| commit.id() == wc_commit_id)
+            {
+                continue;
+            }
+            if !repo.view().heads().contains(wc_commit_id) {
+                writeln!(
+                    ui.warning_default(),
+                    "Not abandoning the working-copy commit of workspace {} because it has \
+                     children",
+                    ws.as_str()
+                )?;
+                continue;
+            }
+            let wc_commit = repo.store().get_commit(wc_commit_id)?;
+            if !args.force && !wc_commit.is_empty(repo.as_ref())? {
+                return Err(user_error_with_hint(
+                    format!(
+                        "Refusing to abandon the working-copy commit of workspace {} because it \
+                         has changes",
+                        ws.as_str()
+                    ),
+                    "Use --force to abandon it anyway.",
+                ));
+            }
+            to_abandon.push(wc_commit);
         }
+        workspace_command.check_rewritable(to_abandon.iter().map(|commit| commit.id()))?;
     }
 
     // bundle every workspace forget into a single transaction, so that e.g.
@@ -68,6 +128,20 @@ pub fn cmd_workspace_forget(
     let mut tx = workspace_command.start_transaction();
     wss.iter()
         .try_for_each(|ws| tx.repo_mut().remove_wc_commit(ws))?;
+    for commit in &to_abandon {
+        tx.repo_mut().record_abandoned_commit(commit);
+    }
+    if let Some(mut formatter) = ui.status_formatter() {
+        if !to_abandon.is_empty() {
+            let template = tx.base_workspace_helper().commit_summary_template();
+            writeln!(formatter, "Abandoned working-copy commits:")?;
+            for commit in &to_abandon {
+                write!(formatter, "  ")?;
+                template.format(commit, formatter.as_mut())?;
+                writeln!(formatter)?;
+            }
+        }
+    }
     let description = if let [ws] = wss.as_slice() {
         format!("forget workspace {}", ws.as_str())
     } else {
@@ -80,3 +154,35 @@ pub fn cmd_workspace_forget(
     tx.finish(ui, description)?;
     Ok(())
 }
+
+fn find_workspaces<'a>(
+    workspace_ids: impl Iterator<Item = &'a WorkspaceId> + Clone,
+    current_workspace_id: &WorkspaceId,
+    patterns: &[StringPattern],
+) -> Result<Vec<WorkspaceId>, CommandError> {
+    let mut matching_workspaces = vec![];
+    let mut unmatched_patterns = vec![];
+    for pattern in patterns {
+        let mut matches = workspace_ids
+            .clone()
+            .filter(|ws| pattern.is_exact() || *ws != current_workspace_id)
+            .filter(|ws| pattern.matches(ws.as_str()))
+            .peekable();
+        if matches.peek().is_none() {
+            unmatched_patterns.push(pattern);
+        }
+        matching_workspaces.extend(matches.cloned());
+    }
+    match &unmatched_patterns[..] {
+        [] => {
+            matching_workspaces.sort_unstable();
+            matching_workspaces.dedup();
+            Ok(matching_workspaces)
+        }
+        [pattern] if pattern.is_exact() => Err(user_error(format!("No such workspace: {pattern}"))),
+        patterns => Err(user_error(format!(
+            "No matching workspaces for patterns: {}",
+            patterns.iter().join(", ")
+        ))),
+    }
+}
@@ -12,33 +12,162 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::io::Write as _;
+
+use clap_complete::ArgValueCandidates;
 use itertools::Itertools;
+use jj_lib::object_id::ObjectId as _;
 use jj_lib::repo::Repo;
 use tracing::instrument;
 
 use crate::cli_util::CommandHelper;
 use crate::command_error::CommandError;
+use crate::commit_templater::CommitTemplateLanguage;
+use crate::commit_templater::WorkspaceRef;
+use crate::complete;
 use crate::ui::Ui;
 
 /// List workspaces
+///
+/// The root path of a workspace is recorded when the workspace is created on
+/// this machine, and updated when jj runs in it after it has been moved. A
+/// workspace is stale if its working copy hasn't been updated to its
+/// working-copy commit, which can only be detected if its root path is known.
 #[derive(clap::Args, Clone, Debug)]
-pub struct WorkspaceListArgs {}
+pub struct WorkspaceListArgs {
+    /// Render each workspace using the given template
+    ///
+    /// All 0-argument methods of the [`WorkspaceRef` type] are available as
+    /// keywords in the [template expression].
+    ///
+    /// If not specified, the workspace name and its working-copy commit are
+    /// shown, followed by "(stale)" if the workspace is stale and the root
+    /// path of the workspace if known.
+    ///
+    /// [template expression]:
+    ///     https://jj-vcs.github.io/jj/latest/templates/
+    ///
+    /// [`WorkspaceRef` type]:
+    ///     https://jj-vcs.github.io/jj/latest/templates/#workspaceref-type
+    #[arg(long, short = 'T', add = ArgValueCandidates::new(complete::template_aliases))]
+    template: Option<String>,
+    /// Print the workspaces as JSON
+    ///
+    /// The output is an array with an object per workspace, with the fields
+    /// `name`, `root`, `root_verified`, `commit_id`, `change_id`, and `stale`.
+    /// `root` is `null` if the root path isn't known. `root_verified` is false
+    /// if the workspace wasn't found at the recorded root path, e.g. because
+    /// it has been moved or deleted since jj last ran in it. `stale` is `null`
+    /// if it can't be determined.
+    #[arg(long, conflicts_with = "template")]
+    json: bool,
+}
 
 #[instrument(skip_all)]
 pub fn cmd_workspace_list(
     ui: &mut Ui,
     command: &CommandHelper,
-    _args: &WorkspaceListArgs,
+    args: &WorkspaceListArgs,
 ) -> Result<(), CommandError> {
     let workspace_command = command.workspace_helper(ui)?;
     let repo = workspace_command.repo();
-    let mut formatter = ui.stdout_formatter();
-    let template = workspace_command.commit_summary_template();
+    let workspace_store = workspace_command.workspace().workspace_store();
+    let mut workspaces = vec![];
     for (workspace_id, wc_commit_id) in repo.view().wc_commit_ids().iter().sorted() {
-        write!(formatter, "{}: ", workspace_id.as_str())?;
-        let commit = repo.store().get_commit(wc_commit_id)?;
-        template.format(&commit, formatter.as_mut())?;
-        writeln!(formatter)?;
+        let target = repo.store().get_commit(wc_commit_id)?;
+        let (root, root_verified, stale) = if workspace_id == workspace_command.workspace_id() {
+            let wc_tree_id = workspace_command.working_copy().tree_id()?;
+            let root = workspace_command.workspace_root().to_owned();
+            (Some(root), true, Some(wc_tree_id != target.tree_id()))
+        } else if let Some(root) = workspace_store.get_workspace_path(workspace_id)? {
+            // The workspace might have been moved or deleted since jj last ran
+            // in it, in which case we can't tell if it's stale.
+            let workspace = command
+                .load_workspace_at(&root, workspace_command.settings())
+                .ok()
+                .filter(|workspace| workspace.workspace_id() == workspace_id);
+            let root_verified = workspace.is_some();
+            let stale = workspace.and_then(|workspace| {
+                let wc_tree_id = workspace.working_copy().tree_id().ok()?;
+                Some(wc_tree_id != target.tree_id())
+            });
+            (Some(root), root_verified, stale)
+        } else {
+            (None, false, None)
+        };
+        workspaces.push(WorkspaceEntry {
+            workspace: WorkspaceRef {
+                name: workspace_id.as_str().to_owned(),
+                target,
+                root,
+                stale: stale.unwrap_or(false),
+            },
+            root_verified,
+            stale,
+        });
     }
+
+    if args.json {
+        return print_workspaces_json(ui, &workspaces);
+    }
+
+    let mut formatter = ui.stdout_formatter();
+    if let Some(template_text) = &args.template {
+        let language = workspace_command.commit_template_language();
+        let template = workspace_command.parse_template(
+            ui,
+            &language,
+            template_text,
+            CommitTemplateLanguage::wrap_workspace_ref,
+        )?;
+        for WorkspaceEntry { workspace, .. } in &workspaces {
+            template.format(workspace, formatter.as_mut())?;
+        }
+    } else {
+        let template = workspace_command.commit_summary_template();
+        for WorkspaceEntry { workspace, .. } in &workspaces {
+            write!(formatter, "{}: ", workspace.name)?;
+            template.format(&workspace.target, formatter.as_mut())?;
+            if workspace.stale {
+                write!(formatter.labeled("stale"), " (stale)")?;
+            }
+            if let Some(root) = &workspace.root {
+                write!(formatter, " {}", root.display())?;
+            }
+            writeln!(formatter)?;
+        }
+    }
+    Ok(())
+}
+
+struct WorkspaceEntry {
+    workspace: WorkspaceRef,
+    /// Whether the workspace was found at its root path.
+    root_verified: bool,
+    /// Whether the workspace is stale, or `None` if it can't be determined.
+    stale: Option<bool>,
+}
+
+fn print_workspaces_json(ui: &mut Ui, entries: &[WorkspaceEntry]) -> Result<(), CommandError> {
+    let entries = entries
+        .iter()
+        .map(|entry| {
+            let WorkspaceEntry {
+                workspace,
+                root_verified,
+                stale,
+            } = entry;
+            serde_json::json!({
+                "name": workspace.name,
+                "root": workspace.root.as_ref().map(|root| root.to_string_lossy()),
+                "root_verified": root_verified,
+                "commit_id": workspace.target.id().hex(),
+                "change_id": workspace.target.change_id().reverse_hex(),
+                "stale": stale,
+            })
+        })
+        .collect_vec();
+    let text = serde_json::to_string_pretty(&entries).unwrap();
+    writeln!(ui.stdout(), "{text}")?;
     Ok(())
 }
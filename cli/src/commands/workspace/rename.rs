@@ -12,19 +12,33 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use clap_complete::ArgValueCandidates;
+use jj_lib::object_id::ObjectId as _;
 use jj_lib::op_store::WorkspaceId;
+use jj_lib::repo::Repo as _;
 use tracing::instrument;
 
+use crate::cli_util::print_checkout_stats;
 use crate::cli_util::CommandHelper;
+use crate::command_error::internal_error_with_message;
 use crate::command_error::user_error;
 use crate::command_error::CommandError;
+use crate::complete;
 use crate::ui::Ui;
 
-/// Renames the current workspace
+/// Renames a workspace
+///
+/// By default, the current workspace is renamed. When renaming another
+/// workspace whose location isn't known, only the repo is updated. Its working
+/// copy will pick up the new name when `jj workspace rename
+/// <NEW_WORKSPACE_NAME>` is run in it.
 #[derive(clap::Args, Clone, Debug)]
 pub struct WorkspaceRenameArgs {
     /// The name of the workspace to update to.
     new_workspace_name: String,
+    /// The workspace to rename (default: the current workspace)
+    #[arg(long, add = ArgValueCandidates::new(complete::workspaces))]
+    workspace: Option<String>,
 }
 
 #[instrument(skip_all)]
@@ -38,26 +52,137 @@ pub fn cmd_workspace_rename(
     }
 
     let mut workspace_command = command.workspace_helper(ui)?;
+    let workspace_store = workspace_command.workspace().workspace_store();
 
-    let old_workspace_id = workspace_command.working_copy().workspace_id().clone();
+    let current_workspace_id = workspace_command.working_copy().workspace_id().clone();
+    let old_workspace_id = match &args.workspace {
+        Some(name) => WorkspaceId::new(name.clone()),
+        None => current_workspace_id.clone(),
+    };
     let new_workspace_id = WorkspaceId::new(args.new_workspace_name.clone());
     if new_workspace_id == old_workspace_id {
         writeln!(ui.status(), "Nothing changed.")?;
         return Ok(());
     }
 
-    if workspace_command
-        .repo()
-        .view()
-        .get_wc_commit_id(&old_workspace_id)
-        .is_none()
-    {
+    let view = workspace_command.repo().view();
+    if view.get_wc_commit_id(&old_workspace_id).is_none() {
+        if old_workspace_id != current_workspace_id {
+            return Err(user_error(format!(
+                "No such workspace: {}",
+                old_workspace_id.as_str()
+            )));
+        }
+        // The workspace may have been renamed from another workspace. If so,
+        // only the working copy needs to be updated.
+        if let Some(new_wc_commit_id) = view.get_wc_commit_id(&new_workspace_id) {
+            let repo = workspace_command.repo().clone();
+            workspace_command.check_working_copy_writable()?;
+            let checkout_options = workspace_command.checkout_options();
+            let mut workspace = command.load_workspace()?;
+            // The working copy must be checked out from the store of the newly
+            // loaded workspace.
+            let new_wc_commit = workspace
+                .repo_loader()
+                .store()
+                .get_commit(new_wc_commit_id)?;
+            let mut locked_ws = workspace.start_working_copy_mutation()?;
+            locked_ws.locked_wc().rename_workspace(new_workspace_id);
+            // The working-copy commit may have been rewritten since the
+            // working copy was last updated. Check it out like
+            // `jj workspace update-stale` would.
+            let stats = if new_wc_commit.tree_id() != locked_ws.locked_wc().old_tree_id() {
+                let stats = locked_ws
+                    .locked_wc()
+                    .check_out(&new_wc_commit, &checkout_options)
+                    .map_err(|err| {
+                        internal_error_with_message(
+                            format!("Failed to check out commit {}", new_wc_commit.id().hex()),
+                            err,
+                        )
+                    })?;
+                Some(stats)
+            } else {
+                None
+            };
+            locked_ws.finish(repo.op_id().clone())?;
+            writeln!(
+                ui.status(),
+                "Updated working copy to workspace name '{}'",
+                args.new_workspace_name
+            )?;
+            if let Some(stats) = stats {
+                print_checkout_stats(
+                    ui,
+                    stats,
+                    &new_wc_commit,
+                    workspace_command.path_converter(),
+                )?;
+            }
+            return Ok(());
+        }
         return Err(user_error(format!(
             "The current workspace '{}' is not tracked in the repo.",
             old_workspace_id.as_str()
         )));
     }
 
+    if old_workspace_id != current_workspace_id {
+        // Update the working copy of the other workspace too if we know where
+        // it is and it's up to date.
+        let mut other_workspace = workspace_store
+            .get_workspace_path(&old_workspace_id)?
+            .and_then(|path| {
+                command
+                    .load_workspace_at(&path, workspace_command.settings())
+                    .ok()
+            })
+            .filter(|workspace| workspace.working_copy().workspace_id() == &old_workspace_id);
+        let mut other_locked_ws = other_workspace
+            .as_mut()
+            .map(|workspace| workspace.start_working_copy_mutation())
+            .transpose()?;
+        let old_wc_commit_id = view.get_wc_commit_id(&old_workspace_id).unwrap().clone();
+        let old_wc_commit = workspace_command
+            .repo()
+            .store()
+            .get_commit(&old_wc_commit_id)?;
+        if other_locked_ws
+            .as_mut()
+            .is_some_and(|locked_ws| locked_ws.locked_wc().old_tree_id() != old_wc_commit.tree_id())
+        {
+            other_locked_ws = None;
+        }
+
+        let mut tx = workspace_command.start_transaction().into_inner();
+        tx.repo_mut()
+            .rename_workspace(&old_workspace_id, new_workspace_id.clone())?;
+        workspace_store.rename(&old_workspace_id, &new_workspace_id)?;
+        let repo = tx.commit(format!(
+            "Renamed workspace '{}' to '{}'",
+            old_workspace_id.as_str(),
+            args.new_workspace_name
+        ))?;
+        if let Some(mut locked_ws) = other_locked_ws {
+            locked_ws
+                .locked_wc()
+                .rename_workspace(new_workspace_id.clone());
+            locked_ws.finish(repo.op_id().clone())?;
+        } else {
+            writeln!(
+                ui.warning_default(),
+                "The working copy of workspace '{}' still uses the old name",
+                args.new_workspace_name
+            )?;
+            writeln!(
+                ui.hint_default(),
+                "Run `jj workspace rename {}` in that workspace to update it",
+                args.new_workspace_name
+            )?;
+        }
+        return Ok(());
+    }
+
     let mut tx = workspace_command.start_transaction().into_inner();
     let (mut locked_ws, _wc_commit) = workspace_command.start_working_copy_mutation()?;
 
@@ -66,7 +191,8 @@ pub fn cmd_workspace_rename(
         .rename_workspace(new_workspace_id.clone());
 
     tx.repo_mut()
-        .rename_workspace(&old_workspace_id, new_workspace_id)?;
+        .rename_workspace(&old_workspace_id, new_workspace_id.clone())?;
+    workspace_store.rename(&old_workspace_id, &new_workspace_id)?;
     let repo = tx.commit(format!(
         "Renamed workspace '{}' to '{}'",
         old_workspace_id.as_str(),
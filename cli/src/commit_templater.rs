@@ -16,7 +16,9 @@ use std::any::Any;
 use std::cmp::max;
 use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::io;
+use std::path::PathBuf;
 use std::rc::Rc;
 
 use bstr::BString;
@@ -35,6 +37,7 @@ use jj_lib::copies::CopiesTreeDiffEntryPath;
 use jj_lib::copies::CopyRecords;
 use jj_lib::extensions_map::ExtensionsMap;
 use jj_lib::fileset;
+use jj_lib::fileset::FilesetAliasesMap;
 use jj_lib::fileset::FilesetDiagnostics;
 use jj_lib::fileset::FilesetExpression;
 use jj_lib::id_prefix::IdPrefixContext;
@@ -65,6 +68,7 @@ use jj_lib::store::Store;
 use once_cell::unsync::OnceCell;
 use pollster::FutureExt as _;
 
+use crate::diff_stat_cache::DiffStatCache;
 use crate::diff_util;
 use crate::diff_util::DiffStats;
 use crate::formatter::Formatter;
@@ -102,6 +106,7 @@ pub trait CommitTemplateLanguageExtension {
 pub struct CommitTemplateLanguage<'repo> {
     repo: &'repo dyn Repo,
     path_converter: &'repo RepoPathUiConverter,
+    fileset_aliases_map: &'repo FilesetAliasesMap,
     workspace_id: WorkspaceId,
     // RevsetParseContext doesn't borrow a repo, but we'll need 'repo lifetime
     // anyway to capture it to evaluate dynamically-constructed user expression
@@ -112,6 +117,7 @@ pub struct CommitTemplateLanguage<'repo> {
     id_prefix_context: &'repo IdPrefixContext,
     immutable_expression: Rc<UserRevsetExpression>,
     conflict_marker_style: ConflictMarkerStyle,
+    diff_stat_cache: &'repo DiffStatCache,
     build_fn_table: CommitTemplateBuildFnTable<'repo>,
     keyword_cache: CommitKeywordCache<'repo>,
     cache_extensions: ExtensionsMap,
@@ -124,11 +130,13 @@ impl<'repo> CommitTemplateLanguage<'repo> {
     pub fn new(
         repo: &'repo dyn Repo,
         path_converter: &'repo RepoPathUiConverter,
+        fileset_aliases_map: &'repo FilesetAliasesMap,
         workspace_id: &WorkspaceId,
         revset_parse_context: RevsetParseContext<'repo>,
         id_prefix_context: &'repo IdPrefixContext,
         immutable_expression: Rc<UserRevsetExpression>,
         conflict_marker_style: ConflictMarkerStyle,
+        diff_stat_cache: &'repo DiffStatCache,
         extensions: &[impl AsRef<dyn CommitTemplateLanguageExtension>],
     ) -> Self {
         let mut build_fn_table = CommitTemplateBuildFnTable::builtin();
@@ -144,16 +152,23 @@ impl<'repo> CommitTemplateLanguage<'repo> {
         CommitTemplateLanguage {
             repo,
             path_converter,
+            fileset_aliases_map,
             workspace_id: workspace_id.clone(),
             revset_parse_context,
             id_prefix_context,
             immutable_expression,
             conflict_marker_style,
+            diff_stat_cache,
             build_fn_table,
             keyword_cache: CommitKeywordCache::default(),
             cache_extensions,
         }
     }
+
+    /// Replaces the converter used to format and parse file paths.
+    pub fn set_path_converter(&mut self, path_converter: &'repo RepoPathUiConverter) {
+        self.path_converter = path_converter;
+    }
 }
 
 impl<'repo> TemplateLanguage<'repo> for CommitTemplateLanguage<'repo> {
@@ -313,6 +328,13 @@ impl<'repo> TemplateLanguage<'repo> for CommitTemplateLanguage<'repo> {
             }
             CommitTemplatePropertyKind::CryptographicSignatureOpt(property) => {
                 let type_name = "CryptographicSignature";
+                // Presence is tested on the optional value, so it doesn't fail
+                // for unsigned commits and doesn't verify the signature.
+                if function.name == "present" {
+                    function.expect_no_arguments()?;
+                    let out_property = property.map(|sig| sig.is_some());
+                    return Ok(Self::wrap_boolean(out_property));
+                }
                 let table = &self.build_fn_table.cryptographic_signature_methods;
                 let build = template_parser::lookup_method(type_name, table, function)?;
                 let inner_property = property.try_unwrap(type_name);
@@ -330,6 +352,11 @@ impl<'repo> TemplateLanguage<'repo> for CommitTemplateLanguage<'repo> {
                 let build = template_parser::lookup_method(type_name, table, function)?;
                 build(self, diagnostics, build_ctx, property, function)
             }
+            CommitTemplatePropertyKind::WorkspaceRef(property) => {
+                let table = &self.build_fn_table.workspace_ref_methods;
+                let build = template_parser::lookup_method(type_name, table, function)?;
+                build(self, diagnostics, build_ctx, property, function)
+            }
         }
     }
 }
@@ -454,6 +481,12 @@ impl<'repo> CommitTemplateLanguage<'repo> {
     ) -> CommitTemplatePropertyKind<'repo> {
         CommitTemplatePropertyKind::AnnotationLine(Box::new(property))
     }
+
+    pub fn wrap_workspace_ref(
+        property: impl TemplateProperty<Output = WorkspaceRef> + 'repo,
+    ) -> CommitTemplatePropertyKind<'repo> {
+        CommitTemplatePropertyKind::WorkspaceRef(Box::new(property))
+    }
 }
 
 pub enum CommitTemplatePropertyKind<'repo> {
@@ -477,6 +510,7 @@ pub enum CommitTemplatePropertyKind<'repo> {
         Box<dyn TemplateProperty<Output = Option<CryptographicSignature>> + 'repo>,
     ),
     AnnotationLine(Box<dyn TemplateProperty<Output = AnnotationLine> + 'repo>),
+    WorkspaceRef(Box<dyn TemplateProperty<Output = WorkspaceRef> + 'repo>),
 }
 
 impl<'repo> IntoTemplateProperty<'repo> for CommitTemplatePropertyKind<'repo> {
@@ -502,6 +536,7 @@ impl<'repo> IntoTemplateProperty<'repo> for CommitTemplatePropertyKind<'repo> {
                 "Option<CryptographicSignature>"
             }
             CommitTemplatePropertyKind::AnnotationLine(_) => "AnnotationLine",
+            CommitTemplatePropertyKind::WorkspaceRef(_) => "WorkspaceRef",
         }
     }
 
@@ -541,6 +576,7 @@ impl<'repo> IntoTemplateProperty<'repo> for CommitTemplatePropertyKind<'repo> {
                 Some(Box::new(property.map(|sig| sig.is_some())))
             }
             CommitTemplatePropertyKind::AnnotationLine(_) => None,
+            CommitTemplatePropertyKind::WorkspaceRef(_) => None,
         }
     }
 
@@ -585,6 +621,7 @@ impl<'repo> IntoTemplateProperty<'repo> for CommitTemplatePropertyKind<'repo> {
             CommitTemplatePropertyKind::DiffStats(property) => Some(property.into_template()),
             CommitTemplatePropertyKind::CryptographicSignatureOpt(_) => None,
             CommitTemplatePropertyKind::AnnotationLine(_) => None,
+            CommitTemplatePropertyKind::WorkspaceRef(_) => None,
         }
     }
 
@@ -611,6 +648,7 @@ impl<'repo> IntoTemplateProperty<'repo> for CommitTemplatePropertyKind<'repo> {
             (CommitTemplatePropertyKind::DiffStats(_), _) => None,
             (CommitTemplatePropertyKind::CryptographicSignatureOpt(_), _) => None,
             (CommitTemplatePropertyKind::AnnotationLine(_), _) => None,
+            (CommitTemplatePropertyKind::WorkspaceRef(_), _) => None,
         }
     }
 
@@ -640,6 +678,7 @@ impl<'repo> IntoTemplateProperty<'repo> for CommitTemplatePropertyKind<'repo> {
             (CommitTemplatePropertyKind::DiffStats(_), _) => None,
             (CommitTemplatePropertyKind::CryptographicSignatureOpt(_), _) => None,
             (CommitTemplatePropertyKind::AnnotationLine(_), _) => None,
+            (CommitTemplatePropertyKind::WorkspaceRef(_), _) => None,
         }
     }
 }
@@ -663,6 +702,7 @@ pub struct CommitTemplateBuildFnTable<'repo> {
     pub cryptographic_signature_methods:
         CommitTemplateBuildMethodFnMap<'repo, CryptographicSignature>,
     pub annotation_line_methods: CommitTemplateBuildMethodFnMap<'repo, AnnotationLine>,
+    pub workspace_ref_methods: CommitTemplateBuildMethodFnMap<'repo, WorkspaceRef>,
 }
 
 impl<'repo> CommitTemplateBuildFnTable<'repo> {
@@ -681,6 +721,7 @@ impl<'repo> CommitTemplateBuildFnTable<'repo> {
             diff_stats_methods: builtin_diff_stats_methods(),
             cryptographic_signature_methods: builtin_cryptographic_signature_methods(),
             annotation_line_methods: builtin_annotation_line_methods(),
+            workspace_ref_methods: builtin_workspace_ref_methods(),
         }
     }
 
@@ -698,6 +739,7 @@ impl<'repo> CommitTemplateBuildFnTable<'repo> {
             diff_stats_methods: HashMap::new(),
             cryptographic_signature_methods: HashMap::new(),
             annotation_line_methods: HashMap::new(),
+            workspace_ref_methods: HashMap::new(),
         }
     }
 
@@ -715,6 +757,7 @@ impl<'repo> CommitTemplateBuildFnTable<'repo> {
             diff_stats_methods,
             cryptographic_signature_methods,
             annotation_line_methods,
+            workspace_ref_methods,
         } = extension;
 
         self.core.merge(core);
@@ -738,6 +781,7 @@ impl<'repo> CommitTemplateBuildFnTable<'repo> {
             cryptographic_signature_methods,
         );
         merge_fn_map(&mut self.annotation_line_methods, annotation_line_methods);
+        merge_fn_map(&mut self.workspace_ref_methods, workspace_ref_methods);
     }
 }
 
@@ -1012,6 +1056,16 @@ fn builtin_commit_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, Comm
             Ok(L::wrap_boolean(out_property))
         },
     );
+    map.insert(
+        "superseded",
+        |language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let repo = language.repo;
+            let out_property =
+                self_property.map(|commit| repo.view().is_deferred_rewrite(commit.id()));
+            Ok(L::wrap_boolean(out_property))
+        },
+    );
     map.insert(
         "immutable",
         |language, _diagnostics, _build_ctx, self_property, function| {
@@ -1060,14 +1114,23 @@ fn builtin_commit_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, Comm
         |language, diagnostics, _build_ctx, self_property, function| {
             let ([], [files_node]) = function.expect_arguments()?;
             let files = if let Some(node) = files_node {
-                expect_fileset_literal(diagnostics, node, language.path_converter)?
+                expect_fileset_literal(
+                    diagnostics,
+                    node,
+                    language.fileset_aliases_map,
+                    language.path_converter,
+                )?
             } else {
                 // TODO: defaults to CLI path arguments?
                 // https://github.com/jj-vcs/jj/issues/2933#issuecomment-1925870731
                 FilesetExpression::all()
             };
+            let matcher = files.to_matcher().map_err(|err| {
+                TemplateParseError::expression("In fileset expression", function.args_span)
+                    .with_source(err)
+            })?;
             let repo = language.repo;
-            let matcher: Rc<dyn Matcher> = files.to_matcher().into();
+            let matcher: Rc<dyn Matcher> = matcher.into();
             let out_property = self_property
                 .and_then(move |commit| Ok(TreeDiff::from_commit(repo, &commit, matcher.clone())?));
             Ok(L::wrap_tree_diff(out_property))
@@ -1104,12 +1167,13 @@ fn extract_working_copies(repo: &dyn Repo, commit: &Commit) -> String {
 fn expect_fileset_literal(
     diagnostics: &mut TemplateDiagnostics,
     node: &ExpressionNode,
+    aliases_map: &FilesetAliasesMap,
     path_converter: &RepoPathUiConverter,
 ) -> Result<FilesetExpression, TemplateParseError> {
     template_parser::expect_string_literal_with(node, |text, span| {
         let mut inner_diagnostics = FilesetDiagnostics::new();
-        let expression =
-            fileset::parse(&mut inner_diagnostics, text, path_converter).map_err(|err| {
+        let expression = fileset::parse(&mut inner_diagnostics, text, aliases_map, path_converter)
+            .map_err(|err| {
                 TemplateParseError::expression("In fileset expression", span).with_source(err)
             })?;
         diagnostics.extend_with(inner_diagnostics, |diag| {
@@ -1838,6 +1902,7 @@ fn builtin_tree_diff_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, T
                     let message = "Failed to load diff settings";
                     TemplateParseError::expression(message, function.name_span).with_source(err)
                 })?;
+            let prefetch_concurrency = prefetch_concurrency_from_settings(language, function)?;
             let conflict_marker_style = language.conflict_marker_style;
             let template = (self_property, context_property)
                 .map(move |(diff, context)| {
@@ -1853,6 +1918,7 @@ fn builtin_tree_diff_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, T
                             path_converter,
                             &options,
                             conflict_marker_style,
+                            prefetch_concurrency,
                         )
                     })
                 })
@@ -1879,6 +1945,7 @@ fn builtin_tree_diff_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, T
                     let message = "Failed to load diff settings";
                     TemplateParseError::expression(message, function.name_span).with_source(err)
                 })?;
+            let prefetch_concurrency = prefetch_concurrency_from_settings(language, function)?;
             let conflict_marker_style = language.conflict_marker_style;
             let template = (self_property, context_property)
                 .map(move |(diff, context)| {
@@ -1893,6 +1960,7 @@ fn builtin_tree_diff_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, T
                             tree_diff,
                             &options,
                             conflict_marker_style,
+                            prefetch_concurrency,
                         )
                     })
                 })
@@ -1917,13 +1985,21 @@ fn builtin_tree_diff_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, T
             let path_converter = language.path_converter;
             // No user configuration exists for diff stat.
             let options = diff_util::DiffStatOptions::default();
+            let prefetch_concurrency = prefetch_concurrency_from_settings(language, function)?;
             let conflict_marker_style = language.conflict_marker_style;
-            // TODO: cache and reuse stats within the current evaluation?
+            let diff_stat_cache = language.diff_stat_cache;
             let out_property = (self_property, width_property).and_then(move |(diff, width)| {
                 let store = diff.from_tree.store();
                 let tree_diff = diff.diff_stream();
-                let stats = DiffStats::calculate(store, tree_diff, &options, conflict_marker_style)
-                    .block_on()?;
+                let stats = DiffStats::calculate(
+                    store,
+                    tree_diff,
+                    &options,
+                    conflict_marker_style,
+                    Some(diff_stat_cache),
+                    prefetch_concurrency,
+                )
+                .block_on()?;
                 Ok(DiffStatsFormatted {
                     stats,
                     path_converter,
@@ -1953,6 +2029,17 @@ fn builtin_tree_diff_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, T
     map
 }
 
+/// Loads the number of files to read ahead when rendering diffs.
+fn prefetch_concurrency_from_settings(
+    language: &CommitTemplateLanguage,
+    function: &FunctionCallNode,
+) -> TemplateParseResult<Option<usize>> {
+    diff_util::prefetch_concurrency_from_settings(language.settings()).map_err(|err| {
+        let message = "Failed to load diff settings";
+        TemplateParseError::expression(message, function.name_span).with_source(err)
+    })
+}
+
 /// [`MergedTree`] diff entry.
 #[derive(Clone, Debug)]
 pub struct TreeDiffEntry {
@@ -2083,6 +2170,31 @@ fn builtin_tree_entry_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo,
             Ok(L::wrap_boolean(out_property))
         },
     );
+    map.insert(
+        "sides",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property
+                .and_then(|entry| Ok(entry.value.simplify().num_sides().try_into()?));
+            Ok(L::wrap_integer(out_property))
+        },
+    );
+    map.insert(
+        "involves_deletion",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.map(|entry| conflict_involves_deletion(&entry.value));
+            Ok(L::wrap_boolean(out_property))
+        },
+    );
+    map.insert(
+        "involves_modes",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.map(|entry| conflict_involves_modes(&entry.value));
+            Ok(L::wrap_boolean(out_property))
+        },
+    );
     map
 }
 
@@ -2102,6 +2214,30 @@ fn is_executable_file(value: &MergedTreeValue) -> Option<bool> {
     executable.resolve_trivial().copied()
 }
 
+/// Returns true if the value is a conflict in which a side deleted the path.
+pub fn conflict_involves_deletion(value: &MergedTreeValue) -> bool {
+    let value = value.clone().simplify();
+    !value.is_resolved() && value.adds().any(Option::is_none)
+}
+
+/// Returns true if the value is a conflict between terms of different types or
+/// executable bits, or between terms which aren't regular files.
+pub fn conflict_involves_modes(value: &MergedTreeValue) -> bool {
+    let value = value.clone().simplify();
+    if value.is_resolved() {
+        return false;
+    }
+    let modes: HashSet<_> = value
+        .iter()
+        .flatten()
+        .map(|term| match term {
+            TreeValue::File { executable, .. } => Some(*executable),
+            _ => None,
+        })
+        .collect();
+    modes.len() > 1 || modes.contains(&None)
+}
+
 /// [`DiffStats`] with rendering parameters.
 #[derive(Clone, Debug)]
 pub struct DiffStatsFormatted<'a> {
@@ -2266,3 +2402,54 @@ pub fn builtin_annotation_line_methods<'repo>(
     );
     map
 }
+
+/// Workspace and its working-copy commit.
+#[derive(Debug, Clone)]
+pub struct WorkspaceRef {
+    pub name: String,
+    pub target: Commit,
+    /// Root path of the workspace if known.
+    pub root: Option<PathBuf>,
+    /// Whether the working copy isn't updated to the working-copy commit.
+    pub stale: bool,
+}
+
+pub fn builtin_workspace_ref_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, WorkspaceRef>
+{
+    type L<'repo> = CommitTemplateLanguage<'repo>;
+    let mut map = CommitTemplateBuildMethodFnMap::<WorkspaceRef>::new();
+    map.insert(
+        "name",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.map(|workspace| workspace.name);
+            Ok(L::wrap_string(out_property))
+        },
+    );
+    map.insert(
+        "target",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.map(|workspace| workspace.target);
+            Ok(L::wrap_commit(out_property))
+        },
+    );
+    map.insert(
+        "root",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property
+                .map(|workspace| workspace.root.map(|root| root.display().to_string()));
+            Ok(L::wrap_string_opt(out_property))
+        },
+    );
+    map.insert(
+        "stale",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.map(|workspace| workspace.stale);
+            Ok(L::wrap_boolean(out_property))
+        },
+    );
+    map
+}
@@ -20,6 +20,7 @@ use clap_complete::CompletionCandidate;
 use itertools::Itertools;
 use jj_lib::config::ConfigNamePathBuf;
 use jj_lib::settings::UserSettings;
+use jj_lib::str_util::GlobPattern;
 use jj_lib::workspace::DefaultWorkspaceLoaderFactory;
 use jj_lib::workspace::WorkspaceLoaderFactory as _;
 
@@ -395,10 +396,12 @@ pub fn workspaces() -> Vec<CompletionCandidate> {
     with_jj(|jj, _| {
         let output = jj
             .build()
-            .arg("--config")
-            .arg(r#"templates.commit_summary='if(description, description.first_line(), "(no description set)")'"#)
             .arg("workspace")
             .arg("list")
+            .arg("--template")
+            .arg(
+                r#"name ++ ": " ++ if(target.description(), target.description().first_line(), "(no description set)") ++ "\n""#,
+            )
             .output()
             .map_err(user_error)?;
         let stdout = String::from_utf8_lossy(&output.stdout);
@@ -555,7 +558,7 @@ fn dir_prefix_from<'a>(path: &'a str, current: &str) -> Option<&'a str> {
 }
 
 fn current_prefix_to_fileset(current: &str) -> String {
-    let cur_esc = glob::Pattern::escape(current);
+    let cur_esc = GlobPattern::escape(current);
     let dir_pat = format!("{cur_esc}*/**");
     let path_pat = format!("{cur_esc}*");
     format!("glob:{dir_pat:?} | glob:{path_pat:?}")
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use std::borrow::Cow;
+use std::cell::OnceCell;
 use std::collections::BTreeSet;
 use std::collections::HashMap;
 use std::env;
@@ -32,6 +33,7 @@ use jj_lib::config::ConfigResolutionContext;
 use jj_lib::config::ConfigSource;
 use jj_lib::config::ConfigValue;
 use jj_lib::config::StackedConfig;
+use jj_lib::file_util::IoResultExt as _;
 use regex::Captures;
 use regex::Regex;
 use thiserror::Error;
@@ -96,7 +98,10 @@ pub struct AnnotatedValue {
     pub value: ConfigValue,
     /// Source of the configuration value.
     pub source: ConfigSource,
-    // TODO: add source file path
+    /// Source file path of the configuration value if any.
+    pub path: Option<PathBuf>,
+    /// Conditions of the scoped table the value is defined in, if any.
+    pub scope: Option<String>,
     /// True if this value is overridden in higher precedence layers.
     pub is_overridden: bool,
 }
@@ -151,6 +156,8 @@ pub fn resolved_config_values(
                     name,
                     value,
                     source: layer.source,
+                    path: layer.path.clone(),
+                    scope: layer.scope.clone(),
                     is_overridden,
                 });
             }
@@ -229,10 +236,26 @@ struct UnresolvedConfigEnv {
 }
 
 impl UnresolvedConfigEnv {
-    fn resolve(self) -> Result<ConfigPath, ConfigEnvError> {
-        if let Some(path) = self.jj_config {
-            // TODO: We should probably support colon-separated (std::env::split_paths)
-            return Ok(ConfigPath::new(Some(PathBuf::from(path))));
+    /// Returns the directory of drop-in config files, which is `conf.d` next to
+    /// the platform config file. Drop-in files aren't loaded if `$JJ_CONFIG` is
+    /// set.
+    fn dropin_dir(&self) -> Option<PathBuf> {
+        if self.jj_config.is_some() {
+            return None;
+        }
+        self.config_dir
+            .as_ref()
+            .map(|config_dir| config_dir.join("jj").join("conf.d"))
+    }
+
+    /// Resolves user config paths in the order they should be loaded. The last
+    /// path is the primary config file which is modified by `jj config set`.
+    fn resolve(self) -> Result<Vec<ConfigPath>, ConfigEnvError> {
+        if let Some(paths) = self.jj_config {
+            return Ok(env::split_paths(&paths)
+                .filter(|path| !path.as_os_str().is_empty())
+                .map(|path| ConfigPath::new(Some(path)))
+                .collect());
         }
         // TODO: Should we drop the final `/config.toml` and read all files in the
         // directory?
@@ -246,14 +269,18 @@ impl UnresolvedConfigEnv {
             home_dir
         }));
         use ConfigPath::*;
-        match (platform_config_path, home_config_path) {
-            (Existing(platform_config_path), Existing(home_config_path)) => Err(
-                ConfigEnvError::AmbiguousSource(platform_config_path, home_config_path),
-            ),
-            (Existing(path), _) | (_, Existing(path)) => Ok(Existing(path)),
-            (New(path), _) | (_, New(path)) => Ok(New(path)),
-            (Unavailable, Unavailable) => Ok(Unavailable),
-        }
+        let path = match (platform_config_path, home_config_path) {
+            (Existing(platform_config_path), Existing(home_config_path)) => {
+                return Err(ConfigEnvError::AmbiguousSource(
+                    platform_config_path,
+                    home_config_path,
+                ));
+            }
+            (Existing(path), _) | (_, Existing(path)) => Existing(path),
+            (New(path), _) | (_, New(path)) => New(path),
+            (Unavailable, Unavailable) => Unavailable,
+        };
+        Ok(vec![path])
     }
 }
 
@@ -261,7 +288,9 @@ impl UnresolvedConfigEnv {
 pub struct ConfigEnv {
     home_dir: Option<PathBuf>,
     repo_path: Option<PathBuf>,
-    user_config_path: ConfigPath,
+    remote_urls: OnceCell<Vec<String>>,
+    user_config_dropin_dir: Option<PathBuf>,
+    user_config_paths: Vec<ConfigPath>,
     repo_config_path: ConfigPath,
     command: Option<String>,
 }
@@ -280,7 +309,9 @@ impl ConfigEnv {
         Ok(ConfigEnv {
             home_dir,
             repo_path: None,
-            user_config_path: env.resolve()?,
+            remote_urls: OnceCell::new(),
+            user_config_dropin_dir: env.dropin_dir(),
+            user_config_paths: env.resolve()?,
             repo_config_path: ConfigPath::Unavailable,
             command: None,
         })
@@ -290,21 +321,13 @@ impl ConfigEnv {
         self.command = Some(command);
     }
 
-    /// Returns a path to the user-specific config file or directory.
+    /// Returns a path to the primary user-specific config file or directory.
     pub fn user_config_path(&self) -> Option<&Path> {
-        self.user_config_path.as_path()
-    }
-
-    /// Returns a path to the existing user-specific config file or directory.
-    fn existing_user_config_path(&self) -> Option<&Path> {
-        match &self.user_config_path {
-            ConfigPath::Existing(path) => Some(path),
-            _ => None,
-        }
+        self.user_config_paths.last().and_then(ConfigPath::as_path)
     }
 
     /// Returns user configuration files for modification. Instantiates one if
-    /// `config` has no user configuration layers.
+    /// `config` has no layers loaded from the primary user configuration path.
     ///
     /// The parent directory for the new file may be created by this function.
     /// If the user configuration path is unknown, this function returns an
@@ -313,7 +336,14 @@ impl ConfigEnv {
         &self,
         config: &RawConfig,
     ) -> Result<Vec<ConfigFile>, ConfigLoadError> {
-        config_files_for(config, ConfigSource::User, || self.new_user_config_file())
+        // Files other than the primary one may be shared with other users.
+        let primary_path = self.user_config_path();
+        config_files_for(
+            config,
+            ConfigSource::User,
+            |path| primary_path.is_some_and(|primary_path| path.starts_with(primary_path)),
+            || self.new_user_config_file(),
+        )
     }
 
     fn new_user_config_file(&self) -> Result<Option<ConfigFile>, ConfigLoadError> {
@@ -336,7 +366,15 @@ impl ConfigEnv {
     #[instrument]
     pub fn reload_user_config(&self, config: &mut RawConfig) -> Result<(), ConfigLoadError> {
         config.as_mut().remove_layers(ConfigSource::User);
-        if let Some(path) = self.existing_user_config_path() {
+        if let Some(dir) = &self.user_config_dropin_dir {
+            for path in dropin_config_file_paths(dir)? {
+                config.as_mut().load_file(ConfigSource::User, path)?;
+            }
+        }
+        for path in &self.user_config_paths {
+            let ConfigPath::Existing(path) = path else {
+                continue;
+            };
             if path.is_dir() {
                 config.as_mut().load_dir(ConfigSource::User, path)?;
             } else {
@@ -350,6 +388,7 @@ impl ConfigEnv {
     /// is usually `.jj/repo`.
     pub fn reset_repo_path(&mut self, path: &Path) {
         self.repo_path = Some(path.to_owned());
+        self.remote_urls = OnceCell::new();
         self.repo_config_path = ConfigPath::new(Some(path.join("config.toml")));
     }
 
@@ -376,7 +415,12 @@ impl ConfigEnv {
         &self,
         config: &RawConfig,
     ) -> Result<Vec<ConfigFile>, ConfigLoadError> {
-        config_files_for(config, ConfigSource::Repo, || self.new_repo_config_file())
+        config_files_for(
+            config,
+            ConfigSource::Repo,
+            |_| true,
+            || self.new_repo_config_file(),
+        )
     }
 
     fn new_repo_config_file(&self) -> Result<Option<ConfigFile>, ConfigLoadError> {
@@ -401,24 +445,75 @@ impl ConfigEnv {
     /// Resolves conditional scopes within the current environment. Returns new
     /// resolved config.
     pub fn resolve_config(&self, config: &RawConfig) -> Result<StackedConfig, ConfigGetError> {
+        let (config, _warnings) = self.resolve_config_with_warnings(config)?;
+        Ok(config)
+    }
+
+    /// Like [`Self::resolve_config()`], but also returns descriptions of the
+    /// scoped tables which were ignored because of malformed conditions.
+    pub fn resolve_config_with_warnings(
+        &self,
+        config: &RawConfig,
+    ) -> Result<(StackedConfig, Vec<String>), ConfigGetError> {
         let context = ConfigResolutionContext {
             home_dir: self.home_dir.as_deref(),
             repo_path: self.repo_path.as_deref(),
+            remote_urls: &|| self.remote_urls(),
             command: self.command.as_deref(),
         };
-        jj_lib::config::resolve(config.as_ref(), &context)
+        jj_lib::config::resolve_with_warnings(config.as_ref(), &context)
     }
+
+    /// Returns the fetch URLs of the Git remotes of the repository. They are
+    /// read on first use.
+    fn remote_urls(&self) -> &[String] {
+        match &self.repo_path {
+            Some(path) => self.remote_urls.get_or_init(|| git_remote_urls(path)),
+            None => &[],
+        }
+    }
+}
+
+/// Reads the fetch URLs of the Git remotes of the repository at `repo_path`,
+/// which is usually `.jj/repo`. Returns an empty list if the repository isn't
+/// backed by Git.
+#[cfg(feature = "git")]
+fn git_remote_urls(repo_path: &Path) -> Vec<String> {
+    let store_path = repo_path.join("store");
+    let Ok(git_target) = std::fs::read_to_string(store_path.join("git_target")) else {
+        return vec![];
+    };
+    let git_repo_path = store_path.join(git_target);
+    let Ok(git_repo) = gix::open_opts(git_repo_path, gix::open::Options::isolated()) else {
+        return vec![];
+    };
+    git_repo
+        .remote_names()
+        .iter()
+        .filter_map(|remote_name| {
+            let remote = git_repo.try_find_remote(&**remote_name)?.ok()?;
+            let url = remote.url(gix::remote::Direction::Fetch)?;
+            Some(url.to_bstring().to_string())
+        })
+        .collect()
+}
+
+#[cfg(not(feature = "git"))]
+fn git_remote_urls(_repo_path: &Path) -> Vec<String> {
+    vec![]
 }
 
 fn config_files_for(
     config: &RawConfig,
     source: ConfigSource,
+    path_filter: impl Fn(&Path) -> bool,
     new_file: impl FnOnce() -> Result<Option<ConfigFile>, ConfigLoadError>,
 ) -> Result<Vec<ConfigFile>, ConfigLoadError> {
     let mut files = config
         .as_ref()
         .layers_for(source)
         .iter()
+        .filter(|layer| layer.path.as_deref().is_some_and(&path_filter))
         .filter_map(|layer| ConfigFile::from_layer(layer.clone()).ok())
         .collect_vec();
     if files.is_empty() {
@@ -427,6 +522,28 @@ fn config_files_for(
     Ok(files)
 }
 
+/// Returns paths to the `*.toml` files in the drop-in config directory in
+/// lexicographical order. Returns an empty list if the directory doesn't exist.
+fn dropin_config_file_paths(dir: &Path) -> Result<Vec<PathBuf>, ConfigLoadError> {
+    if !dir.is_dir() {
+        return Ok(vec![]);
+    }
+    let mut paths: Vec<_> = dir
+        .read_dir()
+        .and_then(|entries| {
+            entries
+                .map(|entry| Ok(entry?.path()))
+                .filter_ok(|path| {
+                    path.extension().is_some_and(|ext| ext == "toml") && path.is_file()
+                })
+                .try_collect()
+        })
+        .context(dir)
+        .map_err(ConfigLoadError::Read)?;
+    paths.sort_unstable();
+    Ok(paths)
+}
+
 /// Initializes stacked config with the given `default_layers` and infallible
 /// sources.
 ///
@@ -620,6 +737,11 @@ pub fn default_config_migrations() -> Vec<ConfigMigrationRule> {
                 }
             },
         ),
+        // TODO: Delete in jj 0.34+
+        ConfigMigrationRule::rename_value(
+            "snapshot.auto-update-stale",
+            "workspace.auto-update-stale",
+        ),
     ]
 }
 
@@ -966,6 +1088,8 @@ mod tests {
                     },
                 ),
                 source: EnvBase,
+                path: None,
+                scope: None,
                 is_overridden: false,
             },
             AnnotatedValue {
@@ -993,6 +1117,8 @@ mod tests {
                     },
                 ),
                 source: EnvBase,
+                path: None,
+                scope: None,
                 is_overridden: true,
             },
             AnnotatedValue {
@@ -1020,6 +1146,8 @@ mod tests {
                     },
                 ),
                 source: Repo,
+                path: None,
+                scope: None,
                 is_overridden: false,
             },
         ]
@@ -1068,6 +1196,8 @@ mod tests {
                     },
                 ),
                 source: User,
+                path: None,
+                scope: None,
                 is_overridden: false,
             },
             AnnotatedValue {
@@ -1095,6 +1225,8 @@ mod tests {
                     },
                 ),
                 source: Repo,
+                path: None,
+                scope: None,
                 is_overridden: false,
             },
         ]
@@ -1350,6 +1482,59 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_config_path_dropin_dir() -> anyhow::Result<()> {
+        let tmp = setup_config_fs(&vec![
+            "config/jj/config.toml",
+            "config/jj/conf.d/b.toml",
+            "config/jj/conf.d/a.toml",
+            "config/jj/conf.d/c.txt",
+        ])?;
+        let env = UnresolvedConfigEnv {
+            config_dir: Some(tmp.path().join("config")),
+            ..Default::default()
+        };
+        let config_env = ConfigEnv {
+            home_dir: None,
+            repo_path: None,
+            remote_urls: OnceCell::new(),
+            user_config_dropin_dir: env.dropin_dir(),
+            user_config_paths: env.resolve()?,
+            repo_config_path: ConfigPath::Unavailable,
+            command: None,
+        };
+        let mut config = RawConfig(StackedConfig::empty());
+        config_env.reload_user_config(&mut config)?;
+        let paths = config
+            .as_ref()
+            .layers()
+            .iter()
+            .map(|layer| layer.path.clone().unwrap())
+            .collect_vec();
+        // Drop-in files are overridden by the primary config file.
+        assert_eq!(
+            paths,
+            [
+                tmp.path().join("config/jj/conf.d/a.toml"),
+                tmp.path().join("config/jj/conf.d/b.toml"),
+                tmp.path().join("config/jj/config.toml"),
+            ]
+        );
+        assert_eq!(
+            config_env.user_config_path(),
+            Some(tmp.path().join("config/jj/config.toml").as_ref())
+        );
+
+        // Drop-in files aren't loaded if $JJ_CONFIG is set.
+        let env = UnresolvedConfigEnv {
+            config_dir: Some(tmp.path().join("config")),
+            jj_config: Some("".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(env.dropin_dir(), None);
+        Ok(())
+    }
+
     fn setup_config_fs(files: &Vec<&'static str>) -> anyhow::Result<tempfile::TempDir> {
         let tmp = testutils::new_temp_dir();
         for file in files {
@@ -1389,7 +1574,9 @@ mod tests {
             Ok(ConfigEnv {
                 home_dir,
                 repo_path: None,
-                user_config_path: env.resolve()?,
+                remote_urls: OnceCell::new(),
+                user_config_dropin_dir: env.dropin_dir(),
+                user_config_paths: env.resolve()?,
                 repo_config_path: ConfigPath::Unavailable,
                 command: None,
             })
@@ -1412,7 +1599,10 @@ mod tests {
             let env = self
                 .resolve(tmp.path())
                 .map_err(|e| anyhow!("existing_config_path: {e}"))?;
-            let got = env.existing_user_config_path();
+            let got = match env.user_config_paths.last() {
+                Some(ConfigPath::Existing(path)) => Some(path.as_path()),
+                _ => None,
+            };
             if got != want.as_deref() {
                 return Err(anyhow!("existing_config_path: got {got:?}, want {want:?}"));
             }
@@ -0,0 +1,377 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Validation of config variables against the config schema.
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use itertools::Itertools as _;
+use jj_lib::config::ConfigItem;
+use jj_lib::config::ConfigLayer;
+use jj_lib::config::ConfigNamePathBuf;
+use jj_lib::config::ConfigSource;
+use jj_lib::config::ConfigTableLike;
+use jj_lib::config::StackedConfig;
+use jj_lib::dsl_util::collect_similar;
+use once_cell::sync::Lazy;
+use serde_json::Value;
+
+use crate::config::CONFIG_SCHEMA;
+
+/// Prefix of config keys which are never reported as unknown. Settings of
+/// third-party tools and experiments can be put under keys with this prefix.
+pub const UNCHECKED_KEY_PREFIX: &str = "x-";
+
+/// Problem found in a config variable.
+#[derive(Clone, Debug)]
+pub struct ConfigProblem {
+    /// Dotted name path to the config variable.
+    pub name: ConfigNamePathBuf,
+    /// What is wrong with the variable.
+    pub kind: ConfigProblemKind,
+    /// Source of the config variable.
+    pub source: ConfigSource,
+    /// Source file path of the config variable if any.
+    pub path: Option<PathBuf>,
+    /// Line number of the config variable in the source file if known.
+    pub line: Option<usize>,
+}
+
+/// Kind of [`ConfigProblem`].
+#[derive(Clone, Debug)]
+pub enum ConfigProblemKind {
+    /// The key isn't known to jj.
+    UnknownKey {
+        /// Known names similar to the key.
+        similar_names: Vec<String>,
+    },
+    /// The value type doesn't match the schema.
+    InvalidType {
+        /// Types allowed by the schema.
+        expected: Vec<String>,
+        /// Type of the value.
+        actual: &'static str,
+    },
+}
+
+impl ConfigProblem {
+    /// Returns true if the key of the variable isn't known to jj.
+    pub fn is_unknown_key(&self) -> bool {
+        matches!(self.kind, ConfigProblemKind::UnknownKey { .. })
+    }
+
+    /// Returns a hint to fix the problem if any.
+    pub fn hint(&self) -> Option<String> {
+        match &self.kind {
+            ConfigProblemKind::UnknownKey { similar_names } if !similar_names.is_empty() => {
+                let names = similar_names.iter().map(|name| format!("`{name}`"));
+                Some(format!("Did you mean {}?", names.format(", ")))
+            }
+            ConfigProblemKind::UnknownKey { .. } => Some(format!(
+                "Config keys prefixed with `{UNCHECKED_KEY_PREFIX}` aren't checked"
+            )),
+            ConfigProblemKind::InvalidType { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for ConfigProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ConfigProblemKind::UnknownKey { .. } => write!(f, "Unknown config key {}", self.name)?,
+            ConfigProblemKind::InvalidType { expected, actual } => write!(
+                f,
+                "Invalid type for {}: expected {}, but is {actual}",
+                self.name,
+                expected.iter().format(" or ")
+            )?,
+        }
+        match (&self.path, self.line) {
+            (Some(path), Some(line)) => write!(f, " in {}:{line}", path.display()),
+            (Some(path), None) => write!(f, " in {}", path.display()),
+            (None, _) => write!(f, " in {} config", self.source),
+        }
+    }
+}
+
+/// Parsed config schema. It's only needed when config variables unknown to the
+/// built-in default config are found.
+static SCHEMA: Lazy<Value> = Lazy::new(|| serde_json::from_str(CONFIG_SCHEMA).unwrap());
+
+/// Checks the variables in the user, repo, and command-line config layers
+/// against the config schema.
+///
+/// Keys are checked only in the tables known to jj. Keys which aren't
+/// described by the schema are known if they have built-in default values.
+pub fn check_config(config: &StackedConfig) -> Vec<ConfigProblem> {
+    let schema = &*SCHEMA;
+    let checker = SchemaChecker {
+        schema,
+        default_layers: config.layers_for(ConfigSource::Default),
+    };
+    let mut problems = Vec::new();
+    for layer in checked_layers(config) {
+        let mut layer_problems = Vec::new();
+        checker.check_table(
+            &ConfigNamePathBuf::root(),
+            layer.data.as_table(),
+            schema,
+            &mut layer_problems,
+        );
+        problems.extend(layer_problems.into_iter().map(|(name, kind)| {
+            let line = layer
+                .path
+                .as_deref()
+                .and_then(|path| find_line(path, &name));
+            ConfigProblem {
+                name,
+                kind,
+                source: layer.source,
+                path: layer.path.clone(),
+                line,
+            }
+        }));
+    }
+    problems
+}
+
+/// Like [`check_config()`], but only reports unknown keys.
+///
+/// The schema isn't loaded if all variables have built-in default values.
+pub fn check_config_unknown_keys(config: &StackedConfig) -> Vec<ConfigProblem> {
+    let default_layers = config.layers_for(ConfigSource::Default);
+    let has_default_value = |name: &ConfigNamePathBuf| {
+        default_layers
+            .iter()
+            .any(|layer| matches!(layer.look_up_item(name), Ok(Some(_))))
+    };
+    let all_known = checked_layers(config).all(|layer| {
+        leaf_names(&ConfigNamePathBuf::root(), layer.data.as_table())
+            .iter()
+            .all(has_default_value)
+    });
+    if all_known {
+        return vec![];
+    }
+    let mut problems = check_config(config);
+    problems.retain(ConfigProblem::is_unknown_key);
+    problems
+}
+
+fn checked_layers(config: &StackedConfig) -> impl Iterator<Item = &Arc<ConfigLayer>> {
+    config.layers().iter().filter(|layer| {
+        matches!(
+            layer.source,
+            ConfigSource::User | ConfigSource::Repo | ConfigSource::CommandArg
+        )
+    })
+}
+
+/// Returns the names of the non-table variables in `table`, excluding the
+/// unchecked ones.
+fn leaf_names(name: &ConfigNamePathBuf, table: &ConfigTableLike) -> Vec<ConfigNamePathBuf> {
+    let mut names = Vec::new();
+    for (key, item) in table.iter() {
+        if key.starts_with(UNCHECKED_KEY_PREFIX) {
+            continue;
+        }
+        let mut sub_name = name.clone();
+        sub_name.push(key);
+        match item.as_table_like() {
+            Some(sub_table) => names.extend(leaf_names(&sub_name, sub_table)),
+            None => names.push(sub_name),
+        }
+    }
+    names
+}
+
+struct SchemaChecker<'a> {
+    schema: &'a Value,
+    default_layers: &'a [Arc<ConfigLayer>],
+}
+
+impl<'a> SchemaChecker<'a> {
+    /// Follows the `$ref` of the schema `node`.
+    fn resolve(&self, mut node: &'a Value) -> &'a Value {
+        // Limit the depth in case the references were cyclic.
+        for _ in 0..10 {
+            let Some(reference) = node.get("$ref").and_then(Value::as_str) else {
+                break;
+            };
+            let Some(target) = reference
+                .strip_prefix('#')
+                .and_then(|pointer| self.schema.pointer(pointer))
+            else {
+                break;
+            };
+            node = target;
+        }
+        node
+    }
+
+    fn has_default_value(&self, name: &ConfigNamePathBuf) -> bool {
+        self.default_layers
+            .iter()
+            .any(|layer| matches!(layer.look_up_item(name), Ok(Some(_))))
+    }
+
+    fn check_table(
+        &self,
+        name: &ConfigNamePathBuf,
+        table: &ConfigTableLike,
+        node: &'a Value,
+        problems: &mut Vec<(ConfigNamePathBuf, ConfigProblemKind)>,
+    ) {
+        let node = self.resolve(node);
+        let properties = node.get("properties").and_then(Value::as_object);
+        let additional_properties = node.get("additionalProperties");
+        for (key, item) in table.iter() {
+            if key.starts_with(UNCHECKED_KEY_PREFIX) {
+                continue;
+            }
+            let mut sub_name = name.clone();
+            sub_name.push(key);
+            let sub_node = match properties.and_then(|properties| properties.get(key)) {
+                Some(sub_node) => sub_node,
+                None => match (properties, additional_properties) {
+                    (_, Some(sub_node)) if sub_node.is_object() => sub_node,
+                    // Arbitrary keys are allowed.
+                    (None, _) | (_, Some(Value::Bool(true))) => continue,
+                    // Top-level tables unknown to jj may belong to other tools.
+                    // Keys with default values are known even if the schema
+                    // doesn't describe them.
+                    _ if name.is_root() || self.has_default_value(&sub_name) => continue,
+                    (Some(properties), _) => {
+                        let similar_names = self.similar_names(name, properties, key);
+                        problems.push((sub_name, ConfigProblemKind::UnknownKey { similar_names }));
+                        continue;
+                    }
+                },
+            };
+            self.check_item(&sub_name, item, sub_node, problems);
+        }
+    }
+
+    fn check_item(
+        &self,
+        name: &ConfigNamePathBuf,
+        item: &ConfigItem,
+        node: &'a Value,
+        problems: &mut Vec<(ConfigNamePathBuf, ConfigProblemKind)>,
+    ) {
+        let node = self.resolve(node);
+        if let Some(expected) = self.expected_types(node) {
+            if !expected.iter().any(|ty| matches_type(ty, item)) {
+                let kind = ConfigProblemKind::InvalidType {
+                    expected: expected
+                        .iter()
+                        .map(|ty| toml_type_name(ty).to_owned())
+                        .unique()
+                        .collect(),
+                    actual: item.type_name(),
+                };
+                problems.push((name.clone(), kind));
+                return;
+            }
+        }
+        if let Some(table) = item.as_table_like() {
+            self.check_table(name, table, node, problems);
+        }
+    }
+
+    /// Returns the JSON types allowed by the schema `node`, or `None` if the
+    /// type isn't constrained.
+    fn expected_types(&self, node: &'a Value) -> Option<Vec<&'a str>> {
+        if let Some(ty) = node.get("type") {
+            return match ty {
+                Value::String(ty) => Some(vec![ty.as_str()]),
+                Value::Array(types) => types.iter().map(Value::as_str).collect(),
+                _ => None,
+            };
+        }
+        let alternatives = node
+            .get("oneOf")
+            .or_else(|| node.get("anyOf"))?
+            .as_array()?;
+        let mut types = Vec::new();
+        for alternative in alternatives {
+            types.extend(self.expected_types(self.resolve(alternative))?);
+        }
+        Some(types)
+    }
+
+    fn similar_names(
+        &self,
+        name: &ConfigNamePathBuf,
+        properties: &'a serde_json::Map<String, Value>,
+        key: &str,
+    ) -> Vec<String> {
+        let default_keys = self
+            .default_layers
+            .iter()
+            .filter_map(|layer| layer.look_up_item(name).ok().flatten()?.as_table_like())
+            .flat_map(|table| table.iter().map(|(key, _)| key));
+        let candidates = properties
+            .keys()
+            .map(String::as_str)
+            .chain(default_keys)
+            .unique();
+        collect_similar(key, candidates)
+            .into_iter()
+            .map(|similar_key| {
+                let mut similar_name = name.clone();
+                similar_name.push(similar_key);
+                similar_name.to_string()
+            })
+            .collect()
+    }
+}
+
+fn matches_type(ty: &str, item: &ConfigItem) -> bool {
+    match ty {
+        "string" => item.is_str() || item.is_datetime(),
+        "boolean" => item.is_bool(),
+        "integer" => item.is_integer(),
+        "number" => item.is_integer() || item.is_float(),
+        "array" => item.is_array() || item.is_array_of_tables(),
+        "object" => item.is_table_like(),
+        _ => true,
+    }
+}
+
+/// Translates JSON type name to the TOML one.
+fn toml_type_name(ty: &str) -> &str {
+    match ty {
+        "object" => "table",
+        _ => ty,
+    }
+}
+
+/// Returns the line number where the variable `name` is defined in the config
+/// file.
+fn find_line(path: &Path, name: &ConfigNamePathBuf) -> Option<usize> {
+    let text = fs::read_to_string(path).ok()?;
+    let doc = toml_edit::ImDocument::parse(text.as_str()).ok()?;
+    let (last_key, parent_keys) = name.as_ref().split_last()?;
+    let mut table: &ConfigTableLike = doc.as_table();
+    for key in parent_keys {
+        table = table.get(key.get())?.as_table_like()?;
+    }
+    let span = table.key(last_key.get())?.span()?;
+    Some(text[..span.start].matches('\n').count() + 1)
+}
@@ -373,6 +373,30 @@ pub fn description_template(
     Ok(output.into_string_lossy())
 }
 
+/// Returns the initial description of a commit which doesn't have one yet.
+///
+/// The `templates.new_commit_description` template is rendered against the
+/// `commit`. If the output is empty, `ui.default-description` is used instead.
+pub fn new_commit_description(
+    ui: &Ui,
+    tx: &WorkspaceCommandTransaction,
+    commit: &Commit,
+) -> Result<String, CommandError> {
+    let template_text = tx
+        .settings()
+        .get_string("templates.new_commit_description")?;
+    let template = tx.parse_commit_template(ui, &template_text)?;
+    let mut output = Vec::new();
+    template
+        .format(commit, &mut PlainTextFormatter::new(&mut output))
+        .expect("write() to vec backed formatter should never fail");
+    if output.is_empty() {
+        Ok(tx.settings().get_string("ui.default-description")?)
+    } else {
+        Ok(output.into_string_lossy())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use indexmap::indexmap;
@@ -0,0 +1,360 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! On-disk cache of the numbers of lines added and removed per file diff.
+//!
+//! Entries are keyed by the content hash of the compared file values and the
+//! diff options, so a cached entry never becomes stale. New and used entries
+//! are appended to the cache file, which is compacted by evicting the least
+//! recently used entries when it grows too large.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fs;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io;
+use std::io::ErrorKind;
+use std::io::Read as _;
+use std::io::Write as _;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use jj_lib::lock::FileLock;
+use tempfile::NamedTempFile;
+
+/// Name of the cache file in the cache directory.
+const CACHE_FILE_NAME: &str = "diff_stats";
+/// Name of the lock file guarding writes to the cache file.
+const LOCK_FILE_NAME: &str = "diff_stats.lock";
+/// Header of the cache file. The version should be bumped if the stats would
+/// be calculated differently.
+const CACHE_FILE_HEADER: &[u8; 8] = b"jjdstat1";
+// key + added (8) + removed (8) + last used day (8)
+const RECORD_LENGTH: usize = KEY_LENGTH + 8 + 8 + 8;
+const KEY_LENGTH: usize = 32;
+
+/// Key of the cache entry.
+pub type DiffStatCacheKey = [u8; KEY_LENGTH];
+
+#[derive(Clone, Copy, Debug)]
+struct CacheEntry {
+    added: u64,
+    removed: u64,
+    /// Days since the epoch when the entry was last used. The coarse
+    /// granularity avoids appending a record on every lookup.
+    last_used: u64,
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    entries: HashMap<DiffStatCacheKey, CacheEntry>,
+    /// Keys of the entries added or used since the cache was loaded or saved.
+    dirty_keys: HashSet<DiffStatCacheKey>,
+}
+
+/// Cache of diff stats stored in a directory, which is loaded on first access
+/// and saved when dropped.
+#[derive(Debug)]
+pub struct DiffStatCache {
+    cache_dir: PathBuf,
+    /// Maximum number of entries to keep when compacting the cache file.
+    max_entries: usize,
+    state: RefCell<Option<CacheState>>,
+}
+
+impl DiffStatCache {
+    /// Creates cache backed by a file in `cache_dir`. The directory is created
+    /// when the cache is saved. The cache file is compacted to the
+    /// `max_entries` most recently used entries when it has twice as many
+    /// records.
+    pub fn new(cache_dir: PathBuf, max_entries: usize) -> Self {
+        DiffStatCache {
+            cache_dir,
+            max_entries,
+            state: RefCell::new(None),
+        }
+    }
+
+    fn file_path(&self) -> PathBuf {
+        self.cache_dir.join(CACHE_FILE_NAME)
+    }
+
+    fn with_state<T>(&self, f: impl FnOnce(&mut CacheState) -> T) -> T {
+        let mut state = self.state.borrow_mut();
+        let state = state.get_or_insert_with(|| {
+            let entries = read_entries(&self.file_path()).unwrap_or_else(|err| {
+                tracing::warn!(?err, "failed to read diff stat cache");
+                HashMap::new()
+            });
+            CacheState {
+                entries,
+                dirty_keys: HashSet::new(),
+            }
+        });
+        f(state)
+    }
+
+    /// Looks up the numbers of lines added and removed.
+    pub fn get(&self, key: &DiffStatCacheKey) -> Option<(usize, usize)> {
+        let today = today();
+        self.with_state(|state| {
+            let entry = state.entries.get_mut(key)?;
+            if entry.last_used != today {
+                entry.last_used = today;
+                state.dirty_keys.insert(*key);
+            }
+            Some((entry.added as usize, entry.removed as usize))
+        })
+    }
+
+    /// Records the numbers of lines added and removed.
+    pub fn insert(&self, key: DiffStatCacheKey, added: usize, removed: usize) {
+        let entry = CacheEntry {
+            added: added as u64,
+            removed: removed as u64,
+            last_used: today(),
+        };
+        self.with_state(|state| {
+            state.entries.insert(key, entry);
+            state.dirty_keys.insert(key);
+        });
+    }
+
+    /// Appends the new and used entries to the cache file, and compacts it if
+    /// it has grown too large. Entries saved concurrently by other processes
+    /// are preserved.
+    pub fn save(&self) -> io::Result<()> {
+        let mut state = self.state.borrow_mut();
+        let Some(state) = state.as_mut().filter(|state| !state.dirty_keys.is_empty()) else {
+            return Ok(());
+        };
+        fs::create_dir_all(&self.cache_dir)?;
+        let _lock =
+            FileLock::lock(self.cache_dir.join(LOCK_FILE_NAME)).map_err(io::Error::other)?;
+        let file_path = self.file_path();
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(&file_path)?;
+        let num_records = prepare_for_append(&mut file)?;
+
+        let mut data = Vec::with_capacity(state.dirty_keys.len() * RECORD_LENGTH);
+        for key in &state.dirty_keys {
+            write_record(&mut data, key, &state.entries[key]);
+        }
+        file.write_all(&data)?;
+        state.dirty_keys.clear();
+
+        if num_records + data.len() / RECORD_LENGTH > self.max_entries.saturating_mul(2) {
+            drop(file);
+            compact(&self.cache_dir, &file_path, self.max_entries)?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for DiffStatCache {
+    fn drop(&mut self) {
+        if let Err(err) = self.save() {
+            tracing::warn!(?err, "failed to save diff stat cache");
+        }
+    }
+}
+
+/// Makes the cache file ready for appending records, and returns the number
+/// of complete records in it.
+///
+/// The file is reset if it is of a different version, and a partially written
+/// last record is truncated.
+fn prepare_for_append(file: &mut File) -> io::Result<usize> {
+    let file_len = file.metadata()?.len();
+    let mut header = [0; CACHE_FILE_HEADER.len()];
+    let has_header = file_len >= header.len() as u64
+        && file.read_exact(&mut header).is_ok()
+        && header == *CACHE_FILE_HEADER;
+    if !has_header {
+        file.set_len(0)?;
+        file.write_all(CACHE_FILE_HEADER)?;
+        return Ok(0);
+    }
+    let records_len = file_len - header.len() as u64;
+    let torn_len = records_len % RECORD_LENGTH as u64;
+    if torn_len != 0 {
+        file.set_len(file_len - torn_len)?;
+    }
+    Ok((records_len / RECORD_LENGTH as u64) as usize)
+}
+
+/// Rewrites the cache file with the `max_entries` most recently used entries.
+/// Must be called while holding the lock.
+fn compact(cache_dir: &Path, file_path: &Path, max_entries: usize) -> io::Result<()> {
+    let entries = read_entries(file_path)?;
+    let mut entries: Vec<_> = entries.into_iter().collect();
+    if entries.len() > max_entries {
+        entries.sort_unstable_by_key(|(_, entry)| std::cmp::Reverse(entry.last_used));
+        entries.truncate(max_entries);
+    }
+    let mut data = Vec::with_capacity(CACHE_FILE_HEADER.len() + entries.len() * RECORD_LENGTH);
+    data.extend_from_slice(CACHE_FILE_HEADER);
+    for (key, entry) in &entries {
+        write_record(&mut data, key, entry);
+    }
+    let mut temp_file = NamedTempFile::new_in(cache_dir)?;
+    temp_file.write_all(&data)?;
+    temp_file.persist(file_path).map_err(|err| err.error)?;
+    Ok(())
+}
+
+fn write_record(data: &mut Vec<u8>, key: &DiffStatCacheKey, entry: &CacheEntry) {
+    data.extend_from_slice(key);
+    data.extend_from_slice(&entry.added.to_le_bytes());
+    data.extend_from_slice(&entry.removed.to_le_bytes());
+    data.extend_from_slice(&entry.last_used.to_le_bytes());
+}
+
+/// Reads the entries from the cache file. Records appended later take
+/// precedence.
+fn read_entries(path: &Path) -> io::Result<HashMap<DiffStatCacheKey, CacheEntry>> {
+    let data = match fs::read(path) {
+        Ok(data) => data,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(err) => return Err(err),
+    };
+    // The cache file of a different version is discarded.
+    let Some(records) = data.strip_prefix(CACHE_FILE_HEADER) else {
+        return Ok(HashMap::new());
+    };
+    let to_u64 = |bytes: &[u8]| u64::from_le_bytes(bytes.try_into().unwrap());
+    let entries = records
+        .chunks_exact(RECORD_LENGTH)
+        .map(|record| {
+            let (key, rest) = record.split_at(KEY_LENGTH);
+            let entry = CacheEntry {
+                added: to_u64(&rest[0..8]),
+                removed: to_u64(&rest[8..16]),
+                last_used: to_u64(&rest[16..24]),
+            };
+            (key.try_into().unwrap(), entry)
+        })
+        .collect();
+    Ok(entries)
+}
+
+fn today() -> u64 {
+    let elapsed = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default();
+    elapsed.as_secs() / (24 * 60 * 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_and_load() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let cache_dir = temp_dir.path().join("cache");
+        let cache = DiffStatCache::new(cache_dir.clone(), 100);
+        assert_eq!(cache.get(&[1; KEY_LENGTH]), None);
+        cache.insert([1; KEY_LENGTH], 2, 3);
+        assert_eq!(cache.get(&[1; KEY_LENGTH]), Some((2, 3)));
+        drop(cache);
+
+        // Entries inserted by another instance are merged on save.
+        let cache1 = DiffStatCache::new(cache_dir.clone(), 100);
+        let cache2 = DiffStatCache::new(cache_dir.clone(), 100);
+        cache1.insert([4; KEY_LENGTH], 5, 6);
+        cache2.insert([7; KEY_LENGTH], 8, 9);
+        cache1.save().unwrap();
+        cache2.save().unwrap();
+
+        let cache = DiffStatCache::new(cache_dir, 100);
+        assert_eq!(cache.get(&[1; KEY_LENGTH]), Some((2, 3)));
+        assert_eq!(cache.get(&[4; KEY_LENGTH]), Some((5, 6)));
+        assert_eq!(cache.get(&[7; KEY_LENGTH]), Some((8, 9)));
+    }
+
+    #[test]
+    fn test_append_records() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let cache_dir = temp_dir.path().to_owned();
+        let file_path = cache_dir.join(CACHE_FILE_NAME);
+        let records_len = || {
+            let file_len = fs::metadata(&file_path).unwrap().len() as usize;
+            file_len - CACHE_FILE_HEADER.len()
+        };
+
+        let cache = DiffStatCache::new(cache_dir.clone(), 100);
+        cache.insert([1; KEY_LENGTH], 2, 3);
+        cache.insert([4; KEY_LENGTH], 5, 6);
+        cache.save().unwrap();
+        assert_eq!(records_len(), 2 * RECORD_LENGTH);
+
+        // Only the new entries are appended.
+        cache.insert([7; KEY_LENGTH], 8, 9);
+        cache.save().unwrap();
+        assert_eq!(records_len(), 3 * RECORD_LENGTH);
+        cache.save().unwrap();
+        assert_eq!(records_len(), 3 * RECORD_LENGTH);
+
+        // A partially written record is truncated before appending.
+        let mut file = OpenOptions::new().append(true).open(&file_path).unwrap();
+        file.write_all(&[0; 10]).unwrap();
+        drop(file);
+        cache.insert([10; KEY_LENGTH], 11, 12);
+        cache.save().unwrap();
+        assert_eq!(records_len(), 4 * RECORD_LENGTH);
+
+        let cache = DiffStatCache::new(cache_dir, 100);
+        assert_eq!(cache.get(&[1; KEY_LENGTH]), Some((2, 3)));
+        assert_eq!(cache.get(&[10; KEY_LENGTH]), Some((11, 12)));
+    }
+
+    #[test]
+    fn test_evict_least_recently_used() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let cache_dir = temp_dir.path().to_owned();
+        let file_path = cache_dir.join(CACHE_FILE_NAME);
+
+        let mut data = CACHE_FILE_HEADER.to_vec();
+        for i in 1..=4 {
+            let entry = CacheEntry {
+                added: i,
+                removed: i,
+                last_used: i,
+            };
+            write_record(&mut data, &[i as u8; KEY_LENGTH], &entry);
+        }
+        fs::write(&file_path, data).unwrap();
+
+        // The file has more than twice as many records as the maximum number of
+        // entries after saving, so the entries used least recently are evicted.
+        let cache = DiffStatCache::new(cache_dir.clone(), 2);
+        assert_eq!(cache.get(&[1; KEY_LENGTH]), Some((1, 1)));
+        cache.insert([5; KEY_LENGTH], 5, 5);
+        cache.save().unwrap();
+        assert_eq!(read_entries(&file_path).unwrap().len(), 2);
+        let cache = DiffStatCache::new(cache_dir, 2);
+        assert_eq!(cache.get(&[1; KEY_LENGTH]), Some((1, 1)));
+        assert_eq!(cache.get(&[2; KEY_LENGTH]), None);
+        assert_eq!(cache.get(&[3; KEY_LENGTH]), None);
+        assert_eq!(cache.get(&[4; KEY_LENGTH]), None);
+        assert_eq!(cache.get(&[5; KEY_LENGTH]), Some((5, 5)));
+    }
+}
@@ -24,6 +24,7 @@ use std::path::PathBuf;
 use bstr::BStr;
 use futures::executor::block_on_stream;
 use futures::stream::BoxStream;
+use futures::Stream;
 use futures::StreamExt as _;
 use futures::TryStreamExt as _;
 use itertools::Itertools;
@@ -35,11 +36,14 @@ use jj_lib::backend::TreeValue;
 use jj_lib::commit::Commit;
 use jj_lib::config::ConfigGetError;
 use jj_lib::config::ConfigGetResultExt as _;
+use jj_lib::conflicts::default_materialize_concurrency;
 use jj_lib::conflicts::materialize_merge_result_to_bytes;
-use jj_lib::conflicts::materialized_diff_stream;
+use jj_lib::conflicts::materialized_diff_stream_with_concurrency;
 use jj_lib::conflicts::ConflictMarkerStyle;
 use jj_lib::conflicts::MaterializedTreeDiffEntry;
 use jj_lib::conflicts::MaterializedTreeValue;
+use jj_lib::conflicts::MIN_CONFLICT_MARKER_LEN;
+use jj_lib::content_hash::blake2b_hash;
 use jj_lib::copies::CopiesTreeDiffEntry;
 use jj_lib::copies::CopiesTreeDiffEntryPath;
 use jj_lib::copies::CopyOperation;
@@ -72,6 +76,8 @@ use tracing::instrument;
 use unicode_width::UnicodeWidthStr as _;
 
 use crate::config::CommandNameAndArgs;
+use crate::diff_stat_cache::DiffStatCache;
+use crate::diff_stat_cache::DiffStatCacheKey;
 use crate::formatter::Formatter;
 use crate::merge_tools;
 use crate::merge_tools::generate_diff;
@@ -280,6 +286,8 @@ pub struct DiffRenderer<'a> {
     path_converter: &'a RepoPathUiConverter,
     conflict_marker_style: ConflictMarkerStyle,
     formats: Vec<DiffFormat>,
+    diff_stat_cache: Option<&'a DiffStatCache>,
+    prefetch_concurrency: Option<usize>,
 }
 
 impl<'a> DiffRenderer<'a> {
@@ -294,9 +302,24 @@ impl<'a> DiffRenderer<'a> {
             path_converter,
             conflict_marker_style,
             formats,
+            diff_stat_cache: None,
+            prefetch_concurrency: None,
         }
     }
 
+    /// Sets cache to look up and record the diff stats.
+    pub fn with_diff_stat_cache(mut self, diff_stat_cache: &'a DiffStatCache) -> Self {
+        self.diff_stat_cache = Some(diff_stat_cache);
+        self
+    }
+
+    /// Sets the maximum number of files to read ahead while rendering diffs.
+    /// If unspecified, the default of the backend is used.
+    pub fn with_prefetch_concurrency(mut self, concurrency: Option<usize>) -> Self {
+        self.prefetch_concurrency = concurrency;
+        self
+    }
+
     /// Generates diff between `from_tree` and `to_tree`.
     #[allow(clippy::too_many_arguments)]
     pub fn show_diff(
@@ -335,6 +358,16 @@ impl<'a> DiffRenderer<'a> {
     ) -> Result<(), DiffRenderError> {
         let store = self.repo.store();
         let path_converter = self.path_converter;
+        // Download file contents at once if the repo is a partial clone.
+        #[cfg(feature = "git")]
+        if self.formats.iter().any(|format| {
+            !matches!(
+                format,
+                DiffFormat::Summary | DiffFormat::Types | DiffFormat::NameOnly
+            )
+        }) {
+            jj_lib::git::fetch_missing_blobs_for_diff(store, from_tree, to_tree, matcher)?;
+        }
         for format in &self.formats {
             match format {
                 DiffFormat::Summary => {
@@ -345,9 +378,15 @@ impl<'a> DiffRenderer<'a> {
                 DiffFormat::Stat(options) => {
                     let tree_diff =
                         from_tree.diff_stream_with_copies(to_tree, matcher, copy_records);
-                    let stats =
-                        DiffStats::calculate(store, tree_diff, options, self.conflict_marker_style)
-                            .block_on()?;
+                    let stats = DiffStats::calculate(
+                        store,
+                        tree_diff,
+                        options,
+                        self.conflict_marker_style,
+                        self.diff_stat_cache,
+                        self.prefetch_concurrency,
+                    )
+                    .block_on()?;
                     show_diff_stats(formatter, &stats, path_converter, width)?;
                 }
                 DiffFormat::Types => {
@@ -369,6 +408,7 @@ impl<'a> DiffRenderer<'a> {
                         tree_diff,
                         options,
                         self.conflict_marker_style,
+                        self.prefetch_concurrency,
                     )?;
                 }
                 DiffFormat::ColorWords(options) => {
@@ -381,6 +421,7 @@ impl<'a> DiffRenderer<'a> {
                         path_converter,
                         options,
                         self.conflict_marker_style,
+                        self.prefetch_concurrency,
                     )?;
                 }
                 DiffFormat::Tool(tool) => {
@@ -396,6 +437,7 @@ impl<'a> DiffRenderer<'a> {
                                 path_converter,
                                 tool,
                                 self.conflict_marker_style,
+                                self.prefetch_concurrency,
                             )
                         }
                         DiffToolMode::Dir => {
@@ -860,6 +902,26 @@ impl FileContent {
     }
 }
 
+/// Loads the maximum number of files to read ahead while rendering diffs.
+/// Returns `None` if unset.
+pub fn prefetch_concurrency_from_settings(
+    settings: &UserSettings,
+) -> Result<Option<usize>, ConfigGetError> {
+    settings.get("diff.prefetch-concurrency").optional()
+}
+
+/// Reads up to `prefetch_concurrency` files ahead of the consumer. The default
+/// of the backend is used if unspecified.
+fn materialized_diff_stream_for<'a>(
+    store: &'a Store,
+    tree_diff: BoxStream<'a, CopiesTreeDiffEntry>,
+    prefetch_concurrency: Option<usize>,
+) -> impl Stream<Item = MaterializedTreeDiffEntry> + 'a {
+    let concurrency =
+        prefetch_concurrency.unwrap_or_else(|| default_materialize_concurrency(store));
+    materialized_diff_stream_with_concurrency(store, tree_diff, concurrency)
+}
+
 fn file_content_for_diff(reader: &mut dyn io::Read) -> io::Result<FileContent> {
     // If this is a binary file, don't show the full contents.
     // Determine whether it's binary by whether the first 8k bytes contain a null
@@ -951,8 +1013,9 @@ pub fn show_color_words_diff(
     path_converter: &RepoPathUiConverter,
     options: &ColorWordsDiffOptions,
     conflict_marker_style: ConflictMarkerStyle,
+    prefetch_concurrency: Option<usize>,
 ) -> Result<(), DiffRenderError> {
-    let mut diff_stream = materialized_diff_stream(store, tree_diff);
+    let mut diff_stream = materialized_diff_stream_for(store, tree_diff, prefetch_concurrency);
     async {
         while let Some(MaterializedTreeDiffEntry { path, values }) = diff_stream.next().await {
             let left_path = path.source();
@@ -1092,6 +1155,7 @@ pub fn show_color_words_diff(
     .block_on()
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn show_file_by_file_diff(
     ui: &Ui,
     formatter: &mut dyn Formatter,
@@ -1100,6 +1164,7 @@ pub fn show_file_by_file_diff(
     path_converter: &RepoPathUiConverter,
     tool: &ExternalMergeTool,
     conflict_marker_style: ConflictMarkerStyle,
+    prefetch_concurrency: Option<usize>,
 ) -> Result<(), DiffRenderError> {
     let create_file = |path: &RepoPath,
                        wc_dir: &Path,
@@ -1115,7 +1180,7 @@ pub fn show_file_by_file_diff(
     let temp_dir = new_utf8_temp_dir("jj-diff-")?;
     let left_wc_dir = temp_dir.path().join("left");
     let right_wc_dir = temp_dir.path().join("right");
-    let mut diff_stream = materialized_diff_stream(store, tree_diff);
+    let mut diff_stream = materialized_diff_stream_for(store, tree_diff, prefetch_concurrency);
     async {
         while let Some(MaterializedTreeDiffEntry { path, values }) = diff_stream.next().await {
             let (left_value, right_value) = values?;
@@ -1508,8 +1573,9 @@ pub fn show_git_diff(
     tree_diff: BoxStream<CopiesTreeDiffEntry>,
     options: &UnifiedDiffOptions,
     conflict_marker_style: ConflictMarkerStyle,
+    prefetch_concurrency: Option<usize>,
 ) -> Result<(), DiffRenderError> {
-    let mut diff_stream = materialized_diff_stream(store, tree_diff);
+    let mut diff_stream = materialized_diff_stream_for(store, tree_diff, prefetch_concurrency);
     async {
         while let Some(MaterializedTreeDiffEntry { path, values }) = diff_stream.next().await {
             let left_path = path.source();
@@ -1660,22 +1726,69 @@ pub struct DiffStats {
 
 impl DiffStats {
     /// Calculates stats of changed lines per file.
+    ///
+    /// If `cache` is specified, the stats of the files found in it aren't
+    /// recalculated, and the calculated stats are recorded.
     pub async fn calculate(
         store: &Store,
-        tree_diff: BoxStream<'_, CopiesTreeDiffEntry>,
+        mut tree_diff: BoxStream<'_, CopiesTreeDiffEntry>,
         options: &DiffStatOptions,
         conflict_marker_style: ConflictMarkerStyle,
+        cache: Option<&DiffStatCache>,
+        prefetch_concurrency: Option<usize>,
     ) -> BackendResult<Self> {
-        let entries = materialized_diff_stream(store, tree_diff)
-            .map(|MaterializedTreeDiffEntry { path, values }| {
-                let (left, right) = values?;
-                let left_content = diff_content(path.source(), left, conflict_marker_style)?;
-                let right_content = diff_content(path.target(), right, conflict_marker_style)?;
-                let stat = get_diff_stat_entry(path, &left_content, &right_content, options);
-                BackendResult::Ok(stat)
-            })
-            .try_collect()
-            .await?;
+        let mut cached_entries = Vec::new();
+        let mut uncached_diff_entries = Vec::new();
+        let mut uncached_keys = Vec::new();
+        while let Some(diff_entry) = tree_diff.next().await {
+            let key = match (cache, &diff_entry.values) {
+                (Some(_), Ok(values)) => {
+                    Some(diff_stat_cache_key(values, options, conflict_marker_style))
+                }
+                _ => None,
+            };
+            match cache
+                .zip(key.as_ref())
+                .and_then(|(cache, key)| cache.get(key))
+            {
+                Some((added, removed)) => cached_entries.push(Some(DiffStatEntry {
+                    path: diff_entry.path,
+                    added,
+                    removed,
+                })),
+                None => {
+                    cached_entries.push(None);
+                    uncached_diff_entries.push(diff_entry);
+                    uncached_keys.push(key);
+                }
+            }
+        }
+
+        let uncached_diff = futures::stream::iter(uncached_diff_entries).boxed();
+        let calculated_entries: Vec<_> =
+            materialized_diff_stream_for(store, uncached_diff, prefetch_concurrency)
+                .map(|MaterializedTreeDiffEntry { path, values }| {
+                    let (left, right) = values?;
+                    let left_content = diff_content(path.source(), left, conflict_marker_style)?;
+                    let right_content = diff_content(path.target(), right, conflict_marker_style)?;
+                    let stat = get_diff_stat_entry(path, &left_content, &right_content, options);
+                    BackendResult::Ok(stat)
+                })
+                .try_collect()
+                .await?;
+        if let Some(cache) = cache {
+            for (stat, key) in iter::zip(&calculated_entries, uncached_keys) {
+                if let Some(key) = key {
+                    cache.insert(key, stat.added, stat.removed);
+                }
+            }
+        }
+
+        let mut calculated_entries = calculated_entries.into_iter();
+        let entries = cached_entries
+            .into_iter()
+            .map(|entry| entry.unwrap_or_else(|| calculated_entries.next().unwrap()))
+            .collect();
         Ok(DiffStats { entries })
     }
 
@@ -1702,6 +1815,32 @@ pub struct DiffStatEntry {
     pub removed: usize,
 }
 
+/// Returns the key of the diff stat of the file values in [`DiffStatCache`].
+fn diff_stat_cache_key(
+    values: &(MergedTreeValue, MergedTreeValue),
+    options: &DiffStatOptions,
+    conflict_marker_style: ConflictMarkerStyle,
+) -> DiffStatCacheKey {
+    let (left, right) = values;
+    let mut data = blake2b_hash(&[left.clone(), right.clone()][..]).to_vec();
+    data.push(match options.line_diff.compare_mode {
+        LineCompareMode::Exact => 0,
+        LineCompareMode::IgnoreAllSpace => 1,
+        LineCompareMode::IgnoreSpaceChange => 2,
+    });
+    data.push(match conflict_marker_style {
+        ConflictMarkerStyle::Diff => 0,
+        ConflictMarkerStyle::Snapshot => 1,
+        ConflictMarkerStyle::Git => 2,
+    });
+    // The conflict marker length is otherwise determined by the contents.
+    data.extend_from_slice(&(MIN_CONFLICT_MARKER_LEN as u64).to_le_bytes());
+    let mut key = DiffStatCacheKey::default();
+    let len = key.len();
+    key.copy_from_slice(&blake2b_hash(&data)[..len]);
+    key
+}
+
 fn get_diff_stat_entry(
     path: CopiesTreeDiffEntryPath,
     left_content: &FileContent,
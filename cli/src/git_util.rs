@@ -14,6 +14,10 @@
 
 //! Git utilities shared by various commands.
 
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::collections::HashSet;
+use std::env;
 use std::error;
 use std::io;
 use std::io::Read;
@@ -23,6 +27,7 @@ use std::mem;
 use std::path::Path;
 use std::path::PathBuf;
 use std::process::Stdio;
+use std::sync::Mutex;
 use std::time::Duration;
 use std::time::Instant;
 
@@ -39,6 +44,7 @@ use jj_lib::op_store::RefTarget;
 use jj_lib::op_store::RemoteRef;
 use jj_lib::repo::ReadonlyRepo;
 use jj_lib::repo::Repo;
+use jj_lib::settings::GitSettings;
 use jj_lib::store::Store;
 use jj_lib::workspace::Workspace;
 use unicode_width::UnicodeWidthStr;
@@ -48,6 +54,7 @@ use crate::command_error::cli_error;
 use crate::command_error::user_error;
 use crate::command_error::CommandError;
 use crate::formatter::Formatter;
+use crate::progress::draw_progress;
 use crate::ui::ProgressOutput;
 use crate::ui::Ui;
 
@@ -99,6 +106,99 @@ fn terminal_get_pw(ui: &Ui, url: &str) -> Option<String> {
     ui.prompt_password(&format!("Passphrase for {url}")).ok()
 }
 
+/// Asks for a credential by running the program specified by `$GIT_ASKPASS` or
+/// `$SSH_ASKPASS`, as Git would do.
+fn askpass_get(prompt: &str) -> Option<String> {
+    let program = ["GIT_ASKPASS", "SSH_ASKPASS"]
+        .into_iter()
+        .filter_map(env::var_os)
+        .find(|program| !program.is_empty())?;
+    run_askpass(Path::new(&program), prompt)
+}
+
+fn run_askpass(program: &Path, prompt: &str) -> Option<String> {
+    tracing::info!(?program, "running askpass program");
+    let output = std::process::Command::new(program)
+        .arg(prompt)
+        .stdin(Stdio::null())
+        .stderr(Stdio::inherit())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        tracing::info!(?program, status = ?output.status, "askpass program failed");
+        return None;
+    }
+    let mut value = String::from_utf8(output.stdout).ok()?;
+    if value.ends_with('\n') {
+        value.pop();
+        if value.ends_with('\r') {
+            value.pop();
+        }
+    }
+    Some(value)
+}
+
+/// Credentials obtained from the user, which are reused for the duration of
+/// the command so that the user isn't prompted again by subsequent remote
+/// operations (e.g. fetch followed by push.)
+static USER_CREDENTIALS: Mutex<BTreeMap<String, (String, String)>> = Mutex::new(BTreeMap::new());
+
+/// Looks up or asks for the credentials for the `url`, and records them in
+/// the `cache`. If `username` is specified, only the cached credentials for
+/// that user are reused.
+///
+/// If the cached credentials were already offered in this session, they
+/// were presumably rejected by the remote, so the user is asked again. The
+/// `cache` isn't locked while the user is being asked.
+fn get_user_credentials(
+    cache: &Mutex<BTreeMap<String, (String, String)>>,
+    url: &str,
+    username: Option<&str>,
+    offered_urls: &mut HashSet<String>,
+    ask: impl FnOnce() -> Option<(String, String)>,
+) -> Option<(String, String)> {
+    if offered_urls.insert(url.to_owned()) {
+        let cached = cache.lock().unwrap().get(url).cloned();
+        if let Some(credentials) = cached {
+            if username.map_or(true, |name| name == credentials.0) {
+                return Some(credentials);
+            }
+        }
+    }
+    let credentials = ask()?;
+    cache
+        .lock()
+        .unwrap()
+        .insert(url.to_owned(), credentials.clone());
+    Some(credentials)
+}
+
+/// Passes the credentials accepted by the remote to `git credential approve`,
+/// so the configured credential helpers can store them.
+fn approve_git_credentials(
+    git_executable_path: &Path,
+    url: &str,
+    username: &str,
+    password: &str,
+) -> io::Result<()> {
+    let mut child = std::process::Command::new(git_executable_path)
+        .args(["credential", "approve"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::inherit())
+        .spawn()?;
+    let input = format!("url={url}\nusername={username}\npassword={password}\n\n");
+    let write_result = child.stdin.take().unwrap().write_all(input.as_bytes());
+    let status = child.wait()?;
+    write_result?;
+    if !status.success() {
+        return Err(io::Error::other(format!(
+            "git credential approve exited with {status}"
+        )));
+    }
+    Ok(())
+}
+
 fn pinentry_get_pw(url: &str) -> Option<String> {
     // https://www.gnupg.org/documentation/manuals/assuan/Server-responses.html#Server-responses
     fn decode_assuan_data(encoded: &str) -> Option<String> {
@@ -252,7 +352,11 @@ impl GitSidebandProgressMessageWriter {
     }
 }
 
-pub fn with_remote_git_callbacks<T>(ui: &Ui, f: impl FnOnce(git::RemoteCallbacks<'_>) -> T) -> T {
+pub fn with_remote_git_callbacks<T, E>(
+    ui: &Ui,
+    git_settings: &GitSettings,
+    f: impl FnOnce(git::RemoteCallbacks<'_>) -> Result<T, E>,
+) -> Result<T, E> {
     let mut callbacks = git::RemoteCallbacks::default();
 
     let mut progress_callback;
@@ -272,15 +376,61 @@ pub fn with_remote_git_callbacks<T>(ui: &Ui, f: impl FnOnce(git::RemoteCallbacks
 
     let mut get_ssh_keys = get_ssh_keys; // Coerce to unit fn type
     callbacks.get_ssh_keys = Some(&mut get_ssh_keys);
-    let mut get_pw =
-        |url: &str, _username: &str| pinentry_get_pw(url).or_else(|| terminal_get_pw(ui, url));
+    let offered_urls = RefCell::new(HashSet::new());
+    // Credentials given by the user, which are stored if the remote accepted
+    // them.
+    let new_credentials = RefCell::new(BTreeMap::new());
+    let mut get_pw = |url: &str, username: &str| {
+        let ask = || {
+            let pw = askpass_get(&format!("Password for {url}: "))
+                .or_else(|| pinentry_get_pw(url))
+                .or_else(|| terminal_get_pw(ui, url))?;
+            new_credentials
+                .borrow_mut()
+                .insert(url.to_owned(), (username.to_owned(), pw.clone()));
+            Some((username.to_owned(), pw))
+        };
+        get_user_credentials(
+            &USER_CREDENTIALS,
+            url,
+            Some(username),
+            &mut offered_urls.borrow_mut(),
+            ask,
+        )
+        .map(|(_, pw)| pw)
+    };
     callbacks.get_password = Some(&mut get_pw);
-    let mut get_user_pw =
-        |url: &str| Some((terminal_get_username(ui, url)?, terminal_get_pw(ui, url)?));
+    let mut get_user_pw = |url: &str| {
+        let ask = || {
+            let username = askpass_get(&format!("Username for {url}: "))
+                .or_else(|| terminal_get_username(ui, url))?;
+            let pw = askpass_get(&format!("Password for {url}: "))
+                .or_else(|| terminal_get_pw(ui, url))?;
+            new_credentials
+                .borrow_mut()
+                .insert(url.to_owned(), (username.clone(), pw.clone()));
+            Some((username, pw))
+        };
+        get_user_credentials(
+            &USER_CREDENTIALS,
+            url,
+            None,
+            &mut offered_urls.borrow_mut(),
+            ask,
+        )
+    };
     callbacks.get_username_password = Some(&mut get_user_pw);
 
     let result = f(callbacks);
     _ = sideband_progress_writer.flush(ui);
+    if result.is_ok() {
+        for (url, (username, pw)) in new_credentials.take() {
+            approve_git_credentials(&git_settings.executable_path, &url, &username, &pw)
+                .unwrap_or_else(|err| {
+                    tracing::warn!(?err, url, "failed to store credentials");
+                });
+        }
+    }
     result
 }
 
@@ -408,23 +558,6 @@ impl Progress {
     }
 }
 
-fn draw_progress(progress: f32, buffer: &mut String, width: usize) {
-    const CHARS: [char; 9] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉', '█'];
-    const RESOLUTION: usize = CHARS.len() - 1;
-    let ticks = (width as f32 * progress.clamp(0.0, 1.0) * RESOLUTION as f32).round() as usize;
-    let whole = ticks / RESOLUTION;
-    for _ in 0..whole {
-        buffer.push(CHARS[CHARS.len() - 1]);
-    }
-    if whole < width {
-        let fraction = ticks % RESOLUTION;
-        buffer.push(CHARS[fraction]);
-    }
-    for _ in (whole + 1)..width {
-        buffer.push(CHARS[0]);
-    }
-}
-
 struct RateEstimate {
     state: Option<RateEstimateState>,
 }
@@ -662,21 +795,114 @@ mod tests {
         );
     }
 
+    #[cfg(unix)]
     #[test]
-    fn test_bar() {
-        let mut buf = String::new();
-        draw_progress(0.0, &mut buf, 10);
-        assert_eq!(buf, "          ");
-        buf.clear();
-        draw_progress(1.0, &mut buf, 10);
-        assert_eq!(buf, "██████████");
-        buf.clear();
-        draw_progress(0.5, &mut buf, 10);
-        assert_eq!(buf, "█████     ");
-        buf.clear();
-        draw_progress(0.54, &mut buf, 10);
-        assert_eq!(buf, "█████▍    ");
-        buf.clear();
+    fn test_run_askpass() {
+        use std::os::unix::fs::PermissionsExt as _;
+
+        let temp_dir = testutils::new_temp_dir();
+        let program = temp_dir.path().join("askpass");
+        std::fs::write(&program, "#!/bin/sh\necho \"answer to $1\"\n").unwrap();
+        std::fs::set_permissions(&program, std::fs::Permissions::from_mode(0o755)).unwrap();
+        assert_eq!(
+            run_askpass(&program, "Password for https://example.org: ").as_deref(),
+            Some("answer to Password for https://example.org: ")
+        );
+
+        let failing_program = temp_dir.path().join("askpass-fail");
+        std::fs::write(&failing_program, "#!/bin/sh\nexit 1\n").unwrap();
+        std::fs::set_permissions(&failing_program, std::fs::Permissions::from_mode(0o755)).unwrap();
+        assert_eq!(run_askpass(&failing_program, "Password: "), None);
+        assert_eq!(
+            run_askpass(&temp_dir.path().join("missing"), "Password: "),
+            None
+        );
+    }
+
+    #[test]
+    fn test_get_user_credentials() {
+        let url = "https://example.org/foo";
+        let credentials = |pw: &str| Some(("user".to_owned(), pw.to_owned()));
+        let cache = Mutex::new(BTreeMap::new());
+        let mut offered_urls = HashSet::new();
+        assert_eq!(
+            get_user_credentials(&cache, url, None, &mut offered_urls, || {
+                credentials("first")
+            }),
+            credentials("first")
+        );
+        // Asked again in the same session: the credentials were rejected
+        assert_eq!(
+            get_user_credentials(&cache, url, None, &mut offered_urls, || {
+                credentials("second")
+            }),
+            credentials("second")
+        );
+        // New session (e.g. push after fetch) reuses the last credentials,
+        // whether or not the username is known
+        let mut offered_urls = HashSet::new();
+        assert_eq!(
+            get_user_credentials(&cache, url, Some("user"), &mut offered_urls, || {
+                panic!("shouldn't ask")
+            }),
+            credentials("second")
+        );
+        let mut offered_urls = HashSet::new();
+        assert_eq!(
+            get_user_credentials(&cache, url, None, &mut offered_urls, || {
+                panic!("shouldn't ask")
+            }),
+            credentials("second")
+        );
+        // Credentials cached for another user aren't reused
+        let mut offered_urls = HashSet::new();
+        assert_eq!(
+            get_user_credentials(&cache, url, Some("other"), &mut offered_urls, || {
+                Some(("other".to_owned(), "third".to_owned()))
+            }),
+            Some(("other".to_owned(), "third".to_owned()))
+        );
+        // Nothing is cached if the user didn't give credentials
+        let other_url = "https://example.org/bar";
+        assert_eq!(
+            get_user_credentials(&cache, other_url, None, &mut offered_urls, || None),
+            None
+        );
+        assert!(!cache.lock().unwrap().contains_key(other_url));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_approve_git_credentials() {
+        use std::os::unix::fs::PermissionsExt as _;
+
+        let temp_dir = testutils::new_temp_dir();
+        let program = temp_dir.path().join("git");
+        let output_path = temp_dir.path().join("output");
+        std::fs::write(
+            &program,
+            format!(
+                "#!/bin/sh\necho \"$@\" > '{}'\ncat >> '{}'\n",
+                output_path.display(),
+                output_path.display()
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&program, std::fs::Permissions::from_mode(0o755)).unwrap();
+        approve_git_credentials(&program, "https://example.org/foo", "user", "secret").unwrap();
+        insta::assert_snapshot!(std::fs::read_to_string(&output_path).unwrap(), @r"
+        credential approve
+        url=https://example.org/foo
+        username=user
+        password=secret
+        ");
+
+        let failing_program = temp_dir.path().join("git-fail");
+        std::fs::write(&failing_program, "#!/bin/sh\nexit 1\n").unwrap();
+        std::fs::set_permissions(&failing_program, std::fs::Permissions::from_mode(0o755)).unwrap();
+        assert!(
+            approve_git_credentials(&failing_program, "https://example.org", "u", "p").is_err()
+        );
     }
 
     #[test]
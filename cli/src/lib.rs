@@ -21,7 +21,9 @@ pub mod commands;
 pub mod commit_templater;
 pub mod complete;
 pub mod config;
+pub mod config_schema;
 pub mod description_util;
+pub mod diff_stat_cache;
 pub mod diff_util;
 pub mod formatter;
 pub mod generic_templater;
@@ -0,0 +1,170 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Merge tools which resolve conflicts without user interaction.
+
+use bstr::BString;
+use jj_lib::backend::MergedTreeId;
+use jj_lib::backend::TreeValue;
+use jj_lib::conflicts::is_binary_conflict;
+use jj_lib::files;
+use jj_lib::files::MergeResult;
+use jj_lib::merge::Merge;
+use jj_lib::merged_tree::MergedTree;
+use jj_lib::merged_tree::MergedTreeBuilder;
+use pollster::FutureExt;
+
+use super::ConflictResolveError;
+use super::MergeToolFile;
+
+/// How a conflict is resolved by one of the `:ours`, `:theirs`, and `:union`
+/// tools.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AutoResolution {
+    /// Resolves each conflicted hunk by picking the first side.
+    Ours,
+    /// Resolves each conflicted hunk by picking the second side.
+    Theirs,
+    /// Resolves each conflicted hunk by concatenating both sides.
+    Union,
+}
+
+impl AutoResolution {
+    /// Resolves a conflicted hunk with 2 sides.
+    fn resolve_hunk(self, hunk: &Merge<BString>) -> BString {
+        let ours = hunk.get_add(0).unwrap();
+        let theirs = hunk.get_add(1).unwrap();
+        match self {
+            AutoResolution::Ours => ours.clone(),
+            AutoResolution::Theirs => theirs.clone(),
+            AutoResolution::Union => {
+                let mut content = ours.clone();
+                content.extend_from_slice(theirs);
+                content
+            }
+        }
+    }
+}
+
+/// Returns the executable bit of the resolved file.
+fn resolved_executable(merge_tool_file: &MergeToolFile) -> bool {
+    merge_tool_file
+        .conflict
+        .to_executable_merge()
+        .as_ref()
+        .and_then(Merge::resolve_trivial)
+        .copied()
+        .unwrap_or_default()
+}
+
+/// Resolves the conflict by picking the side at `index` as a whole.
+fn pick_side(merge_tool_file: &MergeToolFile, index: usize, tree_builder: &mut MergedTreeBuilder) {
+    let picked_id = merge_tool_file
+        .file_merge
+        .clone()
+        .simplify()
+        .get_add(index)
+        .cloned()
+        .flatten();
+    let new_tree_value = match picked_id {
+        Some(id) => Merge::normal(TreeValue::File {
+            id,
+            executable: resolved_executable(merge_tool_file),
+        }),
+        // If the picked side deleted the file, so does the resolution.
+        None => Merge::absent(),
+    };
+    tree_builder.set_or_remove(merge_tool_file.repo_path.clone(), new_tree_value);
+}
+
+fn resolve_single_file(
+    resolution: AutoResolution,
+    tree: &MergedTree,
+    merge_tool_file: &MergeToolFile,
+    tree_builder: &mut MergedTreeBuilder,
+) -> Result<(), ConflictResolveError> {
+    let MergeToolFile {
+        repo_path,
+        conflict: _,
+        file_merge,
+        content,
+    } = merge_tool_file;
+
+    let picked_side = match resolution {
+        AutoResolution::Ours => Some(0),
+        AutoResolution::Theirs => Some(1),
+        AutoResolution::Union => None,
+    };
+    // Binary files can't be merged hunk by hunk, so the whole side is picked.
+    // So is a side which deleted the file.
+    let is_binary = is_binary_conflict(content);
+    match picked_side {
+        Some(index) if is_binary || file_merge.clone().simplify().get_add(index) == Some(&None) => {
+            pick_side(merge_tool_file, index, tree_builder);
+            return Ok(());
+        }
+        None if is_binary => {
+            return Err(ConflictResolveError::BinaryConflict(repo_path.clone()));
+        }
+        _ => {}
+    }
+
+    let new_content = match files::merge(content) {
+        MergeResult::Resolved(content) => content,
+        MergeResult::Conflict(hunks) => {
+            let mut content = BString::default();
+            for hunk in &hunks {
+                match hunk.as_resolved() {
+                    Some(resolved) => content.extend_from_slice(resolved),
+                    None => content.extend_from_slice(&resolution.resolve_hunk(hunk)),
+                }
+            }
+            content
+        }
+    };
+    let new_file_id = tree
+        .store()
+        .write_file(repo_path, &mut new_content.as_slice())
+        .block_on()?;
+    let new_tree_value = Merge::normal(TreeValue::File {
+        id: new_file_id,
+        executable: resolved_executable(merge_tool_file),
+    });
+    tree_builder.set_or_remove(repo_path.clone(), new_tree_value);
+    Ok(())
+}
+
+pub fn run_mergetool_auto(
+    resolution: AutoResolution,
+    tree: &MergedTree,
+    merge_tool_files: &[MergeToolFile],
+) -> Result<MergedTreeId, ConflictResolveError> {
+    let mut tree_builder = MergedTreeBuilder::new(tree.id());
+    for merge_tool_file in merge_tool_files {
+        resolve_single_file(resolution, tree, merge_tool_file, &mut tree_builder)?;
+    }
+    Ok(tree_builder.write_tree(tree.store())?)
+}
+
+/// Resolves the conflicts by picking one of the sides of each file as a whole.
+pub fn run_mergetool_pick_sides(
+    tree: &MergedTree,
+    picks: &[(&MergeToolFile, usize)],
+) -> Result<MergedTreeId, ConflictResolveError> {
+    let mut tree_builder = MergedTreeBuilder::new(tree.id());
+    for &(merge_tool_file, index) in picks {
+        pick_side(merge_tool_file, index, &mut tree_builder);
+    }
+    Ok(tree_builder.write_tree(tree.store())?)
+}
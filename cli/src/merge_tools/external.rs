@@ -1,6 +1,10 @@
+use std::collections::BTreeSet;
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::io;
+use std::io::Read as _;
 use std::io::Write;
+use std::path::Path;
 use std::process::Command;
 use std::process::ExitStatus;
 use std::process::Stdio;
@@ -15,14 +19,18 @@ use jj_lib::conflicts::choose_materialized_conflict_marker_len;
 use jj_lib::conflicts::materialize_merge_result_to_bytes_with_marker_len;
 use jj_lib::conflicts::ConflictMarkerStyle;
 use jj_lib::conflicts::MIN_CONFLICT_MARKER_LEN;
+use jj_lib::eol::EolConversionMode;
 use jj_lib::gitignore::GitIgnoreFile;
 use jj_lib::matchers::Matcher;
 use jj_lib::merge::Merge;
 use jj_lib::merged_tree::MergedTree;
 use jj_lib::merged_tree::MergedTreeBuilder;
+use jj_lib::repo_path::RepoPath;
+use jj_lib::repo_path::RepoPathBuf;
 use jj_lib::repo_path::RepoPathUiConverter;
 use jj_lib::store::Store;
 use jj_lib::working_copy::CheckoutOptions;
+use jj_lib::working_copy::CloneMode;
 use pollster::FutureExt;
 use thiserror::Error;
 
@@ -183,8 +191,8 @@ fn run_mergetool_external_single_file(
 ) -> Result<(), ConflictResolveError> {
     let MergeToolFile {
         repo_path,
-        conflict,
-        file_merge,
+        conflict: _,
+        file_merge: _,
         content,
     } = merge_tool_file;
 
@@ -276,20 +284,52 @@ fn run_mergetool_external_single_file(
     if output_file_contents.is_empty() || output_file_contents == initial_output_content {
         return Err(ConflictResolveError::EmptyOrUnchanged);
     }
+    set_merge_tool_output(
+        editor,
+        store,
+        merge_tool_file,
+        &output_file_contents,
+        exit_status,
+        conflict_marker_style,
+        conflict_marker_len,
+        tree_builder,
+    )
+}
 
+/// Writes the output of the merge tool for the file to the tree builder.
+#[allow(clippy::too_many_arguments)]
+fn set_merge_tool_output(
+    editor: &ExternalMergeTool,
+    store: &Store,
+    merge_tool_file: &MergeToolFile,
+    output_file_contents: &[u8],
+    exit_status: ExitStatus,
+    conflict_marker_style: ConflictMarkerStyle,
+    conflict_marker_len: usize,
+    tree_builder: &mut MergedTreeBuilder,
+) -> Result<(), ConflictResolveError> {
+    let MergeToolFile {
+        repo_path,
+        conflict,
+        file_merge,
+        content: _,
+    } = merge_tool_file;
+    let exit_status_implies_conflict = exit_status
+        .code()
+        .is_some_and(|code| editor.merge_conflict_exit_codes.contains(&code));
     let new_file_ids = if editor.merge_tool_edits_conflict_markers || exit_status_implies_conflict {
         conflicts::update_from_content(
             file_merge,
             store,
             repo_path,
-            output_file_contents.as_slice(),
+            output_file_contents,
             conflict_marker_style,
             conflict_marker_len,
         )
         .block_on()?
     } else {
         let new_file_id = store
-            .write_file(repo_path, &mut output_file_contents.as_slice())
+            .write_file(repo_path, &mut &*output_file_contents)
             .block_on()?;
         Merge::normal(new_file_id)
     };
@@ -365,6 +405,211 @@ pub fn run_mergetool_external(
     Ok((new_tree, partial_resolution_error))
 }
 
+/// Invokes the merge tool once with `base`, `left`, `right`, and `output`
+/// directories containing all the conflicted files.
+///
+/// If `include_context` is true, the other files in the directories of the
+/// conflicted files are also copied to all the directories, read-only.
+///
+/// Files whose output is left empty or unchanged stay conflicted. It's an
+/// error if no files are resolved, and a partial resolution error is returned
+/// if only some of them are.
+#[allow(clippy::too_many_arguments)]
+pub fn run_mergetool_external_dir(
+    ui: &Ui,
+    path_converter: &RepoPathUiConverter,
+    editor: &ExternalMergeTool,
+    tree: &MergedTree,
+    merge_tool_files: &[MergeToolFile],
+    include_context: bool,
+    default_conflict_marker_style: ConflictMarkerStyle,
+) -> Result<(MergedTreeId, Option<MergeToolPartialResolutionError>), ConflictResolveError> {
+    let conflict_marker_style = editor
+        .conflict_marker_style
+        .unwrap_or(default_conflict_marker_style);
+    // There's a single `$marker_length` for all the files, so the longest
+    // markers needed by any of the files are used for all of them.
+    let uses_marker_length = find_all_variables(&editor.merge_args).contains(&"marker_length");
+    let conflict_marker_len = if editor.merge_tool_edits_conflict_markers || uses_marker_length {
+        merge_tool_files
+            .iter()
+            .map(|file| choose_materialized_conflict_marker_len(&file.content))
+            .max()
+            .unwrap_or(MIN_CONFLICT_MARKER_LEN)
+    } else {
+        MIN_CONFLICT_MARKER_LEN
+    };
+
+    // The directory is removed when dropped, including on error.
+    let temp_dir = new_utf8_temp_dir("jj-resolve-").map_err(ExternalToolError::SetUpDir)?;
+    let mut variables: HashMap<&str, String> = HashMap::new();
+    for role in ["base", "left", "right", "output"] {
+        let path = temp_dir.path().join(role);
+        std::fs::create_dir(&path).map_err(ExternalToolError::SetUpDir)?;
+        variables.insert(
+            role,
+            path.into_os_string()
+                .into_string()
+                .expect("temp_dir should be valid utf-8"),
+        );
+    }
+    let write_file = |role: &str, repo_path: &RepoPath, contents: &[u8]| {
+        let path = repo_path.to_fs_path(Path::new(&variables[role]))?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(ExternalToolError::SetUpDir)?;
+        }
+        std::fs::write(&path, contents).map_err(ExternalToolError::SetUpDir)?;
+        Ok::<_, ConflictResolveError>(path)
+    };
+
+    let mut initial_output_contents = Vec::with_capacity(merge_tool_files.len());
+    for merge_tool_file in merge_tool_files {
+        let MergeToolFile {
+            repo_path, content, ..
+        } = merge_tool_file;
+        writeln!(
+            ui.status(),
+            "Resolving conflicts in: {}",
+            path_converter.format_file_path(repo_path)
+        )?;
+        let initial_output_content = if editor.merge_tool_edits_conflict_markers {
+            materialize_merge_result_to_bytes_with_marker_len(
+                content,
+                conflict_marker_style,
+                conflict_marker_len,
+            )
+        } else {
+            BString::default()
+        };
+        assert_eq!(content.num_sides(), 2);
+        let files = [
+            ("base", content.get_remove(0).unwrap().as_slice()),
+            ("left", content.get_add(0).unwrap().as_slice()),
+            ("right", content.get_add(1).unwrap().as_slice()),
+            ("output", initial_output_content.as_slice()),
+        ];
+        for (role, contents) in files {
+            write_file(role, repo_path, contents)?;
+        }
+        initial_output_contents.push(initial_output_content);
+    }
+    let mut readonly_paths = vec![];
+    if include_context {
+        for (repo_path, contents) in read_context_files(tree, merge_tool_files)? {
+            for role in ["base", "left", "right", "output"] {
+                readonly_paths.push(write_file(role, &repo_path, &contents)?);
+            }
+        }
+    }
+    readonly_paths.extend(["base", "left", "right"].map(|role| variables[role].clone().into()));
+    for path in &readonly_paths {
+        if let Err(err) = set_readonly_recursively(path) {
+            writeln!(
+                ui.warning_default(),
+                "Failed to make {} read-only: {err}",
+                path.display()
+            )?;
+        }
+    }
+    variables.insert("marker_length", conflict_marker_len.to_string());
+
+    let mut cmd = Command::new(&editor.program);
+    cmd.args(interpolate_variables(&editor.merge_args, &variables));
+    tracing::info!(?cmd, "Invoking the external merge tool on directories:");
+    let exit_status = cmd
+        .status()
+        .map_err(|e| ExternalToolError::FailedToExecute {
+            tool_binary: editor.program.clone(),
+            source: e,
+        })?;
+    let exit_status_implies_conflict = exit_status
+        .code()
+        .is_some_and(|code| editor.merge_conflict_exit_codes.contains(&code));
+    if !exit_status.success() && !exit_status_implies_conflict {
+        return Err(ConflictResolveError::from(ExternalToolError::ToolAborted {
+            exit_status,
+        }));
+    }
+
+    let mut tree_builder = MergedTreeBuilder::new(tree.id());
+    let mut resolved_count = 0;
+    for (merge_tool_file, initial_output_content) in
+        merge_tool_files.iter().zip(&initial_output_contents)
+    {
+        let path = merge_tool_file
+            .repo_path
+            .to_fs_path(Path::new(&variables["output"]))?;
+        let output_file_contents = match std::fs::read(&path) {
+            Ok(contents) => contents,
+            // The file was deleted by the tool, so leave it unresolved.
+            Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(ExternalToolError::Io(err).into()),
+        };
+        if output_file_contents.is_empty() || output_file_contents == *initial_output_content {
+            continue;
+        }
+        set_merge_tool_output(
+            editor,
+            tree.store(),
+            merge_tool_file,
+            &output_file_contents,
+            exit_status,
+            conflict_marker_style,
+            conflict_marker_len,
+            &mut tree_builder,
+        )?;
+        resolved_count += 1;
+    }
+    if resolved_count == 0 {
+        return Err(ConflictResolveError::EmptyOrUnchanged);
+    }
+    let partial_resolution_error =
+        (resolved_count < merge_tool_files.len()).then_some(MergeToolPartialResolutionError {
+            source: ConflictResolveError::EmptyOrUnchanged,
+            resolved_count,
+        });
+    Ok((
+        tree_builder.write_tree(tree.store())?,
+        partial_resolution_error,
+    ))
+}
+
+/// Reads the resolved files in the directories of the conflicted files, other
+/// than the conflicted files themselves.
+fn read_context_files(
+    tree: &MergedTree,
+    merge_tool_files: &[MergeToolFile],
+) -> Result<Vec<(RepoPathBuf, Vec<u8>)>, ConflictResolveError> {
+    let conflicted_paths: HashSet<&RepoPath> = merge_tool_files
+        .iter()
+        .map(|file| file.repo_path.as_ref())
+        .collect();
+    let dirs: BTreeSet<&RepoPath> = conflicted_paths
+        .iter()
+        .filter_map(|repo_path| repo_path.parent())
+        .collect();
+    let mut context_files = vec![];
+    for dir in dirs {
+        let Some(dir_tree) = tree.sub_tree_recursive(dir)? else {
+            continue;
+        };
+        for name in dir_tree.names() {
+            let repo_path = dir.join(name);
+            if conflicted_paths.contains(repo_path.as_ref()) {
+                continue;
+            }
+            if let Some(Some(TreeValue::File { id, .. })) = dir_tree.value(name).resolve_trivial() {
+                let mut contents = vec![];
+                tree.store()
+                    .read_file(&repo_path, id)?
+                    .read_to_end(&mut contents)?;
+                context_files.push((repo_path, contents));
+            }
+        }
+    }
+    Ok(context_files)
+}
+
 pub fn edit_diff_external(
     editor: &ExternalMergeTool,
     left_tree: &MergedTree,
@@ -379,6 +624,11 @@ pub fn edit_diff_external(
         .unwrap_or(default_conflict_marker_style);
     let options = CheckoutOptions {
         conflict_marker_style,
+        create_symlinks: true,
+        eol_conversion_mode: EolConversionMode::None,
+        clone_mode: CloneMode::Copy,
+        binary_conflict_side: 0,
+        progress: None,
     };
 
     let got_output_field = find_all_variables(&editor.edit_args).contains(&"output");
@@ -427,6 +677,11 @@ pub fn generate_diff(
         .unwrap_or(default_conflict_marker_style);
     let options = CheckoutOptions {
         conflict_marker_style,
+        create_symlinks: true,
+        eol_conversion_mode: EolConversionMode::None,
+        clone_mode: CloneMode::Copy,
+        binary_conflict_side: 0,
+        progress: None,
     };
     let store = left_tree.store();
     let diff_wc = check_out_trees(store, left_tree, right_tree, matcher, None, &options)?;
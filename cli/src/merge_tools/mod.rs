@@ -12,10 +12,13 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod auto;
 mod builtin;
 mod diff_working_copies;
 mod external;
 
+use std::io::Write as _;
+use std::iter;
 use std::sync::Arc;
 
 use bstr::BString;
@@ -26,6 +29,7 @@ use jj_lib::config::ConfigGetError;
 use jj_lib::config::ConfigGetResultExt as _;
 use jj_lib::config::ConfigNamePathBuf;
 use jj_lib::conflicts::extract_as_single_hunk;
+use jj_lib::conflicts::is_binary_conflict;
 use jj_lib::conflicts::ConflictMarkerStyle;
 use jj_lib::gitignore::GitIgnoreFile;
 use jj_lib::matchers::Matcher;
@@ -41,6 +45,7 @@ use jj_lib::working_copy::SnapshotError;
 use pollster::FutureExt;
 use thiserror::Error;
 
+pub use self::auto::AutoResolution;
 use self::builtin::edit_diff_builtin;
 use self::builtin::edit_merge_builtin;
 use self::builtin::BuiltinToolError;
@@ -56,6 +61,9 @@ use crate::config::CommandNameAndArgs;
 use crate::ui::Ui;
 
 const BUILTIN_EDITOR_NAME: &str = ":builtin";
+const OURS_TOOL_NAME: &str = ":ours";
+const THEIRS_TOOL_NAME: &str = ":theirs";
+const UNION_TOOL_NAME: &str = ":union";
 
 #[derive(Debug, Error)]
 pub enum DiffEditError {
@@ -98,6 +106,11 @@ pub enum ConflictResolveError {
     NotNormalFiles(RepoPathBuf, String),
     #[error("The conflict at {path:?} has {sides} sides. At most 2 sides are supported.")]
     ConflictTooComplicated { path: RepoPathBuf, sides: usize },
+    #[error(
+        "The conflict at {0:?} is in a binary file. Use `--tool :ours` or `--tool :theirs` to \
+         pick one of its sides."
+    )]
+    BinaryConflict(RepoPathBuf),
     #[error(
         "The output file is either unchanged or empty after the editor quit (run with --debug to \
          see the exact invocation)."
@@ -122,6 +135,8 @@ pub enum MergeToolConfigError {
     Config(#[from] ConfigGetError),
     #[error("The tool `{tool_name}` cannot be used as a merge tool with `jj resolve`")]
     MergeArgsNotConfigured { tool_name: String },
+    #[error("The tool `{tool_name}` cannot be used as a diff editor")]
+    NotADiffEditor { tool_name: String },
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -129,6 +144,8 @@ pub enum MergeTool {
     Builtin,
     // Boxed because ExternalMergeTool is big compared to the Builtin variant.
     External(Box<ExternalMergeTool>),
+    /// Resolves conflicts without user interaction.
+    Auto(AutoResolution),
 }
 
 impl MergeTool {
@@ -165,10 +182,12 @@ fn get_tool_config(
     settings: &UserSettings,
     name: &str,
 ) -> Result<Option<MergeTool>, ConfigGetError> {
-    if name == BUILTIN_EDITOR_NAME {
-        Ok(Some(MergeTool::Builtin))
-    } else {
-        Ok(get_external_tool_config(settings, name)?.map(MergeTool::external))
+    match name {
+        BUILTIN_EDITOR_NAME => Ok(Some(MergeTool::Builtin)),
+        OURS_TOOL_NAME => Ok(Some(MergeTool::Auto(AutoResolution::Ours))),
+        THEIRS_TOOL_NAME => Ok(Some(MergeTool::Auto(AutoResolution::Theirs))),
+        UNION_TOOL_NAME => Ok(Some(MergeTool::Auto(AutoResolution::Union))),
+        _ => Ok(get_external_tool_config(settings, name)?.map(MergeTool::external)),
     }
 }
 
@@ -207,7 +226,7 @@ impl DiffEditor {
     ) -> Result<Self, MergeToolConfigError> {
         let tool = get_tool_config(settings, name)?
             .unwrap_or_else(|| MergeTool::external(ExternalMergeTool::with_program(name)));
-        Self::new_inner(tool, settings, base_ignores, conflict_marker_style)
+        Self::new_inner(name, tool, settings, base_ignores, conflict_marker_style)
     }
 
     /// Loads the default diff editor from the settings.
@@ -224,15 +243,21 @@ impl DiffEditor {
             None
         }
         .unwrap_or_else(|| MergeTool::external(ExternalMergeTool::with_edit_args(&args)));
-        Self::new_inner(tool, settings, base_ignores, conflict_marker_style)
+        Self::new_inner(&args, tool, settings, base_ignores, conflict_marker_style)
     }
 
     fn new_inner(
+        name: impl ToString,
         tool: MergeTool,
         settings: &UserSettings,
         base_ignores: Arc<GitIgnoreFile>,
         conflict_marker_style: ConflictMarkerStyle,
     ) -> Result<Self, MergeToolConfigError> {
+        if matches!(tool, MergeTool::Auto(_)) {
+            return Err(MergeToolConfigError::NotADiffEditor {
+                tool_name: name.to_string(),
+            });
+        }
         Ok(DiffEditor {
             tool,
             base_ignores,
@@ -275,6 +300,7 @@ impl DiffEditor {
                     self.conflict_marker_style,
                 )
             }
+            MergeTool::Auto(_) => unreachable!("rejected by DiffEditor::new_inner()"),
         }
     }
 }
@@ -388,22 +414,130 @@ impl MergeEditor {
             .iter()
             .map(|&repo_path| MergeToolFile::from_tree_and_path(tree, repo_path))
             .try_collect()?;
+        let (tree, merge_tool_files, num_picked) =
+            self.pick_binary_sides(ui, tree, merge_tool_files)?;
+        if merge_tool_files.is_empty() {
+            return Ok((tree.id(), None));
+        }
+        let tree = &tree;
 
         match &self.tool {
             MergeTool::Builtin => {
                 let tree_id = edit_merge_builtin(tree, &merge_tool_files).map_err(Box::new)?;
                 Ok((tree_id, None))
             }
-            MergeTool::External(editor) => external::run_mergetool_external(
-                ui,
-                &self.path_converter,
-                editor,
-                tree,
-                &merge_tool_files,
-                self.conflict_marker_style,
-            ),
+            MergeTool::External(editor) => {
+                let (tree_id, partial_resolution_error) = external::run_mergetool_external(
+                    ui,
+                    &self.path_converter,
+                    editor,
+                    tree,
+                    &merge_tool_files,
+                    self.conflict_marker_style,
+                )?;
+                let partial_resolution_error =
+                    partial_resolution_error.map(|err| MergeToolPartialResolutionError {
+                        source: err.source,
+                        resolved_count: err.resolved_count + num_picked,
+                    });
+                Ok((tree_id, partial_resolution_error))
+            }
+            MergeTool::Auto(resolution) => {
+                let tree_id = auto::run_mergetool_auto(*resolution, tree, &merge_tool_files)?;
+                Ok((tree_id, None))
+            }
         }
     }
+
+    /// Starts an external merge editor once for all the specified files, which
+    /// are laid out in directories. Files which aren't resolved by the tool
+    /// are left conflicted.
+    ///
+    /// Other tools resolve the files as in [`Self::edit_files()`].
+    pub fn edit_files_in_dir(
+        &self,
+        ui: &Ui,
+        tree: &MergedTree,
+        repo_paths: &[&RepoPath],
+        include_context: bool,
+    ) -> Result<(MergedTreeId, Option<MergeToolPartialResolutionError>), ConflictResolveError> {
+        let MergeTool::External(editor) = &self.tool else {
+            return self.edit_files(ui, tree, repo_paths);
+        };
+        let merge_tool_files: Vec<MergeToolFile> = repo_paths
+            .iter()
+            .map(|&repo_path| MergeToolFile::from_tree_and_path(tree, repo_path))
+            .try_collect()?;
+        external::run_mergetool_external_dir(
+            ui,
+            &self.path_converter,
+            editor,
+            tree,
+            &merge_tool_files,
+            include_context,
+            self.conflict_marker_style,
+        )
+    }
+
+    /// Resolves the binary conflicts, which can't be merged by a merge tool,
+    /// by asking which side to keep. Returns the new tree, the other files,
+    /// and the number of resolved binary conflicts.
+    fn pick_binary_sides(
+        &self,
+        ui: &Ui,
+        tree: &MergedTree,
+        merge_tool_files: Vec<MergeToolFile>,
+    ) -> Result<(MergedTree, Vec<MergeToolFile>, usize), ConflictResolveError> {
+        if matches!(self.tool, MergeTool::Auto(_)) {
+            return Ok((tree.clone(), merge_tool_files, 0));
+        }
+        let (binary_files, other_files): (Vec<_>, Vec<_>) = merge_tool_files
+            .into_iter()
+            .partition(|file| is_binary_conflict(&file.content));
+        if binary_files.is_empty() {
+            return Ok((tree.clone(), other_files, 0));
+        }
+        let picks: Vec<_> = binary_files
+            .iter()
+            .map(|file| Ok((file, prompt_binary_side(ui, &self.path_converter, file)?)))
+            .try_collect::<_, _, ConflictResolveError>()?;
+        let tree_id = auto::run_mergetool_pick_sides(tree, &picks)?;
+        let new_tree = tree.store().get_root_tree(&tree_id)?;
+        Ok((new_tree, other_files, binary_files.len()))
+    }
+}
+
+/// Asks which side of a binary conflict to keep, listing the sides with their
+/// sizes.
+fn prompt_binary_side(
+    ui: &Ui,
+    path_converter: &RepoPathUiConverter,
+    merge_tool_file: &MergeToolFile,
+) -> Result<usize, ConflictResolveError> {
+    if !Ui::can_prompt() {
+        return Err(ConflictResolveError::BinaryConflict(
+            merge_tool_file.repo_path.clone(),
+        ));
+    }
+    let file_ids = merge_tool_file.file_merge.clone().simplify();
+    writeln!(
+        ui.stderr(),
+        "The conflict at {} is in a binary file. Its sides are:",
+        path_converter.format_file_path(&merge_tool_file.repo_path)
+    )?;
+    for (i, (id, content)) in iter::zip(file_ids.adds(), merge_tool_file.content.adds()).enumerate()
+    {
+        if id.is_some() {
+            writeln!(ui.stderr(), "{i}: {} bytes", content.len())?;
+        } else {
+            writeln!(ui.stderr(), "{i}: (deleted)")?;
+        }
+    }
+    let choices = (0..file_ids.num_sides())
+        .map(|i| i.to_string())
+        .collect_vec();
+    let choice = ui.prompt_choice("Enter the number of the side to keep", &choices, None)?;
+    Ok(choice.parse().unwrap())
 }
 
 #[cfg(test)]
@@ -437,6 +571,11 @@ mod tests {
         };
 
         insta::assert_debug_snapshot!(get(":builtin", "").unwrap(), @"Builtin");
+        insta::assert_debug_snapshot!(get(":ours", "").unwrap_err(), @r#"
+        NotADiffEditor {
+            tool_name: ":ours",
+        }
+        "#);
 
         // Just program name, edit_args are filled by default
         insta::assert_debug_snapshot!(get("my diff", "").unwrap(), @r#"
@@ -701,6 +840,11 @@ mod tests {
         };
 
         insta::assert_debug_snapshot!(get(":builtin", "").unwrap(), @"Builtin");
+        insta::assert_debug_snapshot!(get(":theirs", "").unwrap(), @r"
+        Auto(
+            Theirs,
+        )
+        ");
 
         // Just program name
         insta::assert_debug_snapshot!(get("my diff", "").unwrap_err(), @r###"
@@ -64,3 +64,86 @@ pub fn snapshot_progress(ui: &Ui) -> Option<impl Fn(&RepoPath) + '_> {
         _ = state.output.flush();
     })
 }
+
+/// Returns a callback that displays the number of files processed while files
+/// are being checked out.
+pub fn checkout_progress(ui: &Ui) -> Option<impl Fn(usize) + '_> {
+    struct State {
+        guard: Option<OutputGuard>,
+        output: ProgressOutput<std::io::Stderr>,
+        next_display_time: Instant,
+    }
+
+    let output = ui.progress_output()?;
+
+    // Don't clutter the output during fast operations.
+    let next_display_time = Instant::now() + INITIAL_DELAY;
+    let state = Mutex::new(State {
+        guard: None,
+        output,
+        next_display_time,
+    });
+
+    Some(move |processed: usize| {
+        let mut state = state.lock().unwrap();
+        let now = Instant::now();
+        if now < state.next_display_time {
+            return;
+        }
+        state.next_display_time = now + Duration::from_secs(1) / UPDATE_HZ;
+
+        if state.guard.is_none() {
+            state.guard = Some(
+                state
+                    .output
+                    .output_guard(format!("\r{}", Clear(ClearType::CurrentLine))),
+            );
+        }
+
+        _ = write!(
+            state.output,
+            "\r{}Updating {processed} files",
+            Clear(ClearType::CurrentLine),
+        );
+        _ = state.output.flush();
+    })
+}
+
+pub fn draw_progress(progress: f32, buffer: &mut String, width: usize) {
+    const CHARS: [char; 9] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉', '█'];
+    const RESOLUTION: usize = CHARS.len() - 1;
+    let ticks = (width as f32 * progress.clamp(0.0, 1.0) * RESOLUTION as f32).round() as usize;
+    let whole = ticks / RESOLUTION;
+    for _ in 0..whole {
+        buffer.push(CHARS[CHARS.len() - 1]);
+    }
+    if whole < width {
+        let fraction = ticks % RESOLUTION;
+        buffer.push(CHARS[fraction]);
+    }
+    for _ in (whole + 1)..width {
+        buffer.push(CHARS[0]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bar() {
+        let mut buf = String::new();
+        draw_progress(0.0, &mut buf, 10);
+        assert_eq!(buf, "          ");
+        buf.clear();
+        draw_progress(1.0, &mut buf, 10);
+        assert_eq!(buf, "██████████");
+        buf.clear();
+        draw_progress(0.5, &mut buf, 10);
+        assert_eq!(buf, "█████     ");
+        buf.clear();
+        draw_progress(0.54, &mut buf, 10);
+        assert_eq!(buf, "█████▍    ");
+        buf.clear();
+    }
+}
@@ -285,6 +285,39 @@ pub(super) fn evaluate_revset_to_single_commit<'a>(
     }
 }
 
+/// Like `evaluate_revset_to_single_commit()`, but doesn't load the commit
+/// object unless the revset is ambiguous.
+pub(super) fn evaluate_revset_to_single_commit_id<'a>(
+    revision_str: &str,
+    expression: &RevsetExpressionEvaluator<'_>,
+    commit_summary_template: impl FnOnce() -> TemplateRenderer<'a, Commit>,
+    should_hint_about_all_prefix: bool,
+) -> Result<CommitId, CommandError> {
+    let mut iter = expression.evaluate_to_commit_ids()?.fuse();
+    match (iter.next(), iter.next()) {
+        (Some(commit_id), None) => Ok(commit_id?),
+        (None, _) => Err(user_error(format!(
+            "Revset `{revision_str}` didn't resolve to any revisions"
+        ))),
+        (Some(commit_id0), Some(commit_id1)) => {
+            let mut iter = [commit_id0, commit_id1]
+                .into_iter()
+                .chain(iter)
+                .commits(expression.repo.store());
+            let commits: Vec<_> = iter.by_ref().take(5).try_collect()?;
+            let elided = iter.next().is_some();
+            Err(format_multiple_revisions_error(
+                revision_str,
+                expression.expression(),
+                &commits,
+                elided,
+                &commit_summary_template(),
+                should_hint_about_all_prefix,
+            ))
+        }
+    }
+}
+
 fn format_multiple_revisions_error(
     revision_str: &str,
     expression: &UserRevsetExpression,
@@ -70,6 +70,9 @@ pub trait TemplateLanguage<'a> {
     type Property: IntoTemplateProperty<'a>;
 
     fn wrap_string(property: impl TemplateProperty<Output = String> + 'a) -> Self::Property;
+    fn wrap_string_opt(
+        property: impl TemplateProperty<Output = Option<String>> + 'a,
+    ) -> Self::Property;
     fn wrap_string_list(
         property: impl TemplateProperty<Output = Vec<String>> + 'a,
     ) -> Self::Property;
@@ -126,6 +129,7 @@ macro_rules! impl_core_wrap_property_fns {
         $crate::template_builder::impl_wrap_property_fns!(
             $a, $crate::template_builder::CoreTemplatePropertyKind, $outer, {
                 wrap_string(String) => String,
+                wrap_string_opt(Option<String>) => StringOpt,
                 wrap_string_list(Vec<String>) => StringList,
                 wrap_boolean(bool) => Boolean,
                 wrap_integer(i64) => Integer,
@@ -190,6 +194,7 @@ pub trait IntoTemplateProperty<'a> {
 
 pub enum CoreTemplatePropertyKind<'a> {
     String(Box<dyn TemplateProperty<Output = String> + 'a>),
+    StringOpt(Box<dyn TemplateProperty<Output = Option<String>> + 'a>),
     StringList(Box<dyn TemplateProperty<Output = Vec<String>> + 'a>),
     Boolean(Box<dyn TemplateProperty<Output = bool> + 'a>),
     Integer(Box<dyn TemplateProperty<Output = i64> + 'a>),
@@ -219,6 +224,7 @@ impl<'a> IntoTemplateProperty<'a> for CoreTemplatePropertyKind<'a> {
     fn type_name(&self) -> &'static str {
         match self {
             CoreTemplatePropertyKind::String(_) => "String",
+            CoreTemplatePropertyKind::StringOpt(_) => "Option<String>",
             CoreTemplatePropertyKind::StringList(_) => "List<String>",
             CoreTemplatePropertyKind::Boolean(_) => "Boolean",
             CoreTemplatePropertyKind::Integer(_) => "Integer",
@@ -239,6 +245,9 @@ impl<'a> IntoTemplateProperty<'a> for CoreTemplatePropertyKind<'a> {
             CoreTemplatePropertyKind::String(property) => {
                 Some(Box::new(property.map(|s| !s.is_empty())))
             }
+            CoreTemplatePropertyKind::StringOpt(property) => {
+                Some(Box::new(property.map(|opt| opt.is_some())))
+            }
             CoreTemplatePropertyKind::StringList(property) => {
                 Some(Box::new(property.map(|l| !l.is_empty())))
             }
@@ -276,6 +285,9 @@ impl<'a> IntoTemplateProperty<'a> for CoreTemplatePropertyKind<'a> {
     fn try_into_plain_text(self) -> Option<Box<dyn TemplateProperty<Output = String> + 'a>> {
         match self {
             CoreTemplatePropertyKind::String(property) => Some(property),
+            CoreTemplatePropertyKind::StringOpt(property) => {
+                Some(Box::new(property.map(|opt| opt.unwrap_or_default())))
+            }
             _ => {
                 let template = self.try_into_template()?;
                 Some(Box::new(PlainTextFormattedProperty::new(template)))
@@ -286,6 +298,7 @@ impl<'a> IntoTemplateProperty<'a> for CoreTemplatePropertyKind<'a> {
     fn try_into_template(self) -> Option<Box<dyn Template + 'a>> {
         match self {
             CoreTemplatePropertyKind::String(property) => Some(property.into_template()),
+            CoreTemplatePropertyKind::StringOpt(property) => Some(property.into_template()),
             CoreTemplatePropertyKind::StringList(property) => Some(property.into_template()),
             CoreTemplatePropertyKind::Boolean(property) => Some(property.into_template()),
             CoreTemplatePropertyKind::Integer(property) => Some(property.into_template()),
@@ -322,6 +335,7 @@ impl<'a> IntoTemplateProperty<'a> for CoreTemplatePropertyKind<'a> {
                 Some(Box::new((lhs, rhs).map(|(l, r)| l.0 == r)))
             }
             (CoreTemplatePropertyKind::String(_), _) => None,
+            (CoreTemplatePropertyKind::StringOpt(_), _) => None,
             (CoreTemplatePropertyKind::StringList(_), _) => None,
             (CoreTemplatePropertyKind::Boolean(_), _) => None,
             (CoreTemplatePropertyKind::Integer(_), _) => None,
@@ -346,6 +360,7 @@ impl<'a> IntoTemplateProperty<'a> for CoreTemplatePropertyKind<'a> {
                 Some(Box::new((lhs, rhs).map(|(l, r)| l.cmp(&r))))
             }
             (CoreTemplatePropertyKind::String(_), _) => None,
+            (CoreTemplatePropertyKind::StringOpt(_), _) => None,
             (CoreTemplatePropertyKind::StringList(_), _) => None,
             (CoreTemplatePropertyKind::Boolean(_), _) => None,
             (CoreTemplatePropertyKind::Integer(_), _) => None,
@@ -503,6 +518,19 @@ impl<'a, L: TemplateLanguage<'a> + ?Sized> CoreTemplateBuildFnTable<'a, L> {
                 let build = template_parser::lookup_method(type_name, table, function)?;
                 build(language, diagnostics, build_ctx, property, function)
             }
+            CoreTemplatePropertyKind::StringOpt(property) => {
+                let type_name = "String";
+                let table = &self.string_methods;
+                let build = template_parser::lookup_method(type_name, table, function)?;
+                let inner_property = property.try_unwrap(type_name);
+                build(
+                    language,
+                    diagnostics,
+                    build_ctx,
+                    Box::new(inner_property),
+                    function,
+                )
+            }
             CoreTemplatePropertyKind::StringList(property) => {
                 // TODO: migrate to table?
                 build_formattable_list_method(
@@ -1233,6 +1261,16 @@ fn builtin_timestamp_range_methods<'a, L: TemplateLanguage<'a> + ?Sized>(
             Ok(L::wrap_string(out_property))
         },
     );
+    map.insert(
+        "duration_seconds",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.map(|time_range| {
+                (time_range.end.timestamp.0 - time_range.start.timestamp.0) / 1000
+            });
+            Ok(L::wrap_integer(out_property))
+        },
+    );
     map
 }
 
@@ -57,6 +57,7 @@ mod test_next_prev_commands;
 mod test_operations;
 mod test_parallelize_command;
 mod test_rebase_command;
+mod test_reorder_command;
 mod test_repo_change_report;
 mod test_resolve_command;
 mod test_restore_command;
@@ -64,6 +65,7 @@ mod test_revset_output;
 mod test_root;
 mod test_shell_completion;
 mod test_show_command;
+mod test_sign_unsign_commands;
 mod test_simplify_parents_command;
 mod test_sparse_command;
 mod test_split_command;
@@ -118,6 +118,7 @@ fn test_basics() {
       znkkpsqq 5557ece3 e | e
       vruxwmqv b7c62f28 d | d
     Deleted bookmarks: d, e
+    Hint: Run `jj undo` to restore the deleted bookmarks, or use `--retain-bookmarks` to move them to the parent revisions instead.
     Working copy now at: xtnwkqum fa4ee8e6 (empty) (no description set)
     Parent commit      : rlvkpnrz 2443ea76 a | a
     Parent commit      : royxmykx fe2e8e8b c | c
@@ -143,6 +144,7 @@ fn test_basics() {
     insta::assert_snapshot!(stderr, @r"
     Abandoned commit zsuskuln 1394f625 b | b
     Deleted bookmarks: b
+    Hint: Run `jj undo` to restore the deleted bookmarks, or use `--retain-bookmarks` to move them to the parent revisions instead.
     [EOF]
     ");
     insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r"
@@ -165,6 +167,7 @@ fn test_basics() {
       znkkpsqq 5557ece3 e | e
       vruxwmqv b7c62f28 d | d
     Deleted bookmarks: d, e
+    Hint: Run `jj undo` to restore the deleted bookmarks, or use `--retain-bookmarks` to move them to the parent revisions instead.
     Working copy now at: xlzxqlsl 14991aec (empty) (no description set)
     Parent commit      : rlvkpnrz 2443ea76 a | a
     Parent commit      : royxmykx fe2e8e8b c | c
@@ -228,6 +231,7 @@ fn test_bug_2600() {
     Abandoned commit zsuskuln 73c929fc base | base
     Deleted bookmarks: base
     Rebased 3 descendant commits onto parents of abandoned commits
+    Hint: Run `jj undo` to restore the deleted bookmarks, or use `--retain-bookmarks` to move them to the parent revisions instead.
     Working copy now at: znkkpsqq 86e31bec c | c
     Parent commit      : vruxwmqv fd6eb121 b | b
     Added 0 files, modified 0 files, removed 1 files
@@ -253,6 +257,7 @@ fn test_bug_2600() {
     Abandoned commit royxmykx 98f3b9ba a | a
     Deleted bookmarks: a
     Rebased 2 descendant commits onto parents of abandoned commits
+    Hint: Run `jj undo` to restore the deleted bookmarks, or use `--retain-bookmarks` to move them to the parent revisions instead.
     Working copy now at: znkkpsqq 683b9435 c | c
     Parent commit      : vruxwmqv c10cb7b4 b | b
     Added 0 files, modified 0 files, removed 1 files
@@ -277,6 +282,7 @@ fn test_bug_2600() {
     Abandoned commit vruxwmqv 8c0dced0 b | b
     Deleted bookmarks: b
     Rebased 1 descendant commits onto parents of abandoned commits
+    Hint: Run `jj undo` to restore the deleted bookmarks, or use `--retain-bookmarks` to move them to the parent revisions instead.
     Working copy now at: znkkpsqq 33a94991 c | c
     Parent commit      : zsuskuln 73c929fc base | base
     Parent commit      : royxmykx 98f3b9ba a | a
@@ -397,6 +403,7 @@ fn test_double_abandon() {
     insta::assert_snapshot!(stderr, @r"
     Abandoned commit rlvkpnrz 2443ea76 a | a
     Deleted bookmarks: a
+    Hint: Run `jj undo` to restore the deleted bookmarks, or use `--retain-bookmarks` to move them to the parent revisions instead.
     Working copy now at: royxmykx f37b4afd (empty) (no description set)
     Parent commit      : zzzzzzzz 00000000 (empty) (no description set)
     Added 0 files, modified 0 files, removed 1 files
@@ -411,6 +418,69 @@ fn test_double_abandon() {
     ");
 }
 
+#[test]
+fn test_abandon_bookmarks_in_stack() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    test_env.add_config("templates.commit_summary = 'description.first_line()'");
+    let repo_path = test_env.env_root().join("repo");
+
+    create_commit(&test_env, &repo_path, "base", &[]);
+    create_commit(&test_env, &repo_path, "a", &["base"]);
+    create_commit(&test_env, &repo_path, "b", &["a"]);
+    create_commit(&test_env, &repo_path, "c", &["b"]);
+    create_commit(&test_env, &repo_path, "d", &["c"]);
+    test_env.jj_cmd_ok(&repo_path, &["bookmark", "create", "-r=b", "b2"]);
+    let template = r#"separate(" ", description.first_line(), bookmarks)"#;
+
+    // Bookmarks in the middle of the abandoned range land on the stack's base
+    let (stdout, stderr) =
+        test_env.jj_cmd_ok(&repo_path, &["abandon", "--retain-bookmarks", "a::c"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r"
+    Abandoned the following commits:
+      c
+      b
+      a
+    Rebased 1 descendant commits onto parents of abandoned commits
+    Working copy now at: d
+    Parent commit      : base
+    Added 0 files, modified 0 files, removed 3 files
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-T", template]);
+    insta::assert_snapshot!(stdout, @r"
+    @  d d
+    ○  base a b b2 base c
+    ◆
+    [EOF]
+    ");
+
+    // All the deleted bookmarks are listed, along with how to get them back
+    test_env.jj_cmd_ok(&repo_path, &["undo"]);
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["abandon", "b"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r"
+    Abandoned commit b
+    Deleted bookmarks: b, b2
+    Rebased 2 descendant commits onto parents of abandoned commits
+    Hint: Run `jj undo` to restore the deleted bookmarks, or use `--retain-bookmarks` to move them to the parent revisions instead.
+    Working copy now at: d
+    Parent commit      : c
+    Added 0 files, modified 0 files, removed 1 files
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-T", template]);
+    insta::assert_snapshot!(stdout, @r"
+    @  d d
+    ○  c c
+    ○  a a
+    ○  base base
+    ◆
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_abandon_restore_descendants() {
     let test_env = TestEnvironment::default();
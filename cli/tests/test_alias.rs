@@ -14,6 +14,7 @@
 
 use std::fs;
 
+use indoc::indoc;
 use itertools::Itertools as _;
 
 use crate::common::TestEnvironment;
@@ -382,3 +383,110 @@ fn test_alias_in_config_arg() {
     [EOF]
     ");
 }
+
+#[test]
+fn test_command_defaults() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env.add_config(indoc! {r#"
+        [command-defaults]
+        log = ["-r", "root()", "--no-graph", "-T", '"default " ++ commit_id.short() ++ "\n"']
+        "operation log" = ["-n1", "--no-graph", "-T", '"op\n"']
+    "#});
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log"]);
+    insta::assert_snapshot!(stdout, @r"
+    default 000000000000
+    [EOF]
+    ");
+
+    // Arguments specified by the user override the defaults
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-T", r#""user\n""#]);
+    insta::assert_snapshot!(stdout, @r"
+    user
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-r", "none()"]);
+    insta::assert_snapshot!(stdout, @"");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "--no-graph"]);
+    insta::assert_snapshot!(stdout, @r"
+    default 000000000000
+    [EOF]
+    ");
+
+    // Defaults of subcommands, invoked by alias
+    let stdout = test_env.jj_cmd_success(&repo_path, &["op", "log"]);
+    insta::assert_snapshot!(stdout, @r"
+    op
+    [EOF]
+    ");
+
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["--no-defaults", "log", "-r", "root()", "-T", r#""plain\n""#],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    ◆  plain
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_command_defaults_with_alias() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env.add_config(indoc! {r#"
+        aliases.l = ["log", "-r", "root()"]
+        command-defaults.log = ["--no-graph", "-T", '"default\n"']
+    "#});
+
+    // Aliases are expanded before the defaults are inserted
+    let stdout = test_env.jj_cmd_success(&repo_path, &["l"]);
+    insta::assert_snapshot!(stdout, @r"
+    default
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["l", "-T", r#""user\n""#]);
+    insta::assert_snapshot!(stdout, @r"
+    user
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_command_defaults_option_value_like_command_name() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "log"]);
+
+    test_env.add_config(r#"command-defaults.log = ["--no-graph", "-T", '"default\n"']"#);
+    // The defaults are inserted after the subcommand, not the -R value
+    let (stdout, stderr) =
+        test_env.jj_cmd_ok(test_env.env_root(), &["-R", "log", "log", "-r", "root()"]);
+    insta::assert_snapshot!(stdout, @r"
+    default
+    [EOF]
+    ");
+    insta::assert_snapshot!(stderr, @"");
+}
+
+#[test]
+fn test_command_defaults_invalid() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env.add_config(r#"command-defaults.root = ["--bogus"]"#);
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["root"]);
+    insta::assert_snapshot!(stdout, @r"
+    $TEST_ENV/repo
+    [EOF]
+    ");
+    insta::assert_snapshot!(stderr, @r"
+    Warning: Ignored invalid default argument `--bogus` for `jj root`
+    [EOF]
+    ");
+}
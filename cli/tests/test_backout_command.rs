@@ -206,6 +206,29 @@ fn test_backout_multiple() {
     "#);
 }
 
+#[test]
+fn test_backout_skip_emptied() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    create_commit(&test_env, &repo_path, "a", &[], &[("a", "a\n")]);
+    create_commit(&test_env, &repo_path, "b", &["a"], &[]);
+    std::fs::remove_file(repo_path.join("a")).unwrap();
+
+    // The changes of "a" were already reverted by "b"
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &["backout", "-r", "a", "-d", "b", "--skip-emptied"],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r"
+    Skipped 1 commits whose reverse would be empty
+    Nothing changed.
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_backout_description_template() {
     let test_env = TestEnvironment::default();
@@ -228,6 +228,30 @@ fn test_commit_interactive_with_paths() {
     ");
 }
 
+#[test]
+fn test_commit_interactive_nothing_selected() {
+    let mut test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let workspace_path = test_env.env_root().join("repo");
+
+    std::fs::write(workspace_path.join("file1"), "foo\n").unwrap();
+    let edit_script = test_env.set_up_fake_editor();
+    std::fs::write(edit_script, "fail").unwrap();
+    let diff_editor = test_env.set_up_fake_diff_editor();
+    std::fs::write(diff_editor, "reset file1").unwrap();
+
+    let stderr = test_env.jj_cmd_failure(&workspace_path, &["commit", "-i"]);
+    insta::assert_snapshot!(stderr, @r"
+    Error: No changes selected
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(&workspace_path, &["diff", "--summary"]);
+    insta::assert_snapshot!(stdout, @r"
+    A file1
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_commit_with_default_description() {
     let mut test_env = TestEnvironment::default();
@@ -380,7 +404,65 @@ fn test_commit_paths() {
 }
 
 #[test]
-fn test_commit_paths_warning() {
+fn test_commit_paths_from_subdir() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let workspace_path = test_env.env_root().join("repo");
+    let subdir_path = workspace_path.join("dir");
+    std::fs::create_dir(&subdir_path).unwrap();
+
+    std::fs::write(subdir_path.join("file1"), "foo\n").unwrap();
+    std::fs::write(workspace_path.join("file2"), "bar\n").unwrap();
+
+    // Paths are relative to the current directory
+    test_env.jj_cmd_ok(&subdir_path, &["commit", "-m=first", "file1"]);
+    let stdout = test_env.jj_cmd_success(&workspace_path, &["diff", "--summary", "-r=@-"]);
+    insta::assert_snapshot!(stdout, @r"
+    A dir/file1
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(&workspace_path, &["diff", "--summary"]);
+    insta::assert_snapshot!(stdout, @r"
+    A file2
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_commit_exclude_from_subdir() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let workspace_path = test_env.env_root().join("repo");
+    let subdir_path = workspace_path.join("dir");
+    std::fs::create_dir_all(subdir_path.join("sub")).unwrap();
+
+    std::fs::write(subdir_path.join("file1"), "foo\n").unwrap();
+    std::fs::write(subdir_path.join("sub/file2"), "bar\n").unwrap();
+    std::fs::write(workspace_path.join("file3"), "baz\n").unwrap();
+
+    // Excluded paths are relative to the current directory
+    test_env.jj_cmd_ok(&subdir_path, &["commit", "-m=first", "-X", "sub"]);
+    let stdout = test_env.jj_cmd_success(&workspace_path, &["diff", "--summary", "-r=@-"]);
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    A dir/file1
+    A file3
+    [EOF]
+    ");
+
+    let stderr = test_env.jj_cmd_failure(&subdir_path, &["commit", "-m=second", "-X", "."]);
+    insta::assert_snapshot!(stderr, @r"
+    Error: The given paths do not match any changes: --exclude .
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(&workspace_path, &["diff", "--summary"]);
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    A dir/sub/file2
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_commit_paths_no_match() {
     let test_env = TestEnvironment::default();
     test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
     let workspace_path = test_env.env_root().join("repo");
@@ -388,14 +470,11 @@ fn test_commit_paths_warning() {
     std::fs::write(workspace_path.join("file1"), "foo\n").unwrap();
     std::fs::write(workspace_path.join("file2"), "bar\n").unwrap();
 
-    let (stdout, stderr) = test_env.jj_cmd_ok(&workspace_path, &["commit", "-m=first", "file3"]);
+    let stderr = test_env.jj_cmd_failure(&workspace_path, &["commit", "-m=first", "file3"]);
     insta::assert_snapshot!(stderr, @r"
-    Warning: The given paths do not match any file: file3
-    Working copy now at: rlvkpnrz d1872100 (no description set)
-    Parent commit      : qpvuntsm fa15625b (empty) first
+    Error: The given paths do not match any changes: file3
     [EOF]
     ");
-    insta::assert_snapshot!(stdout, @"");
 
     let stdout = test_env.jj_cmd_success(&workspace_path, &["diff"]);
     insta::assert_snapshot!(stdout, @r"
@@ -1470,6 +1470,64 @@ fn test_file_list_entries() {
     ");
 }
 
+#[test]
+fn test_file_list_in_revision() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::create_dir(repo_path.join("lib")).unwrap();
+    std::fs::write(repo_path.join("lib").join("big.so"), "a\n".repeat(1000)).unwrap();
+    std::fs::write(repo_path.join("lib").join("small.so"), "a\n").unwrap();
+    std::fs::write(repo_path.join("conflict.so"), "a\n".repeat(1000)).unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new", "root()"]);
+    std::fs::write(repo_path.join("conflict.so"), "b\n".repeat(1000)).unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new", "all:visible_heads()"]);
+    test_env.jj_cmd_ok(&repo_path, &["bookmark", "create", "-r@", "release"]);
+
+    // The working-copy commit has no files
+    test_env.jj_cmd_ok(&repo_path, &["new", "root()"]);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["file", "list", "glob:**/*.so"]);
+    insta::assert_snapshot!(stdout, @"");
+
+    // Predicates are evaluated against the tree of the specified revision.
+    // Conflicts never match size().
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "file",
+            "list",
+            "-r=release",
+            r#"glob:"**/*.so" & size(>1KiB)"#,
+        ],
+    );
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    lib/big.so
+    [EOF]
+    ");
+
+    let template = r#"separate(" ", path, file_type, conflict) ++ "\n""#;
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["file", "list", "-r=release", "-T", template, "glob:**/*.so"],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    conflict.so conflict true
+    lib/big.so file false
+    lib/small.so file false
+    [EOF]
+    ");
+
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["file", "show", "-r=release", "lib & size(<1KiB)"],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    a
+    [EOF]
+    ");
+}
+
 #[cfg(unix)]
 #[test]
 fn test_file_list_symlink() {
@@ -1487,6 +1545,63 @@ fn test_file_list_symlink() {
     ");
 }
 
+#[test]
+fn test_file_list_glob_i_and_repo_root() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::create_dir(repo_path.join("Dir")).unwrap();
+    std::fs::write(repo_path.join("Dir").join("README.md"), "").unwrap();
+    std::fs::write(repo_path.join("Dir").join("main.RS"), "").unwrap();
+    std::fs::write(repo_path.join("readme.txt"), "").unwrap();
+
+    // glob: is case-sensitive
+    let stdout = test_env.jj_cmd_success(&repo_path, &["file", "list", "glob:dir/*.rs"]);
+    insta::assert_snapshot!(stdout, @"");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["file", "list", "glob-i:dir/*.rs"]);
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    Dir/main.RS
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(
+        &repo_path.join("Dir"),
+        &["file", "list", "root-glob-i:README*", "glob-i:readme.md"],
+    );
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    README.md
+    ../readme.txt
+    [EOF]
+    ");
+
+    // Paths are printed relative to the workspace root
+    let stdout = test_env.jj_cmd_success(
+        &repo_path.join("Dir"),
+        &["file", "list", "--repo-root", "root:Dir"],
+    );
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    Dir/README.md
+    Dir/main.RS
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(&repo_path.join("Dir"), &["file", "list", "--cwd"]);
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    README.md
+    main.RS
+    ../readme.txt
+    [EOF]
+    ");
+    let stderr = test_env.jj_cmd_cli_error(&repo_path, &["file", "list", "--cwd", "--repo-root"]);
+    insta::assert_snapshot!(stderr, @r"
+    error: the argument '--cwd' cannot be used with '--repo-root'
+
+    Usage: jj file list --cwd [FILESETS]...
+
+    For more information, try '--help'.
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_repo_path() {
     let test_env = TestEnvironment::default();
@@ -1550,6 +1665,16 @@ fn test_signature_templates() {
     let stdout = test_env.jj_cmd_success(&repo_path, &["show", "-T", template]);
     insta::assert_snapshot!(stdout, @"good test-display signature[EOF]");
 
+    // presence can be tested on unsigned commits
+    let template = r#"signature.present() ++ "\n""#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-T", template]);
+    insta::assert_snapshot!(stdout, @r"
+    @  true
+    ○  false
+    ◆  false
+    [EOF]
+    ");
+
     // builtin templates
     test_env.add_config("ui.show-cryptographic-signatures = true");
 
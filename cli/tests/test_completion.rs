@@ -85,6 +85,7 @@ fn test_bookmark_names() {
     --ignore-immutable	Allow rewriting immutable commits
     --at-operation	Operation to load the repo at
     --debug	Enable debug logging
+    --no-defaults	Ignore the default arguments configured in `command-defaults`
     --color	When to colorize output
     --quiet	Silence non-primary command output
     --no-pager	Disable the pager
@@ -637,6 +638,7 @@ fn test_config() {
     let stdout = test_env.jj_cmd_success(dir, &["--", "jj", "config", "list", "c"]);
     insta::assert_snapshot!(stdout, @r"
     colors	Mapping from jj formatter labels to colors
+    command-defaults	Default arguments inserted after the command name, keyed by command name such as `log` or `git push`
     core
     core.fsmonitor	Whether to use an external filesystem monitor, useful for large repos
     core.watchman
@@ -66,6 +66,108 @@ fn test_concurrent_operation_divergence() {
     ");
 }
 
+#[test]
+fn test_concurrent_operations_explicit_merge() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    // Avoid operation ids in the output
+    test_env.add_config("templates.op_summary = 'tags'");
+
+    // Point the bookmark to sibling commits so the merge can't resolve it
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m", "A"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "root()", "-m", "B"]);
+    test_env.jj_cmd_ok(&repo_path, &["bookmark", "create", "-r@", "foo"]);
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "bookmark",
+            "create",
+            "-rdescription(A)",
+            "foo",
+            "--at-op",
+            "@-",
+        ],
+    );
+
+    // Listing the heads doesn't merge them
+    let stdout = test_env.jj_cmd_success(&repo_path, &["op", "heads"]);
+    insta::assert_snapshot!(stdout, @r"
+    args: jj bookmark create '-rdescription(A)' foo --at-op @-
+    args: jj bookmark create -r@ foo
+    [EOF]
+    ");
+
+    // Commands fail if automatic merging is disabled
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["log", "--config=op.auto-merge=false"]);
+    insta::assert_snapshot!(stderr, @r"
+    Error: Concurrent modification detected
+    Hint: Use `jj op heads` to list the divergent operations, and `jj op merge` to merge them.
+    [EOF]
+    ");
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["op", "merge"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r"
+    Merging operations:
+      args: jj bookmark create -r@ foo
+      args: jj bookmark create '-rdescription(A)' foo --at-op @-
+    Warning: The merged operation has conflicted bookmarks: foo
+    Hint: Use `jj bookmark list` to see details. Use `jj bookmark set <name> -r <rev>` to resolve.
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["op", "heads"]);
+    insta::assert_snapshot!(stdout, @r"
+    args: jj op merge
+    [EOF]
+    ");
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["op", "merge"]);
+    insta::assert_snapshot!(stderr, @r"
+    Nothing changed.
+    [EOF]
+    ");
+
+    // Keep one of the operations, and discard the other
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "X"]);
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "Y", "--at-op", "@-"]);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["op", "heads", "-T", "id.short()"]);
+    let keep_op_id = stdout.raw().lines().nth(1).unwrap().to_owned();
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["op", "merge", "--keep", &keep_op_id]);
+    insta::assert_snapshot!(stderr, @r"
+    Keeping operation: args: jj describe -m X
+    Discarding changes from operations:
+      args: jj describe -m Y --at-op @-
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-T", "description"]);
+    insta::assert_snapshot!(stdout, @r"
+    @  X
+    │ ○  A
+    ├─╯
+    ◆
+    [EOF]
+    ");
+
+    // Only operation heads can be kept
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "op",
+            "log",
+            "--no-graph",
+            "-n2",
+            "-T",
+            r#"id.short() ++ "\n""#,
+        ],
+    );
+    let parent_op_id = stdout.raw().lines().nth(1).unwrap();
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["op", "merge", "--keep", parent_op_id]);
+    assert_eq!(
+        stderr.raw(),
+        format!("Error: Operation {parent_op_id} is not an operation head\n")
+    );
+}
+
 #[test]
 fn test_concurrent_operations_auto_rebase() {
     let test_env = TestEnvironment::default();
@@ -79,7 +181,7 @@ fn test_concurrent_operations_auto_rebase() {
     @  c62ace5c0522 test-username@host.example.com 2001-02-03 04:05:08.000 +07:00 - 2001-02-03 04:05:08.000 +07:00
     │  describe commit 4e8f9d2be039994f589b4e57ac5e9488703e604d
     │  args: jj describe -m initial
-    ○  82d32fc68fc3 test-username@host.example.com 2001-02-03 04:05:08.000 +07:00 - 2001-02-03 04:05:08.000 +07:00
+    ○  82d32fc68fc3 test-username@host.example.com 2001-02-03 04:05:08.000 +07:00 - 2001-02-03 04:05:08.000 +07:00 (snapshot)
     │  snapshot working copy
     │  args: jj describe -m initial
     ○  eac759b9ab75 test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
@@ -270,6 +270,212 @@ fn test_config_list_layer() {
     "#);
 }
 
+#[test]
+fn test_config_list_origin() {
+    let mut test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let user_config_path = test_env.env_root().join("config.toml");
+    test_env.set_config_path(&user_config_path);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &["config", "set", "--user", "test-key", "user-val"],
+    );
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &["config", "set", "--repo", "test-key", "repo-val"],
+    );
+
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "config",
+            "list",
+            "--include-origin",
+            "--include-overridden",
+            "test-key",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r#"
+    # test-key = "user-val"  # user $TEST_ENV/config.toml
+    test-key = "repo-val"  # repo $TEST_ENV/repo/.jj/repo/config.toml
+    [EOF]
+    "#);
+
+    // Command-line arguments take precedence
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "config",
+            "list",
+            "--include-origin",
+            "--include-overridden",
+            "test-key",
+            "--config=test-key=cli-val",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r#"
+    # test-key = "user-val"  # user $TEST_ENV/config.toml
+    # test-key = "repo-val"  # repo $TEST_ENV/repo/.jj/repo/config.toml
+    test-key = "cli-val"  # cli
+    [EOF]
+    "#);
+
+    // Values derived from environment variables
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["config", "list", "--include-origin", "user.name"],
+    );
+    insta::assert_snapshot!(stdout, @r#"
+    user.name = "Test User"  # env
+    [EOF]
+    "#);
+
+    // Built-in default values
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "config",
+            "list",
+            "--include-defaults",
+            r#"-Tname ++ " " ++ self.source() ++ " " ++ self.overridden() ++ "\n""#,
+            "ui.allow-init-native",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    ui.allow-init-native default false
+    [EOF]
+    ");
+
+    // --include-origin uses the built-in template
+    let stderr = test_env.jj_cmd_cli_error(
+        &repo_path,
+        &["config", "list", "--include-origin", "-Tname"],
+    );
+    insta::assert_snapshot!(stderr, @r"
+    error: the argument '--include-origin' cannot be used with '--template <TEMPLATE>'
+
+    Usage: jj config list --include-origin [NAME]
+
+    For more information, try '--help'.
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_config_check() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["config", "list", "--check"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r"
+    No problems found in config
+    [EOF]
+    ");
+
+    test_env.add_config(indoc! {r#"
+        [ui]
+        default-comand = "log"
+        x-experimental = true
+
+        [git]
+        auto-local-bookmark = "yes"
+
+        [x-mytool]
+        foo = 1
+    "#});
+
+    // Unknown keys are reported by any command other than `jj config`
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["root"]);
+    insta::assert_snapshot!(stderr, @r"
+    Warning: Unknown config key ui.default-comand in $TEST_ENV/config/config0002.toml:2
+    Hint: Did you mean `ui.default-command`, `ui.default-description`?
+    [EOF]
+    ");
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["config", "list", "--check"]);
+    insta::assert_snapshot!(stderr, @r"
+    Warning: Unknown config key ui.default-comand in $TEST_ENV/config/config0002.toml:2
+    Hint: Did you mean `ui.default-command`, `ui.default-description`?
+    Warning: Invalid type for git.auto-local-bookmark: expected boolean, but is string in $TEST_ENV/config/config0002.toml:6
+    Config error: Found 2 problem(s) in config
+    For help, see https://jj-vcs.github.io/jj/latest/config/.
+    [EOF]
+    ");
+
+    // Unknown keys without similar known names
+    let stderr = test_env.jj_cmd_failure(
+        &repo_path,
+        &["config", "list", "--check", "--config=ui.zzz=1"],
+    );
+    insta::assert_snapshot!(stderr, @r"
+    Warning: Unknown config key ui.default-comand in $TEST_ENV/config/config0002.toml:2
+    Hint: Did you mean `ui.default-command`, `ui.default-description`?
+    Warning: Invalid type for git.auto-local-bookmark: expected boolean, but is string in $TEST_ENV/config/config0002.toml:6
+    Warning: Unknown config key ui.zzz in cli config
+    Hint: Config keys prefixed with `x-` aren't checked
+    Config error: Found 3 problem(s) in config
+    For help, see https://jj-vcs.github.io/jj/latest/config/.
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_config_multiple_user_config_files() {
+    let mut test_env = TestEnvironment::default();
+    let org_config_path = test_env.env_root().join("org.toml");
+    let user_config_path = test_env.env_root().join("user.toml");
+    std::fs::write(
+        &org_config_path,
+        indoc! {r#"
+            test-key = "org-val"
+            test-org-key = "org-val"
+        "#},
+    )
+    .unwrap();
+    std::fs::write(&user_config_path, "test-key = \"user-val\"\n").unwrap();
+    test_env.set_config_path(std::env::join_paths([&org_config_path, &user_config_path]).unwrap());
+
+    // Later files take precedence
+    let stdout = test_env.jj_cmd_success(
+        test_env.env_root(),
+        &[
+            "config",
+            "list",
+            "--include-origin",
+            "--include-overridden",
+            "--user",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r#"
+    # test-key = "org-val"  # user $TEST_ENV/org.toml
+    test-org-key = "org-val"  # user $TEST_ENV/org.toml
+    test-key = "user-val"  # user $TEST_ENV/user.toml
+    [EOF]
+    "#);
+
+    // Only the last file is modified
+    test_env.jj_cmd_ok(
+        test_env.env_root(),
+        &["config", "set", "--user", "test-org-key", "user-val"],
+    );
+    insta::assert_snapshot!(std::fs::read_to_string(&org_config_path).unwrap(), @r#"
+    test-key = "org-val"
+    test-org-key = "org-val"
+    "#);
+    insta::assert_snapshot!(std::fs::read_to_string(&user_config_path).unwrap(), @r#"
+    test-key = "user-val"
+    test-org-key = "user-val"
+    "#);
+    let stdout = test_env.jj_cmd_success(test_env.env_root(), &["config", "path", "--user"]);
+    insta::assert_snapshot!(stdout, @r"
+    $TEST_ENV/user.toml
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_config_layer_override_default() {
     let test_env = TestEnvironment::default();
@@ -625,6 +831,73 @@ fn test_config_set_for_repo() {
     "###);
 }
 
+#[test]
+fn test_config_set_preserves_formatting() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    let repo_config_path = repo_path.join(".jj/repo/config.toml");
+    std::fs::write(
+        &repo_config_path,
+        indoc! {r#"
+            # Settings for this repo
+            [test-table]
+            # The list
+            list = ["a"] # one item
+            table = { x = 1 }
+
+            [other]
+            key   =   "untouched"  # keep me
+        "#},
+    )
+    .unwrap();
+
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "config",
+            "set",
+            "--repo",
+            "test-table.list",
+            r#"["a", "b"]"#,
+        ],
+    );
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "config",
+            "set",
+            "--repo",
+            "test-table.table",
+            "{ x = 2, y = 3 }",
+        ],
+    );
+    insta::assert_snapshot!(std::fs::read_to_string(&repo_config_path).unwrap(), @r#"
+    # Settings for this repo
+    [test-table]
+    # The list
+    list = ["a", "b"] # one item
+    table = { x = 2, y = 3 }
+
+    [other]
+    key   =   "untouched"  # keep me
+    "#);
+
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &["config", "unset", "--repo", "test-table.table"],
+    );
+    insta::assert_snapshot!(std::fs::read_to_string(&repo_config_path).unwrap(), @r#"
+    # Settings for this repo
+    [test-table]
+    # The list
+    list = ["a", "b"] # one item
+
+    [other]
+    key   =   "untouched"  # keep me
+    "#);
+}
+
 #[test]
 fn test_config_set_toml_types() {
     let mut test_env = TestEnvironment::default();
@@ -989,12 +1262,10 @@ fn test_config_get() {
     [EOF]
     ");
 
-    let stdout = test_env.jj_cmd_failure(test_env.env_root(), &["config", "get", "table.list"]);
+    let stdout = test_env.jj_cmd_success(test_env.env_root(), &["config", "get", "table.list"]);
     insta::assert_snapshot!(stdout, @r"
-    Config error: Invalid type or value for table.list
-    Caused by: Expected a value convertible to a string, but is an array
-    Hint: Check the config file: $TEST_ENV/config/config0002.toml
-    For help, see https://jj-vcs.github.io/jj/latest/config/.
+    list
+    value
     [EOF]
     ");
 
@@ -1013,6 +1284,211 @@ fn test_config_get() {
     bar
     [EOF]
     ");
+
+    // Multiple names
+    let stdout = test_env.jj_cmd_success(
+        test_env.env_root(),
+        &["config", "get", "table.string", "table.int", "table.list"],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    table.string=some value 1
+    table.int=123
+    table.list=list
+    table.list=value
+    [EOF]
+    ");
+
+    // Nothing is printed if any of the names is missing
+    let stdout = test_env.jj_cmd_failure(
+        test_env.env_root(),
+        &["config", "get", "table.string", "nonexistent"],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    Config error: Value not found for nonexistent
+    For help, see https://jj-vcs.github.io/jj/latest/config/.
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_config_get_default() {
+    let test_env = TestEnvironment::default();
+    test_env.add_config(r#"test-key = "test-val""#);
+
+    let stdout = test_env.jj_cmd_success(
+        test_env.env_root(),
+        &["config", "get", "--default=fallback", "nonexistent"],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    fallback
+    [EOF]
+    ");
+
+    let stdout = test_env.jj_cmd_success(
+        test_env.env_root(),
+        &[
+            "config",
+            "get",
+            "--default=fallback",
+            "test-key",
+            "nonexistent",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    test-key=test-val
+    nonexistent=fallback
+    [EOF]
+    ");
+
+    // The default value is checked against the type
+    let stdout = test_env.jj_cmd_success(
+        test_env.env_root(),
+        &[
+            "config",
+            "get",
+            "--type=int",
+            "--default= 42",
+            "nonexistent",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    42
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(
+        test_env.env_root(),
+        &[
+            "config",
+            "get",
+            "--type=json",
+            "--default=[1, 2]",
+            "nonexistent",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    [1,2]
+    [EOF]
+    ");
+    let stderr = test_env.jj_cmd_failure(
+        test_env.env_root(),
+        &["config", "get", "--type=bool", "--default=yes", "test-key"],
+    );
+    insta::assert_snapshot!(stderr, @r#"
+    Error: Invalid --default value
+    Caused by: Expected a boolean, but is "yes"
+    [EOF]
+    "#);
+    let stderr = test_env.jj_cmd_failure(
+        test_env.env_root(),
+        &[
+            "config",
+            "get",
+            "--type=json",
+            "--default=fallback",
+            "nonexistent",
+        ],
+    );
+    insta::assert_snapshot!(stderr, @r"
+    Error: Invalid --default value
+    Caused by: expected ident at line 1 column 4
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_config_get_type() {
+    let test_env = TestEnvironment::default();
+    test_env.add_config(indoc! {r#"
+        [table]
+        string = "some value"
+        int = 123
+        int-string = " 42"
+        bool = true
+        bool-string = "false"
+        list = ["list", 1]
+    "#});
+    let get = |args: &[&str]| {
+        let mut all_args = vec!["config", "get"];
+        all_args.extend(args);
+        test_env.jj_cmd_success(test_env.env_root(), &all_args)
+    };
+    let get_error = |args: &[&str]| {
+        let mut all_args = vec!["config", "get"];
+        all_args.extend(args);
+        test_env.jj_cmd_failure(test_env.env_root(), &all_args)
+    };
+
+    insta::assert_snapshot!(get(&["--type=string", "table.string"]), @r"
+    some value
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_error(&["--type=string", "table.int"]), @r"
+    Config error: Invalid type or value for table.int
+    Caused by: Expected a string, but is an integer
+    Hint: Check the config file: $TEST_ENV/config/config0002.toml
+    For help, see https://jj-vcs.github.io/jj/latest/config/.
+    [EOF]
+    ");
+
+    insta::assert_snapshot!(get(&["--type=int", "table.int", "table.int-string"]), @r"
+    table.int=123
+    table.int-string=42
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_error(&["--type=int", "table.string"]), @r#"
+    Config error: Invalid type or value for table.string
+    Caused by: Expected an integer, but is "some value"
+    Hint: Check the config file: $TEST_ENV/config/config0002.toml
+    For help, see https://jj-vcs.github.io/jj/latest/config/.
+    [EOF]
+    "#);
+    insta::assert_snapshot!(get_error(&["--type=int", "table.bool"]), @r"
+    Config error: Invalid type or value for table.bool
+    Caused by: Expected an integer, but is a boolean
+    Hint: Check the config file: $TEST_ENV/config/config0002.toml
+    For help, see https://jj-vcs.github.io/jj/latest/config/.
+    [EOF]
+    ");
+
+    insta::assert_snapshot!(get(&["--type=bool", "table.bool", "table.bool-string"]), @r"
+    table.bool=true
+    table.bool-string=false
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_error(&["--type=bool", "table.list"]), @r"
+    Config error: Invalid type or value for table.list
+    Caused by: Expected a boolean, but is an array
+    Hint: Check the config file: $TEST_ENV/config/config0002.toml
+    For help, see https://jj-vcs.github.io/jj/latest/config/.
+    [EOF]
+    ");
+
+    insta::assert_snapshot!(get(&["--type=json", "table.string"]), @r#"
+    "some value"
+    [EOF]
+    "#);
+    insta::assert_snapshot!(get(&["--type=json", "table.list"]), @r#"
+    ["list",1]
+    [EOF]
+    "#);
+    insta::assert_snapshot!(get(&["--type=json", "table"]), @r#"
+    {"bool":true,"bool-string":"false","int":123,"int-string":" 42","list":["list",1],"string":"some value"}
+    [EOF]
+    "#);
+
+    // Array items are printed one per line, but tables need --type=json
+    insta::assert_snapshot!(get(&["table.list"]), @r"
+    list
+    1
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_error(&["table"]), @r"
+    Config error: Invalid type or value for table
+    Caused by: Expected a value convertible to a string, but is a table
+    Hint: Check the config file: $TEST_ENV/config/config0002.toml
+    For help, see https://jj-vcs.github.io/jj/latest/config/.
+    [EOF]
+    ");
 }
 
 #[test]
@@ -1096,7 +1572,7 @@ fn test_config_path_syntax() {
     ");
     let stderr = test_env.jj_cmd_cli_error(test_env.env_root(), &["config", "get", "."]);
     insta::assert_snapshot!(stderr, @r"
-    error: invalid value '.' for '<NAME>': TOML parse error at line 1, column 1
+    error: invalid value '.' for '<NAMES>...': TOML parse error at line 1, column 1
       |
     1 | .
       | ^
@@ -1319,6 +1795,150 @@ fn test_config_conditional_without_home_dir() {
     ");
 }
 
+#[test]
+fn test_config_conditional_repository_patterns() {
+    let mut test_env = TestEnvironment::default();
+    let work_path = test_env.env_root().join("work");
+    std::fs::create_dir(&work_path).unwrap();
+    test_env.jj_cmd_ok(&work_path, &["git", "init", "repo"]);
+    test_env.jj_cmd_ok(&work_path.join("repo"), &["git", "init", "nested"]);
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "other"]);
+    let user_config_path = test_env.env_root().join("config.toml");
+    test_env.set_config_path(&user_config_path);
+    std::fs::write(
+        &user_config_path,
+        format!(
+            indoc! {"
+                foo = 'global'
+                [[--scope]]
+                --when.repositories = [{work_pattern}]
+                foo = 'work'
+                [[--scope]]
+                --when.repositories = [{nested_pattern}]
+                foo = 'nested'
+            "},
+            work_pattern = to_toml_value(dunce::simplified(&work_path.join("*")).to_str().unwrap()),
+            nested_pattern = to_toml_value(
+                dunce::simplified(&work_path.join("**").join("nested"))
+                    .to_str()
+                    .unwrap()
+            ),
+        ),
+    )
+    .unwrap();
+
+    let stdout = test_env.jj_cmd_success(&work_path.join("repo"), &["config", "get", "foo"]);
+    insta::assert_snapshot!(stdout, @r"
+    work
+    [EOF]
+    ");
+    // The nested repo is also inside a directory matching the first pattern
+    let stdout = test_env.jj_cmd_success(
+        &work_path.join("repo").join("nested"),
+        &["config", "get", "foo"],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    nested
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(
+        &test_env.env_root().join("other"),
+        &["config", "get", "foo"],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    global
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_config_conditional_remotes() {
+    let mut test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "--colocate", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    let user_config_path = test_env.env_root().join("config.toml");
+    test_env.set_config_path(&user_config_path);
+    std::fs::write(
+        &user_config_path,
+        indoc! {"
+            foo = 'global'
+            [[--scope]]
+            --when.remotes = ['git@github.com:corp/*']
+            foo = 'corp'
+        "},
+    )
+    .unwrap();
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["config", "get", "foo"]);
+    insta::assert_snapshot!(stdout, @r"
+    global
+    [EOF]
+    ");
+
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "git",
+            "remote",
+            "add",
+            "origin",
+            "git@github.com:corp/repo.git",
+        ],
+    );
+    let stdout = test_env.jj_cmd_success(&repo_path, &["config", "get", "foo"]);
+    insta::assert_snapshot!(stdout, @r"
+    corp
+    [EOF]
+    ");
+    // The active scope is available to the list template
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "config",
+            "list",
+            "--user",
+            r#"-Tname ++ ": " ++ scope ++ "\n""#,
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    foo: --when.remotes = ['git@github.com:corp/*']
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_config_conditional_malformed() {
+    let mut test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    let user_config_path = test_env.env_root().join("config.toml");
+    test_env.set_config_path(&user_config_path);
+    std::fs::write(
+        &user_config_path,
+        indoc! {"
+            foo = 'global'
+            [[--scope]]
+            --when.remote = ['git@github.com:corp/*']
+            foo = 'typo'
+            [[--scope]]
+            --when.remotes = ['[corp']
+            foo = 'bad pattern'
+        "},
+    )
+    .unwrap();
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["config", "get", "foo"]);
+    insta::assert_snapshot!(stdout, @r"
+    global
+    [EOF]
+    ");
+    insta::assert_snapshot!(stderr, @r#"
+    Warning: Ignored config scope in $TEST_ENV/config.toml with unknown condition --when.remote
+    Warning: Ignored config scope in $TEST_ENV/config.toml with invalid pattern "[corp" in --when.remotes: Pattern syntax error near position 0: invalid range pattern
+    [EOF]
+    "#);
+}
+
 #[test]
 fn test_config_show_paths() {
     let test_env = TestEnvironment::default();
@@ -87,6 +87,39 @@ fn test_debug_revset() {
     });
 }
 
+#[test]
+fn test_debug_revset_stats() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let workspace_path = test_env.env_root().join("repo");
+    for i in 0..10 {
+        test_env.jj_cmd_ok(&workspace_path, &["new", "-m", &format!("other {i}")]);
+    }
+    test_env.jj_cmd_ok(&workspace_path, &["new", "-m", "match"]);
+    test_env.jj_cmd_ok(&workspace_path, &["new", "-m", "other top"]);
+    let get_stats = |args: &[&str]| {
+        let stdout = test_env.jj_cmd_success(&workspace_path, args);
+        let (_, stats) = stdout.raw().split_once("-- Stats:\n").unwrap();
+        stats.to_owned()
+    };
+
+    // The filter stops being evaluated once enough commits are found.
+    let stats = get_stats(&["debug", "revset", "--stats", "-n1", "description(match)"]);
+    assert_eq!(stats, "Predicate evaluations: 2\n");
+    let stats = get_stats(&[
+        "debug",
+        "revset",
+        "--stats",
+        "-n1",
+        "heads(description(match))",
+    ]);
+    assert_eq!(stats, "Predicate evaluations: 2\n");
+
+    // All commits are tested if the whole set is evaluated.
+    let stats = get_stats(&["debug", "revset", "--stats", "description(match)"]);
+    assert_eq!(stats, "Predicate evaluations: 14\n");
+}
+
 #[test]
 fn test_debug_index() {
     let test_env = TestEnvironment::default();
@@ -17,6 +17,7 @@ use std::path::PathBuf;
 
 use indoc::indoc;
 
+use crate::common::force_interactive;
 use crate::common::get_stderr_string;
 use crate::common::CommandOutput;
 use crate::common::TestEnvironment;
@@ -240,6 +241,73 @@ fn test_describe_editor_env() {
     assert!(get_stderr_string(&assert).contains("bad-jj-editor-from-jj-editor-env"));
 }
 
+#[test]
+fn test_describe_multiple_commits_message_confirmation() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+
+    // Declining the prompt doesn't change anything
+    let output = test_env.run_jj_with(|cmd| {
+        force_interactive(cmd)
+            .current_dir(&repo_path)
+            .args(["describe", "@", "@-", "-m", "description from CLI"])
+            .write_stdin("n\n")
+    });
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Set the same description on 2 commits? (yN): Error: Describe aborted
+    [EOF]
+    [exit status: 1]
+    ");
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r"
+    @  65b6b74e0897
+    ○  230dd059e1b0
+    ◆  000000000000
+    [EOF]
+    ");
+
+    let output = test_env.run_jj_with(|cmd| {
+        force_interactive(cmd)
+            .current_dir(&repo_path)
+            .args(["describe", "@", "@-", "-m", "description from CLI"])
+            .write_stdin("y\n")
+    });
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Set the same description on 2 commits? (yN): Updated 2 commits
+    Working copy now at: rlvkpnrz dab85127 (empty) description from CLI
+    Parent commit      : qpvuntsm a42f5755 (empty) description from CLI
+    [EOF]
+    ");
+
+    // No prompt with --yes
+    let output = test_env.run_jj_with(|cmd| {
+        force_interactive(cmd).current_dir(&repo_path).args([
+            "describe",
+            "@",
+            "@-",
+            "-m",
+            "another description",
+            "--yes",
+        ])
+    });
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Updated 2 commits
+    Working copy now at: rlvkpnrz be8484ce (empty) another description
+    Parent commit      : qpvuntsm eb9e513d (empty) another description
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r"
+    @  be8484ceb61f another description
+    ○  eb9e513d52a4 another description
+    ◆  000000000000
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_describe_multiple_commits() {
     let mut test_env = TestEnvironment::default();
@@ -615,6 +683,58 @@ fn test_describe_default_description() {
     "###);
 }
 
+#[test]
+fn test_describe_new_commit_description_template() {
+    let mut test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    test_env.add_config(indoc! {r#"
+        [templates]
+        new_commit_description = '''
+        concat(
+          parents.map(|c| c.local_bookmarks().map(|b| "[" ++ b.name().upper() ++ "]")).join(""),
+          "\n\nReviewed-by: TODO\n",
+        )
+        '''
+    "#});
+    let workspace_path = test_env.env_root().join("repo");
+    test_env.jj_cmd_ok(&workspace_path, &["describe", "-m", "base"]);
+    test_env.jj_cmd_ok(
+        &workspace_path,
+        &["bookmark", "create", "-r@", "ticket-123"],
+    );
+    test_env.jj_cmd_ok(&workspace_path, &["new"]);
+
+    std::fs::write(workspace_path.join("file1"), "foo\n").unwrap();
+    let edit_script = test_env.set_up_fake_editor();
+    std::fs::write(&edit_script, ["dump editor"].join("\0")).unwrap();
+    test_env.jj_cmd_ok(&workspace_path, &["describe"]);
+    insta::assert_snapshot!(
+        std::fs::read_to_string(test_env.env_root().join("editor")).unwrap(), @r#"
+    [TICKET-123]
+
+    Reviewed-by: TODO
+
+    JJ: This commit contains the following changes:
+    JJ:     A file1
+
+    JJ: Lines starting with "JJ:" (like this one) will be removed.
+    "#);
+
+    // The template isn't used if the commit already has a description
+    test_env.jj_cmd_ok(&workspace_path, &["describe", "-m", "existing"]);
+    std::fs::write(&edit_script, ["dump editor"].join("\0")).unwrap();
+    test_env.jj_cmd_ok(&workspace_path, &["describe"]);
+    insta::assert_snapshot!(
+        std::fs::read_to_string(test_env.env_root().join("editor")).unwrap(), @r#"
+    existing
+
+    JJ: This commit contains the following changes:
+    JJ:     A file1
+
+    JJ: Lines starting with "JJ:" (like this one) will be removed.
+    "#);
+}
+
 #[test]
 fn test_describe_author() {
     let mut test_env = TestEnvironment::default();
@@ -796,6 +916,46 @@ fn test_describe_author() {
     "#);
 }
 
+#[test]
+fn test_describe_author_keeps_description() {
+    let mut test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "first\n\nbody"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "second"]);
+    let edit_script = test_env.set_up_fake_editor();
+    std::fs::write(edit_script, "fail").unwrap();
+
+    // Only the metadata changes, and the editor isn't opened
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "describe",
+            "@-",
+            "@",
+            "--no-edit",
+            "--author",
+            "Super Seeder <super.seeder@example.com>",
+        ],
+    );
+    let template = r#"author.name() ++ " <" ++ author.email() ++ ">\n" ++ description ++ "\n""#;
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["log", "--no-graph", "-r=@-::", "-T", template],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    Super Seeder <super.seeder@example.com>
+    second
+
+    Super Seeder <super.seeder@example.com>
+    first
+
+    body
+
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_describe_avoids_unc() {
     let mut test_env = TestEnvironment::default();
@@ -518,6 +518,336 @@ fn test_diff_name_only() {
     ");
 }
 
+#[test]
+fn test_diff_file_predicates() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("small"), "a\n").unwrap();
+    std::fs::write(repo_path.join("large"), "a\n".repeat(1000)).unwrap();
+    std::fs::write(repo_path.join("script"), "#!/bin/sh\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["file", "chmod", "x", "script"]);
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--name-only", "size(>1KiB)"]);
+    insta::assert_snapshot!(stdout, @r"
+    large
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--name-only", "size(<=10)"]);
+    insta::assert_snapshot!(stdout, @r"
+    script
+    small
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--name-only", "~executable()"]);
+    insta::assert_snapshot!(stdout, @r"
+    large
+    small
+    [EOF]
+    ");
+
+    // Predicates are evaluated against the new tree
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+    std::fs::remove_file(repo_path.join("large")).unwrap();
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--name-only", "size(>1KiB)"]);
+    insta::assert_snapshot!(stdout, @"");
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["diff", "size(1KiB)"]);
+    insta::assert_snapshot!(stderr, @r"
+    Error: Failed to parse fileset: Function `size`: Expected size comparison such as `>1MiB`
+    Caused by:  --> 1:6
+      |
+    1 | size(1KiB)
+      |      ^--^
+      |
+      = Function `size`: Expected size comparison such as `>1MiB`
+    [EOF]
+    ");
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["status", "size(>1KiB)"]);
+    insta::assert_snapshot!(stderr, @r"
+    Error: Function `size()` can't be used here because there's no tree to evaluate it against
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_diff_fileset_aliases() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.add_config(
+        r#"
+    [fileset-aliases]
+    'docs' = 'glob:"*.md" ~ CHANGELOG.md'
+    'sources' = 'root:src ~ generated'
+    'generated' = 'root-glob:"src/*.gen.rs"'
+    'recurse' = 'recurse1'
+    'recurse1' = 'recurse'
+    'bad' = 'all(x)'
+    'big(x)' = 'size(x)'
+    "#,
+    );
+
+    std::fs::create_dir(repo_path.join("src")).unwrap();
+    std::fs::write(repo_path.join("README.md"), "").unwrap();
+    std::fs::write(repo_path.join("CHANGELOG.md"), "").unwrap();
+    std::fs::write(repo_path.join("src/lib.rs"), "a\n".repeat(1000)).unwrap();
+    std::fs::write(repo_path.join("src/foo.gen.rs"), "").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "first"]);
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--name-only", "docs"]);
+    insta::assert_snapshot!(stdout, @r"
+    README.md
+    [EOF]
+    ");
+
+    // Alias referring to another alias
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--name-only", "sources"]);
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    src/lib.rs
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--name-only", "big(>1KiB)"]);
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    src/lib.rs
+    [EOF]
+    ");
+
+    // Aliases are also resolved in fileset arguments of revsets
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "log",
+            "--no-graph",
+            "-r",
+            "files(sources)",
+            "-T",
+            "description",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    first
+    [EOF]
+    ");
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["diff", "recurse"]);
+    insta::assert_snapshot!(stderr, @r"
+    Error: Failed to parse fileset: In alias `recurse`
+    Caused by:
+    1:  --> 1:1
+      |
+    1 | recurse
+      | ^-----^
+      |
+      = In alias `recurse`
+    2:  --> 1:1
+      |
+    1 | recurse1
+      | ^------^
+      |
+      = In alias `recurse1`
+    3:  --> 1:1
+      |
+    1 | recurse
+      | ^-----^
+      |
+      = Alias `recurse` expanded recursively
+    [EOF]
+    ");
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["diff", "docs | bad"]);
+    insta::assert_snapshot!(stderr, @r"
+    Error: Failed to parse fileset: In alias `bad`
+    Caused by:
+    1:  --> 1:8
+      |
+    1 | docs | bad
+      |        ^-^
+      |
+      = In alias `bad`
+    2:  --> 1:5
+      |
+    1 | all(x)
+      |     ^
+      |
+      = Function `all`: Expected 0 arguments
+    [EOF]
+    ");
+
+    // Unknown function alias
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["diff", "bug(>1KiB)"]);
+    insta::assert_snapshot!(stderr, @r"
+    Error: Failed to parse fileset: Function `bug` doesn't exist
+    Caused by:  --> 1:1
+      |
+    1 | bug(>1KiB)
+      | ^-^
+      |
+      = Function `bug` doesn't exist
+    Hint: Did you mean `big`?
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_diff_glob_brace_alternatives() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::create_dir_all(repo_path.join("src/sub")).unwrap();
+    std::fs::write(repo_path.join("src/lib.rs"), "").unwrap();
+    std::fs::write(repo_path.join("src/README.md"), "").unwrap();
+    std::fs::write(repo_path.join("src/sub/mod.rs"), "").unwrap();
+    std::fs::write(repo_path.join("src/Cargo.toml"), "").unwrap();
+    std::fs::write(repo_path.join("top.rs"), "").unwrap();
+
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["diff", "--name-only", "glob:src/**/*.{rs,md}"],
+    );
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    src/README.md
+    src/lib.rs
+    src/sub/mod.rs
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["diff", "--name-only", r#"glob:"{src,src/sub}/*.rs""#],
+    );
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    src/lib.rs
+    src/sub/mod.rs
+    [EOF]
+    ");
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["diff", "glob:src/*.{rs,md"]);
+    insta::assert_snapshot!(stderr, @r"
+    Error: Failed to parse fileset: Invalid file pattern
+    Caused by:
+    1:  --> 1:1
+      |
+    1 | glob:src/*.{rs,md
+      | ^---------------^
+      |
+      = Invalid file pattern
+    2: Pattern syntax error near position 6: unmatched `{`
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_diff_fileset_parse_errors() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::create_dir(repo_path.join("src")).unwrap();
+    std::fs::write(repo_path.join("src/lib.rs"), "").unwrap();
+
+    // Unknown pattern kind
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["diff", "glb:**/*.rs"]);
+    insta::assert_snapshot!(stderr, @r"
+    Error: Failed to parse fileset: Invalid file pattern kind `glb:`
+    Caused by:  --> 1:1
+      |
+    1 | glb:**/*.rs
+      | ^--^
+      |
+      = Invalid file pattern kind `glb:`
+    Hint: Did you mean `glob:`, `glob-i:`? Valid pattern kinds are `cwd:`, `cwd-file:`, `file:`, `cwd-glob:`, `glob:`, `cwd-glob-i:`, `glob-i:`, `root:`, `root-file:`, `root-glob:`, `root-glob-i:`
+    [EOF]
+    ");
+
+    // Bare path containing glob characters is matched literally, with a hint
+    // if nothing matches
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["diff", "--name-only", "src/*.rs"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr.normalize_backslash(), @r#"
+    Warning: No matching entries for paths: src/*.rs
+    Hint: Paths are matched literally. Use glob:"src/*.rs" to match src/*.rs as a glob pattern.
+    [EOF]
+    "#);
+
+    // No hint if the path exists
+    std::fs::write(repo_path.join("src/[a].rs"), "").unwrap();
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["diff", "--name-only", "src/[a].rs"]);
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    src/[a].rs
+    [EOF]
+    ");
+    insta::assert_snapshot!(stderr, @"");
+}
+
+#[test]
+fn test_diff_exclude_from_subdir() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    let subdir_path = repo_path.join("dir");
+
+    std::fs::create_dir_all(subdir_path.join("sub")).unwrap();
+    std::fs::write(subdir_path.join("file1"), "a\n").unwrap();
+    std::fs::write(subdir_path.join("sub/file2"), "b\n").unwrap();
+    std::fs::write(subdir_path.join("sub/file3.rs"), "c\n").unwrap();
+    std::fs::write(repo_path.join("file4"), "d\n").unwrap();
+
+    // Excluded paths are relative to the current directory
+    let stdout = test_env.jj_cmd_success(&subdir_path, &["diff", "--name-only", "-X", "sub"]);
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    file1
+    ../file4
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(
+        &subdir_path,
+        &["diff", "--name-only", "--exclude", "glob:**/*.rs"],
+    );
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    file1
+    sub/file2
+    ../file4
+    [EOF]
+    ");
+
+    // Exclusions are subtracted from the positional paths
+    let stdout = test_env.jj_cmd_success(
+        &subdir_path,
+        &[
+            "diff",
+            "--name-only",
+            ".",
+            "-X",
+            "sub/file2",
+            "-X",
+            "../file4",
+        ],
+    );
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    file1
+    sub/file3.rs
+    [EOF]
+    ");
+
+    // Explicit paths which don't exist are reported
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &subdir_path,
+        &["diff", "--name-only", "sub", "-X", "sub/missing"],
+    );
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    sub/file2
+    sub/file3.rs
+    [EOF]
+    ");
+    insta::assert_snapshot!(stderr.normalize_backslash(), @r"
+    Warning: No matching entries for paths: sub/missing
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_diff_bad_args() {
     let test_env = TestEnvironment::default();
@@ -438,6 +438,10 @@ fn test_diffedit_external_tool_conflict_marker_style() {
     Parent commit: zsuskuln 8b3de837 side-b
     Warning: There are unresolved conflicts at these paths:
     file    2-sided conflict
+    Conflicting changes to file come from:
+      rlvkpnrz 3765cc27 side-a
+      zsuskuln 8b3de837 side-b
+    Hint: To resolve the conflicts, use `jj resolve`, or edit the conflict markers in the file directly.
     [EOF]
     ");
 }
@@ -781,3 +785,116 @@ fn test_diffedit_restore_descendants() {
     [EOF]
     "#);
 }
+
+#[test]
+fn test_diffedit_paths_restore_descendants() {
+    let mut test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.add_config(r#"templates.commit_summary = 'description.first_line()'"#);
+
+    std::fs::write(repo_path.join("file1"), "a\n").unwrap();
+    std::fs::write(repo_path.join("file2"), "a\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m", "first"]);
+    std::fs::write(repo_path.join("file1"), "b\n").unwrap();
+    std::fs::write(repo_path.join("file2"), "b\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m", "second"]);
+    std::fs::write(repo_path.join("file1"), "c\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "third"]);
+    let old_wc_id = test_env
+        .jj_cmd_success(&repo_path, &["log", "--no-graph", "-r@", "-T", "commit_id"])
+        .into_raw();
+
+    // Only the specified paths are shown in the diff editor
+    let edit_script = test_env.set_up_fake_diff_editor();
+    std::fs::write(
+        &edit_script,
+        "files-before file1\0files-after JJ-INSTRUCTIONS file1\0reset file1",
+    )
+    .unwrap();
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &["diffedit", "-r@-", "--restore-descendants", "file1"],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r"
+    Created second
+    Rebased 1 descendant commits (while preserving their content)
+    Working copy now at: third
+    Parent commit      : second
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "-s", "-r@-"]);
+    insta::assert_snapshot!(stdout, @r"
+    M file2
+    [EOF]
+    ");
+    // The descendant's tree is unchanged
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "-s", "--from", &old_wc_id]);
+    insta::assert_snapshot!(stdout, @"");
+
+    // The same can be done without a diff editor
+    test_env.jj_cmd_ok(&repo_path, &["undo"]);
+    std::fs::write(&edit_script, "fail").unwrap();
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "diffedit",
+            "-r@-",
+            "--restore-descendants",
+            "--reset",
+            "file1",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r"
+    Created second
+    Rebased 1 descendant commits (while preserving their content)
+    Working copy now at: third
+    Parent commit      : second
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "-s", "-r@-"]);
+    insta::assert_snapshot!(stdout, @r"
+    M file2
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "-s", "--from", &old_wc_id]);
+    insta::assert_snapshot!(stdout, @"");
+    // file2 in the working copy now matches its parent again
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "-s"]);
+    insta::assert_snapshot!(stdout, @r"
+    M file1
+    [EOF]
+    ");
+
+    // Files can be reset to the contents of another revision
+    test_env.jj_cmd_ok(&repo_path, &["undo"]);
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "diffedit",
+            "--from=root()",
+            "--to=@-",
+            "--restore-descendants",
+            "--reset",
+            "file2",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r"
+    Created second
+    Rebased 1 descendant commits (while preserving their content)
+    Working copy now at: third
+    Parent commit      : second
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "-s", "-r@-"]);
+    insta::assert_snapshot!(stdout, @r"
+    M file1
+    D file2
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "-s", "--from", &old_wc_id]);
+    insta::assert_snapshot!(stdout, @"");
+}
@@ -88,7 +88,7 @@ fn test_duplicate() {
     let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["undo"]);
     insta::assert_snapshot!(stdout, @"");
     insta::assert_snapshot!(stderr, @r"
-    Undid operation: 6eead29c6998 (2001-02-03 08:05:17) duplicate 1 commit(s)
+    Undid operation: 0108f84e0945 (2001-02-03 08:05:17) duplicate 1 commit(s)
     [EOF]
     ");
     let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["duplicate" /* duplicates `c` */]);
@@ -2457,6 +2457,39 @@ fn test_duplicate_insert_after_before() {
 }
 
 // https://github.com/jj-vcs/jj/issues/1050
+#[test]
+fn test_duplicate_chain_onto_unrelated_branch() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    create_commit(&test_env, &repo_path, "a", &[]);
+    create_commit(&test_env, &repo_path, "b", &["a"]);
+    create_commit(&test_env, &repo_path, "release", &[]);
+
+    test_env.jj_cmd_ok(&repo_path, &["duplicate", "a::b", "-d", "release"]);
+    let template =
+        r#"separate(" ", description.first_line(), bookmarks, if(root, "root")) ++ "\n""#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-T", template]);
+    insta::assert_snapshot!(stdout, @r"
+    ○  b
+    ○  a
+    @  release release
+    │ ○  b b
+    │ ○  a a
+    ├─╯
+    ◆  root
+    [EOF]
+    ");
+    // The duplicates have the same diffs as the originals
+    for (original, duplicate) in [("a", "release+"), ("b", "release++")] {
+        let original_diff = test_env.jj_cmd_success(&repo_path, &["diff", "--git", "-r", original]);
+        let duplicate_diff =
+            test_env.jj_cmd_success(&repo_path, &["diff", "--git", "-r", duplicate]);
+        assert_eq!(original_diff.raw(), duplicate_diff.raw());
+    }
+}
+
 #[test]
 fn test_undo_after_duplicate() {
     let test_env = TestEnvironment::default();
@@ -2487,7 +2520,7 @@ fn test_undo_after_duplicate() {
     let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["undo"]);
     insta::assert_snapshot!(stdout, @"");
     insta::assert_snapshot!(stderr, @r"
-    Undid operation: d64d953f7d2b (2001-02-03 08:05:11) duplicate 1 commit(s)
+    Undid operation: 54e1d1354e12 (2001-02-03 08:05:11) duplicate 1 commit(s)
     [EOF]
     ");
     insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r"
@@ -117,6 +117,76 @@ fn test_edit_current_wc_commit_missing() {
     "###);
 }
 
+#[test]
+fn test_edit_clone() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.add_config(r#"templates.commit_summary = 'description.first_line()'"#);
+    std::fs::write(repo_path.join("file1"), "0").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m", "base"]);
+    std::fs::write(repo_path.join("file1"), "1").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m", "release"]);
+    test_env.jj_cmd_ok(&repo_path, &["bookmark", "create", "-r@-", "main"]);
+    test_env.add_config(r#"revset-aliases."immutable_heads()" = "main""#);
+    let main_id = test_env
+        .jj_cmd_success(
+            &repo_path,
+            &["log", "--no-graph", "-T=commit_id.short()", "-r=main"],
+        )
+        .into_raw();
+    let log_template = r#"separate(" ", description.first_line(), bookmarks)"#;
+
+    // An immutable commit is duplicated, and the copy is edited
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["edit", "--clone", "main"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr.normalize_with(|s| s.replace(&main_id, "<main>")), @r"
+    Duplicated immutable commit <main> as release
+    Working copy now at: release
+    Parent commit      : base
+    [EOF]
+    ");
+    // The bookmark stays on the original commit
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-r=::", "-T", log_template]);
+    insta::assert_snapshot!(stdout, @r"
+    @  release
+    │ ◆  release main
+    ├─╯
+    ◆  base
+    ◆
+    [EOF]
+    ");
+
+    // A mutable commit is edited as usual
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "child"]);
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["edit", "--clone", "@-"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r"
+    Working copy now at: release
+    Parent commit      : base
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-r=::", "-T", log_template]);
+    insta::assert_snapshot!(stdout, @r"
+    ○  child
+    @  release
+    │ ◆  release main
+    ├─╯
+    ◆  base
+    ◆
+    [EOF]
+    ");
+
+    // Without --clone, editing an immutable commit is still an error
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["edit", "main"]);
+    insta::assert_snapshot!(stderr.normalize_with(|s| s.replace(&main_id, "<main>")), @r"
+    Error: Commit <main> is immutable
+    Hint: Could not modify commit: release
+    Hint: Pass `--ignore-immutable` or configure the set of immutable commits via `revset-aliases.immutable_heads()`.
+    [EOF]
+    ");
+}
+
 fn read_file(path: &Path) -> String {
     String::from_utf8(std::fs::read(path).unwrap()).unwrap()
 }
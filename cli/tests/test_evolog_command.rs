@@ -453,3 +453,130 @@ fn test_evolog_reverse_with_graph() {
     [EOF]
     ");
 }
+
+#[test]
+fn test_evolog_with_operations() {
+    let mut test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.add_config(r#"templates.op_summary = 'description.first_line()'"#);
+    let template = r#"separate(" ", description.first_line(), if(empty, "(empty)")) ++ "\n""#;
+    let normalize_commit_ids = |s: String| {
+        regex::Regex::new("[0-9a-f]{40}")
+            .unwrap()
+            .replace_all(&s, "<commit>")
+            .into_owned()
+    };
+
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "first"]);
+    std::fs::write(repo_path.join("file1"), "foo\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "second"]);
+    std::fs::write(repo_path.join("file1"), "foo\nbar\n").unwrap();
+    std::fs::write(repo_path.join("file2"), "foo2\n").unwrap();
+    let edit_script = test_env.set_up_fake_diff_editor();
+    std::fs::write(edit_script, "reset file2").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["squash", "-i"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "root()", "-m", "base"]);
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &["rebase", "-s", "description(first)", "-d", "@"],
+    );
+
+    // Each version is diffed against its predecessors, not against its parents
+    let output = test_env.run_jj_in(
+        &repo_path,
+        [
+            "evolog",
+            "-p",
+            "--operations",
+            "-r",
+            "description(first)",
+            "-T",
+            template,
+        ],
+    );
+    insta::assert_snapshot!(output.normalize_stdout_with(normalize_commit_ids), @r"
+    ○  first
+    │  -- operation rebase commit <commit> and descendants
+    ○    first
+    ├─╮  -- operation squash commits into <commit>
+    │ │  Removed regular file file2:
+    │ │     1     : foo2
+    │ ○  second
+    │ │  -- operation snapshot working copy
+    │ │  Modified regular file file1:
+    │ │     1    1: foo
+    │ │          2: bar
+    │ │  Added regular file file2:
+    │ │          1: foo2
+    │ ○  second (empty)
+    │    -- operation new empty commit
+    ○  first
+    │  -- operation snapshot working copy
+    │  Added regular file file1:
+    │          1: foo
+    ○  first (empty)
+    │  -- operation describe commit <commit>
+    ○  (empty)
+       -- operation add workspace 'default'
+    [EOF]
+    ");
+
+    let output = test_env.run_jj_in(
+        &repo_path,
+        [
+            "evolog",
+            "--operations",
+            "--reversed",
+            "--no-graph",
+            "-r",
+            "description(first)",
+            "-T",
+            template,
+        ],
+    );
+    insta::assert_snapshot!(output.normalize_stdout_with(normalize_commit_ids), @r"
+    (empty)
+    -- operation add workspace 'default'
+    first (empty)
+    -- operation describe commit <commit>
+    first
+    -- operation snapshot working copy
+    second (empty)
+    -- operation new empty commit
+    second
+    -- operation snapshot working copy
+    first
+    -- operation squash commits into <commit>
+    first
+    -- operation rebase commit <commit> and descendants
+    [EOF]
+    ");
+
+    // Versions created by abandoned operations aren't attributed to the oldest
+    // remaining operation
+    test_env.jj_cmd_ok(&repo_path, &["op", "abandon", "..@-"]);
+    let output = test_env.run_jj_in(
+        &repo_path,
+        [
+            "evolog",
+            "--operations",
+            "--no-graph",
+            "-r",
+            "description(first)",
+            "-T",
+            template,
+        ],
+    );
+    insta::assert_snapshot!(output.normalize_stdout_with(normalize_commit_ids), @r"
+    first
+    -- operation rebase commit <commit> and descendants
+    first
+    second
+    second (empty)
+    first
+    first (empty)
+    (empty)
+    [EOF]
+    ");
+}
@@ -271,3 +271,58 @@ fn test_chmod_file_dir_deletion_conflicts() {
     [EOF]
     ");
 }
+
+#[cfg(unix)]
+#[test]
+fn test_chmod_with_preserved_executable_bit() {
+    use std::os::unix::fs::PermissionsExt as _;
+
+    let test_env = TestEnvironment::default();
+    test_env.add_config(r#"working-copy.executable-bit = "preserve-recorded""#);
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    let file_path = repo_path.join("file");
+    let set_mode = |mode| {
+        std::fs::set_permissions(&file_path, std::fs::Permissions::from_mode(mode)).unwrap();
+    };
+
+    // The executable bit on disk is ignored for new files
+    std::fs::write(&file_path, "contents\n").unwrap();
+    set_mode(0o755);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["debug", "tree"]);
+    insta::assert_snapshot!(stdout, @r#"
+    file: Ok(Resolved(Some(File { id: FileId("12f00e90b6ef79117ce6e650416b8cf517099b78"), executable: false })))
+    [EOF]
+    "#);
+
+    // Only `jj file chmod` can change the recorded executable bit
+    test_env.jj_cmd_ok(&repo_path, &["file", "chmod", "x", "file"]);
+    set_mode(0o644);
+    std::fs::write(&file_path, "modified\n").unwrap();
+    let stdout = test_env.jj_cmd_success(&repo_path, &["debug", "tree"]);
+    insta::assert_snapshot!(stdout, @r#"
+    file: Ok(Resolved(Some(File { id: FileId("2e0996000b7e9019eabcad29391bf0f5c7702f0b"), executable: true })))
+    [EOF]
+    "#);
+}
+
+#[test]
+fn test_chmod_with_fileset_exclusion() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::create_dir(repo_path.join("generated")).unwrap();
+    std::fs::write(repo_path.join("run.sh"), "").unwrap();
+    std::fs::write(repo_path.join("generated/build.sh"), "").unwrap();
+
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &["file", "chmod", "x", "glob:**/*.sh ~ generated"],
+    );
+    let stdout = test_env.jj_cmd_success(&repo_path, &["file", "list", "executable()"]);
+    insta::assert_snapshot!(stdout, @r"
+    run.sh
+    [EOF]
+    ");
+}
@@ -35,9 +35,16 @@ fn test_track_untrack() {
     // patterns
     test_env.jj_cmd_ok(&repo_path, &["st"]);
     std::fs::write(repo_path.join(".gitignore"), "*.bak\n").unwrap();
-    let files_before = test_env
-        .jj_cmd_success(&repo_path, &["file", "list"])
-        .into_raw();
+    let (files_before, stderr) = test_env.jj_cmd_ok(&repo_path, &["file", "list"]);
+    let files_before = files_before.into_raw();
+    // The newly ignored files are reported only once
+    insta::assert_snapshot!(stderr, @r"
+    Warning: Some tracked files match ignore patterns and will still be snapshotted:
+      file1.bak
+      file2.bak
+    Hint: Run `jj file untrack --matching-ignores` to stop tracking them.
+    [EOF]
+    ");
 
     // Errors out when not run at the head operation
     let stderr =
@@ -63,7 +70,7 @@ fn test_track_untrack() {
     insta::assert_snapshot!(stderr, @r"
     Error: 'file1' is not ignored.
     Hint: Files that are not ignored will be added back by the next command.
-    Make sure they're ignored, then try again.
+    Make sure they're ignored or excluded from `snapshot.auto-track`, then try again.
     [EOF]
     ");
     let files_after = test_env
@@ -98,7 +105,8 @@ fn test_track_untrack() {
         stderr.raw(),
         format!(
             "Error: '{}' and 1 other files are not ignored.\nHint: Files that are not ignored \
-             will be added back by the next command.\nMake sure they're ignored, then try again.\n",
+             will be added back by the next command.\nMake sure they're ignored or excluded from \
+             `snapshot.auto-track`, then try again.\n",
             PathBuf::from("target").join("file2").display()
         )
     );
@@ -212,6 +220,46 @@ fn test_auto_track() {
     ");
 }
 
+#[test]
+fn test_auto_track_config_override() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    let no_auto_track = "--config=snapshot.auto-track='none()'";
+
+    std::fs::write(repo_path.join("file1"), "initial").unwrap();
+
+    // Auto-tracking can be disabled for a single command
+    let stdout = test_env.jj_cmd_success(&repo_path, &["status", no_auto_track]);
+    insta::assert_snapshot!(stdout, @r"
+    Untracked paths:
+    ? file1
+    Working copy : qpvuntsm 230dd059 (empty) (no description set)
+    Parent commit: zzzzzzzz 00000000 (empty) (no description set)
+    [EOF]
+    ");
+
+    // Tracked files are unaffected by the override
+    test_env.jj_cmd_ok(&repo_path, &["file", "track", "file1", no_auto_track]);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["file", "list", no_auto_track]);
+    insta::assert_snapshot!(stdout, @r"
+    file1
+    [EOF]
+    ");
+
+    // Can untrack a file that isn't ignored but isn't auto-tracked
+    test_env.jj_cmd_ok(&repo_path, &["file", "untrack", "file1", no_auto_track]);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["file", "list", no_auto_track]);
+    insta::assert_snapshot!(stdout, @"");
+
+    // Without the override, the file is tracked again
+    let stdout = test_env.jj_cmd_success(&repo_path, &["file", "list"]);
+    insta::assert_snapshot!(stdout, @r"
+    file1
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_track_ignored() {
     let test_env = TestEnvironment::default();
@@ -242,3 +290,71 @@ fn test_track_ignored() {
     [EOF]
     ");
 }
+
+#[test]
+fn test_untrack_matching_ignores() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::create_dir(repo_path.join("build")).unwrap();
+    std::fs::write(repo_path.join("build").join("out"), "").unwrap();
+    std::fs::write(repo_path.join("file.log"), "").unwrap();
+    std::fs::write(repo_path.join("kept"), "").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+
+    // Tracked files matching new ignore patterns are reported once
+    std::fs::write(repo_path.join(".gitignore"), "build/\n*.log\n").unwrap();
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["file", "list"]);
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    .gitignore
+    build/out
+    file.log
+    kept
+    [EOF]
+    ");
+    insta::assert_snapshot!(stderr.normalize_backslash(), @r"
+    Warning: Some tracked files match ignore patterns and will still be snapshotted:
+      build/out
+      file.log
+    Hint: Run `jj file untrack --matching-ignores` to stop tracking them.
+    [EOF]
+    ");
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["file", "list"]);
+    insta::assert_snapshot!(stderr, @"");
+
+    // Path filters limit which files are untracked
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &["file", "untrack", "--matching-ignores", "build"],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr.normalize_backslash(), @r"
+    Untracking 1 files:
+      build/out
+    [EOF]
+    ");
+
+    let (stdout, stderr) =
+        test_env.jj_cmd_ok(&repo_path, &["file", "untrack", "--matching-ignores"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r"
+    Untracking 1 files:
+      file.log
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["file", "list"]);
+    insta::assert_snapshot!(stdout, @r"
+    .gitignore
+    kept
+    [EOF]
+    ");
+
+    let (stdout, stderr) =
+        test_env.jj_cmd_ok(&repo_path, &["file", "untrack", "--matching-ignores"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r"
+    No tracked files match ignore patterns.
+    [EOF]
+    ");
+}
@@ -725,6 +725,7 @@ fn test_git_clone_ignore_working_copy(subprocess: bool) {
     }
 
     // TODO: Correct, but might be better to check out the root commit?
+    test_env.add_config("workspace.auto-update-stale = false");
     let stderr = test_env.jj_cmd_failure(&clone_path, &["status"]);
     insta::allow_duplicates! {
     insta::assert_snapshot!(stderr, @r"
@@ -1050,6 +1051,127 @@ fn test_git_clone_with_depth_subprocess() {
     insta::assert_snapshot!(stderr, @"");
 }
 
+#[test]
+fn test_git_clone_with_filter_git2() {
+    let test_env = TestEnvironment::default();
+    test_env.add_config("git.subprocess = false");
+    let git_repo_path = test_env.env_root().join("source");
+    let git_repo = git2::Repository::init(git_repo_path).unwrap();
+    set_up_non_empty_git_repo(&git_repo);
+
+    let stderr = test_env.jj_cmd_failure(
+        test_env.env_root(),
+        &["git", "clone", "--filter=blob:none", "source", "clone"],
+    );
+    insta::assert_snapshot!(stderr, @r"
+    Error: Partial clones are not supported by the built-in Git client
+    Hint: Set `git.subprocess = true` to use the git executable.
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_git_clone_with_filter_subprocess() {
+    let test_env = TestEnvironment::default();
+    let clone_path = test_env.env_root().join("clone");
+    let git_repo_path = test_env.env_root().join("source");
+    let git_repo = git2::Repository::init(&git_repo_path).unwrap();
+    let mut config = git_repo.config().unwrap();
+    config.set_bool("uploadpack.allowFilter", true).unwrap();
+    config
+        .set_bool("uploadpack.allowAnySHA1InWant", true)
+        .unwrap();
+    // Each commit modifies the file, so every commit has its own blob
+    let signature =
+        git2::Signature::new("Some One", "some.one@example.com", &git2::Time::new(0, 0)).unwrap();
+    let mut parents = vec![];
+    for content in ["0", "a", "b"] {
+        let mut tree_builder = git_repo.treebuilder(None).unwrap();
+        let file_oid = git_repo.blob(content.as_bytes()).unwrap();
+        tree_builder
+            .insert("file", file_oid, git2::FileMode::Blob.into())
+            .unwrap();
+        let tree = git_repo.find_tree(tree_builder.write().unwrap()).unwrap();
+        let parent_refs = parents.iter().collect::<Vec<_>>();
+        let commit_oid = git_repo
+            .commit(
+                Some("refs/heads/main"),
+                &signature,
+                &signature,
+                content,
+                &tree,
+                &parent_refs,
+            )
+            .unwrap();
+        parents = vec![git_repo.find_commit(commit_oid).unwrap()];
+    }
+    git_repo.set_head("refs/heads/main").unwrap();
+
+    // Every fetch from the promisor remote creates a pack with a ".promisor"
+    // marker file
+    let count_fetches = || {
+        std::fs::read_dir(clone_path.join(".git").join("objects").join("pack"))
+            .unwrap()
+            .filter(|entry| {
+                let path = entry.as_ref().unwrap().path();
+                path.extension().is_some_and(|ext| ext == "promisor")
+            })
+            .count()
+    };
+
+    test_env.jj_cmd_ok(
+        test_env.env_root(),
+        &[
+            "git",
+            "clone",
+            "--colocate",
+            "--filter=blob:none",
+            "source",
+            "clone",
+        ],
+    );
+    // The initial fetch and the file contents of the checked-out commit
+    assert_eq!(count_fetches(), 2);
+    assert_eq!(
+        std::fs::read_to_string(clone_path.join("file")).unwrap(),
+        "b"
+    );
+
+    // Operations on trees don't need file contents
+    test_env.jj_cmd_ok(&clone_path, &["log", "-r=all()"]);
+    test_env.jj_cmd_ok(&clone_path, &["diff", "--summary", "-r=main"]);
+    assert_eq!(count_fetches(), 2);
+
+    // Missing file contents are fetched at once
+    let stdout = test_env.jj_cmd_success(&clone_path, &["diff", "--git", "-r=main"]);
+    insta::assert_snapshot!(stdout, @r"
+    diff --git a/file b/file
+    index 2e65efe2a1..63d8dbd40c 100644
+    --- a/file
+    +++ b/file
+    @@ -1,1 +1,1 @@
+    -a
+    \ No newline at end of file
+    +b
+    \ No newline at end of file
+    [EOF]
+    ");
+    assert_eq!(count_fetches(), 3);
+    test_env.jj_cmd_ok(&clone_path, &["diff", "--git", "-r=main"]);
+    assert_eq!(count_fetches(), 3);
+
+    // Missing file contents can't be fetched if the remote is unavailable
+    std::fs::remove_dir_all(&git_repo_path).unwrap();
+    let stderr = test_env.jj_cmd_failure(&clone_path, &["file", "show", "-r=root()+", "file"]);
+    insta::assert_snapshot!(stderr, @r"
+    Error: Error when reading object c227083464fb9af8955c90d2924774ee50abb547 of type file
+    Caused by:
+    1: Failed to fetch missing objects from promisor remote 'origin'
+    2: Could not find repository at '$TEST_ENV/source'
+    [EOF]
+    ");
+}
+
 #[test_case(false; "use git2 for remote calls")]
 #[test_case(true; "spawn a git subprocess for remote calls")]
 fn test_git_clone_invalid_immutable_heads(subprocess: bool) {
@@ -1109,6 +1231,7 @@ fn test_git_clone_malformed(subprocess: bool) {
     }
 
     // The cloned workspace isn't usable.
+    test_env.add_config("workspace.auto-update-stale = false");
     let stderr = test_env.jj_cmd_failure(&clone_path, &["status"]);
     insta::allow_duplicates! {
     insta::assert_snapshot!(stderr, @r"
@@ -98,6 +98,116 @@ fn test_git_colocated() {
     );
 }
 
+#[test]
+fn test_git_colocated_linked_worktree() {
+    let test_env = TestEnvironment::default();
+    let main_root = test_env.env_root().join("main");
+    let worktree_root = test_env.env_root().join("worktree");
+    let git_repo = git2::Repository::init(&main_root).unwrap();
+
+    // Create an initial commit in the main checkout
+    std::fs::write(main_root.join("file"), "contents").unwrap();
+    let mut index = git_repo.index().unwrap();
+    index.add_path(Path::new("file")).unwrap();
+    let tree1_oid = index.write_tree().unwrap();
+    let tree1 = git_repo.find_tree(tree1_oid).unwrap();
+    let signature = git2::Signature::new(
+        "Someone",
+        "someone@example.com",
+        &git2::Time::new(1234567890, 60),
+    )
+    .unwrap();
+    let initial_oid = git_repo
+        .commit(
+            Some("refs/heads/master"),
+            &signature,
+            &signature,
+            "initial",
+            &tree1,
+            &[],
+        )
+        .unwrap();
+
+    // Check out "feature" bookmark in a linked worktree
+    let initial_commit = git_repo.find_commit(initial_oid).unwrap();
+    let feature_bookmark = git_repo.branch("feature", &initial_commit, false).unwrap();
+    let mut worktree_options = git2::WorktreeAddOptions::new();
+    worktree_options.reference(Some(feature_bookmark.get()));
+    git_repo
+        .worktree("worktree", &worktree_root, Some(&worktree_options))
+        .unwrap();
+    assert!(worktree_root.join(".git").is_file());
+    std::fs::write(
+        main_root.join(".git").join("info").join("exclude"),
+        "ignored\n",
+    )
+    .unwrap();
+    std::fs::write(worktree_root.join("ignored"), "").unwrap();
+
+    test_env.jj_cmd_ok(&worktree_root, &["git", "init", "--colocate"]);
+    let stdout = test_env.jj_cmd_success(&worktree_root, &["root"]);
+    insta::assert_snapshot!(stdout, @r"
+    $TEST_ENV/worktree
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(&worktree_root, &["git", "root"]);
+    insta::assert_snapshot!(stdout, @r"
+    $TEST_ENV/main/.git/worktrees/worktree
+    [EOF]
+    ");
+
+    // The worktree HEAD is imported, and info/exclude of the main repo is
+    // respected
+    let template =
+        r#"separate(" ", commit_id, bookmarks, if(git_head, "git_head()"), description)"#;
+    let stdout = test_env.jj_cmd_success(
+        &worktree_root,
+        &["log", "--no-graph", "-r=@-", "-T", template],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    e61b6729ff4292870702f2f72b2a60165679ef37 feature master git_head() initial
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(&worktree_root, &["file", "list"]);
+    insta::assert_snapshot!(stdout, @r"
+    file
+    [EOF]
+    ");
+
+    // Snapshot a change and commit it. Only the worktree HEAD should move.
+    std::fs::write(worktree_root.join("file"), "modified").unwrap();
+    test_env.jj_cmd_ok(&worktree_root, &["commit", "-m", "modified"]);
+    let modified_commit_id = test_env
+        .jj_cmd_success(
+            &worktree_root,
+            &["log", "--no-graph", "-r=@-", "-T=commit_id"],
+        )
+        .into_raw();
+    let worktree_repo = git2::Repository::open(&worktree_root).unwrap();
+    assert!(worktree_repo.head_detached().unwrap());
+    assert_eq!(
+        worktree_repo.head().unwrap().target().unwrap().to_string(),
+        modified_commit_id
+    );
+    assert_eq!(
+        git_repo.find_reference("HEAD").unwrap().symbolic_target(),
+        Some("refs/heads/master")
+    );
+    assert_eq!(git_repo.head().unwrap().target(), Some(initial_oid));
+
+    // Bookmarks are exported to the refs shared with the main checkout
+    test_env.jj_cmd_ok(&worktree_root, &["bookmark", "set", "-r@-", "feature"]);
+    assert_eq!(
+        git_repo
+            .find_reference("refs/heads/feature")
+            .unwrap()
+            .target()
+            .unwrap()
+            .to_string(),
+        modified_commit_id
+    );
+}
+
 #[test]
 fn test_git_colocated_unborn_bookmark() {
     let test_env = TestEnvironment::default();
@@ -587,6 +697,7 @@ fn test_git_colocated_fetch_deleted_or_moved_bookmark() {
     bookmark: B_to_delete@origin [deleted] untracked
     bookmark: C_to_move@origin   [updated] tracked
     Abandoned 2 commits that are no longer reachable.
+    Pruned 1 remote bookmark that no longer exists on the remote.
     [EOF]
     ");
     // "original C" and "B_to_delete" are abandoned, as the corresponding bookmarks
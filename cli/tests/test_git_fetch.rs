@@ -1771,6 +1771,7 @@ fn test_git_fetch_removed_bookmark(subprocess: bool) {
     insta::assert_snapshot!(stderr, @r"
     bookmark: a2@origin [deleted] untracked
     Abandoned 1 commits that are no longer reachable.
+    Pruned 1 remote bookmark that no longer exists on the remote.
     [EOF]
     ");
     insta::assert_snapshot!(get_log_output(&test_env, &target_jj_repo_path), @r"
@@ -1880,6 +1881,7 @@ fn test_git_fetch_removed_parent_bookmark(subprocess: bool) {
     bookmark: a1@origin     [deleted] untracked
     bookmark: trunk1@origin [deleted] untracked
     Abandoned 1 commits that are no longer reachable.
+    Pruned 2 remote bookmarks that no longer exist on the remote.
     Warning: No branch matching `master` found on any specified/configured remote
     [EOF]
     ");
@@ -2090,3 +2092,135 @@ fn test_git_fetch_preserve_commits_across_repos(subprocess: bool) {
     ");
     }
 }
+
+#[test_case(false; "use git2 for remote calls")]
+#[test_case(true; "spawn a git subprocess for remote calls")]
+fn test_git_fetch_remote_default_branch_changed(subprocess: bool) {
+    let test_env = TestEnvironment::default();
+    if !subprocess {
+        test_env.add_config("git.subprocess = false");
+    }
+    let git_repo = init_git_remote(&test_env, "devel");
+    git_repo.set_head("refs/heads/devel").unwrap();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "clone", "devel", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["config", "list", "--repo", "revset-aliases.'trunk()'"],
+    );
+    insta::allow_duplicates! {
+    insta::assert_snapshot!(stdout, @r#"
+    revset-aliases.'trunk()' = "devel@origin"
+    [EOF]
+    "#);
+    }
+
+    // Fetching without changes to the default branch doesn't touch trunk()
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["git", "fetch"]);
+    insta::allow_duplicates! {
+    insta::assert_snapshot!(stderr, @r"
+    Nothing changed.
+    [EOF]
+    ");
+    }
+
+    // Change the default branch in remote
+    add_commit_to_branch(&git_repo, "trunk");
+    git_repo.set_head("refs/heads/trunk").unwrap();
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["git", "fetch"]);
+    insta::allow_duplicates! {
+    insta::assert_snapshot!(stderr, @r"
+    bookmark: trunk@origin [new] untracked
+    The default branch of remote `origin` changed from `devel` to `trunk`
+    Setting the revset alias `trunk()` to `trunk@origin`
+    [EOF]
+    ");
+    }
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["config", "list", "--repo", "revset-aliases.'trunk()'"],
+    );
+    insta::allow_duplicates! {
+    insta::assert_snapshot!(stdout, @r#"
+    revset-aliases.'trunk()' = "trunk@origin"
+    [EOF]
+    "#);
+    }
+
+    // A user-defined trunk() alias isn't overwritten
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "config",
+            "set",
+            "--repo",
+            "revset-aliases.'trunk()'",
+            "devel",
+        ],
+    );
+    git_repo.set_head("refs/heads/devel").unwrap();
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["git", "fetch"]);
+    insta::allow_duplicates! {
+    insta::assert_snapshot!(stderr, @r"
+    Nothing changed.
+    [EOF]
+    ");
+    }
+}
+
+#[test_case(false; "use git2 for remote calls")]
+#[test_case(true; "spawn a git subprocess for remote calls")]
+fn test_git_fetch_prune_deleted_bookmarks(subprocess: bool) {
+    let test_env = TestEnvironment::default();
+    if !subprocess {
+        test_env.add_config("git.subprocess = false");
+    }
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    let git_repo = add_git_remote(&test_env, &repo_path, "origin");
+    add_commit_to_branch(&git_repo, "a");
+    add_commit_to_branch(&git_repo, "b");
+    test_env.jj_cmd_ok(&repo_path, &["git", "fetch"]);
+
+    // Delete bookmarks in the remote
+    for branch in ["a", "b"] {
+        git_repo
+            .find_branch(branch, git2::BranchType::Local)
+            .unwrap()
+            .delete()
+            .unwrap();
+    }
+
+    // Bookmarks outside of the --branch patterns aren't pruned
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["git", "fetch", "--branch", "a"]);
+    insta::allow_duplicates! {
+    insta::assert_snapshot!(stderr, @r"
+    bookmark: a@origin [deleted] untracked
+    Abandoned 1 commits that are no longer reachable.
+    Pruned 1 remote bookmark that no longer exists on the remote.
+    [EOF]
+    ");
+    }
+
+    // Pruning can be disabled
+    let (_stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &["git", "fetch", "--config=git.prune-on-fetch=false"],
+    );
+    insta::allow_duplicates! {
+    insta::assert_snapshot!(stderr, @r"
+    Nothing changed.
+    [EOF]
+    ");
+    }
+
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["git", "fetch"]);
+    insta::allow_duplicates! {
+    insta::assert_snapshot!(stderr, @r"
+    bookmark: b@origin [deleted] untracked
+    Abandoned 1 commits that are no longer reachable.
+    Pruned 1 remote bookmark that no longer exists on the remote.
+    [EOF]
+    ");
+    }
+}
@@ -1012,6 +1012,96 @@ fn test_git_push_changes(subprocess: bool) {
     }
 }
 
+#[test_case(false; "use git2 for remote calls")]
+#[test_case(true; "spawn a git subprocess for remote calls")]
+fn test_git_push_changes_with_template(subprocess: bool) {
+    let (test_env, workspace_root) = set_up();
+    if !subprocess {
+        test_env.add_config("git.subprocess = false");
+    }
+    test_env.jj_cmd_ok(&workspace_root, &["describe", "-m", "foo"]);
+    std::fs::write(workspace_root.join("file"), "contents").unwrap();
+    test_env.jj_cmd_ok(&workspace_root, &["new", "-m", "bar"]);
+    std::fs::write(workspace_root.join("file"), "modified").unwrap();
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &workspace_root,
+        &[
+            "git",
+            "push",
+            r#"--config=templates.git_push_bookmark='"user/" ++ description.first_line()'"#,
+            "--change=@",
+        ],
+    );
+    insta::allow_duplicates! {
+    insta::assert_snapshot!(stdout, @"");
+    }
+    insta::allow_duplicates! {
+    insta::assert_snapshot!(stderr, @r"
+    Creating bookmark user/bar for revision yostqsxwqrlt
+    Changes to push to origin:
+      Add bookmark user/bar to cf1a53a8800a
+    [EOF]
+    ");
+    }
+
+    // The generated name is recorded in the operation
+    let stdout = test_env.jj_cmd_success(
+        &workspace_root,
+        &[
+            "op",
+            "log",
+            "-n1",
+            "--no-graph",
+            "-T",
+            r#"tags.lines().filter(|tag| tag.starts_with("git-push-bookmark:"))"#,
+        ],
+    );
+    insta::allow_duplicates! {
+    insta::assert_snapshot!(stdout, @"git-push-bookmark:1b76972398e6b49e8e0701307e57d55a: user/bar[EOF]");
+    }
+
+    // The previously generated bookmark is reused even if the template output
+    // differs
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &workspace_root,
+        &[
+            "git",
+            "push",
+            r#"--config=templates.git_push_bookmark='"user/" ++ change_id.short()'"#,
+            "--change=@",
+        ],
+    );
+    insta::allow_duplicates! {
+    insta::assert_snapshot!(stdout, @"");
+    }
+    insta::allow_duplicates! {
+    insta::assert_snapshot!(stderr, @r"
+    Bookmark user/bar@origin already matches user/bar
+    Nothing changed.
+    [EOF]
+    ");
+    }
+
+    // Generated name must be a valid Git branch name
+    let stderr = test_env.jj_cmd_failure(
+        &workspace_root,
+        &[
+            "git",
+            "push",
+            r#"--config=templates.git_push_bookmark='"user:" ++ change_id.short()'"#,
+            "--change=@-",
+        ],
+    );
+    insta::allow_duplicates! {
+    insta::assert_snapshot!(stderr, @r"
+    Error: Invalid bookmark name `user:yqosqzytrlsw` generated for revision yqosqzytrlsw: the name contains invalid character ':'
+    Hint: Check the `templates.git_push_bookmark` setting.
+    [EOF]
+    ");
+    }
+}
+
 #[test_case(false; "use git2 for remote calls")]
 #[test_case(true; "spawn a git subprocess for remote calls")]
 fn test_git_push_revisions(subprocess: bool) {
@@ -67,6 +67,98 @@ fn test_gitignores() {
     ");
 }
 
+#[test]
+fn test_gitignores_workspace_ignore_file() {
+    let test_env = TestEnvironment::default();
+    let workspace_root = test_env.env_root().join("repo");
+    git::init(&workspace_root);
+    test_env.jj_cmd_ok(&workspace_root, &["git", "init", "--git-repo", "."]);
+
+    // The global excludes file ignores all files
+    let mut file = std::fs::OpenOptions::new()
+        .append(true)
+        .open(workspace_root.join(".git").join("config"))
+        .unwrap();
+    file.write_all(b"[core]\nexcludesFile=~/my-ignores\n")
+        .unwrap();
+    drop(file);
+    std::fs::write(
+        test_env.home_dir().join("my-ignores"),
+        "file1\nfile2\nfile3\nfile4\n",
+    )
+    .unwrap();
+
+    // .git/info/exclude overrides the global excludes file
+    let mut file = std::fs::OpenOptions::new()
+        .append(true)
+        .open(workspace_root.join(".git").join("info").join("exclude"))
+        .unwrap();
+    file.write_all(b"!file2\n!file3\n!file4\n").unwrap();
+    drop(file);
+
+    // .jj/working-copy-ignore overrides .git/info/exclude and the global
+    // excludes file
+    std::fs::write(
+        workspace_root.join(".jj").join("working-copy-ignore"),
+        "!file1\nfile2\nfile3\nscratch/\n",
+    )
+    .unwrap();
+
+    // .gitignore files in the working copy override all of the above
+    std::fs::write(workspace_root.join(".gitignore"), "!file3\n").unwrap();
+
+    std::fs::write(workspace_root.join("file0"), "contents").unwrap();
+    std::fs::write(workspace_root.join("file1"), "contents").unwrap();
+    std::fs::write(workspace_root.join("file2"), "contents").unwrap();
+    std::fs::write(workspace_root.join("file3"), "contents").unwrap();
+    std::fs::write(workspace_root.join("file4"), "contents").unwrap();
+    std::fs::create_dir(workspace_root.join("scratch")).unwrap();
+    std::fs::write(workspace_root.join("scratch").join("file"), "contents").unwrap();
+
+    let stdout = test_env.jj_cmd_success(&workspace_root, &["diff", "-s"]);
+    insta::assert_snapshot!(stdout, @r"
+    A .gitignore
+    A file0
+    A file1
+    A file3
+    A file4
+    [EOF]
+    ");
+
+    // Ignored files aren't tracked unless requested
+    test_env.jj_cmd_ok(&workspace_root, &["file", "track", "file2", "scratch/file"]);
+    let stdout = test_env.jj_cmd_success(&workspace_root, &["diff", "-s"]);
+    insta::assert_snapshot!(stdout, @r"
+    A .gitignore
+    A file0
+    A file1
+    A file3
+    A file4
+    [EOF]
+    ");
+    test_env.jj_cmd_ok(
+        &workspace_root,
+        &[
+            "file",
+            "track",
+            "--include-ignored",
+            "file2",
+            "scratch/file",
+        ],
+    );
+    let stdout = test_env.jj_cmd_success(&workspace_root, &["diff", "-s"]);
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    A .gitignore
+    A file0
+    A file1
+    A file2
+    A file3
+    A file4
+    A scratch/file
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_gitignores_relative_excludes_file_path() {
     let test_env = TestEnvironment::default();
@@ -128,8 +220,9 @@ fn test_gitignores_ignored_file_in_target_commit() {
     insta::assert_snapshot!(stderr, @r"
     Working copy now at: qpvuntsm 5ada929e with-file | (no description set)
     Parent commit      : zzzzzzzz 00000000 (empty) (no description set)
-    Added 1 files, modified 0 files, removed 0 files
-    Warning: 1 of those updates were skipped because there were conflicting changes in the working copy.
+    Added 1 files, modified 0 files, removed 0 files, skipped 1 files (see below)
+    Warning: 1 of those updates were skipped because there were conflicting changes in the working copy:
+      ignored: an untracked file or directory is in the way
     Hint: Inspect the changes compared to the intended target with `jj diff --from 5ada929e5d2e`.
     Discard the conflicting changes with `jj restore --from 5ada929e5d2e`.
     [EOF]
@@ -1123,7 +1123,10 @@ fn test_help() {
     insta::assert_snapshot!(output, @r"
     Touch up the content changes in a revision with a diff editor
 
-    Usage: jj diffedit [OPTIONS]
+    Usage: jj diffedit [OPTIONS] [FILESETS]...
+
+    Arguments:
+      [FILESETS]...  Edit only changes to these paths (instead of all paths)
 
     Options:
       -r, --revision <REVSET>    The revision to touch up
@@ -1131,6 +1134,8 @@ fn test_help() {
       -t, --to <REVSET>          Edit changes in this revision
           --tool <NAME>          Specify diff editor to be used
           --restore-descendants  Preserve the content (not the diff) when rebasing descendants
+          --reset                Reset the specified paths to the left side of the diff instead of
+                                 starting the diff editor
       -h, --help                 Print help (see more with '--help')
 
     Global Options:
@@ -1139,6 +1144,7 @@ fn test_help() {
           --ignore-immutable             Allow rewriting immutable commits
           --at-operation <AT_OPERATION>  Operation to load the repo at [aliases: at-op]
           --debug                        Enable debug logging
+          --no-defaults                  Ignore the default arguments configured in `command-defaults`
           --color <WHEN>                 When to colorize output [possible values: always, never, debug,
                                          auto]
           --quiet                        Silence non-primary command output
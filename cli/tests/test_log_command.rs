@@ -859,6 +859,41 @@ fn test_log_reversed() {
     ");
 }
 
+#[test]
+fn test_log_exclude_from_subdir() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    let subdir_path = repo_path.join("dir");
+    std::fs::create_dir_all(subdir_path.join("sub")).unwrap();
+
+    std::fs::write(subdir_path.join("file1"), "foo\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "first"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "second"]);
+    std::fs::write(subdir_path.join("sub/file2"), "bar\n").unwrap();
+
+    // Excluded paths are relative to the current directory
+    let stdout = test_env.jj_cmd_success(&subdir_path, &["log", "-T", "description", "-X", "sub"]);
+    insta::assert_snapshot!(stdout, @r"
+    ○  first
+    │
+    ~
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(
+        &subdir_path,
+        &["log", "-T", "description", "sub", "-X", "sub/file2"],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    let stdout = test_env.jj_cmd_success(&subdir_path, &["log", "-T", "description", "sub"]);
+    insta::assert_snapshot!(stdout, @r"
+    @  second
+    │
+    ~
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_log_filtered_by_path() {
     let test_env = TestEnvironment::default();
@@ -14,6 +14,8 @@
 
 use std::path::Path;
 
+use indoc::indoc;
+
 use crate::common::CommandOutput;
 use crate::common::TestEnvironment;
 
@@ -68,6 +70,47 @@ fn test_new() {
     ");
 }
 
+#[test]
+fn test_new_template_description() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    test_env.add_config(indoc! {r#"
+        [templates]
+        new_commit_description = '''
+        concat(
+          parents.map(|c| c.local_bookmarks().map(|b| "[" ++ b.name().upper() ++ "]")).join(""),
+          "\n\nReviewed-by: TODO\n",
+        )
+        '''
+    "#});
+    let repo_path = test_env.env_root().join("repo");
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "base"]);
+    test_env.jj_cmd_ok(&repo_path, &["bookmark", "create", "-r@", "ticket-123"]);
+
+    // The template isn't used by default
+    test_env.jj_cmd_ok(&repo_path, &["new", "ticket-123"]);
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["log", "--no-graph", "-r@", "-T", "description"],
+    );
+    insta::assert_snapshot!(stdout, @"");
+
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &["new", "--use-template-description", "ticket-123"],
+    );
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["log", "--no-graph", "-r@", "-T", "description"],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    [TICKET-123]
+
+    Reviewed-by: TODO
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_new_merge() {
     let test_env = TestEnvironment::default();
@@ -150,6 +193,92 @@ fn test_new_merge() {
     ");
 }
 
+#[test]
+fn test_new_merge_follows_renames() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.add_config("templates.commit_summary = 'description.first_line()'");
+
+    std::fs::write(repo_path.join("old"), "1\n2\n3\n4\n5\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m", "base"]);
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "rename"]);
+    std::fs::rename(repo_path.join("old"), repo_path.join("new")).unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "edit", "description(base)"]);
+    std::fs::write(repo_path.join("old"), "1\n2\nthree\n4\n5\n").unwrap();
+
+    // The edit lands in the renamed file
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "new",
+            "-m",
+            "merge",
+            "description(rename)",
+            "description(edit)",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r"
+    Merged changes to old into new, where it was renamed
+    Working copy now at: merge
+    Parent commit      : rename
+    Parent commit      : edit
+    Added 1 files, modified 0 files, removed 1 files
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["file", "list"]);
+    insta::assert_snapshot!(stdout, @r"
+    new
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["file", "show", "new"]);
+    insta::assert_snapshot!(stdout, @r"
+    1
+    2
+    three
+    4
+    5
+    [EOF]
+    ");
+
+    // The config value must be a boolean
+    test_env.jj_cmd_ok(&repo_path, &["undo"]);
+    let stderr = test_env.jj_cmd_failure(
+        &repo_path,
+        &[
+            "new",
+            "--config=merge.follow-renames=1",
+            "description(rename)",
+            "description(edit)",
+        ],
+    );
+    insta::assert_snapshot!(stderr, @r"
+    Config error: Invalid type or value for merge.follow-renames
+    Caused by: invalid type: integer `1`, expected a boolean
+
+    For help, see https://jj-vcs.github.io/jj/latest/config/.
+    [EOF]
+    ");
+
+    // Following renames can be disabled
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "new",
+            "--config=merge.follow-renames=false",
+            "description(rename)",
+            "description(edit)",
+        ],
+    );
+    let stdout = test_env.jj_cmd_success(&repo_path, &["file", "list"]);
+    insta::assert_snapshot!(stdout, @r"
+    new
+    old
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_new_insert_after() {
     let test_env = TestEnvironment::default();
@@ -643,6 +772,45 @@ fn test_new_insert_after_before_no_loop() {
     ");
 }
 
+#[test]
+fn test_new_insert_after_before_multiple() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    setup_before_insertion(&test_env, &repo_path);
+
+    // Contradictory anchors are rejected before anything is rewritten
+    let stderr = test_env.jj_cmd_failure(
+        &repo_path,
+        &[
+            "new", "-m", "G", "--after", "C", "--after", "F", "--before", "D",
+        ],
+    );
+    insta::assert_snapshot!(stderr, @r"
+    Error: Refusing to create a loop: commit 7705d353bf5d would be both an ancestor and a descendant of the new commit
+    [EOF]
+    ");
+
+    // The new commit gets all --after targets as parents, and replaces the
+    // edges from the --before targets to them
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "new", "-m", "G", "--after", "C", "--after", "D", "--before", "F",
+        ],
+    );
+    let template = r#"description.first_line() ++ ": " ++ parents.map(|c| c.description().first_line()).join(" ") ++ "\n""#;
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["log", "--no-graph", "-r=F|description(G)", "-T", template],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    F: E G
+    G: C D
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_new_conflicting_bookmarks() {
     let test_env = TestEnvironment::default();
@@ -206,6 +206,80 @@ fn test_op_log_no_graph() {
     ");
 }
 
+#[test]
+fn test_op_log_no_snapshots() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file"), "contents\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["status"]);
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "description"]);
+
+    let template =
+        r#"separate(" ", if(snapshot, "snapshot", "other"), time.duration_seconds()) ++ "\n""#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["op", "log", "-T", template]);
+    insta::assert_snapshot!(stdout, @r"
+    @  other 0
+    ○  snapshot 0
+    ○  other 0
+    ○  other 0
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["op", "log", "--no-snapshots", "--no-graph", "-T", template],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    other 0
+    other 0
+    other 0
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_op_log_op_diff_max_changes() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.add_config("templates.commit_summary = 'description.first_line()'");
+
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "A"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "B"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "C"]);
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-r", "@--", "-m", "A2"]);
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["op", "log", "-T''", "--op-diff", "-n1"]);
+    insta::assert_snapshot!(stdout, @r"
+    @
+    │
+    │  Changed commits:
+    │  ○  + C
+    │  │  - C
+    │  ○  + B
+    │  │  - B
+    │  ○  + A2
+    │     - A
+    [EOF]
+    ");
+
+    // Changes beyond the limit are elided
+    test_env.add_config("ui.op-log-max-changes = 2");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["op", "log", "-T''", "--op-diff", "-n1"]);
+    insta::assert_snapshot!(stdout, @r"
+    @
+    │
+    │  Changed commits:
+    │  ○  + C
+    │  │  - C
+    │  ○  + B
+    │     - B
+    │  … and 1 more
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_op_log_reversed() {
     let test_env = TestEnvironment::default();
@@ -318,7 +392,9 @@ fn test_op_log_no_graph_null_terminated() {
             r#"id.short(4) ++ "\0""#,
         ],
     );
-    insta::assert_debug_snapshot!(stdout.normalized(), @r#""ef17\0f412\0eac7\00000\0""#);
+    insta::assert_debug_snapshot!(stdout.normalized(), @r#"
+    "ef17\0f412\0eac7\00000\0"
+    "#);
 }
 
 #[test]
@@ -359,11 +435,11 @@ fn test_op_log_template() {
     );
     let regex = Regex::new(r"\d\d years").unwrap();
     let stdout = test_env.jj_cmd_success(&repo_path, &["op", "log"]);
-    insta::assert_snapshot!(regex.replace_all(stdout.raw(), "NN years"), @r#"
+    insta::assert_snapshot!(regex.replace_all(stdout.raw(), "NN years"), @r"
     @  eac759b9ab75 test-username@host.example.com NN years ago, lasted less than a microsecond
     │  add workspace 'default'
     ○  000000000000 root()
-    "#);
+    ");
 }
 
 #[test]
@@ -377,7 +453,7 @@ fn test_op_log_builtin_templates() {
     };
     test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "description 0"]);
 
-    insta::assert_snapshot!(render(r#"builtin_op_log_compact"#), @r#"
+    insta::assert_snapshot!(render(r#"builtin_op_log_compact"#), @r"
     d009cfc04993 test-username@host.example.com 2001-02-03 04:05:08.000 +07:00 - 2001-02-03 04:05:08.000 +07:00
     describe commit 230dd059e1b059aefc0da06a2e5a7dbf22362f22
     args: jj describe -m 'description 0'
@@ -385,9 +461,9 @@ fn test_op_log_builtin_templates() {
     add workspace 'default'
     000000000000 root()
     [EOF]
-    "#);
+    ");
 
-    insta::assert_snapshot!(render(r#"builtin_op_log_comfortable"#), @r#"
+    insta::assert_snapshot!(render(r#"builtin_op_log_comfortable"#), @r"
     d009cfc04993 test-username@host.example.com 2001-02-03 04:05:08.000 +07:00 - 2001-02-03 04:05:08.000 +07:00
     describe commit 230dd059e1b059aefc0da06a2e5a7dbf22362f22
     args: jj describe -m 'description 0'
@@ -398,7 +474,7 @@ fn test_op_log_builtin_templates() {
     000000000000 root()
 
     [EOF]
-    "#);
+    ");
 
     insta::assert_snapshot!(render(r#"builtin_op_log_oneline"#), @r"
     d009cfc04993 test-username@host.example.com 2001-02-03 04:05:08.000 +07:00 - 2001-02-03 04:05:08.000 +07:00 describe commit 230dd059e1b059aefc0da06a2e5a7dbf22362f22 args: jj describe -m 'description 0'
@@ -431,19 +507,20 @@ fn test_op_log_word_wrap() {
     };
 
     // ui.log-word-wrap option works
-    insta::assert_snapshot!(render(&["op", "log"], 40, false), @r#"
-    @  b7cd3d0069f6 test-username@host.example.com 2001-02-03 04:05:08.000 +07:00 - 2001-02-03 04:05:08.000 +07:00
+    insta::assert_snapshot!(render(&["op", "log"], 40, false), @r"
+    @  b7cd3d0069f6 test-username@host.example.com 2001-02-03 04:05:08.000 +07:00 - 2001-02-03 04:05:08.000 +07:00 (snapshot)
     │  snapshot working copy
     │  args: jj debug snapshot
     ○  eac759b9ab75 test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
     │  add workspace 'default'
     ○  000000000000 root()
-    "#);
-    insta::assert_snapshot!(render(&["op", "log"], 40, true), @r#"
+    ");
+    insta::assert_snapshot!(render(&["op", "log"], 40, true), @r"
     @  b7cd3d0069f6
     │  test-username@host.example.com
     │  2001-02-03 04:05:08.000 +07:00 -
     │  2001-02-03 04:05:08.000 +07:00
+    │  (snapshot)
     │  snapshot working copy
     │  args: jj debug snapshot
     ○  eac759b9ab75
@@ -452,14 +529,15 @@ fn test_op_log_word_wrap() {
     │  2001-02-03 04:05:07.000 +07:00
     │  add workspace 'default'
     ○  000000000000 root()
-    "#);
+    ");
 
     // Nested graph should be wrapped
-    insta::assert_snapshot!(render(&["op", "log", "--op-diff"], 40, true), @r#"
+    insta::assert_snapshot!(render(&["op", "log", "--op-diff"], 40, true), @r"
     @  b7cd3d0069f6
     │  test-username@host.example.com
     │  2001-02-03 04:05:08.000 +07:00 -
     │  2001-02-03 04:05:08.000 +07:00
+    │  (snapshot)
     │  snapshot working copy
     │  args: jj debug snapshot
     │
@@ -478,14 +556,15 @@ fn test_op_log_word_wrap() {
     │  ○  + qpvuntsm 230dd059 (empty) (no
     │     description set)
     ○  000000000000 root()
-    "#);
+    ");
 
     // Nested diff stat shouldn't exceed the terminal width
-    insta::assert_snapshot!(render(&["op", "log", "-n1", "--stat"], 40, true), @r#"
+    insta::assert_snapshot!(render(&["op", "log", "-n1", "--stat"], 40, true), @r"
     @  b7cd3d0069f6
     │  test-username@host.example.com
     │  2001-02-03 04:05:08.000 +07:00 -
     │  2001-02-03 04:05:08.000 +07:00
+    │  (snapshot)
     │  snapshot working copy
     │  args: jj debug snapshot
     │
@@ -496,12 +575,13 @@ fn test_op_log_word_wrap() {
     │     (no description set)
     │     file1 | 100 +++++++++++++++++++
     │     1 file changed, 100 insertions(+), 0 deletions(-)
-    "#);
-    insta::assert_snapshot!(render(&["op", "log", "-n1", "--no-graph", "--stat"], 40, true), @r#"
+    ");
+    insta::assert_snapshot!(render(&["op", "log", "-n1", "--no-graph", "--stat"], 40, true), @r"
     b7cd3d0069f6
     test-username@host.example.com
     2001-02-03 04:05:08.000 +07:00 -
     2001-02-03 04:05:08.000 +07:00
+    (snapshot)
     snapshot working copy
     args: jj debug snapshot
 
@@ -511,7 +591,7 @@ fn test_op_log_word_wrap() {
     description set)
     file1 | 100 +++++++++++++++++++++++++
     1 file changed, 100 insertions(+), 0 deletions(-)
-    "#);
+    ");
 
     // Nested graph widths should be subtracted from the term width
     let config = r#"templates.commit_summary='"0 1 2 3 4 5 6 7 8 9"'"#;
@@ -824,7 +904,7 @@ fn test_op_recover_from_bad_gc() {
         &["op", "log", "--no-graph", r#"-Tid.short() ++ "\n""#],
     );
     let (head_op_id, _, _, bad_op_id) = stdout.raw().lines().next_tuple().unwrap();
-    insta::assert_snapshot!(head_op_id, @"f999e12a5d8b");
+    insta::assert_snapshot!(head_op_id, @"52f26dbf1de6");
     insta::assert_snapshot!(bad_op_id, @"e7377e6a642b");
 
     // Corrupt the repo by removing hidden but reachable commit object.
@@ -864,13 +944,13 @@ fn test_op_recover_from_bad_gc() {
         &["op", "log", "--ignore-working-copy", "--at-op", head_op_id],
     );
     insta::assert_snapshot!(stdout, @r"
-    @  f999e12a5d8b test-username@host.example.com 2001-02-03 04:05:12.000 +07:00 - 2001-02-03 04:05:12.000 +07:00
+    @  52f26dbf1de6 test-username@host.example.com 2001-02-03 04:05:12.000 +07:00 - 2001-02-03 04:05:12.000 +07:00
     │  describe commit 37bb762e5dc08073ec4323bdffc023a0f0cc901e
     │  args: jj describe -m4
-    ○  fb75e6b1c70a test-username@host.example.com 2001-02-03 04:05:11.000 +07:00 - 2001-02-03 04:05:11.000 +07:00
+    ○  07bffe48b63e test-username@host.example.com 2001-02-03 04:05:11.000 +07:00 - 2001-02-03 04:05:11.000 +07:00
     │  new empty commit
     │  args: jj new -m3
-    ○  44d11f83204d test-username@host.example.com 2001-02-03 04:05:10.000 +07:00 - 2001-02-03 04:05:10.000 +07:00
+    ○  a9fb81d71fb4 test-username@host.example.com 2001-02-03 04:05:10.000 +07:00 - 2001-02-03 04:05:10.000 +07:00
     │  abandon commit ddf84fc5e0dd314092b3dfb13e09e37fa7d04ef9
     │  args: jj abandon
     ○  e7377e6a642b test-username@host.example.com 2001-02-03 04:05:09.000 +07:00 - 2001-02-03 04:05:09.000 +07:00
@@ -988,7 +1068,7 @@ fn test_op_diff() {
     // Overview of op log.
     let stdout = test_env.jj_cmd_success(&repo_path, &["op", "log"]);
     insta::assert_snapshot!(&stdout, @r"
-    @  364d0a677b0c test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
+    @  eb797c61bddd test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
     │  check out git remote's default branch
     │  args: jj git clone git-repo repo
     ○  369ee2939177 test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
@@ -1012,8 +1092,8 @@ fn test_op_diff() {
     ");
     let stdout = test_env.jj_cmd_success(&repo_path, &["op", "diff", "--from", "@", "--to", "@"]);
     insta::assert_snapshot!(&stdout, @r"
-    From operation: 364d0a677b0c (2001-02-03 08:05:07) check out git remote's default branch
-      To operation: 364d0a677b0c (2001-02-03 08:05:07) check out git remote's default branch
+    From operation: eb797c61bddd (2001-02-03 08:05:07) check out git remote's default branch
+      To operation: eb797c61bddd (2001-02-03 08:05:07) check out git remote's default branch
     [EOF]
     ");
 
@@ -1023,11 +1103,11 @@ fn test_op_diff() {
     let stdout = test_env.jj_cmd_success(&repo_path, &["op", "diff", "--from", "@-", "--to", "@"]);
     insta::assert_snapshot!(&stdout, @r"
     From operation: 369ee2939177 (2001-02-03 08:05:07) fetch from git remote into empty repo
-      To operation: 364d0a677b0c (2001-02-03 08:05:07) check out git remote's default branch
+      To operation: eb797c61bddd (2001-02-03 08:05:07) check out git remote's default branch
 
     Changed commits:
     ○  + sqpuoqvx c7b48fea (empty) (no description set)
-    ○  - qpvuntsm hidden 230dd059 (empty) (no description set)
+    ○  - qpvuntsm hidden 230dd059 (empty) (no description set) (abandoned: discarded working copy)
 
     Changed local bookmarks:
     bookmark-1:
@@ -1047,7 +1127,7 @@ fn test_op_diff() {
     let stdout = test_env.jj_cmd_success(&repo_path, &["op", "diff", "--from", "0000000"]);
     insta::assert_snapshot!(&stdout, @r"
     From operation: 000000000000 root()
-      To operation: 364d0a677b0c (2001-02-03 08:05:07) check out git remote's default branch
+      To operation: eb797c61bddd (2001-02-03 08:05:07) check out git remote's default branch
 
     Changed commits:
     ○  + sqpuoqvx c7b48fea (empty) (no description set)
@@ -1076,7 +1156,7 @@ fn test_op_diff() {
     // Diff from latest operation to root operation
     let stdout = test_env.jj_cmd_success(&repo_path, &["op", "diff", "--to", "0000000"]);
     insta::assert_snapshot!(&stdout, @r"
-    From operation: 364d0a677b0c (2001-02-03 08:05:07) check out git remote's default branch
+    From operation: eb797c61bddd (2001-02-03 08:05:07) check out git remote's default branch
       To operation: 000000000000 root()
 
     Changed commits:
@@ -1123,10 +1203,10 @@ fn test_op_diff() {
     ");
     let stdout = test_env.jj_cmd_success(&repo_path, &["op", "log"]);
     insta::assert_snapshot!(&stdout, @r"
-    @    7f6887c5ae04 test-username@host.example.com 2001-02-03 04:05:16.000 +07:00 - 2001-02-03 04:05:16.000 +07:00
+    @    7060ec7512af test-username@host.example.com 2001-02-03 04:05:16.000 +07:00 - 2001-02-03 04:05:16.000 +07:00
     ├─╮  reconcile divergent operations
     │ │  args: jj log
-    ○ │  364d0a677b0c test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
+    ○ │  eb797c61bddd test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
     │ │  check out git remote's default branch
     │ │  args: jj git clone git-repo repo
     │ ○  ee092a3adf88 test-username@host.example.com 2001-02-03 04:05:15.000 +07:00 - 2001-02-03 04:05:15.000 +07:00
@@ -1151,8 +1231,8 @@ fn test_op_diff() {
         &["op", "diff", "--from", first_parent_id, "--to", op_id],
     );
     insta::assert_snapshot!(&stdout, @r"
-    From operation: 364d0a677b0c (2001-02-03 08:05:07) check out git remote's default branch
-      To operation: 7f6887c5ae04 (2001-02-03 08:05:16) reconcile divergent operations
+    From operation: eb797c61bddd (2001-02-03 08:05:07) check out git remote's default branch
+      To operation: 7060ec7512af (2001-02-03 08:05:16) reconcile divergent operations
 
     Changed local bookmarks:
     bookmark-1:
@@ -1170,11 +1250,11 @@ fn test_op_diff() {
     );
     insta::assert_snapshot!(&stdout, @r"
     From operation: ee092a3adf88 (2001-02-03 08:05:15) point bookmark bookmark-1 to commit 4ff6253913375c6ebdddd8423c11df3b3f17e331
-      To operation: 7f6887c5ae04 (2001-02-03 08:05:16) reconcile divergent operations
+      To operation: 7060ec7512af (2001-02-03 08:05:16) reconcile divergent operations
 
     Changed commits:
     ○  + sqpuoqvx c7b48fea (empty) (no description set)
-    ○  - qpvuntsm hidden 230dd059 (empty) (no description set)
+    ○  - qpvuntsm hidden 230dd059 (empty) (no description set) (abandoned: discarded working copy)
 
     Changed local bookmarks:
     bookmark-1:
@@ -1199,17 +1279,18 @@ fn test_op_diff() {
     bookmark: bookmark-2@origin [updated] untracked
     bookmark: bookmark-3@origin [deleted] untracked
     Abandoned 1 commits that are no longer reachable.
+    Pruned 1 remote bookmark that no longer exists on the remote.
     [EOF]
     ");
     let stdout = test_env.jj_cmd_success(&repo_path, &["op", "diff"]);
     insta::assert_snapshot!(&stdout, @r"
-    From operation: 7f6887c5ae04 (2001-02-03 08:05:16) reconcile divergent operations
-      To operation: 0fda9dbbc995 (2001-02-03 08:05:20) fetch from git remote(s) origin
+    From operation: 7060ec7512af (2001-02-03 08:05:16) reconcile divergent operations
+      To operation: 487ecc2f7b28 (2001-02-03 08:05:20) fetch from git remote(s) origin
 
     Changed commits:
     ○  + kulxwnxm e1a239a5 bookmark-2@origin | Commit 5
     ○  + zkmtkqvo 0dee6313 bookmark-1?? bookmark-1@origin | Commit 4
-    ○  - rnnkyono hidden 11671e4c Commit 3
+    ○  - rnnkyono hidden 11671e4c Commit 3 (abandoned: unreachable in git)
 
     Changed local bookmarks:
     bookmark-1:
@@ -1250,8 +1331,8 @@ fn test_op_diff() {
     ");
     let stdout = test_env.jj_cmd_success(&repo_path, &["op", "diff"]);
     insta::assert_snapshot!(&stdout, @r"
-    From operation: 0fda9dbbc995 (2001-02-03 08:05:20) fetch from git remote(s) origin
-      To operation: 505a09f5c0f0 (2001-02-03 08:05:22) create bookmark bookmark-2 pointing to commit e1a239a57eb15cefc5910198befbbbe2b43c47af
+    From operation: 487ecc2f7b28 (2001-02-03 08:05:20) fetch from git remote(s) origin
+      To operation: 5f278462fd57 (2001-02-03 08:05:22) create bookmark bookmark-2 pointing to commit e1a239a57eb15cefc5910198befbbbe2b43c47af
 
     Changed local bookmarks:
     bookmark-2:
@@ -1271,8 +1352,8 @@ fn test_op_diff() {
     ");
     let stdout = test_env.jj_cmd_success(&repo_path, &["op", "diff"]);
     insta::assert_snapshot!(&stdout, @r"
-    From operation: 505a09f5c0f0 (2001-02-03 08:05:22) create bookmark bookmark-2 pointing to commit e1a239a57eb15cefc5910198befbbbe2b43c47af
-      To operation: e7d3f25689e1 (2001-02-03 08:05:24) track remote bookmark bookmark-2@origin
+    From operation: 5f278462fd57 (2001-02-03 08:05:22) create bookmark bookmark-2 pointing to commit e1a239a57eb15cefc5910198befbbbe2b43c47af
+      To operation: e096aa8d8f24 (2001-02-03 08:05:24) track remote bookmark bookmark-2@origin
 
     Changed remote bookmarks:
     bookmark-2@origin:
@@ -1294,8 +1375,8 @@ fn test_op_diff() {
     ");
     let stdout = test_env.jj_cmd_success(&repo_path, &["op", "diff"]);
     insta::assert_snapshot!(&stdout, @r"
-    From operation: 505a09f5c0f0 (2001-02-03 08:05:22) create bookmark bookmark-2 pointing to commit e1a239a57eb15cefc5910198befbbbe2b43c47af
-      To operation: e7d3f25689e1 (2001-02-03 08:05:24) track remote bookmark bookmark-2@origin
+    From operation: 5f278462fd57 (2001-02-03 08:05:22) create bookmark bookmark-2 pointing to commit e1a239a57eb15cefc5910198befbbbe2b43c47af
+      To operation: e096aa8d8f24 (2001-02-03 08:05:24) track remote bookmark bookmark-2@origin
 
     Changed remote bookmarks:
     bookmark-2@origin:
@@ -1319,12 +1400,12 @@ fn test_op_diff() {
     ");
     let stdout = test_env.jj_cmd_success(&repo_path, &["op", "diff"]);
     insta::assert_snapshot!(&stdout, @r"
-    From operation: e7d3f25689e1 (2001-02-03 08:05:24) track remote bookmark bookmark-2@origin
-      To operation: b94b5ef70c8a (2001-02-03 08:05:28) new empty commit
+    From operation: e096aa8d8f24 (2001-02-03 08:05:24) track remote bookmark bookmark-2@origin
+      To operation: 3235ad41233f (2001-02-03 08:05:28) new empty commit
 
     Changed commits:
     ○  + wvuyspvk fefb1e17 (empty) new commit
-    ○  - sqpuoqvx hidden c7b48fea (empty) (no description set)
+    ○  - sqpuoqvx hidden c7b48fea (empty) (no description set) (abandoned: discarded working copy)
     [EOF]
     ");
 
@@ -1339,8 +1420,8 @@ fn test_op_diff() {
     ");
     let stdout = test_env.jj_cmd_success(&repo_path, &["op", "diff"]);
     insta::assert_snapshot!(&stdout, @r"
-    From operation: b94b5ef70c8a (2001-02-03 08:05:28) new empty commit
-      To operation: 26918495eee5 (2001-02-03 08:05:30) point bookmark bookmark-1 to commit fefb1e17c85328767a596c6dc3d9d604c024a02c
+    From operation: 3235ad41233f (2001-02-03 08:05:28) new empty commit
+      To operation: 8da1a9e3bb8d (2001-02-03 08:05:30) point bookmark bookmark-1 to commit fefb1e17c85328767a596c6dc3d9d604c024a02c
 
     Changed local bookmarks:
     bookmark-1:
@@ -1360,8 +1441,8 @@ fn test_op_diff() {
     ");
     let stdout = test_env.jj_cmd_success(&repo_path, &["op", "diff"]);
     insta::assert_snapshot!(&stdout, @r"
-    From operation: 26918495eee5 (2001-02-03 08:05:30) point bookmark bookmark-1 to commit fefb1e17c85328767a596c6dc3d9d604c024a02c
-      To operation: 9969a6088fd3 (2001-02-03 08:05:32) delete bookmark bookmark-2
+    From operation: 8da1a9e3bb8d (2001-02-03 08:05:30) point bookmark bookmark-1 to commit fefb1e17c85328767a596c6dc3d9d604c024a02c
+      To operation: dc6719fc897a (2001-02-03 08:05:32) delete bookmark bookmark-2
 
     Changed local bookmarks:
     bookmark-2:
@@ -1385,8 +1466,8 @@ fn test_op_diff() {
     ");
     let stdout = test_env.jj_cmd_success(&repo_path, &["op", "diff"]);
     insta::assert_snapshot!(&stdout, @r"
-    From operation: 9969a6088fd3 (2001-02-03 08:05:32) delete bookmark bookmark-2
-      To operation: ff305cfe0aca (2001-02-03 08:05:34) push all tracked bookmarks to git remote origin
+    From operation: dc6719fc897a (2001-02-03 08:05:32) delete bookmark bookmark-2
+      To operation: b4e48c9d24de (2001-02-03 08:05:34) push all tracked bookmarks to git remote origin
 
     Changed commits:
     ○  + oupztwtk fe3ad088 (empty) (no description set)
@@ -1456,11 +1537,11 @@ fn test_op_diff_patch() {
     let stdout = test_env.jj_cmd_success(&repo_path, &["op", "diff", "-p", "--git"]);
     insta::assert_snapshot!(&stdout, @r"
     From operation: 15c3c5d0baf0 (2001-02-03 08:05:11) snapshot working copy
-      To operation: 894c12d90345 (2001-02-03 08:05:11) squash commits into 6b1027d2770cd0a39c468e525e52bf8c47e1464a
+      To operation: 22fa103c9ec0 (2001-02-03 08:05:11) squash commits into 6b1027d2770cd0a39c468e525e52bf8c47e1464a
 
     Changed commits:
     ○  + mzvwutvl 9f4fb57f (empty) (no description set)
-    │ ○  - rlvkpnrz hidden 1d7f8f94 (no description set)
+    │ ○  - rlvkpnrz hidden 1d7f8f94 (no description set) (abandoned: squashed into another commit)
     ├─╯  diff --git a/file b/file
     │    index 7898192261..6178079822 100644
     │    --- a/file
@@ -1491,12 +1572,12 @@ fn test_op_diff_patch() {
     ");
     let stdout = test_env.jj_cmd_success(&repo_path, &["op", "diff", "-p", "--git"]);
     insta::assert_snapshot!(&stdout, @r"
-    From operation: 894c12d90345 (2001-02-03 08:05:11) squash commits into 6b1027d2770cd0a39c468e525e52bf8c47e1464a
-      To operation: e5505aa79d31 (2001-02-03 08:05:13) abandon commit 9f4fb57fba25a7b47ce5980a5d9a4766778331e8
+    From operation: 22fa103c9ec0 (2001-02-03 08:05:11) squash commits into 6b1027d2770cd0a39c468e525e52bf8c47e1464a
+      To operation: 5c5fc5e4f4d7 (2001-02-03 08:05:13) abandon commit 9f4fb57fba25a7b47ce5980a5d9a4766778331e8
 
     Changed commits:
     ○  + yqosqzyt 33f321c4 (empty) (no description set)
-    ○  - mzvwutvl hidden 9f4fb57f (empty) (no description set)
+    ○  - mzvwutvl hidden 9f4fb57f (empty) (no description set) (abandoned: explicit)
     [EOF]
     ");
 }
@@ -1527,22 +1608,22 @@ fn test_op_diff_sibling() {
 
     let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["op", "log"]);
     insta::assert_snapshot!(&stdout, @r"
-    @    779ecb7ea7f0 test-username@host.example.com 2001-02-03 04:05:13.000 +07:00 - 2001-02-03 04:05:13.000 +07:00
+    @    d090327b49c5 test-username@host.example.com 2001-02-03 04:05:13.000 +07:00 - 2001-02-03 04:05:13.000 +07:00
     ├─╮  reconcile divergent operations
     │ │  args: jj op log
-    ○ │  d700dc16fded test-username@host.example.com 2001-02-03 04:05:11.000 +07:00 - 2001-02-03 04:05:11.000 +07:00
+    ○ │  b34fa57d052f test-username@host.example.com 2001-02-03 04:05:11.000 +07:00 - 2001-02-03 04:05:11.000 +07:00
     │ │  new empty commit
     │ │  args: jj new 'all:@-+' -mA
-    ○ │  b47de32023e1 test-username@host.example.com 2001-02-03 04:05:11.000 +07:00 - 2001-02-03 04:05:11.000 +07:00
+    ○ │  95caab77cc93 test-username@host.example.com 2001-02-03 04:05:11.000 +07:00 - 2001-02-03 04:05:11.000 +07:00 (snapshot)
     │ │  snapshot working copy
     │ │  args: jj new 'all:@-+' -mA
-    ○ │  8a31868f615d test-username@host.example.com 2001-02-03 04:05:10.000 +07:00 - 2001-02-03 04:05:10.000 +07:00
+    ○ │  db53775e772d test-username@host.example.com 2001-02-03 04:05:10.000 +07:00 - 2001-02-03 04:05:10.000 +07:00
     │ │  new empty commit
     │ │  args: jj new 'root()' -mA.2
-    ○ │  2cd33ddecde8 test-username@host.example.com 2001-02-03 04:05:10.000 +07:00 - 2001-02-03 04:05:10.000 +07:00
+    ○ │  d9083a7fa4d7 test-username@host.example.com 2001-02-03 04:05:10.000 +07:00 - 2001-02-03 04:05:10.000 +07:00 (snapshot)
     │ │  snapshot working copy
     │ │  args: jj new 'root()' -mA.2
-    ○ │  d86c1ae55c48 test-username@host.example.com 2001-02-03 04:05:09.000 +07:00 - 2001-02-03 04:05:09.000 +07:00
+    ○ │  4dedbc534a79 test-username@host.example.com 2001-02-03 04:05:09.000 +07:00 - 2001-02-03 04:05:09.000 +07:00
     │ │  new empty commit
     │ │  args: jj new 'root()' -mA.1
     │ ○  13b143e1f4f9 test-username@host.example.com 2001-02-03 04:05:12.000 +07:00 - 2001-02-03 04:05:12.000 +07:00
@@ -1562,8 +1643,8 @@ fn test_op_diff_sibling() {
         &["op", "log", "--no-graph", r#"-Tid.short() ++ "\n""#],
     );
     let (head_op_id, p1_op_id, _, _, _, _, p2_op_id) = stdout.raw().lines().next_tuple().unwrap();
-    insta::assert_snapshot!(head_op_id, @"779ecb7ea7f0");
-    insta::assert_snapshot!(p1_op_id, @"d700dc16fded");
+    insta::assert_snapshot!(head_op_id, @"d090327b49c5");
+    insta::assert_snapshot!(p1_op_id, @"b34fa57d052f");
     insta::assert_snapshot!(p2_op_id, @"13b143e1f4f9");
 
     // Diff between p1 and p2 operations should work no matter if p2 is chosen
@@ -1583,7 +1664,7 @@ fn test_op_diff_sibling() {
         ],
     );
     insta::assert_snapshot!(&stdout, @r"
-    From operation: d700dc16fded (2001-02-03 08:05:11) new empty commit
+    From operation: b34fa57d052f (2001-02-03 08:05:11) new empty commit
       To operation: 13b143e1f4f9 (2001-02-03 08:05:12) describe commit 230dd059e1b059aefc0da06a2e5a7dbf22362f22
 
     Changed commits:
@@ -1612,7 +1693,7 @@ fn test_op_diff_sibling() {
     );
     insta::assert_snapshot!(&stdout, @r"
     From operation: 13b143e1f4f9 (2001-02-03 08:05:12) describe commit 230dd059e1b059aefc0da06a2e5a7dbf22362f22
-      To operation: d700dc16fded (2001-02-03 08:05:11) new empty commit
+      To operation: b34fa57d052f (2001-02-03 08:05:11) new empty commit
 
     Changed commits:
     ○    + mzvwutvl 270db3d9 (empty) A
@@ -1626,6 +1707,65 @@ fn test_op_diff_sibling() {
     ");
 }
 
+#[test]
+fn test_op_diff_rewritten_stack_and_moved_bookmark() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    // Avoid commit and operation ids in the output
+    test_env.add_config(
+        r#"
+        templates.commit_summary = 'description.first_line()'
+        templates.op_summary = 'tags'
+        "#,
+    );
+
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "A"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "B"]);
+
+    // Rewriting a commit in the middle of a stack shows both the rewritten
+    // commit and its rebased descendant.
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-r", "@-", "-m", "A2"]);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["op", "diff", "--no-graph"]);
+    insta::assert_snapshot!(stdout, @r"
+    From operation: args: jj new -m B
+      To operation: args: jj describe -r @- -m A2
+
+    Changed commits:
+    + B
+    - B
+    + A2
+    - A
+    [EOF]
+    ");
+
+    // An operation which only moves refs doesn't list any changed commits.
+    test_env.jj_cmd_ok(&repo_path, &["bookmark", "create", "-r", "@-", "foo"]);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["op", "diff", "--no-graph"]);
+    insta::assert_snapshot!(stdout, @r"
+    From operation: args: jj describe -r @- -m A2
+      To operation: args: jj bookmark create -r @- foo
+
+    Changed local bookmarks:
+    foo:
+    + A2
+    - (absent)
+    [EOF]
+    ");
+    test_env.jj_cmd_ok(&repo_path, &["bookmark", "set", "-r", "@", "foo"]);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["op", "diff", "--no-graph"]);
+    insta::assert_snapshot!(stdout, @r"
+    From operation: args: jj bookmark create -r @- foo
+      To operation: args: jj bookmark set -r @ foo
+
+    Changed local bookmarks:
+    foo:
+    + B
+    - A2
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_op_diff_word_wrap() {
     let test_env = TestEnvironment::default();
@@ -1654,7 +1794,7 @@ fn test_op_diff_word_wrap() {
     // ui.log-word-wrap option works, and diff stat respects content width
     insta::assert_snapshot!(render(&["op", "diff", "--from=@---", "--stat"], 40, true), @r"
     From operation: eac759b9ab75 (2001-02-03 08:05:07) add workspace 'default'
-      To operation: e1a943dc0fe5 (2001-02-03 08:05:08) snapshot working copy
+      To operation: dc87f5a93d78 (2001-02-03 08:05:08) snapshot working copy
 
     Changed commits:
     ○  + sqpuoqvx 7581c520 (no description
@@ -1703,7 +1843,7 @@ fn test_op_diff_word_wrap() {
     insta::assert_snapshot!(
         render(&["op", "diff", "--from=@---", "--config", config], 10, true), @r"
     From operation: eac759b9ab75 (2001-02-03 08:05:07) add workspace 'default'
-      To operation: e1a943dc0fe5 (2001-02-03 08:05:08) snapshot working copy
+      To operation: dc87f5a93d78 (2001-02-03 08:05:08) snapshot working copy
 
     Changed
     commits:
@@ -1772,7 +1912,7 @@ fn test_op_show() {
     // Overview of op log.
     let stdout = test_env.jj_cmd_success(&repo_path, &["op", "log"]);
     insta::assert_snapshot!(&stdout, @r"
-    @  364d0a677b0c test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
+    @  eb797c61bddd test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
     │  check out git remote's default branch
     │  args: jj git clone git-repo repo
     ○  369ee2939177 test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
@@ -1794,13 +1934,13 @@ fn test_op_show() {
     // Showing the latest operation.
     let stdout = test_env.jj_cmd_success(&repo_path, &["op", "show", "@"]);
     insta::assert_snapshot!(&stdout, @r"
-    364d0a677b0c test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
+    eb797c61bddd test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
     check out git remote's default branch
     args: jj git clone git-repo repo
 
     Changed commits:
     ○  + sqpuoqvx c7b48fea (empty) (no description set)
-    ○  - qpvuntsm hidden 230dd059 (empty) (no description set)
+    ○  - qpvuntsm hidden 230dd059 (empty) (no description set) (abandoned: discarded working copy)
 
     Changed local bookmarks:
     bookmark-1:
@@ -1863,7 +2003,7 @@ fn test_op_show() {
     // Showing a merge operation is empty.
     let stdout = test_env.jj_cmd_success(&repo_path, &["op", "show"]);
     insta::assert_snapshot!(&stdout, @r"
-    774687cc6e4e test-username@host.example.com 2001-02-03 04:05:14.000 +07:00 - 2001-02-03 04:05:14.000 +07:00
+    59361dd5eefd test-username@host.example.com 2001-02-03 04:05:14.000 +07:00 - 2001-02-03 04:05:14.000 +07:00
     reconcile divergent operations
     args: jj log
     [EOF]
@@ -1879,18 +2019,19 @@ fn test_op_show() {
     bookmark: bookmark-2@origin [updated] untracked
     bookmark: bookmark-3@origin [deleted] untracked
     Abandoned 1 commits that are no longer reachable.
+    Pruned 1 remote bookmark that no longer exists on the remote.
     [EOF]
     ");
     let stdout = test_env.jj_cmd_success(&repo_path, &["op", "show"]);
     insta::assert_snapshot!(&stdout, @r"
-    fed3d4f59819 test-username@host.example.com 2001-02-03 04:05:16.000 +07:00 - 2001-02-03 04:05:16.000 +07:00
+    d8702beccbbe test-username@host.example.com 2001-02-03 04:05:16.000 +07:00 - 2001-02-03 04:05:16.000 +07:00
     fetch from git remote(s) origin
     args: jj git fetch
 
     Changed commits:
     ○  + kulxwnxm e1a239a5 bookmark-2@origin | Commit 5
     ○  + zkmtkqvo 0dee6313 bookmark-1?? bookmark-1@origin | Commit 4
-    ○  - rnnkyono hidden 11671e4c Commit 3
+    ○  - rnnkyono hidden 11671e4c Commit 3 (abandoned: unreachable in git)
 
     Changed local bookmarks:
     bookmark-1:
@@ -1931,7 +2072,7 @@ fn test_op_show() {
     ");
     let stdout = test_env.jj_cmd_success(&repo_path, &["op", "show"]);
     insta::assert_snapshot!(&stdout, @r"
-    77ed6ae71f3f test-username@host.example.com 2001-02-03 04:05:18.000 +07:00 - 2001-02-03 04:05:18.000 +07:00
+    398282c0af84 test-username@host.example.com 2001-02-03 04:05:18.000 +07:00 - 2001-02-03 04:05:18.000 +07:00
     create bookmark bookmark-2 pointing to commit e1a239a57eb15cefc5910198befbbbe2b43c47af
     args: jj bookmark create bookmark-2 -r bookmark-2@origin
 
@@ -1953,7 +2094,7 @@ fn test_op_show() {
     ");
     let stdout = test_env.jj_cmd_success(&repo_path, &["op", "show"]);
     insta::assert_snapshot!(&stdout, @r"
-    f4e770fd7370 test-username@host.example.com 2001-02-03 04:05:20.000 +07:00 - 2001-02-03 04:05:20.000 +07:00
+    d229d0842cde test-username@host.example.com 2001-02-03 04:05:20.000 +07:00 - 2001-02-03 04:05:20.000 +07:00
     track remote bookmark bookmark-2@origin
     args: jj bookmark track bookmark-2@origin
 
@@ -1976,7 +2117,7 @@ fn test_op_show() {
     ");
     let stdout = test_env.jj_cmd_success(&repo_path, &["op", "show"]);
     insta::assert_snapshot!(&stdout, @r"
-    f4e770fd7370 test-username@host.example.com 2001-02-03 04:05:20.000 +07:00 - 2001-02-03 04:05:20.000 +07:00
+    d229d0842cde test-username@host.example.com 2001-02-03 04:05:20.000 +07:00 - 2001-02-03 04:05:20.000 +07:00
     track remote bookmark bookmark-2@origin
     args: jj bookmark track bookmark-2@origin
 
@@ -2002,13 +2143,13 @@ fn test_op_show() {
     ");
     let stdout = test_env.jj_cmd_success(&repo_path, &["op", "show"]);
     insta::assert_snapshot!(&stdout, @r"
-    e54bb5012457 test-username@host.example.com 2001-02-03 04:05:24.000 +07:00 - 2001-02-03 04:05:24.000 +07:00
+    6dfcf62190f2 test-username@host.example.com 2001-02-03 04:05:24.000 +07:00 - 2001-02-03 04:05:24.000 +07:00
     new empty commit
     args: jj new bookmark-1@origin -m 'new commit'
 
     Changed commits:
     ○  + xznxytkn 560df364 (empty) new commit
-    ○  - sqpuoqvx hidden c7b48fea (empty) (no description set)
+    ○  - sqpuoqvx hidden c7b48fea (empty) (no description set) (abandoned: discarded working copy)
     [EOF]
     ");
 
@@ -2023,7 +2164,7 @@ fn test_op_show() {
     ");
     let stdout = test_env.jj_cmd_success(&repo_path, &["op", "show"]);
     insta::assert_snapshot!(&stdout, @r"
-    55527022d8b9 test-username@host.example.com 2001-02-03 04:05:26.000 +07:00 - 2001-02-03 04:05:26.000 +07:00
+    386b74ed78de test-username@host.example.com 2001-02-03 04:05:26.000 +07:00 - 2001-02-03 04:05:26.000 +07:00
     point bookmark bookmark-1 to commit 560df364f0a09fe29f6a4fca8bd07c4464c7feee
     args: jj bookmark set bookmark-1 -r @
 
@@ -2045,7 +2186,7 @@ fn test_op_show() {
     ");
     let stdout = test_env.jj_cmd_success(&repo_path, &["op", "show"]);
     insta::assert_snapshot!(&stdout, @r"
-    a6838035030b test-username@host.example.com 2001-02-03 04:05:28.000 +07:00 - 2001-02-03 04:05:28.000 +07:00
+    c147f476b49c test-username@host.example.com 2001-02-03 04:05:28.000 +07:00 - 2001-02-03 04:05:28.000 +07:00
     delete bookmark bookmark-2
     args: jj bookmark delete bookmark-2
 
@@ -2071,7 +2212,7 @@ fn test_op_show() {
     ");
     let stdout = test_env.jj_cmd_success(&repo_path, &["op", "show"]);
     insta::assert_snapshot!(&stdout, @r"
-    2a7821341a99 test-username@host.example.com 2001-02-03 04:05:30.000 +07:00 - 2001-02-03 04:05:30.000 +07:00
+    13d6e0d6e26d test-username@host.example.com 2001-02-03 04:05:30.000 +07:00 - 2001-02-03 04:05:30.000 +07:00
     push all tracked bookmarks to git remote origin
     args: jj git push --tracked
 
@@ -2089,6 +2230,38 @@ fn test_op_show() {
     ");
 }
 
+#[test]
+fn test_op_show_template() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.add_config(
+        r#"templates.commit_summary = 'coalesce(description.first_line(), "(no description)")'"#,
+    );
+
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "A"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "B"]);
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "op",
+            "show",
+            "@-",
+            "--no-graph",
+            "-T",
+            r#"tags ++ " by " ++ user ++ "\n""#,
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    args: jj describe -m A by test-username@host.example.com
+
+    Changed commits:
+    + A
+    - (no description)
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_op_show_patch() {
     let test_env = TestEnvironment::default();
@@ -2106,7 +2279,7 @@ fn test_op_show_patch() {
     ");
     let stdout = test_env.jj_cmd_success(&repo_path, &["op", "show", "@-", "-p", "--git"]);
     insta::assert_snapshot!(&stdout, @r"
-    187a5a9d8a22 test-username@host.example.com 2001-02-03 04:05:08.000 +07:00 - 2001-02-03 04:05:08.000 +07:00
+    187a5a9d8a22 test-username@host.example.com 2001-02-03 04:05:08.000 +07:00 - 2001-02-03 04:05:08.000 +07:00 (snapshot)
     snapshot working copy
     args: jj new
 
@@ -2144,13 +2317,13 @@ fn test_op_show_patch() {
     ");
     let stdout = test_env.jj_cmd_success(&repo_path, &["op", "show", "-p", "--git"]);
     insta::assert_snapshot!(&stdout, @r"
-    894c12d90345 test-username@host.example.com 2001-02-03 04:05:11.000 +07:00 - 2001-02-03 04:05:11.000 +07:00
+    22fa103c9ec0 test-username@host.example.com 2001-02-03 04:05:11.000 +07:00 - 2001-02-03 04:05:11.000 +07:00
     squash commits into 6b1027d2770cd0a39c468e525e52bf8c47e1464a
     args: jj squash
 
     Changed commits:
     ○  + mzvwutvl 9f4fb57f (empty) (no description set)
-    │ ○  - rlvkpnrz hidden 1d7f8f94 (no description set)
+    │ ○  - rlvkpnrz hidden 1d7f8f94 (no description set) (abandoned: squashed into another commit)
     ├─╯  diff --git a/file b/file
     │    index 7898192261..6178079822 100644
     │    --- a/file
@@ -2181,33 +2354,33 @@ fn test_op_show_patch() {
     ");
     let stdout = test_env.jj_cmd_success(&repo_path, &["op", "show", "-p", "--git"]);
     insta::assert_snapshot!(&stdout, @r"
-    e5505aa79d31 test-username@host.example.com 2001-02-03 04:05:13.000 +07:00 - 2001-02-03 04:05:13.000 +07:00
+    5c5fc5e4f4d7 test-username@host.example.com 2001-02-03 04:05:13.000 +07:00 - 2001-02-03 04:05:13.000 +07:00
     abandon commit 9f4fb57fba25a7b47ce5980a5d9a4766778331e8
     args: jj abandon
 
     Changed commits:
     ○  + yqosqzyt 33f321c4 (empty) (no description set)
-    ○  - mzvwutvl hidden 9f4fb57f (empty) (no description set)
+    ○  - mzvwutvl hidden 9f4fb57f (empty) (no description set) (abandoned: explicit)
     [EOF]
     ");
 
     // Try again with "op log".
     let stdout = test_env.jj_cmd_success(&repo_path, &["op", "log", "--git"]);
     insta::assert_snapshot!(&stdout, @r"
-    @  e5505aa79d31 test-username@host.example.com 2001-02-03 04:05:13.000 +07:00 - 2001-02-03 04:05:13.000 +07:00
+    @  5c5fc5e4f4d7 test-username@host.example.com 2001-02-03 04:05:13.000 +07:00 - 2001-02-03 04:05:13.000 +07:00
     │  abandon commit 9f4fb57fba25a7b47ce5980a5d9a4766778331e8
     │  args: jj abandon
     │
     │  Changed commits:
     │  ○  + yqosqzyt 33f321c4 (empty) (no description set)
-    │  ○  - mzvwutvl hidden 9f4fb57f (empty) (no description set)
-    ○  894c12d90345 test-username@host.example.com 2001-02-03 04:05:11.000 +07:00 - 2001-02-03 04:05:11.000 +07:00
+    │  ○  - mzvwutvl hidden 9f4fb57f (empty) (no description set) (abandoned: explicit)
+    ○  22fa103c9ec0 test-username@host.example.com 2001-02-03 04:05:11.000 +07:00 - 2001-02-03 04:05:11.000 +07:00
     │  squash commits into 6b1027d2770cd0a39c468e525e52bf8c47e1464a
     │  args: jj squash
     │
     │  Changed commits:
     │  ○  + mzvwutvl 9f4fb57f (empty) (no description set)
-    │  │ ○  - rlvkpnrz hidden 1d7f8f94 (no description set)
+    │  │ ○  - rlvkpnrz hidden 1d7f8f94 (no description set) (abandoned: squashed into another commit)
     │  ├─╯  diff --git a/file b/file
     │  │    index 7898192261..6178079822 100644
     │  │    --- a/file
@@ -2224,7 +2397,7 @@ fn test_op_show_patch() {
     │     @@ -1,1 +1,1 @@
     │     -a
     │     +b
-    ○  15c3c5d0baf0 test-username@host.example.com 2001-02-03 04:05:11.000 +07:00 - 2001-02-03 04:05:11.000 +07:00
+    ○  15c3c5d0baf0 test-username@host.example.com 2001-02-03 04:05:11.000 +07:00 - 2001-02-03 04:05:11.000 +07:00 (snapshot)
     │  snapshot working copy
     │  args: jj squash
     │
@@ -2244,7 +2417,7 @@ fn test_op_show_patch() {
     │
     │  Changed commits:
     │  ○  + rlvkpnrz 56950632 (empty) (no description set)
-    ○  187a5a9d8a22 test-username@host.example.com 2001-02-03 04:05:08.000 +07:00 - 2001-02-03 04:05:08.000 +07:00
+    ○  187a5a9d8a22 test-username@host.example.com 2001-02-03 04:05:08.000 +07:00 - 2001-02-03 04:05:08.000 +07:00 (snapshot)
     │  snapshot working copy
     │  args: jj new
     │
@@ -2268,6 +2441,119 @@ fn test_op_show_patch() {
     ");
 }
 
+#[test]
+fn test_op_show_abandon_reasons() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    // Avoid commit and operation ids in the output
+    test_env.add_config("templates.commit_summary = 'description.first_line()'");
+
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "a"]);
+    std::fs::write(repo_path.join("file"), "a\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new", "root()", "-m", "b"]);
+    std::fs::write(repo_path.join("file"), "a\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new", "root()", "-m", "c"]);
+    let b_commit_id = test_env
+        .jj_cmd_success(
+            &repo_path,
+            &[
+                "log",
+                "--no-graph",
+                "-r",
+                "description(b)",
+                "-T",
+                "commit_id",
+            ],
+        )
+        .raw()
+        .to_owned();
+
+    // A commit which became empty when rebased is abandoned by
+    // `--skip-emptied`
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "rebase",
+            "-r",
+            "description(b)",
+            "-d",
+            "description(a)",
+            "--skip-emptied",
+        ],
+    );
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["op", "show", "--no-graph", "-T", r#"tags ++ "\n""#],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    args: jj rebase -r 'description(b)' -d 'description(a)' --skip-emptied
+
+    Changed commits:
+    - b (abandoned: emptied by rebase)
+    [EOF]
+    ");
+    let (_, stderr) = test_env.jj_cmd_ok(&repo_path, &["evolog", "-r", &b_commit_id]);
+    insta::assert_snapshot!(stderr, @r"
+    The change was abandoned: emptied by rebase
+    [EOF]
+    ");
+
+    // A commit abandoned by `jj abandon` is explicitly abandoned
+    test_env.jj_cmd_ok(&repo_path, &["abandon", "description(a)"]);
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["op", "show", "--no-graph", "-T", r#"tags ++ "\n""#],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    args: jj abandon 'description(a)'
+
+    Changed commits:
+    - a (abandoned: explicit)
+    [EOF]
+    ");
+
+    // Visible commits weren't abandoned
+    let (_, stderr) = test_env.jj_cmd_ok(&repo_path, &["evolog", "-r", "description(c)"]);
+    insta::assert_snapshot!(stderr, @"");
+
+    // The empty working-copy commit rebased onto the parent of an abandoned
+    // commit isn't reported as discarded
+    test_env.add_config(
+        r#"templates.commit_summary = 'coalesce(description.first_line(), "(no description)")'"#,
+    );
+    test_env.jj_cmd_ok(&repo_path, &["new", "description(c)"]);
+    test_env.jj_cmd_ok(&repo_path, &["abandon", "description(c)"]);
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["op", "show", "--no-graph", "-T", r#"tags ++ "\n""#],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    args: jj abandon 'description(c)'
+
+    Changed commits:
+    + (no description)
+    - (no description)
+    - c (abandoned: explicit)
+    [EOF]
+    ");
+
+    // Abandoning the empty working-copy commit is explicit
+    test_env.jj_cmd_ok(&repo_path, &["abandon", "@"]);
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["op", "show", "--no-graph", "-T", r#"tags ++ "\n""#],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    args: jj abandon @
+
+    Changed commits:
+    + (no description)
+    - (no description) (abandoned: explicit)
+    [EOF]
+    ");
+}
+
 fn init_bare_git_repo(git_repo_path: &Path) -> gix::Repository {
     let git_repo = git::init_bare(git_repo_path);
     let commit_result = git::add_commit(
@@ -14,6 +14,8 @@
 
 use std::path::Path;
 
+use itertools::Itertools as _;
+
 use crate::common::CommandOutput;
 use crate::common::TestEnvironment;
 
@@ -664,6 +666,84 @@ fn test_parallelize_complex_nonlinear_target() {
     ");
 }
 
+#[test]
+fn test_parallelize_diamond() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let workspace_path = test_env.env_root().join("repo");
+    test_env.jj_cmd_ok(&workspace_path, &["commit", "-m=0"]);
+    test_env.jj_cmd_ok(&workspace_path, &["commit", "-m=1"]);
+    test_env.jj_cmd_ok(&workspace_path, &["new", "description(0)", "-m=2"]);
+    test_env.jj_cmd_ok(
+        &workspace_path,
+        &["new", "description(1)", "description(2)", "-m=3"],
+    );
+    test_env.jj_cmd_ok(&workspace_path, &["new", "-m=4"]);
+
+    test_env.jj_cmd_ok(
+        &workspace_path,
+        &["parallelize", "description(0)::description(3)"],
+    );
+    // The merge inside the set doesn't end up with duplicate parents, and the
+    // descendant outside the set is rebased onto all the parallelized commits
+    insta::assert_snapshot!(get_parents_output(&test_env, &workspace_path), @r"
+    0 parents:
+    1 parents:
+    2 parents:
+    3 parents:
+    4 parents: 0 1 2 3
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_parallelize_multiple_roots_with_merge_inside() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let workspace_path = test_env.env_root().join("repo");
+    test_env.jj_cmd_ok(&workspace_path, &["commit", "-m=0"]);
+    test_env.jj_cmd_ok(&workspace_path, &["commit", "-m=1"]);
+    test_env.jj_cmd_ok(&workspace_path, &["new", "root()"]);
+    test_env.jj_cmd_ok(&workspace_path, &["commit", "-m=x"]);
+    test_env.jj_cmd_ok(&workspace_path, &["describe", "-m=a"]);
+    test_env.jj_cmd_ok(
+        &workspace_path,
+        &["new", "description(1)", "description(a)", "-m=2"],
+    );
+    test_env.jj_cmd_ok(&workspace_path, &["new", "-m=3"]);
+
+    test_env.jj_cmd_ok(
+        &workspace_path,
+        &[
+            "parallelize",
+            "description(1) | description(a) | description(2)",
+        ],
+    );
+    // Each target is reparented onto the parents of the roots
+    insta::assert_snapshot!(get_parents_output(&test_env, &workspace_path), @r"
+    0 parents:
+    1 parents: 0
+    2 parents: 0 x
+    3 parents: 1 a 2
+    a parents: x
+    x parents:
+    [EOF]
+    ");
+}
+
+#[must_use]
+fn get_parents_output(test_env: &TestEnvironment, cwd: &Path) -> CommandOutput {
+    let template = r#"
+    separate(" ",
+        description.first_line(),
+        "parents:",
+        parents.map(|c|c.description().first_line())
+    ) ++ "\n""#;
+    test_env
+        .run_jj_in(cwd, ["log", "--no-graph", "-r=~root()", "-T", template])
+        .normalize_stdout_with(|s| s.lines().sorted().map(|l| format!("{l}\n")).collect())
+}
+
 #[must_use]
 fn get_log_output(test_env: &TestEnvironment, cwd: &Path) -> CommandOutput {
     let template = r#"
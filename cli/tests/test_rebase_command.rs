@@ -14,6 +14,8 @@
 
 use std::path::Path;
 
+use testutils::git;
+
 use crate::common::CommandOutput;
 use crate::common::TestEnvironment;
 
@@ -45,16 +47,22 @@ fn test_rebase_invalid() {
     create_commit(&test_env, &repo_path, "b", &["a"]);
 
     // Missing destination
-    let stderr = test_env.jj_cmd_cli_error(&repo_path, &["rebase"]);
+    let stderr = test_env.jj_cmd_cli_error(&repo_path, &["rebase", "-r", "a"]);
     insta::assert_snapshot!(stderr, @r"
     error: the following required arguments were not provided:
       <--destination <REVSETS>|--insert-after <REVSETS>|--insert-before <REVSETS>>
 
-    Usage: jj rebase <--destination <REVSETS>|--insert-after <REVSETS>|--insert-before <REVSETS>>
+    Usage: jj rebase --revisions <REVSETS> <--destination <REVSETS>|--insert-after <REVSETS>|--insert-before <REVSETS>>
 
     For more information, try '--help'.
     [EOF]
     ");
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["rebase"]);
+    insta::assert_snapshot!(stderr, @r"
+    Error: No immutable tracked remote bookmark was found upstream of the branch
+    Hint: Use `--destination` to specify where to rebase the revisions.
+    [EOF]
+    ");
 
     // Both -r and -s
     let stderr =
@@ -74,7 +82,7 @@ fn test_rebase_invalid() {
     insta::assert_snapshot!(stderr, @r"
     error: the argument '--branch <REVSETS>' cannot be used with '--source <REVSETS>'
 
-    Usage: jj rebase --branch <REVSETS> <--destination <REVSETS>|--insert-after <REVSETS>|--insert-before <REVSETS>>
+    Usage: jj rebase --branch <REVSETS> --destination <REVSETS>
 
     For more information, try '--help'.
     [EOF]
@@ -264,6 +272,242 @@ fn test_rebase_bookmark() {
     ");
 }
 
+#[test]
+fn test_rebase_default_destination() {
+    let test_env = TestEnvironment::default();
+    test_env.add_config(r#"templates.commit_summary = 'description.first_line()'"#);
+    git::init_bare(test_env.env_root().join("git-repo"));
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "upstream"]);
+    let upstream_path = test_env.env_root().join("upstream");
+    test_env.jj_cmd_ok(
+        &upstream_path,
+        &["git", "remote", "add", "origin", "../git-repo"],
+    );
+    test_env.jj_cmd_ok(&upstream_path, &["describe", "-m", "base"]);
+    test_env.jj_cmd_ok(&upstream_path, &["bookmark", "create", "-r@", "main"]);
+    test_env.jj_cmd_ok(
+        &upstream_path,
+        &["git", "push", "--allow-new", "-b", "main"],
+    );
+
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "clone", "git-repo", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.jj_cmd_ok(&repo_path, &["bookmark", "track", "main@origin"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "main", "-m", "topic1"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "topic2"]);
+
+    // main moves to "upstream" on the remote, and is fetched
+    // (pushing "base" made it immutable, so the working copy is on top of it)
+    test_env.jj_cmd_ok(&upstream_path, &["describe", "-m", "upstream"]);
+    test_env.jj_cmd_ok(&upstream_path, &["bookmark", "set", "main", "-r@"]);
+    test_env.jj_cmd_ok(&upstream_path, &["git", "push", "-b", "main"]);
+    test_env.jj_cmd_ok(&repo_path, &["git", "fetch"]);
+
+    // Without arguments, the current branch is rebased onto main@origin
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["rebase"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r"
+    Rebasing onto main@origin: upstream
+    Rebased 2 commits onto destination
+    Working copy now at: topic2
+    Parent commit      : topic1
+    [EOF]
+    ");
+    let template = r#"description.first_line() ++ "\n""#;
+    let stdout =
+        test_env.jj_cmd_success(&repo_path, &["log", "-r=::@", "--no-graph", "-T", template]);
+    insta::assert_snapshot!(stdout, @r"
+    topic2
+    topic1
+    upstream
+    base
+
+    [EOF]
+    ");
+
+    // Running it again doesn't duplicate commits already on the destination
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["rebase"]);
+    insta::assert_snapshot!(stderr, @r"
+    Rebasing onto main@origin: upstream
+    Skipped rebase of 2 commits that were already in place
+    Nothing changed.
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_rebase_skip_duplicates() {
+    let test_env = TestEnvironment::default();
+    test_env.add_config(r#"templates.commit_summary = 'description.first_line()'"#);
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    create_commit(&test_env, &repo_path, "base", &[]);
+    create_commit(&test_env, &repo_path, "a", &["base"]);
+    create_commit(&test_env, &repo_path, "b", &["a"]);
+    create_commit(&test_env, &repo_path, "c", &["b"]);
+    // b was landed upstream with a new commit id
+    test_env.jj_cmd_ok(&repo_path, &["duplicate", "b", "-d", "base"]);
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &["bookmark", "create", "-r", "children(base) ~ a", "landed"],
+    );
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &["rebase", "-s", "a", "-d", "landed", "--skip-duplicates"],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r"
+    Rebased 3 commits onto destination
+    Skipped 1 commit whose changes are already in the destination:
+      b
+    Working copy now at: c
+    Parent commit      : a
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r"
+    @  c: a b
+    ○  a b: landed
+    ○  landed: base
+    ○  base
+    ◆
+    [EOF]
+    ");
+
+    // Without the flag, the duplicate is kept
+    test_env.jj_cmd_ok(&repo_path, &["undo"]);
+    test_env.jj_cmd_ok(&repo_path, &["rebase", "-s", "a", "-d", "landed"]);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r"
+    @  c: b
+    ○  b: a
+    ○  a: landed
+    ○  landed: base
+    ○  base
+    ◆
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_rebase_skip_duplicates_divergent() {
+    let test_env = TestEnvironment::default();
+    test_env.add_config(r#"templates.commit_summary = 'description.first_line()'"#);
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    create_commit(&test_env, &repo_path, "base", &[]);
+    create_commit(&test_env, &repo_path, "a", &["base"]);
+    create_commit(&test_env, &repo_path, "b", &["a"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "base"]);
+    // b was landed upstream with the same change id, and rewritten locally
+    let op_id = test_env.jj_cmd_success(
+        &repo_path,
+        &["op", "log", "-n1", "--no-graph", "-T", "id.short()"],
+    );
+    let op_id = op_id.raw().trim_end();
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &["rebase", "-r", "b", "-d", "base", "--at-op", op_id],
+    );
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &["describe", "b", "-m", "b local", "--at-op", op_id],
+    );
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r"
+    @  : base
+    │ ○  b??: base
+    ├─╯
+    │ ○  b??: a
+    │ ○  a: base
+    ├─╯
+    ○  base
+    ◆
+    [EOF]
+    ------- stderr -------
+    Concurrent modification detected, resolving automatically.
+    [EOF]
+    ");
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "rebase",
+            "-s",
+            "a",
+            "-d",
+            r#"description(exact:"b\n")"#,
+            "--skip-duplicates",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r"
+    Rebased 2 commits onto destination
+    Skipped 1 commit whose changes are already in the destination:
+      b local
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r"
+    @  : base
+    │ ○  a b??: b??
+    │ ○  b??: base
+    ├─╯
+    ○  base
+    ◆
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_rebase_resolve_deferred() {
+    let test_env = TestEnvironment::default();
+    test_env.add_config("rewrite.defer-descendant-rebase = true");
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    create_commit(&test_env, &repo_path, "a", &[]);
+    create_commit(&test_env, &repo_path, "b", &["a"]);
+    create_commit(&test_env, &repo_path, "c", &["b"]);
+
+    // The descendants keep the old commit as parent, which isn't divergent
+    let output = test_env.run_jj_in(&repo_path, ["describe", "a", "-m", "a2"]);
+    insta::assert_snapshot!(output, @"");
+    let output = test_env.run_jj_in(&repo_path, ["log", "-T", "builtin_log_oneline"]);
+    insta::assert_snapshot!(output, @r"
+    @  royxmykx test.user 2001-02-03 08:05:13 c 7e4fbf4f c
+    ○  zsuskuln test.user 2001-02-03 08:05:11 b 1394f625 b
+    ○  rlvkpnrz superseded test.user 2001-02-03 08:05:09 2443ea76 a
+    │ ○  rlvkpnrz test.user 2001-02-03 08:05:14 a c379ca26 a2
+    ├─╯
+    ◆  zzzzzzzz root() 00000000
+    [EOF]
+    ");
+
+    let output = test_env.run_jj_in(&repo_path, ["rebase", "--resolve-deferred"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Rebased 2 descendant commits
+    Working copy now at: royxmykx c2e64b24 c | c
+    Parent commit      : zsuskuln d6046478 b | b
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&repo_path, ["log", "-T", "builtin_log_oneline"]);
+    insta::assert_snapshot!(output, @r"
+    @  royxmykx test.user 2001-02-03 08:05:16 c c2e64b24 c
+    ○  zsuskuln test.user 2001-02-03 08:05:16 b d6046478 b
+    ○  rlvkpnrz test.user 2001-02-03 08:05:14 a c379ca26 a2
+    ◆  zzzzzzzz root() 00000000
+    [EOF]
+    ");
+
+    // Nothing is left to rebase
+    let output = test_env.run_jj_in(&repo_path, ["rebase", "--resolve-deferred"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Nothing changed.
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_rebase_bookmark_with_merge() {
     let test_env = TestEnvironment::default();
@@ -713,7 +957,7 @@ fn test_rebase_revision_onto_descendant() {
     let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["op", "restore", &setup_opid]);
     insta::assert_snapshot!(stdout, @"");
     insta::assert_snapshot!(stderr, @r"
-    Restored to operation: 60e340690be6 (2001-02-03 08:05:15) create bookmark merge pointing to commit b05964d109522cd06e48f1a2661e1a0f58be0984
+    Restored to operation: c3421c2f5d82 (2001-02-03 08:05:15) create bookmark merge pointing to commit b05964d109522cd06e48f1a2661e1a0f58be0984
     Working copy now at: vruxwmqv b05964d1 merge | merge
     Parent commit      : royxmykx cea87a87 b | b
     Parent commit      : zsuskuln 2c5b7858 a | a
@@ -2904,6 +3148,48 @@ fn test_rebase_skip_emptied_descendants() {
     ");
 }
 
+#[test]
+fn test_rebase_skip_emptied_landed_upstream() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.add_config("templates.commit_summary = 'description.first_line()'");
+
+    create_commit(&test_env, &repo_path, "base", &[]);
+    create_commit(&test_env, &repo_path, "a", &["base"]);
+    create_commit(&test_env, &repo_path, "b", &["a"]);
+    create_commit(&test_env, &repo_path, "c", &["b"]);
+    // The changes of "b" and "c" landed upstream
+    test_env.jj_cmd_ok(&repo_path, &["new", "base", "-m", "main"]);
+    std::fs::write(repo_path.join("b"), "b\n").unwrap();
+    std::fs::write(repo_path.join("c"), "c\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["bookmark", "create", "-r@", "main"]);
+    test_env.jj_cmd_ok(&repo_path, &["edit", "c"]);
+
+    // The middle commit is abandoned and its bookmark is moved to its parent.
+    // The working-copy commit is kept even though it became empty.
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &["rebase", "-s", "a", "-d", "main", "--skip-emptied"],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r"
+    Rebased 2 commits onto destination
+    Abandoned 1 newly emptied commits
+    Working copy now at: c
+    Parent commit      : a
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r"
+    @  c: a b
+    ○  a b: main
+    ○  main: base
+    ○  base
+    ◆
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_rebase_skip_if_on_destination() {
     let test_env = TestEnvironment::default();
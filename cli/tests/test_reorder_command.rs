@@ -0,0 +1,185 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::Path;
+
+use crate::common::CommandOutput;
+use crate::common::TestEnvironment;
+
+fn create_commit(test_env: &TestEnvironment, repo_path: &Path, name: &str, content: &str) {
+    std::fs::write(repo_path.join("file"), content).unwrap();
+    std::fs::write(repo_path.join(name), format!("{name}\n")).unwrap();
+    test_env.jj_cmd_ok(repo_path, &["bookmark", "create", "-r@", name]);
+    test_env.jj_cmd_ok(repo_path, &["commit", "-m", name]);
+}
+
+fn reorder_id(test_env: &TestEnvironment, repo_path: &Path, rev: &str) -> String {
+    test_env
+        .jj_cmd_success(
+            repo_path,
+            &[
+                "log",
+                "--no-graph",
+                "-r",
+                rev,
+                "-T",
+                r#"change_id.short() ++ " " ++ commit_id.short()"#,
+            ],
+        )
+        .into_raw()
+}
+
+fn set_reorder_list(test_env: &mut TestEnvironment, lines: &[String]) {
+    let edit_script = test_env.set_up_fake_editor();
+    std::fs::write(edit_script, format!("write\n{}\n", lines.join("\n"))).unwrap();
+}
+
+#[must_use]
+fn get_log_output(test_env: &TestEnvironment, repo_path: &Path) -> CommandOutput {
+    let template = r#"separate(" ", description.first_line(), bookmarks, if(conflict, "(conflict)"), if(empty, "(empty)"))"#;
+    test_env.run_jj_in(repo_path, ["log", "-T", template])
+}
+
+#[test]
+fn test_reorder() {
+    let mut test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    create_commit(&test_env, &repo_path, "a", "");
+    create_commit(&test_env, &repo_path, "b", "");
+    create_commit(&test_env, &repo_path, "c", "");
+    let [a, b, c, wc] = ["a", "b", "c", "@"].map(|rev| reorder_id(&test_env, &repo_path, rev));
+
+    // The editor lists the stack oldest first
+    let edit_script = test_env.set_up_fake_editor();
+    std::fs::write(&edit_script, "dump editor").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["reorder", "-s=a"]);
+    let dumped = std::fs::read_to_string(test_env.env_root().join("editor")).unwrap();
+    assert_eq!(
+        dumped.lines().take(4).collect::<Vec<_>>(),
+        [
+            format!("{a} a"),
+            format!("{b} b"),
+            format!("{c} c"),
+            format!("{wc} (no description set)"),
+        ]
+    );
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r"
+    @  (empty)
+    ○  c c
+    ○  b b
+    ○  a a
+    ◆  (empty)
+    [EOF]
+    ");
+
+    // Move c to the bottom and drop b
+    set_reorder_list(
+        &mut test_env,
+        &[format!("{c} c"), format!("{a} a"), wc.clone()],
+    );
+    test_env.jj_cmd_ok(&repo_path, &["reorder", "-s=a"]);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r"
+    @  (empty)
+    ○  a a
+    ○  c c
+    ◆  (empty)
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["file", "list", "-r=@"]);
+    insta::assert_snapshot!(stdout, @r"
+    a
+    c
+    file
+    [EOF]
+    ");
+
+    // A single undo restores the original stack
+    test_env.jj_cmd_ok(&repo_path, &["undo"]);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r"
+    @  (empty)
+    ○  c c
+    ○  b b
+    ○  a a
+    ◆  (empty)
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_reorder_conflict() {
+    let mut test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    create_commit(&test_env, &repo_path, "a", "1\n");
+    create_commit(&test_env, &repo_path, "b", "2\n");
+    let [a, b, wc] = ["a", "b", "@"].map(|rev| reorder_id(&test_env, &repo_path, rev));
+
+    // Moving b to the bottom conflicts, but the reorder still happens
+    set_reorder_list(&mut test_env, &[b, a, wc]);
+    test_env.jj_cmd_ok(&repo_path, &["reorder", "-s=a"]);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r"
+    @  (empty)
+    ○  a a
+    ×  b b (conflict)
+    ◆  (empty)
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_reorder_errors() {
+    let mut test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    create_commit(&test_env, &repo_path, "a", "");
+    create_commit(&test_env, &repo_path, "b", "");
+    let [a, b, wc] = ["a", "b", "@"].map(|rev| reorder_id(&test_env, &repo_path, rev));
+
+    set_reorder_list(
+        &mut test_env,
+        &[a.clone(), b.clone(), "zzzzzzzzzzzz 000000000000".to_owned()],
+    );
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["reorder", "-s=a"]);
+    insta::assert_snapshot!(stderr, @r"
+    Error: Revision zzzzzzzzzzzz 000000000000 is not in the stack being reordered
+    [EOF]
+    ");
+
+    set_reorder_list(&mut test_env, &[a.clone(), b.clone(), a.clone(), wc]);
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["reorder", "-s=a"]);
+    insta::assert_snapshot!(stderr.normalize_with(|s| s.replace(&a, "<a>")), @r"
+    Error: Revision <a> is listed more than once
+    [EOF]
+    ");
+
+    // Non-linear stacks are rejected
+    test_env.jj_cmd_ok(&repo_path, &["new", "a"]);
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["reorder", "-s=a"]);
+    insta::assert_snapshot!(stderr, @r"
+    Error: Cannot reorder a non-linear stack of revisions
+    Hint: Each revision in the stack must have a single parent and at most one child.
+    [EOF]
+    ");
+
+    // Nothing was changed by the failed attempts
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r"
+    @  (empty)
+    │ ○  b b
+    ├─╯
+    ○  a a
+    ◆  (empty)
+    [EOF]
+    ");
+}
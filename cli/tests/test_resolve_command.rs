@@ -14,8 +14,12 @@
 
 use std::path::Path;
 
+use indoc::formatdoc;
 use indoc::indoc;
 
+use crate::common::fake_editor_path;
+use crate::common::force_interactive;
+use crate::common::to_toml_value;
 use crate::common::CommandOutput;
 use crate::common::TestEnvironment;
 
@@ -1836,3 +1840,576 @@ fn test_multiple_conflicts_with_error() {
     "
     );
 }
+
+#[test]
+fn test_resolve_with_tool_patterns() {
+    let mut test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.add_config(r#"templates.commit_summary = 'description.first_line()'"#);
+
+    create_commit(
+        &test_env,
+        &repo_path,
+        "base",
+        &[],
+        &[("a.lock", "base\n"), ("code.rs", "base\n")],
+    );
+    create_commit(
+        &test_env,
+        &repo_path,
+        "a",
+        &["base"],
+        &[("a.lock", "a\n"), ("code.rs", "a\n")],
+    );
+    create_commit(
+        &test_env,
+        &repo_path,
+        "b",
+        &["base"],
+        &[("a.lock", "b\n"), ("code.rs", "b\n")],
+    );
+    create_commit(&test_env, &repo_path, "conflict", &["a", "b"], &[]);
+
+    let editor_script = test_env.set_up_fake_editor();
+    let editor_path = to_toml_value(fake_editor_path());
+    test_env.add_config(formatdoc! {r#"
+        [merge-tools.lock-tool]
+        program = {editor_path}
+        merge-args = ["$output", "lock-tool"]
+        patterns = ["glob:*.lock"]
+
+        [merge-tools.missing-tool]
+        merge-args = ["$output"]
+    "#});
+
+    // Each file is resolved by the tool whose patterns match it, falling back to
+    // the default tool
+    std::fs::write(
+        &editor_script,
+        [
+            "expect-arg 0\nlock-tool",
+            "write\nlock resolution\n",
+            "next invocation\n",
+            "write\ncode resolution\n",
+        ]
+        .join("\0"),
+    )
+    .unwrap();
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["resolve"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r"
+    Resolving conflicts in: a.lock
+    Resolving conflicts in: code.rs
+    Working copy now at: conflict
+    Parent commit      : a
+    Parent commit      : b
+    Added 0 files, modified 2 files, removed 0 files
+    [EOF]
+    ");
+    insta::assert_snapshot!(
+        std::fs::read_to_string(repo_path.join("a.lock")).unwrap(), @"lock resolution");
+    insta::assert_snapshot!(
+        std::fs::read_to_string(repo_path.join("code.rs")).unwrap(), @"code resolution");
+
+    // --tool overrides the patterns
+    test_env.jj_cmd_ok(&repo_path, &["undo"]);
+    std::fs::write(
+        &editor_script,
+        "expect-arg 0\nlock-tool\0write\nresolution\n",
+    )
+    .unwrap();
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["resolve", "--tool=lock-tool"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r"
+    Resolving conflicts in: a.lock
+    Resolving conflicts in: code.rs
+    Working copy now at: conflict
+    Parent commit      : a
+    Parent commit      : b
+    Added 0 files, modified 2 files, removed 0 files
+    [EOF]
+    ");
+    insta::assert_snapshot!(
+        std::fs::read_to_string(repo_path.join("code.rs")).unwrap(), @"resolution");
+
+    // The configured tools can be listed
+    let stdout = test_env.jj_cmd_success(&repo_path, &["resolve", "--list-tools"]);
+    let tool_lines = stdout
+        .raw()
+        .lines()
+        .filter(|line| line.starts_with("lock-tool") || line.starts_with("missing-tool"))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .replace(&fake_editor_path(), "<fake-editor>");
+    insta::assert_snapshot!(tool_lines, @r"
+    lock-tool: <fake-editor> (found), patterns: glob:*.lock
+    missing-tool: missing-tool (not found)
+    ");
+}
+
+#[test]
+fn test_resolve_with_builtin_tools() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.add_config(r#"templates.commit_summary = 'description.first_line()'"#);
+
+    // Each side changes the same three lines, so there are three conflict hunks
+    create_commit(
+        &test_env,
+        &repo_path,
+        "base",
+        &[],
+        &[
+            ("file", "base 1\nkeep\nbase 2\nkeep\nbase 3\n"),
+            ("other", "base\n"),
+        ],
+    );
+    create_commit(
+        &test_env,
+        &repo_path,
+        "a",
+        &["base"],
+        &[("file", "a 1\nkeep\na 2\nkeep\na 3\n"), ("other", "a\n")],
+    );
+    create_commit(
+        &test_env,
+        &repo_path,
+        "b",
+        &["base"],
+        &[("file", "b 1\nkeep\nb 2\nkeep\nb 3\n"), ("other", "b\n")],
+    );
+    create_commit(&test_env, &repo_path, "conflict", &["a", "b"], &[]);
+
+    // :theirs picks the second side of each hunk, and only resolves the given
+    // paths
+    test_env.jj_cmd_ok(&repo_path, &["resolve", "--tool=:theirs", "file"]);
+    assert_eq!(
+        std::fs::read_to_string(repo_path.join("file")).unwrap(),
+        "b 1\nkeep\nb 2\nkeep\nb 3\n"
+    );
+    let stdout = test_env.jj_cmd_success(&repo_path, &["resolve", "--list"]);
+    insta::assert_snapshot!(stdout, @r"
+    other    2-sided conflict
+    [EOF]
+    ");
+
+    // :ours picks the first side of each hunk
+    test_env.jj_cmd_ok(&repo_path, &["undo"]);
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["resolve", "--tool=:ours"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r"
+    Working copy now at: conflict
+    Parent commit      : a
+    Parent commit      : b
+    Added 0 files, modified 2 files, removed 0 files
+    [EOF]
+    ");
+    insta::assert_snapshot!(
+        std::fs::read_to_string(repo_path.join("file")).unwrap(), @r"
+    a 1
+    keep
+    a 2
+    keep
+    a 3
+    ");
+    insta::assert_snapshot!(
+        std::fs::read_to_string(repo_path.join("other")).unwrap(), @"a");
+
+    // :union keeps both sides of each hunk
+    test_env.jj_cmd_ok(&repo_path, &["undo"]);
+    test_env.jj_cmd_ok(&repo_path, &["resolve", "--tool=:union", "file"]);
+    insta::assert_snapshot!(
+        std::fs::read_to_string(repo_path.join("file")).unwrap(), @r"
+    a 1
+    b 1
+    keep
+    a 2
+    b 2
+    keep
+    a 3
+    b 3
+    ");
+
+    // The built-in tools can be selected by patterns
+    test_env.jj_cmd_ok(&repo_path, &["undo"]);
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "resolve",
+            r#"--config=merge-tools.":theirs".patterns=["other"]"#,
+            "other",
+        ],
+    );
+    insta::assert_snapshot!(
+        std::fs::read_to_string(repo_path.join("other")).unwrap(), @"b");
+
+    // The built-in tools can't be used as diff editors
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["diffedit", "--tool=:ours"]);
+    insta::assert_snapshot!(stderr, @r"
+    Error: The tool `:ours` cannot be used as a diff editor
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_resolve_list_template_and_json() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    // "deleted" is deleted on one side, "exec" is made executable on one side,
+    // and "plain" is a plain content conflict
+    create_commit(
+        &test_env,
+        &repo_path,
+        "base",
+        &[],
+        &[
+            ("deleted", "base\n"),
+            ("exec", "base\n"),
+            ("plain", "base\n"),
+        ],
+    );
+    create_commit(
+        &test_env,
+        &repo_path,
+        "a",
+        &["base"],
+        &[("deleted", "a\n"), ("exec", "a\n"), ("plain", "a\n")],
+    );
+    create_commit(
+        &test_env,
+        &repo_path,
+        "b",
+        &["base"],
+        &[("exec", "b\n"), ("plain", "b\n")],
+    );
+    std::fs::remove_file(repo_path.join("deleted")).unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["file", "chmod", "x", "exec"]);
+    create_commit(&test_env, &repo_path, "conflict", &["a", "b"], &[]);
+
+    insta::assert_snapshot!(test_env.jj_cmd_success(&repo_path, &["resolve", "--list"]), @r"
+    deleted    2-sided conflict including 1 deletion
+    exec       2-sided conflict including an executable
+    plain      2-sided conflict
+    [EOF]
+    ");
+
+    let template = r#"
+        separate(" ", path, sides, involves_deletion, involves_modes) ++ "\n"
+    "#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["resolve", "--list", "-T", template]);
+    insta::assert_snapshot!(stdout, @r"
+    deleted 2 true false
+    exec 2 false true
+    plain 2 false false
+    [EOF]
+    ");
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["resolve", "--list", "--json", "exec"]);
+    insta::assert_snapshot!(stdout, @r#"
+    [
+      {
+        "involves_deletion": false,
+        "involves_modes": true,
+        "path": "exec",
+        "sides": 2
+      }
+    ]
+    [EOF]
+    "#);
+}
+
+#[test]
+fn test_resolve_dir_mode() {
+    let mut test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    create_commit(
+        &test_env,
+        &repo_path,
+        "base",
+        &[],
+        &[
+            ("file1", "base1\n"),
+            ("file2", "base2\n"),
+            ("file3", "context\n"),
+        ],
+    );
+    create_commit(
+        &test_env,
+        &repo_path,
+        "a",
+        &["base"],
+        &[("file1", "a1\n"), ("file2", "a2\n")],
+    );
+    create_commit(
+        &test_env,
+        &repo_path,
+        "b",
+        &["base"],
+        &[("file1", "b1\n"), ("file2", "b2\n")],
+    );
+    create_commit(&test_env, &repo_path, "conflict", &["a", "b"], &[]);
+
+    // Use the fake diff editor as a GUI tool which sees the left side and the
+    // output directories
+    let edit_script = test_env.set_up_fake_diff_editor();
+    test_env.add_config(r#"merge-tools.fake-diff-editor.merge-args = ["$left", "$output"]"#);
+
+    // If the tool fails, nothing is resolved
+    std::fs::write(&edit_script, "fail").unwrap();
+    let output = test_env.run_jj_in(
+        &repo_path,
+        ["resolve", "--tool=fake-diff-editor", "--dir-mode"],
+    );
+    insta::assert_snapshot!(output.normalize_stderr_exit_status(), @r"
+    ------- stderr -------
+    Resolving conflicts in: file1
+    Resolving conflicts in: file2
+    Error: Failed to resolve conflicts
+    Caused by: Tool exited with exit status: 1 (run with --debug to see the exact invocation)
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // The tool is invoked once with all the conflicted files, and the other
+    // files in their directories as context. It resolves one of the conflicted
+    // files, and the other one is left conflicted.
+    std::fs::write(
+        &edit_script,
+        [
+            "files-before file1 file2 file3",
+            "files-after file1 file2 file3",
+            "write file1\nresolution1\n",
+        ]
+        .join("\0"),
+    )
+    .unwrap();
+    let output = test_env.run_jj_in(
+        &repo_path,
+        [
+            "resolve",
+            "--tool=fake-diff-editor",
+            "--dir-mode",
+            "--include-context",
+        ],
+    );
+    insta::assert_snapshot!(output.normalize_stderr_exit_status(), @r"
+    ------- stderr -------
+    Resolving conflicts in: file1
+    Resolving conflicts in: file2
+    Working copy now at: vruxwmqv 8e301a33 conflict | (conflict) conflict
+    Parent commit      : zsuskuln f42464b5 a | a
+    Parent commit      : royxmykx 2e2fa9df b | b
+    Added 0 files, modified 1 files, removed 0 files
+    Warning: There are unresolved conflicts at these paths:
+    file2    2-sided conflict
+    New conflicts appeared in these commits:
+      vruxwmqv 8e301a33 conflict | (conflict) conflict
+    Hint: To resolve the conflicts, start by updating to it:
+      jj new vruxwmqv
+    Then use `jj resolve`, or edit the conflict markers in the file directly.
+    Once the conflicts are resolved, you may want to inspect the result with `jj diff`.
+    Then run `jj squash` to move the resolution into the conflicted commit.
+    Error: Stopped due to error after resolving 1 conflicts
+    Caused by: The output file is either unchanged or empty after the editor quit (run with --debug to see the exact invocation).
+    [EOF]
+    [exit status: 1]
+    ");
+    insta::assert_snapshot!(
+        std::fs::read_to_string(repo_path.join("file1")).unwrap(), @"resolution1");
+    insta::assert_snapshot!(test_env.jj_cmd_success(&repo_path, &["resolve", "--list"]), @r"
+    file2    2-sided conflict
+    [EOF]
+    ");
+
+    // If the tool doesn't resolve anything, it's an error
+    std::fs::write(&edit_script, "").unwrap();
+    let output = test_env.run_jj_in(
+        &repo_path,
+        ["resolve", "--tool=fake-diff-editor", "--dir-mode"],
+    );
+    insta::assert_snapshot!(output.normalize_stderr_exit_status(), @r"
+    ------- stderr -------
+    Resolving conflicts in: file2
+    Error: Failed to resolve conflicts
+    Caused by: The output file is either unchanged or empty after the editor quit (run with --debug to see the exact invocation).
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
+#[test]
+fn test_resolve_binary_conflict() {
+    let mut test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.add_config(r#"templates.commit_summary = 'description.first_line()'"#);
+    let edit_script = test_env.set_up_fake_editor();
+    std::fs::write(edit_script, "fail").unwrap();
+
+    create_commit(
+        &test_env,
+        &repo_path,
+        "base",
+        &[],
+        &[("logo.png", "base\0")],
+    );
+    create_commit(
+        &test_env,
+        &repo_path,
+        "a",
+        &["base"],
+        &[("logo.png", "a\0a")],
+    );
+    create_commit(
+        &test_env,
+        &repo_path,
+        "b",
+        &["base"],
+        &[("logo.png", "b\0bb")],
+    );
+    create_commit(&test_env, &repo_path, "conflict", &["a", "b"], &[]);
+
+    // The first side is written instead of conflict markers, and all the terms
+    // are written next to it
+    let read_file = |name: &str| std::fs::read(repo_path.join(name)).unwrap();
+    assert_eq!(read_file("logo.png"), b"a\0a");
+    assert_eq!(read_file("logo.png.jj-side-0"), b"a\0a");
+    assert_eq!(read_file("logo.png.jj-side-1"), b"b\0bb");
+    assert_eq!(read_file("logo.png.jj-base"), b"base\0");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["resolve", "--list"]);
+    insta::assert_snapshot!(stdout, @r"
+    logo.png    2-sided binary conflict
+    [EOF]
+    ");
+    // The files next to it aren't tracked
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--summary"]);
+    insta::assert_snapshot!(stdout, @"");
+
+    // Without a terminal to prompt on, a side must be picked with a built-in
+    // tool
+    let output = test_env.run_jj_in(&repo_path, ["resolve"]);
+    insta::assert_snapshot!(output.normalize_stderr_exit_status(), @r#"
+    ------- stderr -------
+    Error: Failed to resolve conflicts
+    Caused by: The conflict at "logo.png" is in a binary file. Use `--tool :ours` or `--tool :theirs` to pick one of its sides.
+    [EOF]
+    [exit status: 1]
+    "#);
+
+    // The merge tool isn't launched, and side 1 is picked at the prompt instead
+    let output = test_env.run_jj_with(|cmd| {
+        force_interactive(cmd)
+            .current_dir(&repo_path)
+            .arg("resolve")
+            .write_stdin("1\n")
+    });
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    The conflict at logo.png is in a binary file. Its sides are:
+    0: 3 bytes
+    1: 4 bytes
+    Enter the number of the side to keep: Working copy now at: conflict
+    Parent commit      : a
+    Parent commit      : b
+    Added 0 files, modified 1 files, removed 0 files
+    [EOF]
+    ");
+    assert_eq!(read_file("logo.png"), b"b\0bb");
+    assert!(!repo_path.join("logo.png.jj-side-0").exists());
+    assert!(!repo_path.join("logo.png.jj-side-1").exists());
+    assert!(!repo_path.join("logo.png.jj-base").exists());
+
+    // Built-in tools pick a whole side
+    test_env.jj_cmd_ok(&repo_path, &["undo"]);
+    test_env.jj_cmd_ok(&repo_path, &["resolve", "--tool=:theirs"]);
+    assert_eq!(read_file("logo.png"), b"b\0bb");
+    test_env.jj_cmd_ok(&repo_path, &["undo"]);
+    let output = test_env.run_jj_in(&repo_path, ["resolve", "--tool=:union"]);
+    insta::assert_snapshot!(output.normalize_stderr_exit_status(), @r#"
+    ------- stderr -------
+    Error: Failed to resolve conflicts
+    Caused by: The conflict at "logo.png" is in a binary file. Use `--tool :ours` or `--tool :theirs` to pick one of its sides.
+    [EOF]
+    [exit status: 1]
+    "#);
+
+    // Replacing the file with side 1 resolves the conflict when snapshotting
+    std::fs::copy(
+        repo_path.join("logo.png.jj-side-1"),
+        repo_path.join("logo.png"),
+    )
+    .unwrap();
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "--no-graph", "-r@", "-T=conflict"]);
+    insta::assert_snapshot!(stdout, @"false[EOF]");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["file", "show", "logo.png"]);
+    assert_eq!(stdout.raw(), "b\0bb");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--summary"]);
+    insta::assert_snapshot!(stdout, @r"
+    M logo.png
+    [EOF]
+    ");
+    assert!(!repo_path.join("logo.png.jj-side-0").exists());
+    assert!(!repo_path.join("logo.png.jj-base").exists());
+}
+
+#[test]
+fn test_resolve_binary_conflict_existing_files() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    create_commit(
+        &test_env,
+        &repo_path,
+        "base",
+        &[],
+        &[(".gitignore", "*.jj-side-1\n"), ("logo.png", "base\0")],
+    );
+    create_commit(
+        &test_env,
+        &repo_path,
+        "a",
+        &["base"],
+        &[("logo.png", "a\0a")],
+    );
+    create_commit(
+        &test_env,
+        &repo_path,
+        "b",
+        &["base"],
+        &[("logo.png", "b\0bb")],
+    );
+    // An ignored file of the user is in the way of one of the files written
+    // next to the conflict
+    std::fs::write(repo_path.join("logo.png.jj-side-1"), "mine").unwrap();
+    create_commit(&test_env, &repo_path, "conflict", &["a", "b"], &[]);
+
+    let read_file = |name: &str| std::fs::read(repo_path.join(name)).unwrap();
+    assert_eq!(read_file("logo.png.jj-side-0"), b"a\0a");
+    assert_eq!(read_file("logo.png.jj-side-1"), b"mine");
+    let output = test_env.run_jj_in(&repo_path, ["status"]);
+    insta::assert_snapshot!(output, @r"
+    The working copy has no changes.
+    Working copy : vruxwmqv 728161aa conflict | (conflict) (empty) conflict
+    Parent commit: zsuskuln 6372f665 a | a
+    Parent commit: royxmykx 8f7c88c9 b | b
+    Warning: There are unresolved conflicts at these paths:
+    logo.png    2-sided binary conflict
+    Conflicting changes to logo.png come from:
+      zsuskuln 6372f665 a | a
+      royxmykx 8f7c88c9 b | b
+    Hint: To resolve the conflicts, use `jj resolve`, or edit the conflict markers in the file directly.
+    [EOF]
+    ");
+
+    // The file of the user isn't removed along with the conflict
+    test_env.jj_cmd_ok(&repo_path, &["resolve", "--tool=:ours"]);
+    assert!(!repo_path.join("logo.png.jj-side-0").exists());
+    assert_eq!(read_file("logo.png.jj-side-1"), b"mine");
+}
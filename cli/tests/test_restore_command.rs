@@ -161,6 +161,38 @@ fn test_restore() {
 }
 
 // Much of this test is copied from test_resolve_command
+#[test]
+fn test_restore_with_fileset_exclusion() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::create_dir(repo_path.join("generated")).unwrap();
+    std::fs::write(repo_path.join("file1"), "a\n").unwrap();
+    std::fs::write(repo_path.join("generated/file2"), "b\n").unwrap();
+
+    test_env.jj_cmd_ok(&repo_path, &["restore", "~generated"]);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "-s"]);
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    A generated/file2
+    [EOF]
+    ");
+
+    // The error points into the argument which failed to parse
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["restore", "file1", "~generated &"]);
+    insta::assert_snapshot!(stderr, @r"
+    Error: Failed to parse fileset: Syntax error
+    Caused by:  --> 1:13
+      |
+    1 | ~generated &
+      |             ^---
+      |
+      = expected `~` or <primary>
+    Hint: See https://jj-vcs.github.io/jj/latest/filesets/ for filesets syntax, or for how to match file paths.
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_restore_conflicted_merge() {
     let test_env = TestEnvironment::default();
@@ -364,6 +396,70 @@ fn test_restore_restore_descendants() {
     ");
 }
 
+#[test]
+fn test_restore_from_operation() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    let current_op_id = || {
+        test_env
+            .jj_cmd_success(
+                &repo_path,
+                &["op", "log", "--no-graph", "-n1", "-T=id.short()"],
+            )
+            .into_raw()
+    };
+
+    // Rewrite file1 in place twice; each snapshot is a separate operation
+    std::fs::write(repo_path.join("file1"), "v1\n").unwrap();
+    std::fs::write(repo_path.join("file2"), "a\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["status"]);
+    let op_v1 = current_op_id();
+    std::fs::write(repo_path.join("file1"), "v2\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["status"]);
+    std::fs::write(repo_path.join("file1"), "v3\n").unwrap();
+    std::fs::write(repo_path.join("file2"), "b\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["status"]);
+
+    // Only the given path is restored from the working copy at that operation
+    test_env.jj_cmd_ok(&repo_path, &["restore", "--from-op", &op_v1, "file1"]);
+    insta::assert_snapshot!(
+        std::fs::read_to_string(repo_path.join("file1")).unwrap(), @"v1");
+    insta::assert_snapshot!(
+        std::fs::read_to_string(repo_path.join("file2")).unwrap(), @"b");
+
+    // The source revision can be one which is hidden now
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "old"]);
+    let op_old = current_op_id();
+    test_env.jj_cmd_ok(&repo_path, &["abandon"]);
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "restore",
+            "--from-op",
+            &op_old,
+            "--from",
+            "description(old)",
+        ],
+    );
+    let stdout = test_env.jj_cmd_success(&repo_path, &["file", "show", "file1"]);
+    insta::assert_snapshot!(stdout, @r"
+    v1
+    [EOF]
+    ");
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["restore", "--from", "description(old)"]);
+    insta::assert_snapshot!(stderr, @r"
+    Error: Revset `description(old)` didn't resolve to any revisions
+    [EOF]
+    ");
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["restore", "--from-op", "fffffff"]);
+    insta::assert_snapshot!(stderr, @r#"
+    Error: No operation ID matching "fffffff"
+    [EOF]
+    "#);
+}
+
 #[test]
 fn test_restore_interactive() {
     let mut test_env = TestEnvironment::default();
@@ -184,10 +184,10 @@ fn test_bad_function_call() {
     2:  --> 1:1
       |
     1 | foo:"bar"
-      | ^-------^
+      | ^--^
       |
-      = Invalid file pattern
-    3: Invalid file pattern kind `foo:`
+      = Invalid file pattern kind `foo:`
+    Hint: Did you mean `root:`? Valid pattern kinds are `cwd:`, `cwd-file:`, `file:`, `cwd-glob:`, `glob:`, `cwd-glob-i:`, `glob-i:`, `root:`, `root-file:`, `root-glob:`, `root-glob-i:`
     [EOF]
     "#);
 
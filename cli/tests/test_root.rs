@@ -41,3 +41,57 @@ fn test_root_outside_a_repo() {
     [EOF]
     "#);
 }
+
+#[test]
+fn test_root_does_not_load_index() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    // If the index were loaded, it would be rebuilt from scratch.
+    let index_dir = repo_path.join(".jj").join("repo").join("index");
+    let index_subdirs = [index_dir.join("operations"), index_dir.join("segments")];
+    for dir in &index_subdirs {
+        std::fs::remove_dir_all(dir).unwrap();
+        std::fs::create_dir(dir).unwrap();
+    }
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["root"]);
+    insta::assert_snapshot!(stdout, @r"
+    $TEST_ENV/repo
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["workspace", "root"]);
+    insta::assert_snapshot!(stdout, @r"
+    $TEST_ENV/repo
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["git", "root"]);
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    $TEST_ENV/repo/.jj/repo/store/git
+    [EOF]
+    ");
+    test_env.jj_cmd_success(&repo_path, &["config", "list"]);
+    // These load the repo and snapshot the working copy, but never look up
+    // commits in the index.
+    let stdout =
+        test_env.jj_cmd_success(&repo_path, &["op", "log", "-T", r#"description ++ "\n""#]);
+    insta::assert_snapshot!(stdout, @r"
+    @  add workspace 'default'
+    ○
+    [EOF]
+    ");
+    let stdout =
+        test_env.jj_cmd_success(&repo_path, &["workspace", "list", "-T", r#"name ++ "\n""#]);
+    insta::assert_snapshot!(stdout, @r"
+    default
+    [EOF]
+    ");
+    for dir in &index_subdirs {
+        assert_eq!(std::fs::read_dir(dir).unwrap().count(), 0, "{dir:?}");
+    }
+
+    // Commands that use the repo still rebuild the index
+    test_env.jj_cmd_ok(&repo_path, &["status"]);
+    assert_ne!(std::fs::read_dir(&index_subdirs[0]).unwrap().count(), 0);
+}
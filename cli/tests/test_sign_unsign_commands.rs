@@ -0,0 +1,166 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::Path;
+
+use crate::common::CommandOutputString;
+use crate::common::TestEnvironment;
+
+fn get_log_output(test_env: &TestEnvironment, repo_path: &Path) -> CommandOutputString {
+    let template = r#"
+    separate(" ",
+      description.first_line(),
+      if(signature, "key=" ++ signature.key(), "(unsigned)"),
+    ) ++ "\n"
+    "#;
+    test_env.jj_cmd_success(
+        repo_path,
+        &[
+            "log",
+            "--no-graph",
+            "-r",
+            "description(one)::",
+            "-T",
+            template,
+        ],
+    )
+}
+
+#[test]
+fn test_sign_unsign() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.add_config(
+        r#"
+    signing.backend = "test"
+    signing.key = "impeccable"
+    "#,
+    );
+
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "one"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "two"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "three"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "root()"]);
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["sign", "-r", "description(two)"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r"
+    Signed 1 commit
+    Rebased 1 descendant commits
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r"
+    three (unsigned)
+    two key=impeccable
+    one (unsigned)
+    [EOF]
+    ");
+
+    // Already signed commit is skipped. Its signature is kept while being
+    // rebased.
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["sign", "-r", "description(one)::"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r"
+    Signed 2 commits
+    Skipped 1 commit which is already signed
+    Rebased 1 descendant commits
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r"
+    three key=impeccable
+    two key=impeccable
+    one key=impeccable
+    [EOF]
+    ");
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["sign", "-r", "description(one)::"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r"
+    Skipped 3 commits which are already signed
+    Nothing changed.
+    [EOF]
+    ");
+
+    // Re-sign with another key
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "sign",
+            "-r",
+            "description(three)",
+            "--force",
+            "--key",
+            "other",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r"
+    Signed 1 commit
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r"
+    three key=other
+    two key=impeccable
+    one key=impeccable
+    [EOF]
+    ");
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["unsign", "-r", "description(three)"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r"
+    Unsigned 1 commit
+    [EOF]
+    ");
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["unsign", "-r", "description(one)::"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r"
+    Unsigned 2 commits
+    Skipped 1 commit which isn't signed
+    Rebased 1 descendant commits
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r"
+    three (unsigned)
+    two (unsigned)
+    one (unsigned)
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_sign_errors() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["sign"]);
+    insta::assert_snapshot!(stderr, @r"
+    Error: No signing backend is configured
+    Hint: Set `signing.backend` to the backend to sign commits with
+    [EOF]
+    ");
+
+    test_env.add_config(r#"signing.backend = "test""#);
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["sign", "-r", "root()"]);
+    insta::assert_snapshot!(stderr, @r"
+    Error: The root commit 000000000000 is immutable
+    [EOF]
+    ");
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["unsign", "-r", "root()"]);
+    insta::assert_snapshot!(stderr, @r"
+    Error: The root commit 000000000000 is immutable
+    [EOF]
+    ");
+}
@@ -191,6 +191,92 @@ fn test_sparse_manage_patterns() {
     ");
 }
 
+#[test]
+fn test_sparse_exclude_patterns() {
+    let mut test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    let edit_script = test_env.set_up_fake_editor();
+
+    std::fs::write(repo_path.join("file1"), "contents").unwrap();
+    std::fs::write(repo_path.join("file2"), "contents").unwrap();
+    std::fs::write(repo_path.join("file3"), "contents").unwrap();
+
+    // Can exclude a file from the default pattern
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["sparse", "set", "--exclude", "file2"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r"
+    Added 0 files, modified 0 files, removed 1 files
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["sparse", "list"]);
+    insta::assert_snapshot!(stdout, @r"
+    .
+    !file2
+    [EOF]
+    ");
+    assert!(repo_path.join("file1").exists());
+    assert!(!repo_path.join("file2").exists());
+    assert!(repo_path.join("file3").exists());
+
+    // Exclusions round-trip through the editor
+    std::fs::write(&edit_script, "dump patterns0\0write\n.\n!file1\n! file3\n").unwrap();
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["sparse", "edit"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r"
+    Added 1 files, modified 0 files, removed 2 files
+    [EOF]
+    ");
+    insta::assert_snapshot!(
+        std::fs::read_to_string(test_env.env_root().join("patterns0")).unwrap(), @r"
+    .
+    !file2
+    ");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["sparse", "list"]);
+    insta::assert_snapshot!(stdout, @r"
+    .
+    !file1
+    !file3
+    [EOF]
+    ");
+    assert!(!repo_path.join("file1").exists());
+    assert!(repo_path.join("file2").exists());
+    assert!(!repo_path.join("file3").exists());
+
+    // Adding a pattern replaces the exclusion of the same path
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["sparse", "set", "--add", "file1"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r"
+    Added 1 files, modified 0 files, removed 0 files
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["sparse", "list"]);
+    insta::assert_snapshot!(stdout, @r"
+    .
+    !file3
+    file1
+    [EOF]
+    ");
+
+    // Removing a path also removes its exclusion
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["sparse", "set", "--remove", "file3"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r"
+    Added 1 files, modified 0 files, removed 0 files
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["sparse", "list"]);
+    insta::assert_snapshot!(stdout, @r"
+    .
+    file1
+    [EOF]
+    ");
+    assert!(repo_path.join("file1").exists());
+    assert!(repo_path.join("file2").exists());
+    assert!(repo_path.join("file3").exists());
+}
+
 #[test]
 fn test_sparse_editor_avoids_unc() {
     use std::path::PathBuf;
@@ -145,31 +145,63 @@ fn test_split_by_paths() {
     // Remove newly created empty commit
     test_env.jj_cmd_ok(&repo_path, &["abandon", "@-"]);
 
-    // Insert an empty commit before @- with "split nonexistent"
-    test_env.set_up_fake_editor();
-    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["split", "-r", "@-", "nonexistent"]);
-    insta::assert_snapshot!(stdout, @"");
+    // Splitting by paths which don't select anything is an error
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["split", "-r", "@-", "nonexistent"]);
     insta::assert_snapshot!(stderr, @r"
-    Warning: No changes have been selected, so the first commit will be empty
-    Rebased 1 descendant commits
-    First part: qpvuntsm bd42f95a (empty) (no description set)
-    Second part: lylxulpl ed55c86b (no description set)
-    Working copy now at: zsuskuln 1e1ed741 (no description set)
-    Parent commit      : lylxulpl ed55c86b (no description set)
+    Error: No changes have been selected by the given paths
+    Hint: Use `jj new --insert-before` if you want to insert an empty commit.
     [EOF]
     ");
+}
 
-    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r"
-    @  zsuskulnrvyr false
-    ○  lylxulplsnyw false
-    ○  qpvuntsmwlqt true
-    ◆  zzzzzzzzzzzz true
+#[test]
+fn test_split_with_fileset_exclusion() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let workspace_path = test_env.env_root().join("repo");
+
+    std::fs::create_dir(workspace_path.join("generated")).unwrap();
+    std::fs::write(workspace_path.join("file1"), "a\n").unwrap();
+    std::fs::write(workspace_path.join("generated/file2"), "b\n").unwrap();
+    test_env.jj_cmd_ok(&workspace_path, &["describe", "-m", "original"]);
+
+    test_env.jj_cmd_ok(&workspace_path, &["split", "-m", "first", "~generated"]);
+    let stdout = test_env.jj_cmd_success(&workspace_path, &["diff", "-s", "-r", "@-"]);
+    insta::assert_snapshot!(stdout, @r"
+    A file1
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(&workspace_path, &["diff", "-s"]);
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    A generated/file2
     [EOF]
     ");
+}
 
-    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "-s", "-r", "@-"]);
-    insta::assert_snapshot!(stdout, @r"
-    A file2
+#[test]
+fn test_split_exclude_from_subdir() {
+    let mut test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let workspace_path = test_env.env_root().join("repo");
+    let subdir_path = workspace_path.join("dir");
+    std::fs::create_dir_all(subdir_path.join("sub")).unwrap();
+
+    std::fs::write(subdir_path.join("file1"), "foo\n").unwrap();
+    std::fs::write(subdir_path.join("sub/file2"), "bar\n").unwrap();
+    test_env.jj_cmd_ok(&workspace_path, &["describe", "-m", "original"]);
+
+    // Neither the editor nor the diff editor is opened
+    let edit_script = test_env.set_up_fake_editor();
+    std::fs::write(edit_script, "fail").unwrap();
+    test_env.jj_cmd_ok(&subdir_path, &["split", "-m", "first", "-X", "sub"]);
+    let stdout = test_env.jj_cmd_success(&workspace_path, &["diff", "--summary", "-r=@-"]);
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    A dir/file1
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(&workspace_path, &["diff", "--summary"]);
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    A dir/sub/file2
     [EOF]
     ");
 }
@@ -235,6 +267,133 @@ fn test_split_with_non_empty_description() {
     ");
 }
 
+#[test]
+fn test_split_with_message() {
+    let mut test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let workspace_path = test_env.env_root().join("repo");
+
+    std::fs::write(workspace_path.join("file1"), "foo\n").unwrap();
+    std::fs::write(workspace_path.join("file2"), "bar\n").unwrap();
+    test_env.jj_cmd_ok(&workspace_path, &["describe", "-m", "original"]);
+    // The editor isn't opened for either commit
+    let edit_script = test_env.set_up_fake_editor();
+    std::fs::write(edit_script, "fail").unwrap();
+    test_env.jj_cmd_ok(
+        &workspace_path,
+        &["split", "file1", "-m", "part 1", "-m", "more"],
+    );
+    insta::assert_snapshot!(get_log_output(&test_env, &workspace_path), @r"
+    @  kkmpptxzrspx false original
+    ○  qpvuntsmwlqt false part 1
+    │
+    │  more
+    ◆  zzzzzzzzzzzz true
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_split_with_message_second_and_templates() {
+    let mut test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let workspace_path = test_env.env_root().join("repo");
+
+    std::fs::write(workspace_path.join("file1"), "foo\n").unwrap();
+    std::fs::write(workspace_path.join("file2"), "bar\n").unwrap();
+    test_env.jj_cmd_ok(&workspace_path, &["describe", "-m", "original"]);
+
+    // The editor is only opened for the first commit
+    let edit_script = test_env.set_up_fake_editor();
+    std::fs::write(
+        &edit_script,
+        ["write\npart 1", "next invocation\n", "fail"].join("\0"),
+    )
+    .unwrap();
+    test_env.jj_cmd_ok(
+        &workspace_path,
+        &["split", "file1", "--message-second", "part 2"],
+    );
+    insta::assert_snapshot!(get_log_output(&test_env, &workspace_path), @r"
+    @  kkmpptxzrspx false part 2
+    ○  qpvuntsmwlqt false part 1
+    ◆  zzzzzzzzzzzz true
+    [EOF]
+    ");
+
+    // Descriptions generated by templates don't open the editor
+    test_env.jj_cmd_ok(&workspace_path, &["undo"]);
+    std::fs::write(&edit_script, "fail").unwrap();
+    test_env.add_config(
+        r#"
+        [templates]
+        split_first_description = '"(first) " ++ description'
+        split_second_description = '"(split) " ++ description'
+        "#,
+    );
+    test_env.jj_cmd_ok(&workspace_path, &["split", "file1"]);
+    let template = r#"description.first_line() ++ "\n""#;
+    let stdout = test_env.jj_cmd_success(&workspace_path, &["log", "-T", template]);
+    insta::assert_snapshot!(stdout, @r"
+    @  (split) original
+    ○  (first) original
+    ◆
+    [EOF]
+    ");
+
+    // Explicit messages take precedence over the templates
+    test_env.jj_cmd_ok(&workspace_path, &["undo"]);
+    test_env.jj_cmd_ok(
+        &workspace_path,
+        &["split", "file1", "--message-first", "explicit"],
+    );
+    let stdout = test_env.jj_cmd_success(&workspace_path, &["log", "-T", template]);
+    insta::assert_snapshot!(stdout, @r"
+    @  (split) original
+    ○  explicit
+    ◆
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_split_bookmarks_to() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let workspace_path = test_env.env_root().join("repo");
+    // The flag takes precedence over the config, without a warning
+    test_env.add_config("split.legacy-bookmark-behavior=false");
+
+    std::fs::write(workspace_path.join("file1"), "foo\n").unwrap();
+    std::fs::write(workspace_path.join("file2"), "bar\n").unwrap();
+    test_env.jj_cmd_ok(&workspace_path, &["bookmark", "create", "-r@", "book"]);
+    let (_, stderr) = test_env.jj_cmd_ok(
+        &workspace_path,
+        &["split", "file1", "-m", "first", "--bookmarks-to", "second"],
+    );
+    assert!(!stderr.raw().contains("Warning"), "{stderr}");
+    insta::assert_snapshot!(get_log_output(&test_env, &workspace_path), @r"
+    @  kkmpptxzrspx false book
+    ○  qpvuntsmwlqt false first
+    ◆  zzzzzzzzzzzz true
+    [EOF]
+    ");
+
+    test_env.jj_cmd_ok(&workspace_path, &["undo"]);
+    test_env.add_config("split.legacy-bookmark-behavior=true");
+    let (_, stderr) = test_env.jj_cmd_ok(
+        &workspace_path,
+        &["split", "file1", "-m", "first", "--bookmarks-to", "first"],
+    );
+    assert!(!stderr.raw().contains("Warning"), "{stderr}");
+    insta::assert_snapshot!(get_log_output(&test_env, &workspace_path), @r"
+    @  royxmykxtrkr false
+    ○  qpvuntsmwlqt false book first
+    ◆  zzzzzzzzzzzz true
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_split_with_default_description() {
     let mut test_env = TestEnvironment::default();
@@ -799,7 +958,7 @@ fn test_split_with_multiple_workspaces_different_working_copy() {
     // node in the graph indicates the current workspace's working-copy commit.
     insta::assert_snapshot!(get_workspace_log_output(&test_env, &main_path), @r"
     @  qpvuntsmwlqt default@ first-commit
-    │ ○  pmmvwywvzvvn second@
+    │ ◎  pmmvwywvzvvn second@
     ├─╯
     ◆  zzzzzzzzzzzz
     [EOF]
@@ -816,7 +975,7 @@ fn test_split_with_multiple_workspaces_different_working_copy() {
     insta::assert_snapshot!(get_workspace_log_output(&test_env, &main_path), @r"
     @  mzvwutvlkqwt default@ second-commit
     ○  qpvuntsmwlqt first-commit
-    │ ○  pmmvwywvzvvn second@
+    │ ◎  pmmvwywvzvvn second@
     ├─╯
     ◆  zzzzzzzzzzzz
     [EOF]
@@ -834,7 +993,7 @@ fn test_split_with_multiple_workspaces_different_working_copy() {
     @  vruxwmqvtpmx default@ second-commit
     │ ○  qpvuntsmwlqt first-commit
     ├─╯
-    │ ○  pmmvwywvzvvn second@
+    │ ◎  pmmvwywvzvvn second@
     ├─╯
     ◆  zzzzzzzzzzzz
     [EOF]
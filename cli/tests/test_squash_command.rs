@@ -309,6 +309,32 @@ fn test_squash_partial() {
     insta::assert_snapshot!(stdout, @"");
 }
 
+#[test]
+fn test_squash_with_fileset_exclusion() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("base"), "base\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m", "base"]);
+    std::fs::create_dir(repo_path.join("generated")).unwrap();
+    std::fs::write(repo_path.join("file1"), "a\n").unwrap();
+    std::fs::write(repo_path.join("generated/file2"), "b\n").unwrap();
+
+    test_env.jj_cmd_ok(&repo_path, &["squash", "~generated"]);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "-s"]);
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    A generated/file2
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "-s", "-r", "@-"]);
+    insta::assert_snapshot!(stdout, @r"
+    A base
+    A file1
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_squash_keep_emptied() {
     let test_env = TestEnvironment::default();
@@ -1110,6 +1136,52 @@ fn get_log_output(test_env: &TestEnvironment, repo_path: &Path) -> CommandOutput
     test_env.run_jj_in(repo_path, ["log", "-T", template])
 }
 
+#[test]
+fn test_squash_from_multiple_non_adjacent_into_ancestor() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    // Create a linear stack a..g, each commit adding a file of the same name
+    for name in ["a", "b", "c", "d", "e", "f", "g"] {
+        std::fs::write(repo_path.join(name), format!("{name}\n")).unwrap();
+        test_env.jj_cmd_ok(&repo_path, &["bookmark", "create", "-r@", name]);
+        test_env.jj_cmd_ok(&repo_path, &["commit", "-m", name]);
+    }
+    let old_head_id = test_env
+        .jj_cmd_success(&repo_path, &["log", "--no-graph", "-r=g", "-T=commit_id"])
+        .into_raw();
+
+    // Squash the fixups into their common ancestor
+    test_env.jj_cmd_ok(&repo_path, &["squash", "--from=b|d|f", "--into=a", "-m=a"]);
+    let template = r#"separate(" ", description.first_line(), bookmarks) ++ "\n""#;
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["log", "--no-graph", "-r=a::g", "-T", template],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    g g
+    e e f
+    c c d
+    a a b
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--summary", "-r=a"]);
+    insta::assert_snapshot!(stdout, @r"
+    A a
+    A b
+    A d
+    A f
+    [EOF]
+    ");
+    // The descendants' trees are unchanged
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["diff", "--summary", "--from", &old_head_id, "--to=g"],
+    );
+    insta::assert_snapshot!(stdout, @"");
+}
+
 #[test]
 fn test_squash_description() {
     let mut test_env = TestEnvironment::default();
@@ -1313,6 +1385,48 @@ fn test_squash_use_destination_message() {
 }
 
 // The --use-destination-message and --message options are incompatible.
+#[test]
+fn test_squash_use_destination_message_keep_emptied_partial() {
+    let mut test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file0"), "0\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m=parent"]);
+    std::fs::write(repo_path.join("file1"), "1\n").unwrap();
+    std::fs::write(repo_path.join("file2"), "2\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m=child"]);
+    test_env.jj_cmd_ok(&repo_path, &["bookmark", "create", "-r@", "child"]);
+    // The editor must not be invoked even though both commits have descriptions
+    let edit_script = test_env.set_up_fake_editor();
+    std::fs::write(&edit_script, "fail").unwrap();
+    let template = r#"separate(" ", description.first_line(), bookmarks, if(empty, "(empty)"))"#;
+
+    // Move only some of the changes
+    test_env.jj_cmd_ok(&repo_path, &["squash", "-u", "--keep-emptied", "file1"]);
+    insta::assert_snapshot!(test_env.jj_cmd_success(&repo_path, &["log", "-T", template]), @r"
+    @  child child
+    ○  parent
+    ◆  (empty)
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--summary", "-r=@-"]);
+    insta::assert_snapshot!(stdout, @r"
+    A file0
+    A file1
+    [EOF]
+    ");
+
+    // Move the rest, keeping the emptied source and its bookmark
+    test_env.jj_cmd_ok(&repo_path, &["squash", "-u", "--keep-emptied"]);
+    insta::assert_snapshot!(test_env.jj_cmd_success(&repo_path, &["log", "-T", template]), @r"
+    @  child child (empty)
+    ○  parent
+    ◆  (empty)
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_squash_use_destination_message_and_message_mutual_exclusion() {
     let test_env = TestEnvironment::default();
@@ -213,6 +213,9 @@ fn test_status_display_relevant_working_commit_conflict_hints() {
     Parent commit: royxmykx 664a4c6c (conflict) (empty) boom-cont
     Warning: There are unresolved conflicts at these paths:
     conflicted.txt    2-sided conflict
+    Conflicting changes to conflicted.txt come from:
+      zsuskuln 2c8b19fd Second part of conflicting change
+      kkmpptxz 1e8c2956 First part of conflicting change
     Hint: To resolve the conflicts, start by updating to the first one:
       jj new mzvwutvl
     Then use `jj resolve`, or edit the conflict markers in the file directly.
@@ -228,6 +231,9 @@ fn test_status_display_relevant_working_commit_conflict_hints() {
     Parent commit: [1m[38;5;5mr[0m[38;5;8moyxmykx[39m [1m[38;5;4m6[0m[38;5;8m64a4c6c[39m [38;5;1m(conflict)[39m [38;5;2m(empty)[39m boom-cont
     [1m[38;5;3mWarning: [39mThere are unresolved conflicts at these paths:[0m
     conflicted.txt    [38;5;3m2-sided conflict[39m
+    Conflicting changes to conflicted.txt come from:
+      [1m[38;5;5mzs[0m[38;5;8muskuln[39m [1m[38;5;4m2[0m[38;5;8mc8b19fd[39m Second part of conflicting change
+      [1m[38;5;5mk[0m[38;5;8mkmpptxz[39m [1m[38;5;4m1[0m[38;5;8me8c2956[39m First part of conflicting change
     [1m[38;5;6mHint: [0m[39mTo resolve the conflicts, start by updating to the first one:[39m
     [39m  jj new [1m[38;5;5mm[0m[38;5;8mzvwutvl[39m[39m
     [39mThen use `jj resolve`, or edit the conflict markers in the file directly.[39m
@@ -383,15 +389,23 @@ fn test_status_simplify_conflict_sides() {
         &[],
     );
 
+    // Each conflict comes from its own pair of commits
+    test_env.add_config("templates.commit_summary = 'description.first_line()'");
     insta::assert_snapshot!(test_env.jj_cmd_success(&repo_path, &["status"]),
     @r"
     The working copy has no changes.
-    Working copy : nkmrtpmo 83c4b9e7 conflict | (conflict) (empty) conflict
-    Parent commit: kmkuslsw 4601566f conflictA | (conflict) (empty) conflictA
-    Parent commit: lylxulpl 6f8d8381 conflictB | (conflict) (empty) conflictB
+    Working copy : conflict
+    Parent commit: conflictA
+    Parent commit: conflictB
     Warning: There are unresolved conflicts at these paths:
     fileA    2-sided conflict
     fileB    2-sided conflict
+    Conflicting changes to fileA come from:
+      a1
+      a2
+    Conflicting changes to fileB come from:
+      b1
+      b2
     Hint: To resolve the conflicts, start by updating to one of the first ones:
       jj new lylxulpl
       jj new kmkuslsw
@@ -402,6 +416,76 @@ fn test_status_simplify_conflict_sides() {
     ");
 }
 
+#[test]
+fn test_status_conflict_introduced_by_rebase() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.add_config("templates.commit_summary = 'description.first_line()'");
+
+    create_commit(&test_env, &repo_path, "base", &[], &[("file", "base\n")]);
+    create_commit(&test_env, &repo_path, "a", &["base"], &[("file", "a\n")]);
+    create_commit(&test_env, &repo_path, "b", &["base"], &[("file", "b\n")]);
+    test_env.jj_cmd_ok(&repo_path, &["rebase", "-r", "b", "-d", "a"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "child", "b"]);
+
+    insta::assert_snapshot!(test_env.jj_cmd_success(&repo_path, &["status"]), @r"
+    The working copy has no changes.
+    Working copy : child
+    Parent commit: b
+    Warning: There are unresolved conflicts at these paths:
+    file    2-sided conflict
+    Conflicting changes to file come from:
+      a
+      b
+    Hint: The conflict at file was introduced by rebasing royxmykx onto zsuskuln.
+    Hint: To resolve the conflicts, start by updating to it:
+      jj new royxmykx
+    Then use `jj resolve`, or edit the conflict markers in the file directly.
+    Once the conflicts are resolved, you may want to inspect the result with `jj diff`.
+    Then run `jj squash` to move the resolution into the conflicted commit.
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_status_conflict_origins_limit() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.add_config("templates.commit_summary = 'description.first_line()'");
+
+    let files = |content| ["file1", "file2", "file3", "file4"].map(|name| (name, content));
+    create_commit(&test_env, &repo_path, "base", &[], &files("base\n"));
+    create_commit(&test_env, &repo_path, "a", &["base"], &files("a\n"));
+    create_commit(&test_env, &repo_path, "b", &["base"], &files("b\n"));
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "merge", "a", "b"]);
+
+    // Only the origins of the first few conflicts are traced
+    insta::assert_snapshot!(test_env.jj_cmd_success(&repo_path, &["status"]), @r"
+    The working copy has no changes.
+    Working copy : merge
+    Parent commit: a
+    Parent commit: b
+    Warning: There are unresolved conflicts at these paths:
+    file1    2-sided conflict
+    file2    2-sided conflict
+    file3    2-sided conflict
+    file4    2-sided conflict
+    Conflicting changes to file1 come from:
+      a
+      b
+    Conflicting changes to file2 come from:
+      a
+      b
+    Conflicting changes to file3 come from:
+      a
+      b
+    Hint: To resolve the conflicts, use `jj resolve`, or edit the conflict markers in the file directly.
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_status_untracked_files() {
     let test_env = TestEnvironment::default();
@@ -421,8 +505,7 @@ fn test_status_untracked_files() {
     Untracked paths:
     ? always-untracked-file
     ? initially-untracked-file
-    ? sub/always-untracked
-    ? sub/initially-untracked
+    ? sub/
     Working copy : qpvuntsm 230dd059 (empty) (no description set)
     Parent commit: zzzzzzzz 00000000 (empty) (no description set)
     [EOF]
@@ -480,8 +563,7 @@ fn test_status_untracked_files() {
     Untracked paths:
     ? always-untracked-file
     ? initially-untracked-file
-    ? sub/always-untracked
-    ? sub/initially-untracked
+    ? sub/
     Working copy : mzvwutvl bb362aaf (no description set)
     Parent commit: qpvuntsm 99798fcd (no description set)
     [EOF]
@@ -494,10 +576,111 @@ fn test_status_untracked_files() {
     Untracked paths:
     ? always-untracked-file
     ? initially-untracked-file
-    ? sub/always-untracked
-    ? sub/initially-untracked
+    ? sub/
     Working copy : yostqsxw 8e8c02fe (empty) (no description set)
     Parent commit: mzvwutvl bb362aaf (no description set)
     [EOF]
     ");
 }
+
+#[test]
+fn test_status_untracked_and_ignored_paths() {
+    let test_env = TestEnvironment::default();
+    // Only the large file is tracked automatically, so it's refused
+    test_env.add_config(r#"snapshot.auto-track = "large""#);
+    test_env.add_config(r#"snapshot.max-new-file-size = 20"#);
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join(".gitignore"), "*.log\ntarget/\n").unwrap();
+    std::fs::write(repo_path.join("tracked"), "").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["file", "track", ".gitignore", "tracked"]);
+
+    std::fs::write(repo_path.join("file"), "").unwrap();
+    std::fs::write(repo_path.join("large"), "a lot of text, too much").unwrap();
+    std::fs::write(repo_path.join("debug.log"), "").unwrap();
+    std::fs::create_dir_all(repo_path.join("new/sub")).unwrap();
+    std::fs::write(repo_path.join("new/file"), "").unwrap();
+    std::fs::write(repo_path.join("new/sub/file"), "").unwrap();
+    std::fs::create_dir(repo_path.join("target")).unwrap();
+    std::fs::write(repo_path.join("target/out"), "").unwrap();
+
+    // Directories without tracked files are collapsed, and large files are
+    // listed with their sizes.
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["status"]);
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    Working copy changes:
+    A .gitignore
+    A tracked
+    Untracked paths:
+    ? file
+    ? new/
+    Untracked paths too large to snapshot:
+    ? large: 23.0B (23 bytes); the maximum size allowed is 20.0B (20 bytes)
+    Working copy : qpvuntsm 0d85db7a (no description set)
+    Parent commit: zzzzzzzz 00000000 (empty) (no description set)
+    [EOF]
+    ");
+    insta::assert_snapshot!(stderr, @r"
+    Warning: Refused to snapshot some files:
+      large: 23.0B (23 bytes); the maximum size allowed is 20.0B (20 bytes)
+    Hint: This is to prevent large files from being added by accident. You can fix this by:
+      - Adding the file to `.gitignore`
+      - Run `jj config set --repo snapshot.max-new-file-size 23`
+        This will increase the maximum file size allowed for new files, in this repository only.
+      - Run `jj --config snapshot.max-new-file-size=23 st`
+        This will increase the maximum file size allowed for new files, for this command only.
+    [EOF]
+    ");
+
+    // Ignored paths are listed only if requested
+    let (stdout, _stderr) = test_env.jj_cmd_ok(&repo_path, &["status", "--ignored"]);
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    Working copy changes:
+    A .gitignore
+    A tracked
+    Untracked paths:
+    ? file
+    ? new/
+    Untracked paths too large to snapshot:
+    ? large: 23.0B (23 bytes); the maximum size allowed is 20.0B (20 bytes)
+    Ignored paths:
+    ! debug.log
+    ! target/
+    Working copy : qpvuntsm 0d85db7a (no description set)
+    Parent commit: zzzzzzzz 00000000 (empty) (no description set)
+    [EOF]
+    ");
+
+    // Path filters apply to all sections, and directories outside of the
+    // filter aren't collapsed
+    let (stdout, _stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &["status", "--ignored", "new/sub", "target/out", "tracked"],
+    );
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    Working copy changes:
+    A tracked
+    Untracked paths:
+    ? new/sub/
+    Ignored paths:
+    ! target/
+    Working copy : qpvuntsm 0d85db7a (no description set)
+    Parent commit: zzzzzzzz 00000000 (empty) (no description set)
+    [EOF]
+    ");
+
+    // Once a file in the directory is tracked, only its untracked
+    // subdirectory is collapsed
+    test_env.jj_cmd_ok(&repo_path, &["file", "track", "new/file"]);
+    let (stdout, _stderr) = test_env.jj_cmd_ok(&repo_path, &["status", "new"]);
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    Working copy changes:
+    A new/file
+    Untracked paths:
+    ? new/sub/
+    Working copy : qpvuntsm d3eb9a23 (no description set)
+    Parent commit: zzzzzzzz 00000000 (empty) (no description set)
+    [EOF]
+    ");
+}
@@ -88,7 +88,7 @@ fn test_templater_parse_error() {
       |                    ^^
       |
       = Keyword `se` doesn't exist
-    Hint: Did you mean `s`, `self`?
+    Hint: Did you mean `s`, `self`, `superseded`?
     [EOF]
     "#);
     insta::assert_snapshot!(render_err(r#"format_id(commit_id)"#), @r"
@@ -15,6 +15,7 @@ use std::path::Path;
 
 use testutils::git;
 
+use crate::common::force_interactive;
 use crate::common::CommandOutput;
 use crate::common::TestEnvironment;
 
@@ -417,6 +418,192 @@ fn test_bookmark_track_untrack_undo() {
     ");
 }
 
+#[test]
+fn test_undo_selected_bookmarks() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    // Avoid operation ids in the output
+    test_env.add_config("templates.op_summary = 'tags'");
+    let get_bookmarks = || {
+        let template = r#"name ++ ": " ++ normal_target.description().first_line() ++ "\n""#;
+        test_env.jj_cmd_success(&repo_path, &["bookmark", "list", "-T", template])
+    };
+
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "A"]);
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &["bookmark", "create", "-r@", "main", "feature", "other"],
+    );
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "B"]);
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &["bookmark", "set", "-r@", "main", "feature", "other"],
+    );
+    insta::assert_snapshot!(get_bookmarks(), @r"
+    feature: B
+    main: B
+    other: B
+    [EOF]
+    ");
+
+    // Only `main` is moved back
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["undo", "--bookmark", "main"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r"
+    Undid operation: args: jj bookmark set -r@ main feature other
+    Restored bookmarks: main
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_bookmarks(), @r"
+    feature: B
+    main: A
+    other: B
+    [EOF]
+    ");
+
+    // Restoring the local bookmarks doesn't touch the commits
+    let (_stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "op",
+            "restore",
+            "--what=local-bookmarks",
+            "--bookmark=glob:*",
+            "@---",
+        ],
+    );
+    insta::assert_snapshot!(stderr, @r"
+    Restored to operation: args: jj bookmark create -r@ main feature other
+    Restored bookmarks: feature, other
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_bookmarks(), @r"
+    feature: A
+    main: A
+    other: A
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-T", "description"]);
+    insta::assert_snapshot!(stdout, @r"
+    @  B
+    ○  A
+    ◆
+    [EOF]
+    ");
+
+    // Nothing to restore, no operation is created
+    let op_log_before = test_env.jj_cmd_success(&repo_path, &["op", "log"]);
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["undo", "--bookmark", "unknown"]);
+    insta::assert_snapshot!(stderr, @r"
+    Warning: No matching bookmarks to restore
+    [EOF]
+    ");
+    let op_log_after = test_env.jj_cmd_success(&repo_path, &["op", "log"]);
+    assert_eq!(op_log_before.raw(), op_log_after.raw());
+
+    // The commits and the working copy aren't restored
+    test_env.jj_cmd_ok(&repo_path, &["abandon", "description(A)"]);
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["undo", "--bookmark", "main"]);
+    insta::assert_snapshot!(stderr, @r"
+    Undid operation: args: jj abandon 'description(A)'
+    Restored bookmarks: main
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_bookmarks(), @r"
+    main: A
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-T", "description"]);
+    insta::assert_snapshot!(stdout, @r"
+    @  B
+    │ ○  A
+    ├─╯
+    ◆
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_undo_dry_run_and_interactive() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    // Avoid commit and operation ids in the output
+    test_env.add_config(
+        r#"
+        templates.commit_summary = 'coalesce(description.first_line(), "(no description)")'
+        templates.op_summary = 'tags'
+        "#,
+    );
+
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "A"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "B"]);
+    let get_op_count = || {
+        let stdout =
+            test_env.jj_cmd_success(&repo_path, &["op", "log", "--no-graph", "-T", r#""x""#]);
+        stdout.raw().len()
+    };
+    let op_count = get_op_count();
+
+    // A dry run previews the changes and warns about the working-copy commit
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["undo", "--dry-run"]);
+    insta::assert_snapshot!(stdout, @r"
+    The following changes would be made:
+
+    Changed commits:
+    ○  - B
+    [EOF]
+    ");
+    insta::assert_snapshot!(stderr, @r"
+    Warning: The current working-copy commit would become hidden:
+      B
+    Dry-run requested, not undoing.
+    [EOF]
+    ");
+    assert_eq!(get_op_count(), op_count);
+
+    // The prompt defaults to no if it can't be answered
+    let output = test_env.run_jj_in(&repo_path, ["undo", "--interactive"]);
+    insta::assert_snapshot!(output, @r"
+    The following changes would be made:
+
+    Changed commits:
+    ○  - B
+    [EOF]
+    ------- stderr -------
+    Warning: The current working-copy commit would become hidden:
+      B
+    Undo this operation? (yN): N
+    Error: Undo aborted
+    [EOF]
+    [exit status: 1]
+    ");
+    assert_eq!(get_op_count(), op_count);
+
+    // Restoring to the current operation doesn't change anything
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["op", "restore", "--dry-run", "@"]);
+    insta::assert_snapshot!(stdout, @r"
+    The following changes would be made:
+    [EOF]
+    ");
+    insta::assert_snapshot!(stderr, @r"
+    Dry-run requested, not restoring.
+    [EOF]
+    ");
+    assert_eq!(get_op_count(), op_count);
+
+    let stderr = test_env.jj_cmd_cli_error(&repo_path, &["undo", "--dry-run", "-i"]);
+    insta::assert_snapshot!(stderr, @r"
+    error: the argument '--dry-run' cannot be used with '--interactive'
+
+    Usage: jj undo --dry-run [OPERATION]
+
+    For more information, try '--help'.
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_shows_a_warning_when_undoing_an_undo_operation_as_bare_jj_undo() {
     let test_env = TestEnvironment::default();
@@ -443,7 +630,7 @@ fn test_shows_a_warning_when_undoing_an_undo_operation_as_bare_jj_undo() {
     let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["undo"]);
     insta::assert_snapshot!(stdout, @"");
     insta::assert_snapshot!(stderr, @r"
-    Undid operation: b16799358b33 (2001-02-03 08:05:12) undo operation b14487c6d6d98f7f575ea03c48ed92d899c2a0ecbe9458221b6fc11af2bf6d918c9620cae1f8268012b0e25c7dd6f78b19ec628d0504a0830dc562d6625ba9ec
+    Undid operation: 404b601fb88e (2001-02-03 08:05:12) undo operation 4525066d65ef4e7d7f2d5f1420aa99b34700bb6ade3223f45e26de4c67dcd83a952a464de8a0496cf8d9e051d6f63cb1a88288b78d184a09cde7db8f65a5eda6
     Working copy now at: mzvwutvl 167f90e7 (empty) (no description set)
     Parent commit      : qpvuntsm 230dd059 (empty) (no description set)
     Hint: This action reverted an 'undo' operation. The repository is now in the same state as it was before the original 'undo'.
@@ -472,6 +659,61 @@ fn test_shows_no_warning_when_undoing_a_specific_undo_change() {
     ");
 }
 
+#[test]
+fn test_asks_for_confirmation_when_undoing_an_undo_operation_on_a_terminal() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    // Avoid commit and operation ids in the output
+    test_env.add_config(
+        r#"
+        templates.commit_summary = 'coalesce(description.first_line(), "(no description)")'
+        templates.op_summary = 'tags'
+        "#,
+    );
+
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "B"]);
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["undo"]);
+    insta::assert_snapshot!(stderr, @r"
+    Undid operation: args: jj new -m B
+    Working copy now at: (no description)
+    Parent commit      : (no description)
+    [EOF]
+    ");
+
+    // The second undo would redo the first one
+    let output = test_env.run_jj_with(|cmd| {
+        force_interactive(cmd)
+            .current_dir(&repo_path)
+            .arg("undo")
+            .write_stdin("n\n")
+    });
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Warning: The operation to undo is itself an 'undo' operation. Undoing it will redo the previously undone operation.
+    Hint: To go further back, use `jj op log` to see past states, and `jj op restore` to restore one of these states.
+    Undo the 'undo' operation? (yN): Error: Undo aborted
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // --yes skips the confirmation
+    let output = test_env.run_jj_with(|cmd| {
+        force_interactive(cmd)
+            .current_dir(&repo_path)
+            .args(["undo", "--yes"])
+    });
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Undid operation: args: jj undo
+    Working copy now at: B
+    Parent commit      : (no description)
+    Hint: This action reverted an 'undo' operation. The repository is now in the same state as it was before the original 'undo'.
+    Hint: If your goal is to undo multiple operations, consider using `jj op log` to see past states, and `jj op restore` to restore one of these states.
+    [EOF]
+    ");
+}
+
 #[must_use]
 fn get_bookmark_output(test_env: &TestEnvironment, repo_path: &Path) -> CommandOutput {
     // --quiet to suppress deleted bookmarks hint
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use insta::assert_snapshot;
+use regex::Regex;
 
 use crate::common::TestEnvironment;
 
@@ -48,8 +49,7 @@ fn test_gc_args() {
     );
     let repo_path = test_env.env_root().join("repo");
 
-    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["util", "gc"]);
-    insta::assert_snapshot!(stderr, @"");
+    test_env.jj_cmd_ok(&repo_path, &["util", "gc"]);
 
     let stderr = test_env.jj_cmd_failure(&repo_path, &["util", "gc", "--at-op=@-"]);
     insta::assert_snapshot!(stderr, @r"
@@ -59,7 +59,8 @@ fn test_gc_args() {
 
     let stderr = test_env.jj_cmd_failure(&repo_path, &["util", "gc", "--expire=foobar"]);
     insta::assert_snapshot!(stderr, @r"
-    Error: --expire only accepts 'now'
+    Error: Invalid --expire date
+    Caused by: expected week day or month name
     [EOF]
     ");
 }
@@ -98,6 +99,122 @@ fn test_gc_operation_log() {
     "#);
 }
 
+#[test]
+fn test_gc_packs_operations() {
+    let test_env = TestEnvironment::default();
+    // Use the local backend because GitBackend::gc() depends on the git CLI.
+    test_env.jj_cmd_ok(
+        test_env.env_root(),
+        &["init", "repo", "--config=ui.allow-init-native=true"],
+    );
+    let repo_path = test_env.env_root().join("repo");
+    let op_store_path = repo_path.join(".jj").join("repo").join("op_store");
+    let count_files = |dir: &str| std::fs::read_dir(op_store_path.join(dir)).unwrap().count();
+
+    for i in 0..20 {
+        test_env.jj_cmd_ok(&repo_path, &["describe", "-m", &format!("description {i}")]);
+    }
+    let op_to_restore = test_env.current_operation_id(&repo_path);
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+    assert!(count_files("operations") > 20);
+    assert!(count_files("views") > 20);
+
+    // All operations are reachable, so they are moved into a single pack.
+    test_env.jj_cmd_ok(&repo_path, &["util", "gc"]);
+    assert_eq!(count_files("operations"), 0);
+    assert_eq!(count_files("views"), 0);
+    let pack_count = std::fs::read_dir(op_store_path.join("packs"))
+        .unwrap()
+        .filter(|entry| {
+            let path = entry.as_ref().unwrap().path();
+            path.extension().is_some_and(|ext| ext == "pack")
+        })
+        .count();
+    assert_eq!(pack_count, 1);
+
+    // Packed operations can still be listed and restored.
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["op", "log", "--no-graph", "-T", "id ++ \"\\n\""],
+    );
+    assert!(stdout.raw().lines().count() > 20);
+    test_env.jj_cmd_ok(&repo_path, &["op", "restore", &op_to_restore]);
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["log", "--no-graph", "-r@", "-T", "description"],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    description 19
+    [EOF]
+    ");
+
+    // New operations are written as loose files until the next gc.
+    assert_eq!(count_files("operations"), 1);
+}
+
+#[test]
+fn test_gc_expired_operations() {
+    // GitBackend::gc() depends on the git CLI.
+    if std::process::Command::new("git")
+        .arg("--version")
+        .status()
+        .is_err()
+    {
+        eprintln!("Skipping because git command might fail to run");
+        return;
+    }
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    let git_repo_path = repo_path.join(".jj").join("repo").join("store").join("git");
+    let count_git_objects = || {
+        let output = std::process::Command::new("git")
+            .args(["count-objects", "-v"])
+            .current_dir(&git_repo_path)
+            .output()
+            .unwrap();
+        String::from_utf8(output.stdout)
+            .unwrap()
+            .lines()
+            .filter_map(|line| {
+                let (key, value) = line.split_once(": ")?;
+                matches!(key, "count" | "in-pack").then(|| value.parse::<usize>().unwrap())
+            })
+            .sum::<usize>()
+    };
+
+    // Create a stack of commits, move away from it, and abandon it.
+    for i in 0..5 {
+        std::fs::write(
+            repo_path.join(format!("file{i}")),
+            format!("contents {i}\n"),
+        )
+        .unwrap();
+        test_env.jj_cmd_ok(&repo_path, &["commit", "-m", &format!("commit {i}")]);
+    }
+    test_env.jj_cmd_ok(&repo_path, &["new", "root()"]);
+    test_env.jj_cmd_ok(&repo_path, &["abandon", "description(commit)"]);
+    let object_count_before = count_git_objects();
+    let op_count = || {
+        let stdout = test_env.jj_cmd_success(&repo_path, &["op", "log", "--no-graph", "-Tid"]);
+        stdout.raw().len() / 128
+    };
+    let op_count_before = op_count();
+
+    // No operations are abandoned by default, so the commits are still
+    // reachable from the operation log.
+    test_env.jj_cmd_ok(&repo_path, &["util", "gc"]);
+    assert_eq!(op_count(), op_count_before);
+    assert!(count_git_objects() >= object_count_before);
+
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["util", "gc", "--expire=now"]);
+    let first_line = stderr.raw().lines().next().unwrap();
+    insta::assert_snapshot!(first_line, @"Abandoned 12 operations older than now.");
+    // Only the current operation is left on top of the root operation.
+    assert_eq!(op_count(), 2);
+    assert!(count_git_objects() < object_count_before);
+}
+
 #[test]
 fn test_shell_completions() {
     #[track_caller]
@@ -150,3 +267,137 @@ fn test_util_exec_fail() {
     [exit status: 1]
     ");
 }
+
+#[test]
+fn test_util_cache_clear() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    let cache_dir = repo_path.join(".jj").join("repo").join("cache");
+    std::fs::write(repo_path.join("file"), "a\nb\n").unwrap();
+
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["util", "cache", "clear"]);
+    insta::assert_snapshot!(stderr, @r"
+    Nothing to remove.
+    [EOF]
+    ");
+
+    // Diff stats are cached, and the cached stats are used on the next run
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--stat"]);
+    insta::assert_snapshot!(stdout, @r"
+    file | 2 ++
+    1 file changed, 2 insertions(+), 0 deletions(-)
+    [EOF]
+    ");
+    assert!(cache_dir.join("diff_stats").exists());
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["log", "--no-graph", "-r@", "-T", "self.diff().stat(80)"],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    file | 2 ++
+    1 file changed, 2 insertions(+), 0 deletions(-)
+    [EOF]
+    ");
+
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["util", "cache", "clear"]);
+    insta::assert_snapshot!(stderr, @r"
+    Removed cached data.
+    [EOF]
+    ");
+    assert!(!cache_dir.exists());
+}
+
+#[test]
+fn test_util_index() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+    let filter_stats = |text: String| {
+        let hash_regex = Regex::new(r"[0-9a-f]{128}").unwrap();
+        let number_regex = Regex::new(r"(files|operations): \d+").unwrap();
+        let text = hash_regex.replace_all(&text, "[hash]");
+        number_regex.replace_all(&text, "$1: [N]").into_owned()
+    };
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["util", "index", "stats"]);
+    insta::assert_snapshot!(stdout.normalize_with(filter_stats), @r"
+    Number of segment files: [N]
+    Total size of segment files: [N] bytes
+    Number of indexed operations: [N]
+    Number of commits: 4
+    Segments at the operation:
+      Number of commits in [hash]: 3
+      Number of commits in [hash]: 1
+    [EOF]
+    ");
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["util", "index", "compact"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r"
+    Compacted 2 index segments into one with 4 commits.
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["util", "index", "stats"]);
+    insta::assert_snapshot!(stdout.normalize_with(filter_stats), @r"
+    Number of segment files: [N]
+    Total size of segment files: [N] bytes
+    Number of indexed operations: [N]
+    Number of commits: 4
+    Segments at the operation:
+      Number of commits in [hash]: 4
+    [EOF]
+    ");
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["util", "index", "compact"]);
+    insta::assert_snapshot!(stderr, @r"
+    The index is already compact.
+    [EOF]
+    ");
+
+    // Corrupt the parent position of the first entry in each segment file
+    let segments_dir = repo_path.join(".jj/repo/index/segments");
+    for entry in std::fs::read_dir(&segments_dir).unwrap() {
+        let path = entry.unwrap().path();
+        let mut data = std::fs::read(&path).unwrap();
+        let parent_filename_len = u32::from_le_bytes(data[4..8].try_into().unwrap()) as usize;
+        let offset = 8 + parent_filename_len + 16 + 4;
+        data[offset..offset + 4].copy_from_slice(&0x7fff_fff0_u32.to_le_bytes());
+        std::fs::write(&path, data).unwrap();
+    }
+
+    // The corruption is reported by verify without rebuilding the index
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["util", "index", "verify"]);
+    insta::assert_snapshot!(stderr.normalize_with(filter_stats), @r"
+    Error: Failed to load commit index file '[hash]'
+    Caused by: invalid parent position
+    Hint: Run `jj util index rebuild` to rebuild the index from the operation log
+    [EOF]
+    ");
+
+    // The index should be rebuilt automatically
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &["log", "--no-graph", "-r@-", "-T", r#""ok\n""#],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    ok
+    [EOF]
+    ");
+    insta::assert_snapshot!(stderr.normalize_with(filter_stats), @r"
+    Failed to load commit index file '[hash]' (maybe the format has changed): invalid parent position. Reindexing...
+    [EOF]
+    ");
+
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["util", "index", "rebuild"]);
+    insta::assert_snapshot!(stderr, @r"
+    Finished indexing 4 commits.
+    [EOF]
+    ");
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["util", "index", "verify"]);
+    insta::assert_snapshot!(stderr, @r"
+    Verified 4 commits in 1 index segments.
+    [EOF]
+    ");
+}
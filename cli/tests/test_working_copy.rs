@@ -158,8 +158,9 @@ fn test_snapshot_large_file_restore() {
     Created kkmpptxz e3eb7e81 (no description set)
     Working copy now at: kkmpptxz e3eb7e81 (no description set)
     Parent commit      : zzzzzzzz 00000000 (empty) (no description set)
-    Added 1 files, modified 0 files, removed 0 files
-    Warning: 1 of those updates were skipped because there were conflicting changes in the working copy.
+    Added 1 files, modified 0 files, removed 0 files, skipped 1 files (see below)
+    Warning: 1 of those updates were skipped because there were conflicting changes in the working copy:
+      file: an untracked file or directory is in the way
     Hint: Inspect the changes compared to the intended target with `jj diff --from e3eb7e819de5`.
     Discard the conflicting changes with `jj restore --from e3eb7e819de5`.
     [EOF]
@@ -389,9 +390,9 @@ fn test_conflict_marker_length_stored_in_working_copy() {
     // Working copy should contain conflict marker length
     let stdout = test_env.jj_cmd_success(&repo_path, &["debug", "local-working-copy"]);
     insta::assert_snapshot!(redact_output(&stdout), @r#"
-    Current operation: OperationId("6feb53603f9f7324085d2d89dca19a6dac93fef6795cfd5d57090ff803d404ab1196b45d5b97faa641f6a78302ac0fbd149f5e5a880d1fd64d6520c31beab213")
+    Current operation: OperationId("a39e48926238bd0f753b43bce60a7d6617e23035b161746214402319c4813695c56760e20515899a96c323a12acc187b129949accc97c5585f961e5fdf05745b")
     Current tree: Merge(Conflicted([TreeId("381273b50cf73f8c81b3f1502ee89e9bbd6c1518"), TreeId("771f3d31c4588ea40a8864b2a981749888e596c2"), TreeId("f56b8223da0dab22b03b8323ced4946329aeb4e0")]))
-    Normal { <executable> }           249 <timestamp> Some(MaterializedConflictData { conflict_marker_len: 11 }) "file"
+    Normal { <executable> }           249 <timestamp> Some(MaterializedConflictData { conflict_marker_len: 11, binary_side: None }) "file"
     [EOF]
     "#);
 
@@ -429,6 +430,10 @@ fn test_conflict_marker_length_stored_in_working_copy() {
     Parent commit: zsuskuln 7b2b03ab side-b
     Warning: There are unresolved conflicts at these paths:
     file    2-sided conflict
+    Conflicting changes to file come from:
+      rlvkpnrz ce613b49 side-a
+      zsuskuln 7b2b03ab side-b
+    Hint: To resolve the conflicts, use `jj resolve`, or edit the conflict markers in the file directly.
     [EOF]
     ");
     insta::assert_snapshot!(test_env.jj_cmd_success(&repo_path, &["diff", "--git"]), @r"
@@ -453,9 +458,9 @@ fn test_conflict_marker_length_stored_in_working_copy() {
     // Working copy should still contain conflict marker length
     let stdout = test_env.jj_cmd_success(&repo_path, &["debug", "local-working-copy"]);
     insta::assert_snapshot!(redact_output(&stdout), @r#"
-    Current operation: OperationId("205bc702428a522e0b175938a51c51b59741c854a609ba63c89de76ffda6e5eff6fcc00725328b1a91f448401769773cefcff01fac3448190d2cea4e137d2166")
+    Current operation: OperationId("7c1eed7445d87d08ad31f688bc957e2e69431c456fa1a28c144f7e3c4fda88bfb8d138256b54078e88ffaa63a696885ff561fa91dfab5ba942afd7f8cce1f43d")
     Current tree: Merge(Conflicted([TreeId("381273b50cf73f8c81b3f1502ee89e9bbd6c1518"), TreeId("771f3d31c4588ea40a8864b2a981749888e596c2"), TreeId("3329c18c95f7b7a55c278c2259e9c4ce711fae59")]))
-    Normal { <executable> }           289 <timestamp> Some(MaterializedConflictData { conflict_marker_len: 11 }) "file"
+    Normal { <executable> }           289 <timestamp> Some(MaterializedConflictData { conflict_marker_len: 11, binary_side: None }) "file"
     [EOF]
     "#);
 
@@ -489,9 +494,206 @@ fn test_conflict_marker_length_stored_in_working_copy() {
     // working copy
     let stdout = test_env.jj_cmd_success(&repo_path, &["debug", "local-working-copy"]);
     insta::assert_snapshot!(redact_output(&stdout), @r#"
-    Current operation: OperationId("2206ce3c108b1573df0841138c226bba1ab3cff900a5899ed31ac69162c7d6f30d37fb5ab43da60dba88047b8ab22d453887fff688f26dfcf04f2c99420a5563")
+    Current operation: OperationId("75af73b7685fa77b2663d2240e5efdf0dd11733b3c38cad01ce0e943c26cbf2400eb9e84fa10192e9e44b3c96d5c0106e22a65557887a837f2ac9f239d15dd3c")
     Current tree: Merge(Resolved(TreeId("6120567b3cb2472d549753ed3e4b84183d52a650")))
     Normal { <executable> }           130 <timestamp> None "file"
     [EOF]
     "#);
 }
+
+#[test]
+fn test_conflict_marker_length_from_gitattributes() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(
+        repo_path.join(".gitattributes"),
+        "file conflict-marker-size=15\n",
+    )
+    .unwrap();
+    let conflict_file = repo_path.join("file");
+    std::fs::write(&conflict_file, "line 1\nline 2\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m", "base"]);
+    std::fs::write(&conflict_file, "line 1\nline 2 - left\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m", "side-a"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "description(base)", "-m", "side-b"]);
+    std::fs::write(&conflict_file, "line 1\nline 2 - right\n").unwrap();
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &["new", "description(side-a)", "description(side-b)"],
+    );
+
+    // The markers have the length set by the attribute
+    insta::assert_snapshot!(std::fs::read_to_string(&conflict_file).unwrap(), @r"
+    line 1
+    <<<<<<<<<<<<<<< Conflict 1 of 1
+    %%%%%%%%%%%%%%% Changes from base to side #1
+    -line 2
+    +line 2 - left
+    +++++++++++++++ Contents of side #2
+    line 2 - right
+    >>>>>>>>>>>>>>> Conflict 1 of 1 ends
+    ");
+
+    // Shorter marker-like lines are parsed as contents when snapshotting
+    std::fs::write(
+        &conflict_file,
+        indoc! {"
+            line 1
+            <<<<<<<<<<<<<<< Conflict 1 of 1
+            %%%%%%%%%%%%%%% Changes from base to side #1
+            -line 2
+            +line 2 - left
+            +++++++++++++++ Contents of side #2
+            <<<<<<< fake marker
+            line 2 - right
+            >>>>>>> fake marker
+            >>>>>>>>>>>>>>> Conflict 1 of 1 ends
+        "},
+    )
+    .unwrap();
+    let stdout = test_env.jj_cmd_success(&repo_path, &["resolve", "--list"]);
+    insta::assert_snapshot!(stdout, @r"
+    file    2-sided conflict
+    [EOF]
+    ");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_check_out_symlinks_as_files() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file"), "contents").unwrap();
+    std::os::unix::fs::symlink("file", repo_path.join("symlink")).unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m=symlink"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "root()"]);
+
+    // Symlinks are written as regular files when disabled
+    let (_stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "new",
+            "description(symlink)",
+            "--config=working-copy.symlinks=false",
+        ],
+    );
+    assert!(stderr
+        .raw()
+        .contains("Warning: 1 symlink was written as a regular file containing the link target."));
+    let metadata = repo_path.join("symlink").symlink_metadata().unwrap();
+    assert!(metadata.is_file());
+    assert_eq!(
+        std::fs::read_to_string(repo_path.join("symlink")).unwrap(),
+        "file"
+    );
+
+    // The file isn't reported as modified
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--summary"]);
+    insta::assert_snapshot!(stdout, @"");
+
+    // Changing the contents changes the symlink target
+    std::fs::write(repo_path.join("symlink"), "other").unwrap();
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--git"]);
+    insta::assert_snapshot!(stdout, @r"
+    diff --git a/symlink b/symlink
+    index 1a010b1c0f..27fa34919a 120000
+    --- a/symlink
+    +++ b/symlink
+    @@ -1,1 +1,1 @@
+    -file
+    \ No newline at end of file
+    +other
+    \ No newline at end of file
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_eol_conversion() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.add_config(r#"working-copy.eol-conversion = "input-output""#);
+
+    std::fs::write(repo_path.join("crlf"), "a\r\nb\r\n").unwrap();
+    std::fs::write(repo_path.join("mixed"), "a\r\nb\n").unwrap();
+
+    // CRLF is converted to LF in the repo, files with mixed line endings are
+    // left alone
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["file", "show", "crlf"]);
+    insta::assert_snapshot!(stdout, @r"
+    a
+    b
+    [EOF]
+    ");
+    insta::assert_snapshot!(stderr, @r"
+    Warning: Line endings of some files weren't converted because they contain both LF and CRLF:
+      mixed
+    [EOF]
+    ");
+    let (stdout, _stderr) = test_env.jj_cmd_ok(&repo_path, &["file", "show", "mixed"]);
+    assert_eq!(stdout.raw(), "a\r\nb\n");
+
+    // LF is converted to CRLF on checkout, and the file isn't reported as
+    // modified
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m=files"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "root()"]);
+    assert!(!repo_path.join("crlf").exists());
+    test_env.jj_cmd_ok(&repo_path, &["new", "description(files)"]);
+    assert_eq!(
+        std::fs::read(repo_path.join("crlf")).unwrap(),
+        b"a\r\nb\r\n"
+    );
+    let (stdout, _stderr) = test_env.jj_cmd_ok(&repo_path, &["diff", "--summary"]);
+    insta::assert_snapshot!(stdout, @"");
+
+    // Conversion on checkout can be disabled per command
+    test_env.jj_cmd_ok(&repo_path, &["new", "root()"]);
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "new",
+            "description(files)",
+            "--config=working-copy.eol-conversion='input'",
+        ],
+    );
+    assert_eq!(std::fs::read(repo_path.join("crlf")).unwrap(), b"a\nb\n");
+}
+
+#[test]
+fn test_clone_mode_hardlink_from_cache() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.add_config(r#"working-copy.clone-mode = "hardlink-from-cache""#);
+
+    std::fs::write(repo_path.join("file"), "contents\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m=file"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "root()"]);
+    assert!(!repo_path.join("file").exists());
+    test_env.jj_cmd_ok(&repo_path, &["new", "description(file)"]);
+    assert_eq!(
+        std::fs::read_to_string(repo_path.join("file")).unwrap(),
+        "contents\n"
+    );
+    let (stdout, _stderr) = test_env.jj_cmd_ok(&repo_path, &["diff", "--summary"]);
+    insta::assert_snapshot!(stdout, @"");
+
+    // Replacing the file doesn't affect the cached copy
+    std::fs::remove_file(repo_path.join("file")).unwrap();
+    std::fs::write(repo_path.join("file"), "modified\n").unwrap();
+    let (stdout, _stderr) = test_env.jj_cmd_ok(&repo_path, &["diff", "--summary"]);
+    insta::assert_snapshot!(stdout, @r"
+    M file
+    [EOF]
+    ");
+    test_env.jj_cmd_ok(&repo_path, &["new", "description(file)"]);
+    assert_eq!(
+        std::fs::read_to_string(repo_path.join("file")).unwrap(),
+        "contents\n"
+    );
+}
@@ -14,6 +14,7 @@
 
 use std::path::Path;
 
+use itertools::Itertools as _;
 use test_case::test_case;
 
 use crate::common::CommandOutput;
@@ -31,8 +32,8 @@ fn test_workspaces_add_second_workspace() {
     test_env.jj_cmd_ok(&main_path, &["commit", "-m", "initial"]);
 
     let stdout = test_env.jj_cmd_success(&main_path, &["workspace", "list"]);
-    insta::assert_snapshot!(stdout, @r"
-    default: rlvkpnrz 8183d0fc (empty) (no description set)
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    default: rlvkpnrz 8183d0fc (empty) (no description set) $TEST_ENV/main
     [EOF]
     ");
 
@@ -53,7 +54,7 @@ fn test_workspaces_add_second_workspace() {
     // node in the graph indicates the current workspace's working-copy commit.
     insta::assert_snapshot!(get_log_output(&test_env, &main_path), @r"
     @  8183d0fcaa4c default@
-    │ ○  5ed2222c28e2 second@
+    │ ◎  5ed2222c28e2 second@
     ├─╯
     ○  751b12b7b981
     ◆  000000000000
@@ -61,7 +62,7 @@ fn test_workspaces_add_second_workspace() {
     ");
     insta::assert_snapshot!(get_log_output(&test_env, &secondary_path), @r"
     @  5ed2222c28e2 second@
-    │ ○  8183d0fcaa4c default@
+    │ ◎  8183d0fcaa4c default@
     ├─╯
     ○  751b12b7b981
     ◆  000000000000
@@ -70,9 +71,9 @@ fn test_workspaces_add_second_workspace() {
 
     // Both workspaces show up when we list them
     let stdout = test_env.jj_cmd_success(&main_path, &["workspace", "list"]);
-    insta::assert_snapshot!(stdout, @r"
-    default: rlvkpnrz 8183d0fc (empty) (no description set)
-    second: rzvqmyuk 5ed2222c (empty) (no description set)
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    default: rlvkpnrz 8183d0fc (empty) (no description set) $TEST_ENV/main
+    second: rzvqmyuk 5ed2222c (empty) (no description set) $TEST_ENV/secondary
     [EOF]
     ");
 }
@@ -132,6 +133,55 @@ fn test_workspaces_sparse_patterns() {
     insta::assert_snapshot!(stdout, @"");
 }
 
+/// Test that only the files matching the sparse patterns are checked out in a
+/// new workspace
+#[test]
+fn test_workspaces_sparse_patterns_with_revision() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "ws1"]);
+    let ws1_path = test_env.env_root().join("ws1");
+    let ws2_path = test_env.env_root().join("ws2");
+    let ws3_path = test_env.env_root().join("ws3");
+    let list_files = |path: &Path| {
+        let mut names = std::fs::read_dir(path)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .filter(|name| name != ".jj")
+            .collect_vec();
+        names.sort();
+        names
+    };
+
+    std::fs::create_dir(ws1_path.join("foo")).unwrap();
+    std::fs::create_dir(ws1_path.join("bar")).unwrap();
+    std::fs::write(ws1_path.join("foo/file"), "").unwrap();
+    std::fs::write(ws1_path.join("bar/file"), "").unwrap();
+    test_env.jj_cmd_ok(&ws1_path, &["commit", "-m", "add files"]);
+    test_env.jj_cmd_ok(&ws1_path, &["sparse", "set", "--clear", "--add=foo"]);
+
+    test_env.jj_cmd_ok(&ws1_path, &["workspace", "add", "-r@-", "../ws2"]);
+    let stdout = test_env.jj_cmd_success(&ws2_path, &["sparse", "list"]);
+    insta::assert_snapshot!(stdout, @r"
+    foo
+    [EOF]
+    ");
+    assert_eq!(list_files(&ws2_path), ["foo"]);
+
+    test_env.jj_cmd_ok(
+        &ws1_path,
+        &[
+            "workspace",
+            "add",
+            "--sparse-patterns=empty",
+            "-r@-",
+            "../ws3",
+        ],
+    );
+    let stdout = test_env.jj_cmd_success(&ws3_path, &["sparse", "list"]);
+    insta::assert_snapshot!(stdout, @"");
+    assert!(list_files(&ws3_path).is_empty());
+}
+
 /// Test adding a second workspace while the current workspace is editing a
 /// merge
 #[test]
@@ -145,8 +195,8 @@ fn test_workspaces_add_second_workspace_on_merge() {
     test_env.jj_cmd_ok(&main_path, &["new", "all:@-+", "-m=merge"]);
 
     let stdout = test_env.jj_cmd_success(&main_path, &["workspace", "list"]);
-    insta::assert_snapshot!(stdout, @r"
-    default: zsuskuln 35e47bff (empty) merge
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    default: zsuskuln 35e47bff (empty) merge $TEST_ENV/main
     [EOF]
     ");
 
@@ -159,7 +209,7 @@ fn test_workspaces_add_second_workspace_on_merge() {
     insta::assert_snapshot!(get_log_output(&test_env, &main_path), @r"
     @    35e47bff781e default@
     ├─╮
-    │ │ ○  7013a493bd09 second@
+    │ │ ◎  7013a493bd09 second@
     ╭─┬─╯
     │ ○  444b77e99d43
     ○ │  1694f2ddf8ec
@@ -276,8 +326,8 @@ fn test_workspaces_add_workspace_at_revision() {
     test_env.jj_cmd_ok(&main_path, &["commit", "-m", "second"]);
 
     let stdout = test_env.jj_cmd_success(&main_path, &["workspace", "list"]);
-    insta::assert_snapshot!(stdout, @r"
-    default: kkmpptxz dadeedb4 (empty) (no description set)
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    default: kkmpptxz dadeedb4 (empty) (no description set) $TEST_ENV/main
     [EOF]
     ");
 
@@ -306,7 +356,7 @@ fn test_workspaces_add_workspace_at_revision() {
     insta::assert_snapshot!(get_log_output(&test_env, &main_path), @r"
     @  dadeedb493e8 default@
     ○  c420244c6398
-    │ ○  e374e74aa0c8 second@
+    │ ◎  e374e74aa0c8 second@
     ├─╯
     ○  f6097c2f7cac
     ◆  000000000000
@@ -314,7 +364,7 @@ fn test_workspaces_add_workspace_at_revision() {
     ");
     insta::assert_snapshot!(get_log_output(&test_env, &secondary_path), @r"
     @  e374e74aa0c8 second@
-    │ ○  dadeedb493e8 default@
+    │ ◎  dadeedb493e8 default@
     │ ○  c420244c6398
     ├─╯
     ○  f6097c2f7cac
@@ -379,7 +429,7 @@ fn test_workspaces_add_workspace_multiple_revisions() {
 
     insta::assert_snapshot!(get_log_output(&test_env, &main_path), @r"
     @  5b36783cd11c default@
-    │ ○      f4fa64f40944 merge@
+    │ ◎      f4fa64f40944 merge@
     │ ├─┬─╮
     │ │ │ ○  f6097c2f7cac
     ├─────╯
@@ -392,6 +442,123 @@ fn test_workspaces_add_workspace_multiple_revisions() {
     ");
 }
 
+/// Test that conflicts between the parents are materialized in a new workspace
+/// created on multiple revisions.
+#[test]
+fn test_workspaces_add_workspace_multiple_revisions_conflict() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "main"]);
+    let main_path = test_env.env_root().join("main");
+    let merged_path = test_env.env_root().join("merged");
+
+    std::fs::write(main_path.join("file"), "base\n").unwrap();
+    test_env.jj_cmd_ok(&main_path, &["commit", "-m", "base"]);
+    std::fs::write(main_path.join("file"), "a\n").unwrap();
+    test_env.jj_cmd_ok(&main_path, &["commit", "-m", "a"]);
+    test_env.jj_cmd_ok(&main_path, &["new", "description(base)"]);
+    std::fs::write(main_path.join("file"), "b\n").unwrap();
+    test_env.jj_cmd_ok(&main_path, &["commit", "-m", "b"]);
+
+    test_env.jj_cmd_ok(
+        &main_path,
+        &[
+            "workspace",
+            "add",
+            "../merged",
+            r#"-r=description(exact:"a\n")"#,
+            r#"-r=description(exact:"b\n")"#,
+        ],
+    );
+    let stdout = test_env.jj_cmd_success(
+        &merged_path,
+        &["log", "--no-graph", "-rparents(@)", "-Tdescription"],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    b
+    a
+    [EOF]
+    ");
+    insta::assert_snapshot!(std::fs::read_to_string(merged_path.join("file")).unwrap(), @r"
+    <<<<<<< Conflict 1 of 1
+    %%%%%%% Changes from base to side #1
+    -base
+    +a
+    +++++++ Contents of side #2
+    b
+    >>>>>>> Conflict 1 of 1 ends
+    ");
+}
+
+/// Test creating a bookmark at the parent of the new working-copy commit.
+#[test]
+fn test_workspaces_add_workspace_with_bookmark() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "main"]);
+    let main_path = test_env.env_root().join("main");
+    let secondary_path = test_env.env_root().join("secondary");
+
+    test_env.jj_cmd_ok(&main_path, &["commit", "-m", "first"]);
+    test_env.jj_cmd_ok(&main_path, &["commit", "-m", "second"]);
+    test_env.jj_cmd_ok(&main_path, &["bookmark", "create", "-r@-", "existing"]);
+
+    test_env.jj_cmd_ok(
+        &main_path,
+        &["workspace", "add", "-b=feature", "-r=@--", "../secondary"],
+    );
+    let stdout = test_env.jj_cmd_success(
+        &secondary_path,
+        &["log", "--no-graph", "-rfeature", "-Tdescription"],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    first
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(
+        &secondary_path,
+        &["log", "--no-graph", "-rparents(@)", "-Tbookmarks"],
+    );
+    insta::assert_snapshot!(stdout, @"feature[EOF]");
+
+    let stderr =
+        test_env.jj_cmd_failure(&main_path, &["workspace", "add", "-b=existing", "../third"]);
+    insta::assert_snapshot!(stderr, @r"
+    Error: Bookmark already exists: existing
+    [EOF]
+    ");
+    let stderr = test_env.jj_cmd_failure(
+        &main_path,
+        &[
+            "workspace",
+            "add",
+            "-b=merge",
+            "-r=@-",
+            "-r=@--",
+            "../third",
+        ],
+    );
+    insta::assert_snapshot!(stderr, @r"
+    Error: Cannot create a bookmark for a working-copy commit with multiple parents
+    [EOF]
+    ");
+    let stderr = test_env.jj_cmd_cli_error(
+        &main_path,
+        &["workspace", "add", "-b=invalid name", "../third"],
+    );
+    insta::assert_snapshot!(stderr, @r"
+    error: invalid value 'invalid name' for '--bookmark <BOOKMARK>':  --> 1:8
+      |
+    1 | invalid name
+      |        ^---
+      |
+      = expected <EOI>
+
+    For more information, try '--help'.
+    Hint: See https://jj-vcs.github.io/jj/latest/revsets/ for revsets syntax, or for how to quote symbols.
+    [EOF]
+    ");
+    assert!(!test_env.env_root().join("third").exists());
+}
+
 #[test]
 fn test_workspaces_add_workspace_from_subdir() {
     let test_env = TestEnvironment::default();
@@ -405,8 +572,8 @@ fn test_workspaces_add_workspace_from_subdir() {
     test_env.jj_cmd_ok(&main_path, &["commit", "-m", "initial"]);
 
     let stdout = test_env.jj_cmd_success(&main_path, &["workspace", "list"]);
-    insta::assert_snapshot!(stdout, @r"
-    default: rlvkpnrz e1038e77 (empty) (no description set)
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    default: rlvkpnrz e1038e77 (empty) (no description set) $TEST_ENV/main
     [EOF]
     ");
 
@@ -424,9 +591,9 @@ fn test_workspaces_add_workspace_from_subdir() {
 
     // Both workspaces show up when we list them
     let stdout = test_env.jj_cmd_success(&secondary_path, &["workspace", "list"]);
-    insta::assert_snapshot!(stdout, @r"
-    default: rlvkpnrz e1038e77 (empty) (no description set)
-    secondary: rzvqmyuk 7ad84461 (empty) (no description set)
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    default: rlvkpnrz e1038e77 (empty) (no description set) $TEST_ENV/main
+    secondary: rzvqmyuk 7ad84461 (empty) (no description set) $TEST_ENV/secondary
     [EOF]
     ");
 }
@@ -454,9 +621,9 @@ fn test_workspaces_add_workspace_in_current_workspace() {
 
     // Workspace created despite warning
     let stdout = test_env.jj_cmd_success(&main_path, &["workspace", "list"]);
-    insta::assert_snapshot!(stdout, @r"
-    default: rlvkpnrz 46d9ba8b (no description set)
-    secondary: pmmvwywv 0a77a39d (empty) (no description set)
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    default: rlvkpnrz 46d9ba8b (no description set) $TEST_ENV/main
+    secondary: pmmvwywv 0a77a39d (empty) (no description set) $TEST_ENV/main/secondary
     [EOF]
     ");
 
@@ -473,10 +640,10 @@ fn test_workspaces_add_workspace_in_current_workspace() {
 
     // Both workspaces created
     let stdout = test_env.jj_cmd_success(&main_path, &["workspace", "list"]);
-    insta::assert_snapshot!(stdout, @r"
-    default: rlvkpnrz 477c647f (no description set)
-    secondary: pmmvwywv 0a77a39d (empty) (no description set)
-    third: zxsnswpr 64746d4b (empty) (no description set)
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    default: rlvkpnrz 477c647f (no description set) $TEST_ENV/main
+    secondary: pmmvwywv 0a77a39d (empty) (no description set) $TEST_ENV/main/secondary
+    third: zxsnswpr 64746d4b (empty) (no description set) $TEST_ENV/main/third
     [EOF]
     ");
 
@@ -507,7 +674,7 @@ fn test_workspaces_conflicting_edits() {
 
     insta::assert_snapshot!(get_log_output(&test_env, &main_path), @r"
     @  06b57f44a3ca default@
-    │ ○  3224de8ae048 secondary@
+    │ ◎  3224de8ae048 secondary@
     ├─╯
     ○  506f4ec3c2c6
     ◆  000000000000
@@ -531,15 +698,16 @@ fn test_workspaces_conflicting_edits() {
     // The secondary workspace's working-copy commit was updated
     insta::assert_snapshot!(get_log_output(&test_env, &main_path), @r"
     @  a58c9a9b19ce default@
-    │ ○  e82cd4ee8faa secondary@
+    │ ◎  e82cd4ee8faa secondary@
     ├─╯
     ○  d41244767d45
     ◆  000000000000
     [EOF]
     ");
+    test_env.add_config("workspace.auto-update-stale = false");
     let stderr = test_env.jj_cmd_failure(&secondary_path, &["st"]);
     insta::assert_snapshot!(stderr, @r"
-    Error: The working copy is stale (not updated since operation c81af45155a2).
+    Error: The working copy is stale (not updated since operation 6132894f151c).
     Hint: Run `jj workspace update-stale` to update it.
     See https://jj-vcs.github.io/jj/latest/working-copy/#stale-working-copy for more information.
     [EOF]
@@ -547,7 +715,7 @@ fn test_workspaces_conflicting_edits() {
     // Same error on second run, and from another command
     let stderr = test_env.jj_cmd_failure(&secondary_path, &["log"]);
     insta::assert_snapshot!(stderr, @r"
-    Error: The working copy is stale (not updated since operation c81af45155a2).
+    Error: The working copy is stale (not updated since operation 6132894f151c).
     Hint: Run `jj workspace update-stale` to update it.
     See https://jj-vcs.github.io/jj/latest/working-copy/#stale-working-copy for more information.
     [EOF]
@@ -570,7 +738,7 @@ fn test_workspaces_conflicting_edits() {
     @  e82cd4ee8faa secondary@ (divergent)
     │ ×  30816012e0da (divergent)
     ├─╯
-    │ ○  a58c9a9b19ce default@
+    │ ◎  a58c9a9b19ce default@
     ├─╯
     ○  d41244767d45
     ◆  000000000000
@@ -581,7 +749,7 @@ fn test_workspaces_conflicting_edits() {
     @  e82cd4ee8faa secondary@ (divergent)
     │ ×  30816012e0da (divergent)
     ├─╯
-    │ ○  a58c9a9b19ce default@
+    │ ◎  a58c9a9b19ce default@
     ├─╯
     ○  d41244767d45
     ◆  000000000000
@@ -604,7 +772,7 @@ fn test_workspaces_updated_by_other() {
 
     insta::assert_snapshot!(get_log_output(&test_env, &main_path), @r"
     @  06b57f44a3ca default@
-    │ ○  3224de8ae048 secondary@
+    │ ◎  3224de8ae048 secondary@
     ├─╯
     ○  506f4ec3c2c6
     ◆  000000000000
@@ -625,15 +793,16 @@ fn test_workspaces_updated_by_other() {
     // The secondary workspace's working-copy commit was updated.
     insta::assert_snapshot!(get_log_output(&test_env, &main_path), @r"
     @  a58c9a9b19ce default@
-    │ ○  e82cd4ee8faa secondary@
+    │ ◎  e82cd4ee8faa secondary@
     ├─╯
     ○  d41244767d45
     ◆  000000000000
     [EOF]
     ");
+    test_env.add_config("workspace.auto-update-stale = false");
     let stderr = test_env.jj_cmd_failure(&secondary_path, &["st"]);
     insta::assert_snapshot!(stderr, @r"
-    Error: The working copy is stale (not updated since operation c81af45155a2).
+    Error: The working copy is stale (not updated since operation 6132894f151c).
     Hint: Run `jj workspace update-stale` to update it.
     See https://jj-vcs.github.io/jj/latest/working-copy/#stale-working-copy for more information.
     [EOF]
@@ -651,7 +820,7 @@ fn test_workspaces_updated_by_other() {
     insta::assert_snapshot!(get_log_output(&test_env, &secondary_path),
     @r"
     @  e82cd4ee8faa secondary@
-    │ ○  a58c9a9b19ce default@
+    │ ◎  a58c9a9b19ce default@
     ├─╯
     ○  d41244767d45
     ◆  000000000000
@@ -663,8 +832,6 @@ fn test_workspaces_updated_by_other() {
 #[test]
 fn test_workspaces_updated_by_other_automatic() {
     let test_env = TestEnvironment::default();
-    test_env.add_config("[snapshot]\nauto-update-stale = true\n");
-
     test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "main"]);
     let main_path = test_env.env_root().join("main");
     let secondary_path = test_env.env_root().join("secondary");
@@ -676,7 +843,7 @@ fn test_workspaces_updated_by_other_automatic() {
 
     insta::assert_snapshot!(get_log_output(&test_env, &main_path), @r"
     @  06b57f44a3ca default@
-    │ ○  3224de8ae048 secondary@
+    │ ◎  3224de8ae048 secondary@
     ├─╯
     ○  506f4ec3c2c6
     ◆  000000000000
@@ -697,7 +864,7 @@ fn test_workspaces_updated_by_other_automatic() {
     // The secondary workspace's working-copy commit was updated.
     insta::assert_snapshot!(get_log_output(&test_env, &main_path), @r"
     @  a58c9a9b19ce default@
-    │ ○  e82cd4ee8faa secondary@
+    │ ◎  e82cd4ee8faa secondary@
     ├─╯
     ○  d41244767d45
     ◆  000000000000
@@ -722,7 +889,7 @@ fn test_workspaces_updated_by_other_automatic() {
     insta::assert_snapshot!(get_log_output(&test_env, &secondary_path),
     @r"
     @  e82cd4ee8faa secondary@
-    │ ○  a58c9a9b19ce default@
+    │ ◎  a58c9a9b19ce default@
     ├─╯
     ○  d41244767d45
     ◆  000000000000
@@ -734,8 +901,8 @@ fn test_workspaces_updated_by_other_automatic() {
 #[test_case(true; "automatic")]
 fn test_workspaces_current_op_discarded_by_other(automatic: bool) {
     let test_env = TestEnvironment::default();
-    if automatic {
-        test_env.add_config("[snapshot]\nauto-update-stale = true\n");
+    if !automatic {
+        test_env.add_config("workspace.auto-update-stale = false");
     }
 
     // Use the local backend because GitBackend::gc() depends on the git CLI.
@@ -785,8 +952,8 @@ fn test_workspaces_current_op_discarded_by_other(automatic: bool) {
     );
     insta::allow_duplicates! {
         insta::assert_snapshot!(stdout, @r"
-        @  757bc1140b abandon commit 20dd439c4bd12c6ad56c187ac490bd0141804618f638dc5c4dc92ff9aecba20f152b23160db9dcf61beb31a5cb14091d9def5a36d11c9599cc4d2e5689236af1
-        ○  8d4abed655 create initial working-copy commit in workspace secondary
+        @  4c9cb120ef abandon commit 20dd439c4bd12c6ad56c187ac490bd0141804618f638dc5c4dc92ff9aecba20f152b23160db9dcf61beb31a5cb14091d9def5a36d11c9599cc4d2e5689236af1
+        ○  d336e2ba4d create initial working-copy commit in workspace secondary
         ○  3de27432e5 add workspace 'secondary'
         ○  bcf69de808 new empty commit
         ○  a36b99a15c snapshot working copy
@@ -805,7 +972,7 @@ fn test_workspaces_current_op_discarded_by_other(automatic: bool) {
     insta::allow_duplicates! {
         insta::assert_snapshot!(get_log_output(&test_env, &main_path), @r"
         @  6c051bd1ccd5 default@
-        │ ○  96b31dafdc41 secondary@
+        │ ◎  96b31dafdc41 secondary@
         ├─╯
         ○  7c5b25a4fc8f
         ◆  000000000000
@@ -828,7 +995,7 @@ fn test_workspaces_current_op_discarded_by_other(automatic: bool) {
         [EOF]
         ");
         insta::assert_snapshot!(stderr, @r"
-        Failed to read working copy's current operation; attempting recovery. Error message from read attempt: Object 8d4abed655badb70b1bab62aa87136619dbc3c8015a8ce8dfb7abfeca4e2f36c713d8f84e070a0613907a6cee7e1cc05323fe1205a319b93fe978f11a060c33c of type operation not found
+        Failed to read working copy's current operation; attempting recovery. Error message from read attempt: Object d336e2ba4d095c529546115c9176d71c4787967b7243e0334ff96abae91dd27f4b3e4e5a414338a8ed7726d17575f848ba11eeaef481b12205516a5cd892f980 of type operation not found
         Created and checked out recovery commit 76d0126b3e5c
         [EOF]
         ");
@@ -843,7 +1010,7 @@ fn test_workspaces_current_op_discarded_by_other(automatic: bool) {
 
         let (stdout, stderr) = test_env.jj_cmd_ok(&secondary_path, &["workspace", "update-stale"]);
         insta::assert_snapshot!(stderr, @r"
-        Failed to read working copy's current operation; attempting recovery. Error message from read attempt: Object 8d4abed655badb70b1bab62aa87136619dbc3c8015a8ce8dfb7abfeca4e2f36c713d8f84e070a0613907a6cee7e1cc05323fe1205a319b93fe978f11a060c33c of type operation not found
+        Failed to read working copy's current operation; attempting recovery. Error message from read attempt: Object d336e2ba4d095c529546115c9176d71c4787967b7243e0334ff96abae91dd27f4b3e4e5a414338a8ed7726d17575f848ba11eeaef481b12205516a5cd892f980 of type operation not found
         Created and checked out recovery commit 76d0126b3e5c
         [EOF]
         ");
@@ -853,7 +1020,7 @@ fn test_workspaces_current_op_discarded_by_other(automatic: bool) {
     insta::allow_duplicates! {
         insta::assert_snapshot!(get_log_output(&test_env, &main_path), @r"
         @  6c051bd1ccd5 default@
-        │ ○  15df8cb57d3f secondary@
+        │ ◎  15df8cb57d3f secondary@
         │ ○  96b31dafdc41
         ├─╯
         ○  7c5b25a4fc8f
@@ -969,7 +1136,7 @@ fn test_workspaces_update_stale_snapshot() {
 
     insta::assert_snapshot!(get_log_output(&test_env, &secondary_path), @r"
     @  e672fd8fefac secondary@
-    │ ○  ea37b073f5ab default@
+    │ ◎  ea37b073f5ab default@
     │ ○  b13c81dedc64
     ├─╯
     ○  e6e9989f1179
@@ -995,8 +1162,8 @@ fn test_workspaces_forget() {
 
     // When listing workspaces, only the secondary workspace shows up
     let stdout = test_env.jj_cmd_success(&main_path, &["workspace", "list"]);
-    insta::assert_snapshot!(stdout, @r"
-    secondary: pmmvwywv 18463f43 (empty) (no description set)
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    secondary: pmmvwywv 18463f43 (empty) (no description set) $TEST_ENV/secondary
     [EOF]
     ");
 
@@ -1060,25 +1227,25 @@ fn test_workspaces_forget_multi_transaction() {
 
     // there should be three workspaces
     let stdout = test_env.jj_cmd_success(&main_path, &["workspace", "list"]);
-    insta::assert_snapshot!(stdout, @r"
-    default: rlvkpnrz 909d51b1 (empty) (no description set)
-    second: pmmvwywv 18463f43 (empty) (no description set)
-    third: rzvqmyuk cc383fa2 (empty) (no description set)
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    default: rlvkpnrz 909d51b1 (empty) (no description set) $TEST_ENV/main
+    second: pmmvwywv 18463f43 (empty) (no description set) $TEST_ENV/second
+    third: rzvqmyuk cc383fa2 (empty) (no description set) $TEST_ENV/third
     [EOF]
     ");
 
     // delete two at once, in a single tx
     test_env.jj_cmd_ok(&main_path, &["workspace", "forget", "second", "third"]);
     let stdout = test_env.jj_cmd_success(&main_path, &["workspace", "list"]);
-    insta::assert_snapshot!(stdout, @r"
-    default: rlvkpnrz 909d51b1 (empty) (no description set)
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    default: rlvkpnrz 909d51b1 (empty) (no description set) $TEST_ENV/main
     [EOF]
     ");
 
     // the op log should have multiple workspaces forgotten in a single tx
     let stdout = test_env.jj_cmd_success(&main_path, &["op", "log", "--limit", "1"]);
     insta::assert_snapshot!(stdout, @r"
-    @  60b2b5a71a84 test-username@host.example.com 2001-02-03 04:05:12.000 +07:00 - 2001-02-03 04:05:12.000 +07:00
+    @  bdb51e744b50 test-username@host.example.com 2001-02-03 04:05:12.000 +07:00 - 2001-02-03 04:05:12.000 +07:00
     │  forget workspaces second, third
     │  args: jj workspace forget second third
     [EOF]
@@ -1089,10 +1256,10 @@ fn test_workspaces_forget_multi_transaction() {
 
     // finally, there should be three workspaces at the end
     let stdout = test_env.jj_cmd_success(&main_path, &["workspace", "list"]);
-    insta::assert_snapshot!(stdout, @r"
-    default: rlvkpnrz 909d51b1 (empty) (no description set)
-    second: pmmvwywv 18463f43 (empty) (no description set)
-    third: rzvqmyuk cc383fa2 (empty) (no description set)
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    default: rlvkpnrz 909d51b1 (empty) (no description set) $TEST_ENV/main
+    second: pmmvwywv 18463f43 (empty) (no description set) $TEST_ENV/second
+    third: rzvqmyuk cc383fa2 (empty) (no description set) $TEST_ENV/third
     [EOF]
     ");
 }
@@ -1115,16 +1282,16 @@ fn test_workspaces_forget_abandon_commits() {
 
     // there should be four workspaces, three of which are at the same empty commit
     let stdout = test_env.jj_cmd_success(&main_path, &["workspace", "list"]);
-    insta::assert_snapshot!(stdout, @r"
-    default: qpvuntsm 4e8f9d2b (no description set)
-    fourth: uuqppmxq 57d63245 (empty) (no description set)
-    second: uuqppmxq 57d63245 (empty) (no description set)
-    third: uuqppmxq 57d63245 (empty) (no description set)
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    default: qpvuntsm 4e8f9d2b (no description set) $TEST_ENV/main
+    fourth: uuqppmxq 57d63245 (empty) (no description set) $TEST_ENV/fourth
+    second: uuqppmxq 57d63245 (empty) (no description set) $TEST_ENV/second
+    third: uuqppmxq 57d63245 (empty) (no description set) $TEST_ENV/third
     [EOF]
     ");
     insta::assert_snapshot!(get_log_output(&test_env, &main_path), @r"
     @  4e8f9d2be039 default@
-    │ ○  57d63245a308 fourth@ second@ third@
+    │ ◎  57d63245a308 fourth@ second@ third@
     ├─╯
     ◆  000000000000
     [EOF]
@@ -1133,7 +1300,7 @@ fn test_workspaces_forget_abandon_commits() {
     // delete the default workspace (should not abandon commit since not empty)
     test_env.jj_cmd_success(&main_path, &["workspace", "forget", "default"]);
     insta::assert_snapshot!(get_log_output(&test_env, &main_path), @r"
-    ○  57d63245a308 fourth@ second@ third@
+    ◎  57d63245a308 fourth@ second@ third@
     │ ○  4e8f9d2be039
     ├─╯
     ◆  000000000000
@@ -1144,7 +1311,7 @@ fn test_workspaces_forget_abandon_commits() {
     // still have commit checked out)
     test_env.jj_cmd_success(&main_path, &["workspace", "forget", "second"]);
     insta::assert_snapshot!(get_log_output(&test_env, &main_path), @r"
-    ○  57d63245a308 fourth@ third@
+    ◎  57d63245a308 fourth@ third@
     │ ○  4e8f9d2be039
     ├─╯
     ◆  000000000000
@@ -1161,6 +1328,121 @@ fn test_workspaces_forget_abandon_commits() {
     ");
 }
 
+/// Test forgetting workspaces by pattern and abandoning their working copies
+#[test]
+fn test_workspaces_forget_abandon_working_copy() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "main"]);
+    test_env.add_config(r#"templates.commit_summary = "description.first_line()""#);
+    let main_path = test_env.env_root().join("main");
+    let keep_path = test_env.env_root().join("keep");
+    let get_log = || {
+        test_env.jj_cmd_success(
+            &main_path,
+            &[
+                "log",
+                "--no-graph",
+                "-r=all() ~ root()",
+                "-T",
+                r#"separate(" ", description.first_line(), working_copies) ++ "\n""#,
+            ],
+        )
+    };
+
+    for name in ["scratch1", "scratch2", "keep"] {
+        test_env.jj_cmd_ok(&main_path, &["workspace", "add", &format!("../{name}")]);
+    }
+    // Described working-copy commits aren't abandoned by default
+    test_env.jj_cmd_ok(&main_path, &["describe", "-r=scratch1@", "-m=scratch 1"]);
+    test_env.jj_cmd_ok(&main_path, &["describe", "-r=scratch2@", "-m=scratch 2"]);
+    test_env.jj_cmd_ok(&main_path, &["describe", "-r=keep@", "-m=keep"]);
+    insta::assert_snapshot!(get_log(), @r"
+    keep keep@
+    scratch 2 scratch2@
+    scratch 1 scratch1@
+    default@
+    [EOF]
+    ");
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &main_path,
+        &[
+            "workspace",
+            "forget",
+            "--abandon-working-copy",
+            "glob:scratch*",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r"
+    Abandoned working-copy commits:
+      scratch 1
+      scratch 2
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log(), @r"
+    keep keep@
+    default@
+    [EOF]
+    ");
+
+    // Working-copy commits with changes aren't abandoned unless forced
+    std::fs::write(keep_path.join("file"), "contents").unwrap();
+    test_env.jj_cmd_ok(&keep_path, &["st"]);
+    let stderr = test_env.jj_cmd_failure(
+        &main_path,
+        &["workspace", "forget", "--abandon-working-copy", "keep"],
+    );
+    insta::assert_snapshot!(stderr, @r"
+    Error: Refusing to abandon the working-copy commit of workspace keep because it has changes
+    Hint: Use --force to abandon it anyway.
+    [EOF]
+    ");
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &main_path,
+        &[
+            "workspace",
+            "forget",
+            "--abandon-working-copy",
+            "--force",
+            "keep",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r"
+    Abandoned working-copy commits:
+      keep
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log(), @"[EOF]");
+
+    // Working-copy commits with children aren't abandoned
+    test_env.jj_cmd_ok(&main_path, &["workspace", "add", "../parent"]);
+    test_env.jj_cmd_ok(&main_path, &["describe", "-r=parent@", "-m=parent"]);
+    test_env.jj_cmd_ok(&main_path, &["new", "parent@", "-m=child"]);
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &main_path,
+        &["workspace", "forget", "--abandon-working-copy", "parent"],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r"
+    Warning: Not abandoning the working-copy commit of workspace parent because it has children
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log(), @r"
+    child
+    parent
+    [EOF]
+    ");
+
+    // Patterns don't match the current workspace
+    let stderr = test_env.jj_cmd_failure(&main_path, &["workspace", "forget", "glob:*"]);
+    insta::assert_snapshot!(stderr, @r"
+    Error: No matching workspaces for patterns: *
+    [EOF]
+    ");
+}
+
 /// Test context of commit summary template
 #[test]
 fn test_list_workspaces_template() {
@@ -1184,20 +1466,154 @@ fn test_list_workspaces_template() {
 
     // "current_working_copy" should point to the workspace we operate on
     let stdout = test_env.jj_cmd_success(&main_path, &["workspace", "list"]);
-    insta::assert_snapshot!(stdout, @r"
-    default: 8183d0fcaa4c  (current)
-    second: 0a77a39d7d6f 
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    default: 8183d0fcaa4c  (current) $TEST_ENV/main
+    second: 0a77a39d7d6f  $TEST_ENV/secondary
     [EOF]
     ");
 
     let stdout = test_env.jj_cmd_success(&secondary_path, &["workspace", "list"]);
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    default: 8183d0fcaa4c  $TEST_ENV/main
+    second: 0a77a39d7d6f  (current) $TEST_ENV/secondary
+    [EOF]
+    ");
+}
+
+/// Test listing the root paths and staleness of workspaces
+#[test]
+fn test_list_workspaces_stale_and_root() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "main"]);
+    let main_path = test_env.env_root().join("main");
+    let secondary_path = test_env.env_root().join("secondary");
+
+    std::fs::write(main_path.join("file"), "contents\n").unwrap();
+    test_env.jj_cmd_ok(&main_path, &["new"]);
+
+    test_env.jj_cmd_ok(&main_path, &["workspace", "add", "../secondary"]);
+
+    insta::assert_snapshot!(get_log_output(&test_env, &main_path), @r"
+    @  06b57f44a3ca default@
+    │ ◎  3224de8ae048 secondary@
+    ├─╯
+    ○  506f4ec3c2c6
+    ◆  000000000000
+    [EOF]
+    ");
+
+    // Rewrite the check-out commit of the secondary workspace from the main
+    // workspace.
+    std::fs::write(main_path.join("file"), "changed in main\n").unwrap();
+    test_env.jj_cmd_ok(&main_path, &["squash"]);
+
+    let stdout = test_env.jj_cmd_success(&main_path, &["workspace", "list"]);
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    default: mzvwutvl a58c9a9b (empty) (no description set) $TEST_ENV/main
+    secondary: pmmvwywv e82cd4ee (empty) (no description set) (stale) $TEST_ENV/secondary
+    [EOF]
+    ");
+    let template =
+        r#"name ++ ": " ++ if(root, root, "<unknown>") ++ if(stale, " (stale)") ++ "\n""#;
+    let stdout = test_env.jj_cmd_success(&main_path, &["workspace", "list", "-T", template]);
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    default: $TEST_ENV/main
+    secondary: $TEST_ENV/secondary (stale)
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(
+        &main_path,
+        &[
+            "workspace",
+            "list",
+            "-T",
+            r#"name ++ ": " ++ target.commit_id().short() ++ "\n""#,
+        ],
+    );
     insta::assert_snapshot!(stdout, @r"
-    default: 8183d0fcaa4c 
-    second: 0a77a39d7d6f  (current)
+    default: a58c9a9b19ce
+    secondary: e82cd4ee8faa
+    [EOF]
+    ");
+
+    // The secondary workspace is no longer stale once updated
+    test_env.jj_cmd_ok(&secondary_path, &["st"]);
+    let stdout = test_env.jj_cmd_success(&main_path, &["workspace", "list", "-T", template]);
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    default: $TEST_ENV/main
+    secondary: $TEST_ENV/secondary
+    [EOF]
+    ");
+
+    // The root paths of other workspaces are unknown without the records
+    std::fs::remove_dir_all(main_path.join(".jj/repo/workspace_store")).unwrap();
+    let stdout = test_env.jj_cmd_success(&main_path, &["workspace", "list", "-T", template]);
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    default: $TEST_ENV/main
+    secondary: <unknown>
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(&secondary_path, &["workspace", "list", "-T", template]);
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    default: <unknown>
+    secondary: $TEST_ENV/secondary
     [EOF]
     ");
 }
 
+/// Test listing workspaces as JSON, including workspaces that have been moved
+#[test]
+fn test_list_workspaces_json() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "main"]);
+    let main_path = test_env.env_root().join("main");
+    let secondary_path = test_env.env_root().join("secondary");
+    let moved_path = test_env.env_root().join("moved");
+    test_env.jj_cmd_ok(&main_path, &["workspace", "add", "../secondary"]);
+
+    let list_workspaces = || {
+        let stdout = test_env.jj_cmd_success(&main_path, &["workspace", "list", "--json"]);
+        let entries: Vec<serde_json::Value> = serde_json::from_str(stdout.raw()).unwrap();
+        entries
+    };
+    let commit_id = |revision: &str| {
+        let stdout = test_env.jj_cmd_success(
+            &main_path,
+            &["log", "--no-graph", "-r", revision, "-Tcommit_id"],
+        );
+        serde_json::Value::from(stdout.into_raw())
+    };
+
+    let entries = list_workspaces();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0]["name"], "default");
+    assert_eq!(entries[0]["root"], main_path.to_str().unwrap());
+    assert_eq!(entries[0]["root_verified"], true);
+    assert_eq!(entries[0]["commit_id"], commit_id("default@"));
+    assert_eq!(entries[0]["stale"], false);
+    assert_eq!(entries[1]["name"], "secondary");
+    assert_eq!(entries[1]["root"], secondary_path.to_str().unwrap());
+    assert_eq!(entries[1]["root_verified"], true);
+    assert_eq!(entries[1]["commit_id"], commit_id("secondary@"));
+    assert_eq!(entries[1]["stale"], false);
+
+    // The recorded root path of a moved workspace can't be verified
+    std::fs::rename(&secondary_path, &moved_path).unwrap();
+    let entries = list_workspaces();
+    assert_eq!(entries[1]["name"], "secondary");
+    assert_eq!(entries[1]["root"], secondary_path.to_str().unwrap());
+    assert_eq!(entries[1]["root_verified"], false);
+    assert_eq!(entries[1]["stale"], serde_json::Value::Null);
+
+    // Running a command in the moved workspace updates the recorded root path
+    test_env.jj_cmd_ok(&moved_path, &["st"]);
+    let entries = list_workspaces();
+    assert_eq!(entries[1]["name"], "secondary");
+    assert_eq!(entries[1]["root"], moved_path.to_str().unwrap());
+    assert_eq!(entries[1]["root_verified"], true);
+    assert_eq!(entries[1]["stale"], false);
+}
+
 /// Test getting the workspace root from primary and secondary workspaces
 #[test]
 fn test_workspaces_root() {
@@ -1247,7 +1663,7 @@ fn test_debug_snapshot() {
     test_env.jj_cmd_ok(&repo_path, &["debug", "snapshot"]);
     let stdout = test_env.jj_cmd_success(&repo_path, &["op", "log"]);
     insta::assert_snapshot!(stdout, @r"
-    @  c55ebc67e3db test-username@host.example.com 2001-02-03 04:05:08.000 +07:00 - 2001-02-03 04:05:08.000 +07:00
+    @  c55ebc67e3db test-username@host.example.com 2001-02-03 04:05:08.000 +07:00 - 2001-02-03 04:05:08.000 +07:00 (snapshot)
     │  snapshot working copy
     │  args: jj debug snapshot
     ○  eac759b9ab75 test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
@@ -1261,7 +1677,7 @@ fn test_debug_snapshot() {
     @  c9a40b951848 test-username@host.example.com 2001-02-03 04:05:10.000 +07:00 - 2001-02-03 04:05:10.000 +07:00
     │  describe commit 4e8f9d2be039994f589b4e57ac5e9488703e604d
     │  args: jj describe -m initial
-    ○  c55ebc67e3db test-username@host.example.com 2001-02-03 04:05:08.000 +07:00 - 2001-02-03 04:05:08.000 +07:00
+    ○  c55ebc67e3db test-username@host.example.com 2001-02-03 04:05:08.000 +07:00 - 2001-02-03 04:05:08.000 +07:00 (snapshot)
     │  snapshot working copy
     │  args: jj debug snapshot
     ○  eac759b9ab75 test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
@@ -1332,9 +1748,9 @@ fn test_workspaces_rename_workspace() {
 
     // Both workspaces show up when we list them
     let stdout = test_env.jj_cmd_success(&main_path, &["workspace", "list"]);
-    insta::assert_snapshot!(stdout, @r"
-    default: qpvuntsm 230dd059 (empty) (no description set)
-    second: uuqppmxq 57d63245 (empty) (no description set)
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    default: qpvuntsm 230dd059 (empty) (no description set) $TEST_ENV/main
+    second: uuqppmxq 57d63245 (empty) (no description set) $TEST_ENV/secondary
     [EOF]
     ");
 
@@ -1342,29 +1758,118 @@ fn test_workspaces_rename_workspace() {
     insta::assert_snapshot!(stdout, @"");
 
     let stdout = test_env.jj_cmd_success(&main_path, &["workspace", "list"]);
-    insta::assert_snapshot!(stdout, @r"
-    default: qpvuntsm 230dd059 (empty) (no description set)
-    third: uuqppmxq 57d63245 (empty) (no description set)
+    insta::assert_snapshot!(stdout.normalize_backslash(), @r"
+    default: qpvuntsm 230dd059 (empty) (no description set) $TEST_ENV/main
+    third: uuqppmxq 57d63245 (empty) (no description set) $TEST_ENV/secondary
     [EOF]
     ");
 
     // Can see the working-copy commit in each workspace in the log output.
     insta::assert_snapshot!(get_log_output(&test_env, &main_path), @r"
     @  230dd059e1b0 default@
-    │ ○  57d63245a308 third@
+    │ ◎  57d63245a308 third@
     ├─╯
     ◆  000000000000
     [EOF]
     ");
     insta::assert_snapshot!(get_log_output(&test_env, &secondary_path), @r"
     @  57d63245a308 third@
-    │ ○  230dd059e1b0 default@
+    │ ◎  230dd059e1b0 default@
     ├─╯
     ◆  000000000000
     [EOF]
     ");
 }
 
+#[test]
+fn test_workspaces_rename_other_workspace() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "main"]);
+    let main_path = test_env.env_root().join("main");
+    test_env.jj_cmd_ok(
+        &main_path,
+        &["workspace", "add", "--name", "second", "../secondary"],
+    );
+    let secondary_path = test_env.env_root().join("secondary");
+
+    // The working copy of the other workspace is updated as its path is known
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &main_path,
+        &["workspace", "rename", "--workspace=second", "third"],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @"");
+    let stdout = test_env.jj_cmd_success(
+        &main_path,
+        &[
+            "log",
+            "--no-graph",
+            "-rthird@",
+            r#"-Tcommit_id.short() ++ "\n""#,
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r"
+    57d63245a308
+    [EOF]
+    ");
+    let stderr = test_env.jj_cmd_failure(&main_path, &["log", "-rsecond@"]);
+    insta::assert_snapshot!(stderr, @r"
+    Error: Workspace `second` doesn't have a working-copy commit
+    [EOF]
+    ");
+    insta::assert_snapshot!(get_log_output(&test_env, &secondary_path), @r"
+    @  57d63245a308 third@
+    │ ◎  230dd059e1b0 default@
+    ├─╯
+    ◆  000000000000
+    [EOF]
+    ");
+
+    // If the working copy can't be found, it picks up the new name when the
+    // workspace is renamed from there
+    let moved_path = test_env.env_root().join("moved");
+    std::fs::rename(&secondary_path, &moved_path).unwrap();
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &main_path,
+        &["workspace", "rename", "--workspace=third", "fourth"],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r"
+    Warning: The working copy of workspace 'fourth' still uses the old name
+    Hint: Run `jj workspace rename fourth` in that workspace to update it
+    [EOF]
+    ");
+    // Local edits are kept, and a rewritten working-copy commit is checked out
+    std::fs::write(moved_path.join("local"), "local\n").unwrap();
+    std::fs::write(main_path.join("file"), "contents\n").unwrap();
+    test_env.jj_cmd_ok(&main_path, &["squash", "--into=fourth@", "file"]);
+    let (stdout, stderr) = test_env.jj_cmd_ok(&moved_path, &["workspace", "rename", "fourth"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r"
+    Updated working copy to workspace name 'fourth'
+    Added 1 files, modified 0 files, removed 0 files
+    [EOF]
+    ");
+    let stdout = test_env.jj_cmd_success(&moved_path, &["status"]);
+    insta::assert_snapshot!(stdout, @r"
+    Working copy changes:
+    A file
+    A local
+    Working copy : uuqppmxq 41eb5385 (no description set)
+    Parent commit: zzzzzzzz 00000000 (empty) (no description set)
+    [EOF]
+    ");
+
+    let stderr = test_env.jj_cmd_failure(
+        &main_path,
+        &["workspace", "rename", "--workspace=unknown", "fifth"],
+    );
+    insta::assert_snapshot!(stderr, @r"
+    Error: No such workspace: unknown
+    [EOF]
+    ");
+}
+
 #[must_use]
 fn get_log_output(test_env: &TestEnvironment, cwd: &Path) -> CommandOutput {
     let template = r#"
@@ -0,0 +1,103 @@
+use criterion::criterion_group;
+use criterion::criterion_main;
+use criterion::BatchSize;
+use criterion::BenchmarkId;
+use criterion::Criterion;
+use futures::StreamExt as _;
+use itertools::Itertools as _;
+use jj_lib::matchers::EverythingMatcher;
+use jj_lib::merged_tree::MergedTree;
+use jj_lib::merged_tree::TreeDiffIterator;
+use jj_lib::merged_tree::TreeDiffStreamImpl;
+use jj_lib::repo::Repo as _;
+use jj_lib::repo_path::RepoPathBuf;
+use pollster::FutureExt as _;
+use testutils::create_single_tree;
+use testutils::TestRepo;
+use testutils::TestRepoBackend;
+
+/// Creates trees with directories nested 4 levels deep with a fan-out of 8,
+/// each with 12 files, and a file modified in every other directory.
+fn deep_trees(test_repo: &TestRepo) -> (MergedTree, MergedTree) {
+    let dirs = (0..4).fold(vec![String::new()], |parents, _| {
+        parents
+            .iter()
+            .flat_map(|parent| (0..8).map(move |i| format!("{parent}dir{i}/")))
+            .collect()
+    });
+    let paths = dirs
+        .iter()
+        .flat_map(|dir| {
+            (0..12).map(move |i| RepoPathBuf::from_internal_string(format!("{dir}file{i}")))
+        })
+        .collect_vec();
+    let left_contents = paths
+        .iter()
+        .map(|path| (path.as_ref(), "left"))
+        .collect_vec();
+    let right_contents = paths
+        .iter()
+        .enumerate()
+        .map(|(i, path)| (path.as_ref(), if i % 24 == 0 { "right" } else { "left" }))
+        .collect_vec();
+    let left_tree = create_single_tree(&test_repo.repo, &left_contents);
+    let right_tree = create_single_tree(&test_repo.repo, &right_contents);
+    (
+        MergedTree::resolved(left_tree),
+        MergedTree::resolved(right_tree),
+    )
+}
+
+fn bench_tree_diff(c: &mut Criterion) {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init_with_backend(TestRepoBackend::Git);
+    let (left_tree, right_tree) = deep_trees(&test_repo);
+    // Trees are read through a fresh store so they aren't served from its cache.
+    let load_trees = || {
+        let repo = test_repo
+            .env
+            .load_repo_at_head(&settings, test_repo.repo_path());
+        let store = repo.store();
+        let left = store.get_root_tree(&left_tree.id()).unwrap();
+        let right = store.get_root_tree(&right_tree.id()).unwrap();
+        (left, right)
+    };
+
+    let mut group = c.benchmark_group("bench_tree_diff");
+    group.sample_size(10);
+    group.bench_function("serial", |b| {
+        b.iter_batched(
+            load_trees,
+            |(left, right)| {
+                TreeDiffIterator::new(left.as_merge(), right.as_merge(), &EverythingMatcher).count()
+            },
+            BatchSize::PerIteration,
+        );
+    });
+    for max_concurrent_reads in [1, 4, 16] {
+        group.bench_with_input(
+            BenchmarkId::new("concurrent", max_concurrent_reads),
+            &max_concurrent_reads,
+            |b, &max_concurrent_reads| {
+                b.iter_batched(
+                    load_trees,
+                    |(left, right)| {
+                        TreeDiffStreamImpl::new(
+                            left.as_merge(),
+                            right.as_merge(),
+                            &EverythingMatcher,
+                            max_concurrent_reads,
+                        )
+                        .count()
+                        .block_on()
+                    },
+                    BatchSize::PerIteration,
+                );
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_tree_diff);
+criterion_main!(benches);
@@ -42,6 +42,7 @@ use crate::matchers::Matcher;
 use crate::merge::Merge;
 use crate::merged_tree::MergedTree;
 use crate::merged_tree::MergedTreeBuilder;
+use crate::op_store::AbandonReason;
 use crate::repo::MutableRepo;
 use crate::repo::Repo;
 use crate::repo_path::RepoPath;
@@ -293,6 +294,7 @@ pub fn absorb_hunks(
     let mut rewritten_source = None;
     let mut rewritten_destinations = Vec::new();
     let mut num_rebased = 0;
+    let mut source_abandoned = false;
     // Rewrite commits in topological order so that descendant commits wouldn't
     // be rewritten multiple times.
     repo.transform_descendants(selected_trees.keys().cloned().collect(), |rewriter| {
@@ -301,6 +303,7 @@ pub fn absorb_hunks(
             let commit_builder = rewriter.reparent();
             if commit_builder.is_discardable()? {
                 commit_builder.abandon();
+                source_abandoned = true;
             } else {
                 rewritten_source = Some(commit_builder.write()?);
                 num_rebased += 1;
@@ -327,6 +330,9 @@ pub fn absorb_hunks(
         rewritten_destinations.push(new_commit);
         Ok(())
     })?;
+    if source_abandoned {
+        repo.set_abandon_reason(source.commit.id().clone(), AbandonReason::SquashedInto);
+    }
     Ok(AbsorbStats {
         rewritten_source,
         rewritten_destinations,
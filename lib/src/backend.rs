@@ -17,6 +17,7 @@
 use std::any::Any;
 use std::collections::BTreeMap;
 use std::fmt::Debug;
+use std::io;
 use std::io::Read;
 use std::time::SystemTime;
 
@@ -413,9 +414,9 @@ pub trait Backend: Send + Sync + Debug {
     fn empty_tree_id(&self) -> &TreeId;
 
     /// An estimate of how many concurrent requests this backend handles well. A
-    /// local backend like the Git backend (at until it supports partial clones)
-    /// may want to set this to 1. A cloud-backed backend may want to set it to
-    /// 100 or so.
+    /// local backend like the Git backend may want to set this to the number of
+    /// threads it reads objects on. A cloud-backed backend may want to set it
+    /// to 100 or so.
     ///
     /// It is not guaranteed that at most this number of concurrent requests are
     /// sent.
@@ -423,6 +424,19 @@ pub trait Backend: Send + Sync + Debug {
 
     async fn read_file(&self, path: &RepoPath, id: &FileId) -> BackendResult<Box<dyn Read>>;
 
+    /// Returns the size of the file contents in bytes.
+    ///
+    /// The default implementation reads the whole file. Backends which can
+    /// look up the size without reading the contents should override this.
+    async fn read_file_size(&self, path: &RepoPath, id: &FileId) -> BackendResult<u64> {
+        let mut reader = self.read_file(path, id).await?;
+        io::copy(&mut reader, &mut io::sink()).map_err(|err| BackendError::ReadFile {
+            path: path.to_owned(),
+            id: id.clone(),
+            source: err.into(),
+        })
+    }
+
     async fn write_file(
         &self,
         path: &RepoPath,
@@ -490,3 +504,26 @@ pub trait Backend: Send + Sync + Debug {
     /// risk of deleting new commits created concurrently by another process.
     fn gc(&self, index: &dyn Index, keep_newer: SystemTime) -> BackendResult<()>;
 }
+
+/// Runs the blocking `read` on the global thread pool, so that reads requested
+/// concurrently (e.g. by `MergedTree::diff_stream()`) are served in parallel.
+///
+/// If called from a worker thread of the pool, `read` is run inline instead
+/// since waiting for the pool from within it could deadlock. It's also run
+/// inline if the pool has a single thread, which couldn't serve reads in
+/// parallel anyway.
+pub(crate) async fn read_on_thread_pool<T: Send + 'static>(
+    read: impl FnOnce() -> BackendResult<T> + Send + 'static,
+) -> BackendResult<T> {
+    if rayon::current_num_threads() <= 1 || rayon::current_thread_index().is_some() {
+        return read();
+    }
+    let (sender, receiver) = futures::channel::oneshot::channel();
+    rayon::spawn(move || {
+        // The receiver is gone if the read is no longer needed.
+        sender.send(read()).ok();
+    });
+    receiver
+        .await
+        .map_err(|err| BackendError::Other(err.into()))?
+}
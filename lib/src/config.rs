@@ -36,6 +36,7 @@ use toml_edit::ImDocument;
 
 pub use crate::config_resolver::migrate;
 pub use crate::config_resolver::resolve;
+pub use crate::config_resolver::resolve_with_warnings;
 pub use crate::config_resolver::ConfigMigrateError;
 pub use crate::config_resolver::ConfigMigrateLayerError;
 pub use crate::config_resolver::ConfigMigrationRule;
@@ -293,6 +294,19 @@ pub enum ConfigSource {
     CommandArg,
 }
 
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ConfigSource::Default => "default",
+            ConfigSource::EnvBase | ConfigSource::EnvOverrides => "env",
+            ConfigSource::User => "user",
+            ConfigSource::Repo => "repo",
+            ConfigSource::CommandArg => "cli",
+        };
+        f.write_str(name)
+    }
+}
+
 /// Set of configuration variables with source information.
 #[derive(Clone, Debug)]
 pub struct ConfigLayer {
@@ -300,6 +314,8 @@ pub struct ConfigLayer {
     pub source: ConfigSource,
     /// Source file path of this layer if any.
     pub path: Option<PathBuf>,
+    /// Conditions of the scoped table this layer was resolved from, if any.
+    pub scope: Option<String>,
     /// Configuration variables.
     pub data: DocumentMut,
 }
@@ -315,6 +331,7 @@ impl ConfigLayer {
         ConfigLayer {
             source,
             path: None,
+            scope: None,
             data,
         }
     }
@@ -340,6 +357,7 @@ impl ConfigLayer {
         Ok(ConfigLayer {
             source,
             path: Some(path),
+            scope: None,
             data: data.into_mut(),
         })
     }
@@ -423,6 +441,9 @@ impl ConfigLayer {
                         name: name.to_string(),
                     });
                 }
+                // Keep comments and whitespace around the old value
+                let mut new_value = new_value.into();
+                *new_value.decor_mut() = entry.get().as_value().unwrap().decor().clone();
                 let old_item = entry.insert(toml_edit::value(new_value));
                 Ok(Some(old_item.into_value().unwrap()))
             }
@@ -548,6 +569,7 @@ impl ConfigFile {
                 Arc::new(ConfigLayer {
                     source,
                     path: Some(path),
+                    scope: None,
                     data: DocumentMut::new(),
                 })
             }
@@ -975,6 +997,29 @@ mod tests {
         'baz' = "new value"
         blah = 0
         "#);
+
+        // Comments around the overwritten value are preserved
+        let mut layer = ConfigLayer::parse(
+            ConfigSource::User,
+            indoc! {"
+                # Comment on table
+                [foo]
+                # Comment on key
+                bar = 1 # Comment on value
+                baz = 2
+            "},
+        )
+        .unwrap();
+        layer
+            .set_value("foo.bar", ConfigValue::from_str("[1, 2]").unwrap())
+            .unwrap();
+        insta::assert_snapshot!(layer.data, @r"
+        # Comment on table
+        [foo]
+        # Comment on key
+        bar = [1, 2] # Comment on value
+        baz = 2
+        ");
     }
 
     #[test]
@@ -38,12 +38,15 @@ const SCOPE_CONDITION_KEY: &str = "--when";
 const SCOPE_TABLE_KEY: &str = "--scope";
 
 /// Parameters to enable scoped config tables conditionally.
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 pub struct ConfigResolutionContext<'a> {
     /// Home directory. `~` will be substituted with this path.
     pub home_dir: Option<&'a Path>,
     /// Repository path, which is usually `<workspace_root>/.jj/repo`.
     pub repo_path: Option<&'a Path>,
+    /// Returns URLs of the remotes of the repository. This is only called if
+    /// a scoped table has remote conditions.
+    pub remote_urls: &'a dyn Fn() -> &'a [String],
     /// Space-separated subcommand. `jj file show ...` should result in `"file
     /// show"`.
     pub command: Option<&'a str>,
@@ -59,23 +62,45 @@ pub struct ConfigResolutionContext<'a> {
 #[derive(Clone, Debug, Default, serde::Deserialize)]
 #[serde(default, rename_all = "kebab-case")]
 struct ScopeCondition {
-    /// Paths to match the repository path prefix.
+    /// Paths to match the repository path prefix. Paths containing glob
+    /// metacharacters are matched against the repository path and its parent
+    /// directories.
     pub repositories: Option<Vec<PathBuf>>,
+    /// Glob patterns to match the URLs of the repository's remotes.
+    pub remotes: Option<Vec<String>>,
     /// Commands to match. Subcommands are matched space-separated.
     /// - `--when.commands = ["foo"]` -> matches "foo", "foo bar", "foo bar baz"
     /// - `--when.commands = ["foo bar"]` -> matches "foo bar", "foo bar baz",
     ///   NOT "foo"
     pub commands: Option<Vec<String>>,
     // TODO: maybe add "workspaces"?
+    /// Condition keys which aren't recognized. The condition never matches if
+    /// there are any.
+    #[serde(skip)]
+    pub unknown_keys: Vec<String>,
 }
 
 impl ScopeCondition {
+    const KEYS: [&'static str; 3] = ["repositories", "remotes", "commands"];
+
     fn from_value(
         value: ConfigValue,
         context: &ConfigResolutionContext,
     ) -> Result<Self, toml_edit::de::Error> {
+        let unknown_keys = value
+            .as_inline_table()
+            .into_iter()
+            .flat_map(|table| table.iter())
+            .map(|(key, _)| key)
+            .filter(|key| !Self::KEYS.iter().any(|known| known == key))
+            .map(|key| key.to_owned())
+            .collect();
         Self::deserialize(value.into_deserializer())?
             .expand_paths(context)
+            .map(|condition| ScopeCondition {
+                unknown_keys,
+                ..condition
+            })
             .map_err(serde::de::Error::custom)
     }
 
@@ -91,8 +116,43 @@ impl ScopeCondition {
         Ok(self)
     }
 
+    /// Returns descriptions of the problems which prevent this condition from
+    /// matching.
+    fn problems(&self) -> Vec<String> {
+        let unknown_keys = self
+            .unknown_keys
+            .iter()
+            .map(|key| format!("unknown condition {SCOPE_CONDITION_KEY}.{key}"));
+        let repository_patterns = self
+            .repositories
+            .iter()
+            .flatten()
+            .filter_map(|path| path.to_str().filter(|path| is_glob(path)))
+            .map(|pattern| ("repositories", pattern));
+        let remote_patterns = self
+            .remotes
+            .iter()
+            .flatten()
+            .map(|pattern| ("remotes", pattern.as_str()));
+        let invalid_pattern = |(key, pattern): (&str, &str)| {
+            let err = glob::Pattern::new(pattern).err()?;
+            Some(format!(
+                "invalid pattern {pattern:?} in {SCOPE_CONDITION_KEY}.{key}: {err}"
+            ))
+        };
+        unknown_keys
+            .chain(
+                repository_patterns
+                    .chain(remote_patterns)
+                    .filter_map(invalid_pattern),
+            )
+            .collect()
+    }
+
     fn matches(&self, context: &ConfigResolutionContext) -> bool {
-        matches_path_prefix(self.repositories.as_deref(), context.repo_path)
+        self.unknown_keys.is_empty()
+            && matches_repository_path(self.repositories.as_deref(), context.repo_path)
+            && matches_remote_urls(self.remotes.as_deref(), context.remote_urls)
             && matches_command(self.commands.as_deref(), context.command)
     }
 }
@@ -107,14 +167,50 @@ fn expand_home(path: &Path, home_dir: Option<&Path>) -> Result<Option<PathBuf>,
     }
 }
 
-fn matches_path_prefix(candidates: Option<&[PathBuf]>, actual: Option<&Path>) -> bool {
+fn is_glob(pattern: &str) -> bool {
+    pattern.contains(['*', '?', '['])
+}
+
+const GLOB_MATCH_OPTIONS: glob::MatchOptions = glob::MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+fn matches_repository_path(candidates: Option<&[PathBuf]>, actual: Option<&Path>) -> bool {
+    let matches = |base: &PathBuf, actual: &Path| match base.to_str().filter(|s| is_glob(s)) {
+        Some(pattern) => glob::Pattern::new(pattern).is_ok_and(|pattern| {
+            actual
+                .ancestors()
+                .any(|path| pattern.matches_path_with(path, GLOB_MATCH_OPTIONS))
+        }),
+        None => actual.starts_with(base),
+    };
     match (candidates, actual) {
-        (Some(candidates), Some(actual)) => candidates.iter().any(|base| actual.starts_with(base)),
+        (Some(candidates), Some(actual)) => candidates.iter().any(|base| matches(base, actual)),
         (Some(_), None) => false, // actual path not known (e.g. not in workspace)
         (None, _) => true,        // no constraints
     }
 }
 
+fn matches_remote_urls<'a>(
+    candidates: Option<&[String]>,
+    actual: &dyn Fn() -> &'a [String],
+) -> bool {
+    let Some(candidates) = candidates else {
+        return true;
+    };
+    let actual = actual();
+    candidates
+        .iter()
+        .filter_map(|pattern| glob::Pattern::new(pattern).ok())
+        .any(|pattern| {
+            actual
+                .iter()
+                .any(|url| pattern.matches_with(url, GLOB_MATCH_OPTIONS))
+        })
+}
+
 fn matches_command(candidates: Option<&[String]>, actual: Option<&str>) -> bool {
     match (candidates, actual) {
         (Some(candidates), Some(actual)) => candidates.iter().any(|candidate| {
@@ -133,9 +229,20 @@ pub fn resolve(
     source_config: &StackedConfig,
     context: &ConfigResolutionContext,
 ) -> Result<StackedConfig, ConfigGetError> {
+    let (resolved_config, _warnings) = resolve_with_warnings(source_config, context)?;
+    Ok(resolved_config)
+}
+
+/// Like [`resolve()`], but also returns descriptions of the scoped tables
+/// which were ignored because of malformed conditions.
+pub fn resolve_with_warnings(
+    source_config: &StackedConfig,
+    context: &ConfigResolutionContext,
+) -> Result<(StackedConfig, Vec<String>), ConfigGetError> {
     let mut source_layers_stack: Vec<Arc<ConfigLayer>> =
         source_config.layers().iter().rev().cloned().collect();
     let mut resolved_layers: Vec<Arc<ConfigLayer>> = Vec::new();
+    let mut warnings = Vec::new();
     while let Some(mut source_layer) = source_layers_stack.pop() {
         if !source_layer.data.contains_key(SCOPE_CONDITION_KEY)
             && !source_layer.data.contains_key(SCOPE_TABLE_KEY)
@@ -145,9 +252,26 @@ pub fn resolve(
         }
 
         let layer_mut = Arc::make_mut(&mut source_layer);
-        let condition = pop_scope_condition(layer_mut, context)?;
-        if !condition.matches(context) {
-            continue;
+        if let Some((condition, description)) = pop_scope_condition(layer_mut, context)? {
+            let problems = condition.problems();
+            if !problems.is_empty() {
+                let location = match &layer_mut.path {
+                    Some(path) => format!(" in {}", path.display()),
+                    None => String::new(),
+                };
+                warnings.extend(
+                    problems
+                        .iter()
+                        .map(|problem| format!("Ignored config scope{location} with {problem}")),
+                );
+            }
+            if !problems.is_empty() || !condition.matches(context) {
+                continue;
+            }
+            layer_mut.scope = Some(match layer_mut.scope.take() {
+                Some(outer) => format!("{outer}, {description}"),
+                None => description,
+            });
         }
         let tables = pop_scope_tables(layer_mut)?;
         // tables.iter() does not implement DoubleEndedIterator as of toml_edit
@@ -157,6 +281,7 @@ pub fn resolve(
             let layer = ConfigLayer {
                 source: source_layer.source,
                 path: source_layer.path.clone(),
+                scope: source_layer.scope.clone(),
                 data: DocumentMut::from(table),
             };
             source_layers_stack.push(Arc::new(layer));
@@ -166,25 +291,42 @@ pub fn resolve(
     }
     let mut resolved_config = StackedConfig::empty();
     resolved_config.extend_layers(resolved_layers);
-    Ok(resolved_config)
+    Ok((resolved_config, warnings))
 }
 
+/// Removes the condition from the `layer`. Returns the condition and its
+/// description if any.
 fn pop_scope_condition(
     layer: &mut ConfigLayer,
     context: &ConfigResolutionContext,
-) -> Result<ScopeCondition, ConfigGetError> {
+) -> Result<Option<(ScopeCondition, String)>, ConfigGetError> {
     let Some(item) = layer.data.remove(SCOPE_CONDITION_KEY) else {
-        return Ok(ScopeCondition::default());
+        return Ok(None);
     };
     let value = item
         .clone()
         .into_value()
         .expect("Item::None should not exist in table");
-    ScopeCondition::from_value(value, context).map_err(|err| ConfigGetError::Type {
-        name: SCOPE_CONDITION_KEY.to_owned(),
-        error: err.into(),
-        source_path: layer.path.clone(),
-    })
+    let description = match value.as_inline_table() {
+        Some(table) => table
+            .iter()
+            .map(|(key, value)| {
+                let value = value.clone().decorated("", "");
+                format!("{SCOPE_CONDITION_KEY}.{key} = {value}")
+            })
+            .join(", "),
+        None => format!(
+            "{SCOPE_CONDITION_KEY} = {}",
+            value.clone().decorated("", "")
+        ),
+    };
+    let condition =
+        ScopeCondition::from_value(value, context).map_err(|err| ConfigGetError::Type {
+            name: SCOPE_CONDITION_KEY.to_owned(),
+            error: err.into(),
+            source_path: layer.path.clone(),
+        })?;
+    Ok(Some((condition, description)))
 }
 
 fn pop_scope_tables(layer: &mut ConfigLayer) -> Result<toml_edit::ArrayOfTables, ConfigGetError> {
@@ -427,12 +569,14 @@ mod tests {
         let context = ConfigResolutionContext {
             home_dir: None,
             repo_path: None,
+            remote_urls: &|| &[],
             command: None,
         };
         assert!(condition.matches(&context));
         let context = ConfigResolutionContext {
             home_dir: None,
             repo_path: Some(Path::new("/foo")),
+            remote_urls: &|| &[],
             command: None,
         };
         assert!(condition.matches(&context));
@@ -442,36 +586,43 @@ mod tests {
     fn test_condition_repo_path() {
         let condition = ScopeCondition {
             repositories: Some(["/foo", "/bar"].map(PathBuf::from).into()),
+            remotes: None,
             commands: None,
+            unknown_keys: vec![],
         };
 
         let context = ConfigResolutionContext {
             home_dir: None,
             repo_path: None,
+            remote_urls: &|| &[],
             command: None,
         };
         assert!(!condition.matches(&context));
         let context = ConfigResolutionContext {
             home_dir: None,
             repo_path: Some(Path::new("/foo")),
+            remote_urls: &|| &[],
             command: None,
         };
         assert!(condition.matches(&context));
         let context = ConfigResolutionContext {
             home_dir: None,
             repo_path: Some(Path::new("/fooo")),
+            remote_urls: &|| &[],
             command: None,
         };
         assert!(!condition.matches(&context));
         let context = ConfigResolutionContext {
             home_dir: None,
             repo_path: Some(Path::new("/foo/baz")),
+            remote_urls: &|| &[],
             command: None,
         };
         assert!(condition.matches(&context));
         let context = ConfigResolutionContext {
             home_dir: None,
             repo_path: Some(Path::new("/bar")),
+            remote_urls: &|| &[],
             command: None,
         };
         assert!(condition.matches(&context));
@@ -481,30 +632,36 @@ mod tests {
     fn test_condition_repo_path_windows() {
         let condition = ScopeCondition {
             repositories: Some(["c:/foo", r"d:\bar/baz"].map(PathBuf::from).into()),
+            remotes: None,
             commands: None,
+            unknown_keys: vec![],
         };
 
         let context = ConfigResolutionContext {
             home_dir: None,
             repo_path: Some(Path::new(r"c:\foo")),
+            remote_urls: &|| &[],
             command: None,
         };
         assert_eq!(condition.matches(&context), cfg!(windows));
         let context = ConfigResolutionContext {
             home_dir: None,
             repo_path: Some(Path::new(r"c:\foo\baz")),
+            remote_urls: &|| &[],
             command: None,
         };
         assert_eq!(condition.matches(&context), cfg!(windows));
         let context = ConfigResolutionContext {
             home_dir: None,
             repo_path: Some(Path::new(r"d:\foo")),
+            remote_urls: &|| &[],
             command: None,
         };
         assert!(!condition.matches(&context));
         let context = ConfigResolutionContext {
             home_dir: None,
             repo_path: Some(Path::new(r"d:/bar\baz")),
+            remote_urls: &|| &[],
             command: None,
         };
         assert_eq!(condition.matches(&context), cfg!(windows));
@@ -523,6 +680,7 @@ mod tests {
         let context = ConfigResolutionContext {
             home_dir: None,
             repo_path: None,
+            remote_urls: &|| &[],
             command: None,
         };
         let resolved_config = resolve(&source_config, &context).unwrap();
@@ -560,6 +718,7 @@ mod tests {
         let context = ConfigResolutionContext {
             home_dir: None,
             repo_path: None,
+            remote_urls: &|| &[],
             command: None,
         };
         let resolved_config = resolve(&source_config, &context).unwrap();
@@ -599,6 +758,7 @@ mod tests {
         let context = ConfigResolutionContext {
             home_dir: Some(Path::new("/home/dir")),
             repo_path: None,
+            remote_urls: &|| &[],
             command: None,
         };
         let resolved_config = resolve(&source_config, &context).unwrap();
@@ -608,6 +768,7 @@ mod tests {
         let context = ConfigResolutionContext {
             home_dir: Some(Path::new("/home/dir")),
             repo_path: Some(Path::new("/foo/.jj/repo")),
+            remote_urls: &|| &[],
             command: None,
         };
         let resolved_config = resolve(&source_config, &context).unwrap();
@@ -619,6 +780,7 @@ mod tests {
         let context = ConfigResolutionContext {
             home_dir: Some(Path::new("/home/dir")),
             repo_path: Some(Path::new("/bar/.jj/repo")),
+            remote_urls: &|| &[],
             command: None,
         };
         let resolved_config = resolve(&source_config, &context).unwrap();
@@ -629,6 +791,7 @@ mod tests {
         let context = ConfigResolutionContext {
             home_dir: Some(Path::new("/home/dir")),
             repo_path: Some(Path::new("/home/dir/baz/.jj/repo")),
+            remote_urls: &|| &[],
             command: None,
         };
         let resolved_config = resolve(&source_config, &context).unwrap();
@@ -659,6 +822,7 @@ mod tests {
         let context = ConfigResolutionContext {
             home_dir: None,
             repo_path: None,
+            remote_urls: &|| &[],
             command: None,
         };
         let resolved_config = resolve(&source_config, &context).unwrap();
@@ -668,6 +832,7 @@ mod tests {
         let context = ConfigResolutionContext {
             home_dir: None,
             repo_path: None,
+            remote_urls: &|| &[],
             command: Some("foo"),
         };
         let resolved_config = resolve(&source_config, &context).unwrap();
@@ -679,6 +844,7 @@ mod tests {
         let context = ConfigResolutionContext {
             home_dir: None,
             repo_path: None,
+            remote_urls: &|| &[],
             command: Some("bar"),
         };
         let resolved_config = resolve(&source_config, &context).unwrap();
@@ -689,6 +855,7 @@ mod tests {
         let context = ConfigResolutionContext {
             home_dir: None,
             repo_path: None,
+            remote_urls: &|| &[],
             command: Some("foo baz"),
         };
         let resolved_config = resolve(&source_config, &context).unwrap();
@@ -702,6 +869,7 @@ mod tests {
         let context = ConfigResolutionContext {
             home_dir: None,
             repo_path: None,
+            remote_urls: &|| &[],
             command: Some("fooqux"),
         };
         let resolved_config = resolve(&source_config, &context).unwrap();
@@ -723,6 +891,7 @@ mod tests {
         let context = ConfigResolutionContext {
             home_dir: Some(Path::new("/home/dir")),
             repo_path: None,
+            remote_urls: &|| &[],
             command: None,
         };
         let resolved_config = resolve(&source_config, &context).unwrap();
@@ -733,6 +902,7 @@ mod tests {
         let context = ConfigResolutionContext {
             home_dir: Some(Path::new("/home/dir")),
             repo_path: Some(Path::new("/foo")),
+            remote_urls: &|| &[],
             command: Some("other"),
         };
         let resolved_config = resolve(&source_config, &context).unwrap();
@@ -743,6 +913,7 @@ mod tests {
         let context = ConfigResolutionContext {
             home_dir: Some(Path::new("/home/dir")),
             repo_path: Some(Path::new("/qux")),
+            remote_urls: &|| &[],
             command: Some("ABC"),
         };
         let resolved_config = resolve(&source_config, &context).unwrap();
@@ -753,6 +924,7 @@ mod tests {
         let context = ConfigResolutionContext {
             home_dir: Some(Path::new("/home/dir")),
             repo_path: Some(Path::new("/bar")),
+            remote_urls: &|| &[],
             command: Some("DEF"),
         };
         let resolved_config = resolve(&source_config, &context).unwrap();
@@ -761,6 +933,166 @@ mod tests {
         insta::assert_snapshot!(resolved_config.layers()[1].data, @r"a = 'a #0.1'");
     }
 
+    #[test]
+    fn test_condition_repo_path_glob() {
+        let condition = ScopeCondition {
+            repositories: Some(["/work/*", "/src/**/jj"].map(PathBuf::from).into()),
+            remotes: None,
+            commands: None,
+            unknown_keys: vec![],
+        };
+
+        let context = ConfigResolutionContext {
+            home_dir: None,
+            repo_path: Some(Path::new("/work/foo/.jj/repo")),
+            remote_urls: &|| &[],
+            command: None,
+        };
+        assert!(condition.matches(&context));
+        let context = ConfigResolutionContext {
+            home_dir: None,
+            repo_path: Some(Path::new("/work/foo/nested/.jj/repo")),
+            remote_urls: &|| &[],
+            command: None,
+        };
+        assert!(condition.matches(&context));
+        let context = ConfigResolutionContext {
+            home_dir: None,
+            repo_path: Some(Path::new("/src/a/b/jj/.jj/repo")),
+            remote_urls: &|| &[],
+            command: None,
+        };
+        assert!(condition.matches(&context));
+        let context = ConfigResolutionContext {
+            home_dir: None,
+            repo_path: Some(Path::new("/home/work/foo/.jj/repo")),
+            remote_urls: &|| &[],
+            command: None,
+        };
+        assert!(!condition.matches(&context));
+    }
+
+    #[test]
+    fn test_resolve_remotes() {
+        let mut source_config = StackedConfig::empty();
+        source_config.add_layer(new_user_layer(indoc! {"
+            a = 'a #0'
+            [[--scope]]
+            --when.remotes = ['git@github.com:corp/*']
+            a = 'a #0.1 corp'
+            [[--scope]]
+            --when.remotes = ['https://example.com/**', 'git@github.com:corp/*']
+            a = 'a #0.2 example|corp'
+        "}));
+
+        let context = ConfigResolutionContext {
+            home_dir: None,
+            repo_path: Some(Path::new("/foo/.jj/repo")),
+            remote_urls: &|| &[],
+            command: None,
+        };
+        let resolved_config = resolve(&source_config, &context).unwrap();
+        assert_eq!(resolved_config.layers().len(), 1);
+        insta::assert_snapshot!(resolved_config.layers()[0].data, @"a = 'a #0'");
+
+        let remote_urls = ["git@github.com:corp/foo".to_owned()];
+        let context = ConfigResolutionContext {
+            home_dir: None,
+            repo_path: Some(Path::new("/foo/.jj/repo")),
+            remote_urls: &|| &remote_urls,
+            command: None,
+        };
+        let resolved_config = resolve(&source_config, &context).unwrap();
+        assert_eq!(resolved_config.layers().len(), 3);
+        insta::assert_snapshot!(resolved_config.layers()[0].data, @"a = 'a #0'");
+        insta::assert_snapshot!(resolved_config.layers()[1].data, @"a = 'a #0.1 corp'");
+        insta::assert_snapshot!(resolved_config.layers()[2].data, @"a = 'a #0.2 example|corp'");
+        assert_eq!(resolved_config.layers()[0].scope, None);
+        assert_eq!(
+            resolved_config.layers()[1].scope.as_deref(),
+            Some("--when.remotes = ['git@github.com:corp/*']")
+        );
+
+        // Nested directories don't match "*"
+        let remote_urls = [
+            "git@github.com:corp/foo/bar".to_owned(),
+            "https://example.com/foo/bar".to_owned(),
+        ];
+        let context = ConfigResolutionContext {
+            home_dir: None,
+            repo_path: Some(Path::new("/foo/.jj/repo")),
+            remote_urls: &|| &remote_urls,
+            command: None,
+        };
+        let resolved_config = resolve(&source_config, &context).unwrap();
+        assert_eq!(resolved_config.layers().len(), 2);
+        insta::assert_snapshot!(resolved_config.layers()[0].data, @"a = 'a #0'");
+        insta::assert_snapshot!(resolved_config.layers()[1].data, @"a = 'a #0.2 example|corp'");
+    }
+
+    #[test]
+    fn test_resolve_nested_scope_description() {
+        let mut source_config = StackedConfig::empty();
+        source_config.add_layer(new_user_layer(indoc! {"
+            --when.commands = ['foo']
+            a = 'a #0'
+            [[--scope]]
+            --when.repositories = ['/foo']
+            a = 'a #0.1'
+        "}));
+
+        let context = ConfigResolutionContext {
+            home_dir: None,
+            repo_path: Some(Path::new("/foo/.jj/repo")),
+            remote_urls: &|| &[],
+            command: Some("foo"),
+        };
+        let resolved_config = resolve(&source_config, &context).unwrap();
+        assert_eq!(resolved_config.layers().len(), 2);
+        assert_eq!(
+            resolved_config.layers()[0].scope.as_deref(),
+            Some("--when.commands = ['foo']")
+        );
+        assert_eq!(
+            resolved_config.layers()[1].scope.as_deref(),
+            Some("--when.commands = ['foo'], --when.repositories = ['/foo']")
+        );
+    }
+
+    #[test]
+    fn test_resolve_malformed_condition() {
+        let mut source_config = StackedConfig::empty();
+        source_config.add_layer(new_user_layer(indoc! {"
+            a = 'a #0'
+            [[--scope]]
+            --when.repository = ['/foo']
+            a = 'a #0.1 typo'
+            [[--scope]]
+            --when.remotes = ['[foo']
+            a = 'a #0.2 bad pattern'
+            [[--scope]]
+            --when.repositories = ['/foo']
+            a = 'a #0.3 foo'
+        "}));
+
+        let context = ConfigResolutionContext {
+            home_dir: None,
+            repo_path: Some(Path::new("/foo/.jj/repo")),
+            remote_urls: &|| &[],
+            command: None,
+        };
+        let (resolved_config, warnings) = resolve_with_warnings(&source_config, &context).unwrap();
+        assert_eq!(resolved_config.layers().len(), 2);
+        insta::assert_snapshot!(resolved_config.layers()[0].data, @"a = 'a #0'");
+        insta::assert_snapshot!(resolved_config.layers()[1].data, @"a = 'a #0.3 foo'");
+        insta::assert_debug_snapshot!(warnings, @r#"
+        [
+            "Ignored config scope with unknown condition --when.repository",
+            "Ignored config scope with invalid pattern \"[foo\" in --when.remotes: Pattern syntax error near position 0: invalid range pattern",
+        ]
+        "#);
+    }
+
     #[test]
     fn test_resolve_invalid_condition() {
         let new_config = |text: &str| {
@@ -771,6 +1103,7 @@ mod tests {
         let context = ConfigResolutionContext {
             home_dir: Some(Path::new("/home/dir")),
             repo_path: Some(Path::new("/foo/.jj/repo")),
+            remote_urls: &|| &[],
             command: None,
         };
         assert_matches!(
@@ -789,6 +1122,7 @@ mod tests {
         let context = ConfigResolutionContext {
             home_dir: Some(Path::new("/home/dir")),
             repo_path: Some(Path::new("/foo/.jj/repo")),
+            remote_urls: &|| &[],
             command: None,
         };
         assert_matches!(
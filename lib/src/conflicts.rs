@@ -338,6 +338,40 @@ pub fn choose_materialized_conflict_marker_len<T: AsRef<[u8]>>(single_hunk: &Mer
         .max(MIN_CONFLICT_MARKER_LEN)
 }
 
+/// Returns true if any term of the conflict looks like binary content, in
+/// which case conflict markers can't be materialized meaningfully.
+pub fn is_binary_conflict<T: AsRef<[u8]>>(single_hunk: &Merge<T>) -> bool {
+    single_hunk
+        .iter()
+        .any(|file| files::is_binary(file.as_ref()))
+}
+
+/// Returns the names of the files next to a binary conflict at `file_name`
+/// which the terms of the conflict are written to. The names of the sides come
+/// first, followed by the names of the bases.
+pub fn binary_conflict_sidecar_names(file_name: &str, num_sides: usize) -> Vec<String> {
+    let sides = (0..num_sides).map(|i| format!("{file_name}.jj-side-{i}"));
+    let bases = (0..num_sides - 1).map(|i| {
+        if num_sides == 2 {
+            format!("{file_name}.jj-base")
+        } else {
+            format!("{file_name}.jj-base-{i}")
+        }
+    });
+    sides.chain(bases).collect()
+}
+
+/// If `file_name` looks like the name of a file written next to a binary
+/// conflict, returns the name of the conflicted file.
+pub fn binary_conflict_sidecar_owner(file_name: &str) -> Option<&str> {
+    let (owner, suffix) = file_name.rsplit_once(".jj-")?;
+    let is_index = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    let is_sidecar = suffix == "base"
+        || suffix.strip_prefix("base-").is_some_and(is_index)
+        || suffix.strip_prefix("side-").is_some_and(is_index);
+    (is_sidecar && !owner.is_empty()).then_some(owner)
+}
+
 pub fn materialize_merge_result<T: AsRef<[u8]>>(
     single_hunk: &Merge<T>,
     conflict_marker_style: ConflictMarkerStyle,
@@ -665,6 +699,23 @@ pub struct MaterializedTreeDiffEntry {
 pub fn materialized_diff_stream<'a>(
     store: &'a Store,
     tree_diff: BoxStream<'a, CopiesTreeDiffEntry>,
+) -> impl Stream<Item = MaterializedTreeDiffEntry> + 'a {
+    let concurrency = default_materialize_concurrency(store);
+    materialized_diff_stream_with_concurrency(store, tree_diff, concurrency)
+}
+
+/// Returns the number of diff entries to materialize ahead by default, which
+/// is derived from the concurrency of the backend.
+pub fn default_materialize_concurrency(store: &Store) -> usize {
+    (store.concurrency() / 2).max(1)
+}
+
+/// Like [`materialized_diff_stream()`], but materializes up to `concurrency`
+/// entries ahead of the consumer. The entries are emitted in order.
+pub fn materialized_diff_stream_with_concurrency<'a>(
+    store: &'a Store,
+    tree_diff: BoxStream<'a, CopiesTreeDiffEntry>,
+    concurrency: usize,
 ) -> impl Stream<Item = MaterializedTreeDiffEntry> + 'a {
     tree_diff
         .map(|CopiesTreeDiffEntry { path, values }| async {
@@ -681,7 +732,7 @@ pub fn materialized_diff_stream<'a>(
                 }
             }
         })
-        .buffered((store.concurrency() / 2).max(1))
+        .buffered(concurrency.max(1))
 }
 
 /// Parses conflict markers from a slice.
@@ -482,6 +482,26 @@ impl DefaultMutableIndex {
     pub(super) fn squash_and_save_in(self, dir: &Path) -> io::Result<Arc<ReadonlyIndexSegment>> {
         self.0.maybe_squash_with_ancestors().save_in(dir)
     }
+
+    /// Creates new index which contains all commits of the `segment` and its
+    /// ancestors in a single segment. The commits are added in the original
+    /// order, so the index positions are preserved.
+    pub(super) fn squashed_from(segment: &ReadonlyIndexSegment) -> Self {
+        let mut mutable_segment =
+            MutableIndexSegment::full(segment.commit_id_length(), segment.change_id_length());
+        let segments = segment
+            .as_composite()
+            .ancestor_index_segments()
+            .collect_vec();
+        for segment in segments.into_iter().rev() {
+            mutable_segment.add_commits_from(segment);
+        }
+        DefaultMutableIndex(mutable_segment)
+    }
+
+    pub(super) fn save_in(self, dir: &Path) -> io::Result<Arc<ReadonlyIndexSegment>> {
+        self.0.save_in(dir)
+    }
 }
 
 impl AsCompositeIndex for DefaultMutableIndex {
@@ -355,7 +355,7 @@ impl ReadonlyIndexSegment {
             ));
         }
 
-        Ok(Arc::new(ReadonlyIndexSegment {
+        let segment = ReadonlyIndexSegment {
             parent_file,
             num_parent_commits,
             name,
@@ -370,7 +370,107 @@ impl ReadonlyIndexSegment {
             parent_overflow_base,
             change_overflow_base,
             data,
-        }))
+        };
+        if let Err(message) = segment.validate() {
+            return Err(ReadonlyIndexLoadError::invalid_data(segment.name, message));
+        }
+        Ok(Arc::new(segment))
+    }
+
+    /// Reads the name of the parent segment file from the header of the file
+    /// `name` without loading the entries.
+    pub(super) fn load_parent_file_name(
+        dir: &Path,
+        name: &str,
+    ) -> Result<Option<String>, ReadonlyIndexLoadError> {
+        let from_io_err = |err| ReadonlyIndexLoadError::from_io_err(name, err);
+        let mut file = File::open(dir.join(name)).map_err(from_io_err)?;
+        let mut read_u32 = || {
+            let mut buf = [0; 4];
+            file.read_exact(&mut buf).map_err(from_io_err)?;
+            Ok(u32::from_le_bytes(buf))
+        };
+        let format_version = read_u32()?;
+        if format_version != INDEX_SEGMENT_FILE_FORMAT_VERSION {
+            return Err(ReadonlyIndexLoadError::UnexpectedVersion {
+                found_version: format_version,
+                expected_version: INDEX_SEGMENT_FILE_FORMAT_VERSION,
+            });
+        }
+        let parent_filename_len = read_u32()?;
+        if parent_filename_len == 0 {
+            return Ok(None);
+        }
+        let mut parent_filename_bytes = vec![0; parent_filename_len as usize];
+        file.read_exact(&mut parent_filename_bytes)
+            .map_err(from_io_err)?;
+        let parent_filename = String::from_utf8(parent_filename_bytes).map_err(|_| {
+            ReadonlyIndexLoadError::invalid_data(name, "parent file name is not valid UTF-8")
+        })?;
+        Ok(Some(parent_filename))
+    }
+
+    /// Checks that the positions stored in the tables point to valid entries,
+    /// so a corrupted file is detected on load instead of causing a panic
+    /// later.
+    fn validate(&self) -> Result<(), &'static str> {
+        let num_parent_overflow_entries =
+            u32::try_from((self.change_overflow_base - self.parent_overflow_base) / 4).unwrap();
+        for local_pos in (0..self.num_local_commits).map(LocalPosition) {
+            let global_pos = self.num_parent_commits + local_pos.0;
+            let entry = self.graph_entry(local_pos);
+            let pos1_or_overflow_pos = entry.parent1_pos_or_overflow_pos();
+            let pos2_or_overflow_len = entry.parent2_pos_or_overflow_len();
+            let parents_valid = if let Some(pos1) = pos1_or_overflow_pos.as_inlined() {
+                pos1.0 < global_pos
+                    && pos2_or_overflow_len
+                        .as_inlined()
+                        .map_or(pos2_or_overflow_len.0 == u32::MAX, |pos2| {
+                            pos2.0 < global_pos
+                        })
+            } else {
+                let overflow_pos = pos1_or_overflow_pos.as_overflow().unwrap();
+                match pos2_or_overflow_len.as_overflow() {
+                    Some(num_parents)
+                        if u64::from(overflow_pos) + u64::from(num_parents)
+                            <= u64::from(num_parent_overflow_entries) =>
+                    {
+                        self.overflow_parents(overflow_pos, num_parents)
+                            .iter()
+                            .all(|pos| pos.0 < global_pos)
+                    }
+                    _ => false,
+                }
+            };
+            if !parents_valid {
+                return Err("invalid parent position");
+            }
+            if entry.change_id_lookup_pos() >= self.num_local_change_ids {
+                return Err("invalid change id position");
+            }
+        }
+        for lookup_pos in 0..self.num_local_commits {
+            if self.commit_lookup_pos(lookup_pos).0 >= self.num_local_commits {
+                return Err("invalid commit lookup position");
+            }
+        }
+        for lookup_pos in 0..self.num_local_change_ids {
+            let change_pos = self.change_lookup_pos(lookup_pos);
+            let valid = match change_pos.as_inlined() {
+                Some(local_pos) => local_pos.0 < self.num_local_commits,
+                None => change_pos.as_overflow().unwrap() < self.num_change_overflow_entries,
+            };
+            if !valid {
+                return Err("invalid change lookup position");
+            }
+        }
+        if self
+            .overflow_changes_from(0)
+            .any(|local_pos| local_pos.0 >= self.num_local_commits)
+        {
+            return Err("invalid change overflow position");
+        }
+        Ok(())
     }
 
     pub(super) fn as_composite(&self) -> &CompositeIndex {
@@ -14,10 +14,10 @@
 
 #![allow(missing_docs)]
 
+use std::cell::Cell;
 use std::cell::RefCell;
 use std::cmp::Ordering;
 use std::cmp::Reverse;
-use std::collections::BTreeSet;
 use std::collections::BinaryHeap;
 use std::collections::HashSet;
 use std::fmt;
@@ -49,6 +49,7 @@ use crate::conflicts::MaterializedTreeValue;
 use crate::default_index::AsCompositeIndex;
 use crate::default_index::CompositeIndex;
 use crate::default_index::IndexPosition;
+use crate::fileset::FilesetEvaluationError;
 use crate::graph::GraphNode;
 use crate::matchers::Matcher;
 use crate::matchers::Visit;
@@ -120,11 +121,20 @@ impl<T: InternalRevset + ?Sized> InternalRevset for Box<T> {
 pub struct RevsetImpl<I> {
     inner: Box<dyn InternalRevset>,
     index: I,
+    num_predicate_evaluations: Rc<Cell<usize>>,
 }
 
 impl<I: AsCompositeIndex + Clone> RevsetImpl<I> {
-    fn new(inner: Box<dyn InternalRevset>, index: I) -> Self {
-        Self { inner, index }
+    fn new(
+        inner: Box<dyn InternalRevset>,
+        index: I,
+        num_predicate_evaluations: Rc<Cell<usize>>,
+    ) -> Self {
+        Self {
+            inner,
+            index,
+            num_predicate_evaluations,
+        }
     }
 
     fn positions(&self) -> impl Iterator<Item = Result<IndexPosition, RevsetEvaluationError>> + '_ {
@@ -218,6 +228,10 @@ impl<I: AsCompositeIndex + Clone> Revset for RevsetImpl<I> {
         let positions = PositionsAccumulator::new(self.index.clone(), self.inner.positions());
         Box::new(move |commit_id| positions.contains(commit_id))
     }
+
+    fn num_predicate_evaluations(&self) -> usize {
+        self.num_predicate_evaluations.get()
+    }
 }
 
 /// Incrementally consumes `RevWalk` of the revset collecting positions.
@@ -425,6 +439,153 @@ where
     }
 }
 
+/// Heads of the candidates, computed while walking the candidates so that
+/// the consumer can stop early. The heads are cached once the walk completes.
+#[derive(Debug)]
+struct HeadsRevset<S> {
+    candidates: S,
+    heads: Rc<RefCell<Option<Vec<IndexPosition>>>>,
+}
+
+impl<S> HeadsRevset<S> {
+    fn new(candidates: S) -> Self {
+        HeadsRevset {
+            candidates,
+            heads: Rc::new(RefCell::new(None)),
+        }
+    }
+}
+
+impl<S: InternalRevset> InternalRevset for HeadsRevset<S> {
+    fn positions<'a>(&self) -> BoxedRevWalk<'a>
+    where
+        Self: 'a,
+    {
+        if let Some(heads) = &*self.heads.borrow() {
+            let walk = EagerRevWalk::new(heads.clone().into_iter());
+            return Box::new(walk.map(|_index, pos| Ok(pos)));
+        }
+        // Ancestors of the candidates visited so far. Since the candidates are
+        // visited in descending position order, a candidate is a head unless
+        // it is one of these ancestors.
+        let mut ancestors = BinaryHeap::new();
+        let walk = self
+            .candidates
+            .positions()
+            .filter_map(move |index: &CompositeIndex, pos| {
+                let pos = match pos {
+                    Ok(pos) => pos,
+                    Err(err) => return Some(Err(err)),
+                };
+                let mut is_head = true;
+                while let Some(ancestor_pos) = ancestors.pop() {
+                    while ancestors.peek() == Some(&ancestor_pos) {
+                        ancestors.pop();
+                    }
+                    if ancestor_pos < pos {
+                        ancestors.push(ancestor_pos);
+                        break;
+                    } else if ancestor_pos == pos {
+                        is_head = false;
+                        break;
+                    }
+                    ancestors.extend(index.entry_by_pos(ancestor_pos).parent_positions());
+                }
+                ancestors.extend(index.entry_by_pos(pos).parent_positions());
+                is_head.then_some(Ok(pos))
+            });
+        Box::new(CachingHeadsWalk {
+            walk,
+            found: Some(vec![]),
+            cache: self.heads.clone(),
+        })
+    }
+
+    fn into_predicate<'a>(self: Box<Self>) -> Box<dyn ToPredicateFn + 'a>
+    where
+        Self: 'a,
+    {
+        self
+    }
+}
+
+impl<S: InternalRevset> ToPredicateFn for HeadsRevset<S> {
+    fn to_predicate_fn<'a>(&self) -> BoxedPredicateFn<'a>
+    where
+        Self: 'a,
+    {
+        let mut walk = self.positions().peekable();
+        Box::new(move |index, entry_pos| {
+            while walk
+                .next_if(index, |pos| pos.as_ref().is_ok_and(|&pos| pos > entry_pos))
+                .is_some()
+            {
+                continue;
+            }
+            match walk.next_if(index, |pos| {
+                pos.as_ref().map_or(true, |&pos| pos == entry_pos)
+            }) {
+                Some(pos) => pos.map(|_| true),
+                None => Ok(false),
+            }
+        })
+    }
+}
+
+/// Walk that records the heads found, and saves them to the cache once the
+/// walk completes without error.
+struct CachingHeadsWalk<W> {
+    walk: W,
+    found: Option<Vec<IndexPosition>>,
+    cache: Rc<RefCell<Option<Vec<IndexPosition>>>>,
+}
+
+impl<W> RevWalk<CompositeIndex> for CachingHeadsWalk<W>
+where
+    W: RevWalk<CompositeIndex, Item = Result<IndexPosition, RevsetEvaluationError>>,
+{
+    type Item = W::Item;
+
+    fn next(&mut self, index: &CompositeIndex) -> Option<Self::Item> {
+        let item = self.walk.next(index);
+        match &item {
+            Some(Ok(pos)) => {
+                if let Some(found) = &mut self.found {
+                    found.push(*pos);
+                }
+            }
+            Some(Err(_)) => self.found = None,
+            None => {
+                if let Some(found) = self.found.take() {
+                    *self.cache.borrow_mut() = Some(found);
+                }
+            }
+        }
+        item
+    }
+}
+
+/// Filter predicate which counts the number of entries tested.
+#[derive(Debug)]
+struct CountingPredicate<P> {
+    predicate: P,
+    count: Rc<Cell<usize>>,
+}
+
+impl<P: ToPredicateFn> ToPredicateFn for CountingPredicate<P> {
+    fn to_predicate_fn<'a>(&self) -> BoxedPredicateFn<'a>
+    where
+        Self: 'a,
+    {
+        let mut p = self.predicate.to_predicate_fn();
+        let count = self.count.clone();
+        Box::new(move |index, pos| {
+            count.set(count.get() + 1);
+            p(index, pos)
+        })
+    }
+}
+
 #[derive(Debug)]
 struct NotInPredicate<S>(S);
 
@@ -769,14 +930,20 @@ pub fn evaluate<I: AsCompositeIndex + Clone>(
     let context = EvaluationContext {
         store: store.clone(),
         index: index.as_composite(),
+        num_predicate_evaluations: Rc::new(Cell::new(0)),
     };
     let internal_revset = context.evaluate(expression)?;
-    Ok(RevsetImpl::new(internal_revset, index))
+    Ok(RevsetImpl::new(
+        internal_revset,
+        index.clone(),
+        context.num_predicate_evaluations,
+    ))
 }
 
 struct EvaluationContext<'index> {
     store: Arc<Store>,
     index: &'index CompositeIndex,
+    num_predicate_evaluations: Rc<Cell<usize>>,
 }
 
 fn to_u32_generation_range(range: &Range<u64>) -> Result<Range<u32>, RevsetEvaluationError> {
@@ -929,11 +1096,8 @@ impl EvaluationContext<'_> {
                 Ok(Box::new(EagerRevset { positions }))
             }
             ResolvedExpression::Heads(candidates) => {
-                let candidate_set = self.evaluate(candidates)?;
-                let head_positions: BTreeSet<_> =
-                    index.heads_pos(candidate_set.positions().attach(index).try_collect()?);
-                let positions = head_positions.into_iter().rev().collect();
-                Ok(Box::new(EagerRevset { positions }))
+                let candidates = self.evaluate(candidates)?;
+                Ok(Box::new(HeadsRevset::new(candidates)))
             }
             ResolvedExpression::Roots(candidates) => {
                 let mut positions: Vec<_> = self
@@ -1012,9 +1176,10 @@ impl EvaluationContext<'_> {
         expression: &ResolvedPredicateExpression,
     ) -> Result<Box<dyn ToPredicateFn>, RevsetEvaluationError> {
         match expression {
-            ResolvedPredicateExpression::Filter(predicate) => {
-                Ok(build_predicate_fn(self.store.clone(), predicate))
-            }
+            ResolvedPredicateExpression::Filter(predicate) => Ok(Box::new(CountingPredicate {
+                predicate: build_predicate_fn(self.store.clone(), predicate)?,
+                count: self.num_predicate_evaluations.clone(),
+            })),
             ResolvedPredicateExpression::Set(expression) => {
                 Ok(self.evaluate(expression)?.into_predicate())
             }
@@ -1144,8 +1309,8 @@ fn box_pure_predicate_fn<'a>(
 fn build_predicate_fn(
     store: Arc<Store>,
     predicate: &RevsetFilterPredicate,
-) -> Box<dyn ToPredicateFn> {
-    match predicate {
+) -> Result<Box<dyn ToPredicateFn>, RevsetEvaluationError> {
+    let predicate_fn = match predicate {
         RevsetFilterPredicate::ParentCount(parent_count_range) => {
             let parent_count_range = parent_count_range.clone();
             box_pure_predicate_fn(move |index, pos| {
@@ -1220,7 +1385,7 @@ fn build_predicate_fn(
             })
         }
         RevsetFilterPredicate::File(expr) => {
-            let matcher: Rc<dyn Matcher> = expr.to_matcher().into();
+            let matcher: Rc<dyn Matcher> = expr.to_matcher().map_err(fileset_error)?.into();
             box_pure_predicate_fn(move |index, pos| {
                 let entry = index.entry_by_pos(pos);
                 let commit = store.get_commit(&entry.commit_id())?;
@@ -1229,7 +1394,7 @@ fn build_predicate_fn(
         }
         RevsetFilterPredicate::DiffContains { text, files } => {
             let text_pattern = text.clone();
-            let files_matcher: Rc<dyn Matcher> = files.to_matcher().into();
+            let files_matcher: Rc<dyn Matcher> = files.to_matcher().map_err(fileset_error)?.into();
             box_pure_predicate_fn(move |index, pos| {
                 let entry = index.entry_by_pos(pos);
                 let commit = store.get_commit(&entry.commit_id())?;
@@ -1255,6 +1420,17 @@ fn build_predicate_fn(
                 Ok(ext.matches_commit(&commit))
             })
         }
+    };
+    Ok(predicate_fn)
+}
+
+/// Converts the error of building a matcher from a fileset expression. File
+/// predicates such as `size()` are rejected by the revset parser, but the
+/// expression may be constructed programmatically.
+fn fileset_error(err: FilesetEvaluationError) -> RevsetEvaluationError {
+    match err {
+        FilesetEvaluationError::Backend(err) => RevsetEvaluationError::StoreError(err),
+        err @ FilesetEvaluationError::TreeRequired(_) => RevsetEvaluationError::Other(err.into()),
     }
 }
 
@@ -1700,4 +1876,52 @@ mod tests {
 
         assert!(positions_accum.contains(&id_1).unwrap());
     }
+
+    #[test]
+    fn test_heads_revset_cache() {
+        let mut new_change_id = change_id_generator();
+        let mut index = DefaultMutableIndex::full(3, 16);
+        let id_0 = CommitId::from_hex("000000");
+        let id_1 = CommitId::from_hex("111111");
+        let id_2 = CommitId::from_hex("222222");
+        let id_3 = CommitId::from_hex("333333");
+        index.add_commit_data(id_0.clone(), new_change_id(), &[]);
+        index.add_commit_data(id_1.clone(), new_change_id(), &[id_0.clone()]);
+        index.add_commit_data(id_2.clone(), new_change_id(), &[id_0.clone()]);
+        index.add_commit_data(id_3.clone(), new_change_id(), &[id_1.clone()]);
+
+        let index = index.as_composite();
+        let get_pos = |id: &CommitId| index.commit_id_to_pos(id).unwrap();
+        // Count how many times the candidates are tested to check that the
+        // cached heads are reused.
+        let count = Rc::new(Cell::new(0));
+        let candidates = FilterRevset {
+            candidates: EagerRevset {
+                positions: [&id_3, &id_2, &id_1, &id_0].map(get_pos).to_vec(),
+            },
+            predicate: CountingPredicate {
+                predicate: as_pure_predicate_fn(|_index, _pos| Ok(true)),
+                count: count.clone(),
+            },
+        };
+        let set = HeadsRevset::new(candidates);
+
+        // Partially consumed walk doesn't fill the cache.
+        let mut walk = set.positions();
+        assert_eq!(walk.next(index).unwrap().unwrap(), get_pos(&id_3));
+        drop(walk);
+        assert!(set.heads.borrow().is_none());
+        assert_eq!(count.get(), 1);
+
+        let expected = vec![get_pos(&id_3), get_pos(&id_2)];
+        let positions = try_collect_vec(set.positions().attach(index)).unwrap();
+        assert_eq!(positions, expected);
+        assert_eq!(set.heads.borrow().as_ref(), Some(&expected));
+        assert_eq!(count.get(), 5);
+
+        // The candidates aren't walked again.
+        let positions = try_collect_vec(set.positions().attach(index)).unwrap();
+        assert_eq!(positions, expected);
+        assert_eq!(count.get(), 5);
+    }
 }
@@ -19,14 +19,17 @@ use std::collections::HashSet;
 use std::fs;
 use std::io;
 use std::io::Write;
+use std::iter;
 use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::SystemTime;
 
 use itertools::Itertools;
 use tempfile::NamedTempFile;
 use thiserror::Error;
 
+use super::composite::IndexSegment as _;
 use super::mutable::DefaultMutableIndex;
 use super::readonly::DefaultReadonlyIndex;
 use super::readonly::ReadonlyIndexLoadError;
@@ -79,6 +82,8 @@ pub enum DefaultIndexStoreError {
     LoadIndex(ReadonlyIndexLoadError),
     #[error("Failed to write commit index file")]
     SaveIndex(#[source] io::Error),
+    #[error("Failed to remove unused commit index files")]
+    RemoveSegments(#[source] PathError),
     #[error("Failed to index commits at operation {op_id}")]
     IndexCommits {
         op_id: OperationId,
@@ -88,6 +93,17 @@ pub enum DefaultIndexStoreError {
     OpStore(#[from] OpStoreError),
 }
 
+/// Statistics of the files in [`DefaultIndexStore`].
+#[derive(Clone, Debug)]
+pub struct DefaultIndexStoreStats {
+    /// Number of index segment files.
+    pub num_segment_files: usize,
+    /// Total size of the index segment files in bytes.
+    pub segment_files_size: u64,
+    /// Number of operations associated with index segment files.
+    pub num_indexed_operations: usize,
+}
+
 #[derive(Debug)]
 pub struct DefaultIndexStore {
     dir: PathBuf,
@@ -133,6 +149,36 @@ impl DefaultIndexStore {
         Ok(())
     }
 
+    /// Counts the index segment files and their sizes.
+    pub fn stats(&self) -> Result<DefaultIndexStoreStats, PathError> {
+        let segments_dir = self.segments_dir();
+        let mut num_segment_files = 0;
+        let mut segment_files_size = 0;
+        for entry in segments_dir.read_dir().context(&segments_dir)? {
+            let entry = entry.context(&segments_dir)?;
+            if entry.file_name().len() != SEGMENT_FILE_NAME_LENGTH {
+                // Skip temporary files
+                continue;
+            }
+            num_segment_files += 1;
+            segment_files_size += entry.metadata().context(entry.path())?.len();
+        }
+        let operations_dir = self.operations_dir();
+        let mut num_indexed_operations = 0;
+        for entry in operations_dir.read_dir().context(&operations_dir)? {
+            let entry = entry.context(&operations_dir)?;
+            // Skip temporary files
+            if !entry.file_name().to_string_lossy().starts_with(".tmp") {
+                num_indexed_operations += 1;
+            }
+        }
+        Ok(DefaultIndexStoreStats {
+            num_segment_files,
+            segment_files_size,
+            num_indexed_operations,
+        })
+    }
+
     fn ensure_base_dirs(&self) -> Result<(), PathError> {
         for dir in [self.operations_dir(), self.segments_dir()] {
             file_util::create_or_reuse_dir(&dir).context(&dir)?;
@@ -179,6 +225,144 @@ impl DefaultIndexStore {
         Ok(DefaultReadonlyIndex::from_segment(index_segment))
     }
 
+    /// Loads the index segments for the given `operation`, which checks that
+    /// the positions stored in all segments point to valid entries.
+    ///
+    /// Unlike [`IndexStore::get_index_at_op()`], this doesn't rebuild the
+    /// index if it's missing or corrupted.
+    pub fn verify_index_at_operation(
+        &self,
+        operation: &Operation,
+        store: &Arc<Store>,
+    ) -> Result<DefaultReadonlyIndex, DefaultIndexStoreError> {
+        let index_segment = self.load_index_segments_at_operation(
+            operation.id(),
+            store.commit_id_length(),
+            store.change_id_length(),
+        )?;
+        Ok(DefaultReadonlyIndex::from_segment(index_segment))
+    }
+
+    /// Merges the index segments for the given `operation` into one file,
+    /// building the index first if needed.
+    ///
+    /// The operation is associated with the new file by atomic rename. The
+    /// replaced segment files are kept since concurrent processes may still be
+    /// loading them or writing new segments on top of them, but their
+    /// modification time is renewed. Segment files which aren't referenced by
+    /// any operation and are older than `keep_newer` are then removed.
+    pub fn compact_index_at_operation(
+        &self,
+        operation: &Operation,
+        store: &Arc<Store>,
+        keep_newer: SystemTime,
+    ) -> Result<DefaultReadonlyIndex, DefaultIndexStoreError> {
+        let index_segment = match self.load_index_segments_at_operation(
+            operation.id(),
+            store.commit_id_length(),
+            store.change_id_length(),
+        ) {
+            Err(DefaultIndexStoreError::LoadAssociation(err))
+                if err.kind() == io::ErrorKind::NotFound =>
+            {
+                self.build_index_segments_at_operation(operation, store)?
+            }
+            result => result?,
+        };
+        if index_segment.parent_file().is_none() {
+            return Ok(DefaultReadonlyIndex::from_segment(index_segment));
+        }
+        let old_segment_names =
+            iter::successors(Some(&index_segment), |segment| segment.parent_file())
+                .map(|segment| segment.name().to_owned())
+                .collect_vec();
+        let mutable_index = DefaultMutableIndex::squashed_from(&index_segment);
+        let index_segment = mutable_index
+            .save_in(&self.segments_dir())
+            .map_err(DefaultIndexStoreError::SaveIndex)?;
+        self.associate_file_with_operation(&index_segment, operation.id())
+            .map_err(|source| DefaultIndexStoreError::AssociateIndex {
+                op_id: operation.id().to_owned(),
+                source,
+            })?;
+        self.remove_unreferenced_segments(&old_segment_names, keep_newer)
+            .map_err(DefaultIndexStoreError::RemoveSegments)?;
+        Ok(DefaultReadonlyIndex::from_segment(index_segment))
+    }
+
+    /// Renews the modification time of the `replaced_names` segment files, and
+    /// removes the other segment files which aren't reachable from any
+    /// operation link and are older than `keep_newer`.
+    fn remove_unreferenced_segments(
+        &self,
+        replaced_names: &[String],
+        keep_newer: SystemTime,
+    ) -> Result<(), PathError> {
+        let operations_dir = self.operations_dir();
+        let segments_dir = self.segments_dir();
+        for name in replaced_names {
+            let path = segments_dir.join(name);
+            let renewed = fs::File::options()
+                .write(true)
+                .open(&path)
+                .and_then(|file| file.set_modified(SystemTime::now()));
+            match renewed {
+                Ok(()) => {}
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                Err(err) => return Err(err).context(&path),
+            }
+        }
+        let mut referenced_names = HashSet::new();
+        for entry in operations_dir.read_dir().context(&operations_dir)? {
+            let path = entry.context(&operations_dir)?.path();
+            let name = match fs::read_to_string(&path) {
+                Ok(name) => name,
+                // Temporary file may be renamed or removed concurrently.
+                Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+                Err(err) => return Err(err).context(&path),
+            };
+            let mut next_name = Some(name);
+            while let Some(name) = next_name.take() {
+                if !referenced_names.insert(name.clone()) {
+                    break;
+                }
+                // Missing or corrupt segments would be rebuilt when loaded.
+                next_name = ReadonlyIndexSegment::load_parent_file_name(&segments_dir, &name)
+                    .ok()
+                    .flatten();
+            }
+        }
+        for entry in segments_dir.read_dir().context(&segments_dir)? {
+            let entry = entry.context(&segments_dir)?;
+            let Ok(name) = entry.file_name().into_string() else {
+                continue;
+            };
+            // Skip temporary files, which may be persisted concurrently.
+            if name.len() != SEGMENT_FILE_NAME_LENGTH
+                || replaced_names.contains(&name)
+                || referenced_names.contains(&name)
+            {
+                continue;
+            }
+            let path = entry.path();
+            // There's still TOCTOU problem if a file is renewed concurrently.
+            let mtime = match entry.metadata() {
+                Ok(metadata) => metadata.modified().expect("unsupported platform?"),
+                Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+                Err(err) => return Err(err).context(&path),
+            };
+            if mtime > keep_newer {
+                continue;
+            }
+            match fs::remove_file(&path) {
+                Ok(()) => {}
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                Err(err) => return Err(err).context(&path),
+            }
+        }
+        Ok(())
+    }
+
     #[tracing::instrument(skip(self, store))]
     fn build_index_segments_at_operation(
         &self,
@@ -0,0 +1,127 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Line-ending conversion between the working copy and the store.
+
+use std::borrow::Cow;
+
+use bstr::ByteSlice as _;
+
+use crate::files;
+
+/// Describes how line endings of text files are converted.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum EolConversionMode {
+    /// Files are stored and checked out as they are.
+    #[default]
+    None,
+    /// CRLF line endings are converted to LF when snapshotting.
+    Input,
+    /// CRLF line endings are converted to LF when snapshotting, and LF line
+    /// endings are converted to CRLF on checkout.
+    InputOutput,
+}
+
+/// Line endings used in file contents.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LineEndings {
+    /// The file is binary, or has no line endings.
+    None,
+    /// All line endings are LF.
+    Lf,
+    /// All line endings are CRLF.
+    Crlf,
+    /// The file contains both LF and CRLF line endings.
+    Mixed,
+}
+
+impl LineEndings {
+    /// Detects the line endings of the given file contents.
+    pub fn detect(contents: &[u8]) -> Self {
+        if files::is_binary(contents) {
+            return LineEndings::None;
+        }
+        let mut has_lf = false;
+        let mut has_crlf = false;
+        for pos in contents.find_iter(b"\n") {
+            if pos > 0 && contents[pos - 1] == b'\r' {
+                has_crlf = true;
+            } else {
+                has_lf = true;
+            }
+        }
+        match (has_lf, has_crlf) {
+            (false, false) => LineEndings::None,
+            (true, false) => LineEndings::Lf,
+            (false, true) => LineEndings::Crlf,
+            (true, true) => LineEndings::Mixed,
+        }
+    }
+}
+
+/// Converts CRLF line endings to LF. Binary contents and contents with mixed
+/// line endings are left alone.
+pub fn convert_to_lf(contents: &[u8]) -> Cow<'_, [u8]> {
+    match LineEndings::detect(contents) {
+        LineEndings::Crlf => Cow::Owned(contents.replace(b"\r\n", b"\n")),
+        LineEndings::None | LineEndings::Lf | LineEndings::Mixed => Cow::Borrowed(contents),
+    }
+}
+
+/// Converts LF line endings to CRLF. Binary contents and contents with mixed
+/// line endings are left alone.
+pub fn convert_to_crlf(contents: &[u8]) -> Cow<'_, [u8]> {
+    match LineEndings::detect(contents) {
+        LineEndings::Lf => Cow::Owned(contents.replace(b"\n", b"\r\n")),
+        LineEndings::None | LineEndings::Crlf | LineEndings::Mixed => Cow::Borrowed(contents),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_line_endings() {
+        assert_eq!(LineEndings::detect(b""), LineEndings::None);
+        assert_eq!(LineEndings::detect(b"no newline"), LineEndings::None);
+        assert_eq!(LineEndings::detect(b"a\nb\n"), LineEndings::Lf);
+        assert_eq!(LineEndings::detect(b"\na"), LineEndings::Lf);
+        assert_eq!(LineEndings::detect(b"a\r\nb\r\n"), LineEndings::Crlf);
+        assert_eq!(LineEndings::detect(b"a\r\nb\n"), LineEndings::Mixed);
+        assert_eq!(LineEndings::detect(b"a\rb\r"), LineEndings::None);
+        assert_eq!(LineEndings::detect(b"a\0\r\nb\n"), LineEndings::None);
+    }
+
+    #[test]
+    fn test_convert_line_endings() {
+        assert_eq!(convert_to_lf(b"a\r\nb\r\n").as_ref(), b"a\nb\n");
+        assert_eq!(convert_to_lf(b"a\r\nb\n").as_ref(), b"a\r\nb\n");
+        assert_eq!(convert_to_lf(b"a\0\r\n").as_ref(), b"a\0\r\n");
+        assert!(matches!(convert_to_lf(b"a\nb\n"), Cow::Borrowed(_)));
+
+        assert_eq!(convert_to_crlf(b"a\nb\n").as_ref(), b"a\r\nb\r\n");
+        assert_eq!(convert_to_crlf(b"a\r\nb\n").as_ref(), b"a\r\nb\n");
+        assert_eq!(convert_to_crlf(b"a\0\n").as_ref(), b"a\0\n");
+        assert!(matches!(convert_to_crlf(b"a\r\nb\r\n"), Cow::Borrowed(_)));
+
+        // Round trip
+        let contents = b"a\nb\n\nc";
+        assert_eq!(
+            convert_to_lf(&convert_to_crlf(contents)).as_ref(),
+            contents.as_slice()
+        );
+    }
+}
@@ -165,8 +165,11 @@ pub fn persist_content_addressed_temp_file<P: AsRef<Path>>(
 
 #[cfg(unix)]
 mod platform {
+    use std::fs;
+    use std::fs::File;
     use std::io;
     use std::os::unix::fs::symlink;
+    use std::os::unix::fs::PermissionsExt as _;
     use std::path::Path;
 
     /// Symlinks are always available on UNIX
@@ -174,13 +177,40 @@ mod platform {
         Ok(true)
     }
 
+    /// Checks whether the file system at `dir` keeps track of the executable
+    /// bit by creating a temporary file in it. Some file systems such as
+    /// exFAT mounts report the same mode for all files.
+    pub fn check_executable_bit_support(dir: &Path) -> io::Result<bool> {
+        let temp_file = tempfile::NamedTempFile::new_in(dir)?;
+        let old_mode = temp_file.as_file().metadata()?.permissions().mode();
+        let new_mode = old_mode ^ 0o100;
+        fs::set_permissions(temp_file.path(), fs::Permissions::from_mode(new_mode))?;
+        let mode = temp_file.as_file().metadata()?.permissions().mode();
+        Ok(mode & 0o100 == new_mode & 0o100)
+    }
+
     pub fn try_symlink<P: AsRef<Path>, Q: AsRef<Path>>(original: P, link: Q) -> io::Result<()> {
         symlink(original, link)
     }
+
+    /// Makes `dest` share the contents of `src` using copy-on-write. Fails
+    /// with `EOPNOTSUPP` or similar if the file system doesn't support it.
+    #[cfg_attr(not(target_os = "linux"), allow(unused_variables))]
+    pub fn try_reflink(src: &File, dest: &File) -> io::Result<()> {
+        #[cfg(target_os = "linux")]
+        {
+            rustix::fs::ioctl_ficlone(dest, src).map_err(io::Error::from)
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            Err(io::ErrorKind::Unsupported.into())
+        }
+    }
 }
 
 #[cfg(windows)]
 mod platform {
+    use std::fs::File;
     use std::io;
     use std::os::windows::fs::symlink_file;
     use std::path::Path;
@@ -198,6 +228,11 @@ mod platform {
         Ok(developer_mode == 1)
     }
 
+    /// The executable bit isn't supported on Windows.
+    pub fn check_executable_bit_support(_dir: &Path) -> io::Result<bool> {
+        Ok(false)
+    }
+
     pub fn try_symlink<P: AsRef<Path>, Q: AsRef<Path>>(original: P, link: Q) -> io::Result<()> {
         // this will create a nonfunctional link for directories, but at the moment
         // we don't have enough information in the tree to determine whether the
@@ -207,6 +242,11 @@ mod platform {
 
         symlink_file(original, link)
     }
+
+    /// Copy-on-write cloning isn't implemented on Windows.
+    pub fn try_reflink(_src: &File, _dest: &File) -> io::Result<()> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
 }
 
 #[cfg(test)]
@@ -257,4 +297,13 @@ mod tests {
 
         assert!(persist_content_addressed_temp_file(temp_file, &target).is_ok());
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_check_executable_bit_support() {
+        let temp_dir = new_temp_dir();
+        assert!(check_executable_bit_support(temp_dir.path()).unwrap());
+        // The probe file should be cleaned up
+        assert!(temp_dir.path().read_dir().unwrap().next().is_none());
+    }
 }
@@ -28,6 +28,15 @@ use crate::diff::DiffHunk;
 use crate::diff::DiffHunkKind;
 use crate::merge::Merge;
 
+/// Returns true if the file contents look like binary data.
+///
+/// Same heuristic as used by git and `jj diff`: a file is binary if the first
+/// 8k bytes contain a null character.
+pub fn is_binary(contents: &[u8]) -> bool {
+    const PEEK_SIZE: usize = 8000;
+    contents[..PEEK_SIZE.min(contents.len())].contains(&b'\0')
+}
+
 /// A diff line which may contain small hunks originating from both sides.
 #[derive(PartialEq, Eq, Clone, Debug)]
 pub struct DiffLine<'a> {
@@ -16,6 +16,7 @@
 
 use std::collections::HashMap;
 use std::iter;
+use std::ops::Range;
 use std::path;
 use std::slice;
 
@@ -23,11 +24,17 @@ use itertools::Itertools as _;
 use once_cell::sync::Lazy;
 use thiserror::Error;
 
+use crate::backend::BackendError;
+use crate::backend::BackendResult;
+use crate::backend::TreeValue;
+use crate::dsl_util;
 use crate::dsl_util::collect_similar;
 use crate::fileset_parser;
 use crate::fileset_parser::BinaryOp;
+use crate::fileset_parser::CompareOp;
 use crate::fileset_parser::ExpressionKind;
 use crate::fileset_parser::ExpressionNode;
+pub use crate::fileset_parser::FilesetAliasesMap;
 pub use crate::fileset_parser::FilesetDiagnostics;
 pub use crate::fileset_parser::FilesetParseError;
 pub use crate::fileset_parser::FilesetParseErrorKind;
@@ -43,11 +50,16 @@ use crate::matchers::Matcher;
 use crate::matchers::NothingMatcher;
 use crate::matchers::PrefixMatcher;
 use crate::matchers::UnionMatcher;
+use crate::merge::MergedTreeValue;
+use crate::merged_tree::MergedTree;
 use crate::repo_path::RelativePathParseError;
 use crate::repo_path::RepoPath;
 use crate::repo_path::RepoPathBuf;
 use crate::repo_path::RepoPathUiConverter;
 use crate::repo_path::UiPathParseError;
+use crate::store::Store;
+use crate::str_util::GlobPattern;
+use crate::str_util::GlobPatternError;
 
 /// Error occurred during file pattern parsing.
 #[derive(Debug, Error)]
@@ -63,9 +75,25 @@ pub enum FilePatternParseError {
     RelativePath(#[from] RelativePathParseError),
     /// Failed to parse glob pattern.
     #[error(transparent)]
-    GlobPattern(#[from] glob::PatternError),
+    GlobPattern(#[from] GlobPatternError),
 }
 
+/// Names of the file pattern kinds accepted by
+/// [`FilePattern::from_str_kind()`].
+pub const FILE_PATTERN_KINDS: &[&str] = &[
+    "cwd",
+    "cwd-file",
+    "file",
+    "cwd-glob",
+    "glob",
+    "cwd-glob-i",
+    "glob-i",
+    "root",
+    "root-file",
+    "root-glob",
+    "root-glob-i",
+];
+
 /// Basic pattern to match `RepoPath`.
 #[derive(Clone, Debug)]
 pub enum FilePattern {
@@ -78,7 +106,14 @@ pub enum FilePattern {
         /// Prefix directory path where the `pattern` will be evaluated.
         dir: RepoPathBuf,
         /// Glob pattern relative to `dir`.
-        pattern: glob::Pattern,
+        pattern: GlobPattern,
+    },
+    /// Matches file (or exact) path with case-insensitive glob pattern.
+    FileGlobI {
+        /// Glob pattern relative to the workspace root. The directory part is
+        /// escaped and included in the pattern so it will also be matched
+        /// case-insensitively.
+        pattern: GlobPattern,
     },
     // TODO: add more patterns:
     // - FilesInPath: files in directory, non-recursively?
@@ -102,17 +137,22 @@ impl FilePattern {
         //   * files-in: files in directory non-recursively
         //   * name: file name component (or suffix match?)
         //   * substring: substring match?
-        // * string pattern syntax (+ case sensitivity?)
+        // * string pattern syntax
         //   * path: literal path (default) (default anchor: prefix)
         //   * glob: glob pattern (default anchor: file)
         //   * regex?
+        // * case sensitivity
+        //   * (none): case-sensitive (default)
+        //   * i: case-insensitive
         match kind {
             "cwd" => Self::cwd_prefix_path(path_converter, input),
             "cwd-file" | "file" => Self::cwd_file_path(path_converter, input),
             "cwd-glob" | "glob" => Self::cwd_file_glob(path_converter, input),
+            "cwd-glob-i" | "glob-i" => Self::cwd_file_glob_i(path_converter, input),
             "root" => Self::root_prefix_path(input),
             "root-file" => Self::root_file_path(input),
             "root-glob" => Self::root_file_glob(input),
+            "root-glob-i" => Self::root_file_glob_i(input),
             _ => Err(FilePatternParseError::InvalidKind(kind.to_owned())),
         }
     }
@@ -141,8 +181,20 @@ impl FilePattern {
         input: impl AsRef<str>,
     ) -> Result<Self, FilePatternParseError> {
         let (dir, pattern) = split_glob_path(input.as_ref());
+        let pattern_pos = dir.chars().count();
+        let dir = path_converter.parse_file_path(dir)?;
+        Self::file_glob_at(dir, pattern, pattern_pos)
+    }
+
+    /// Pattern that matches cwd-relative file path glob case-insensitively.
+    pub fn cwd_file_glob_i(
+        path_converter: &RepoPathUiConverter,
+        input: impl AsRef<str>,
+    ) -> Result<Self, FilePatternParseError> {
+        let (dir, pattern) = split_glob_path(input.as_ref());
+        let pattern_pos = dir.chars().count();
         let dir = path_converter.parse_file_path(dir)?;
-        Self::file_glob_at(dir, pattern)
+        Self::file_glob_i_at(&dir, pattern, pattern_pos)
     }
 
     /// Pattern that matches workspace-relative file (or exact) path.
@@ -161,20 +213,65 @@ impl FilePattern {
     /// Pattern that matches workspace-relative file path glob.
     pub fn root_file_glob(input: impl AsRef<str>) -> Result<Self, FilePatternParseError> {
         let (dir, pattern) = split_glob_path(input.as_ref());
+        let pattern_pos = dir.chars().count();
+        let dir = RepoPathBuf::from_relative_path(dir)?;
+        Self::file_glob_at(dir, pattern, pattern_pos)
+    }
+
+    /// Pattern that matches workspace-relative file path glob
+    /// case-insensitively.
+    pub fn root_file_glob_i(input: impl AsRef<str>) -> Result<Self, FilePatternParseError> {
+        let (dir, pattern) = split_glob_path(input.as_ref());
+        let pattern_pos = dir.chars().count();
         let dir = RepoPathBuf::from_relative_path(dir)?;
-        Self::file_glob_at(dir, pattern)
+        Self::file_glob_i_at(&dir, pattern, pattern_pos)
     }
 
-    fn file_glob_at(dir: RepoPathBuf, input: &str) -> Result<Self, FilePatternParseError> {
+    fn file_glob_at(
+        dir: RepoPathBuf,
+        input: &str,
+        input_pos: usize,
+    ) -> Result<Self, FilePatternParseError> {
         if input.is_empty() {
             return Ok(FilePattern::FilePath(dir));
         }
+        // Check the syntax before normalization so the error position points
+        // to the character in the original input.
+        GlobPattern::new(input).map_err(|err| GlobPatternError {
+            pos: input_pos + err.pos,
+            ..err
+        })?;
         // Normalize separator to '/', reject ".." which will never match
         let normalized = RepoPathBuf::from_relative_path(input)?;
-        let pattern = glob::Pattern::new(normalized.as_internal_file_string())?;
+        let pattern = GlobPattern::new(normalized.as_internal_file_string())?;
         Ok(FilePattern::FileGlob { dir, pattern })
     }
 
+    fn file_glob_i_at(
+        dir: &RepoPath,
+        input: &str,
+        input_pos: usize,
+    ) -> Result<Self, FilePatternParseError> {
+        let escaped_dir = GlobPattern::escape(dir.as_internal_file_string());
+        let source = if input.is_empty() {
+            escaped_dir
+        } else {
+            GlobPattern::new(input).map_err(|err| GlobPatternError {
+                pos: input_pos + err.pos,
+                ..err
+            })?;
+            let normalized = RepoPathBuf::from_relative_path(input)?;
+            let pattern = normalized.as_internal_file_string();
+            if dir.is_root() {
+                pattern.to_owned()
+            } else {
+                format!("{escaped_dir}/{pattern}")
+            }
+        };
+        let pattern = GlobPattern::new(&source)?;
+        Ok(FilePattern::FileGlobI { pattern })
+    }
+
     /// Returns path if this pattern represents a literal path in a workspace.
     /// Returns `None` if this is a glob pattern for example.
     pub fn as_path(&self) -> Option<&RepoPath> {
@@ -182,13 +279,65 @@ impl FilePattern {
             FilePattern::FilePath(path) => Some(path),
             FilePattern::PrefixPath(path) => Some(path),
             FilePattern::FileGlob { .. } => None,
+            FilePattern::FileGlobI { .. } => None,
         }
     }
 }
 
+/// Predicate on the tree entry of a file, which can only be evaluated against
+/// a tree.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FilePredicate {
+    /// Matches regular files whose size in bytes is in the range.
+    Size(Range<u64>),
+    /// Matches regular files with the executable bit set.
+    Executable,
+}
+
+impl FilePredicate {
+    fn function_name(&self) -> &'static str {
+        match self {
+            FilePredicate::Size(_) => "size",
+            FilePredicate::Executable => "executable",
+        }
+    }
+
+    /// Evaluates the predicate against the tree `value` at `path`. Conflicts
+    /// and entries other than regular files never match.
+    fn matches(
+        &self,
+        store: &Store,
+        path: &RepoPath,
+        value: &MergedTreeValue,
+    ) -> BackendResult<bool> {
+        let Some(Some(TreeValue::File { id, executable })) = value.as_resolved() else {
+            return Ok(false);
+        };
+        match self {
+            FilePredicate::Size(range) => {
+                let size = store.read_file_size(path, id)?;
+                Ok(range.contains(&size))
+            }
+            FilePredicate::Executable => Ok(*executable),
+        }
+    }
+}
+
+/// Error occurred while building a matcher from a fileset expression.
+#[derive(Debug, Error)]
+pub enum FilesetEvaluationError {
+    /// The expression contains a function such as `size()` which needs a tree
+    /// to be evaluated against.
+    #[error("Function `{0}()` can't be used here because there's no tree to evaluate it against")]
+    TreeRequired(&'static str),
+    /// Failed to read the tree or the files in it.
+    #[error(transparent)]
+    Backend(#[from] BackendError),
+}
+
 /// Splits `input` path into literal directory path and glob pattern.
 fn split_glob_path(input: &str) -> (&str, &str) {
-    const GLOB_CHARS: &[char] = &['?', '*', '[', ']']; // see glob::Pattern::escape()
+    const GLOB_CHARS: &[char] = &['?', '*', '[', ']', '{', '}']; // see GlobPattern::escape()
     let prefix_len = input
         .split_inclusive(path::is_separator)
         .take_while(|component| !component.contains(GLOB_CHARS))
@@ -215,6 +364,8 @@ pub enum FilesetExpression {
     Intersection(Box<FilesetExpression>, Box<FilesetExpression>),
     /// Matches the first expression, but not the second expression.
     Difference(Box<FilesetExpression>, Box<FilesetExpression>),
+    /// Matches files whose tree entries satisfy the predicate.
+    FilePredicate(FilePredicate),
 }
 
 impl FilesetExpression {
@@ -278,7 +429,8 @@ impl FilesetExpression {
             match expr {
                 FilesetExpression::None
                 | FilesetExpression::All
-                | FilesetExpression::Pattern(_) => {}
+                | FilesetExpression::Pattern(_)
+                | FilesetExpression::FilePredicate(_) => {}
                 FilesetExpression::UnionAll(exprs) => stack.extend(exprs.iter().rev()),
                 FilesetExpression::Intersection(expr1, expr2)
                 | FilesetExpression::Difference(expr1, expr2) => {
@@ -304,8 +456,33 @@ impl FilesetExpression {
     }
 
     /// Transforms the expression tree to `Matcher` object.
-    pub fn to_matcher(&self) -> Box<dyn Matcher> {
-        build_union_matcher(self.as_union_all())
+    ///
+    /// Fails if the expression contains file predicates such as `size()`,
+    /// which need a tree to be evaluated against.
+    pub fn to_matcher(&self) -> Result<Box<dyn Matcher>, FilesetEvaluationError> {
+        build_union_matcher(self.as_union_all(), None, &EverythingMatcher)
+    }
+
+    /// Returns the name of the first function such as `size()` which needs a
+    /// tree to be evaluated against.
+    pub fn tree_dependent_function_name(&self) -> Option<&'static str> {
+        self.dfs_pre().find_map(|expr| match expr {
+            FilesetExpression::FilePredicate(predicate) => Some(predicate.function_name()),
+            _ => None,
+        })
+    }
+
+    fn has_file_predicates(&self) -> bool {
+        self.tree_dependent_function_name().is_some()
+    }
+
+    /// Transforms the expression tree to `Matcher` object. File predicates
+    /// such as `size()` are evaluated against the entries of the `tree`.
+    pub fn to_matcher_in_tree(
+        &self,
+        tree: &MergedTree,
+    ) -> Result<Box<dyn Matcher>, FilesetEvaluationError> {
+        build_union_matcher(self.as_union_all(), Some(tree), &EverythingMatcher)
     }
 }
 
@@ -313,10 +490,19 @@ impl FilesetExpression {
 ///
 /// Since `Matcher` typically accepts a set of patterns to be OR-ed, this
 /// function takes a list of union `expressions` as input.
-fn build_union_matcher(expressions: &[FilesetExpression]) -> Box<dyn Matcher> {
+///
+/// File predicates are evaluated only against the files matched by `scope`.
+/// Files outside of the scope may or may not be matched by the resulting
+/// matcher, so the caller should only care about the files in the scope.
+fn build_union_matcher(
+    expressions: &[FilesetExpression],
+    tree: Option<&MergedTree>,
+    scope: &dyn Matcher,
+) -> Result<Box<dyn Matcher>, FilesetEvaluationError> {
     let mut file_paths = Vec::new();
     let mut prefix_paths = Vec::new();
     let mut file_globs = Vec::new();
+    let mut file_globs_i = Vec::new();
     let mut matchers: Vec<Option<Box<dyn Matcher>>> = Vec::new();
     for expr in expressions {
         let matcher: Box<dyn Matcher> = match expr {
@@ -328,23 +514,46 @@ fn build_union_matcher(expressions: &[FilesetExpression]) -> Box<dyn Matcher> {
                     FilePattern::FilePath(path) => file_paths.push(path),
                     FilePattern::PrefixPath(path) => prefix_paths.push(path),
                     FilePattern::FileGlob { dir, pattern } => {
-                        file_globs.push((dir, pattern.clone()));
+                        let patterns = pattern.patterns().iter().cloned();
+                        file_globs.extend(patterns.map(|pattern| (dir, pattern)));
+                    }
+                    FilePattern::FileGlobI { pattern } => {
+                        let patterns = pattern.patterns().iter().cloned();
+                        file_globs_i.extend(patterns.map(|pattern| (RepoPath::root(), pattern)));
                     }
                 }
                 continue;
             }
             // UnionAll is supposed to be flattened by caller.
-            FilesetExpression::UnionAll(exprs) => build_union_matcher(exprs),
+            FilesetExpression::UnionAll(exprs) => build_union_matcher(exprs, tree, scope)?,
             FilesetExpression::Intersection(expr1, expr2) => {
-                let m1 = build_union_matcher(expr1.as_union_all());
-                let m2 = build_union_matcher(expr2.as_union_all());
-                Box::new(IntersectionMatcher::new(m1, m2))
+                // Evaluate the side without file predicates first, so the file
+                // predicates are tested only against the files it selects.
+                if expr1.has_file_predicates() && !expr2.has_file_predicates() {
+                    let m2 = build_union_matcher(expr2.as_union_all(), tree, scope)?;
+                    let inner_scope = IntersectionMatcher::new(scope, &m2);
+                    let m1 = build_union_matcher(expr1.as_union_all(), tree, &inner_scope)?;
+                    Box::new(IntersectionMatcher::new(m1, m2))
+                } else {
+                    let m1 = build_union_matcher(expr1.as_union_all(), tree, scope)?;
+                    let inner_scope = IntersectionMatcher::new(scope, &m1);
+                    let m2 = build_union_matcher(expr2.as_union_all(), tree, &inner_scope)?;
+                    Box::new(IntersectionMatcher::new(m1, m2))
+                }
             }
             FilesetExpression::Difference(expr1, expr2) => {
-                let m1 = build_union_matcher(expr1.as_union_all());
-                let m2 = build_union_matcher(expr2.as_union_all());
+                let m1 = build_union_matcher(expr1.as_union_all(), tree, scope)?;
+                let inner_scope = IntersectionMatcher::new(scope, &m1);
+                let m2 = build_union_matcher(expr2.as_union_all(), tree, &inner_scope)?;
                 Box::new(DifferenceMatcher::new(m1, m2))
             }
+            FilesetExpression::FilePredicate(predicate) => {
+                let tree = tree.ok_or(FilesetEvaluationError::TreeRequired(
+                    predicate.function_name(),
+                ))?;
+                let paths = evaluate_file_predicate(predicate, tree, scope)?;
+                Box::new(FilesMatcher::new(paths))
+            }
         };
         matchers.push(Some(matcher));
     }
@@ -358,7 +567,28 @@ fn build_union_matcher(expressions: &[FilesetExpression]) -> Box<dyn Matcher> {
     if !file_globs.is_empty() {
         matchers.push(Some(Box::new(FileGlobsMatcher::new(file_globs))));
     }
-    union_all_matchers(&mut matchers)
+    if !file_globs_i.is_empty() {
+        let matcher = FileGlobsMatcher::new_case_insensitive(file_globs_i);
+        matchers.push(Some(Box::new(matcher)));
+    }
+    Ok(union_all_matchers(&mut matchers))
+}
+
+/// Collects the paths of the files matched by `scope` in the `tree` which
+/// satisfy the `predicate`.
+fn evaluate_file_predicate(
+    predicate: &FilePredicate,
+    tree: &MergedTree,
+    scope: &dyn Matcher,
+) -> BackendResult<Vec<RepoPathBuf>> {
+    let store = tree.store();
+    let mut paths = Vec::new();
+    for (path, value) in tree.entries_matching(scope) {
+        if predicate.matches(store, &path, &value?)? {
+            paths.push(path);
+        }
+    }
+    Ok(paths)
 }
 
 /// Concatenates all `matchers` as union.
@@ -397,9 +627,74 @@ static BUILTIN_FUNCTION_MAP: Lazy<HashMap<&'static str, FilesetFunction>> = Lazy
         function.expect_no_arguments()?;
         Ok(FilesetExpression::all())
     });
+    map.insert("size", |diagnostics, _path_converter, function| {
+        let [arg] = function.expect_exact_arguments()?;
+        let range = parse_size_range(diagnostics, function, arg)?;
+        Ok(FilesetExpression::FilePredicate(FilePredicate::Size(range)))
+    });
+    map.insert("executable", |_diagnostics, _path_converter, function| {
+        function.expect_no_arguments()?;
+        Ok(FilesetExpression::FilePredicate(FilePredicate::Executable))
+    });
     map
 });
 
+/// Parses size comparison such as `>1MiB` into range of file sizes in bytes.
+fn parse_size_range(
+    diagnostics: &mut FilesetDiagnostics,
+    function: &FunctionCallNode,
+    node: &ExpressionNode,
+) -> FilesetParseResult<Range<u64>> {
+    fileset_parser::expect_expression_with(diagnostics, node, |_diagnostics, node| {
+        let invalid_arguments = |message: String| {
+            let kind = FilesetParseErrorKind::InvalidArguments {
+                name: function.name.to_owned(),
+                message,
+            };
+            FilesetParseError::new(kind, node.span)
+        };
+        let ExpressionKind::Comparison(op, value) = &node.kind else {
+            return Err(invalid_arguments(
+                "Expected size comparison such as `>1MiB`".to_owned(),
+            ));
+        };
+        let size = parse_size(value)
+            .ok_or_else(|| invalid_arguments(format!("Invalid size `{value}`")))?;
+        let range = match op {
+            CompareOp::Greater => size.saturating_add(1)..u64::MAX,
+            CompareOp::GreaterEqual => size..u64::MAX,
+            CompareOp::Less => 0..size,
+            CompareOp::LessEqual => 0..size.saturating_add(1),
+        };
+        Ok(range)
+    })
+}
+
+/// Parses file size such as `100`, `10kB`, or `1.5MiB` in bytes.
+fn parse_size(text: &str) -> Option<u64> {
+    let number_len = text
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(number_len);
+    let multiplier: u64 = match unit {
+        "" | "B" => 1,
+        "kB" | "KB" => 1000,
+        "MB" => 1000_u64.pow(2),
+        "GB" => 1000_u64.pow(3),
+        "TB" => 1000_u64.pow(4),
+        "KiB" => 1 << 10,
+        "MiB" => 1 << 20,
+        "GiB" => 1 << 30,
+        "TiB" => 1 << 40,
+        _ => return None,
+    };
+    if let Ok(number) = number.parse::<u64>() {
+        return number.checked_mul(multiplier);
+    }
+    let size = number.parse::<f64>().ok()? * multiplier as f64;
+    (size.is_finite() && size < u64::MAX as f64).then(|| size.round() as u64)
+}
+
 fn resolve_function(
     diagnostics: &mut FilesetDiagnostics,
     path_converter: &RepoPathUiConverter,
@@ -437,8 +732,21 @@ fn resolve_expression(
             Ok(FilesetExpression::pattern(pattern))
         }
         ExpressionKind::StringPattern { kind, value } => {
-            let pattern = FilePattern::from_str_kind(path_converter, value, kind)
-                .map_err(wrap_pattern_error)?;
+            let pattern = match FilePattern::from_str_kind(path_converter, value, kind) {
+                Ok(pattern) => pattern,
+                Err(FilePatternParseError::InvalidKind(kind)) => {
+                    let span = node.span;
+                    let kind_end = span.start() + kind.len() + 1; // including ':'
+                    let kind_span =
+                        pest::Span::new(span.get_input(), span.start(), kind_end).unwrap_or(span);
+                    let candidates = collect_similar(&kind, FILE_PATTERN_KINDS);
+                    return Err(FilesetParseError::new(
+                        FilesetParseErrorKind::NoSuchPatternKind { kind, candidates },
+                        kind_span,
+                    ));
+                }
+                Err(err) => return Err(wrap_pattern_error(err)),
+            };
             Ok(FilesetExpression::pattern(pattern))
         }
         ExpressionKind::Unary(op, arg_node) => {
@@ -465,6 +773,15 @@ fn resolve_expression(
         ExpressionKind::FunctionCall(function) => {
             resolve_function(diagnostics, path_converter, function)
         }
+        ExpressionKind::Comparison(..) => Err(FilesetParseError::expression(
+            "Comparison isn't allowed here",
+            node.span,
+        )),
+        ExpressionKind::AliasExpanded(..) => {
+            fileset_parser::expect_expression_with(diagnostics, node, |diagnostics, node| {
+                resolve_expression(diagnostics, path_converter, node)
+            })
+        }
     }
 }
 
@@ -472,11 +789,14 @@ fn resolve_expression(
 pub fn parse(
     diagnostics: &mut FilesetDiagnostics,
     text: &str,
+    aliases_map: &FilesetAliasesMap,
     path_converter: &RepoPathUiConverter,
 ) -> FilesetParseResult<FilesetExpression> {
     let node = fileset_parser::parse_program(text)?;
+    let node = dsl_util::expand_aliases(node, aliases_map)?;
     // TODO: add basic tree substitution pass to eliminate redundant expressions
     resolve_expression(diagnostics, path_converter, &node)
+        .map_err(|err| err.extend_function_candidates(aliases_map.function_names()))
 }
 
 /// Parses text into `FilesetExpression` with bare string fallback.
@@ -486,17 +806,22 @@ pub fn parse(
 pub fn parse_maybe_bare(
     diagnostics: &mut FilesetDiagnostics,
     text: &str,
+    aliases_map: &FilesetAliasesMap,
     path_converter: &RepoPathUiConverter,
 ) -> FilesetParseResult<FilesetExpression> {
     let node = fileset_parser::parse_program_or_bare_string(text)?;
+    let node = dsl_util::expand_aliases(node, aliases_map)?;
     // TODO: add basic tree substitution pass to eliminate redundant expressions
     resolve_expression(diagnostics, path_converter, &node)
+        .map_err(|err| err.extend_function_candidates(aliases_map.function_names()))
 }
 
 #[cfg(test)]
 mod tests {
     use std::path::PathBuf;
 
+    use assert_matches::assert_matches;
+
     use super::*;
 
     fn repo_path_buf(value: impl Into<String>) -> RepoPathBuf {
@@ -529,7 +854,15 @@ mod tests {
             cwd: PathBuf::from("/ws/cur"),
             base: PathBuf::from("/ws"),
         };
-        let parse = |text| parse_maybe_bare(&mut FilesetDiagnostics::new(), text, &path_converter);
+        let aliases_map = FilesetAliasesMap::new();
+        let parse = |text| {
+            parse_maybe_bare(
+                &mut FilesetDiagnostics::new(),
+                text,
+                &aliases_map,
+                &path_converter,
+            )
+        };
 
         // cwd-relative patterns
         insta::assert_debug_snapshot!(
@@ -574,7 +907,15 @@ mod tests {
             cwd: PathBuf::from("/ws/cur*"),
             base: PathBuf::from("/ws"),
         };
-        let parse = |text| parse_maybe_bare(&mut FilesetDiagnostics::new(), text, &path_converter);
+        let aliases_map = FilesetAliasesMap::new();
+        let parse = |text| {
+            parse_maybe_bare(
+                &mut FilesetDiagnostics::new(),
+                text,
+                &aliases_map,
+                &path_converter,
+            )
+        };
 
         // cwd-relative, without meta characters
         insta::assert_debug_snapshot!(
@@ -598,11 +939,7 @@ mod tests {
         Pattern(
             FileGlob {
                 dir: "cur*",
-                pattern: Pattern {
-                    original: "*",
-                    tokens: _,
-                    is_recursive: false,
-                },
+                pattern: GlobPattern("*"),
             },
         )
         "#);
@@ -611,11 +948,7 @@ mod tests {
         Pattern(
             FileGlob {
                 dir: "cur*",
-                pattern: Pattern {
-                    original: "*",
-                    tokens: _,
-                    is_recursive: false,
-                },
+                pattern: GlobPattern("*"),
             },
         )
         "#);
@@ -624,11 +957,7 @@ mod tests {
         Pattern(
             FileGlob {
                 dir: "",
-                pattern: Pattern {
-                    original: "*",
-                    tokens: _,
-                    is_recursive: false,
-                },
+                pattern: GlobPattern("*"),
             },
         )
         "#);
@@ -638,11 +967,7 @@ mod tests {
         Pattern(
             FileGlob {
                 dir: "cur*",
-                pattern: Pattern {
-                    original: "**",
-                    tokens: _,
-                    is_recursive: true,
-                },
+                pattern: GlobPattern("**"),
             },
         )
         "#);
@@ -651,11 +976,7 @@ mod tests {
         Pattern(
             FileGlob {
                 dir: "foo",
-                pattern: Pattern {
-                    original: "b?r/baz",
-                    tokens: _,
-                    is_recursive: false,
-                },
+                pattern: GlobPattern("b?r/baz"),
             },
         )
         "#);
@@ -671,11 +992,7 @@ mod tests {
             Pattern(
                 FileGlob {
                     dir: "foo",
-                    pattern: Pattern {
-                        original: "*/bar",
-                        tokens: _,
-                        is_recursive: false,
-                    },
+                    pattern: GlobPattern("*/bar"),
                 },
             )
             "#);
@@ -685,11 +1002,7 @@ mod tests {
             Pattern(
                 FileGlob {
                     dir: "cur*",
-                    pattern: Pattern {
-                        original: "..\\foo\\*\\bar",
-                        tokens: _,
-                        is_recursive: false,
-                    },
+                    pattern: GlobPattern("..\\foo\\*\\bar"),
                 },
             )
             "#);
@@ -712,11 +1025,7 @@ mod tests {
         Pattern(
             FileGlob {
                 dir: "",
-                pattern: Pattern {
-                    original: "*",
-                    tokens: _,
-                    is_recursive: false,
-                },
+                pattern: GlobPattern("*"),
             },
         )
         "#);
@@ -725,18 +1034,164 @@ mod tests {
         Pattern(
             FileGlob {
                 dir: "foo/bar",
-                pattern: Pattern {
-                    original: "b[az]",
-                    tokens: _,
-                        ),
-                    ],
-                    is_recursive: false,
-                },
+                pattern: GlobPattern("b[az]"),
             },
         )
         "#);
         assert!(parse(r#"root-glob:"../*""#).is_err());
         assert!(parse(r#"root-glob:"/*""#).is_err());
+
+        // brace alternatives
+        insta::assert_debug_snapshot!(
+            parse(r#"root-glob:"foo/{bar,baz}/*.rs""#).unwrap(), @r#"
+        Pattern(
+            FileGlob {
+                dir: "foo",
+                pattern: GlobPattern("{bar,baz}/*.rs"),
+            },
+        )
+        "#);
+        insta::assert_debug_snapshot!(
+            parse("glob:*.{rs,md}").unwrap(), @r#"
+        Pattern(
+            FileGlob {
+                dir: "cur*",
+                pattern: GlobPattern("*.{rs,md}"),
+            },
+        )
+        "#);
+        // literal braces
+        insta::assert_debug_snapshot!(
+            parse(r#"root-glob:"foo/[{]bar[}]""#).unwrap(), @r#"
+        Pattern(
+            FileGlob {
+                dir: "foo",
+                pattern: GlobPattern("[{]bar[}]"),
+            },
+        )
+        "#);
+
+        // error position is relative to the input pattern
+        let glob_error = |input: &str| match FilePattern::root_file_glob(input) {
+            Err(FilePatternParseError::GlobPattern(err)) => err,
+            result => panic!("unexpected result: {result:?}"),
+        };
+        assert_eq!(glob_error("foo/*.{rs").pos, 6);
+        assert_eq!(glob_error("foo/./*.rs}").pos, 10);
+        assert_eq!(glob_error("foo/{a,b}/[").pos, 10);
+
+        // case-insensitive, literal directory part is escaped
+        insta::assert_debug_snapshot!(
+            parse(r#"glob-i:"Foo""#).unwrap(), @r#"
+        Pattern(
+            FileGlobI {
+                pattern: GlobPattern("cur[*]/Foo"),
+            },
+        )
+        "#);
+        insta::assert_debug_snapshot!(
+            parse(r#"cwd-glob-i:"../foo/*.RS""#).unwrap(), @r#"
+        Pattern(
+            FileGlobI {
+                pattern: GlobPattern("foo/*.RS"),
+            },
+        )
+        "#);
+        insta::assert_debug_snapshot!(
+            parse(r#"root-glob-i:"*.TXT""#).unwrap(), @r#"
+        Pattern(
+            FileGlobI {
+                pattern: GlobPattern("*.TXT"),
+            },
+        )
+        "#);
+        insta::assert_debug_snapshot!(
+            parse(r#"root-glob-i:"Foo/Bar""#).unwrap(), @r#"
+        Pattern(
+            FileGlobI {
+                pattern: GlobPattern("Foo/Bar"),
+            },
+        )
+        "#);
+        assert!(parse(r#"root-glob-i:"../*""#).is_err());
+        let glob_i_error = |input: &str| match FilePattern::root_file_glob_i(input) {
+            Err(FilePatternParseError::GlobPattern(err)) => err,
+            result => panic!("unexpected result: {result:?}"),
+        };
+        assert_eq!(glob_i_error("foo/*.{rs").pos, 6);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let path_converter = RepoPathUiConverter::Fs {
+            cwd: PathBuf::from("/ws/cur"),
+            base: PathBuf::from("/ws"),
+        };
+        let aliases_map = FilesetAliasesMap::new();
+        let parse = |text| {
+            parse_maybe_bare(
+                &mut FilesetDiagnostics::new(),
+                text,
+                &aliases_map,
+                &path_converter,
+            )
+        };
+
+        // unknown pattern kind is reported at the prefix
+        let err = parse("glb:**/*.rs").unwrap_err();
+        assert_eq!(
+            *err.kind(),
+            FilesetParseErrorKind::NoSuchPatternKind {
+                kind: "glb".to_owned(),
+                candidates: vec!["glob".to_owned(), "glob-i".to_owned()],
+            }
+        );
+        assert_eq!(err.span(), 0..4);
+        let err = parse(r#"foo | xyz:"bar""#).unwrap_err();
+        assert_eq!(
+            *err.kind(),
+            FilesetParseErrorKind::NoSuchPatternKind {
+                kind: "xyz".to_owned(),
+                candidates: vec![],
+            }
+        );
+        assert_eq!(err.span(), 6..10);
+
+        // invalid pattern is reported at the whole pattern
+        let err = parse(r#"foo | root:"..""#).unwrap_err();
+        assert_matches!(err.kind(), FilesetParseErrorKind::Expression(_));
+        assert_eq!(err.span(), 6..15);
+
+        // syntax error is reported at the position
+        let err = parse("foo | (bar").unwrap_err();
+        assert_eq!(*err.kind(), FilesetParseErrorKind::SyntaxError);
+        assert_eq!(err.span(), 10..10);
+    }
+
+    #[test]
+    fn test_parse_bare_path_with_glob_chars() {
+        let path_converter = RepoPathUiConverter::Fs {
+            cwd: PathBuf::from("/ws/cur"),
+            base: PathBuf::from("/ws"),
+        };
+        let aliases_map = FilesetAliasesMap::new();
+        let parse = |text| {
+            let mut diagnostics = FilesetDiagnostics::new();
+            let expression =
+                parse_maybe_bare(&mut diagnostics, text, &aliases_map, &path_converter).unwrap();
+            assert!(diagnostics.is_empty());
+            expression
+        };
+
+        // bare path is matched literally
+        assert_matches!(
+            parse("src/*.rs"),
+            FilesetExpression::Pattern(FilePattern::PrefixPath(_))
+        );
+        assert_matches!(
+            parse("glob:src/*.rs"),
+            FilesetExpression::Pattern(FilePattern::FileGlob { .. })
+        );
     }
 
     #[test]
@@ -747,7 +1202,15 @@ mod tests {
             cwd: PathBuf::from("/ws/cur"),
             base: PathBuf::from("/ws"),
         };
-        let parse = |text| parse_maybe_bare(&mut FilesetDiagnostics::new(), text, &path_converter);
+        let aliases_map = FilesetAliasesMap::new();
+        let parse = |text| {
+            parse_maybe_bare(
+                &mut FilesetDiagnostics::new(),
+                text,
+                &aliases_map,
+                &path_converter,
+            )
+        };
 
         insta::assert_debug_snapshot!(parse("all()").unwrap(), @"All");
         insta::assert_debug_snapshot!(parse("none()").unwrap(), @"None");
@@ -767,6 +1230,89 @@ mod tests {
         "###);
     }
 
+    #[test]
+    fn test_parse_file_predicate_function() {
+        let path_converter = RepoPathUiConverter::Fs {
+            cwd: PathBuf::from("/ws/cur"),
+            base: PathBuf::from("/ws"),
+        };
+        let aliases_map = FilesetAliasesMap::new();
+        let parse = |text| {
+            parse_maybe_bare(
+                &mut FilesetDiagnostics::new(),
+                text,
+                &aliases_map,
+                &path_converter,
+            )
+        };
+        let parse_predicate = |text| match parse(text).unwrap() {
+            FilesetExpression::FilePredicate(predicate) => predicate,
+            expr => panic!("unexpected expression: {expr:?}"),
+        };
+        let invalid_arguments = |message: &str| FilesetParseErrorKind::InvalidArguments {
+            name: "size".to_owned(),
+            message: message.to_owned(),
+        };
+
+        assert_eq!(
+            parse_predicate("size(>1MiB)"),
+            FilePredicate::Size((1 << 20) + 1..u64::MAX)
+        );
+        assert_eq!(
+            parse_predicate("size(>= 10kB)"),
+            FilePredicate::Size(10_000..u64::MAX)
+        );
+        assert_eq!(parse_predicate("size(<100)"), FilePredicate::Size(0..100));
+        assert_eq!(
+            parse_predicate("size(<=1.5KiB)"),
+            FilePredicate::Size(0..1537)
+        );
+        assert_eq!(parse_predicate("executable()"), FilePredicate::Executable);
+        assert_eq!(
+            *parse("size(1MiB)").unwrap_err().kind(),
+            invalid_arguments("Expected size comparison such as `>1MiB`")
+        );
+        assert_eq!(
+            *parse("size(>1MB_)").unwrap_err().kind(),
+            invalid_arguments("Invalid size `1MB_`")
+        );
+        assert_eq!(
+            *parse("size(>1.2.3)").unwrap_err().kind(),
+            invalid_arguments("Invalid size `1.2.3`")
+        );
+        assert!(parse("executable(x)").is_err());
+
+        // Predicates can't be evaluated without tree
+        assert!(matches!(
+            parse("~executable()").unwrap().to_matcher(),
+            Err(FilesetEvaluationError::TreeRequired("executable"))
+        ));
+        assert!(matches!(
+            parse("foo & size(>1)").unwrap().to_matcher(),
+            Err(FilesetEvaluationError::TreeRequired("size"))
+        ));
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("0"), Some(0));
+        assert_eq!(parse_size("123B"), Some(123));
+        assert_eq!(parse_size("2kB"), Some(2_000));
+        assert_eq!(parse_size("2KB"), Some(2_000));
+        assert_eq!(parse_size("2KiB"), Some(2_048));
+        assert_eq!(parse_size("3MB"), Some(3_000_000));
+        assert_eq!(parse_size("3MiB"), Some(3 << 20));
+        assert_eq!(parse_size("1GiB"), Some(1 << 30));
+        assert_eq!(parse_size("1TB"), Some(1_000_000_000_000));
+        assert_eq!(parse_size("0.5KiB"), Some(512));
+        assert_eq!(parse_size(".5kB"), Some(500));
+        assert_eq!(parse_size(""), None);
+        assert_eq!(parse_size("MiB"), None);
+        assert_eq!(parse_size("1mib"), None);
+        assert_eq!(parse_size("99999999999999999999"), None);
+        assert_eq!(parse_size("99999999TiB"), None);
+    }
+
     #[test]
     fn test_parse_compound_expression() {
         let settings = insta_settings();
@@ -775,7 +1321,15 @@ mod tests {
             cwd: PathBuf::from("/ws/cur"),
             base: PathBuf::from("/ws"),
         };
-        let parse = |text| parse_maybe_bare(&mut FilesetDiagnostics::new(), text, &path_converter);
+        let aliases_map = FilesetAliasesMap::new();
+        let parse = |text| {
+            parse_maybe_bare(
+                &mut FilesetDiagnostics::new(),
+                text,
+                &aliases_map,
+                &path_converter,
+            )
+        };
 
         insta::assert_debug_snapshot!(parse("~x").unwrap(), @r###"
         Difference(
@@ -805,6 +1359,67 @@ mod tests {
         "###);
     }
 
+    #[test]
+    fn test_parse_alias() {
+        let path_converter = RepoPathUiConverter::Fs {
+            cwd: PathBuf::from("/ws/cur"),
+            base: PathBuf::from("/ws"),
+        };
+        let mut aliases_map = FilesetAliasesMap::new();
+        aliases_map.insert("src", "root:src ~ GENERATED").unwrap();
+        aliases_map.insert("GENERATED", "glob:**/*.gen").unwrap();
+        aliases_map.insert("big(x)", "size(x)").unwrap();
+        aliases_map.insert("BAD", "all(x)").unwrap();
+        aliases_map.insert("LOOP", "x | LOOP").unwrap();
+        let parse = |text| {
+            parse_maybe_bare(
+                &mut FilesetDiagnostics::new(),
+                text,
+                &aliases_map,
+                &path_converter,
+            )
+        };
+        let parse_debug = |text| format!("{:?}", parse(text).unwrap());
+
+        // Alias in alias
+        assert_eq!(parse_debug("src"), parse_debug("root:src ~ glob:**/*.gen"));
+        assert_eq!(
+            parse_debug("src | big(>1MiB)"),
+            parse_debug("root:src ~ glob:**/*.gen | size(>1MiB)")
+        );
+        // Path-like names aren't substituted
+        assert_eq!(parse_debug("src/foo"), parse_debug("cwd:src/foo"));
+
+        // Error in alias is reported with the expansion chain
+        let err = parse("BAD").unwrap_err();
+        assert_eq!(
+            *err.kind(),
+            FilesetParseErrorKind::InAliasExpansion("BAD".to_owned())
+        );
+        assert_matches!(
+            err.origin().unwrap().kind(),
+            FilesetParseErrorKind::InvalidArguments { name, .. } if name == "all"
+        );
+        let err = parse("LOOP").unwrap_err();
+        assert_eq!(
+            *err.kind(),
+            FilesetParseErrorKind::InAliasExpansion("LOOP".to_owned())
+        );
+        assert_eq!(
+            *err.origin().unwrap().kind(),
+            FilesetParseErrorKind::RecursiveAlias("LOOP".to_owned())
+        );
+
+        // Function aliases are suggested
+        assert_eq!(
+            *parse("bug(>1)").unwrap_err().kind(),
+            FilesetParseErrorKind::NoSuchFunction {
+                name: "bug".to_owned(),
+                candidates: vec!["big".to_owned()],
+            }
+        );
+    }
+
     #[test]
     fn test_explicit_paths() {
         let collect = |expr: &FilesetExpression| -> Vec<RepoPathBuf> {
@@ -843,10 +1458,10 @@ mod tests {
         let settings = insta_settings();
         let _guard = settings.bind_to_scope();
 
-        insta::assert_debug_snapshot!(FilesetExpression::none().to_matcher(), @"NothingMatcher");
-        insta::assert_debug_snapshot!(FilesetExpression::all().to_matcher(), @"EverythingMatcher");
+        insta::assert_debug_snapshot!(FilesetExpression::none().to_matcher().unwrap(), @"NothingMatcher");
+        insta::assert_debug_snapshot!(FilesetExpression::all().to_matcher().unwrap(), @"EverythingMatcher");
         insta::assert_debug_snapshot!(
-            FilesetExpression::file_path(repo_path_buf("foo")).to_matcher(),
+            FilesetExpression::file_path(repo_path_buf("foo")).to_matcher().unwrap(),
             @r###"
         FilesMatcher {
             tree: Dir {
@@ -855,7 +1470,7 @@ mod tests {
         }
         "###);
         insta::assert_debug_snapshot!(
-            FilesetExpression::prefix_path(repo_path_buf("foo")).to_matcher(),
+            FilesetExpression::prefix_path(repo_path_buf("foo")).to_matcher().unwrap(),
             @r###"
         PrefixMatcher {
             tree: Dir {
@@ -872,11 +1487,24 @@ mod tests {
         let glob_expr = |dir: &str, pattern: &str| {
             FilesetExpression::pattern(FilePattern::FileGlob {
                 dir: repo_path_buf(dir),
-                pattern: glob::Pattern::new(pattern).unwrap(),
+                pattern: GlobPattern::new(pattern).unwrap(),
             })
         };
 
-        insta::assert_debug_snapshot!(glob_expr("", "*").to_matcher(), @r#"
+        let matcher = glob_expr("foo", "*.{rs,md}").to_matcher().unwrap();
+        assert!(matcher.matches(&repo_path_buf("foo/a.rs")));
+        assert!(matcher.matches(&repo_path_buf("foo/a.md")));
+        assert!(!matcher.matches(&repo_path_buf("foo/a.toml")));
+        assert!(!matcher.matches(&repo_path_buf("a.rs")));
+
+        let pattern = FilePattern::root_file_glob_i("foo/*.{RS,md}").unwrap();
+        let matcher = FilesetExpression::pattern(pattern).to_matcher().unwrap();
+        assert!(matcher.matches(&repo_path_buf("foo/a.rs")));
+        assert!(matcher.matches(&repo_path_buf("FOO/A.Md")));
+        assert!(!matcher.matches(&repo_path_buf("foo/a.toml")));
+        assert!(!matcher.matches(&repo_path_buf("foo/bar/a.rs")));
+
+        insta::assert_debug_snapshot!(glob_expr("", "*").to_matcher().unwrap(), @r#"
         FileGlobsMatcher {
             tree: [
                 Pattern {
@@ -885,12 +1513,13 @@ mod tests {
                     is_recursive: false,
                 },
             ] {},
+            ignore_case: false,
         }
         "#);
 
         let expr =
             FilesetExpression::union_all(vec![glob_expr("foo", "*"), glob_expr("foo/bar", "*")]);
-        insta::assert_debug_snapshot!(expr.to_matcher(), @r#"
+        insta::assert_debug_snapshot!(expr.to_matcher().unwrap(), @r#"
         FileGlobsMatcher {
             tree: [] {
                 "foo": [
@@ -909,6 +1538,7 @@ mod tests {
                     ] {},
                 },
             },
+            ignore_case: false,
         }
         "#);
     }
@@ -922,7 +1552,7 @@ mod tests {
             FilesetExpression::file_path(repo_path_buf("foo")),
             FilesetExpression::file_path(repo_path_buf("foo/bar")),
         ]);
-        insta::assert_debug_snapshot!(expr.to_matcher(), @r###"
+        insta::assert_debug_snapshot!(expr.to_matcher().unwrap(), @r###"
         FilesMatcher {
             tree: Dir {
                 "foo": File {
@@ -936,7 +1566,7 @@ mod tests {
             FilesetExpression::prefix_path(repo_path_buf("bar")),
             FilesetExpression::prefix_path(repo_path_buf("bar/baz")),
         ]);
-        insta::assert_debug_snapshot!(expr.to_matcher(), @r###"
+        insta::assert_debug_snapshot!(expr.to_matcher().unwrap(), @r###"
         PrefixMatcher {
             tree: Dir {
                 "bar": Prefix {
@@ -956,7 +1586,7 @@ mod tests {
             FilesetExpression::file_path(repo_path_buf("foo")),
             FilesetExpression::prefix_path(repo_path_buf("bar")),
         ]);
-        insta::assert_debug_snapshot!(expr.to_matcher(), @r###"
+        insta::assert_debug_snapshot!(expr.to_matcher().unwrap(), @r###"
         UnionMatcher {
             input1: FilesMatcher {
                 tree: Dir {
@@ -978,11 +1608,11 @@ mod tests {
         let _guard = settings.bind_to_scope();
 
         let expr = FilesetExpression::UnionAll(vec![]);
-        insta::assert_debug_snapshot!(expr.to_matcher(), @"NothingMatcher");
+        insta::assert_debug_snapshot!(expr.to_matcher().unwrap(), @"NothingMatcher");
 
         let expr =
             FilesetExpression::UnionAll(vec![FilesetExpression::None, FilesetExpression::All]);
-        insta::assert_debug_snapshot!(expr.to_matcher(), @r###"
+        insta::assert_debug_snapshot!(expr.to_matcher().unwrap(), @r###"
         UnionMatcher {
             input1: NothingMatcher,
             input2: EverythingMatcher,
@@ -1001,7 +1631,7 @@ mod tests {
             FilesetExpression::file_path(repo_path_buf("foo")),
             FilesetExpression::prefix_path(repo_path_buf("bar")),
         ]);
-        insta::assert_debug_snapshot!(expr.to_matcher(), @r###"
+        insta::assert_debug_snapshot!(expr.to_matcher().unwrap(), @r###"
         UnionMatcher {
             input1: UnionMatcher {
                 input1: IntersectionMatcher {
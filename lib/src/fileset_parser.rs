@@ -15,6 +15,8 @@
 //! Parser for the fileset language.
 
 use std::error;
+use std::mem;
+use std::ops::Range;
 
 use itertools::Itertools as _;
 use once_cell::sync::Lazy;
@@ -27,7 +29,17 @@ use pest_derive::Parser;
 use thiserror::Error;
 
 use crate::dsl_util;
+use crate::dsl_util::collect_similar;
+use crate::dsl_util::AliasDeclaration;
+use crate::dsl_util::AliasDeclarationParser;
+use crate::dsl_util::AliasDefinitionParser;
+use crate::dsl_util::AliasExpandError;
+use crate::dsl_util::AliasExpandableExpression;
+use crate::dsl_util::AliasId;
+use crate::dsl_util::AliasesMap;
 use crate::dsl_util::Diagnostics;
+use crate::dsl_util::ExpressionFolder;
+use crate::dsl_util::FoldableExpression;
 use crate::dsl_util::InvalidArguments;
 use crate::dsl_util::StringLiteralParser;
 
@@ -45,6 +57,8 @@ impl Rule {
         match self {
             Rule::EOI => None,
             Rule::whitespace => None,
+            Rule::identifier_char => None,
+            Rule::identifier_braces => None,
             Rule::identifier => None,
             Rule::strict_identifier_part => None,
             Rule::strict_identifier => None,
@@ -62,15 +76,21 @@ impl Rule {
             Rule::difference_op => Some("~"),
             Rule::prefix_ops => None,
             Rule::infix_ops => None,
+            Rule::comparison_op => None,
+            Rule::comparison => None,
             Rule::function => None,
             Rule::function_name => None,
+            Rule::function_argument => None,
             Rule::function_arguments => None,
+            Rule::formal_parameters => None,
             Rule::string_pattern => None,
             Rule::bare_string_pattern => None,
             Rule::primary => None,
             Rule::expression => None,
             Rule::program => None,
             Rule::program_or_bare_string => None,
+            Rule::function_alias_declaration => None,
+            Rule::alias_declaration => None,
         }
     }
 }
@@ -97,6 +117,11 @@ pub struct FilesetParseError {
 pub enum FilesetParseErrorKind {
     #[error("Syntax error")]
     SyntaxError,
+    #[error("Invalid file pattern kind `{kind}:`")]
+    NoSuchPatternKind {
+        kind: String,
+        candidates: Vec<String>,
+    },
     #[error("Function `{name}` doesn't exist")]
     NoSuchFunction {
         name: String,
@@ -104,8 +129,16 @@ pub enum FilesetParseErrorKind {
     },
     #[error("Function `{name}`: {message}")]
     InvalidArguments { name: String, message: String },
+    #[error("Redefinition of function parameter")]
+    RedefinedFunctionParameter,
     #[error("{0}")]
     Expression(String),
+    #[error("In alias `{0}`")]
+    InAliasExpansion(String),
+    #[error("In function parameter `{0}`")]
+    InParameterExpansion(String),
+    #[error("Alias `{0}` expanded recursively")]
+    RecursiveAlias(String),
 }
 
 impl FilesetParseError {
@@ -135,10 +168,59 @@ impl FilesetParseError {
         FilesetParseError::new(FilesetParseErrorKind::Expression(message.into()), span)
     }
 
+    /// If this is a `NoSuchFunction` error, expands the candidates list with
+    /// the given `other_functions`.
+    pub(super) fn extend_function_candidates<I>(mut self, other_functions: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        if let FilesetParseErrorKind::NoSuchFunction { name, candidates } = &mut self.kind {
+            let other_candidates = collect_similar(name, other_functions);
+            *candidates = itertools::merge(mem::take(candidates), other_candidates)
+                .dedup()
+                .collect();
+        }
+        self
+    }
+
     /// Category of the underlying error.
     pub fn kind(&self) -> &FilesetParseErrorKind {
         &self.kind
     }
+
+    /// Byte range in the input text where the error occurred.
+    pub fn span(&self) -> Range<usize> {
+        match self.pest_error.location {
+            pest::error::InputLocation::Pos(pos) => pos..pos,
+            pest::error::InputLocation::Span((start, end)) => start..end,
+        }
+    }
+
+    /// Original parsing error which typically occurred in an alias expression.
+    pub fn origin(&self) -> Option<&Self> {
+        self.source.as_ref().and_then(|e| e.downcast_ref())
+    }
+}
+
+impl AliasExpandError for FilesetParseError {
+    fn invalid_arguments(err: InvalidArguments<'_>) -> Self {
+        err.into()
+    }
+
+    fn recursive_expansion(id: AliasId<'_>, span: pest::Span<'_>) -> Self {
+        Self::new(FilesetParseErrorKind::RecursiveAlias(id.to_string()), span)
+    }
+
+    fn within_alias_expansion(self, id: AliasId<'_>, span: pest::Span<'_>) -> Self {
+        let kind = match id {
+            AliasId::Symbol(_) | AliasId::Function(..) => {
+                FilesetParseErrorKind::InAliasExpansion(id.to_string())
+            }
+            AliasId::Parameter(_) => FilesetParseErrorKind::InParameterExpansion(id.to_string()),
+        };
+        Self::new(kind, span).with_source(self)
+    }
 }
 
 impl From<pest::error::Error<Rule>> for FilesetParseError {
@@ -182,6 +264,56 @@ pub enum ExpressionKind<'i> {
     /// `x | y | ..`
     UnionAll(Vec<ExpressionNode<'i>>),
     FunctionCall(Box<FunctionCallNode<'i>>),
+    /// `>x`, `<=x`, etc. Only allowed as a function argument.
+    Comparison(CompareOp, &'i str),
+    /// Identity node to preserve the span in the source text.
+    AliasExpanded(AliasId<'i>, Box<ExpressionNode<'i>>),
+}
+
+impl<'i> FoldableExpression<'i> for ExpressionKind<'i> {
+    fn fold<F>(self, folder: &mut F, span: pest::Span<'i>) -> Result<Self, F::Error>
+    where
+        F: ExpressionFolder<'i, Self> + ?Sized,
+    {
+        match self {
+            ExpressionKind::Identifier(name) => folder.fold_identifier(name, span),
+            ExpressionKind::String(_)
+            | ExpressionKind::StringPattern { .. }
+            | ExpressionKind::Comparison(..) => Ok(self),
+            ExpressionKind::Unary(op, arg) => {
+                let arg = Box::new(folder.fold_expression(*arg)?);
+                Ok(ExpressionKind::Unary(op, arg))
+            }
+            ExpressionKind::Binary(op, lhs, rhs) => {
+                let lhs = Box::new(folder.fold_expression(*lhs)?);
+                let rhs = Box::new(folder.fold_expression(*rhs)?);
+                Ok(ExpressionKind::Binary(op, lhs, rhs))
+            }
+            ExpressionKind::UnionAll(nodes) => {
+                let nodes = dsl_util::fold_expression_nodes(folder, nodes)?;
+                Ok(ExpressionKind::UnionAll(nodes))
+            }
+            ExpressionKind::FunctionCall(function) => folder.fold_function_call(function, span),
+            ExpressionKind::AliasExpanded(id, subst) => {
+                let subst = Box::new(folder.fold_expression(*subst)?);
+                Ok(ExpressionKind::AliasExpanded(id, subst))
+            }
+        }
+    }
+}
+
+impl<'i> AliasExpandableExpression<'i> for ExpressionKind<'i> {
+    fn identifier(name: &'i str) -> Self {
+        ExpressionKind::Identifier(name)
+    }
+
+    fn function_call(function: Box<FunctionCallNode<'i>>) -> Self {
+        ExpressionKind::FunctionCall(function)
+    }
+
+    fn alias_expanded(id: AliasId<'i>, subst: Box<ExpressionNode<'i>>) -> Self {
+        ExpressionKind::AliasExpanded(id, subst)
+    }
 }
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
@@ -198,6 +330,18 @@ pub enum BinaryOp {
     Difference,
 }
 
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum CompareOp {
+    /// `>`
+    Greater,
+    /// `>=`
+    GreaterEqual,
+    /// `<`
+    Less,
+    /// `<=`
+    LessEqual,
+}
+
 pub type ExpressionNode<'i> = dsl_util::ExpressionNode<'i, ExpressionKind<'i>>;
 pub type FunctionCallNode<'i> = dsl_util::FunctionCallNode<'i, ExpressionKind<'i>>;
 
@@ -225,7 +369,10 @@ fn parse_function_call_node(pair: Pair<Rule>) -> FilesetParseResult<FunctionCall
     let name = name_pair.as_str();
     let args = args_pair
         .into_inner()
-        .map(parse_expression_node)
+        .map(|pair| match pair.as_rule() {
+            Rule::comparison => Ok(parse_comparison_node(pair)),
+            _ => parse_expression_node(pair),
+        })
         .try_collect()?;
     Ok(FunctionCallNode {
         name,
@@ -236,6 +383,22 @@ fn parse_function_call_node(pair: Pair<Rule>) -> FilesetParseResult<FunctionCall
     })
 }
 
+fn parse_comparison_node(pair: Pair<Rule>) -> ExpressionNode {
+    assert_eq!(pair.as_rule(), Rule::comparison);
+    let span = pair.as_span();
+    let (op, value) = pair.into_inner().collect_tuple().unwrap();
+    assert_eq!(op.as_rule(), Rule::comparison_op);
+    assert_eq!(value.as_rule(), Rule::identifier);
+    let op_kind = match op.as_str() {
+        ">" => CompareOp::Greater,
+        ">=" => CompareOp::GreaterEqual,
+        "<" => CompareOp::Less,
+        "<=" => CompareOp::LessEqual,
+        s => panic!("unexpected comparison operator {s:?}"),
+    };
+    ExpressionNode::new(ExpressionKind::Comparison(op_kind, value.as_str()), span)
+}
+
 fn parse_as_string_literal(pair: Pair<Rule>) -> String {
     match pair.as_rule() {
         Rule::identifier => pair.as_str().to_owned(),
@@ -346,6 +509,77 @@ pub fn parse_program_or_bare_string(text: &str) -> FilesetParseResult<Expression
     Ok(ExpressionNode::new(expr, span))
 }
 
+/// Fileset aliases loaded from the `fileset-aliases` config table.
+pub type FilesetAliasesMap = AliasesMap<FilesetAliasParser, String>;
+
+/// Parser for fileset alias declarations and definitions.
+#[derive(Clone, Debug, Default)]
+pub struct FilesetAliasParser;
+
+impl AliasDeclarationParser for FilesetAliasParser {
+    type Error = FilesetParseError;
+
+    fn parse_declaration(&self, source: &str) -> Result<AliasDeclaration, Self::Error> {
+        let mut pairs = FilesetParser::parse(Rule::alias_declaration, source)?;
+        let first = pairs.next().unwrap();
+        match first.as_rule() {
+            Rule::strict_identifier => Ok(AliasDeclaration::Symbol(first.as_str().to_owned())),
+            Rule::function_alias_declaration => {
+                let (name_pair, params_pair) = first.into_inner().collect_tuple().unwrap();
+                assert_eq!(name_pair.as_rule(), Rule::function_name);
+                assert_eq!(params_pair.as_rule(), Rule::formal_parameters);
+                let name = name_pair.as_str().to_owned();
+                let params_span = params_pair.as_span();
+                let params = params_pair
+                    .into_inner()
+                    .map(|pair| match pair.as_rule() {
+                        Rule::strict_identifier => pair.as_str().to_owned(),
+                        r => panic!("unexpected formal parameter rule {r:?}"),
+                    })
+                    .collect_vec();
+                if params.iter().all_unique() {
+                    Ok(AliasDeclaration::Function(name, params))
+                } else {
+                    Err(FilesetParseError::new(
+                        FilesetParseErrorKind::RedefinedFunctionParameter,
+                        params_span,
+                    ))
+                }
+            }
+            r => panic!("unexpected alias declaration rule {r:?}"),
+        }
+    }
+}
+
+impl AliasDefinitionParser for FilesetAliasParser {
+    type Output<'i> = ExpressionKind<'i>;
+    type Error = FilesetParseError;
+
+    fn parse_definition<'i>(&self, source: &'i str) -> Result<ExpressionNode<'i>, Self::Error> {
+        parse_program(source)
+    }
+}
+
+/// Applies the given function to the innermost `node` by unwrapping alias
+/// expansion nodes.
+pub(super) fn expect_expression_with<T>(
+    diagnostics: &mut FilesetDiagnostics,
+    node: &ExpressionNode,
+    f: impl FnOnce(&mut FilesetDiagnostics, &ExpressionNode) -> FilesetParseResult<T>,
+) -> FilesetParseResult<T> {
+    if let ExpressionKind::AliasExpanded(id, subst) = &node.kind {
+        let mut inner_diagnostics = FilesetDiagnostics::new();
+        let expression = expect_expression_with(&mut inner_diagnostics, subst, f)
+            .map_err(|e| e.within_alias_expansion(*id, node.span))?;
+        diagnostics.extend_with(inner_diagnostics, |diag| {
+            diag.within_alias_expansion(*id, node.span)
+        });
+        Ok(expression)
+    } else {
+        f(diagnostics, node)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use assert_matches::assert_matches;
@@ -353,6 +587,30 @@ mod tests {
     use super::*;
     use crate::dsl_util::KeywordArgument;
 
+    #[derive(Debug)]
+    struct WithFilesetAliasesMap(FilesetAliasesMap);
+
+    impl WithFilesetAliasesMap {
+        fn parse<'i>(&'i self, text: &'i str) -> Result<ExpressionNode<'i>, FilesetParseError> {
+            let node = parse_program(text)?;
+            dsl_util::expand_aliases(node, &self.0)
+        }
+
+        fn parse_normalized<'i>(&'i self, text: &'i str) -> ExpressionNode<'i> {
+            normalize_tree(self.parse(text).unwrap())
+        }
+    }
+
+    fn with_aliases(
+        aliases: impl IntoIterator<Item = (impl AsRef<str>, impl Into<String>)>,
+    ) -> WithFilesetAliasesMap {
+        let mut aliases_map = FilesetAliasesMap::new();
+        for (decl, defn) in aliases {
+            aliases_map.insert(decl, defn).unwrap();
+        }
+        WithFilesetAliasesMap(aliases_map)
+    }
+
     fn parse_into_kind(text: &str) -> Result<ExpressionKind, FilesetParseErrorKind> {
         parse_program(text)
             .map(|node| node.kind)
@@ -404,7 +662,8 @@ mod tests {
         let normalized_kind = match node.kind {
             ExpressionKind::Identifier(_)
             | ExpressionKind::String(_)
-            | ExpressionKind::StringPattern { .. } => node.kind,
+            | ExpressionKind::StringPattern { .. }
+            | ExpressionKind::Comparison(..) => node.kind,
             ExpressionKind::Unary(op, arg) => {
                 let arg = Box::new(normalize_tree(*arg));
                 ExpressionKind::Unary(op, arg)
@@ -422,6 +681,7 @@ mod tests {
                 let function = Box::new(normalize_function_call(*function));
                 ExpressionKind::FunctionCall(function)
             }
+            ExpressionKind::AliasExpanded(_, subst) => normalize_tree(*subst).kind,
         };
         ExpressionNode {
             kind: normalized_kind,
@@ -552,6 +812,23 @@ mod tests {
                 value: "glob*[chars]?".to_owned()
             })
         );
+        assert_eq!(
+            parse_into_kind(" foo:*.{rs,md} "),
+            Ok(ExpressionKind::StringPattern {
+                kind: "foo",
+                value: "*.{rs,md}".to_owned()
+            })
+        );
+        assert_eq!(
+            parse_into_kind(" foo:{a,{b,c}d} "),
+            Ok(ExpressionKind::StringPattern {
+                kind: "foo",
+                value: "{a,{b,c}d}".to_owned()
+            })
+        );
+        // Commas outside braces aren't part of the pattern
+        assert!(parse_into_kind(" foo:a,b ").is_err());
+        assert!(parse_into_kind(" foo:{a,b ").is_err());
         assert_eq!(
             parse_into_kind(r#" foo:"bar" "#),
             Ok(ExpressionKind::StringPattern {
@@ -645,6 +922,35 @@ mod tests {
         assert!(parse_into_kind("foo(a  , , )").is_err());
         assert_eq!(parse_normalized("foo(a,b,)"), parse_normalized("foo(a,b)"));
         assert!(parse_into_kind("foo(a,,b)").is_err());
+        // Commas in glob braces don't separate arguments
+        assert_matches!(
+            parse_into_kind("foo(*.{a,b}, c)"),
+            Ok(ExpressionKind::FunctionCall(function))
+                if function.args.len() == 2
+                    && function.args[0].kind == ExpressionKind::Identifier("*.{a,b}")
+        );
+
+        // Comparison is allowed as an argument
+        let comparison_args = |text| match parse_into_kind(text) {
+            Ok(ExpressionKind::FunctionCall(function)) => function
+                .args
+                .into_iter()
+                .map(|node| node.kind)
+                .collect_vec(),
+            other => panic!("unexpected result: {other:?}"),
+        };
+        assert_eq!(
+            comparison_args("foo(>1MiB, >= 2, <3.5kB, <=4)"),
+            [
+                ExpressionKind::Comparison(CompareOp::Greater, "1MiB"),
+                ExpressionKind::Comparison(CompareOp::GreaterEqual, "2"),
+                ExpressionKind::Comparison(CompareOp::Less, "3.5kB"),
+                ExpressionKind::Comparison(CompareOp::LessEqual, "4"),
+            ]
+        );
+        assert!(parse_into_kind(">1").is_err());
+        assert!(parse_into_kind("foo(>)").is_err());
+        assert!(parse_into_kind("foo(x|>1)").is_err());
     }
 
     #[test]
@@ -678,6 +984,14 @@ mod tests {
             parse_maybe_bare_into_kind("Unicode emoji 💩"),
             Ok(ExpressionKind::String("Unicode emoji 💩".to_owned()))
         );
+        assert_eq!(
+            parse_maybe_bare_into_kind("*.{rs,md}"),
+            Ok(ExpressionKind::Identifier("*.{rs,md}"))
+        );
+        assert_eq!(
+            parse_maybe_bare_into_kind("a,b{c"),
+            Ok(ExpressionKind::String("a,b{c".to_owned()))
+        );
         assert_eq!(
             parse_maybe_bare_into_kind("looks like & expression"),
             Err(FilesetParseErrorKind::SyntaxError)
@@ -702,6 +1016,13 @@ mod tests {
                 value: "glob * [chars]?".to_owned()
             })
         );
+        assert_eq!(
+            parse_maybe_bare_into_kind("glob:src/**/*.{rs,md}"),
+            Ok(ExpressionKind::StringPattern {
+                kind: "glob",
+                value: "src/**/*.{rs,md}".to_owned()
+            })
+        );
         assert_eq!(
             parse_maybe_bare_into_kind("foo:bar:baz"),
             Err(FilesetParseErrorKind::SyntaxError)
@@ -723,6 +1044,119 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_alias_decl() {
+        let mut aliases_map = FilesetAliasesMap::new();
+        aliases_map.insert("src-files", "src/*").unwrap();
+        assert!(aliases_map.get_symbol("src-files").is_some());
+        // Path-like name cannot be used as an alias name.
+        assert!(aliases_map.insert("foo.txt", "bar").is_err());
+        assert!(aliases_map.insert("foo/bar", "baz").is_err());
+        assert!(aliases_map.insert("*.rs", "baz").is_err());
+
+        aliases_map.insert("f( x, y )", "x|y").unwrap();
+        let (id, params, defn) = aliases_map.get_function("f", 2).unwrap();
+        assert_eq!(
+            id,
+            AliasId::Function("f", &["x".to_owned(), "y".to_owned()])
+        );
+        assert_eq!(params, ["x", "y"]);
+        assert_eq!(defn, "x|y");
+        assert_matches!(
+            aliases_map.insert("f(x, x)", "x").unwrap_err().kind,
+            FilesetParseErrorKind::RedefinedFunctionParameter
+        );
+    }
+
+    #[test]
+    fn test_expand_symbol_alias() {
+        assert_eq!(
+            with_aliases([("AB", "a&b")]).parse_normalized("AB|c"),
+            parse_normalized("(a&b)|c")
+        );
+        assert_eq!(
+            with_aliases([("BC", "b|c")]).parse_normalized("a&BC"),
+            parse_normalized("a&(b|c)")
+        );
+
+        // String literal and string pattern should not be substituted.
+        assert_eq!(
+            with_aliases([("A", "a")]).parse_normalized(r#"A|"A"|glob:A"#),
+            parse_normalized(r#"a|"A"|glob:A"#)
+        );
+
+        // Path-like identifier cannot be substituted.
+        assert_eq!(
+            with_aliases([("A", "a")]).parse_normalized("A/b|A.txt"),
+            parse_normalized("A/b|A.txt")
+        );
+
+        // Multi-level substitution.
+        assert_eq!(
+            with_aliases([("A", "BC"), ("BC", "b|C"), ("C", "c")]).parse_normalized("A"),
+            parse_normalized("b|c")
+        );
+
+        // Infinite recursion, where the top-level error isn't of RecursiveAlias kind.
+        assert_eq!(
+            with_aliases([("A", "A")]).parse("A").unwrap_err().kind,
+            FilesetParseErrorKind::InAliasExpansion("A".to_owned())
+        );
+        let err = with_aliases([("A", "B"), ("B", "b|C"), ("C", "c|B")])
+            .parse("A")
+            .unwrap_err();
+        assert_eq!(
+            err.kind,
+            FilesetParseErrorKind::InAliasExpansion("A".to_owned())
+        );
+        let bottom_err = std::iter::successors(Some(&err), |e| e.origin())
+            .last()
+            .unwrap();
+        assert_eq!(
+            bottom_err.kind,
+            FilesetParseErrorKind::RecursiveAlias("B".to_owned())
+        );
+
+        // Error in alias definition.
+        assert_eq!(
+            with_aliases([("A", "a(")]).parse("A").unwrap_err().kind,
+            FilesetParseErrorKind::InAliasExpansion("A".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_expand_function_alias() {
+        assert_eq!(
+            with_aliases([("F(  )", "a")]).parse_normalized("F()"),
+            parse_normalized("a")
+        );
+        assert_eq!(
+            with_aliases([("F( x,  y )", "x|y")]).parse_normalized("F(a, b)"),
+            parse_normalized("a|b")
+        );
+
+        // Function parameter should precede the symbol alias.
+        assert_eq!(
+            with_aliases([("F(X)", "X"), ("X", "x")]).parse_normalized("F(a)|X"),
+            parse_normalized("a|x")
+        );
+
+        // Comparison can be passed as argument.
+        assert_eq!(
+            with_aliases([("big(x)", "size(x)")]).parse_normalized("big(>1MiB)"),
+            parse_normalized("size(>1MiB)")
+        );
+
+        // Invalid number of arguments.
+        assert_matches!(
+            with_aliases([("F(x)", "x")])
+                .parse("F(a, b)")
+                .unwrap_err()
+                .kind,
+            FilesetParseErrorKind::InvalidArguments { .. }
+        );
+    }
+
     #[test]
     fn test_parse_error() {
         insta::assert_snapshot!(parse_program("foo|").unwrap_err().to_string(), @r###"
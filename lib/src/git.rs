@@ -26,7 +26,9 @@ use std::path::PathBuf;
 use std::str;
 
 use bstr::BStr;
+use futures::StreamExt as _;
 use itertools::Itertools;
+use pollster::FutureExt as _;
 use tempfile::NamedTempFile;
 use thiserror::Error;
 
@@ -39,8 +41,11 @@ use crate::git_backend::GitBackend;
 use crate::git_subprocess::GitSubprocessContext;
 use crate::git_subprocess::GitSubprocessError;
 use crate::index::Index;
+use crate::matchers::Matcher;
 use crate::merged_tree::MergedTree;
+use crate::merged_tree::TreeDiffEntry;
 use crate::object_id::ObjectId;
+use crate::op_store::AbandonReason;
 use crate::op_store::RefTarget;
 use crate::op_store::RefTargetOptionExt;
 use crate::op_store::RemoteRef;
@@ -229,6 +234,40 @@ pub fn get_git_repo(store: &Store) -> Result<gix::Repository, UnexpectedGitBacke
     get_git_backend(store).map(|backend| backend.git_repo())
 }
 
+/// Fetches file contents that differ between the trees in one request if the
+/// repo is backed by a partial clone.
+///
+/// Nothing is fetched if the repo isn't a partial clone. Without this, missing
+/// blobs would be fetched one by one as they're read.
+pub fn fetch_missing_blobs_for_diff(
+    store: &Store,
+    from_tree: &MergedTree,
+    to_tree: &MergedTree,
+    matcher: &dyn Matcher,
+) -> BackendResult<()> {
+    let Ok(git_backend) = get_git_backend(store) else {
+        return Ok(());
+    };
+    if git_backend.promisor_remote_name().is_none() {
+        return Ok(());
+    }
+    let file_ids = async {
+        let mut file_ids = vec![];
+        let mut tree_diff = from_tree.diff_stream(to_tree, matcher);
+        while let Some(TreeDiffEntry { values, .. }) = tree_diff.next().await {
+            let (before, after) = values?;
+            for value in before.iter().chain(after.iter()).flatten() {
+                if let TreeValue::File { id, .. } = value {
+                    file_ids.push(id.clone());
+                }
+            }
+        }
+        Ok::<_, BackendError>(file_ids)
+    }
+    .block_on()?;
+    git_backend.fetch_missing_blobs(&file_ids)
+}
+
 /// Checks if `git_ref` points to a Git commit object, and returns its id.
 ///
 /// If the ref points to the previously `known_target` (i.e. unchanged), this
@@ -489,6 +528,7 @@ fn abandon_unreachable_commits(
     for id in &abandoned_commit_ids {
         let commit = mut_repo.store().get_commit(id)?;
         mut_repo.record_abandoned_commit(&commit);
+        mut_repo.set_abandon_reason(id.clone(), AbandonReason::UnreachableInGit);
     }
     Ok(abandoned_commit_ids)
 }
@@ -1459,6 +1499,36 @@ pub fn set_remote_url(
     Ok(())
 }
 
+/// Configures the remote as the promisor remote of a partial clone.
+///
+/// Subsequent fetches from the remote will omit objects matching the `filter`
+/// (e.g. `blob:none`), and missing objects will be fetched on demand. Fetching
+/// is only supported by the Git subprocess.
+pub fn set_remote_partial_clone_filter(
+    git_repo: &git2::Repository,
+    remote_name: &str,
+    filter: &str,
+) -> Result<(), GitRemoteManagementError> {
+    git_repo.find_remote(remote_name).map_err(|err| {
+        if is_remote_not_found_err(&err) {
+            GitRemoteManagementError::NoSuchRemote(remote_name.to_owned())
+        } else {
+            GitRemoteManagementError::InternalGitError(err)
+        }
+    })?;
+
+    let mut config = git_repo
+        .config()
+        .map_err(GitRemoteManagementError::InternalGitError)?;
+    config
+        .set_bool(&format!("remote.{remote_name}.promisor"), true)
+        .map_err(GitRemoteManagementError::InternalGitError)?;
+    config
+        .set_str(&format!("remote.{remote_name}.partialclonefilter"), filter)
+        .map_err(GitRemoteManagementError::InternalGitError)?;
+    Ok(())
+}
+
 fn rename_remote_refs(mut_repo: &mut MutableRepo, old_remote_name: &str, new_remote_name: &str) {
     mut_repo.rename_remote(old_remote_name, new_remote_name);
     let prefix = format!("refs/remotes/{old_remote_name}/");
@@ -1567,6 +1637,10 @@ impl<'a> GitFetch<'a> {
     ///
     /// Keeps track of the {branch_names, remote_name} pair the refs can be
     /// subsequently imported into the `jj` repo by calling `import_refs()`.
+    ///
+    /// If `git.prune-on-fetch` is enabled, remote-tracking branches matching
+    /// `branch_names` but no longer present in the remote are deleted.
+    /// Branches outside of `branch_names` are left intact.
     #[tracing::instrument(skip(self, callbacks))]
     pub fn fetch(
         &mut self,
@@ -1578,8 +1652,13 @@ impl<'a> GitFetch<'a> {
         if remote_name.contains("/") {
             return Err(GitFetchError::RemoteWithSlash(remote_name.to_owned()));
         }
-        self.fetch_impl
-            .fetch(remote_name, branch_names, callbacks, depth)?;
+        self.fetch_impl.fetch(
+            remote_name,
+            branch_names,
+            callbacks,
+            depth,
+            self.git_settings.prune_on_fetch,
+        )?;
         self.fetched.push(FetchedBranches {
             remote: remote_name.to_string(),
             branches: branch_names.to_vec(),
@@ -1690,10 +1769,11 @@ impl<'a> GitFetchImpl<'a> {
         branch_names: &[StringPattern],
         callbacks: RemoteCallbacks<'_>,
         depth: Option<NonZeroU32>,
+        prune: bool,
     ) -> Result<(), GitFetchError> {
         match self {
             GitFetchImpl::Git2 { git_repo } => {
-                git2_fetch(git_repo, remote_name, branch_names, callbacks, depth)
+                git2_fetch(git_repo, remote_name, branch_names, callbacks, depth, prune)
             }
             GitFetchImpl::Subprocess { git_repo, git_ctx } => subprocess_fetch(
                 git_repo,
@@ -1702,6 +1782,7 @@ impl<'a> GitFetchImpl<'a> {
                 branch_names,
                 callbacks,
                 depth,
+                prune,
             ),
         }
     }
@@ -1728,6 +1809,7 @@ fn git2_fetch(
     branch_names: &[StringPattern],
     callbacks: RemoteCallbacks<'_>,
     depth: Option<NonZeroU32>,
+    prune: bool,
 ) -> Result<(), GitFetchError> {
     let mut remote = git_repo.find_remote(remote_name).map_err(|err| {
         if is_remote_not_found_err(&err) {
@@ -1750,8 +1832,11 @@ fn git2_fetch(
 
     tracing::debug!("remote.download");
     remote.download(&refspecs, Some(&mut git2_fetch_options(callbacks, depth)))?;
-    tracing::debug!("remote.prune");
-    remote.prune(None)?;
+    if prune {
+        // Only refs matching the refspecs passed to .download() are pruned.
+        tracing::debug!("remote.prune");
+        remote.prune(None)?;
+    }
     tracing::debug!("remote.update_tips");
     remote.update_tips(
         None,
@@ -1809,6 +1894,7 @@ fn subprocess_fetch(
     branch_names: &[StringPattern],
     mut callbacks: RemoteCallbacks<'_>,
     depth: Option<NonZeroU32>,
+    prune: bool,
 ) -> Result<(), GitFetchError> {
     // check the remote exists
     if git_repo.try_find_remote(remote_name).is_none() {
@@ -1830,11 +1916,18 @@ fn subprocess_fetch(
     //
     // even more unfortunately, git errors out one refspec at a time,
     // meaning that the below cycle runs in O(#failed refspecs)
-    while let Some(failing_refspec) =
-        git_ctx.spawn_fetch(remote_name, &remaining_refspecs, &mut callbacks, depth)?
-    {
+    while let Some(failing_refspec) = git_ctx.spawn_fetch(
+        remote_name,
+        &remaining_refspecs,
+        &mut callbacks,
+        depth,
+        prune,
+    )? {
         remaining_refspecs.retain(|r| r.source.as_ref() != Some(&failing_refspec));
 
+        if !prune {
+            continue;
+        }
         if let Some(branch_name) = failing_refspec.strip_prefix("refs/heads/") {
             branches_to_prune.push(format!("{remote_name}/{branch_name}"));
         }
@@ -2025,6 +2118,15 @@ fn git2_push_refs(
         let mut proxy_options = git2::ProxyOptions::new();
         proxy_options.auto();
         push_options.proxy_options(proxy_options);
+        // Compress objects using the number of threads configured by
+        // `pack.threads`, which defaults to all available CPUs as in Git.
+        let pack_threads = git_repo
+            .config()
+            .and_then(|config| config.get_i32("pack.threads"))
+            .ok()
+            .and_then(|threads| u32::try_from(threads).ok())
+            .unwrap_or(0);
+        push_options.packbuilder_parallelism(pack_threads);
         let mut callbacks = callbacks.into_git();
         callbacks.push_negotiation(|updates| {
             for update in updates {
@@ -2149,8 +2251,14 @@ fn subprocess_push_refs(
         .map(|full_refspec| RefToPush::new(full_refspec, qualified_remote_refs_expected_locations))
         .collect();
 
+    // Negotiate common commits by default, unless the user configured
+    // otherwise.
+    let negotiate = git_repo
+        .config_snapshot()
+        .boolean("push.negotiate")
+        .is_none();
     let (failed_ref_matches, successful_pushes) =
-        git_ctx.spawn_push(remote_name, &refs_to_push, &mut callbacks)?;
+        git_ctx.spawn_push(remote_name, &refs_to_push, negotiate, &mut callbacks)?;
 
     for remote_ref in successful_pushes {
         remaining_remote_refs.remove(remote_ref.as_str());
@@ -2269,17 +2377,30 @@ impl<'a> RemoteCallbacks<'a> {
         }
         // TODO: We should expose the callbacks to the caller instead -- the library
         // crate shouldn't read environment variables.
+        let mut tried_mechanisms: Vec<String> = vec![];
+        let mut tried_credential_helper = false;
         let mut tried_ssh_agent = false;
+        let mut tried_default = false;
         let mut ssh_key_paths_to_try: Option<Vec<PathBuf>> = None;
         callbacks.credentials(move |url, username_from_url, allowed_types| {
             let span = tracing::debug_span!("RemoteCallbacks.credentials");
             let _ = span.enter();
 
-            let git_config = git2::Config::open_default();
-            let credential_helper = git_config
-                .and_then(|conf| git2::Cred::credential_helper(&conf, url, username_from_url));
-            if let Ok(creds) = credential_helper {
+            // Consult the credential helpers only once. If the returned
+            // credentials were rejected, asking again would give the same
+            // result.
+            let credential_helper = if tried_credential_helper {
+                None
+            } else {
+                tried_credential_helper = true;
+                let git_config = git2::Config::open_default();
+                git_config
+                    .and_then(|conf| git2::Cred::credential_helper(&conf, url, username_from_url))
+                    .ok()
+            };
+            if let Some(creds) = credential_helper {
                 tracing::info!("using credential_helper");
+                tried_mechanisms.push("credential helper".to_owned());
                 return Ok(creds);
             } else if let Some(username) = username_from_url {
                 if allowed_types.contains(git2::CredentialType::SSH_KEY) {
@@ -2288,6 +2409,7 @@ impl<'a> RemoteCallbacks<'a> {
                     if !tried_ssh_agent {
                         tracing::info!(username, "trying ssh_key_from_agent");
                         tried_ssh_agent = true;
+                        tried_mechanisms.push("ssh-agent".to_owned());
                         return git2::Cred::ssh_key_from_agent(username).map_err(|err| {
                             tracing::error!(err = %err);
                             err
@@ -2306,6 +2428,7 @@ impl<'a> RemoteCallbacks<'a> {
 
                     if let Some(path) = paths.pop() {
                         tracing::info!(username, path = ?path, "trying ssh_key");
+                        tried_mechanisms.push(format!("ssh key {}", path.display()));
                         return git2::Cred::ssh_key(username, None, &path, None).map_err(|err| {
                             tracing::error!(err = %err);
                             err
@@ -2319,6 +2442,7 @@ impl<'a> RemoteCallbacks<'a> {
                                 username,
                                 "using userpass_plaintext with username from url"
                             );
+                            tried_mechanisms.push("password".to_owned());
                             return git2::Cred::userpass_plaintext(username, &pw).map_err(|err| {
                                 tracing::error!(err = %err);
                                 err
@@ -2330,6 +2454,7 @@ impl<'a> RemoteCallbacks<'a> {
                 if let Some(ref mut cb) = self.get_username_password {
                     if let Some((username, pw)) = cb(url) {
                         tracing::info!(username, "using userpass_plaintext");
+                        tried_mechanisms.push("username and password".to_owned());
                         return git2::Cred::userpass_plaintext(&username, &pw).map_err(|err| {
                             tracing::error!(err = %err);
                             err
@@ -2337,8 +2462,22 @@ impl<'a> RemoteCallbacks<'a> {
                     }
                 }
             }
-            tracing::info!("using default");
-            git2::Cred::default()
+            if allowed_types.contains(git2::CredentialType::DEFAULT) && !tried_default {
+                tracing::info!("using default");
+                tried_default = true;
+                return git2::Cred::default();
+            }
+            let tried = if tried_mechanisms.is_empty() {
+                "no credentials available".to_owned()
+            } else {
+                format!("tried {}", tried_mechanisms.iter().join(", "))
+            };
+            tracing::info!(url, tried, "no more credentials to try");
+            Err(git2::Error::new(
+                git2::ErrorCode::Auth,
+                git2::ErrorClass::Callback,
+                format!("Failed to authenticate to {url} ({tried})"),
+            ))
         });
         callbacks
     }
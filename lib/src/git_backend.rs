@@ -36,6 +36,7 @@ use std::time::SystemTime;
 use async_trait::async_trait;
 use futures::stream::BoxStream;
 use gix::bstr::BString;
+use gix::bstr::ByteSlice as _;
 use gix::objs::CommitRef;
 use gix::objs::CommitRefIter;
 use gix::objs::WriteTo;
@@ -44,8 +45,10 @@ use pollster::FutureExt;
 use prost::Message;
 use smallvec::SmallVec;
 use thiserror::Error;
+use thread_local::ThreadLocal;
 
 use crate::backend::make_root_commit;
+use crate::backend::read_on_thread_pool;
 use crate::backend::Backend;
 use crate::backend::BackendError;
 use crate::backend::BackendInitError;
@@ -69,8 +72,12 @@ use crate::backend::Timestamp;
 use crate::backend::Tree;
 use crate::backend::TreeId;
 use crate::backend::TreeValue;
+use crate::config::ConfigGetError;
+use crate::config::ConfigGetResultExt as _;
 use crate::file_util::IoResultExt as _;
 use crate::file_util::PathError;
+use crate::git_subprocess::GitSubprocessContext;
+use crate::git_subprocess::GitSubprocessError;
 use crate::index::Index;
 use crate::lock::FileLock;
 use crate::merge::Merge;
@@ -101,6 +108,8 @@ pub enum GitBackendInitError {
     #[error("Failed to open git repository")]
     OpenRepository(#[source] gix::open::Error),
     #[error(transparent)]
+    Config(ConfigGetError),
+    #[error(transparent)]
     Path(PathError),
 }
 
@@ -115,6 +124,8 @@ pub enum GitBackendLoadError {
     #[error("Failed to open git repository")]
     OpenRepository(#[source] gix::open::Error),
     #[error(transparent)]
+    Config(ConfigGetError),
+    #[error(transparent)]
     Path(PathError),
 }
 
@@ -131,6 +142,12 @@ pub enum GitBackendError {
     ReadMetadata(#[source] TableStoreError),
     #[error("Failed to write non-git metadata")]
     WriteMetadata(#[source] TableStoreError),
+    #[error("Failed to fetch missing objects from promisor remote '{remote_name}'")]
+    FetchMissingObjects {
+        remote_name: String,
+        #[source]
+        source: GitSubprocessError,
+    },
 }
 
 impl From<GitBackendError> for BackendError {
@@ -154,11 +171,15 @@ pub struct GitBackend {
     // used in a single-threaded context.
     base_repo: gix::ThreadSafeRepository,
     repo: Mutex<gix::Repository>,
+    // Instances for the threads reading trees, which are usually the worker
+    // threads of the thread pool. See read_on_thread_pool().
+    worker_repos: Arc<ThreadLocal<gix::Repository>>,
     root_commit_id: CommitId,
     root_change_id: ChangeId,
     empty_tree_id: TreeId,
     extra_metadata_store: TableStore,
     cached_extra_metadata: Mutex<Option<Arc<ReadonlyTable>>>,
+    git_executable_path: PathBuf,
 }
 
 impl GitBackend {
@@ -166,7 +187,11 @@ impl GitBackend {
         "git"
     }
 
-    fn new(base_repo: gix::ThreadSafeRepository, extra_metadata_store: TableStore) -> Self {
+    fn new(
+        base_repo: gix::ThreadSafeRepository,
+        extra_metadata_store: TableStore,
+        git_executable_path: PathBuf,
+    ) -> Self {
         let repo = Mutex::new(base_repo.to_thread_local());
         let root_commit_id = CommitId::from_bytes(&[0; HASH_LENGTH]);
         let root_change_id = ChangeId::from_bytes(&[0; CHANGE_ID_LENGTH]);
@@ -174,11 +199,13 @@ impl GitBackend {
         GitBackend {
             base_repo,
             repo,
+            worker_repos: Arc::new(ThreadLocal::new()),
             root_commit_id,
             root_change_id,
             empty_tree_id,
             extra_metadata_store,
             cached_extra_metadata: Mutex::new(None),
+            git_executable_path,
         }
     }
 
@@ -194,7 +221,7 @@ impl GitBackend {
             gix_open_opts_from_settings(settings),
         )
         .map_err(GitBackendInitError::InitRepository)?;
-        Self::init_with_repo(store_path, git_repo_path, git_repo)
+        Self::init_with_repo(settings, store_path, git_repo_path, git_repo)
     }
 
     /// Initializes backend by creating a new Git repo at the specified
@@ -218,7 +245,7 @@ impl GitBackend {
         )
         .map_err(GitBackendInitError::InitRepository)?;
         let git_repo_path = workspace_root.join(".git");
-        Self::init_with_repo(store_path, &git_repo_path, git_repo)
+        Self::init_with_repo(settings, store_path, &git_repo_path, git_repo)
     }
 
     /// Initializes backend with an existing Git repo at the specified path.
@@ -238,10 +265,11 @@ impl GitBackend {
             gix_open_opts_from_settings(settings),
         )
         .map_err(GitBackendInitError::OpenRepository)?;
-        Self::init_with_repo(store_path, git_repo_path, git_repo)
+        Self::init_with_repo(settings, store_path, git_repo_path, git_repo)
     }
 
     fn init_with_repo(
+        settings: &UserSettings,
         store_path: &Path,
         git_repo_path: &Path,
         git_repo: gix::ThreadSafeRepository,
@@ -271,7 +299,11 @@ impl GitBackend {
                 .map_err(GitBackendInitError::Path)?;
         };
         let extra_metadata_store = TableStore::init(extra_path, HASH_LENGTH);
-        Ok(GitBackend::new(git_repo, extra_metadata_store))
+        Ok(GitBackend::new(
+            git_repo,
+            extra_metadata_store,
+            git_executable_path_from_settings(settings).map_err(GitBackendInitError::Config)?,
+        ))
     }
 
     pub fn load(
@@ -294,7 +326,11 @@ impl GitBackend {
         )
         .map_err(GitBackendLoadError::OpenRepository)?;
         let extra_metadata_store = TableStore::load(store_path.join("extra"), HASH_LENGTH);
-        Ok(GitBackend::new(repo, extra_metadata_store))
+        Ok(GitBackend::new(
+            repo,
+            extra_metadata_store,
+            git_executable_path_from_settings(settings).map_err(GitBackendLoadError::Config)?,
+        ))
     }
 
     fn lock_git_repo(&self) -> MutexGuard<'_, gix::Repository> {
@@ -316,6 +352,70 @@ impl GitBackend {
         self.base_repo.path()
     }
 
+    /// Path to the directory shared by all worktrees of the repository.
+    ///
+    /// This is usually the same as `git_repo_path()`, but differs if the
+    /// repository is opened through a linked worktree.
+    pub fn git_common_dir(&self) -> &Path {
+        self.base_repo
+            .common_dir
+            .as_deref()
+            .unwrap_or_else(|| self.base_repo.path())
+    }
+
+    /// Name of the promisor remote if the repository is a partial clone.
+    pub fn promisor_remote_name(&self) -> Option<String> {
+        let repo = self.git_repo();
+        let config = repo.config_snapshot();
+        if let Some(name) = config.string("extensions.partialClone") {
+            return Some(name.to_str_lossy().into_owned());
+        }
+        let remote_names = repo.remote_names();
+        let name = remote_names.iter().find(|name| {
+            let key = format!("remote.{name}.promisor");
+            config.boolean(key.as_str()).unwrap_or(false)
+        })?;
+        Some(name.to_str_lossy().into_owned())
+    }
+
+    /// Fetches the specified file contents from the promisor remote in one
+    /// request.
+    ///
+    /// Files that already exist locally are skipped. This does nothing if the
+    /// repository isn't a partial clone.
+    pub fn fetch_missing_blobs(&self, ids: &[FileId]) -> BackendResult<()> {
+        let Some(remote_name) = self.promisor_remote_name() else {
+            return Ok(());
+        };
+        let missing_ids: Vec<gix::ObjectId> = {
+            let locked_repo = self.lock_git_repo();
+            ids.iter()
+                .unique()
+                .map(validate_git_object_id)
+                .filter_ok(|id| !locked_repo.has_object(id))
+                .try_collect()?
+        };
+        self.fetch_missing_objects(&remote_name, &missing_ids)
+            .map_err(|err| BackendError::Other(err.into()))
+    }
+
+    fn fetch_missing_objects(
+        &self,
+        remote_name: &str,
+        ids: &[gix::ObjectId],
+    ) -> Result<(), GitBackendError> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+        tracing::debug!(remote_name, count = ids.len(), "fetching missing objects");
+        GitSubprocessContext::from_git_backend(self, &self.git_executable_path)
+            .spawn_fetch_objects(remote_name, ids)
+            .map_err(|source| GitBackendError::FetchMissingObjects {
+                remote_name: remote_name.to_owned(),
+                source,
+            })
+    }
+
     /// Path to the working directory if the repository isn't bare.
     pub fn git_workdir(&self) -> Option<&Path> {
         self.base_repo.work_dir()
@@ -410,14 +510,32 @@ impl GitBackend {
     }
 
     fn read_file_sync(&self, id: &FileId) -> BackendResult<Box<dyn Read>> {
+        let data = self.read_blob_data(id)?;
+        Ok(Box::new(Cursor::new(data)))
+    }
+
+    /// Reads blob data, fetching it from the promisor remote if the blob is
+    /// missing in a partial clone.
+    fn read_blob_data(&self, id: &impl ObjectId) -> BackendResult<Vec<u8>> {
         let git_blob_id = validate_git_object_id(id)?;
-        let locked_repo = self.lock_git_repo();
-        let mut blob = locked_repo
-            .find_object(git_blob_id)
-            .map_err(|err| map_not_found_err(err, id))?
-            .try_into_blob()
+        let read_blob = |repo: &gix::Repository| -> BackendResult<Vec<u8>> {
+            let mut blob = repo
+                .find_object(git_blob_id)
+                .map_err(|err| map_not_found_err(err, id))?
+                .try_into_blob()
+                .map_err(|err| to_read_object_err(err, id))?;
+            Ok(blob.take_data())
+        };
+        let result = read_blob(&self.lock_git_repo());
+        if !matches!(result, Err(BackendError::ObjectNotFound { .. })) {
+            return result;
+        }
+        let Some(remote_name) = self.promisor_remote_name() else {
+            return result;
+        };
+        self.fetch_missing_objects(&remote_name, &[git_blob_id])
             .map_err(|err| to_read_object_err(err, id))?;
-        Ok(Box::new(Cursor::new(blob.take_data())))
+        read_blob(&self.lock_git_repo())
     }
 
     fn new_diff_platform(&self) -> BackendResult<gix::diff::blob::Platform> {
@@ -494,6 +612,11 @@ fn gix_open_opts_from_settings(settings: &UserSettings) -> gix::open::Options {
         .open_path_as_is(true)
 }
 
+fn git_executable_path_from_settings(settings: &UserSettings) -> Result<PathBuf, ConfigGetError> {
+    let path = settings.get("git.executable-path").optional()?;
+    Ok(path.unwrap_or_else(|| PathBuf::from("git")))
+}
+
 /// Reads the `jj:trees` header from the commit.
 fn root_tree_from_header(git_commit: &CommitRef) -> Result<Option<MergedTreeId>, ()> {
     for (key, value) in &git_commit.extra_headers {
@@ -770,7 +893,7 @@ fn recreate_no_gc_refs(
         // will always have a unique name. Doing that with the current
         // ref-per-head strategy would increase the number of the no-gc refs.
         // https://github.com/jj-vcs/jj/pull/2659#issuecomment-1837057782
-        let loose_ref_path = git_repo.path().join(git_ref.name.to_path());
+        let loose_ref_path = git_repo.common_dir().join(git_ref.name.to_path());
         if let Ok(metadata) = loose_ref_path.metadata() {
             let mtime = metadata.modified().expect("unsupported platform?");
             if mtime > keep_newer {
@@ -802,10 +925,18 @@ fn recreate_no_gc_refs(
     Ok(())
 }
 
-fn run_git_gc(git_dir: &Path) -> Result<(), GitGcError> {
+fn run_git_gc(git_dir: &Path, keep_newer: SystemTime) -> Result<(), GitGcError> {
+    // "git gc" keeps unreachable objects for 2 weeks by default. Pass the
+    // threshold in Git's raw date format instead, rounded up to seconds.
+    let keep_newer_secs = keep_newer
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |duration| {
+            duration.as_secs() + u64::from(duration.subsec_nanos() > 0)
+        });
     let mut git = Command::new("git");
     git.arg("--git-dir=."); // turn off discovery
     git.arg("gc");
+    git.arg(format!("--prune=@{keep_newer_secs} +0000"));
     // Don't specify it by GIT_DIR/--git-dir. On Windows, the path could be
     // canonicalized as UNC path, which wouldn't be supported by git.
     git.current_dir(git_dir);
@@ -817,6 +948,64 @@ fn run_git_gc(git_dir: &Path) -> Result<(), GitGcError> {
     Ok(())
 }
 
+fn read_tree_from_repo(repo: &gix::Repository, id: &TreeId) -> BackendResult<Tree> {
+    let git_tree_id = validate_git_object_id(id)?;
+
+    let git_tree = repo
+        .find_object(git_tree_id)
+        .map_err(|err| map_not_found_err(err, id))?
+        .try_into_tree()
+        .map_err(|err| to_read_object_err(err, id))?;
+    let mut tree = Tree::default();
+    for entry in git_tree.iter() {
+        let entry = entry.map_err(|err| to_read_object_err(err, id))?;
+        let name = str::from_utf8(entry.filename()).map_err(|err| to_invalid_utf8_err(err, id))?;
+        let (name, value) = match entry.mode().kind() {
+            gix::object::tree::EntryKind::Tree => {
+                let id = TreeId::from_bytes(entry.oid().as_bytes());
+                (name, TreeValue::Tree(id))
+            }
+            gix::object::tree::EntryKind::Blob => {
+                let id = FileId::from_bytes(entry.oid().as_bytes());
+                if let Some(basename) = name.strip_suffix(CONFLICT_SUFFIX) {
+                    (
+                        basename,
+                        TreeValue::Conflict(ConflictId::from_bytes(entry.oid().as_bytes())),
+                    )
+                } else {
+                    (
+                        name,
+                        TreeValue::File {
+                            id,
+                            executable: false,
+                        },
+                    )
+                }
+            }
+            gix::object::tree::EntryKind::BlobExecutable => {
+                let id = FileId::from_bytes(entry.oid().as_bytes());
+                (
+                    name,
+                    TreeValue::File {
+                        id,
+                        executable: true,
+                    },
+                )
+            }
+            gix::object::tree::EntryKind::Link => {
+                let id = SymlinkId::from_bytes(entry.oid().as_bytes());
+                (name, TreeValue::Symlink(id))
+            }
+            gix::object::tree::EntryKind::Commit => {
+                let id = CommitId::from_bytes(entry.oid().as_bytes());
+                (name, TreeValue::GitSubmodule(id))
+            }
+        };
+        tree.set(RepoPathComponentBuf::from(name), value);
+    }
+    Ok(tree)
+}
+
 fn validate_git_object_id(id: &impl ObjectId) -> BackendResult<gix::ObjectId> {
     if id.as_bytes().len() != HASH_LENGTH {
         return Err(BackendError::InvalidHashLength {
@@ -942,13 +1131,25 @@ impl Backend for GitBackend {
     }
 
     fn concurrency(&self) -> usize {
-        1
+        // Trees are read on the global thread pool by read_on_thread_pool().
+        rayon::current_num_threads()
     }
 
     async fn read_file(&self, _path: &RepoPath, id: &FileId) -> BackendResult<Box<dyn Read>> {
         self.read_file_sync(id)
     }
 
+    async fn read_file_size(&self, _path: &RepoPath, id: &FileId) -> BackendResult<u64> {
+        let git_blob_id = validate_git_object_id(id)?;
+        let header = self.lock_git_repo().find_header(git_blob_id);
+        match header {
+            Ok(header) => Ok(header.size()),
+            // The blob may be missing in a partial clone. Let the full read
+            // fetch it or report the error.
+            Err(_) => Ok(self.read_blob_data(id)?.len() as u64),
+        }
+    }
+
     async fn write_file(
         &self,
         _path: &RepoPath,
@@ -967,15 +1168,9 @@ impl Backend for GitBackend {
     }
 
     async fn read_symlink(&self, _path: &RepoPath, id: &SymlinkId) -> BackendResult<String> {
-        let git_blob_id = validate_git_object_id(id)?;
-        let locked_repo = self.lock_git_repo();
-        let mut blob = locked_repo
-            .find_object(git_blob_id)
-            .map_err(|err| map_not_found_err(err, id))?
-            .try_into_blob()
-            .map_err(|err| to_read_object_err(err, id))?;
-        let target = String::from_utf8(blob.take_data())
-            .map_err(|err| to_invalid_utf8_err(err.utf8_error(), id))?;
+        let data = self.read_blob_data(id)?;
+        let target =
+            String::from_utf8(data).map_err(|err| to_invalid_utf8_err(err.utf8_error(), id))?;
         Ok(target)
     }
 
@@ -995,63 +1190,14 @@ impl Backend for GitBackend {
         if id == &self.empty_tree_id {
             return Ok(Tree::default());
         }
-        let git_tree_id = validate_git_object_id(id)?;
-
-        let locked_repo = self.lock_git_repo();
-        let git_tree = locked_repo
-            .find_object(git_tree_id)
-            .map_err(|err| map_not_found_err(err, id))?
-            .try_into_tree()
-            .map_err(|err| to_read_object_err(err, id))?;
-        let mut tree = Tree::default();
-        for entry in git_tree.iter() {
-            let entry = entry.map_err(|err| to_read_object_err(err, id))?;
-            let name =
-                str::from_utf8(entry.filename()).map_err(|err| to_invalid_utf8_err(err, id))?;
-            let (name, value) = match entry.mode().kind() {
-                gix::object::tree::EntryKind::Tree => {
-                    let id = TreeId::from_bytes(entry.oid().as_bytes());
-                    (name, TreeValue::Tree(id))
-                }
-                gix::object::tree::EntryKind::Blob => {
-                    let id = FileId::from_bytes(entry.oid().as_bytes());
-                    if let Some(basename) = name.strip_suffix(CONFLICT_SUFFIX) {
-                        (
-                            basename,
-                            TreeValue::Conflict(ConflictId::from_bytes(entry.oid().as_bytes())),
-                        )
-                    } else {
-                        (
-                            name,
-                            TreeValue::File {
-                                id,
-                                executable: false,
-                            },
-                        )
-                    }
-                }
-                gix::object::tree::EntryKind::BlobExecutable => {
-                    let id = FileId::from_bytes(entry.oid().as_bytes());
-                    (
-                        name,
-                        TreeValue::File {
-                            id,
-                            executable: true,
-                        },
-                    )
-                }
-                gix::object::tree::EntryKind::Link => {
-                    let id = SymlinkId::from_bytes(entry.oid().as_bytes());
-                    (name, TreeValue::Symlink(id))
-                }
-                gix::object::tree::EntryKind::Commit => {
-                    let id = CommitId::from_bytes(entry.oid().as_bytes());
-                    (name, TreeValue::GitSubmodule(id))
-                }
-            };
-            tree.set(RepoPathComponentBuf::from(name), value);
-        }
-        Ok(tree)
+        let base_repo = self.base_repo.clone();
+        let worker_repos = self.worker_repos.clone();
+        let id = id.clone();
+        read_on_thread_pool(move || {
+            let repo = worker_repos.get_or(|| base_repo.to_thread_local());
+            read_tree_from_repo(repo, &id)
+        })
+        .await
     }
 
     async fn write_tree(&self, _path: &RepoPath, contents: &Tree) -> BackendResult<TreeId> {
@@ -1391,8 +1537,8 @@ impl Backend for GitBackend {
         // mtime <= keep_newer? (it won't be consistent with no-gc refs
         // preserved by the keep_newer timestamp though)
         // TODO: remove unreachable extras table segments
-        // TODO: pass in keep_newer to "git gc" command
-        run_git_gc(self.git_repo_path()).map_err(|err| BackendError::Other(err.into()))?;
+        run_git_gc(self.git_repo_path(), keep_newer)
+            .map_err(|err| BackendError::Other(err.into()))?;
         // Since "git gc" will move loose refs into packed refs, in-memory
         // packed-refs cache should be invalidated without relying on mtime.
         git_repo.refs.force_refresh_packed_buffer().ok();
@@ -15,6 +15,7 @@
 use std::io;
 use std::io::BufReader;
 use std::io::Read;
+use std::io::Write as _;
 use std::num::NonZeroU32;
 use std::path::Path;
 use std::path::PathBuf;
@@ -52,6 +53,8 @@ pub enum GitSubprocessError {
     },
     #[error("Failed to wait for the git process")]
     Wait(std::io::Error),
+    #[error("Failed to write to the git process")]
+    Write(std::io::Error),
     #[error("Git process failed: {0}")]
     External(String),
 }
@@ -124,15 +127,17 @@ impl<'a> GitSubprocessContext<'a> {
         refspecs: &[RefSpec],
         callbacks: &mut RemoteCallbacks<'_>,
         depth: Option<NonZeroU32>,
+        prune: bool,
     ) -> Result<Option<String>, GitSubprocessError> {
         if refspecs.is_empty() {
             return Ok(None);
         }
         let mut command = self.create_command();
         command.stdout(Stdio::piped());
-        // attempt to prune stale refs with --prune
         // --no-write-fetch-head ensures our request is invisible to other parties
-        command.args(["fetch", "--prune", "--no-write-fetch-head"]);
+        command.args(["fetch", "--no-write-fetch-head"]);
+        // attempt to prune stale refs with --prune
+        command.arg(if prune { "--prune" } else { "--no-prune" });
         if callbacks.progress.is_some() {
             command.arg("--progress");
         }
@@ -147,6 +152,46 @@ impl<'a> GitSubprocessContext<'a> {
         parse_git_fetch_output(output)
     }
 
+    /// Fetch particular objects from a promisor remote
+    ///
+    /// This is how Git lazily fetches missing objects in a partial clone, but
+    /// we request all the objects at once.
+    pub(crate) fn spawn_fetch_objects(
+        &self,
+        remote_name: &str,
+        object_ids: &[gix::ObjectId],
+    ) -> Result<(), GitSubprocessError> {
+        if object_ids.is_empty() {
+            return Ok(());
+        }
+        let mut command = self.create_command();
+        command.stdin(Stdio::piped());
+        command.stdout(Stdio::null());
+        // We know exactly which objects we want, so don't bother negotiating
+        // common commits
+        command.args(["-c", "fetch.negotiationAlgorithm=noop"]);
+        command.args([
+            "fetch",
+            "--no-tags",
+            "--no-write-fetch-head",
+            "--recurse-submodules=no",
+            "--filter=blob:none",
+            "--stdin",
+            "--",
+            remote_name,
+        ]);
+
+        let mut child = self.spawn_cmd(command)?;
+        let mut stdin = child.stdin.take().unwrap();
+        for id in object_ids {
+            writeln!(stdin, "{id}").map_err(GitSubprocessError::Write)?;
+        }
+        drop(stdin);
+        let output = wait_with_output(child)?;
+
+        parse_git_fetch_objects_output(output)
+    }
+
     /// Prune particular branches
     pub(crate) fn spawn_branch_prune(
         &self,
@@ -194,6 +239,9 @@ impl<'a> GitSubprocessContext<'a> {
     /// All pushes are forced, using --force-with-lease to perform a test&set
     /// operation on the remote repository
     ///
+    /// If `negotiate` is true, common commits are negotiated with the remote
+    /// regardless of the `push.negotiate` config.
+    ///
     /// Return tuple with
     ///     1. refs that failed to push
     ///     2. refs that succeeded to push
@@ -201,10 +249,20 @@ impl<'a> GitSubprocessContext<'a> {
         &self,
         remote_name: &str,
         references: &[RefToPush],
+        negotiate: bool,
         callbacks: &mut RemoteCallbacks<'_>,
     ) -> Result<(Vec<String>, Vec<String>), GitSubprocessError> {
         let mut command = self.create_command();
         command.stdout(Stdio::piped());
+        // Negotiate common commits with the remote before generating the pack.
+        // Otherwise, objects reachable from our remote-tracking refs would be
+        // sent again if the remote refs moved to commits we don't know about.
+        // `git push` has no `--negotiation-tip` option. The negotiation starts
+        // from the pushed commits instead, which covers the remote bookmarks
+        // they are based on.
+        if negotiate {
+            command.args(["-c", "push.negotiate=true"]);
+        }
         // Currently jj does not support commit hooks, so we prevent git from running
         // them
         //
@@ -329,6 +387,19 @@ fn parse_git_fetch_output(output: Output) -> Result<Option<String>, GitSubproces
     Err(external_git_error(&output.stderr))
 }
 
+fn parse_git_fetch_objects_output(output: Output) -> Result<(), GitSubprocessError> {
+    if output.status.success() {
+        return Ok(());
+    }
+
+    // There are some git errors we want to parse out
+    if let Some(remote) = parse_no_such_remote(&output.stderr) {
+        return Err(GitSubprocessError::NoSuchRepository(remote));
+    }
+
+    Err(external_git_error(&output.stderr))
+}
+
 fn parse_git_branch_prune_output(output: Output) -> Result<(), GitSubprocessError> {
     if output.status.success() {
         return Ok(());
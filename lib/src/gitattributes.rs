@@ -0,0 +1,172 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Minimal support for `.gitattributes` files.
+//!
+//! Only the attributes jj understands are kept, which is currently just
+//! `conflict-marker-size`.
+
+use ignore::gitignore;
+
+use crate::repo_path::RepoPath;
+
+const CONFLICT_MARKER_SIZE: &str = "conflict-marker-size";
+
+/// A line of a `.gitattributes` file which sets or unsets
+/// `conflict-marker-size`.
+#[derive(Debug)]
+struct ConflictMarkerSizeRule {
+    matcher: gitignore::Gitignore,
+    /// The size, or `None` if the attribute is unset by this line.
+    size: Option<usize>,
+}
+
+/// Parsed contents of a `.gitattributes` file at the root of the workspace.
+#[derive(Debug, Default)]
+pub struct GitAttributesFile {
+    conflict_marker_size_rules: Vec<ConflictMarkerSizeRule>,
+}
+
+impl GitAttributesFile {
+    /// Parses the contents of a `.gitattributes` file.
+    ///
+    /// Like Git, lines which can't be parsed are ignored.
+    pub fn parse(input: &[u8]) -> Self {
+        let mut conflict_marker_size_rules = vec![];
+        for line in input.split(|b| *b == b'\n') {
+            let Ok(line) = std::str::from_utf8(line) else {
+                continue;
+            };
+            let mut fields = line.split_whitespace();
+            let Some(pattern) = fields.next() else {
+                continue;
+            };
+            // Negative patterns are forbidden in `.gitattributes`.
+            if pattern.starts_with('#') || pattern.starts_with('!') {
+                continue;
+            }
+            let Some(size) = fields.filter_map(parse_conflict_marker_size).next_back() else {
+                continue;
+            };
+            let mut builder = gitignore::GitignoreBuilder::new("");
+            if builder.add_line(None, pattern).is_err() {
+                continue;
+            }
+            let Ok(matcher) = builder.build() else {
+                continue;
+            };
+            conflict_marker_size_rules.push(ConflictMarkerSizeRule { matcher, size });
+        }
+        GitAttributesFile {
+            conflict_marker_size_rules,
+        }
+    }
+
+    /// Returns the `conflict-marker-size` attribute of the file at `path`, if
+    /// set.
+    pub fn conflict_marker_size(&self, path: &RepoPath) -> Option<usize> {
+        // The last matching line takes precedence.
+        self.conflict_marker_size_rules
+            .iter()
+            .rev()
+            .find(|rule| {
+                rule.matcher
+                    .matched(path.as_internal_file_string(), false)
+                    .is_ignore()
+            })
+            .and_then(|rule| rule.size)
+    }
+}
+
+/// Parses a `conflict-marker-size` attribute. Returns `Some(None)` if the
+/// attribute is unset, and `None` if it's another attribute.
+fn parse_conflict_marker_size(attribute: &str) -> Option<Option<usize>> {
+    if let Some(value) = attribute
+        .strip_prefix(CONFLICT_MARKER_SIZE)
+        .and_then(|rest| rest.strip_prefix('='))
+    {
+        Some(value.parse().ok())
+    } else if attribute
+        .strip_prefix(['-', '!'])
+        .is_some_and(|name| name == CONFLICT_MARKER_SIZE)
+    {
+        Some(None)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conflict_marker_size(input: &str, path: &str) -> Option<usize> {
+        let file = GitAttributesFile::parse(input.as_bytes());
+        file.conflict_marker_size(RepoPath::from_internal_string(path))
+    }
+
+    #[test]
+    fn test_gitattributes_conflict_marker_size() {
+        assert_eq!(conflict_marker_size("", "foo"), None);
+        assert_eq!(
+            conflict_marker_size("foo conflict-marker-size=15\n", "foo"),
+            Some(15)
+        );
+        assert_eq!(
+            conflict_marker_size("foo conflict-marker-size=15\n", "dir/foo"),
+            Some(15)
+        );
+        assert_eq!(
+            conflict_marker_size("foo conflict-marker-size=15\n", "food"),
+            None
+        );
+        assert_eq!(
+            conflict_marker_size("*.md text conflict-marker-size=9 diff\n", "doc/a.md"),
+            Some(9)
+        );
+        assert_eq!(
+            conflict_marker_size("/foo conflict-marker-size=15\n", "dir/foo"),
+            None
+        );
+        // Invalid values and other attributes are ignored
+        assert_eq!(
+            conflict_marker_size("foo conflict-marker-size=big\n", "foo"),
+            None
+        );
+        assert_eq!(conflict_marker_size("foo text\n", "foo"), None);
+        // Comments and negative patterns are ignored
+        assert_eq!(
+            conflict_marker_size("# foo conflict-marker-size=15\n", "foo"),
+            None
+        );
+        assert_eq!(
+            conflict_marker_size("!foo conflict-marker-size=15\n", "foo"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_gitattributes_conflict_marker_size_precedence() {
+        let input = indoc::indoc! {"
+            * conflict-marker-size=10
+            *.txt conflict-marker-size=20
+            unset.txt -conflict-marker-size
+            other.txt text
+        "};
+        assert_eq!(conflict_marker_size(input, "foo"), Some(10));
+        assert_eq!(conflict_marker_size(input, "foo.txt"), Some(20));
+        assert_eq!(conflict_marker_size(input, "unset.txt"), None);
+        assert_eq!(conflict_marker_size(input, "other.txt"), Some(20));
+    }
+}
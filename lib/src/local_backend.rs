@@ -34,6 +34,7 @@ use prost::Message;
 use tempfile::NamedTempFile;
 
 use crate::backend::make_root_commit;
+use crate::backend::read_on_thread_pool;
 use crate::backend::Backend;
 use crate::backend::BackendError;
 use crate::backend::BackendResult;
@@ -181,7 +182,8 @@ impl Backend for LocalBackend {
     }
 
     fn concurrency(&self) -> usize {
-        1
+        // Trees are read on the global thread pool by read_on_thread_pool().
+        rayon::current_num_threads()
     }
 
     async fn read_file(&self, _path: &RepoPath, id: &FileId) -> BackendResult<Box<dyn Read>> {
@@ -190,6 +192,12 @@ impl Backend for LocalBackend {
         Ok(Box::new(file))
     }
 
+    async fn read_file_size(&self, _path: &RepoPath, id: &FileId) -> BackendResult<u64> {
+        let path = self.file_path(id);
+        let metadata = fs::metadata(path).map_err(|err| map_not_found_err(err, id))?;
+        Ok(metadata.len())
+    }
+
     async fn write_file(
         &self,
         _path: &RepoPath,
@@ -240,10 +248,13 @@ impl Backend for LocalBackend {
 
     async fn read_tree(&self, _path: &RepoPath, id: &TreeId) -> BackendResult<Tree> {
         let path = self.tree_path(id);
-        let buf = fs::read(path).map_err(|err| map_not_found_err(err, id))?;
-
-        let proto = crate::protos::local_store::Tree::decode(&*buf).map_err(to_other_err)?;
-        Ok(tree_from_proto(proto))
+        let id = id.clone();
+        read_on_thread_pool(move || {
+            let buf = fs::read(path).map_err(|err| map_not_found_err(err, &id))?;
+            let proto = crate::protos::local_store::Tree::decode(&*buf).map_err(to_other_err)?;
+            Ok(tree_from_proto(proto))
+        })
+        .await
     }
 
     async fn write_tree(&self, _path: &RepoPath, tree: &Tree) -> BackendResult<TreeId> {
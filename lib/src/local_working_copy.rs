@@ -16,7 +16,10 @@
 #![allow(clippy::let_unit_value)]
 
 use std::any::Any;
+use std::borrow::Cow;
 use std::cmp::Ordering;
+use std::collections::hash_map;
+use std::collections::HashMap;
 use std::collections::HashSet;
 use std::error::Error;
 use std::fs;
@@ -35,12 +38,15 @@ use std::os::unix::fs::PermissionsExt;
 use std::path::Path;
 use std::path::PathBuf;
 use std::slice;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering as AtomicOrdering;
 use std::sync::mpsc::channel;
 use std::sync::mpsc::Sender;
 use std::sync::Arc;
 use std::sync::OnceLock;
 use std::time::UNIX_EPOCH;
 
+use bstr::BString;
 use either::Either;
 use futures::StreamExt;
 use itertools::EitherOrBoth;
@@ -66,19 +72,30 @@ use crate::backend::TreeId;
 use crate::backend::TreeValue;
 use crate::commit::Commit;
 use crate::conflicts;
+use crate::conflicts::binary_conflict_sidecar_names;
+use crate::conflicts::binary_conflict_sidecar_owner;
 use crate::conflicts::choose_materialized_conflict_marker_len;
+use crate::conflicts::is_binary_conflict;
 use crate::conflicts::materialize_merge_result_to_bytes_with_marker_len;
 use crate::conflicts::materialize_tree_value;
 use crate::conflicts::ConflictMarkerStyle;
 use crate::conflicts::MaterializedTreeValue;
 use crate::conflicts::MIN_CONFLICT_MARKER_LEN;
+use crate::eol;
+use crate::eol::EolConversionMode;
+use crate::eol::LineEndings;
+use crate::file_util::check_executable_bit_support;
 use crate::file_util::check_symlink_support;
+use crate::file_util::create_or_reuse_dir;
+use crate::file_util::persist_content_addressed_temp_file;
+use crate::file_util::try_reflink;
 use crate::file_util::try_symlink;
 #[cfg(feature = "watchman")]
 use crate::fsmonitor::watchman;
 use crate::fsmonitor::FsmonitorSettings;
 #[cfg(feature = "watchman")]
 use crate::fsmonitor::WatchmanConfig;
+use crate::gitattributes::GitAttributesFile;
 use crate::gitignore::GitIgnoreFile;
 use crate::lock::FileLock;
 use crate::matchers::DifferenceMatcher;
@@ -87,6 +104,7 @@ use crate::matchers::FilesMatcher;
 use crate::matchers::IntersectionMatcher;
 use crate::matchers::Matcher;
 use crate::matchers::PrefixMatcher;
+use crate::matchers::UnionMatcher;
 use crate::merge::Merge;
 use crate::merge::MergeBuilder;
 use crate::merge::MergedTreeValue;
@@ -101,15 +119,21 @@ use crate::repo_path::RepoPathBuf;
 use crate::repo_path::RepoPathComponent;
 use crate::store::Store;
 use crate::tree::Tree;
+use crate::working_copy::sparse_patterns_matcher;
 use crate::working_copy::CheckoutError;
 use crate::working_copy::CheckoutOptions;
+use crate::working_copy::CheckoutSkipReason;
 use crate::working_copy::CheckoutStats;
+use crate::working_copy::CloneMode;
+use crate::working_copy::ExecutableBitMode;
+use crate::working_copy::IgnoredPathKind;
 use crate::working_copy::LockedWorkingCopy;
 use crate::working_copy::ResetError;
 use crate::working_copy::SnapshotError;
 use crate::working_copy::SnapshotOptions;
 use crate::working_copy::SnapshotProgress;
 use crate::working_copy::SnapshotStats;
+use crate::working_copy::SparsePattern;
 use crate::working_copy::UntrackedReason;
 use crate::working_copy::WorkingCopy;
 use crate::working_copy::WorkingCopyFactory;
@@ -130,6 +154,9 @@ pub enum FileType {
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub struct MaterializedConflictData {
     pub conflict_marker_len: u32,
+    /// Set if the conflict is in a binary file, in which case the file
+    /// contains this side of the conflict instead of conflict markers.
+    pub binary_side: Option<u32>,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -138,6 +165,9 @@ pub struct FileState {
     pub mtime: MillisSinceEpoch,
     pub size: u64,
     pub materialized_conflict_data: Option<MaterializedConflictData>,
+    /// Whether this is a regular file standing in for a symlink in the tree.
+    /// The file contains the link target.
+    pub is_fake_symlink: bool,
     /* TODO: What else do we need here? Git stores a lot of fields.
      * TODO: Could possibly handle case-insensitive file systems keeping an
      *       Option<PathBuf> with the actual path here. */
@@ -152,6 +182,13 @@ impl FileState {
             && self.size == old_file_state.size
     }
 
+    /// Whether the file contains a side of a binary conflict instead of
+    /// conflict markers.
+    pub fn is_binary_conflict(&self) -> bool {
+        self.materialized_conflict_data
+            .is_some_and(|data| data.binary_side.is_some())
+    }
+
     /// Indicates that a file exists in the tree but that it needs to be
     /// re-stat'ed on the next snapshot.
     fn placeholder() -> Self {
@@ -164,6 +201,7 @@ impl FileState {
             mtime: MillisSinceEpoch(0),
             size: 0,
             materialized_conflict_data: None,
+            is_fake_symlink: false,
         }
     }
 
@@ -183,6 +221,7 @@ impl FileState {
             mtime: mtime_from_metadata(metadata),
             size,
             materialized_conflict_data,
+            is_fake_symlink: false,
         }
     }
 
@@ -195,6 +234,7 @@ impl FileState {
             mtime: mtime_from_metadata(metadata),
             size: metadata.len(),
             materialized_conflict_data: None,
+            is_fake_symlink: false,
         }
     }
 
@@ -204,6 +244,7 @@ impl FileState {
             mtime: MillisSinceEpoch(0),
             size: 0,
             materialized_conflict_data: None,
+            is_fake_symlink: false,
         }
     }
 }
@@ -412,10 +453,12 @@ pub struct TreeState {
     state_path: PathBuf,
     tree_id: MergedTreeId,
     file_states: FileStatesMap,
-    // Currently only path prefixes
-    sparse_patterns: Vec<RepoPathBuf>,
+    sparse_patterns: Vec<SparsePattern>,
     own_mtime: MillisSinceEpoch,
     symlink_support: bool,
+    /// Whether the file system keeps track of the executable bit. This is
+    /// probed when the working copy is initialized.
+    executable_bit_support: bool,
 
     /// The most recent clock value returned by Watchman. Will only be set if
     /// the repo is configured to use the Watchman filesystem monitor and
@@ -446,8 +489,10 @@ fn file_state_from_proto(proto: &crate::protos::working_copy::FileState) -> File
         materialized_conflict_data: proto.materialized_conflict_data.as_ref().map(|data| {
             MaterializedConflictData {
                 conflict_marker_len: data.conflict_marker_len,
+                binary_side: data.binary_side,
             }
         }),
+        is_fake_symlink: proto.is_fake_symlink,
     }
 }
 
@@ -469,8 +514,10 @@ fn file_state_to_proto(file_state: &FileState) -> crate::protos::working_copy::F
     proto.materialized_conflict_data = file_state.materialized_conflict_data.map(|data| {
         crate::protos::working_copy::MaterializedConflictData {
             conflict_marker_len: data.conflict_marker_len,
+            binary_side: data.binary_side,
         }
     });
+    proto.is_fake_symlink = file_state.is_fake_symlink;
     proto
 }
 
@@ -502,20 +549,66 @@ fn is_file_state_entries_proto_unique_and_sorted(
 
 fn sparse_patterns_from_proto(
     proto: Option<&crate::protos::working_copy::SparsePatterns>,
-) -> Vec<RepoPathBuf> {
+) -> Vec<SparsePattern> {
     let mut sparse_patterns = vec![];
     if let Some(proto_sparse_patterns) = proto {
-        for prefix in &proto_sparse_patterns.prefixes {
-            sparse_patterns.push(RepoPathBuf::from_internal_string(prefix));
+        if proto_sparse_patterns.patterns.is_empty() {
+            for prefix in &proto_sparse_patterns.prefixes {
+                let path = RepoPathBuf::from_internal_string(prefix);
+                sparse_patterns.push(SparsePattern::Include(path));
+            }
+        } else {
+            for pattern in &proto_sparse_patterns.patterns {
+                let path = RepoPathBuf::from_internal_string(&pattern.prefix);
+                sparse_patterns.push(if pattern.exclude {
+                    SparsePattern::Exclude(path)
+                } else {
+                    SparsePattern::Include(path)
+                });
+            }
         }
     } else {
         // For compatibility with old working copies.
         // TODO: Delete this is late 2022 or so.
-        sparse_patterns.push(RepoPathBuf::root());
+        sparse_patterns.push(SparsePattern::Include(RepoPathBuf::root()));
     }
     sparse_patterns
 }
 
+fn sparse_patterns_to_proto(
+    sparse_patterns: &[SparsePattern],
+) -> crate::protos::working_copy::SparsePatterns {
+    // Older versions only understand the included prefixes, so the ordered
+    // patterns are written only if there are exclusions. Included prefixes
+    // which overlap an excluded prefix are then left out of the prefixes for
+    // older versions, which would otherwise check out the excluded files.
+    let excluded_paths = sparse_patterns
+        .iter()
+        .filter(|pattern| pattern.is_exclude())
+        .map(SparsePattern::path)
+        .collect_vec();
+    let mut proto = crate::protos::working_copy::SparsePatterns::default();
+    for pattern in sparse_patterns {
+        let path = pattern.path();
+        let prefix = path.as_internal_file_string().to_owned();
+        if !excluded_paths.is_empty() {
+            proto
+                .patterns
+                .push(crate::protos::working_copy::SparsePattern {
+                    prefix: prefix.clone(),
+                    exclude: pattern.is_exclude(),
+                });
+        }
+        let overlaps_exclusion = excluded_paths
+            .iter()
+            .any(|excluded| path.starts_with(excluded) || excluded.starts_with(path));
+        if !pattern.is_exclude() && !overlaps_exclusion {
+            proto.prefixes.push(prefix);
+        }
+    }
+    proto
+}
+
 /// Creates intermediate directories from the `working_copy_path` to the
 /// `repo_path` parent. Returns disk path for the `repo_path` file.
 ///
@@ -642,8 +735,366 @@ fn can_create_new_file(disk_path: &Path) -> Result<bool, CheckoutError> {
     Ok(new_file_created)
 }
 
+/// Writes the sides and bases of a binary conflict at `disk_path` to the
+/// files next to it. Existing files aren't overwritten.
+fn write_binary_conflict_sidecars(
+    disk_path: &Path,
+    contents: &Merge<BString>,
+) -> Result<(), CheckoutError> {
+    let dir = disk_path.parent().expect("content path shouldn't be root");
+    let file_name = disk_path.file_name().unwrap().to_str().unwrap();
+    let names = binary_conflict_sidecar_names(file_name, contents.num_sides());
+    for (name, content) in iter::zip(names, contents.adds().chain(contents.removes())) {
+        let sidecar_path = dir.join(name);
+        let result = OpenOptions::new()
+            .write(true)
+            .create_new(true) // Don't follow symlink
+            .open(&sidecar_path);
+        match result {
+            Ok(mut file) => file.write_all(content),
+            // The file may belong to the user, so it's left alone.
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => Ok(()),
+            Err(err) => Err(err),
+        }
+        .map_err(|err| CheckoutError::Other {
+            message: format!("Failed to write file {}", sidecar_path.display()),
+            err: err.into(),
+        })?;
+    }
+    Ok(())
+}
+
+/// Removes the files written next to a binary conflict at `disk_path` whose
+/// terms are `file_ids`. Since the files aren't tracked, only the files which
+/// still contain the terms are removed, and errors are ignored.
+fn remove_binary_conflict_sidecars(
+    store: &Store,
+    repo_path: &RepoPath,
+    disk_path: &Path,
+    file_ids: &Merge<Option<FileId>>,
+) {
+    let dir = disk_path.parent().expect("content path shouldn't be root");
+    let file_name = disk_path.file_name().unwrap().to_str().unwrap();
+    let names = binary_conflict_sidecar_names(file_name, file_ids.num_sides());
+    for (name, id) in iter::zip(names, file_ids.adds().chain(file_ids.removes())) {
+        let sidecar_path = dir.join(name);
+        let Ok(content) = fs::read(&sidecar_path) else {
+            continue;
+        };
+        let mut expected_content = vec![];
+        if let Some(id) = id {
+            let result = store
+                .read_file(repo_path, id)
+                .map_err(io::Error::other)
+                .and_then(|mut reader| reader.read_to_end(&mut expected_content));
+            if result.is_err() {
+                continue;
+            }
+        }
+        if content == expected_content {
+            fs::remove_file(&sidecar_path).ok();
+        }
+    }
+}
+
+/// Number of diff entries to materialize before writing them to disk in
+/// parallel.
+const CHECKOUT_BATCH_SIZE: usize = 1000;
+
+/// Size of the file contents to read before they are written to disk in
+/// parallel. This bounds the memory used by a batch of large files.
+const CHECKOUT_BATCH_MAX_BYTES: usize = 64 << 20;
+
+/// File to be written to disk by a checkout worker thread. The parent
+/// directories have already been created.
+struct CheckoutFileTask {
+    disk_path: PathBuf,
+    /// Whether the path was present in the old tree, in which case the file on
+    /// disk is replaced.
+    replace_existing: bool,
+    contents: CheckoutContents,
+}
+
+/// Contents of a [`CheckoutFileTask`], read from the store beforehand because
+/// the store readers can't be sent to other threads.
+enum CheckoutContents {
+    File {
+        id: FileId,
+        executable: bool,
+        data: Vec<u8>,
+        clone_mode: CloneMode,
+    },
+    Symlink {
+        target: String,
+    },
+    GitSubmodule,
+    Conflict {
+        data: Vec<u8>,
+        executable: bool,
+        materialized_conflict_data: Option<MaterializedConflictData>,
+        /// The terms to write next to a binary conflict.
+        binary_contents: Option<Merge<BString>>,
+    },
+}
+
+impl CheckoutContents {
+    /// Number of bytes held in memory until the file is written.
+    fn len(&self) -> usize {
+        match self {
+            CheckoutContents::File { data, .. } => data.len(),
+            CheckoutContents::Symlink { target } => target.len(),
+            CheckoutContents::GitSubmodule => 0,
+            CheckoutContents::Conflict {
+                data,
+                binary_contents,
+                ..
+            } => {
+                let binary_len = binary_contents
+                    .iter()
+                    .flat_map(|contents| contents.iter())
+                    .map(|term| term.len())
+                    .sum::<usize>();
+                data.len() + binary_len
+            }
+        }
+    }
+}
+
+enum CheckoutFileOutcome {
+    Written {
+        file_state: FileState,
+        bytes_written: u64,
+        fake_symlink: bool,
+    },
+    Removed,
+    Skipped(CheckoutSkipReason),
+}
+
+impl CheckoutFileOutcome {
+    fn parent_not_directory() -> Self {
+        CheckoutFileOutcome::Skipped(CheckoutSkipReason::ParentNotDirectory)
+    }
+}
+
+fn is_removal(value: &MaterializedTreeValue) -> bool {
+    matches!(
+        value,
+        MaterializedTreeValue::Absent | MaterializedTreeValue::AccessDenied(_)
+    )
+}
+
+/// Returns the paths which may refer to the same file or directory as another
+/// path on a case-insensitive file system, either themselves or through their
+/// parent directories.
+fn find_case_colliding_paths<'a>(
+    paths: impl IntoIterator<Item = &'a RepoPath>,
+) -> HashSet<RepoPathBuf> {
+    let paths = paths.into_iter().collect_vec();
+    let prefixes = |path: &'a RepoPath| {
+        let path = path.as_internal_file_string();
+        path.match_indices('/')
+            .map(move |(i, _)| &path[..i])
+            .chain([path])
+    };
+    let mut spellings: HashMap<String, &str> = HashMap::new();
+    let mut colliding_prefixes = HashSet::new();
+    for &path in &paths {
+        for prefix in prefixes(path) {
+            let folded = prefix.to_lowercase();
+            match spellings.entry(folded) {
+                hash_map::Entry::Occupied(entry) => {
+                    if *entry.get() != prefix {
+                        colliding_prefixes.insert(entry.key().clone());
+                    }
+                }
+                hash_map::Entry::Vacant(entry) => {
+                    entry.insert(prefix);
+                }
+            }
+        }
+    }
+    if colliding_prefixes.is_empty() {
+        return HashSet::new();
+    }
+    paths
+        .into_iter()
+        .filter(|&path| {
+            prefixes(path).any(|prefix| colliding_prefixes.contains(&prefix.to_lowercase()))
+        })
+        .map(|path| path.to_owned())
+        .collect()
+}
+
+/// Reads the contents of the materialized value at `path` to be written by a
+/// checkout worker thread.
+fn checkout_contents(
+    path: &RepoPath,
+    value: MaterializedTreeValue,
+    git_attributes: &GitAttributesFile,
+    options: &CheckoutOptions,
+) -> Result<CheckoutContents, CheckoutError> {
+    let contents = match value {
+        MaterializedTreeValue::Absent | MaterializedTreeValue::AccessDenied(_) => {
+            panic!("unexpected absent value at {path:?}");
+        }
+        MaterializedTreeValue::File {
+            id,
+            executable,
+            mut reader,
+        } => {
+            let mut data = vec![];
+            reader
+                .read_to_end(&mut data)
+                .map_err(|err| CheckoutError::Other {
+                    message: format!("Failed to read file {}", path.as_internal_file_string()),
+                    err: err.into(),
+                })?;
+            // Converted contents don't match the file id, so they can't be
+            // shared through the cache.
+            let clone_mode = if options.eol_conversion_mode == EolConversionMode::InputOutput {
+                data = eol::convert_to_crlf(&data).into_owned();
+                CloneMode::Copy
+            } else {
+                options.clone_mode
+            };
+            CheckoutContents::File {
+                id,
+                executable,
+                data,
+                clone_mode,
+            }
+        }
+        MaterializedTreeValue::Symlink { id: _, target } => CheckoutContents::Symlink { target },
+        MaterializedTreeValue::GitSubmodule(_) => CheckoutContents::GitSubmodule,
+        MaterializedTreeValue::Tree(_) => {
+            panic!("unexpected tree entry in diff at {path:?}");
+        }
+        MaterializedTreeValue::FileConflict {
+            id: _,
+            contents,
+            executable,
+        } if is_binary_conflict(&contents) => {
+            // Conflict markers would corrupt binary files, so one of the sides
+            // is written instead, and all the terms are written next to it.
+            let side = options.binary_conflict_side.min(contents.num_sides() - 1);
+            let materialized_conflict_data = MaterializedConflictData {
+                conflict_marker_len: MIN_CONFLICT_MARKER_LEN as u32,
+                binary_side: Some(side as u32),
+            };
+            CheckoutContents::Conflict {
+                data: contents.get_add(side).unwrap().to_vec(),
+                executable,
+                materialized_conflict_data: Some(materialized_conflict_data),
+                binary_contents: Some(contents),
+            }
+        }
+        MaterializedTreeValue::FileConflict {
+            id: _,
+            contents,
+            executable,
+        } => {
+            // The markers must be longer than any marker-like lines in the
+            // contents, but they can be made longer by `.gitattributes`.
+            let conflict_marker_len = choose_materialized_conflict_marker_len(&contents)
+                .max(git_attributes.conflict_marker_size(path).unwrap_or(0));
+            let mut data: Vec<u8> = materialize_merge_result_to_bytes_with_marker_len(
+                &contents,
+                options.conflict_marker_style,
+                conflict_marker_len,
+            )
+            .into();
+            if options.eol_conversion_mode == EolConversionMode::InputOutput {
+                data = eol::convert_to_crlf(&data).into_owned();
+            }
+            let materialized_conflict_data = MaterializedConflictData {
+                conflict_marker_len: conflict_marker_len.try_into().unwrap_or(u32::MAX),
+                binary_side: None,
+            };
+            CheckoutContents::Conflict {
+                data,
+                executable,
+                materialized_conflict_data: Some(materialized_conflict_data),
+                binary_contents: None,
+            }
+        }
+        MaterializedTreeValue::OtherConflict { id } => {
+            // Unless all terms are regular files, we can't do much better than
+            // trying to describe the merge.
+            CheckoutContents::Conflict {
+                data: id.describe().into_bytes(),
+                executable: false,
+                materialized_conflict_data: None,
+                binary_contents: None,
+            }
+        }
+    };
+    Ok(contents)
+}
+
+/// Combines the errors of the files which couldn't be written into one error
+/// listing all the failed paths.
+fn checkout_error_for_failed_paths(
+    mut failed_paths: Vec<(RepoPathBuf, CheckoutError)>,
+) -> CheckoutError {
+    if failed_paths.len() == 1 {
+        let (_, err) = failed_paths.pop().unwrap();
+        return err;
+    }
+    let message = format!(
+        "Failed to write {} files: {}",
+        failed_paths.len(),
+        failed_paths
+            .iter()
+            .map(|(path, _)| path.as_internal_file_string())
+            .join(", ")
+    );
+    let (_, err) = failed_paths.swap_remove(0);
+    CheckoutError::Other {
+        message,
+        err: err.into(),
+    }
+}
+
 const RESERVED_DIR_NAMES: &[&str] = &[".git", ".jj"];
 
+#[cfg_attr(windows, allow(unused_variables))]
+fn file_cache_entry_name(id: &FileId, executable: bool) -> String {
+    if executable {
+        format!("{}-x", id.hex())
+    } else {
+        id.hex()
+    }
+}
+
+/// Makes the temporary file read-only and moves it into the file cache.
+#[cfg_attr(windows, allow(unused_variables))]
+fn persist_file_cache_entry(
+    temp_file: NamedTempFile,
+    cache_path: &Path,
+    executable: bool,
+) -> io::Result<()> {
+    let mut permissions = temp_file.as_file().metadata()?.permissions();
+    #[cfg(unix)]
+    permissions.set_mode(if executable { 0o555 } else { 0o444 });
+    #[cfg(windows)]
+    permissions.set_readonly(true);
+    fs::set_permissions(temp_file.path(), permissions)?;
+    persist_content_addressed_temp_file(temp_file, cache_path)?;
+    Ok(())
+}
+
+fn is_read_only_cache_entry(metadata: &Metadata, executable: bool) -> bool {
+    #[cfg(unix)]
+    {
+        let expected_mode = if executable { 0o555 } else { 0o444 };
+        metadata.permissions().mode() & 0o777 == expected_mode
+    }
+    #[cfg(windows)]
+    {
+        metadata.permissions().readonly()
+    }
+}
+
 /// Suppose the `disk_path` exists, checks if the last component points to
 /// ".git" or ".jj" in the same parent directory.
 fn reject_reserved_existing_path(disk_path: &Path) -> Result<(), CheckoutError> {
@@ -686,6 +1137,13 @@ fn mtime_from_metadata(metadata: &Metadata) -> MillisSinceEpoch {
     )
 }
 
+/// Checks whether the file system at `dir` keeps track of the executable bit.
+/// If that can't be determined, the executable bit is assumed to be
+/// supported on Unix.
+fn probe_executable_bit_support(dir: &Path) -> bool {
+    check_executable_bit_support(dir).unwrap_or(cfg!(unix))
+}
+
 fn file_state(metadata: &Metadata) -> Option<FileState> {
     let metadata_file_type = metadata.file_type();
     let file_type = if metadata_file_type.is_dir() {
@@ -712,6 +1170,7 @@ fn file_state(metadata: &Metadata) -> Option<FileState> {
             mtime,
             size,
             materialized_conflict_data: None,
+            is_fake_symlink: false,
         }
     })
 }
@@ -751,12 +1210,12 @@ impl TreeState {
         self.file_states.all()
     }
 
-    pub fn sparse_patterns(&self) -> &Vec<RepoPathBuf> {
+    pub fn sparse_patterns(&self) -> &Vec<SparsePattern> {
         &self.sparse_patterns
     }
 
     fn sparse_matcher(&self) -> Box<dyn Matcher> {
-        Box::new(PrefixMatcher::new(&self.sparse_patterns))
+        sparse_patterns_matcher(&self.sparse_patterns)
     }
 
     pub fn init(
@@ -765,6 +1224,7 @@ impl TreeState {
         state_path: PathBuf,
     ) -> Result<TreeState, TreeStateError> {
         let mut wc = TreeState::empty(store, working_copy_path, state_path);
+        wc.executable_bit_support = probe_executable_bit_support(&wc.working_copy_path);
         wc.save()?;
         Ok(wc)
     }
@@ -777,9 +1237,10 @@ impl TreeState {
             state_path,
             tree_id,
             file_states: FileStatesMap::new(),
-            sparse_patterns: vec![RepoPathBuf::root()],
+            sparse_patterns: vec![SparsePattern::Include(RepoPathBuf::root())],
             own_mtime: MillisSinceEpoch(0),
             symlink_support: check_symlink_support().unwrap_or(false),
+            executable_bit_support: true,
             watchman_clock: None,
         }
     }
@@ -844,6 +1305,9 @@ impl TreeState {
             FileStatesMap::from_proto(proto.file_states, proto.is_file_states_sorted);
         self.sparse_patterns = sparse_patterns_from_proto(proto.sparse_patterns.as_ref());
         self.watchman_clock = proto.watchman_clock;
+        self.executable_bit_support = proto
+            .executable_bit_supported
+            .unwrap_or_else(|| probe_executable_bit_support(&self.working_copy_path));
         Ok(())
     }
 
@@ -863,14 +1327,9 @@ impl TreeState {
         proto.file_states = self.file_states.data.clone();
         // `FileStatesMap` is guaranteed to be sorted.
         proto.is_file_states_sorted = true;
-        let mut sparse_patterns = crate::protos::working_copy::SparsePatterns::default();
-        for path in &self.sparse_patterns {
-            sparse_patterns
-                .prefixes
-                .push(path.as_internal_file_string().to_owned());
-        }
-        proto.sparse_patterns = Some(sparse_patterns);
+        proto.sparse_patterns = Some(sparse_patterns_to_proto(&self.sparse_patterns));
         proto.watchman_clock = self.watchman_clock.clone();
+        proto.executable_bit_supported = Some(self.executable_bit_support);
 
         let mut temp_file = NamedTempFile::new_in(&self.state_path).unwrap();
         temp_file
@@ -954,11 +1413,20 @@ impl TreeState {
             ref fsmonitor_settings,
             progress,
             start_tracking_matcher,
+            force_tracking_matcher,
+            report_ignored_paths,
             max_new_file_size,
             conflict_marker_style,
+            eol_conversion_mode,
+            executable_bit_mode,
         } = options;
 
         let sparse_matcher = self.sparse_matcher();
+        let honor_executable_bit = match executable_bit_mode {
+            ExecutableBitMode::Auto => self.executable_bit_support,
+            ExecutableBitMode::PreserveRecorded => false,
+            ExecutableBitMode::HonorFilesystem => true,
+        };
 
         let fsmonitor_clock_needs_save = *fsmonitor_settings != FsmonitorSettings::None;
         let mut is_dirty = fsmonitor_clock_needs_save;
@@ -981,7 +1449,11 @@ impl TreeState {
         let (tree_entries_tx, tree_entries_rx) = channel();
         let (file_states_tx, file_states_rx) = channel();
         let (untracked_paths_tx, untracked_paths_rx) = channel();
+        let (ignored_paths_tx, ignored_paths_rx) = channel();
+        let (ignored_tracked_paths_tx, ignored_tracked_paths_rx) = channel();
+        let (mixed_eol_paths_tx, mixed_eol_paths_rx) = channel();
         let (deleted_files_tx, deleted_files_rx) = channel();
+        let num_read_files = AtomicUsize::new(0);
 
         trace_span!("traverse filesystem").in_scope(|| -> Result<(), SnapshotError> {
             let snapshotter = FileSnapshotter {
@@ -989,20 +1461,28 @@ impl TreeState {
                 current_tree: &self.current_tree()?,
                 matcher: &matcher,
                 start_tracking_matcher,
+                force_tracking_matcher,
                 // Move tx sides so they'll be dropped at the end of the scope.
                 tree_entries_tx,
                 file_states_tx,
                 untracked_paths_tx,
+                ignored_paths_tx: report_ignored_paths.then_some(ignored_paths_tx),
+                ignored_tracked_paths_tx,
+                mixed_eol_paths_tx,
                 deleted_files_tx,
+                num_read_files: &num_read_files,
                 error: OnceLock::new(),
                 progress,
                 max_new_file_size,
                 conflict_marker_style,
+                eol_conversion_mode,
+                honor_executable_bit,
             };
             let directory_to_visit = DirectoryToVisit {
                 dir: RepoPathBuf::root(),
                 disk_dir: self.working_copy_path.clone(),
                 git_ignore: base_ignores.clone(),
+                git_ignore_changed: false,
                 file_states: self.file_states.all(),
             };
             // Here we use scope as a queue of per-directory jobs.
@@ -1016,6 +1496,13 @@ impl TreeState {
 
         let stats = SnapshotStats {
             untracked_paths: untracked_paths_rx.into_iter().collect(),
+            mixed_eol_paths: mixed_eol_paths_rx.into_iter().sorted_unstable().collect(),
+            ignored_paths: ignored_paths_rx.into_iter().collect(),
+            ignored_tracked_paths: ignored_tracked_paths_rx
+                .into_iter()
+                .sorted_unstable()
+                .collect(),
+            num_read_files: num_read_files.into_inner(),
         };
         let mut tree_builder = MergedTreeBuilder::new(self.tree_id.clone());
         trace_span!("process tree entries").in_scope(|| {
@@ -1085,19 +1572,10 @@ impl TreeState {
                 });
             }
         };
-        let matcher: Option<Box<dyn Matcher>> = match changed_files {
-            None => None,
-            Some(changed_files) => {
-                let repo_paths = trace_span!("processing fsmonitor paths").in_scope(|| {
-                    changed_files
-                        .into_iter()
-                        .filter_map(|path| RepoPathBuf::from_relative_path(path).ok())
-                        .collect_vec()
-                });
-
-                Some(Box::new(FilesMatcher::new(repo_paths)))
-            }
-        };
+        let matcher = changed_files.map(|changed_files| {
+            trace_span!("processing fsmonitor paths")
+                .in_scope(|| matcher_for_changed_files(changed_files))
+        });
         Ok(FsmonitorMatcher {
             matcher,
             watchman_clock,
@@ -1105,10 +1583,38 @@ impl TreeState {
     }
 }
 
+/// Builds a matcher for the files reported by the filesystem monitor.
+///
+/// If a `.gitignore` file changed, files that weren't reported may have become
+/// visible, so the whole directory containing the `.gitignore` is scanned.
+fn matcher_for_changed_files(changed_files: Vec<PathBuf>) -> Box<dyn Matcher> {
+    let repo_paths = changed_files
+        .into_iter()
+        .filter_map(|path| RepoPathBuf::from_relative_path(path).ok())
+        .collect_vec();
+    let gitignore_dirs = repo_paths
+        .iter()
+        .filter_map(|path| path.split())
+        .filter(|(_, name)| name.as_internal_str() == ".gitignore")
+        .map(|(dir, _)| dir)
+        .collect_vec();
+    if gitignore_dirs.is_empty() {
+        Box::new(FilesMatcher::new(&repo_paths))
+    } else {
+        Box::new(UnionMatcher::new(
+            FilesMatcher::new(&repo_paths),
+            PrefixMatcher::new(gitignore_dirs),
+        ))
+    }
+}
+
 struct DirectoryToVisit<'a> {
     dir: RepoPathBuf,
     disk_dir: PathBuf,
     git_ignore: Arc<GitIgnoreFile>,
+    /// Whether any `.gitignore` file in this directory or its ancestors has
+    /// changed since the last snapshot.
+    git_ignore_changed: bool,
     file_states: FileStates<'a>,
 }
 
@@ -1130,14 +1636,21 @@ struct FileSnapshotter<'a> {
     current_tree: &'a MergedTree,
     matcher: &'a dyn Matcher,
     start_tracking_matcher: &'a dyn Matcher,
+    force_tracking_matcher: &'a dyn Matcher,
     tree_entries_tx: Sender<(RepoPathBuf, MergedTreeValue)>,
     file_states_tx: Sender<(RepoPathBuf, FileState)>,
     untracked_paths_tx: Sender<(RepoPathBuf, UntrackedReason)>,
+    ignored_paths_tx: Option<Sender<(RepoPathBuf, IgnoredPathKind)>>,
+    ignored_tracked_paths_tx: Sender<RepoPathBuf>,
+    mixed_eol_paths_tx: Sender<RepoPathBuf>,
     deleted_files_tx: Sender<RepoPathBuf>,
+    num_read_files: &'a AtomicUsize,
     error: OnceLock<SnapshotError>,
     progress: Option<&'a SnapshotProgress<'a>>,
     max_new_file_size: u64,
     conflict_marker_style: ConflictMarkerStyle,
+    eol_conversion_mode: EolConversionMode,
+    honor_executable_bit: bool,
 }
 
 impl FileSnapshotter<'_> {
@@ -1175,11 +1688,14 @@ impl FileSnapshotter<'_> {
             dir,
             disk_dir,
             git_ignore,
+            git_ignore_changed,
             file_states,
         } = directory_to_visit;
 
         let git_ignore = git_ignore
             .chain_with_file(&dir.to_internal_dir_string(), disk_dir.join(".gitignore"))?;
+        let git_ignore_changed = git_ignore_changed
+            || self.is_git_ignore_changed(&dir, &disk_dir, &git_ignore, file_states);
         let dir_entries: Vec<_> = disk_dir
             .read_dir()
             .and_then(|entries| entries.try_collect())
@@ -1193,8 +1709,15 @@ impl FileSnapshotter<'_> {
             // sequential scan should be fast enough.
             .with_min_len(100)
             .filter_map(|entry| {
-                self.process_dir_entry(&dir, &git_ignore, file_states, &entry, scope)
-                    .transpose()
+                self.process_dir_entry(
+                    &dir,
+                    &git_ignore,
+                    git_ignore_changed,
+                    file_states,
+                    &entry,
+                    scope,
+                )
+                .transpose()
             })
             .map(|item| match item {
                 Ok((PresentDirEntryKind::Dir, name)) => Ok(Either::Left(name)),
@@ -1211,6 +1734,7 @@ impl FileSnapshotter<'_> {
         &'scope self,
         dir: &RepoPath,
         git_ignore: &Arc<GitIgnoreFile>,
+        git_ignore_changed: bool,
         file_states: FileStates<'scope>,
         entry: &DirEntry,
         scope: &rayon::Scope<'scope>,
@@ -1232,21 +1756,40 @@ impl FileSnapshotter<'_> {
                 return Ok(None);
             }
         }
+        // The files written next to a binary conflict are left untracked.
+        if maybe_current_file_state.is_none() && !file_type.is_dir() {
+            let is_binary_conflict_sidecar = binary_conflict_sidecar_owner(&name_string)
+                .and_then(|owner| file_states.get_at(dir, RepoPathComponent::new(owner)))
+                .is_some_and(|state| state.is_binary_conflict());
+            if is_binary_conflict_sidecar {
+                return Ok(None);
+            }
+        }
 
         if file_type.is_dir() {
             let file_states = file_states.prefixed_at(dir, name);
-            if git_ignore.matches(&path.to_internal_dir_string()) {
+            if git_ignore.matches(&path.to_internal_dir_string())
+                && self.force_tracking_matcher.visit(&path).is_nothing()
+            {
                 // If the whole directory is ignored by .gitignore, visit only
                 // paths we're already tracking. This is because .gitignore in
                 // ignored directory must be ignored. It's also more efficient.
                 // start_tracking_matcher is NOT tested here because we need to
                 // scan directory entries to report untracked paths.
-                self.spawn_ok(scope, move |_| self.visit_tracked_files(file_states));
+                if let Some(tx) = &self.ignored_paths_tx {
+                    if file_states.is_empty() && !self.matcher.visit(&path).is_nothing() {
+                        tx.send((path, IgnoredPathKind::Directory)).ok();
+                    }
+                }
+                self.spawn_ok(scope, move |_| {
+                    self.visit_tracked_files(file_states, git_ignore_changed)
+                });
             } else if !self.matcher.visit(&path).is_nothing() {
                 let directory_to_visit = DirectoryToVisit {
                     dir: path,
                     disk_dir: entry.path(),
                     git_ignore: git_ignore.clone(),
+                    git_ignore_changed,
                     file_states,
                 };
                 self.spawn_ok(scope, |scope| {
@@ -1261,10 +1804,14 @@ impl FileSnapshotter<'_> {
                 progress(&path);
             }
             if maybe_current_file_state.is_none()
+                && !self.force_tracking_matcher.matches(&path)
                 && git_ignore.matches(path.as_internal_file_string())
             {
                 // If it wasn't already tracked and it matches
                 // the ignored paths, then ignore it.
+                if let Some(tx) = &self.ignored_paths_tx {
+                    tx.send((path, IgnoredPathKind::File)).ok();
+                }
                 Ok(None)
             } else if maybe_current_file_state.is_none()
                 && !self.start_tracking_matcher.matches(&path)
@@ -1288,6 +1835,12 @@ impl FileSnapshotter<'_> {
                     self.untracked_paths_tx.send((path, reason)).ok();
                     Ok(None)
                 } else if let Some(new_file_state) = file_state(&metadata) {
+                    if maybe_current_file_state.is_some()
+                        && git_ignore_changed
+                        && git_ignore.matches(path.as_internal_file_string())
+                    {
+                        self.ignored_tracked_paths_tx.send(path.clone()).ok();
+                    }
                     self.process_present_file(
                         path,
                         &entry.path(),
@@ -1305,8 +1858,43 @@ impl FileSnapshotter<'_> {
         }
     }
 
-    /// Visits only paths we're already tracking.
-    fn visit_tracked_files(&self, file_states: FileStates<'_>) -> Result<(), SnapshotError> {
+    /// Returns true if the `.gitignore` file in the directory is new or has
+    /// been modified since the last snapshot.
+    fn is_git_ignore_changed(
+        &self,
+        dir: &RepoPath,
+        disk_dir: &Path,
+        git_ignore: &GitIgnoreFile,
+        file_states: FileStates<'_>,
+    ) -> bool {
+        let name = RepoPathComponent::new(".gitignore");
+        let Some(new_file_state) = disk_dir
+            .join(name.as_internal_str())
+            .symlink_metadata()
+            .ok()
+            .and_then(|metadata| file_state(&metadata))
+        else {
+            return false;
+        };
+        match file_states.get_at(dir, name) {
+            Some(current_file_state) => !new_file_state.is_clean(&current_file_state),
+            // An untracked .gitignore would be detected as new by every
+            // snapshot, so only consider it if it is about to be tracked.
+            None => {
+                let path = dir.join(name);
+                self.start_tracking_matcher.matches(&path)
+                    && !git_ignore.matches(path.as_internal_file_string())
+            }
+        }
+    }
+
+    /// Visits only paths we're already tracking. If `report_ignored` is true,
+    /// the present files are reported as ignored tracked paths.
+    fn visit_tracked_files(
+        &self,
+        file_states: FileStates<'_>,
+        report_ignored: bool,
+    ) -> Result<(), SnapshotError> {
         for (tracked_path, current_file_state) in file_states {
             if current_file_state.file_type == FileType::GitSubmodule {
                 continue;
@@ -1326,6 +1914,11 @@ impl FileSnapshotter<'_> {
                 }
             };
             if let Some(new_file_state) = metadata.as_ref().and_then(file_state) {
+                if report_ignored {
+                    self.ignored_tracked_paths_tx
+                        .send(tracked_path.to_owned())
+                        .ok();
+                }
                 self.process_present_file(
                     tracked_path.to_owned(),
                     &disk_path,
@@ -1346,6 +1939,15 @@ impl FileSnapshotter<'_> {
         maybe_current_file_state: Option<&FileState>,
         mut new_file_state: FileState,
     ) -> Result<(), SnapshotError> {
+        // A file whose mtime isn't older than the state file is "racy": it may
+        // have been modified after it was recorded, so its contents are read
+        // again. If it turns out to be unchanged, its file state is still
+        // recorded so that the state file is saved with a newer mtime, and the
+        // file won't have to be read again by the next snapshot.
+        let is_racy = maybe_current_file_state.is_some_and(|current_file_state| {
+            new_file_state.is_clean(current_file_state)
+                && current_file_state.mtime >= self.tree_state.own_mtime
+        });
         let update = self.get_updated_tree_value(
             &path,
             disk_path,
@@ -1359,10 +1961,15 @@ impl FileSnapshotter<'_> {
             new_file_state.materialized_conflict_data =
                 maybe_current_file_state.and_then(|state| state.materialized_conflict_data);
         }
+        // Keep track of regular files standing in for symlinks
+        if matches!(new_file_state.file_type, FileType::Normal { .. }) {
+            new_file_state.is_fake_symlink =
+                maybe_current_file_state.is_some_and(|state| state.is_fake_symlink);
+        }
         if let Some(tree_value) = update {
             self.tree_entries_tx.send((path.clone(), tree_value)).ok();
         }
-        if Some(&new_file_state) != maybe_current_file_state {
+        if is_racy || Some(&new_file_state) != maybe_current_file_state {
             self.file_states_tx.send((path, new_file_state)).ok();
         }
         Ok(())
@@ -1423,15 +2030,16 @@ impl FileSnapshotter<'_> {
         if clean {
             Ok(None)
         } else {
+            self.num_read_files.fetch_add(1, AtomicOrdering::Relaxed);
             let current_tree_values = self.current_tree.path_value(repo_path)?;
-            let new_file_type = if !self.tree_state.symlink_support {
-                let mut new_file_type = new_file_state.file_type.clone();
-                if matches!(new_file_type, FileType::Normal { .. })
-                    && matches!(current_tree_values.as_normal(), Some(TreeValue::Symlink(_)))
-                {
-                    new_file_type = FileType::Symlink;
-                }
-                new_file_type
+            // A regular file in place of a symlink contains the link target if
+            // symlinks aren't supported or the file was checked out as such.
+            let is_fake_symlink = matches!(new_file_state.file_type, FileType::Normal { .. })
+                && (!self.tree_state.symlink_support
+                    || maybe_current_file_state.is_some_and(|state| state.is_fake_symlink))
+                && matches!(current_tree_values.as_normal(), Some(TreeValue::Symlink(_)));
+            let new_file_type = if is_fake_symlink {
+                FileType::Symlink
             } else {
                 new_file_state.file_type.clone()
             };
@@ -1447,7 +2055,7 @@ impl FileSnapshotter<'_> {
                     .block_on()?,
                 FileType::Symlink => {
                     let id = self
-                        .write_symlink_to_store(repo_path, disk_path)
+                        .write_symlink_to_store(repo_path, disk_path, is_fake_symlink)
                         .block_on()?;
                     Merge::normal(TreeValue::Symlink(id))
                 }
@@ -1474,21 +2082,40 @@ impl FileSnapshotter<'_> {
         materialized_conflict_data: Option<MaterializedConflictData>,
     ) -> Result<MergedTreeValue, SnapshotError> {
         if let Some(current_tree_value) = current_tree_values.as_resolved() {
-            #[cfg(unix)]
-            let _ = current_tree_value; // use the variable
             let id = self.write_file_to_store(repo_path, disk_path).await?;
-            // On Windows, we preserve the executable bit from the current tree.
-            #[cfg(windows)]
-            let executable = {
-                let () = executable; // use the variable
+            let executable = self.executable_bit_for_store(executable, || {
                 if let Some(TreeValue::File { id: _, executable }) = current_tree_value {
                     *executable
                 } else {
                     false
                 }
-            };
+            });
             Ok(Merge::normal(TreeValue::File { id, executable }))
         } else if let Some(old_file_ids) = current_tree_values.to_file_merge() {
+            let recorded_executable = || {
+                if let Some(merge) = current_tree_values.to_executable_merge() {
+                    merge.resolve_trivial().copied().unwrap_or_default()
+                } else {
+                    false
+                }
+            };
+            if let Some(side) = materialized_conflict_data.and_then(|data| data.binary_side) {
+                // The file contained a side of a binary conflict. Unless it
+                // was replaced, the conflict is kept.
+                let old_file_ids = old_file_ids.simplify();
+                let id = self.write_file_to_store(repo_path, disk_path).await?;
+                if old_file_ids.get_add(side as usize) == Some(&Some(id.clone())) {
+                    return Ok(current_tree_values.clone());
+                }
+                remove_binary_conflict_sidecars(
+                    &self.tree_state.store,
+                    repo_path,
+                    disk_path,
+                    &old_file_ids,
+                );
+                let executable = self.executable_bit_for_store(executable, recorded_executable);
+                return Ok(Merge::normal(TreeValue::File { id, executable }));
+            }
             // If the file contained a conflict before and is a normal file on
             // disk, we try to parse any conflict markers in the file into a
             // conflict.
@@ -1496,6 +2123,7 @@ impl FileSnapshotter<'_> {
                 message: format!("Failed to open file {}", disk_path.display()),
                 err: err.into(),
             })?;
+            let content = self.convert_eol_for_store(repo_path, &content);
             let new_file_ids = conflicts::update_from_content(
                 &old_file_ids,
                 self.store(),
@@ -1509,16 +2137,7 @@ impl FileSnapshotter<'_> {
             .block_on()?;
             match new_file_ids.into_resolved() {
                 Ok(file_id) => {
-                    // On Windows, we preserve the executable bit from the merged trees.
-                    #[cfg(windows)]
-                    let executable = {
-                        let () = executable; // use the variable
-                        if let Some(merge) = current_tree_values.to_executable_merge() {
-                            merge.resolve_trivial().copied().unwrap_or_default()
-                        } else {
-                            false
-                        }
-                    };
+                    let executable = self.executable_bit_for_store(executable, recorded_executable);
                     Ok(Merge::normal(TreeValue::File {
                         id: file_id.unwrap(),
                         executable,
@@ -1537,11 +2156,36 @@ impl FileSnapshotter<'_> {
         }
     }
 
+    /// Returns the executable bit to be recorded in the tree. The bit on disk
+    /// is used if it's honored. Otherwise (and always on Windows), the bit
+    /// recorded in the current tree is preserved.
+    fn executable_bit_for_store(
+        &self,
+        disk_executable: FileExecutableFlag,
+        recorded_executable: impl FnOnce() -> bool,
+    ) -> bool {
+        #[cfg(unix)]
+        if self.honor_executable_bit {
+            return disk_executable;
+        }
+        #[cfg(windows)]
+        let ((), _) = (disk_executable, self.honor_executable_bit); // use the variables
+        recorded_executable()
+    }
+
     async fn write_file_to_store(
         &self,
         path: &RepoPath,
         disk_path: &Path,
     ) -> Result<FileId, SnapshotError> {
+        if self.eol_conversion_mode != EolConversionMode::None {
+            let content = fs::read(disk_path).map_err(|err| SnapshotError::Other {
+                message: format!("Failed to open file {}", disk_path.display()),
+                err: err.into(),
+            })?;
+            let content = self.convert_eol_for_store(path, &content);
+            return Ok(self.store().write_file(path, &mut content.as_ref()).await?);
+        }
         let mut file = File::open(disk_path).map_err(|err| SnapshotError::Other {
             message: format!("Failed to open file {}", disk_path.display()),
             err: err.into(),
@@ -1549,12 +2193,26 @@ impl FileSnapshotter<'_> {
         Ok(self.store().write_file(path, &mut file).await?)
     }
 
+    /// Converts line endings of file contents to be stored according to the
+    /// configured mode. Files with mixed line endings are reported and left
+    /// alone.
+    fn convert_eol_for_store<'b>(&self, path: &RepoPath, content: &'b [u8]) -> Cow<'b, [u8]> {
+        if self.eol_conversion_mode == EolConversionMode::None {
+            return Cow::Borrowed(content);
+        }
+        if LineEndings::detect(content) == LineEndings::Mixed {
+            self.mixed_eol_paths_tx.send(path.to_owned()).ok();
+        }
+        eol::convert_to_lf(content)
+    }
+
     async fn write_symlink_to_store(
         &self,
         path: &RepoPath,
         disk_path: &Path,
+        is_fake_symlink: bool,
     ) -> Result<SymlinkId, SnapshotError> {
-        if self.tree_state.symlink_support {
+        if !is_fake_symlink {
             let target = disk_path.read_link().map_err(|err| SnapshotError::Other {
                 message: format!("Failed to read symlink {}", disk_path.display()),
                 err: err.into(),
@@ -1611,8 +2269,8 @@ impl TreeState {
         Ok(FileState::for_file(executable, size, &metadata, None))
     }
 
-    fn write_symlink(&self, disk_path: &Path, target: String) -> Result<FileState, CheckoutError> {
-        let target = PathBuf::from(&target);
+    fn write_symlink(&self, disk_path: &Path, target: &str) -> Result<FileState, CheckoutError> {
+        let target = PathBuf::from(target);
         try_symlink(&target, disk_path).map_err(|err| CheckoutError::Other {
             message: format!(
                 "Failed to create symlink from {} to {}",
@@ -1627,6 +2285,18 @@ impl TreeState {
         Ok(FileState::for_symlink(&metadata))
     }
 
+    /// Writes a regular file containing the symlink target in place of the
+    /// symlink.
+    fn write_fake_symlink(
+        &self,
+        disk_path: &Path,
+        target: &str,
+    ) -> Result<FileState, CheckoutError> {
+        let mut file_state = self.write_file(disk_path, &mut target.as_bytes(), false)?;
+        file_state.is_fake_symlink = true;
+        Ok(file_state)
+    }
+
     fn write_conflict(
         &self,
         disk_path: &Path,
@@ -1660,6 +2330,146 @@ impl TreeState {
         ))
     }
 
+    /// Writes a file by cloning or hard-linking it from the file cache, adding
+    /// the contents to the cache if needed. Returns the file state and the
+    /// number of bytes written to disk.
+    fn write_file_from_cache(
+        &self,
+        disk_path: &Path,
+        id: &FileId,
+        contents: &mut dyn Read,
+        executable: bool,
+        clone_mode: CloneMode,
+    ) -> Result<(FileState, u64), CheckoutError> {
+        let cache_path = self.file_cache_path(id, executable);
+        let cached = cache_path.symlink_metadata().is_ok_and(|metadata| {
+            metadata.is_file() && is_read_only_cache_entry(&metadata, executable)
+        });
+        if !cached && clone_mode == CloneMode::Reflink {
+            // Write the contents only once, and let the cache share the blocks
+            // of the new file.
+            let file_state = self.write_file(disk_path, contents, executable)?;
+            self.add_clone_to_file_cache(disk_path, &cache_path, executable);
+            let size = file_state.size;
+            return Ok((file_state, size));
+        }
+        let mut bytes_written = 0;
+        if !cached {
+            bytes_written = self.add_to_file_cache(&cache_path, contents, executable)?;
+        }
+        // Read-only files can't be removed on Windows, so hard links are only
+        // used on Unix.
+        if cfg!(unix) && clone_mode == CloneMode::HardlinkFromCache {
+            match fs::hard_link(&cache_path, disk_path) {
+                Ok(()) => {
+                    let metadata = disk_path
+                        .symlink_metadata()
+                        .map_err(|err| checkout_error_for_stat_error(err, disk_path))?;
+                    let size = metadata.len();
+                    let file_state = FileState::for_file(executable, size, &metadata, None);
+                    return Ok((file_state, bytes_written));
+                }
+                Err(err) => {
+                    tracing::warn!(?err, "failed to hard-link file from cache, copying");
+                }
+            }
+        }
+        let mut cached_file = File::open(&cache_path).map_err(|err| CheckoutError::Other {
+            message: format!("Failed to open cached file {}", cache_path.display()),
+            err: err.into(),
+        })?;
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create_new(true) // Don't overwrite un-ignored file. Don't follow symlink.
+            .open(disk_path)
+            .map_err(|err| CheckoutError::Other {
+                message: format!("Failed to open file {} for writing", disk_path.display()),
+                err: err.into(),
+            })?;
+        let reflinked = clone_mode == CloneMode::Reflink
+            && try_reflink(&cached_file, &file)
+                .inspect_err(|err| {
+                    tracing::debug!(?err, "failed to clone file from cache, copying");
+                })
+                .is_ok();
+        if !reflinked {
+            bytes_written +=
+                io::copy(&mut cached_file, &mut file).map_err(|err| CheckoutError::Other {
+                    message: format!("Failed to write file {}", disk_path.display()),
+                    err: err.into(),
+                })?;
+        }
+        self.set_executable(disk_path, executable)?;
+        let metadata = file
+            .metadata()
+            .map_err(|err| checkout_error_for_stat_error(err, disk_path))?;
+        let size = metadata.len();
+        Ok((
+            FileState::for_file(executable, size, &metadata, None),
+            bytes_written,
+        ))
+    }
+
+    fn file_cache_dir(&self) -> PathBuf {
+        self.state_path.join("file_cache")
+    }
+
+    fn file_cache_path(&self, id: &FileId, executable: bool) -> PathBuf {
+        self.file_cache_dir()
+            .join(file_cache_entry_name(id, executable))
+    }
+
+    /// Writes a read-only copy of the file to the cache. Returns the number of
+    /// bytes written to the cache.
+    fn add_to_file_cache(
+        &self,
+        cache_path: &Path,
+        contents: &mut dyn Read,
+        executable: bool,
+    ) -> Result<u64, CheckoutError> {
+        let cache_error = |err: io::Error| CheckoutError::Other {
+            message: format!("Failed to write cached file {}", cache_path.display()),
+            err: err.into(),
+        };
+        let cache_dir = self.file_cache_dir();
+        create_or_reuse_dir(&cache_dir).map_err(cache_error)?;
+        let mut temp_file = NamedTempFile::new_in(&cache_dir).map_err(cache_error)?;
+        let size = io::copy(contents, temp_file.as_file_mut()).map_err(cache_error)?;
+        persist_file_cache_entry(temp_file, cache_path, executable).map_err(cache_error)?;
+        Ok(size)
+    }
+
+    /// Adds a clone of the newly written file to the cache. Nothing is written
+    /// if the file system can't share the blocks, since the cache is only an
+    /// optimization.
+    fn add_clone_to_file_cache(&self, disk_path: &Path, cache_path: &Path, executable: bool) {
+        let result = (|| {
+            let cache_dir = self.file_cache_dir();
+            create_or_reuse_dir(&cache_dir)?;
+            let file = File::open(disk_path)?;
+            let temp_file = NamedTempFile::new_in(&cache_dir)?;
+            try_reflink(&file, temp_file.as_file())?;
+            persist_file_cache_entry(temp_file, cache_path, executable)
+        })();
+        if let Err(err) = result {
+            tracing::debug!(?err, "failed to add cloned file to cache");
+        }
+    }
+
+    /// Removes cache entries of files that were replaced or removed by the
+    /// checkout, so the cache doesn't grow with every checkout. Files
+    /// hard-linked from the removed entries are unaffected.
+    fn prune_file_cache(&self, stale_entry_names: HashSet<String>) {
+        let cache_dir = self.file_cache_dir();
+        for name in stale_entry_names {
+            match fs::remove_file(cache_dir.join(&name)) {
+                Ok(()) => {}
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                Err(err) => tracing::debug!(?err, name, "failed to remove cached file"),
+            }
+        }
+    }
+
     #[cfg_attr(windows, allow(unused_variables))]
     fn set_executable(&self, disk_path: &Path, executable: bool) -> Result<(), CheckoutError> {
         #[cfg(unix)]
@@ -1674,7 +2484,7 @@ impl TreeState {
     pub fn check_out(
         &mut self,
         new_tree: &MergedTree,
-        options: &CheckoutOptions,
+        options: &CheckoutOptions<'_>,
     ) -> Result<CheckoutStats, CheckoutError> {
         let old_tree = self.current_tree().map_err(|err| match err {
             err @ BackendError::ObjectNotFound { .. } => CheckoutError::SourceNotFound {
@@ -1683,12 +2493,7 @@ impl TreeState {
             other => CheckoutError::InternalBackendError(other),
         })?;
         let stats = self
-            .update(
-                &old_tree,
-                new_tree,
-                self.sparse_matcher().as_ref(),
-                options.conflict_marker_style,
-            )
+            .update(&old_tree, new_tree, self.sparse_matcher().as_ref(), options)
             .block_on()?;
         self.tree_id = new_tree.id();
         Ok(stats)
@@ -1696,8 +2501,8 @@ impl TreeState {
 
     pub fn set_sparse_patterns(
         &mut self,
-        sparse_patterns: Vec<RepoPathBuf>,
-        options: &CheckoutOptions,
+        sparse_patterns: Vec<SparsePattern>,
+        options: &CheckoutOptions<'_>,
     ) -> Result<CheckoutStats, CheckoutError> {
         let tree = self.current_tree().map_err(|err| match err {
             err @ BackendError::ObjectNotFound { .. } => CheckoutError::SourceNotFound {
@@ -1705,26 +2510,16 @@ impl TreeState {
             },
             other => CheckoutError::InternalBackendError(other),
         })?;
-        let old_matcher = PrefixMatcher::new(&self.sparse_patterns);
-        let new_matcher = PrefixMatcher::new(&sparse_patterns);
+        let old_matcher = sparse_patterns_matcher(&self.sparse_patterns);
+        let new_matcher = sparse_patterns_matcher(&sparse_patterns);
         let added_matcher = DifferenceMatcher::new(&new_matcher, &old_matcher);
         let removed_matcher = DifferenceMatcher::new(&old_matcher, &new_matcher);
         let empty_tree = MergedTree::resolved(Tree::empty(self.store.clone(), RepoPathBuf::root()));
         let added_stats = self
-            .update(
-                &empty_tree,
-                &tree,
-                &added_matcher,
-                options.conflict_marker_style,
-            )
+            .update(&empty_tree, &tree, &added_matcher, options)
             .block_on()?;
         let removed_stats = self
-            .update(
-                &tree,
-                &empty_tree,
-                &removed_matcher,
-                options.conflict_marker_style,
-            )
+            .update(&tree, &empty_tree, &removed_matcher, options)
             .block_on()?;
         self.sparse_patterns = sparse_patterns;
         assert_eq!(added_stats.updated_files, 0);
@@ -1737,15 +2532,39 @@ impl TreeState {
             added_files: added_stats.added_files,
             removed_files: removed_stats.removed_files,
             skipped_files: added_stats.skipped_files,
+            skipped_paths: added_stats.skipped_paths,
+            fake_symlinks: added_stats.fake_symlinks,
+            bytes_written: added_stats.bytes_written,
         })
     }
 
+    /// Reads the `.gitattributes` file at the root of the tree, if any.
+    async fn read_git_attributes(
+        &self,
+        tree: &MergedTree,
+    ) -> Result<GitAttributesFile, CheckoutError> {
+        let path = RepoPath::from_internal_string(".gitattributes");
+        let Ok(Some(TreeValue::File { id, .. })) = tree.path_value(path)?.into_resolved() else {
+            return Ok(GitAttributesFile::default());
+        };
+        let mut content = vec![];
+        self.store
+            .read_file_async(path, &id)
+            .await?
+            .read_to_end(&mut content)
+            .map_err(|err| CheckoutError::Other {
+                message: "Failed to read .gitattributes".to_string(),
+                err: err.into(),
+            })?;
+        Ok(GitAttributesFile::parse(&content))
+    }
+
     async fn update(
         &mut self,
         old_tree: &MergedTree,
         new_tree: &MergedTree,
         matcher: &dyn Matcher,
-        conflict_marker_style: ConflictMarkerStyle,
+        options: &CheckoutOptions<'_>,
     ) -> Result<CheckoutStats, CheckoutError> {
         // TODO: maybe it's better not include the skipped counts in the "intended"
         // counts
@@ -1754,9 +2573,19 @@ impl TreeState {
             added_files: 0,
             removed_files: 0,
             skipped_files: 0,
+            skipped_paths: vec![],
+            fake_symlinks: 0,
+            bytes_written: 0,
         };
         let mut changed_file_states = Vec::new();
         let mut deleted_files = HashSet::new();
+        let mut failed_paths = Vec::new();
+        // Cache entries of the old file contents, and of the new contents which
+        // have to be kept even if they were stale before.
+        let mut stale_cache_entries = HashSet::new();
+        let mut used_cache_entries = HashSet::new();
+        let git_attributes = self.read_git_attributes(new_tree).await?;
+        let mut processed_entries = 0;
         let mut diff_stream = old_tree
             .diff_stream(new_tree, matcher)
             .map(|TreeDiffEntry { path, values }| async {
@@ -1768,118 +2597,361 @@ impl TreeState {
                     Err(err) => (path, Err(err)),
                 }
             })
-            .buffered(self.store.concurrency().max(1));
-        while let Some((path, data)) = diff_stream.next().await {
-            let (before, after) = data?;
-            if after.is_absent() {
-                stats.removed_files += 1;
-            } else if before.is_absent() {
-                stats.added_files += 1;
-            } else {
-                stats.updated_files += 1;
+            .buffered(self.store.concurrency().max(1))
+            .chunks(CHECKOUT_BATCH_SIZE);
+        while let Some(batch) = diff_stream.next().await {
+            let mut entries = Vec::with_capacity(batch.len());
+            for (path, data) in batch {
+                processed_entries += 1;
+                if let Some(progress) = options.progress {
+                    progress(processed_entries);
+                }
+                let (before, after) = match data {
+                    Ok(values) => values,
+                    Err(err) => {
+                        failed_paths.push((path, err.into()));
+                        continue;
+                    }
+                };
+                if options.clone_mode != CloneMode::Copy {
+                    if let Some(TreeValue::File { id, executable }) = before.as_normal() {
+                        stale_cache_entries.insert(file_cache_entry_name(id, *executable));
+                    }
+                    if let MaterializedTreeValue::File { id, executable, .. } = &after {
+                        used_cache_entries.insert(file_cache_entry_name(id, *executable));
+                    }
+                }
+                if after.is_absent() {
+                    stats.removed_files += 1;
+                } else if before.is_absent() {
+                    stats.added_files += 1;
+                } else {
+                    stats.updated_files += 1;
+                }
+
+                // Existing Git submodule can be a non-empty directory on disk. We
+                // shouldn't attempt to manage it as a tracked path.
+                //
+                // TODO: It might be better to add general support for paths not
+                // tracked by jj than processing submodules specially. For example,
+                // paths excluded by .gitignore can be marked as such so that
+                // newly-"unignored" paths won't be snapshotted automatically.
+                if matches!(before.as_normal(), Some(TreeValue::GitSubmodule(_)))
+                    && matches!(after, MaterializedTreeValue::GitSubmodule(_))
+                {
+                    eprintln!("ignoring git submodule at {path:?}");
+                    // Not updating the file state as if there were no diffs. Leave
+                    // the state type as FileType::GitSubmodule if it was before.
+                    continue;
+                }
+                entries.push((path, before, after));
             }
 
-            // Existing Git submodule can be a non-empty directory on disk. We
-            // shouldn't attempt to manage it as a tracked path.
-            //
-            // TODO: It might be better to add general support for paths not
-            // tracked by jj than processing submodules specially. For example,
-            // paths excluded by .gitignore can be marked as such so that
-            // newly-"unignored" paths won't be snapshotted automatically.
-            if matches!(before.as_normal(), Some(TreeValue::GitSubmodule(_)))
-                && matches!(after, MaterializedTreeValue::GitSubmodule(_))
-            {
-                eprintln!("ignoring git submodule at {path:?}");
-                // Not updating the file state as if there were no diffs. Leave
-                // the state type as FileType::GitSubmodule if it was before.
-                continue;
+            // Paths which may refer to the same file on a case-insensitive file
+            // system are checked out serially in path order after the others,
+            // so the result doesn't depend on the order of the writes.
+            let case_colliding_paths =
+                find_case_colliding_paths(entries.iter().map(|(path, _, _)| path.as_ref()));
+            let (serial_entries, parallel_entries): (Vec<_>, Vec<_>) = entries
+                .into_iter()
+                .partition(|(path, _, _)| case_colliding_paths.contains(path));
+
+            // Files are removed before the parent directories of the new files
+            // are created, so removing the emptied directories can't race with
+            // writing files into them.
+            let (removals, writes): (Vec<_>, Vec<_>) = parallel_entries
+                .into_iter()
+                .partition(|(_, _, after)| is_removal(after));
+            let mut outcomes = Vec::with_capacity(removals.len() + writes.len());
+            for (path, before, _) in removals {
+                let result = self.remove_checkout_file(&path, &before);
+                outcomes.push((path, result));
+            }
+            let mut tasks = Vec::new();
+            let mut tasks_len = 0;
+            for (path, before, after) in writes {
+                let task = match self.prepare_checkout_file(
+                    &path,
+                    &before,
+                    after,
+                    &git_attributes,
+                    options,
+                ) {
+                    Ok(Some(task)) => task,
+                    Ok(None) => {
+                        let outcome = CheckoutFileOutcome::parent_not_directory();
+                        outcomes.push((path, Ok(outcome)));
+                        continue;
+                    }
+                    Err(err) => {
+                        outcomes.push((path, Err(err)));
+                        continue;
+                    }
+                };
+                tasks_len += task.contents.len();
+                tasks.push((path, task));
+                if tasks_len >= CHECKOUT_BATCH_MAX_BYTES {
+                    outcomes.extend(self.write_checkout_files(mem::take(&mut tasks), options));
+                    tasks_len = 0;
+                }
             }
+            outcomes.extend(self.write_checkout_files(tasks, options));
 
-            // Create parent directories no matter if after.is_present(). This
-            // ensures that the path never traverses symlinks.
-            let Some(disk_path) = create_parent_dirs(&self.working_copy_path, &path)? else {
-                changed_file_states.push((path, FileState::placeholder()));
-                stats.skipped_files += 1;
-                continue;
-            };
-            // If the path was present, check reserved path first and delete it.
-            let present_file_deleted = before.is_present() && remove_old_file(&disk_path)?;
-            // If not, create temporary file to test the path validity.
-            if !present_file_deleted && !can_create_new_file(&disk_path)? {
-                changed_file_states.push((path, FileState::placeholder()));
-                stats.skipped_files += 1;
-                continue;
+            for (path, before, after) in serial_entries {
+                let result = if is_removal(&after) {
+                    self.remove_checkout_file(&path, &before)
+                } else {
+                    self.prepare_checkout_file(&path, &before, after, &git_attributes, options)
+                        .and_then(|task| match task {
+                            Some(task) => {
+                                self.write_checkout_file(&path, task, options.create_symlinks)
+                            }
+                            None => Ok(CheckoutFileOutcome::parent_not_directory()),
+                        })
+                };
+                outcomes.push((path, result));
             }
 
-            // TODO: Check that the file has not changed before overwriting/removing it.
-            let file_state = match after {
-                MaterializedTreeValue::Absent | MaterializedTreeValue::AccessDenied(_) => {
-                    let mut parent_dir = disk_path.parent().unwrap();
-                    loop {
-                        if fs::remove_dir(parent_dir).is_err() {
-                            break;
+            for (path, result) in outcomes {
+                match result {
+                    Ok(CheckoutFileOutcome::Written {
+                        file_state,
+                        bytes_written,
+                        fake_symlink,
+                    }) => {
+                        // Symlinks which were already written as regular files
+                        // are reported only once.
+                        let was_fake_symlink = self
+                            .file_states
+                            .all()
+                            .get(&path)
+                            .is_some_and(|state| state.is_fake_symlink);
+                        if fake_symlink && !was_fake_symlink {
+                            stats.fake_symlinks += 1;
                         }
-                        parent_dir = parent_dir.parent().unwrap();
+                        stats.bytes_written += bytes_written;
+                        changed_file_states.push((path, file_state));
                     }
-                    deleted_files.insert(path);
-                    continue;
-                }
-                MaterializedTreeValue::File {
-                    executable,
-                    mut reader,
-                    ..
-                } => self.write_file(&disk_path, &mut reader, executable)?,
-                MaterializedTreeValue::Symlink { id: _, target } => {
-                    if self.symlink_support {
-                        self.write_symlink(&disk_path, target)?
-                    } else {
-                        self.write_file(&disk_path, &mut target.as_bytes(), false)?
+                    Ok(CheckoutFileOutcome::Removed) => {
+                        deleted_files.insert(path);
                     }
+                    Ok(CheckoutFileOutcome::Skipped(reason)) => {
+                        changed_file_states.push((path.clone(), FileState::placeholder()));
+                        stats.skipped_files += 1;
+                        stats.skipped_paths.push((path, reason));
+                    }
+                    Err(err) => failed_paths.push((path, err)),
                 }
-                MaterializedTreeValue::GitSubmodule(_) => {
-                    eprintln!("ignoring git submodule at {path:?}");
-                    FileState::for_gitsubmodule()
-                }
-                MaterializedTreeValue::Tree(_) => {
-                    panic!("unexpected tree entry in diff at {path:?}");
-                }
-                MaterializedTreeValue::FileConflict {
-                    id: _,
-                    contents,
-                    executable,
-                } => {
-                    let conflict_marker_len = choose_materialized_conflict_marker_len(&contents);
-                    let data = materialize_merge_result_to_bytes_with_marker_len(
-                        &contents,
-                        conflict_marker_style,
-                        conflict_marker_len,
-                    )
-                    .into();
-                    let materialized_conflict_data = MaterializedConflictData {
-                        conflict_marker_len: conflict_marker_len.try_into().unwrap_or(u32::MAX),
-                    };
-                    self.write_conflict(
-                        &disk_path,
-                        data,
-                        executable,
-                        Some(materialized_conflict_data),
-                    )?
-                }
-                MaterializedTreeValue::OtherConflict { id } => {
-                    // Unless all terms are regular files, we can't do much
-                    // better than trying to describe the merge.
-                    let data = id.describe().into_bytes();
-                    let executable = false;
-                    self.write_conflict(&disk_path, data, executable, None)?
-                }
-            };
-            changed_file_states.push((path, file_state));
+            }
+            // The other files of the batch are still written, so all the
+            // failed paths of the batch can be reported at once, but later
+            // batches aren't started.
+            if !failed_paths.is_empty() {
+                break;
+            }
+        }
+        if !failed_paths.is_empty() {
+            failed_paths.sort_unstable_by(|(path1, _), (path2, _)| path1.cmp(path2));
+            return Err(checkout_error_for_failed_paths(failed_paths));
         }
+        stale_cache_entries.retain(|name| !used_cache_entries.contains(name));
+        self.prune_file_cache(stale_cache_entries);
+        // The outcomes are collected in a different order than the paths in
+        // the diff.
+        changed_file_states.sort_unstable_by(|(path1, _), (path2, _)| path1.cmp(path2));
+        stats
+            .skipped_paths
+            .sort_unstable_by(|(path1, _), (path2, _)| path1.cmp(path2));
         self.file_states
             .merge_in(changed_file_states, &deleted_files);
         Ok(stats)
     }
 
+    /// Removes the files next to a binary conflict at `path`. They aren't
+    /// tracked, so they have to be removed along with the conflict.
+    fn remove_binary_conflict_sidecars_at(
+        &self,
+        path: &RepoPath,
+        disk_path: &Path,
+        before: &MergedTreeValue,
+    ) {
+        if self
+            .file_states
+            .all()
+            .get(path)
+            .is_some_and(|state| state.is_binary_conflict())
+        {
+            if let Some(file_merge) = before.to_file_merge() {
+                remove_binary_conflict_sidecars(
+                    &self.store,
+                    path,
+                    disk_path,
+                    &file_merge.simplify(),
+                );
+            }
+        }
+    }
+
+    /// Removes the file at `path` which is absent in the new tree, and then
+    /// the parent directories if they become empty.
+    fn remove_checkout_file(
+        &self,
+        path: &RepoPath,
+        before: &MergedTreeValue,
+    ) -> Result<CheckoutFileOutcome, CheckoutError> {
+        // Create parent directories no matter if after.is_present(). This
+        // ensures that the path never traverses symlinks.
+        let Some(disk_path) = create_parent_dirs(&self.working_copy_path, path)? else {
+            return Ok(CheckoutFileOutcome::parent_not_directory());
+        };
+        self.remove_binary_conflict_sidecars_at(path, &disk_path, before);
+        // If the path was present, check reserved path first and delete it.
+        let present_file_deleted = before.is_present() && remove_old_file(&disk_path)?;
+        // If not, create temporary file to test the path validity.
+        if !present_file_deleted && !can_create_new_file(&disk_path)? {
+            return Ok(CheckoutFileOutcome::Skipped(
+                CheckoutSkipReason::UntrackedPathExists,
+            ));
+        }
+        let mut parent_dir = disk_path.parent().unwrap();
+        loop {
+            if fs::remove_dir(parent_dir).is_err() {
+                break;
+            }
+            parent_dir = parent_dir.parent().unwrap();
+        }
+        Ok(CheckoutFileOutcome::Removed)
+    }
+
+    /// Creates the parent directories of `path` and reads the contents to be
+    /// written. Returns `None` if a parent directory can't be created because
+    /// a file is in the way.
+    fn prepare_checkout_file(
+        &self,
+        path: &RepoPath,
+        before: &MergedTreeValue,
+        after: MaterializedTreeValue,
+        git_attributes: &GitAttributesFile,
+        options: &CheckoutOptions,
+    ) -> Result<Option<CheckoutFileTask>, CheckoutError> {
+        // Create parent directories no matter if after.is_present(). This
+        // ensures that the path never traverses symlinks.
+        let Some(disk_path) = create_parent_dirs(&self.working_copy_path, path)? else {
+            return Ok(None);
+        };
+        self.remove_binary_conflict_sidecars_at(path, &disk_path, before);
+        let contents = checkout_contents(path, after, git_attributes, options)?;
+        Ok(Some(CheckoutFileTask {
+            disk_path,
+            replace_existing: before.is_present(),
+            contents,
+        }))
+    }
+
+    /// Writes the files of the checkout on the thread pool.
+    fn write_checkout_files(
+        &self,
+        tasks: Vec<(RepoPathBuf, CheckoutFileTask)>,
+        options: &CheckoutOptions,
+    ) -> Vec<(RepoPathBuf, Result<CheckoutFileOutcome, CheckoutError>)> {
+        tasks
+            .into_par_iter()
+            .map(|(path, task)| {
+                let result = self.write_checkout_file(&path, task, options.create_symlinks);
+                (path, result)
+            })
+            .collect()
+    }
+
+    /// Writes a file of the checkout. This may be called from worker threads,
+    /// so the path on disk is checked right before the file is written, and
+    /// an untracked file is never overwritten.
+    fn write_checkout_file(
+        &self,
+        path: &RepoPath,
+        task: CheckoutFileTask,
+        create_symlinks: bool,
+    ) -> Result<CheckoutFileOutcome, CheckoutError> {
+        let CheckoutFileTask {
+            disk_path,
+            replace_existing,
+            contents,
+        } = task;
+        // If the path was present, check reserved path first and delete it.
+        let present_file_deleted = replace_existing && remove_old_file(&disk_path)?;
+        // If not, create temporary file to test the path validity.
+        if !present_file_deleted && !can_create_new_file(&disk_path)? {
+            return Ok(CheckoutFileOutcome::Skipped(
+                CheckoutSkipReason::UntrackedPathExists,
+            ));
+        }
+
+        // Set if the file was cloned or linked from the cache, in which case
+        // the file size doesn't reflect the number of bytes written.
+        let mut cached_bytes_written = None;
+        let mut fake_symlink = false;
+        // TODO: Check that the file has not changed before overwriting/removing it.
+        let file_state = match contents {
+            CheckoutContents::File {
+                id,
+                executable,
+                data,
+                clone_mode,
+            } => {
+                if clone_mode == CloneMode::Copy {
+                    self.write_file(&disk_path, &mut data.as_slice(), executable)?
+                } else {
+                    let (file_state, written) = self.write_file_from_cache(
+                        &disk_path,
+                        &id,
+                        &mut data.as_slice(),
+                        executable,
+                        clone_mode,
+                    )?;
+                    cached_bytes_written = Some(written);
+                    file_state
+                }
+            }
+            CheckoutContents::Symlink { target } => {
+                let symlink_file_state = if create_symlinks && self.symlink_support {
+                    self.write_symlink(&disk_path, &target)
+                        .inspect_err(|err| {
+                            tracing::warn!(?err, "failed to create symlink, writing file");
+                        })
+                        .ok()
+                } else {
+                    None
+                };
+                if let Some(file_state) = symlink_file_state {
+                    file_state
+                } else {
+                    fake_symlink = true;
+                    self.write_fake_symlink(&disk_path, &target)?
+                }
+            }
+            CheckoutContents::GitSubmodule => {
+                eprintln!("ignoring git submodule at {path:?}");
+                FileState::for_gitsubmodule()
+            }
+            CheckoutContents::Conflict {
+                data,
+                executable,
+                materialized_conflict_data,
+                binary_contents,
+            } => {
+                if let Some(binary_contents) = &binary_contents {
+                    write_binary_conflict_sidecars(&disk_path, binary_contents)?;
+                }
+                self.write_conflict(&disk_path, data, executable, materialized_conflict_data)?
+            }
+        };
+        Ok(CheckoutFileOutcome::Written {
+            bytes_written: cached_bytes_written.unwrap_or(file_state.size),
+            file_state,
+            fake_symlink,
+        })
+    }
+
     pub async fn reset(&mut self, new_tree: &MergedTree) -> Result<(), ResetError> {
         let old_tree = self.current_tree().map_err(|err| match err {
             err @ BackendError::ObjectNotFound { .. } => ResetError::SourceNotFound {
@@ -1927,6 +2999,7 @@ impl TreeState {
                     mtime: MillisSinceEpoch(0),
                     size: 0,
                     materialized_conflict_data: None,
+                    is_fake_symlink: false,
                 };
                 changed_file_states.push((path, file_state));
             }
@@ -1987,7 +3060,7 @@ impl WorkingCopy for LocalWorkingCopy {
         Ok(self.tree_state()?.current_tree_id())
     }
 
-    fn sparse_patterns(&self) -> Result<&[RepoPathBuf], WorkingCopyStateError> {
+    fn sparse_patterns(&self) -> Result<&[SparsePattern], WorkingCopyStateError> {
         Ok(self.tree_state()?.sparse_patterns())
     }
 
@@ -2255,7 +3328,7 @@ impl LockedWorkingCopy for LockedLocalWorkingCopy {
     fn check_out(
         &mut self,
         commit: &Commit,
-        options: &CheckoutOptions,
+        options: &CheckoutOptions<'_>,
     ) -> Result<CheckoutStats, CheckoutError> {
         // TODO: Write a "pending_checkout" file with the new TreeId so we can
         // continue an interrupted update if we find such a file.
@@ -2308,14 +3381,14 @@ impl LockedWorkingCopy for LockedLocalWorkingCopy {
         Ok(())
     }
 
-    fn sparse_patterns(&self) -> Result<&[RepoPathBuf], WorkingCopyStateError> {
+    fn sparse_patterns(&self) -> Result<&[SparsePattern], WorkingCopyStateError> {
         self.wc.sparse_patterns()
     }
 
     fn set_sparse_patterns(
         &mut self,
-        new_sparse_patterns: Vec<RepoPathBuf>,
-        options: &CheckoutOptions,
+        new_sparse_patterns: Vec<SparsePattern>,
+        options: &CheckoutOptions<'_>,
     ) -> Result<CheckoutStats, CheckoutError> {
         // TODO: Write a "pending_checkout" file with new sparse patterns so we can
         // continue an interrupted update if we find such a file.
@@ -2382,6 +3455,34 @@ mod tests {
         RepoPath::from_internal_string(value)
     }
 
+    #[test]
+    fn test_sparse_patterns_to_proto() {
+        let include = |value| SparsePattern::Include(repo_path(value).to_owned());
+        let exclude = |value| SparsePattern::Exclude(repo_path(value).to_owned());
+
+        // Without exclusions, only the prefixes are written
+        let patterns = vec![include("docs"), include("src")];
+        let proto = sparse_patterns_to_proto(&patterns);
+        assert_eq!(proto.prefixes, ["docs", "src"]);
+        assert!(proto.patterns.is_empty());
+        assert_eq!(sparse_patterns_from_proto(Some(&proto)), patterns);
+
+        // Included prefixes overlapping an exclusion aren't written for older
+        // versions
+        let patterns = vec![
+            include("docs"),
+            include("src"),
+            exclude("src/generated"),
+            include("src/generated/keep"),
+            exclude("tests"),
+            include("tests/unit"),
+        ];
+        let proto = sparse_patterns_to_proto(&patterns);
+        assert_eq!(proto.prefixes, ["docs"]);
+        assert_eq!(proto.patterns.len(), 6);
+        assert_eq!(sparse_patterns_from_proto(Some(&proto)), patterns);
+    }
+
     #[test]
     fn test_file_states_merge() {
         let new_state = |size| FileState {
@@ -2391,6 +3492,7 @@ mod tests {
             mtime: MillisSinceEpoch(0),
             size,
             materialized_conflict_data: None,
+            is_fake_symlink: false,
         };
         let new_static_entry = |path: &'static str, size| (repo_path(path), new_state(size));
         let new_owned_entry = |path: &str, size| (repo_path(path).to_owned(), new_state(size));
@@ -2431,6 +3533,47 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_find_case_colliding_paths() {
+        let find = |paths: &[&str]| {
+            find_case_colliding_paths(paths.iter().map(|&path| repo_path(path)))
+                .into_iter()
+                .map(RepoPathBuf::into_internal_string)
+                .sorted()
+                .collect_vec()
+        };
+        assert!(find(&["a", "b", "dir/a", "dir/b"]).is_empty());
+        assert_eq!(find(&["A", "a", "b"]), ["A", "a"]);
+        // Collisions through the parent directories
+        assert_eq!(find(&["B", "b/c", "b/d", "c"]), ["B", "b/c", "b/d"]);
+        assert_eq!(find(&["dir/a", "DIR/b", "dir2/a"]), ["DIR/b", "dir/a"]);
+    }
+
+    #[test]
+    fn test_matcher_for_changed_files() {
+        let matcher =
+            matcher_for_changed_files(vec![PathBuf::from("foo"), ["dir", "bar"].iter().collect()]);
+        assert!(matcher.matches(repo_path("foo")));
+        assert!(matcher.matches(repo_path("dir/bar")));
+        assert!(!matcher.matches(repo_path("dir/baz")));
+        assert!(!matcher.matches(repo_path("baz")));
+
+        // Everything in the directory of a changed .gitignore should be scanned
+        let matcher = matcher_for_changed_files(vec![
+            PathBuf::from("foo"),
+            ["dir", ".gitignore"].iter().collect(),
+        ]);
+        assert!(matcher.matches(repo_path("foo")));
+        assert!(matcher.matches(repo_path("dir/.gitignore")));
+        assert!(matcher.matches(repo_path("dir/baz")));
+        assert!(matcher.matches(repo_path("dir/sub/baz")));
+        assert!(!matcher.matches(repo_path("baz")));
+
+        let matcher = matcher_for_changed_files(vec![PathBuf::from(".gitignore")]);
+        assert!(matcher.matches(repo_path("baz")));
+        assert!(matcher.matches(repo_path("dir/baz")));
+    }
+
     #[test]
     fn test_file_states_lookup() {
         let new_state = |size| FileState {
@@ -2440,6 +3583,7 @@ mod tests {
             mtime: MillisSinceEpoch(0),
             size,
             materialized_conflict_data: None,
+            is_fake_symlink: false,
         };
         let new_proto_entry = |path: &str, size| {
             file_state_entry_to_proto(repo_path(path).to_owned(), &new_state(size))
@@ -2505,6 +3649,7 @@ mod tests {
             mtime: MillisSinceEpoch(0),
             size,
             materialized_conflict_data: None,
+            is_fake_symlink: false,
         };
         let new_proto_entry = |path: &str, size| {
             file_state_entry_to_proto(repo_path(path).to_owned(), &new_state(size))
@@ -25,6 +25,7 @@ use tracing::instrument;
 
 use crate::repo_path::RepoPath;
 use crate::repo_path::RepoPathComponentBuf;
+use crate::str_util::fold_case;
 
 #[derive(PartialEq, Eq, Debug)]
 pub enum Visit {
@@ -244,6 +245,7 @@ fn prefix_tree_to_visit_sets(tree: &RepoPathTree<PrefixNodeKind>) -> Visit {
 #[derive(Clone, Debug)]
 pub struct FileGlobsMatcher {
     tree: RepoPathTree<Vec<glob::Pattern>>,
+    ignore_case: bool,
 }
 
 impl FileGlobsMatcher {
@@ -254,7 +256,26 @@ impl FileGlobsMatcher {
         for (dir, pattern) in dir_patterns {
             tree.add(dir.as_ref()).value.push(pattern);
         }
-        FileGlobsMatcher { tree }
+        FileGlobsMatcher {
+            tree,
+            ignore_case: false,
+        }
+    }
+
+    /// Creates matcher that evaluates the patterns case-insensitively. The
+    /// `dir` paths are still matched case-sensitively.
+    pub fn new_case_insensitive<D: AsRef<RepoPath>>(
+        dir_patterns: impl IntoIterator<Item = (D, glob::Pattern)>,
+    ) -> Self {
+        let folded_patterns = dir_patterns.into_iter().map(|(dir, pattern)| {
+            let folded = glob::Pattern::new(&fold_case(pattern.as_str()))
+                .expect("case folding shouldn't break valid pattern");
+            (dir, folded)
+        });
+        FileGlobsMatcher {
+            ignore_case: true,
+            ..Self::new(folded_patterns)
+        }
     }
 }
 
@@ -274,7 +295,12 @@ impl Matcher for FileGlobsMatcher {
             .take_while(|(_, tail_path)| !tail_path.is_root()) // only dirs
             .any(|(sub, tail_path)| {
                 let name = tail_path.as_internal_file_string();
-                sub.value.iter().any(|pat| pat.matches_with(name, OPTIONS))
+                if self.ignore_case {
+                    let name = fold_case(name);
+                    sub.value.iter().any(|pat| pat.matches_with(&name, OPTIONS))
+                } else {
+                    sub.value.iter().any(|pat| pat.matches_with(name, OPTIONS))
+                }
             })
     }
 
@@ -775,6 +801,25 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_fileglobsmatcher_case_insensitive() {
+        let to_pattern = |s| glob::Pattern::new(s).unwrap();
+
+        let m = FileGlobsMatcher::new_case_insensitive([
+            (RepoPath::root(), to_pattern("Foo/*.RS")),
+            (repo_path("Dir"), to_pattern("ä?")),
+        ]);
+        assert!(m.matches(repo_path("foo/bar.rs")));
+        assert!(m.matches(repo_path("FOO/Bar.Rs")));
+        assert!(!m.matches(repo_path("foo/bar.rss")));
+        assert!(m.matches(repo_path("Dir/Äb")));
+        // Directory is matched case-sensitively
+        assert!(!m.matches(repo_path("dir/äb")));
+
+        let m = FileGlobsMatcher::new([(RepoPath::root(), to_pattern("Foo/*.RS"))]);
+        assert!(!m.matches(repo_path("foo/bar.rs")));
+    }
+
     #[test]
     fn test_fileglobsmatcher_wildcard_any() {
         let to_pattern = |s| glob::Pattern::new(s).unwrap();
@@ -748,6 +748,29 @@ mod tests {
         assert_eq!(trivial_merge(&[0, 1, 2, 3, 4]), None);
     }
 
+    #[test]
+    fn test_trivial_merge_four_sides() {
+        // All sides made the same change
+        assert_eq!(trivial_merge(&[1, 0, 1, 0, 1, 0, 1]), Some(&1));
+        // Two sides made the same change, and the other sides are unchanged
+        assert_eq!(trivial_merge(&[1, 0, 0, 0, 1, 0, 0]), Some(&1));
+        assert_eq!(trivial_merge(&[0, 0, 1, 0, 0, 0, 1]), Some(&1));
+        // Two sides made the same change, and the other sides cancel out
+        assert_eq!(trivial_merge(&[1, 0, 1, 2, 2, 0, 0]), Some(&1));
+        // Two pairs of sides made the same change within each pair, but the
+        // pairs made different changes. Collapsing each pair would lose track
+        // of the terms when the simplified conflict is updated.
+        assert_eq!(trivial_merge(&[1, 0, 1, 0, 2, 0, 2]), None);
+        assert_eq!(trivial_merge(&[1, 0, 2, 0, 1, 0, 2]), None);
+
+        let merge = c(&[1, 0, 1, 0, 0, 0, 0]).simplify();
+        assert_eq!(merge, c(&[1, 0, 1]));
+        assert_eq!(merge.resolve_trivial(), Some(&1));
+        let merge = c(&[1, 0, 1, 0, 2, 0, 2]).simplify();
+        assert_eq!(merge, c(&[1, 0, 1, 0, 2, 0, 2]));
+        assert_eq!(merge.resolve_trivial(), None);
+    }
+
     #[test]
     fn test_legacy_form_conversion() {
         fn test_equivalent<T>(legacy_form: (Vec<T>, Vec<T>), merge: Merge<Option<T>>)
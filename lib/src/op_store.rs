@@ -32,6 +32,7 @@ use crate::backend::CommitId;
 use crate::backend::MillisSinceEpoch;
 use crate::backend::Timestamp;
 use crate::content_hash::ContentHash;
+use crate::content_hash::DigestUpdate;
 use crate::merge::Merge;
 use crate::object_id::id_type;
 use crate::object_id::HexPrefix;
@@ -264,7 +265,7 @@ pub struct BookmarkTarget<'a> {
 
 /// Represents the way the repo looks at a given time, just like how a Tree
 /// object represents how the file system looks at a given time.
-#[derive(ContentHash, PartialEq, Eq, Clone, Debug)]
+#[derive(PartialEq, Eq, Clone, Debug)]
 pub struct View {
     /// All head commits
     pub head_ids: HashSet<CommitId>,
@@ -280,6 +281,26 @@ pub struct View {
     // (.jj/working_copy/) has the source of truth about which commit *is* checked out (to be
     // precise: the commit to which we most recently completed an update to).
     pub wc_commit_ids: HashMap<WorkspaceId, CommitId>,
+    /// Rewritten commits whose descendants haven't been rebased yet, mapped to
+    /// the new parents for their children.
+    pub deferred_rewrites: BTreeMap<CommitId, Vec<CommitId>>,
+}
+
+// Views without deferred rewrites hash the same as before the field was added,
+// so existing view ids don't change.
+impl ContentHash for View {
+    fn hash(&self, state: &mut impl DigestUpdate) {
+        self.head_ids.hash(state);
+        self.local_bookmarks.hash(state);
+        self.tags.hash(state);
+        self.remote_views.hash(state);
+        self.git_refs.hash(state);
+        self.git_head.hash(state);
+        self.wc_commit_ids.hash(state);
+        if !self.deferred_rewrites.is_empty() {
+            self.deferred_rewrites.hash(state);
+        }
+    }
 }
 
 impl View {
@@ -296,6 +317,7 @@ impl View {
             git_refs: BTreeMap::new(),
             git_head: RefTarget::absent(),
             wc_commit_ids: HashMap::new(),
+            deferred_rewrites: BTreeMap::new(),
         }
     }
 
@@ -309,6 +331,7 @@ impl View {
             git_refs: BTreeMap::new(),
             git_head: RefTarget::absent(),
             wc_commit_ids: HashMap::new(),
+            deferred_rewrites: BTreeMap::new(),
         }
     }
 }
@@ -391,11 +414,28 @@ pub(crate) fn flatten_remote_bookmarks(
 /// taken and it will be checked that the current head of the operation
 /// graph is unchanged. If the current head has changed, there has been
 /// concurrent operation.
-#[derive(ContentHash, PartialEq, Eq, Clone, Debug)]
+#[derive(PartialEq, Eq, Clone, Debug)]
 pub struct Operation {
     pub view_id: ViewId,
     pub parents: Vec<OperationId>,
     pub metadata: OperationMetadata,
+    /// Commits abandoned by this operation, and why they were abandoned.
+    ///
+    /// Operations written by older versions don't have this information.
+    pub abandoned_commits: BTreeMap<CommitId, AbandonReason>,
+}
+
+// Operations without abandoned commits hash the same as before the field was
+// added, so existing operation ids don't change.
+impl ContentHash for Operation {
+    fn hash(&self, state: &mut impl DigestUpdate) {
+        self.view_id.hash(state);
+        self.parents.hash(state);
+        self.metadata.hash(state);
+        if !self.abandoned_commits.is_empty() {
+            self.abandoned_commits.hash(state);
+        }
+    }
 }
 
 impl Operation {
@@ -417,6 +457,37 @@ impl Operation {
             view_id: root_view_id,
             parents: vec![],
             metadata,
+            abandoned_commits: BTreeMap::new(),
+        }
+    }
+}
+
+/// Why an operation abandoned a commit.
+#[derive(ContentHash, Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum AbandonReason {
+    /// The commit was abandoned explicitly, e.g. by `jj abandon`.
+    Explicit,
+    /// The commit became empty when it was rebased, and empty commits were
+    /// requested to be abandoned.
+    EmptiedByRebase,
+    /// All changes in the commit were squashed into another commit.
+    SquashedInto,
+    /// The working-copy commit was empty and undescribed, and was discarded
+    /// when another commit was checked out.
+    DiscardedWorkingCopy,
+    /// The commit became unreachable in the backing Git repository.
+    UnreachableInGit,
+}
+
+impl AbandonReason {
+    /// Returns a short human-readable description of the reason.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AbandonReason::Explicit => "explicit",
+            AbandonReason::EmptiedByRebase => "emptied by rebase",
+            AbandonReason::SquashedInto => "squashed into another commit",
+            AbandonReason::DiscardedWorkingCopy => "discarded working copy",
+            AbandonReason::UnreachableInGit => "unreachable in git",
         }
     }
 }
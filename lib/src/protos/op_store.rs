@@ -122,6 +122,27 @@ pub struct View {
     pub git_head_legacy: ::prost::alloc::vec::Vec<u8>,
     #[prost(message, optional, tag = "9")]
     pub git_head: ::core::option::Option<RefTarget>,
+    /// Introduced in jj 0.27.
+    #[prost(message, repeated, tag = "11")]
+    pub deferred_rewrites: ::prost::alloc::vec::Vec<DeferredRewrite>,
+}
+/// A rewritten commit whose descendants haven't been rebased yet.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeferredRewrite {
+    #[prost(bytes = "vec", tag = "1")]
+    pub old_commit_id: ::prost::alloc::vec::Vec<u8>,
+    /// The commits the children of the old commit should be rebased onto.
+    #[prost(bytes = "vec", repeated, tag = "2")]
+    pub new_parent_ids: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AbandonedCommit {
+    #[prost(bytes = "vec", tag = "1")]
+    pub commit_id: ::prost::alloc::vec::Vec<u8>,
+    #[prost(enumeration = "AbandonReason", tag = "2")]
+    pub reason: i32,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -132,6 +153,9 @@ pub struct Operation {
     pub parents: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
     #[prost(message, optional, tag = "3")]
     pub metadata: ::core::option::Option<OperationMetadata>,
+    /// Introduced in jj 0.27.
+    #[prost(message, repeated, tag = "4")]
+    pub abandoned_commits: ::prost::alloc::vec::Vec<AbandonedCommit>,
 }
 /// TODO: Share with store.proto? Do we even need the timezone here?
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -189,3 +213,41 @@ impl RemoteRefState {
         }
     }
 }
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum AbandonReason {
+    Unknown = 0,
+    Explicit = 1,
+    EmptiedByRebase = 2,
+    SquashedInto = 3,
+    DiscardedWorkingCopy = 4,
+    UnreachableInGit = 5,
+}
+impl AbandonReason {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            AbandonReason::Unknown => "Unknown",
+            AbandonReason::Explicit => "Explicit",
+            AbandonReason::EmptiedByRebase => "EmptiedByRebase",
+            AbandonReason::SquashedInto => "SquashedInto",
+            AbandonReason::DiscardedWorkingCopy => "DiscardedWorkingCopy",
+            AbandonReason::UnreachableInGit => "UnreachableInGit",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "Unknown" => Some(Self::Unknown),
+            "Explicit" => Some(Self::Explicit),
+            "EmptiedByRebase" => Some(Self::EmptiedByRebase),
+            "SquashedInto" => Some(Self::SquashedInto),
+            "DiscardedWorkingCopy" => Some(Self::DiscardedWorkingCopy),
+            "UnreachableInGit" => Some(Self::UnreachableInGit),
+            _ => None,
+        }
+    }
+}
@@ -5,6 +5,10 @@ pub struct MaterializedConflictData {
     /// TODO: maybe we should store num_sides here as well
     #[prost(uint32, tag = "1")]
     pub conflict_marker_len: u32,
+    /// Set if this is a binary conflict, in which case the file contains this
+    /// side of the conflict instead of conflict markers.
+    #[prost(uint32, optional, tag = "2")]
+    pub binary_side: ::core::option::Option<u32>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -21,6 +25,10 @@ pub struct FileState {
     pub conflict_id: ::prost::alloc::vec::Vec<u8>,
     #[prost(message, optional, tag = "5")]
     pub materialized_conflict_data: ::core::option::Option<MaterializedConflictData>,
+    /// Set if this is a symlink in the tree that was written as a regular file
+    /// containing the link target.
+    #[prost(bool, tag = "6")]
+    pub is_fake_symlink: bool,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -33,8 +41,21 @@ pub struct FileStateEntry {
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SparsePatterns {
+    /// Included prefixes. If `patterns` is set, this only exists for
+    /// compatibility with older versions.
     #[prost(string, repeated, tag = "1")]
     pub prefixes: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Included and excluded prefixes in order. The last matching pattern wins.
+    #[prost(message, repeated, tag = "2")]
+    pub patterns: ::prost::alloc::vec::Vec<SparsePattern>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SparsePattern {
+    #[prost(string, tag = "1")]
+    pub prefix: ::prost::alloc::string::String,
+    #[prost(bool, tag = "2")]
+    pub exclude: bool,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -53,6 +74,11 @@ pub struct TreeState {
     pub sparse_patterns: ::core::option::Option<SparsePatterns>,
     #[prost(message, optional, tag = "4")]
     pub watchman_clock: ::core::option::Option<WatchmanClock>,
+    /// Whether the file system keeps track of the executable bit. Unset if the
+    /// state was written by an older version and the file system hasn't been
+    /// probed yet.
+    #[prost(bool, optional, tag = "7")]
+    pub executable_bit_supported: ::core::option::Option<bool>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -15,6 +15,7 @@
 #![allow(missing_docs)]
 
 use std::collections::hash_map::Entry;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::fmt::Debug;
@@ -64,6 +65,7 @@ use crate::op_heads_store::OpHeadResolutionError;
 use crate::op_heads_store::OpHeadsStore;
 use crate::op_heads_store::OpHeadsStoreError;
 use crate::op_store;
+use crate::op_store::AbandonReason;
 use crate::op_store::OpStore;
 use crate::op_store::OpStoreError;
 use crate::op_store::OpStoreResult;
@@ -131,7 +133,9 @@ pub trait Repo {
 pub struct ReadonlyRepo {
     loader: RepoLoader,
     operation: Operation,
-    index: Box<dyn ReadonlyIndex>,
+    /// Loaded on first use so that commands which only need the view or the
+    /// store don't pay for opening the index.
+    index: OnceCell<Box<dyn ReadonlyIndex>>,
     change_id_index: OnceCell<Box<dyn ChangeIdIndex>>,
     // TODO: This should eventually become part of the index and not be stored fully in memory.
     view: View,
@@ -248,7 +252,7 @@ impl ReadonlyRepo {
         Ok(Arc::new(ReadonlyRepo {
             loader,
             operation: root_operation,
-            index,
+            index: OnceCell::with_value(index),
             change_id_index: OnceCell::new(),
             view: root_view,
         }))
@@ -270,8 +274,19 @@ impl ReadonlyRepo {
         &self.view
     }
 
+    /// Returns the index at this operation, loading it if needed.
+    pub fn load_index(&self) -> Result<&dyn ReadonlyIndex, IndexReadError> {
+        let index = self.index.get_or_try_init(|| {
+            self.index_store()
+                .get_index_at_op(&self.operation, self.store())
+        })?;
+        Ok(index.as_ref())
+    }
+
+    /// Returns the index at this operation. Panics if the index couldn't be
+    /// loaded; use [`Self::load_index()`] to handle the error.
     pub fn readonly_index(&self) -> &dyn ReadonlyIndex {
-        self.index.as_ref()
+        self.load_index().expect("failed to load index")
     }
 
     fn change_id_index(&self) -> &dyn ChangeIdIndex {
@@ -336,7 +351,8 @@ impl Repo for ReadonlyRepo {
     }
 
     fn resolve_change_id_prefix(&self, prefix: &HexPrefix) -> PrefixResolution<Vec<CommitId>> {
-        self.change_id_index().resolve_prefix(prefix)
+        let resolution = self.change_id_index().resolve_prefix(prefix);
+        exclude_deferred_rewrites(resolution, &self.view)
     }
 
     fn shortest_unique_change_id_prefix_len(&self, target_id: &ChangeId) -> usize {
@@ -344,6 +360,32 @@ impl Repo for ReadonlyRepo {
     }
 }
 
+/// Excludes the commits superseded by deferred rewrites from a change id
+/// resolution. They're only visible because their descendants haven't been
+/// rebased yet, so they shouldn't make the change divergent.
+fn exclude_deferred_rewrites(
+    resolution: PrefixResolution<Vec<CommitId>>,
+    view: &View,
+) -> PrefixResolution<Vec<CommitId>> {
+    if view.deferred_rewrites().is_empty() {
+        return resolution;
+    }
+    match resolution {
+        PrefixResolution::SingleMatch(ids) => {
+            let ids = ids
+                .into_iter()
+                .filter(|id| !view.is_deferred_rewrite(id))
+                .collect_vec();
+            if ids.is_empty() {
+                PrefixResolution::NoMatch
+            } else {
+                PrefixResolution::SingleMatch(ids)
+            }
+        }
+        resolution => resolution,
+    }
+}
+
 pub type BackendInitializer<'a> =
     dyn Fn(&UserSettings, &Path) -> Result<Box<dyn Backend>, BackendInitError> + 'a;
 #[rustfmt::skip] // auto-formatted line would exceed the maximum width
@@ -740,7 +782,7 @@ impl RepoLoader {
         let repo = ReadonlyRepo {
             loader: self.clone(),
             operation,
-            index,
+            index: OnceCell::with_value(index),
             change_id_index: OnceCell::new(),
             view,
         };
@@ -804,11 +846,10 @@ impl RepoLoader {
         operation: Operation,
         view: View,
     ) -> Result<Arc<ReadonlyRepo>, RepoLoaderError> {
-        let index = self.index_store.get_index_at_op(&operation, &self.store)?;
         let repo = ReadonlyRepo {
             loader: self.clone(),
             operation,
-            index,
+            index: OnceCell::new(),
             change_id_index: OnceCell::new(),
             view,
         };
@@ -852,6 +893,11 @@ pub struct MutableRepo {
     //   commits. However, if the type is `Abandoned`, a new working-copy commit should be created
     //   on top of all of the new commits instead.
     parent_mapping: HashMap<CommitId, Rewrite>,
+    // Why commits were abandoned in this transaction. Unlike `parent_mapping`,
+    // this isn't cleared by `rebase_descendants` so it can be recorded in the
+    // operation.
+    abandon_reasons: BTreeMap<CommitId, AbandonReason>,
+    defer_descendant_rebase: bool,
 }
 
 impl MutableRepo {
@@ -862,11 +908,14 @@ impl MutableRepo {
     ) -> MutableRepo {
         let mut_view = view.clone();
         let mut_index = index.start_modification();
+        let defer_descendant_rebase = base_repo.settings().defer_descendant_rebase();
         MutableRepo {
             base_repo,
             index: mut_index,
             view: DirtyCell::with_clean(mut_view),
             parent_mapping: Default::default(),
+            abandon_reasons: Default::default(),
+            defer_descendant_rebase,
         }
     }
 
@@ -887,9 +936,15 @@ impl MutableRepo {
         !(self.parent_mapping.is_empty() && self.view() == &self.base_repo.view)
     }
 
-    pub(crate) fn consume(self) -> (Box<dyn MutableIndex>, View) {
+    pub(crate) fn consume(
+        self,
+    ) -> (
+        Box<dyn MutableIndex>,
+        View,
+        BTreeMap<CommitId, AbandonReason>,
+    ) {
         self.view.ensure_clean(|v| self.enforce_view_invariants(v));
-        (self.index, self.view.into_inner())
+        (self.index, self.view.into_inner(), self.abandon_reasons)
     }
 
     /// Returns a [`CommitBuilder`] to write new commit to the repo.
@@ -972,10 +1027,52 @@ impl MutableRepo {
         );
     }
 
+    /// Records why a commit was abandoned in this transaction.
+    ///
+    /// The reason is stored in the operation, and can later be looked up by
+    /// e.g. `jj evolog`. Commits abandoned without a recorded reason are
+    /// reported as abandoned for an unknown reason.
+    pub fn set_abandon_reason(&mut self, commit_id: CommitId, reason: AbandonReason) {
+        self.abandon_reasons.insert(commit_id, reason);
+    }
+
+    /// Records the given reasons for the commits which have been abandoned in
+    /// this transaction, unless a reason has already been recorded.
+    pub(crate) fn inherit_abandon_reasons(
+        &mut self,
+        reasons: impl IntoIterator<Item = (CommitId, AbandonReason)>,
+    ) {
+        for (commit_id, reason) in reasons {
+            if matches!(
+                self.parent_mapping.get(&commit_id),
+                Some(Rewrite::Abandoned(_))
+            ) {
+                self.abandon_reasons.entry(commit_id).or_insert(reason);
+            }
+        }
+    }
+
     pub fn has_rewrites(&self) -> bool {
         !self.parent_mapping.is_empty()
     }
 
+    /// Whether `rebase_descendants()` leaves the descendants of rewritten
+    /// commits in place. Defaults to the `rewrite.defer-descendant-rebase`
+    /// setting.
+    pub fn defers_descendant_rebase(&self) -> bool {
+        self.defer_descendant_rebase
+    }
+
+    /// Sets whether `rebase_descendants()` should leave the descendants of
+    /// rewritten commits in place.
+    ///
+    /// If set, the rewrites are recorded in the view instead, and the
+    /// descendants are rebased in bulk by a later `rebase_descendants()` call
+    /// with deferring disabled.
+    pub fn set_defer_descendant_rebase(&mut self, defer: bool) {
+        self.defer_descendant_rebase = defer;
+    }
+
     /// Calculates new parents for a commit that's currently based on the given
     /// parents. It does that by considering how previous commits have been
     /// rewritten and abandoned.
@@ -1274,10 +1371,69 @@ impl MutableRepo {
         Ok(())
     }
 
+    /// Moves the rewrites deferred by earlier operations to `parent_mapping`.
+    ///
+    /// Rewrites recorded in this transaction take precedence, and the deferred
+    /// ones are chained to them.
+    fn load_deferred_rewrites(&mut self) {
+        if self.view().deferred_rewrites().is_empty() {
+            return;
+        }
+        let deferred_rewrites = self.view().deferred_rewrites().clone();
+        self.view_mut().set_deferred_rewrites(BTreeMap::new());
+        for (old_id, new_parent_ids) in deferred_rewrites {
+            self.parent_mapping.entry(old_id).or_insert_with(|| {
+                match <[CommitId; 1]>::try_from(new_parent_ids) {
+                    Ok([new_id]) => Rewrite::Rewritten(new_id),
+                    Err(new_parent_ids) => Rewrite::Abandoned(new_parent_ids),
+                }
+            });
+        }
+    }
+
+    /// Updates references to the rewritten commits, but records the rewrites
+    /// in the view instead of rebasing their descendants.
+    fn defer_rebase_descendants(&mut self, options: &RewriteRefsOptions) -> BackendResult<()> {
+        if self.parent_mapping.is_empty() {
+            return Ok(());
+        }
+        self.update_rewritten_references(options)?;
+        self.load_deferred_rewrites();
+        let rewrite_mapping =
+            self.resolve_rewrite_mapping_with(|rewrite| !matches!(rewrite, Rewrite::Divergent(_)));
+        // Only the rewritten commits which still have children need to be
+        // remembered.
+        let old_commits_expression =
+            RevsetExpression::commits(rewrite_mapping.keys().cloned().collect());
+        let children: Vec<_> = old_commits_expression
+            .children()
+            .minus(&old_commits_expression)
+            .evaluate(self)
+            .map_err(|err| err.expect_backend_error())?
+            .iter()
+            .commits(self.store())
+            .try_collect()
+            .map_err(|err| err.expect_backend_error())?;
+        let mut deferred_rewrites = BTreeMap::new();
+        for child in &children {
+            for parent_id in child.parent_ids() {
+                if let Some(new_parent_ids) = rewrite_mapping.get(parent_id) {
+                    deferred_rewrites.insert(parent_id.clone(), new_parent_ids.clone());
+                }
+            }
+        }
+        self.view_mut().set_deferred_rewrites(deferred_rewrites);
+        self.parent_mapping.clear();
+        Ok(())
+    }
+
     /// Rebase descendants of the rewritten commits with options and callback.
     ///
     /// The descendants of the commits registered in `self.parent_mappings` will
-    /// be recursively rebased onto the new version of their parents.
+    /// be recursively rebased onto the new version of their parents. Unless
+    /// rebasing is [deferred](Self::set_defer_descendant_rebase), the
+    /// descendants of commits rewritten by earlier operations with deferred
+    /// rebasing are rebased too.
     ///
     /// If `options.empty` is the default (`EmptyBehaviour::Keep`), all rebased
     /// descendant commits will be preserved even if they were emptied following
@@ -1294,6 +1450,10 @@ impl MutableRepo {
         options: &RebaseOptions,
         mut progress: impl FnMut(Commit, RebasedCommit),
     ) -> BackendResult<()> {
+        if self.defer_descendant_rebase {
+            return self.defer_rebase_descendants(&options.rewrite_refs);
+        }
+        self.load_deferred_rewrites();
         let roots = self.parent_mapping.keys().cloned().collect();
         self.transform_descendants_with_options(roots, &options.rewrite_refs, |rewriter| {
             if rewriter.parents_changed() {
@@ -1428,6 +1588,15 @@ impl MutableRepo {
                 // Abandon the working-copy commit we're leaving if it's
                 // discardable, not pointed by local bookmark or other working
                 // copies, and a head commit.
+                // If the commit has already been rewritten or abandoned, e.g.
+                // by the command which moved the working copy, keep that
+                // reason instead.
+                if !self.parent_mapping.contains_key(wc_commit.id()) {
+                    self.set_abandon_reason(
+                        wc_commit.id().clone(),
+                        AbandonReason::DiscardedWorkingCopy,
+                    );
+                }
                 self.record_abandoned_commit(&wc_commit);
             }
         }
@@ -1746,6 +1915,23 @@ impl MutableRepo {
         );
         self.set_git_head_target(new_git_head_target);
 
+        // If one side resolved a deferred rewrite (or recorded a new one), so
+        // does the merged view.
+        let mut deferred_rewrites = self.view().deferred_rewrites().clone();
+        for old_id in base.deferred_rewrites().keys() {
+            if !other.deferred_rewrites().contains_key(old_id) {
+                deferred_rewrites.remove(old_id);
+            }
+        }
+        for (old_id, new_parent_ids) in other.deferred_rewrites() {
+            if base.deferred_rewrites().get(old_id) != Some(new_parent_ids) {
+                deferred_rewrites.insert(old_id.clone(), new_parent_ids.clone());
+            }
+        }
+        if &deferred_rewrites != self.view().deferred_rewrites() {
+            self.view_mut().set_deferred_rewrites(deferred_rewrites);
+        }
+
         Ok(())
     }
 
@@ -1840,7 +2026,8 @@ impl Repo for MutableRepo {
 
     fn resolve_change_id_prefix(&self, prefix: &HexPrefix) -> PrefixResolution<Vec<CommitId>> {
         let change_id_index = self.index.change_id_index(&mut self.view().heads().iter());
-        change_id_index.resolve_prefix(prefix)
+        let resolution = change_id_index.resolve_prefix(prefix);
+        exclude_deferred_rewrites(resolution, self.view())
     }
 
     fn shortest_unique_change_id_prefix_len(&self, target_id: &ChangeId) -> usize {
@@ -35,6 +35,7 @@ use crate::dsl_util;
 use crate::dsl_util::collect_similar;
 use crate::dsl_util::AliasExpandError as _;
 use crate::fileset;
+use crate::fileset::FilesetAliasesMap;
 use crate::fileset::FilesetDiagnostics;
 use crate::fileset::FilesetExpression;
 use crate::graph::GraphNode;
@@ -933,7 +934,14 @@ static BUILTIN_FUNCTION_MAP: Lazy<HashMap<&'static str, RevsetFunction>> = Lazy:
             ));
         }
         let file_expressions = itertools::chain([arg], args)
-            .map(|arg| expect_fileset_expression(diagnostics, arg, ctx.path_converter))
+            .map(|arg| {
+                expect_fileset_expression(
+                    diagnostics,
+                    arg,
+                    ctx.fileset_aliases_map,
+                    ctx.path_converter,
+                )
+            })
             .try_collect()?;
         let expr = FilesetExpression::union_all(file_expressions);
         Ok(RevsetExpression::filter(RevsetFilterPredicate::File(expr)))
@@ -950,7 +958,12 @@ static BUILTIN_FUNCTION_MAP: Lazy<HashMap<&'static str, RevsetFunction>> = Lazy:
                     files_arg.span,
                 )
             })?;
-            expect_fileset_expression(diagnostics, files_arg, ctx.path_converter)?
+            expect_fileset_expression(
+                diagnostics,
+                files_arg,
+                ctx.fileset_aliases_map,
+                ctx.path_converter,
+            )?
         } else {
             // TODO: defaults to CLI path arguments?
             // https://github.com/jj-vcs/jj/issues/2933#issuecomment-1925870731
@@ -1006,6 +1019,7 @@ static BUILTIN_FUNCTION_MAP: Lazy<HashMap<&'static str, RevsetFunction>> = Lazy:
 pub fn expect_fileset_expression(
     diagnostics: &mut RevsetDiagnostics,
     node: &ExpressionNode,
+    aliases_map: &FilesetAliasesMap,
     path_converter: &RepoPathUiConverter,
 ) -> Result<FilesetExpression, RevsetParseError> {
     // Alias handling is a bit tricky. The outermost expression `alias` is
@@ -1013,13 +1027,24 @@ pub fn expect_fileset_expression(
     // weird, we can either transform AST or turn off revset aliases completely.
     revset_parser::expect_expression_with(diagnostics, node, |diagnostics, node| {
         let mut inner_diagnostics = FilesetDiagnostics::new();
-        let expression = fileset::parse(&mut inner_diagnostics, node.span.as_str(), path_converter)
-            .map_err(|err| {
-                RevsetParseError::expression("In fileset expression", node.span).with_source(err)
-            })?;
+        let expression = fileset::parse(
+            &mut inner_diagnostics,
+            node.span.as_str(),
+            aliases_map,
+            path_converter,
+        )
+        .map_err(|err| {
+            RevsetParseError::expression("In fileset expression", node.span).with_source(err)
+        })?;
         diagnostics.extend_with(inner_diagnostics, |diag| {
             RevsetParseError::expression("In fileset expression", node.span).with_source(diag)
         });
+        if let Some(name) = expression.tree_dependent_function_name() {
+            return Err(RevsetParseError::expression(
+                format!("Fileset function `{name}()` can't be used in revsets"),
+                node.span,
+            ));
+        }
         Ok(expression)
     })
 }
@@ -2558,6 +2583,16 @@ pub trait Revset: fmt::Debug {
     fn containing_fn<'a>(&self) -> Box<RevsetContainingFn<'a>>
     where
         Self: 'a;
+
+    /// Returns the number of times the filter predicates such as `author()`
+    /// have been tested against commits so far.
+    ///
+    /// This is a debugging aid to check how much of the revset had to be
+    /// evaluated. Returns 0 if the implementation doesn't count the
+    /// evaluations.
+    fn num_predicate_evaluations(&self) -> usize {
+        0
+    }
 }
 
 /// Function that checks if a commit is contained within the revset.
@@ -2682,6 +2717,7 @@ impl<'a> RevsetParseContext<'a> {
 #[derive(Clone, Debug)]
 pub struct RevsetWorkspaceContext<'a> {
     pub path_converter: &'a RepoPathUiConverter,
+    pub fileset_aliases_map: &'a FilesetAliasesMap,
     pub workspace_id: &'a WorkspaceId,
 }
 
@@ -2758,8 +2794,10 @@ mod tests {
             cwd: PathBuf::from("/"),
             base: PathBuf::from("/"),
         };
+        let fileset_aliases_map = FilesetAliasesMap::new();
         let workspace_ctx = RevsetWorkspaceContext {
             path_converter: &path_converter,
+            fileset_aliases_map: &fileset_aliases_map,
             workspace_id,
         };
         let mut aliases_map = RevsetAliasesMap::new();
@@ -29,23 +29,29 @@ use crate::backend::BackendError;
 use crate::backend::BackendResult;
 use crate::backend::CommitId;
 use crate::backend::MergedTreeId;
+use crate::backend::TreeValue;
 use crate::commit::Commit;
 use crate::commit::CommitIteratorExt;
 use crate::commit_builder::CommitBuilder;
+use crate::copies::CopyRecords;
 use crate::dag_walk;
 use crate::index::Index;
 use crate::index::IndexError;
 use crate::matchers::Matcher;
 use crate::matchers::Visit;
+use crate::merge::Merge;
 use crate::merged_tree::MergedTree;
 use crate::merged_tree::MergedTreeBuilder;
 use crate::merged_tree::TreeDiffEntry;
+use crate::op_store::AbandonReason;
 use crate::repo::MutableRepo;
 use crate::repo::Repo;
 use crate::repo_path::RepoPath;
+use crate::repo_path::RepoPathBuf;
 use crate::revset::RevsetExpression;
 use crate::revset::RevsetIteratorExt;
 use crate::store::Store;
+use crate::tree::try_resolve_file_conflict;
 
 /// Merges `commits` and tries to resolve any conflicts recursively.
 #[instrument(skip(repo))]
@@ -57,6 +63,126 @@ pub fn merge_commit_trees(repo: &dyn Repo, commits: &[Commit]) -> BackendResult<
     }
 }
 
+/// Like `merge_commit_trees()`, but if two commits with a single common
+/// ancestor are merged, modify/delete conflicts are resolved by moving the
+/// modification to where the file was renamed. Returns the renames which were
+/// followed.
+#[instrument(skip(repo))]
+pub fn merge_commit_trees_following_renames(
+    repo: &dyn Repo,
+    commits: &[Commit],
+) -> BackendResult<(MergedTree, Vec<FollowedRename>)> {
+    let tree = merge_commit_trees(repo, commits)?;
+    if let [commit1, commit2] = commits {
+        if tree.has_conflict() {
+            let ancestor_ids = repo
+                .index()
+                .common_ancestors(&[commit1.id().clone()], &[commit2.id().clone()]);
+            if let [base_id] = ancestor_ids.as_slice() {
+                let base = repo.store().get_commit(base_id)?;
+                return follow_renames(repo.store(), tree, &base, [commit1, commit2]);
+            }
+        }
+    }
+    Ok((tree, vec![]))
+}
+
+/// A file which was modified on one side of a merge and renamed on the other.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FollowedRename {
+    /// The path of the file in the merge base.
+    pub source: RepoPathBuf,
+    /// The path the file was renamed to, where the modification was moved.
+    pub target: RepoPathBuf,
+    /// Whether the modification could be merged into the renamed file without
+    /// conflicts.
+    pub resolved: bool,
+}
+
+/// Resolves modify/delete conflicts in the merge of `sides` by moving the
+/// modification to where the file was renamed on the deleting side.
+///
+/// Renames are detected by the backend's copy tracing between `base` and each
+/// of the sides.
+fn follow_renames(
+    store: &Arc<Store>,
+    tree: MergedTree,
+    base: &Commit,
+    sides: [&Commit; 2],
+) -> BackendResult<(MergedTree, Vec<FollowedRename>)> {
+    let mut side_copy_records: [Option<CopyRecords>; 2] = [None, None];
+    let mut tree_builder = MergedTreeBuilder::new(tree.id());
+    let mut followed_renames = vec![];
+    for (path, value) in tree.conflicts() {
+        let value = value?.simplify();
+        if value.num_sides() != 2 {
+            continue;
+        }
+        let Some(deleted_side) = (0..2).find(|&i| value.get_add(i) == Some(&None)) else {
+            continue;
+        };
+        let modified_side = 1 - deleted_side;
+        let modified_value = value.get_add(modified_side).unwrap();
+        let base_value = value.get_remove(0).unwrap();
+        if !matches!(modified_value, Some(TreeValue::File { .. }))
+            || !matches!(base_value, Some(TreeValue::File { .. }))
+        {
+            continue;
+        }
+
+        if side_copy_records[deleted_side].is_none() {
+            let stream = store.get_copy_records(None, base.id(), sides[deleted_side].id())?;
+            let mut copy_records = CopyRecords::default();
+            copy_records.add_records(stream.collect::<Vec<_>>().block_on())?;
+            side_copy_records[deleted_side] = Some(copy_records);
+        }
+        let copy_records = side_copy_records[deleted_side].as_ref().unwrap();
+        let Some(record) = copy_records.for_source(&path) else {
+            continue;
+        };
+        let renamed_value = tree.path_value(&record.target)?;
+        let Some(renamed_value @ Some(TreeValue::File { .. })) = renamed_value.as_resolved() else {
+            continue;
+        };
+        // The modifying side mustn't have added a file at the same path.
+        if !sides[modified_side]
+            .tree()?
+            .path_value(&record.target)?
+            .is_absent()
+        {
+            continue;
+        }
+
+        let mut adds = [renamed_value.clone(), modified_value.clone()];
+        if deleted_side == 1 {
+            adds.reverse();
+        }
+        let new_value = Merge::from_removes_adds([base_value.clone()], adds);
+        let resolved_value = try_resolve_file_conflict(
+            store,
+            &record.target,
+            &new_value.map(|value| value.as_ref()),
+        )
+        .block_on()?;
+        let resolved = resolved_value.is_some();
+        tree_builder.set_or_remove(path.clone(), Merge::absent());
+        tree_builder.set_or_remove(
+            record.target.clone(),
+            resolved_value.map_or(new_value, Merge::normal),
+        );
+        followed_renames.push(FollowedRename {
+            source: path,
+            target: record.target.clone(),
+            resolved,
+        });
+    }
+    if followed_renames.is_empty() {
+        return Ok((tree, followed_renames));
+    }
+    let tree_id = tree_builder.write_tree(store)?;
+    Ok((store.get_root_tree(&tree_id)?, followed_renames))
+}
+
 /// Merges `commits` without attempting to resolve file conflicts.
 #[instrument(skip(index))]
 pub fn merge_commit_trees_no_resolve_without_repo(
@@ -209,6 +335,16 @@ impl<'repo> CommitRewriter<'repo> {
         Ok(())
     }
 
+    /// Returns true if the old commit is the working-copy commit of any
+    /// workspace.
+    fn is_working_copy_commit(&self) -> bool {
+        self.mut_repo
+            .view()
+            .wc_commit_ids()
+            .values()
+            .any(|id| id == self.old_commit.id())
+    }
+
     /// Records the old commit as abandoned with the new parents.
     ///
     /// This is equivalent to `reparent(settings).abandon()`, but is cheaper.
@@ -262,10 +398,16 @@ impl<'repo> CommitRewriter<'repo> {
         if let [parent] = &new_parents[..] {
             let should_abandon = match empty {
                 EmptyBehaviour::Keep => false,
-                EmptyBehaviour::AbandonNewlyEmpty => *parent.tree_id() == new_tree_id && !was_empty,
+                EmptyBehaviour::AbandonNewlyEmpty => {
+                    *parent.tree_id() == new_tree_id && !was_empty && !self.is_working_copy_commit()
+                }
                 EmptyBehaviour::AbandonAllEmpty => *parent.tree_id() == new_tree_id,
             };
             if should_abandon {
+                self.mut_repo.set_abandon_reason(
+                    self.old_commit.id().clone(),
+                    AbandonReason::EmptiedByRebase,
+                );
                 self.abandon();
                 return Ok(None);
             }
@@ -357,7 +499,8 @@ pub enum EmptyBehaviour {
     Keep,
     /// Skips commits that would be empty after the rebase, but that were not
     /// originally empty.
-    /// Will never skip merge commits with multiple non-empty parents.
+    /// Will never skip merge commits with multiple non-empty parents, nor
+    /// working-copy commits.
     AbandonNewlyEmpty,
     /// Skips all empty commits, including ones that were empty before the
     /// rebase.
@@ -401,6 +544,9 @@ pub struct MoveCommitsStats {
     pub num_skipped_rebases: u32,
     /// The number of commits which were abandoned.
     pub num_abandoned: u32,
+    /// Map of original commit ID to the result of rebasing it, for the commits
+    /// which were rebased.
+    pub rebased_commits: HashMap<CommitId, RebasedCommit>,
 }
 
 pub enum MoveCommitsTarget {
@@ -741,6 +887,7 @@ pub fn move_commits(
     let mut num_rebased_descendants = 0;
     let mut num_skipped_rebases = 0;
     let mut num_abandoned = 0;
+    let mut rebased_commits = HashMap::new();
 
     // Always keep empty commits when rebasing descendants.
     let rebase_descendant_options = &RebaseOptions {
@@ -773,6 +920,7 @@ pub fn move_commits(
             } else {
                 num_rebased_descendants += 1;
             }
+            rebased_commits.insert(old_commit_id, rebased_commit);
         } else {
             num_skipped_rebases += 1;
         }
@@ -784,6 +932,7 @@ pub fn move_commits(
         num_rebased_descendants,
         num_skipped_rebases,
         num_abandoned,
+        rebased_commits,
     })
 }
 
@@ -1099,6 +1248,10 @@ pub fn squash_commits<'repo>(
     for source in &source_commits {
         if source.abandon {
             repo.record_abandoned_commit(&source.commit.commit);
+            repo.set_abandon_reason(
+                source.commit.commit.id().clone(),
+                AbandonReason::SquashedInto,
+            );
             abandoned_commits.push(source.commit.commit.clone());
         } else {
             let source_tree = source.commit.commit.tree()?;
@@ -53,12 +53,14 @@ struct UserSettingsData {
     operation_timestamp: Option<Timestamp>,
     operation_hostname: String,
     operation_username: String,
+    defer_descendant_rebase: bool,
 }
 
 #[derive(Debug, Clone)]
 pub struct GitSettings {
     pub auto_local_bookmark: bool,
     pub abandon_unreachable_commits: bool,
+    pub prune_on_fetch: bool,
     pub subprocess: bool,
     pub executable_path: PathBuf,
 }
@@ -68,6 +70,7 @@ impl GitSettings {
         Ok(GitSettings {
             auto_local_bookmark: settings.get_bool("git.auto-local-bookmark")?,
             abandon_unreachable_commits: settings.get_bool("git.abandon-unreachable-commits")?,
+            prune_on_fetch: settings.get_bool("git.prune-on-fetch")?,
             subprocess: settings.get_bool("git.subprocess")?,
             executable_path: settings.get("git.executable-path")?,
         })
@@ -79,6 +82,7 @@ impl Default for GitSettings {
         GitSettings {
             auto_local_bookmark: false,
             abandon_unreachable_commits: true,
+            prune_on_fetch: true,
             subprocess: true,
             executable_path: PathBuf::from("git"),
         }
@@ -157,6 +161,7 @@ impl UserSettings {
             .optional()?;
         let operation_hostname = config.get("operation.hostname")?;
         let operation_username = config.get("operation.username")?;
+        let defer_descendant_rebase = config.get("rewrite.defer-descendant-rebase")?;
         let data = UserSettingsData {
             user_name,
             user_email,
@@ -164,6 +169,7 @@ impl UserSettings {
             operation_timestamp,
             operation_hostname,
             operation_username,
+            defer_descendant_rebase,
         };
         Ok(UserSettings {
             config: Arc::new(config),
@@ -219,6 +225,12 @@ impl UserSettings {
         &self.data.operation_username
     }
 
+    /// Whether descendants of rewritten commits should be left in place until
+    /// they're explicitly rebased.
+    pub fn defer_descendant_rebase(&self) -> bool {
+        self.data.defer_descendant_rebase
+    }
+
     pub fn signature(&self) -> Signature {
         let timestamp = self.data.commit_timestamp.unwrap_or_else(Timestamp::now);
         Signature {
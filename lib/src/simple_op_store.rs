@@ -20,13 +20,21 @@ use std::collections::HashMap;
 use std::collections::HashSet;
 use std::fmt::Debug;
 use std::fs;
+use std::fs::File;
 use std::io;
 use std::io::ErrorKind;
+use std::io::Read as _;
+use std::io::Seek as _;
+use std::io::SeekFrom;
 use std::io::Write;
 use std::path::Path;
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
 use std::time::SystemTime;
 
+use blake2::Blake2b512;
+use blake2::Digest as _;
 use itertools::Itertools as _;
 use prost::Message;
 use tempfile::NamedTempFile;
@@ -41,11 +49,13 @@ use crate::dag_walk;
 use crate::file_util::persist_content_addressed_temp_file;
 use crate::file_util::IoResultExt as _;
 use crate::file_util::PathError;
+use crate::lock::FileLock;
 use crate::merge::Merge;
 use crate::object_id::HexPrefix;
 use crate::object_id::ObjectId;
 use crate::object_id::PrefixResolution;
 use crate::op_store;
+use crate::op_store::AbandonReason;
 use crate::op_store::OpStore;
 use crate::op_store::OpStoreError;
 use crate::op_store::OpStoreResult;
@@ -65,6 +75,23 @@ use crate::op_store::WorkspaceId;
 const OPERATION_ID_LENGTH: usize = 64;
 const VIEW_ID_LENGTH: usize = 64;
 
+// Operations and views are packed with the same index entry layout.
+const _: () = assert!(OPERATION_ID_LENGTH == VIEW_ID_LENGTH);
+const PACK_ID_LENGTH: usize = OPERATION_ID_LENGTH;
+
+const PACK_FILE_EXTENSION: &str = "pack";
+const PACK_MAGIC: &[u8; 8] = b"jjoppack";
+/// Version of the pack file format. Packs of other versions can't be read.
+const PACK_FORMAT_VERSION: u32 = 1;
+// kind (1) + id + data offset (8) + data length (8) + mtime millis (8)
+const PACK_INDEX_ENTRY_LENGTH: usize = 1 + PACK_ID_LENGTH + 8 + 8 + 8;
+// number of entries (8) + index offset (8) + format version (4) + magic
+const PACK_FOOTER_LENGTH: usize = 8 + 8 + 4 + PACK_MAGIC.len();
+
+/// Number of loose operations above which they are packed when a new
+/// operation is written.
+const AUTO_PACK_LOOSE_OPERATIONS: usize = 1000;
+
 /// Error that may occur during [`SimpleOpStore`] initialization.
 #[derive(Debug, Error)]
 #[error("Failed to initialize simple operation store")]
@@ -97,6 +124,8 @@ pub struct SimpleOpStore {
     root_data: RootOperationData,
     root_operation_id: OperationId,
     root_view_id: ViewId,
+    /// Cached list of pack files, loaded on first access.
+    packs: Mutex<Option<Arc<Vec<OpPack>>>>,
 }
 
 impl SimpleOpStore {
@@ -125,6 +154,7 @@ impl SimpleOpStore {
             root_data,
             root_operation_id: OperationId::from_bytes(&[0; OPERATION_ID_LENGTH]),
             root_view_id: ViewId::from_bytes(&[0; VIEW_ID_LENGTH]),
+            packs: Mutex::new(None),
         }
     }
 
@@ -142,6 +172,203 @@ impl SimpleOpStore {
     fn operations_dir(&self) -> PathBuf {
         self.path.join("operations")
     }
+
+    fn packs_dir(&self) -> PathBuf {
+        self.path.join("packs")
+    }
+
+    fn loose_dir(&self, kind: PackEntryKind) -> PathBuf {
+        match kind {
+            PackEntryKind::Operation => self.operations_dir(),
+            PackEntryKind::View => self.views_dir(),
+        }
+    }
+
+    /// Returns the pack files, reloading the list from disk if `reload` is
+    /// set or if it hasn't been loaded yet.
+    fn packs(&self, reload: bool) -> Result<Arc<Vec<OpPack>>, PathError> {
+        let mut packs = self.packs.lock().unwrap();
+        match &*packs {
+            Some(loaded) if !reload => Ok(loaded.clone()),
+            _ => {
+                let loaded = Arc::new(load_packs(&self.packs_dir())?);
+                *packs = Some(loaded.clone());
+                Ok(loaded)
+            }
+        }
+    }
+
+    fn has_packed(&self, kind: PackEntryKind, id: &[u8]) -> Result<bool, PathError> {
+        let key = (kind, id.to_vec());
+        for reload in [false, true] {
+            if self
+                .packs(reload)?
+                .iter()
+                .any(|pack| pack.entries.contains_key(&key))
+            {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    fn read_packed(&self, kind: PackEntryKind, id: &[u8]) -> Result<Option<Vec<u8>>, PathError> {
+        let key = (kind, id.to_vec());
+        // The entry may have been packed after the packs were loaded, so the
+        // list is reloaded before giving up.
+        for reload in [false, true] {
+            let packs = self.packs(reload)?;
+            let Some((pack, entry)) = packs
+                .iter()
+                .find_map(|pack| Some((pack, pack.entries.get(&key)?)))
+            else {
+                continue;
+            };
+            return pack.read_entry(entry).map(Some).context(&pack.path);
+        }
+        Ok(None)
+    }
+
+    /// Reads the serialized object from its loose file, or from the packs if
+    /// it has been packed.
+    fn read_object_data(&self, kind: PackEntryKind, id: &impl ObjectId) -> OpStoreResult<Vec<u8>> {
+        let path = self.loose_dir(kind).join(id.hex());
+        let not_found_err = match fs::read(path) {
+            Ok(data) => return Ok(data),
+            Err(err) if err.kind() == ErrorKind::NotFound => err,
+            Err(err) => return Err(io_to_read_error(err, id)),
+        };
+        match self.read_packed(kind, id.as_bytes()) {
+            Ok(Some(data)) => Ok(data),
+            Ok(None) => Err(io_to_read_error(not_found_err, id)),
+            Err(err) => Err(OpStoreError::ReadObject {
+                object_type: id.object_type(),
+                hash: id.hex(),
+                source: Box::new(err),
+            }),
+        }
+    }
+
+    /// Moves the loose operations and views into a new pack file, and merges
+    /// the existing packs into it.
+    ///
+    /// Loose files written concurrently are left as they are. Unreachable
+    /// entries aren't removed by this; use [`OpStore::gc()`] for that.
+    pub fn pack(&self) -> OpStoreResult<()> {
+        self.compact(true, |_, _, _| true)
+            .map_err(OpStoreError::Other)
+    }
+
+    /// Merges the existing packs into a new pack file, dropping the packed
+    /// entries for which `keep_packed` returns false. If `pack_loose` is set,
+    /// the loose operations and views are also moved into the new pack.
+    #[tracing::instrument(skip_all)]
+    fn compact(
+        &self,
+        pack_loose: bool,
+        keep_packed: impl Fn(PackEntryKind, &[u8], MillisSinceEpoch) -> bool,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let packs_dir = self.packs_dir();
+        fs::create_dir_all(&packs_dir).context(&packs_dir)?;
+        let _lock = FileLock::lock(packs_dir.join("lock"))?;
+        let old_packs = load_packs(&packs_dir)?;
+        let mut writer = PackWriter::new(&packs_dir).context(&packs_dir)?;
+
+        let mut packed_loose_paths = vec![];
+        let loose_kinds = if pack_loose {
+            [PackEntryKind::Operation, PackEntryKind::View].as_slice()
+        } else {
+            &[]
+        };
+        for &kind in loose_kinds {
+            let dir = self.loose_dir(kind);
+            for entry in dir.read_dir().context(&dir)? {
+                let entry = entry.context(&dir)?;
+                let Some(id) = entry
+                    .file_name()
+                    .to_str()
+                    .and_then(|name| hex::decode(name).ok())
+                    .filter(|id| id.len() == PACK_ID_LENGTH)
+                else {
+                    tracing::trace!(?entry, "skipping invalid file name");
+                    continue;
+                };
+                let path = entry.path();
+                // The file may have been removed by concurrent gc.
+                let (mtime, data) = match entry.metadata().and_then(|metadata| {
+                    let mtime = metadata.modified().expect("unsupported platform?");
+                    Ok((mtime, fs::read(&path)?))
+                }) {
+                    Ok(loaded) => loaded,
+                    Err(err) if err.kind() == ErrorKind::NotFound => continue,
+                    Err(error) => return Err(PathError { path, error }.into()),
+                };
+                writer
+                    .add(kind, &id, &data, millis_since_epoch(mtime))
+                    .context(&packs_dir)?;
+                packed_loose_paths.push(path);
+            }
+        }
+
+        let mut num_dropped = 0;
+        for pack in &old_packs {
+            for ((kind, id), entry) in &pack.entries {
+                if writer.contains(*kind, id) {
+                    continue;
+                }
+                if !keep_packed(*kind, id, entry.mtime) {
+                    num_dropped += 1;
+                    continue;
+                }
+                let data = pack.read_entry(entry).context(&pack.path)?;
+                writer
+                    .add(*kind, id, &data, entry.mtime)
+                    .context(&packs_dir)?;
+            }
+        }
+        if packed_loose_paths.is_empty() && old_packs.len() <= 1 && num_dropped == 0 {
+            tracing::debug!("nothing to compact");
+            return Ok(());
+        }
+        tracing::info!(
+            num_loose = packed_loose_paths.len(),
+            num_old_packs = old_packs.len(),
+            num_dropped,
+            "writing pack"
+        );
+
+        // The new pack has to be in place before the packed files are removed
+        // so that concurrent readers can find the entries in either of them.
+        let new_pack_path = writer.finish(&packs_dir).context(&packs_dir)?;
+        // The old packs are closed before removing them, which is required on
+        // Windows.
+        *self.packs.lock().unwrap() = None;
+        let old_pack_paths = old_packs.into_iter().map(|pack| pack.path).collect_vec();
+        for path in &old_pack_paths {
+            if Some(path) != new_pack_path.as_ref() {
+                remove_file_if_exists(path).context(path)?;
+            }
+        }
+        for path in &packed_loose_paths {
+            remove_file_if_exists(path).context(path)?;
+        }
+        Ok(())
+    }
+
+    /// Packs the loose operations if there are many of them. Errors are only
+    /// logged since the operation has already been written.
+    fn compact_if_many_loose_operations(&self) {
+        let op_dir = self.operations_dir();
+        let Ok(entries) = op_dir.read_dir() else {
+            return;
+        };
+        if entries.count() <= AUTO_PACK_LOOSE_OPERATIONS {
+            return;
+        }
+        if let Err(err) = self.pack() {
+            tracing::warn!(?err, "failed to pack loose operations");
+        }
+    }
 }
 
 impl OpStore for SimpleOpStore {
@@ -162,8 +389,7 @@ impl OpStore for SimpleOpStore {
             return Ok(View::make_root(self.root_data.root_commit_id.clone()));
         }
 
-        let path = self.views_dir().join(id.hex());
-        let buf = fs::read(path).map_err(|err| io_to_read_error(err, id))?;
+        let buf = self.read_object_data(PackEntryKind::View, id)?;
 
         let proto = crate::protos::op_store::View::decode(&*buf).map_err(|err| DecodeError {
             kind: "view",
@@ -196,8 +422,7 @@ impl OpStore for SimpleOpStore {
             return Ok(Operation::make_root(self.root_view_id.clone()));
         }
 
-        let path = self.operations_dir().join(id.hex());
-        let buf = fs::read(path).map_err(|err| io_to_read_error(err, id))?;
+        let buf = self.read_object_data(PackEntryKind::Operation, id)?;
 
         let proto =
             crate::protos::op_store::Operation::decode(&*buf).map_err(|err| DecodeError {
@@ -230,6 +455,12 @@ impl OpStore for SimpleOpStore {
 
         persist_content_addressed_temp_file(temp_file, dir.join(id.hex()))
             .map_err(|err| io_to_write_error(err, "operation"))?;
+
+        // Counting the loose operations requires listing the directory, so
+        // it's only done for a sample of the written operations.
+        if id.as_bytes()[0] == 0 {
+            self.compact_if_many_loose_operations();
+        }
         Ok(id)
     }
 
@@ -238,13 +469,17 @@ impl OpStore for SimpleOpStore {
         prefix: &HexPrefix,
     ) -> OpStoreResult<PrefixResolution<OperationId>> {
         let op_dir = self.operations_dir();
-        let find = || -> io::Result<_> {
+        let find = || -> Result<_, PathError> {
             let matches_root = prefix.matches(&self.root_operation_id);
             let hex_prefix = prefix.hex();
             if hex_prefix.len() == OPERATION_ID_LENGTH * 2 {
                 // Fast path for full-length ID
-                if matches_root || op_dir.join(hex_prefix).try_exists()? {
-                    let id = OperationId::from_bytes(prefix.as_full_bytes().unwrap());
+                let full_bytes = prefix.as_full_bytes().unwrap();
+                if matches_root
+                    || op_dir.join(hex_prefix).try_exists().context(&op_dir)?
+                    || self.has_packed(PackEntryKind::Operation, full_bytes)?
+                {
+                    let id = OperationId::from_bytes(full_bytes);
                     return Ok(PrefixResolution::SingleMatch(id));
                 } else {
                     return Ok(PrefixResolution::NoMatch);
@@ -252,8 +487,15 @@ impl OpStore for SimpleOpStore {
             }
 
             let mut matched = matches_root.then(|| self.root_operation_id.clone());
-            for entry in op_dir.read_dir()? {
-                let Ok(name) = entry?.file_name().into_string() else {
+            let add_match = |matched: &mut Option<OperationId>, id: OperationId| {
+                if matched.as_ref().is_some_and(|matched| *matched != id) {
+                    return Err(());
+                }
+                *matched = Some(id);
+                Ok(())
+            };
+            for entry in op_dir.read_dir().context(&op_dir)? {
+                let Ok(name) = entry.context(&op_dir)?.file_name().into_string() else {
                     continue; // Skip invalid UTF-8
                 };
                 if !name.starts_with(&hex_prefix) {
@@ -262,10 +504,31 @@ impl OpStore for SimpleOpStore {
                 let Ok(id) = OperationId::try_from_hex(&name) else {
                     continue; // Skip invalid hex
                 };
-                if matched.is_some() {
+                if add_match(&mut matched, id).is_err() {
                     return Ok(PrefixResolution::AmbiguousMatch);
                 }
-                matched = Some(id);
+            }
+            // Operations packed after the packs were loaded (e.g. while listing
+            // the loose files) aren't in the cached packs, so the packs are
+            // reloaded if nothing has matched.
+            for reload in [false, true] {
+                if reload && matched.is_some() {
+                    break;
+                }
+                for pack in self.packs(reload)?.iter() {
+                    for (kind, id) in pack.entries.keys() {
+                        if *kind != PackEntryKind::Operation {
+                            continue;
+                        }
+                        let id = OperationId::from_bytes(id);
+                        if !prefix.matches(&id) {
+                            continue;
+                        }
+                        if add_match(&mut matched, id).is_err() {
+                            return Ok(PrefixResolution::AmbiguousMatch);
+                        }
+                    }
+                }
             }
             if let Some(id) = matched {
                 Ok(PrefixResolution::SingleMatch(id))
@@ -273,9 +536,7 @@ impl OpStore for SimpleOpStore {
                 Ok(PrefixResolution::NoMatch)
             }
         };
-        find()
-            .context(&op_dir)
-            .map_err(|err| OpStoreError::Other(err.into()))
+        find().map_err(|err| OpStoreError::Other(err.into()))
     }
 
     #[tracing::instrument(skip(self))]
@@ -358,10 +619,246 @@ impl OpStore for SimpleOpStore {
         };
         prune_views().map_err(|err| OpStoreError::Other(err.into()))?;
 
+        // Unreachable packed entries are dropped by the same rule as the loose
+        // files.
+        let keep_newer = millis_since_epoch(keep_newer);
+        self.compact(false, |kind, id, mtime| {
+            mtime > keep_newer
+                || match kind {
+                    PackEntryKind::Operation => {
+                        reachable_ops.contains_key(&OperationId::from_bytes(id))
+                    }
+                    PackEntryKind::View => reachable_views.contains(&ViewId::from_bytes(id)),
+                }
+        })
+        .map_err(OpStoreError::Other)?;
+
         Ok(())
     }
 }
 
+/// Kind of the object stored in a pack file.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+enum PackEntryKind {
+    Operation,
+    View,
+}
+
+impl PackEntryKind {
+    fn to_byte(self) -> u8 {
+        match self {
+            PackEntryKind::Operation => 0,
+            PackEntryKind::View => 1,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(PackEntryKind::Operation),
+            1 => Some(PackEntryKind::View),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct PackEntry {
+    offset: u64,
+    len: u64,
+    /// Modification time of the loose file the entry was packed from.
+    mtime: MillisSinceEpoch,
+}
+
+/// Pack file of serialized operations and views.
+///
+/// A pack file consists of the concatenated entry data, followed by the index
+/// of the entries, and a fixed-size footer pointing to the index.
+#[derive(Debug)]
+struct OpPack {
+    path: PathBuf,
+    /// The pack file, kept open so that entries can be read without reopening
+    /// it.
+    file: Mutex<File>,
+    entries: HashMap<(PackEntryKind, Vec<u8>), PackEntry>,
+}
+
+impl OpPack {
+    fn load(path: PathBuf) -> io::Result<Self> {
+        let corrupt_error = || io::Error::new(ErrorKind::InvalidData, "Corrupt operation pack");
+        let mut file = File::open(&path)?;
+        let file_len = file.metadata()?.len();
+        if file_len < PACK_FOOTER_LENGTH as u64 {
+            return Err(corrupt_error());
+        }
+        let mut footer = [0; PACK_FOOTER_LENGTH];
+        file.seek(SeekFrom::Start(file_len - PACK_FOOTER_LENGTH as u64))?;
+        file.read_exact(&mut footer)?;
+        let (num_entries, rest) = footer.split_at(8);
+        let (index_offset, rest) = rest.split_at(8);
+        let (version, magic) = rest.split_at(4);
+        if magic != PACK_MAGIC {
+            return Err(corrupt_error());
+        }
+        let version = u32::from_le_bytes(version.try_into().unwrap());
+        if version != PACK_FORMAT_VERSION {
+            return Err(io::Error::new(
+                ErrorKind::InvalidData,
+                format!("Unsupported operation pack format version {version}"),
+            ));
+        }
+        let num_entries = u64::from_le_bytes(num_entries.try_into().unwrap());
+        let index_offset = u64::from_le_bytes(index_offset.try_into().unwrap());
+        let index_len = num_entries
+            .checked_mul(PACK_INDEX_ENTRY_LENGTH as u64)
+            .filter(|&len| {
+                index_offset.checked_add(len) == Some(file_len - PACK_FOOTER_LENGTH as u64)
+            })
+            .ok_or_else(corrupt_error)?;
+
+        let mut index = vec![0; usize::try_from(index_len).map_err(|_| corrupt_error())?];
+        file.seek(SeekFrom::Start(index_offset))?;
+        file.read_exact(&mut index)?;
+        let mut entries = HashMap::with_capacity(index.len() / PACK_INDEX_ENTRY_LENGTH);
+        for chunk in index.chunks_exact(PACK_INDEX_ENTRY_LENGTH) {
+            let (&kind, rest) = chunk.split_first().unwrap();
+            let (id, rest) = rest.split_at(PACK_ID_LENGTH);
+            let (offset, rest) = rest.split_at(8);
+            let (len, mtime) = rest.split_at(8);
+            let kind = PackEntryKind::from_byte(kind).ok_or_else(corrupt_error)?;
+            let entry = PackEntry {
+                offset: u64::from_le_bytes(offset.try_into().unwrap()),
+                len: u64::from_le_bytes(len.try_into().unwrap()),
+                mtime: MillisSinceEpoch(i64::from_le_bytes(mtime.try_into().unwrap())),
+            };
+            if entry
+                .offset
+                .checked_add(entry.len)
+                .map_or(true, |end| end > index_offset)
+            {
+                return Err(corrupt_error());
+            }
+            entries.insert((kind, id.to_vec()), entry);
+        }
+        Ok(OpPack {
+            path,
+            file: Mutex::new(file),
+            entries,
+        })
+    }
+
+    fn read_entry(&self, entry: &PackEntry) -> io::Result<Vec<u8>> {
+        let mut file = self.file.lock().unwrap();
+        file.seek(SeekFrom::Start(entry.offset))?;
+        let mut data = vec![0; entry.len as usize];
+        file.read_exact(&mut data)?;
+        Ok(data)
+    }
+}
+
+fn load_packs(packs_dir: &Path) -> Result<Vec<OpPack>, PathError> {
+    let dir_entries = match packs_dir.read_dir() {
+        Ok(dir_entries) => dir_entries,
+        // Nothing has been packed yet.
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(vec![]),
+        Err(err) => return Err(err).context(packs_dir),
+    };
+    let mut packs = vec![];
+    for dir_entry in dir_entries {
+        let path = dir_entry.context(packs_dir)?.path();
+        if !path
+            .extension()
+            .is_some_and(|ext| ext == PACK_FILE_EXTENSION)
+        {
+            continue;
+        }
+        match OpPack::load(path.clone()) {
+            Ok(pack) => packs.push(pack),
+            // The pack may have been removed by concurrent compaction.
+            Err(err) if err.kind() == ErrorKind::NotFound => {}
+            Err(err) => return Err(err).context(&path),
+        }
+    }
+    Ok(packs)
+}
+
+struct PackWriter {
+    temp_file: NamedTempFile,
+    data_len: u64,
+    index: Vec<u8>,
+    ids: HashSet<(PackEntryKind, Vec<u8>)>,
+}
+
+impl PackWriter {
+    fn new(packs_dir: &Path) -> io::Result<Self> {
+        Ok(PackWriter {
+            temp_file: NamedTempFile::new_in(packs_dir)?,
+            data_len: 0,
+            index: vec![],
+            ids: HashSet::new(),
+        })
+    }
+
+    fn contains(&self, kind: PackEntryKind, id: &[u8]) -> bool {
+        self.ids.contains(&(kind, id.to_vec()))
+    }
+
+    fn add(
+        &mut self,
+        kind: PackEntryKind,
+        id: &[u8],
+        data: &[u8],
+        mtime: MillisSinceEpoch,
+    ) -> io::Result<()> {
+        assert_eq!(id.len(), PACK_ID_LENGTH);
+        self.temp_file.as_file_mut().write_all(data)?;
+        self.index.push(kind.to_byte());
+        self.index.extend_from_slice(id);
+        self.index.extend_from_slice(&self.data_len.to_le_bytes());
+        self.index
+            .extend_from_slice(&(data.len() as u64).to_le_bytes());
+        self.index.extend_from_slice(&mtime.0.to_le_bytes());
+        self.data_len += data.len() as u64;
+        self.ids.insert((kind, id.to_vec()));
+        Ok(())
+    }
+
+    /// Writes the index and moves the pack into place. Returns the path to
+    /// the pack, or `None` if there were no entries to pack.
+    fn finish(mut self, packs_dir: &Path) -> io::Result<Option<PathBuf>> {
+        if self.ids.is_empty() {
+            return Ok(None);
+        }
+        let file = self.temp_file.as_file_mut();
+        file.write_all(&self.index)?;
+        file.write_all(&(self.ids.len() as u64).to_le_bytes())?;
+        file.write_all(&self.data_len.to_le_bytes())?;
+        file.write_all(&PACK_FORMAT_VERSION.to_le_bytes())?;
+        file.write_all(PACK_MAGIC)?;
+        let mut hasher = Blake2b512::new();
+        hasher.update(&self.index);
+        let path = packs_dir
+            .join(hex::encode(hasher.finalize()))
+            .with_extension(PACK_FILE_EXTENSION);
+        persist_content_addressed_temp_file(self.temp_file, &path)?;
+        Ok(Some(path))
+    }
+}
+
+fn millis_since_epoch(time: SystemTime) -> MillisSinceEpoch {
+    let millis = match time.duration_since(SystemTime::UNIX_EPOCH) {
+        Ok(duration) => i64::try_from(duration.as_millis()).unwrap_or(i64::MAX),
+        Err(err) => -i64::try_from(err.duration().as_millis()).unwrap_or(i64::MAX),
+    };
+    MillisSinceEpoch(millis)
+}
+
+fn remove_file_if_exists(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(err) if err.kind() != ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
+
 fn io_to_read_error(err: std::io::Error, id: &impl ObjectId) -> OpStoreError {
     if err.kind() == ErrorKind::NotFound {
         OpStoreError::ObjectNotFound {
@@ -438,6 +935,14 @@ fn operation_to_proto(operation: &Operation) -> crate::protos::op_store::Operati
     for parent in &operation.parents {
         proto.parents.push(parent.to_bytes());
     }
+    for (commit_id, reason) in &operation.abandoned_commits {
+        proto
+            .abandoned_commits
+            .push(crate::protos::op_store::AbandonedCommit {
+                commit_id: commit_id.to_bytes(),
+                reason: abandon_reason_to_proto(*reason),
+            });
+    }
     proto
 }
 
@@ -445,13 +950,50 @@ fn operation_from_proto(proto: crate::protos::op_store::Operation) -> Operation
     let parents = proto.parents.into_iter().map(OperationId::new).collect();
     let view_id = ViewId::new(proto.view_id);
     let metadata = operation_metadata_from_proto(proto.metadata.unwrap_or_default());
+    let abandoned_commits = proto
+        .abandoned_commits
+        .into_iter()
+        .filter_map(|entry| {
+            let reason = abandon_reason_from_proto(entry.reason)?;
+            Some((CommitId::new(entry.commit_id), reason))
+        })
+        .collect();
     Operation {
         view_id,
         parents,
         metadata,
+        abandoned_commits,
     }
 }
 
+fn abandon_reason_to_proto(reason: AbandonReason) -> i32 {
+    let proto_reason = match reason {
+        AbandonReason::Explicit => crate::protos::op_store::AbandonReason::Explicit,
+        AbandonReason::EmptiedByRebase => crate::protos::op_store::AbandonReason::EmptiedByRebase,
+        AbandonReason::SquashedInto => crate::protos::op_store::AbandonReason::SquashedInto,
+        AbandonReason::DiscardedWorkingCopy => {
+            crate::protos::op_store::AbandonReason::DiscardedWorkingCopy
+        }
+        AbandonReason::UnreachableInGit => crate::protos::op_store::AbandonReason::UnreachableInGit,
+    };
+    proto_reason as i32
+}
+
+fn abandon_reason_from_proto(proto_value: i32) -> Option<AbandonReason> {
+    let proto_reason = proto_value.try_into().ok()?;
+    let reason = match proto_reason {
+        crate::protos::op_store::AbandonReason::Unknown => return None,
+        crate::protos::op_store::AbandonReason::Explicit => AbandonReason::Explicit,
+        crate::protos::op_store::AbandonReason::EmptiedByRebase => AbandonReason::EmptiedByRebase,
+        crate::protos::op_store::AbandonReason::SquashedInto => AbandonReason::SquashedInto,
+        crate::protos::op_store::AbandonReason::DiscardedWorkingCopy => {
+            AbandonReason::DiscardedWorkingCopy
+        }
+        crate::protos::op_store::AbandonReason::UnreachableInGit => AbandonReason::UnreachableInGit,
+    };
+    Some(reason)
+}
+
 fn view_to_proto(view: &View) -> crate::protos::op_store::View {
     let mut proto = crate::protos::op_store::View {
         ..Default::default()
@@ -484,6 +1026,15 @@ fn view_to_proto(view: &View) -> crate::protos::op_store::View {
 
     proto.git_head = ref_target_to_proto(&view.git_head);
 
+    for (old_commit_id, new_parent_ids) in &view.deferred_rewrites {
+        proto
+            .deferred_rewrites
+            .push(crate::protos::op_store::DeferredRewrite {
+                old_commit_id: old_commit_id.to_bytes(),
+                new_parent_ids: new_parent_ids.iter().map(|id| id.to_bytes()).collect(),
+            });
+    }
+
     proto
 }
 
@@ -530,6 +1081,18 @@ fn view_from_proto(proto: crate::protos::op_store::View) -> View {
         view.git_head = RefTarget::normal(CommitId::new(proto.git_head_legacy));
     }
 
+    for deferred_rewrite in proto.deferred_rewrites {
+        let new_parent_ids = deferred_rewrite
+            .new_parent_ids
+            .into_iter()
+            .map(CommitId::new)
+            .collect();
+        view.deferred_rewrites.insert(
+            CommitId::new(deferred_rewrite.old_commit_id),
+            new_parent_ids,
+        );
+    }
+
     view
 }
 
@@ -699,6 +1262,7 @@ fn remote_ref_state_from_proto(proto_value: Option<i32>) -> Option<RemoteRefStat
 
 #[cfg(test)]
 mod tests {
+    use assert_matches::assert_matches;
     use insta::assert_snapshot;
     use itertools::Itertools as _;
     use maplit::btreemap;
@@ -755,6 +1319,7 @@ mod tests {
                 WorkspaceId::default() => default_wc_commit_id,
                 WorkspaceId::new("test".to_string()) => test_wc_commit_id,
             },
+            deferred_rewrites: BTreeMap::new(),
         }
     }
 
@@ -783,6 +1348,10 @@ mod tests {
                     "key2".to_string() => "value2".to_string(),
                 },
             },
+            abandoned_commits: btreemap! {
+                CommitId::from_hex("ccc111") => AbandonReason::Explicit,
+                CommitId::from_hex("ccc222") => AbandonReason::EmptiedByRebase,
+            },
         }
     }
 
@@ -800,6 +1369,15 @@ mod tests {
         // Test exact output so we detect regressions in compatibility
         assert_snapshot!(
             OperationId::new(blake2b_hash(&create_operation()).to_vec()).hex(),
+            @"681db26964c045c9760358a40c62961cef489c9e5b5c1b66545a1ddf076644321919ed5d62436d3f7c0826ecc0ac83d33582ff2ae69942f75d30aac9cd9b8d64"
+        );
+        // Operations without abandoned commits keep the same hash
+        let operation = Operation {
+            abandoned_commits: BTreeMap::new(),
+            ..create_operation()
+        };
+        assert_snapshot!(
+            OperationId::new(blake2b_hash(&operation).to_vec()).hex(),
             @"20b495d54aa3be3a672a2ed6dbbf7a711dabce4cc0161d657e5177070491c1e780eec3fd35c2aa9dcc22371462aeb412a502a847f29419e65718f56a0ad1b2d0"
         );
     }
@@ -815,6 +1393,20 @@ mod tests {
         let view_id = store.write_view(&view).unwrap();
         let read_view = store.read_view(&view_id).unwrap();
         assert_eq!(read_view, view);
+
+        // Deferred rewrites are stored and contribute to the view id
+        let mut view = create_view();
+        view.deferred_rewrites = btreemap! {
+            CommitId::from_hex("eee111") => vec![CommitId::from_hex("eee222")],
+            CommitId::from_hex("eee333") => vec![
+                CommitId::from_hex("eee444"),
+                CommitId::from_hex("eee555"),
+            ],
+        };
+        let deferred_view_id = store.write_view(&view).unwrap();
+        assert_ne!(deferred_view_id, view_id);
+        let read_view = store.read_view(&deferred_view_id).unwrap();
+        assert_eq!(read_view, view);
     }
 
     #[test]
@@ -830,6 +1422,101 @@ mod tests {
         assert_eq!(read_operation, operation);
     }
 
+    #[test]
+    fn test_pack_operations() {
+        let temp_dir = new_temp_dir();
+        let root_data = RootOperationData {
+            root_commit_id: CommitId::from_hex("000000"),
+        };
+        let store = SimpleOpStore::init(temp_dir.path(), root_data.clone()).unwrap();
+        let count_files = |dir: PathBuf| dir.read_dir().unwrap().count();
+        let write_op = |store: &SimpleOpStore, parent_id: &OperationId, view_id: &ViewId| {
+            let mut operation = create_operation();
+            operation.view_id = view_id.clone();
+            operation.parents = vec![parent_id.clone()];
+            operation.metadata.description = format!("child of {parent_id:?}");
+            store.write_operation(&operation).unwrap()
+        };
+        let view_id = store.write_view(&create_view()).unwrap();
+        let mut op_ids = vec![store.root_operation_id().clone()];
+        for _ in 0..10 {
+            let op_id = write_op(&store, op_ids.last().unwrap(), &view_id);
+            op_ids.push(op_id);
+        }
+        assert_eq!(count_files(store.operations_dir()), 10);
+        assert_eq!(count_files(store.views_dir()), 1);
+
+        // Everything is reachable, so nothing is removed by gc.
+        store
+            .gc(&[op_ids.last().unwrap().clone()], SystemTime::now())
+            .unwrap();
+        assert_eq!(count_files(store.operations_dir()), 10);
+        assert_eq!(count_files(store.views_dir()), 1);
+
+        // All loose files are packed.
+        store.pack().unwrap();
+        assert_eq!(count_files(store.operations_dir()), 0);
+        assert_eq!(count_files(store.views_dir()), 0);
+        for id in &op_ids[1..] {
+            assert_eq!(store.read_operation(id).unwrap().view_id, view_id);
+        }
+        assert_eq!(store.read_view(&view_id).unwrap(), create_view());
+        let prefix = HexPrefix::new(&op_ids[5].hex()[..20]).unwrap();
+        assert_eq!(
+            store.resolve_operation_id_prefix(&prefix).unwrap(),
+            PrefixResolution::SingleMatch(op_ids[5].clone())
+        );
+        let prefix = HexPrefix::new(&op_ids[5].hex()).unwrap();
+        assert_eq!(
+            store.resolve_operation_id_prefix(&prefix).unwrap(),
+            PrefixResolution::SingleMatch(op_ids[5].clone())
+        );
+
+        // New operations are written as loose files next to the pack, and can
+        // be read by another store instance.
+        let new_op_id = {
+            let mut operation = create_operation();
+            operation.view_id = view_id.clone();
+            operation.parents = vec![op_ids[5].clone()];
+            operation.metadata.description = "new child".to_owned();
+            store.write_operation(&operation).unwrap()
+        };
+        assert_eq!(count_files(store.operations_dir()), 1);
+        let store = SimpleOpStore::load(temp_dir.path(), root_data);
+        assert_eq!(store.read_operation(&new_op_id).unwrap().view_id, view_id);
+        assert_eq!(store.read_operation(&op_ids[10]).unwrap().view_id, view_id);
+
+        // Unreachable packed operations are dropped by gc.
+        store.gc(&[new_op_id.clone()], SystemTime::now()).unwrap();
+        assert_eq!(count_files(store.operations_dir()), 1);
+        assert_eq!(
+            store
+                .packs(true)
+                .unwrap()
+                .iter()
+                .map(|pack| pack.entries.len())
+                .collect_vec(),
+            vec![5 + 1]
+        );
+        store.pack().unwrap();
+        assert_eq!(count_files(store.operations_dir()), 0);
+        assert_eq!(
+            store
+                .packs(true)
+                .unwrap()
+                .iter()
+                .map(|pack| pack.entries.len())
+                .collect_vec(),
+            vec![1 + 5 + 1]
+        );
+        assert!(store.read_operation(&new_op_id).is_ok());
+        assert!(store.read_operation(&op_ids[5]).is_ok());
+        assert_matches!(
+            store.read_operation(&op_ids[6]),
+            Err(OpStoreError::ObjectNotFound { .. })
+        );
+    }
+
     #[test]
     fn test_bookmark_views_legacy_roundtrip() {
         let new_remote_ref = |target: &RefTarget| RemoteRef {
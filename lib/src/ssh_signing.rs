@@ -84,15 +84,34 @@ fn run_command(command: &mut Command, stdin: &[u8]) -> SshResult<Vec<u8>> {
     }
 }
 
+// Returns the public key data if the given key is inlined rather than a path
+// to a key file. Like git, the "key::" prefix can be used to mark the key as
+// inlined explicitly.
+fn parse_inlined_key(key: &str) -> Option<&str> {
+    const KEY_TYPE_PREFIXES: &[&str] = &["ssh-", "ecdsa-", "sk-ssh-", "sk-ecdsa-"];
+    if let Some(key) = key.strip_prefix("key::") {
+        Some(key)
+    } else if KEY_TYPE_PREFIXES
+        .iter()
+        .any(|prefix| key.starts_with(prefix))
+    {
+        Some(key)
+    } else {
+        None
+    }
+}
+
 // This attempts to convert given key data into a file and return the filepath.
 // If the given data is actually already a filepath to a key on disk then the
 // key input is returned directly.
+//
+// If the file contains a public key, ssh-keygen will look up the private key
+// in the ssh-agent.
 fn ensure_key_as_file(key: &str) -> SshResult<Either<PathBuf, tempfile::TempPath>> {
-    let is_inlined_ssh_key = key.starts_with("ssh-");
-    if !is_inlined_ssh_key {
+    let Some(key) = parse_inlined_key(key) else {
         let key_path = crate::file_util::expand_home_path(key);
         return Ok(either::Left(key_path));
-    }
+    };
 
     let mut pub_key_file = tempfile::Builder::new()
         .prefix("jj-signing-key-")
@@ -294,6 +313,40 @@ mod tests {
         assert_eq!("ssh-ed25519 some-key-data", String::from_utf8(buf).unwrap());
     }
 
+    #[test]
+    fn test_ssh_key_to_file_conversion_prefixed_key_data() {
+        let keydata = "key::ecdsa-sha2-nistp256 some-key-data";
+        let path = ensure_key_as_file(keydata).unwrap();
+
+        let mut buf = vec![];
+        let mut file = File::open(path.right().unwrap()).unwrap();
+        file.read_to_end(&mut buf).unwrap();
+
+        assert_eq!(
+            "ecdsa-sha2-nistp256 some-key-data",
+            String::from_utf8(buf).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_inlined_key() {
+        assert_eq!(
+            parse_inlined_key("ssh-ed25519 AAAA"),
+            Some("ssh-ed25519 AAAA")
+        );
+        assert_eq!(
+            parse_inlined_key("ecdsa-sha2-nistp256 AAAA"),
+            Some("ecdsa-sha2-nistp256 AAAA")
+        );
+        assert_eq!(
+            parse_inlined_key("sk-ssh-ed25519@openssh.com AAAA"),
+            Some("sk-ssh-ed25519@openssh.com AAAA")
+        );
+        assert_eq!(parse_inlined_key("key::ssh-rsa AAAA"), Some("ssh-rsa AAAA"));
+        assert_eq!(parse_inlined_key("~/.ssh/id_ed25519.pub"), None);
+        assert_eq!(parse_inlined_key("ssh_keys/id_ed25519"), None);
+    }
+
     #[test]
     fn test_ssh_key_to_file_conversion_existing_file() {
         let mut file = tempfile::Builder::new()
@@ -242,6 +242,14 @@ impl Store {
         self.backend.read_file(path, id).await
     }
 
+    pub fn read_file_size(&self, path: &RepoPath, id: &FileId) -> BackendResult<u64> {
+        self.read_file_size_async(path, id).block_on()
+    }
+
+    pub async fn read_file_size_async(&self, path: &RepoPath, id: &FileId) -> BackendResult<u64> {
+        self.backend.read_file_size(path, id).await
+    }
+
     pub async fn write_file(
         &self,
         path: &RepoPath,
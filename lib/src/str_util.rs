@@ -20,6 +20,7 @@ use std::collections::BTreeMap;
 use std::fmt;
 
 use either::Either;
+use itertools::Itertools as _;
 use thiserror::Error;
 
 /// Error occurred during pattern string parsing.
@@ -30,14 +31,218 @@ pub enum StringPatternParseError {
     InvalidKind(String),
     /// Failed to parse glob pattern.
     #[error(transparent)]
-    GlobPattern(glob::PatternError),
+    GlobPattern(GlobPatternError),
     /// Failed to parse regular expression.
     #[error(transparent)]
     Regex(regex::Error),
 }
 
-fn parse_glob(src: &str) -> Result<glob::Pattern, StringPatternParseError> {
-    glob::Pattern::new(src).map_err(StringPatternParseError::GlobPattern)
+fn parse_glob(src: &str) -> Result<GlobPattern, StringPatternParseError> {
+    GlobPattern::new(src).map_err(StringPatternParseError::GlobPattern)
+}
+
+/// Error occurred during glob pattern parsing.
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+#[error("Pattern syntax error near position {pos}: {msg}")]
+pub struct GlobPatternError {
+    /// Character index in the pattern string where the error occurred.
+    pub pos: usize,
+    /// Description of the error.
+    pub msg: &'static str,
+}
+
+/// Unix-style shell wildcard pattern.
+///
+/// In addition to the syntax supported by [`glob::Pattern`], brace
+/// alternatives such as `*.{rs,md}` can be used. They are expanded to multiple
+/// `glob::Pattern`s, and a string matches if any of them matches. Braces and
+/// commas in character class such as `[{]` are literal.
+#[derive(Clone)]
+pub struct GlobPattern {
+    source: String,
+    patterns: Vec<glob::Pattern>,
+}
+
+impl GlobPattern {
+    /// Parses the given string as a glob pattern.
+    pub fn new(source: &str) -> Result<Self, GlobPatternError> {
+        let patterns = expand_glob_braces(source)?
+            .into_iter()
+            .map(|(expanded, positions)| {
+                glob::Pattern::new(&expanded).map_err(|err| GlobPatternError {
+                    // Map the position in the expanded pattern back to the source.
+                    pos: positions
+                        .get(err.pos)
+                        .copied()
+                        .unwrap_or_else(|| source.chars().count()),
+                    msg: err.msg,
+                })
+            })
+            .try_collect()?;
+        Ok(GlobPattern {
+            source: source.to_owned(),
+            patterns,
+        })
+    }
+
+    /// Escapes meta characters in the given string so it will be matched
+    /// literally.
+    pub fn escape(s: &str) -> String {
+        let mut escaped = String::with_capacity(s.len());
+        for c in s.chars() {
+            if matches!(c, '?' | '*' | '[' | ']' | '{' | '}') {
+                escaped.extend(['[', c, ']']);
+            } else {
+                escaped.push(c);
+            }
+        }
+        escaped
+    }
+
+    /// Returns the original string of this pattern.
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Returns the glob patterns the braces are expanded to.
+    pub fn patterns(&self) -> &[glob::Pattern] {
+        &self.patterns
+    }
+
+    /// Returns true if the `s` matches this pattern.
+    pub fn matches(&self, s: &str) -> bool {
+        self.patterns.iter().any(|pattern| pattern.matches(s))
+    }
+
+    /// Returns true if the `s` matches this pattern with the given options.
+    pub fn matches_with(&self, s: &str, options: glob::MatchOptions) -> bool {
+        self.patterns
+            .iter()
+            .any(|pattern| pattern.matches_with(s, options))
+    }
+}
+
+impl fmt::Debug for GlobPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("GlobPattern").field(&self.source).finish()
+    }
+}
+
+/// Maximum number of patterns a glob can be expanded to. Nested or repeated
+/// braces multiply the number of patterns.
+const MAX_GLOB_EXPANSIONS: usize = 1024;
+
+#[derive(Debug)]
+enum GlobNode {
+    /// Character and its index in the source pattern.
+    Char(char, usize),
+    /// Alternatives and the index of the opening brace in the source pattern.
+    Alternatives(Vec<Vec<GlobNode>>, usize),
+}
+
+/// Expands brace alternatives in the glob `source`. Returns pairs of the
+/// expanded pattern and the source indices of its characters.
+fn expand_glob_braces(source: &str) -> Result<Vec<(String, Vec<usize>)>, GlobPatternError> {
+    let chars = source.chars().collect_vec();
+    let mut pos = 0;
+    let nodes = parse_glob_sequence(&chars, &mut pos, false)?;
+    assert_eq!(pos, chars.len());
+    let mut expanded = vec![(String::new(), vec![])];
+    expand_glob_sequence(&nodes, &mut expanded)?;
+    Ok(expanded)
+}
+
+fn parse_glob_sequence(
+    chars: &[char],
+    pos: &mut usize,
+    in_braces: bool,
+) -> Result<Vec<GlobNode>, GlobPatternError> {
+    let mut nodes = vec![];
+    while let Some(&c) = chars.get(*pos) {
+        match c {
+            '[' => {
+                // Copy character class as is. "]" can be the first character
+                // of the class. Unclosed "[" will be reported by glob::Pattern.
+                let start = *pos;
+                let mut end = start + 1;
+                if chars.get(end) == Some(&'!') {
+                    end += 1;
+                }
+                if chars.get(end) == Some(&']') {
+                    end += 1;
+                }
+                let end = match chars[end.min(chars.len())..].iter().position(|&c| c == ']') {
+                    Some(offset) => end + offset + 1,
+                    None => start + 1,
+                };
+                nodes.extend((start..end).map(|i| GlobNode::Char(chars[i], i)));
+                *pos = end;
+            }
+            '{' => {
+                let start = *pos;
+                *pos += 1;
+                let mut alternatives = vec![];
+                loop {
+                    alternatives.push(parse_glob_sequence(chars, pos, true)?);
+                    match chars.get(*pos) {
+                        Some(',') => *pos += 1,
+                        Some('}') => break,
+                        _ => {
+                            return Err(GlobPatternError {
+                                pos: start,
+                                msg: "unmatched `{`",
+                            })
+                        }
+                    }
+                }
+                *pos += 1;
+                nodes.push(GlobNode::Alternatives(alternatives, start));
+            }
+            ',' | '}' if in_braces => break,
+            '}' => {
+                return Err(GlobPatternError {
+                    pos: *pos,
+                    msg: "unmatched `}`",
+                })
+            }
+            _ => {
+                nodes.push(GlobNode::Char(c, *pos));
+                *pos += 1;
+            }
+        }
+    }
+    Ok(nodes)
+}
+
+fn expand_glob_sequence(
+    nodes: &[GlobNode],
+    expanded: &mut Vec<(String, Vec<usize>)>,
+) -> Result<(), GlobPatternError> {
+    for node in nodes {
+        match node {
+            GlobNode::Char(c, pos) => {
+                for (text, positions) in expanded.iter_mut() {
+                    text.push(*c);
+                    positions.push(*pos);
+                }
+            }
+            GlobNode::Alternatives(alternatives, start) => {
+                let prefixes = std::mem::take(expanded);
+                for alternative in alternatives {
+                    let mut suffixed = prefixes.clone();
+                    expand_glob_sequence(alternative, &mut suffixed)?;
+                    expanded.extend(suffixed);
+                    if expanded.len() > MAX_GLOB_EXPANSIONS {
+                        return Err(GlobPatternError {
+                            pos: *start,
+                            msg: "too many brace alternatives",
+                        });
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
 }
 
 /// Pattern to be tested against string property like commit description or
@@ -53,9 +258,9 @@ pub enum StringPattern {
     /// Matches strings that case‐insensitively contain a substring.
     SubstringI(String),
     /// Matches with a Unix‐style shell wildcard pattern.
-    Glob(glob::Pattern),
+    Glob(GlobPattern),
     /// Matches with a case‐insensitive Unix‐style shell wildcard pattern.
-    GlobI(glob::Pattern),
+    GlobI(GlobPattern),
     /// Matches substrings with a regular expression.
     Regex(regex::Regex),
     // TODO: Should we add RegexI and "regex-i" prefix?
@@ -172,15 +377,19 @@ impl StringPattern {
         // expect they can use case‐insensitive patterns in contexts where they
         // generally can’t.
         match self {
-            StringPattern::Exact(literal) => Some(glob::Pattern::escape(literal).into()),
+            StringPattern::Exact(literal) => Some(GlobPattern::escape(literal).into()),
             StringPattern::Substring(needle) => {
                 if needle.is_empty() {
                     Some("*".into())
                 } else {
-                    Some(format!("*{}*", glob::Pattern::escape(needle)).into())
+                    Some(format!("*{}*", GlobPattern::escape(needle)).into())
                 }
             }
-            StringPattern::Glob(pattern) => Some(pattern.as_str().into()),
+            // Brace alternatives can't be represented as a single glob.
+            StringPattern::Glob(pattern) => match pattern.patterns() {
+                [pattern] => Some(pattern.as_str().into()),
+                _ => None,
+            },
             StringPattern::ExactI(_) => None,
             StringPattern::SubstringI(_) => None,
             StringPattern::GlobI(_) => None,
@@ -247,6 +456,36 @@ impl fmt::Display for StringPattern {
     }
 }
 
+/// Folds the case of `s` for case-insensitive comparison.
+///
+/// Each character is mapped to its simple (single-character) case folding, so
+/// the result has the same number of characters as the input. Characters
+/// which only have multi-character foldings are left unchanged.
+pub fn fold_case(s: &str) -> Cow<'_, str> {
+    if s.chars().all(|c| fold_case_char(c) == c) {
+        Cow::Borrowed(s)
+    } else {
+        Cow::Owned(s.chars().map(fold_case_char).collect())
+    }
+}
+
+fn fold_case_char(c: char) -> char {
+    if c.is_ascii() {
+        return c.to_ascii_lowercase();
+    }
+    match c {
+        // Final sigma folds to the same character as the other lowercase form.
+        'ς' => 'σ',
+        _ => {
+            let mut lower = c.to_lowercase();
+            match (lower.next(), lower.next()) {
+                (Some(folded), None) => folded,
+                _ => c,
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use assert_matches::assert_matches;
@@ -270,6 +509,92 @@ mod tests {
             StringPattern::Substring("*".into()).to_glob(),
             Some("*[*]*".into())
         );
+        assert_eq!(
+            StringPattern::exact("{a,b}").to_glob(),
+            Some("[{]a,b[}]".into())
+        );
+        assert_eq!(
+            StringPattern::glob("{a}*").unwrap().to_glob(),
+            Some("a*".into())
+        );
+        assert_eq!(StringPattern::glob("{a,b}").unwrap().to_glob(), None);
+    }
+
+    #[test]
+    fn test_fold_case() {
+        assert_matches!(fold_case("foo/bar.rs"), Cow::Borrowed("foo/bar.rs"));
+        assert_eq!(fold_case("Foo/BAR.rs"), "foo/bar.rs");
+        assert_eq!(fold_case("ÄÖÜ"), "äöü");
+        assert_eq!(fold_case("ΣΑΣ ΣΑς"), "σασ σασ");
+        assert_eq!(fold_case("ẞ"), "ß");
+        // Characters with multi-character folding are preserved
+        assert_eq!(fold_case("İ"), "İ");
+        assert_eq!(fold_case("[A-Z]*.TXT"), "[a-z]*.txt");
+    }
+
+    #[test]
+    fn test_glob_pattern() {
+        let expand = |src: &str| {
+            GlobPattern::new(src)
+                .unwrap()
+                .patterns()
+                .iter()
+                .map(|pattern| pattern.as_str().to_owned())
+                .collect_vec()
+        };
+        let parse_err = |src: &str| GlobPattern::new(src).unwrap_err().to_string();
+
+        assert_eq!(expand(""), [""]);
+        assert_eq!(expand("*.rs"), ["*.rs"]);
+        assert_eq!(expand("*.{rs,md}"), ["*.rs", "*.md"]);
+        assert_eq!(expand("{a,b}{c,d}"), ["ac", "bc", "ad", "bd"]);
+        assert_eq!(expand("a{b,{c,d}e}f"), ["abf", "acef", "adef"]);
+        assert_eq!(expand("a{,b}"), ["a", "ab"]);
+        assert_eq!(expand("a{}"), ["a"]);
+        assert_eq!(expand("a,b"), ["a,b"]);
+
+        // Braces and commas in character class are literal
+        assert_eq!(expand("[{]a,b[}]"), ["[{]a,b[}]"]);
+        assert_eq!(expand("{[,],b}"), ["[,]", "b"]);
+        assert_eq!(expand("{[]}],b}"), ["[]}]", "b"]);
+        assert_eq!(expand("{[!}],b}"), ["[!}]", "b"]);
+        assert_eq!(expand("{a,[b-c]}"), ["a", "[b-c]"]);
+
+        // Error positions point to the source characters
+        assert_eq!(
+            parse_err("a{b,c"),
+            "Pattern syntax error near position 1: unmatched `{`"
+        );
+        assert_eq!(
+            parse_err("{a,{b}"),
+            "Pattern syntax error near position 0: unmatched `{`"
+        );
+        assert_eq!(
+            parse_err("a,b}"),
+            "Pattern syntax error near position 3: unmatched `}`"
+        );
+        assert_eq!(
+            parse_err(&"{a,b,c,d}".repeat(6)),
+            "Pattern syntax error near position 45: too many brace alternatives"
+        );
+        assert_eq!(
+            parse_err("{a,b}c[d"),
+            "Pattern syntax error near position 6: invalid range pattern"
+        );
+        assert_eq!(
+            parse_err("{a,bb}***"),
+            "Pattern syntax error near position 8: wildcards are either regular `*` or recursive \
+             `**`"
+        );
+
+        let pattern = GlobPattern::new("*.{rs,md}").unwrap();
+        assert!(pattern.matches("foo.rs"));
+        assert!(pattern.matches("foo.md"));
+        assert!(!pattern.matches("foo.toml"));
+        assert!(!pattern.matches("foo.{rs,md}"));
+        let pattern = GlobPattern::new(&GlobPattern::escape("*.{rs,md}")).unwrap();
+        assert!(pattern.matches("*.{rs,md}"));
+        assert!(!pattern.matches("foo.rs"));
     }
 
     #[test]
@@ -14,6 +14,7 @@
 
 #![allow(missing_docs)]
 
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
 use itertools::Itertools as _;
@@ -92,9 +93,33 @@ impl Transaction {
         let repo_loader = self.base_repo().loader();
         let base_repo = repo_loader.load_at(&ancestor_op)?;
         let other_repo = repo_loader.load_at(&other_op)?;
+        // Collect the reasons why the merged operations abandoned commits so
+        // they can be carried over if the merge abandons the same commits.
+        let mut abandon_reasons = BTreeMap::new();
+        let merged_ops = dag_walk::dfs_ok(
+            self.parent_ops.iter().chain([&other_op]).cloned().map(Ok),
+            |op: &Operation| op.id().clone(),
+            |op: &Operation| {
+                if op.id() == ancestor_op.id() {
+                    vec![]
+                } else {
+                    op.parents().collect_vec()
+                }
+            },
+        );
+        for op in merged_ops {
+            let op = op?;
+            if op.id() == ancestor_op.id() {
+                continue;
+            }
+            for (commit_id, reason) in &op.store_operation().abandoned_commits {
+                abandon_reasons.entry(commit_id.clone()).or_insert(*reason);
+            }
+        }
         self.parent_ops.push(other_op);
         let merged_repo = self.repo_mut();
         merged_repo.merge(&base_repo, &other_repo)?;
+        merged_repo.inherit_abandon_reasons(abandon_reasons);
         Ok(())
     }
 
@@ -121,7 +146,7 @@ impl Transaction {
             "BUG: Descendants have not been rebased after the last rewrites."
         );
         let base_repo = mut_repo.base_repo().clone();
-        let (mut_index, view) = mut_repo.consume();
+        let (mut_index, view, abandoned_commits) = mut_repo.consume();
 
         let view_id = base_repo.op_store().write_view(view.store_view()).unwrap();
         self.op_metadata.description = description.into();
@@ -131,6 +156,7 @@ impl Transaction {
             view_id,
             parents,
             metadata: self.op_metadata,
+            abandoned_commits,
         };
         let new_op_id = base_repo
             .op_store()
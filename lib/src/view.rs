@@ -88,6 +88,18 @@ impl View {
         &self.data.git_head
     }
 
+    /// Rewritten commits whose descendants haven't been rebased yet, mapped to
+    /// the new parents for their children.
+    pub fn deferred_rewrites(&self) -> &BTreeMap<CommitId, Vec<CommitId>> {
+        &self.data.deferred_rewrites
+    }
+
+    /// Returns true if the commit has been rewritten, but rebasing its
+    /// descendants onto the new version has been deferred.
+    pub fn is_deferred_rewrite(&self, commit_id: &CommitId) -> bool {
+        self.data.deferred_rewrites.contains_key(commit_id)
+    }
+
     pub fn set_wc_commit(&mut self, workspace_id: WorkspaceId, commit_id: CommitId) {
         self.data.wc_commit_ids.insert(workspace_id, commit_id);
     }
@@ -350,6 +362,10 @@ impl View {
         self.data.git_head = target;
     }
 
+    pub fn set_deferred_rewrites(&mut self, deferred_rewrites: BTreeMap<CommitId, Vec<CommitId>>) {
+        self.data.deferred_rewrites = deferred_rewrites;
+    }
+
     /// Iterates all commit ids referenced by this view.
     ///
     /// This can include hidden commits referenced by remote bookmarks, previous
@@ -375,6 +391,7 @@ impl View {
             git_refs,
             git_head,
             wc_commit_ids,
+            deferred_rewrites,
         } = &self.data;
         itertools::chain!(
             head_ids,
@@ -388,7 +405,8 @@ impl View {
             }),
             git_refs.values().flat_map(ref_target_ids),
             ref_target_ids(git_head),
-            wc_commit_ids.values()
+            wc_commit_ids.values(),
+            deferred_rewrites.values().flatten()
         )
     }
 
@@ -30,11 +30,16 @@ use crate::backend::MergedTreeId;
 use crate::commit::Commit;
 use crate::conflicts::ConflictMarkerStyle;
 use crate::dag_walk;
+use crate::eol::EolConversionMode;
 use crate::fsmonitor::FsmonitorSettings;
 use crate::gitignore::GitIgnoreError;
 use crate::gitignore::GitIgnoreFile;
+use crate::matchers::DifferenceMatcher;
 use crate::matchers::EverythingMatcher;
 use crate::matchers::Matcher;
+use crate::matchers::NothingMatcher;
+use crate::matchers::PrefixMatcher;
+use crate::matchers::UnionMatcher;
 use crate::op_heads_store::OpHeadsStoreError;
 use crate::op_store::OpStoreError;
 use crate::op_store::OperationId;
@@ -68,9 +73,9 @@ pub trait WorkingCopy: Send {
 
     /// Patterns that decide which paths from the current tree should be checked
     /// out in the working copy. An empty list means that no paths should be
-    /// checked out in the working copy. A single `RepoPath::root()` entry means
-    /// that all files should be checked out.
-    fn sparse_patterns(&self) -> Result<&[RepoPathBuf], WorkingCopyStateError>;
+    /// checked out in the working copy. A single `RepoPath::root()` include
+    /// pattern means that all files should be checked out.
+    fn sparse_patterns(&self) -> Result<&[SparsePattern], WorkingCopyStateError>;
 
     /// Locks the working copy and returns an instance with methods for updating
     /// the working copy files and state.
@@ -136,7 +141,7 @@ pub trait LockedWorkingCopy {
     fn recover(&mut self, commit: &Commit) -> Result<(), ResetError>;
 
     /// See `WorkingCopy::sparse_patterns()`
-    fn sparse_patterns(&self) -> Result<&[RepoPathBuf], WorkingCopyStateError>;
+    fn sparse_patterns(&self) -> Result<&[SparsePattern], WorkingCopyStateError>;
 
     /// Updates the patterns that decide which paths from the current tree
     /// should be checked out in the working copy.
@@ -146,7 +151,7 @@ pub trait LockedWorkingCopy {
     // to use sparse).
     fn set_sparse_patterns(
         &mut self,
-        new_sparse_patterns: Vec<RepoPathBuf>,
+        new_sparse_patterns: Vec<SparsePattern>,
         options: &CheckoutOptions,
     ) -> Result<CheckoutStats, CheckoutError>;
 
@@ -158,6 +163,46 @@ pub trait LockedWorkingCopy {
     ) -> Result<Box<dyn WorkingCopy>, WorkingCopyStateError>;
 }
 
+/// A pattern that decides which paths are checked out in the working copy.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum SparsePattern {
+    /// Checks out all files under the path.
+    Include(RepoPathBuf),
+    /// Doesn't check out files under the path even if an earlier pattern
+    /// included them.
+    Exclude(RepoPathBuf),
+}
+
+impl SparsePattern {
+    /// The path prefix the pattern applies to.
+    pub fn path(&self) -> &RepoPath {
+        match self {
+            SparsePattern::Include(path) | SparsePattern::Exclude(path) => path,
+        }
+    }
+
+    /// Whether files matching the pattern are excluded.
+    pub fn is_exclude(&self) -> bool {
+        matches!(self, SparsePattern::Exclude(_))
+    }
+}
+
+/// Creates a matcher for the files to check out according to the sparse
+/// patterns. Patterns are evaluated in order, and the last pattern matching a
+/// path wins.
+pub fn sparse_patterns_matcher(patterns: &[SparsePattern]) -> Box<dyn Matcher> {
+    let mut matcher: Box<dyn Matcher> = Box::new(NothingMatcher);
+    for (is_exclude, chunk) in &patterns.iter().chunk_by(|pattern| pattern.is_exclude()) {
+        let prefix_matcher = PrefixMatcher::new(chunk.map(SparsePattern::path));
+        matcher = if is_exclude {
+            Box::new(DifferenceMatcher::new(matcher, prefix_matcher))
+        } else {
+            Box::new(UnionMatcher::new(matcher, prefix_matcher))
+        };
+    }
+    matcher
+}
+
 /// An error while snapshotting the working copy.
 #[derive(Debug, Error)]
 pub enum SnapshotError {
@@ -213,6 +258,13 @@ pub struct SnapshotOptions<'a> {
     /// For new files that are not already tracked, start tracking them if they
     /// match this.
     pub start_tracking_matcher: &'a dyn Matcher,
+    /// For new files that are not already tracked, start tracking them if they
+    /// match this even if they're ignored. Should be a subset of
+    /// `start_tracking_matcher`.
+    pub force_tracking_matcher: &'a dyn Matcher,
+    /// Whether to report untracked paths which are ignored in
+    /// `SnapshotStats::ignored_paths`.
+    pub report_ignored_paths: bool,
     /// The size of the largest file that should be allowed to become tracked
     /// (already tracked files are always snapshotted). If there are larger
     /// files in the working copy, then `LockedWorkingCopy::snapshot()` may
@@ -221,6 +273,10 @@ pub struct SnapshotOptions<'a> {
     pub max_new_file_size: u64,
     /// Expected conflict marker style for checking for changed files.
     pub conflict_marker_style: ConflictMarkerStyle,
+    /// How to convert line endings of text files before storing them.
+    pub eol_conversion_mode: EolConversionMode,
+    /// How to determine the executable bit of files.
+    pub executable_bit_mode: ExecutableBitMode,
 }
 
 impl SnapshotOptions<'_> {
@@ -231,12 +287,32 @@ impl SnapshotOptions<'_> {
             fsmonitor_settings: FsmonitorSettings::None,
             progress: None,
             start_tracking_matcher: &EverythingMatcher,
+            force_tracking_matcher: &NothingMatcher,
+            report_ignored_paths: false,
             max_new_file_size: u64::MAX,
             conflict_marker_style: ConflictMarkerStyle::default(),
+            eol_conversion_mode: EolConversionMode::default(),
+            executable_bit_mode: ExecutableBitMode::default(),
         }
     }
 }
 
+/// Describes how the executable bit of files in the working copy is
+/// snapshotted.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ExecutableBitMode {
+    /// Honor the executable bit on disk if the file system supports it.
+    /// Otherwise, behave like `PreserveRecorded`.
+    #[default]
+    Auto,
+    /// Keep the executable bit recorded in the tree. New files are recorded
+    /// as non-executable.
+    PreserveRecorded,
+    /// Record the executable bit as found on disk.
+    HonorFilesystem,
+}
+
 /// A callback for getting progress updates.
 pub type SnapshotProgress<'a> = dyn Fn(&RepoPath) + 'a + Sync;
 
@@ -245,6 +321,19 @@ pub type SnapshotProgress<'a> = dyn Fn(&RepoPath) + 'a + Sync;
 pub struct SnapshotStats {
     /// List of new (previously untracked) files which are still untracked.
     pub untracked_paths: BTreeMap<RepoPathBuf, UntrackedReason>,
+    /// Text files whose line endings weren't converted because they contain
+    /// both LF and CRLF line endings.
+    pub mixed_eol_paths: Vec<RepoPathBuf>,
+    /// List of untracked paths which are ignored by `.gitignore`. A directory
+    /// which is ignored as a whole is reported as a single entry. This is only
+    /// populated if `SnapshotOptions::report_ignored_paths` is set.
+    pub ignored_paths: BTreeMap<RepoPathBuf, IgnoredPathKind>,
+    /// Tracked files which match ignore patterns. These are only reported if
+    /// a `.gitignore` file affecting them has changed since the last snapshot.
+    pub ignored_tracked_paths: Vec<RepoPathBuf>,
+    /// Number of files whose contents were read because they were new or
+    /// their file states didn't show them to be unchanged.
+    pub num_read_files: usize,
 }
 
 /// Reason why the new path isn't tracked.
@@ -261,22 +350,70 @@ pub enum UntrackedReason {
     FileNotAutoTracked,
 }
 
+/// Kind of ignored path reported by snapshot.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum IgnoredPathKind {
+    /// Ignored file.
+    File,
+    /// Ignored directory containing no tracked files.
+    Directory,
+}
+
 /// Options used when checking out a tree in the working copy.
 #[derive(Clone)]
-pub struct CheckoutOptions {
+pub struct CheckoutOptions<'a> {
     /// Conflict marker style to use when materializing files
     pub conflict_marker_style: ConflictMarkerStyle,
+    /// Whether to create symlinks. If false, or if a symlink can't be created,
+    /// the symlink is written as a regular file containing the link target.
+    pub create_symlinks: bool,
+    /// How to convert line endings of text files when writing them.
+    pub eol_conversion_mode: EolConversionMode,
+    /// How to write the contents of regular files.
+    pub clone_mode: CloneMode,
+    /// Which side of a binary conflict to write to the conflicted file. The
+    /// other terms of the conflict are written next to it.
+    pub binary_conflict_side: usize,
+    /// A callback for the UI to display progress.
+    pub progress: Option<&'a CheckoutProgress<'a>>,
 }
 
-impl CheckoutOptions {
+impl CheckoutOptions<'_> {
     /// Create an instance for use in tests.
     pub fn empty_for_test() -> Self {
         CheckoutOptions {
             conflict_marker_style: ConflictMarkerStyle::default(),
+            create_symlinks: true,
+            eol_conversion_mode: EolConversionMode::default(),
+            clone_mode: CloneMode::default(),
+            binary_conflict_side: 0,
+            progress: None,
         }
     }
 }
 
+/// Describes how the contents of regular files are written on checkout.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CloneMode {
+    /// Write a full copy of each file.
+    #[default]
+    Copy,
+    /// Clone files from a cache of previously checked-out contents, sharing
+    /// the underlying storage on file systems supporting copy-on-write. Falls
+    /// back to a full copy per file if cloning isn't supported.
+    Reflink,
+    /// Hard-link files from a cache of previously checked-out contents. The
+    /// linked files are made read-only so they can't be edited in place.
+    /// Falls back to a full copy per file if linking fails, and on Windows.
+    HardlinkFromCache,
+}
+
+/// A callback for getting checkout progress updates. It's called with the
+/// number of entries processed so far. The total number isn't known in
+/// advance because the tree diff is processed as it is computed.
+pub type CheckoutProgress<'a> = dyn Fn(usize) + 'a + Sync;
+
 /// Stats about a checkout operation on a working copy. All "files" mentioned
 /// below may also be symlinks or materialized conflicts.
 #[derive(Debug, PartialEq, Eq, Clone, Default)]
@@ -292,6 +429,25 @@ pub struct CheckoutStats {
     /// working copy but were skipped because there was an untracked (probably
     /// ignored) file in its place.
     pub skipped_files: u32,
+    /// The paths that were skipped, and why. Has one entry per skipped file.
+    pub skipped_paths: Vec<(RepoPathBuf, CheckoutSkipReason)>,
+    /// The number of symlinks that were written as regular files containing
+    /// the link target because symlinks couldn't be created. Paths which were
+    /// already written as regular files aren't counted.
+    pub fake_symlinks: u32,
+    /// The number of bytes actually written to disk. Files cloned or linked
+    /// from the cache don't count.
+    pub bytes_written: u64,
+}
+
+/// The reason a file was skipped during checkout.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CheckoutSkipReason {
+    /// An untracked file or directory exists at the path.
+    UntrackedPathExists,
+    /// A parent directory couldn't be created because an untracked file or
+    /// symlink exists in its place.
+    ParentNotDirectory,
 }
 
 /// The working-copy checkout failed.
@@ -60,6 +60,8 @@ use crate::working_copy::LockedWorkingCopy;
 use crate::working_copy::WorkingCopy;
 use crate::working_copy::WorkingCopyFactory;
 use crate::working_copy::WorkingCopyStateError;
+use crate::workspace_store::SimpleWorkspaceStore;
+use crate::workspace_store::WorkspaceStoreError;
 
 #[derive(Error, Debug)]
 pub enum WorkspaceInitError {
@@ -79,6 +81,8 @@ pub enum WorkspaceInitError {
     Backend(#[from] BackendInitError),
     #[error(transparent)]
     SignInit(#[from] SignInitError),
+    #[error(transparent)]
+    WorkspaceStore(#[from] WorkspaceStoreError),
 }
 
 #[derive(Error, Debug)]
@@ -317,6 +321,7 @@ impl Workspace {
             )?;
             let repo_loader = repo.loader().clone();
             let workspace = Workspace::new(workspace_root, repo_dir, working_copy, repo_loader)?;
+            workspace.record_workspace_path()?;
             Ok((workspace, repo))
         })()
         .inspect_err(|_err| {
@@ -378,6 +383,7 @@ impl Workspace {
             working_copy,
             repo.loader().clone(),
         )?;
+        workspace.record_workspace_path()?;
         Ok((workspace, repo))
     }
 
@@ -408,6 +414,33 @@ impl Workspace {
         &self.repo_loader
     }
 
+    /// Store of the root paths of the workspaces of the repo.
+    pub fn workspace_store(&self) -> SimpleWorkspaceStore {
+        SimpleWorkspaceStore::load(&self.repo_path)
+    }
+
+    fn record_workspace_path(&self) -> Result<(), WorkspaceStoreError> {
+        self.workspace_store()
+            .add(self.workspace_id(), &self.workspace_root)
+    }
+
+    /// Updates the recorded root path of this workspace if the workspace has
+    /// been moved, i.e. if there's no longer a workspace at the recorded path.
+    ///
+    /// Nothing is recorded if the root path of this workspace isn't known.
+    pub fn update_recorded_path(&self) -> Result<(), WorkspaceStoreError> {
+        let Some(recorded_path) = self
+            .workspace_store()
+            .get_workspace_path(self.workspace_id())?
+        else {
+            return Ok(());
+        };
+        if recorded_path != self.workspace_root && !recorded_path.join(".jj").is_dir() {
+            self.record_workspace_path()?;
+        }
+        Ok(())
+    }
+
     /// Settings for this workspace.
     pub fn settings(&self) -> &UserSettings {
         self.repo_loader.settings()
@@ -0,0 +1,171 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Records of where the workspaces of a repo are located on disk.
+//!
+//! The paths are recorded when workspaces are created on this machine, and
+//! updated when they're moved. Workspaces created elsewhere (or before the
+//! store existed) are unknown.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::io::Write as _;
+use std::path::Path;
+use std::path::PathBuf;
+
+use tempfile::NamedTempFile;
+use thiserror::Error;
+
+use crate::file_util::IoResultExt as _;
+use crate::file_util::PathError;
+use crate::lock::FileLock;
+use crate::lock::FileLockError;
+use crate::op_store::WorkspaceId;
+
+/// Error that may occur while reading or updating the workspace store.
+#[derive(Debug, Error)]
+pub enum WorkspaceStoreError {
+    /// Failed to lock the store.
+    #[error(transparent)]
+    Lock(#[from] FileLockError),
+    /// Failed to read or write the index file.
+    #[error(transparent)]
+    Path(#[from] PathError),
+    /// The index file couldn't be parsed.
+    #[error("Failed to parse workspace store index {path}")]
+    Parse {
+        /// Path to the index file.
+        path: PathBuf,
+        /// Underlying error.
+        #[source]
+        source: serde_json::Error,
+    },
+}
+
+/// Stores the root paths of workspaces in `<repo>/workspace_store/index.json`.
+#[derive(Clone, Debug)]
+pub struct SimpleWorkspaceStore {
+    store_dir: PathBuf,
+}
+
+impl SimpleWorkspaceStore {
+    /// Creates a store for the repo at `repo_path`. The store directory is
+    /// created when a path is first recorded.
+    pub fn load(repo_path: &Path) -> Self {
+        SimpleWorkspaceStore {
+            store_dir: repo_path.join("workspace_store"),
+        }
+    }
+
+    fn index_path(&self) -> PathBuf {
+        self.store_dir.join("index.json")
+    }
+
+    fn read_index(&self) -> Result<BTreeMap<String, PathBuf>, WorkspaceStoreError> {
+        let index_path = self.index_path();
+        let data = match fs::read(&index_path) {
+            Ok(data) => data,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+            Err(error) => {
+                return Err(PathError {
+                    path: index_path,
+                    error,
+                }
+                .into())
+            }
+        };
+        serde_json::from_slice(&data).map_err(|source| WorkspaceStoreError::Parse {
+            path: index_path,
+            source,
+        })
+    }
+
+    fn update_index(
+        &self,
+        f: impl FnOnce(&mut BTreeMap<String, PathBuf>),
+    ) -> Result<(), WorkspaceStoreError> {
+        fs::create_dir_all(&self.store_dir).context(&self.store_dir)?;
+        let _lock = FileLock::lock(self.store_dir.join("lock"))?;
+        let mut index = self.read_index()?;
+        f(&mut index);
+        let index_path = self.index_path();
+        let data = serde_json::to_vec_pretty(&index).unwrap();
+        let mut temp_file = NamedTempFile::new_in(&self.store_dir).context(&self.store_dir)?;
+        temp_file.write_all(&data).context(temp_file.path())?;
+        temp_file
+            .persist(&index_path)
+            .map_err(|err| err.error)
+            .context(&index_path)?;
+        Ok(())
+    }
+
+    /// Returns the recorded root path of the workspace if any.
+    pub fn get_workspace_path(
+        &self,
+        workspace_id: &WorkspaceId,
+    ) -> Result<Option<PathBuf>, WorkspaceStoreError> {
+        Ok(self.read_index()?.remove(workspace_id.as_str()))
+    }
+
+    /// Records the root path of the workspace, replacing any existing record.
+    pub fn add(&self, workspace_id: &WorkspaceId, path: &Path) -> Result<(), WorkspaceStoreError> {
+        self.update_index(|index| {
+            index.insert(workspace_id.as_str().to_owned(), path.to_owned());
+        })
+    }
+
+    /// Moves the record of the workspace to the new name.
+    pub fn rename(
+        &self,
+        old_workspace_id: &WorkspaceId,
+        new_workspace_id: &WorkspaceId,
+    ) -> Result<(), WorkspaceStoreError> {
+        self.update_index(|index| {
+            if let Some(path) = index.remove(old_workspace_id.as_str()) {
+                index.insert(new_workspace_id.as_str().to_owned(), path);
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::new_temp_dir;
+
+    #[test]
+    fn test_workspace_store() {
+        let temp_dir = new_temp_dir();
+        let store = SimpleWorkspaceStore::load(temp_dir.path());
+        let default_id = WorkspaceId::default();
+        let second_id = WorkspaceId::new("second".to_owned());
+        let third_id = WorkspaceId::new("third".to_owned());
+        assert_eq!(store.get_workspace_path(&default_id).unwrap(), None);
+
+        store.add(&default_id, Path::new("/main")).unwrap();
+        store.add(&second_id, Path::new("/second")).unwrap();
+        assert_eq!(
+            store.get_workspace_path(&default_id).unwrap(),
+            Some(PathBuf::from("/main"))
+        );
+
+        store.rename(&second_id, &third_id).unwrap();
+        assert_eq!(store.get_workspace_path(&second_id).unwrap(), None);
+        assert_eq!(
+            store.get_workspace_path(&third_id).unwrap(),
+            Some(PathBuf::from("/second"))
+        );
+    }
+}
@@ -12,21 +12,31 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use futures::StreamExt as _;
 use indoc::indoc;
 use itertools::Itertools;
 use jj_lib::backend::FileId;
+use jj_lib::conflicts::binary_conflict_sidecar_names;
+use jj_lib::conflicts::binary_conflict_sidecar_owner;
 use jj_lib::conflicts::choose_materialized_conflict_marker_len;
 use jj_lib::conflicts::extract_as_single_hunk;
+use jj_lib::conflicts::is_binary_conflict;
 use jj_lib::conflicts::materialize_merge_result_to_bytes;
+use jj_lib::conflicts::materialized_diff_stream_with_concurrency;
 use jj_lib::conflicts::parse_conflict;
 use jj_lib::conflicts::update_from_content;
 use jj_lib::conflicts::ConflictMarkerStyle;
 use jj_lib::conflicts::MIN_CONFLICT_MARKER_LEN;
+use jj_lib::copies::CopyRecords;
+use jj_lib::matchers::EverythingMatcher;
 use jj_lib::merge::Merge;
 use jj_lib::repo::Repo;
 use jj_lib::repo_path::RepoPath;
+use jj_lib::repo_path::RepoPathBuf;
 use jj_lib::store::Store;
 use pollster::FutureExt;
+use testutils::create_tree;
+use testutils::test_backend::TestBackend;
 use testutils::TestRepo;
 
 #[test]
@@ -610,6 +620,81 @@ fn test_materialize_parse_roundtrip_different_markers() {
     }
 }
 
+#[test]
+fn test_materialize_parse_roundtrip_three_sides() {
+    let test_repo = TestRepo::init();
+    let store = test_repo.repo.store();
+
+    let path = RepoPath::from_internal_string("file");
+    let base_id = testutils::write_file(store, path, "line 1\nline 2 base\nline 3\n");
+    let a_id = testutils::write_file(store, path, "line 1\nline 2 a\nline 3\n");
+    let b_id = testutils::write_file(store, path, "line 1\nline 2 b\nline 3\n");
+    let c_id = testutils::write_file(store, path, "line 1\nline 2 c\nline 3\n");
+
+    let conflict = Merge::from_removes_adds(
+        vec![Some(base_id.clone()), Some(base_id.clone())],
+        vec![Some(a_id.clone()), Some(b_id.clone()), Some(c_id.clone())],
+    );
+
+    // Git-style markers only support 2 sides, so the snapshot style is used
+    // instead
+    insta::assert_snapshot!(
+        materialize_conflict_string(store, path, &conflict, ConflictMarkerStyle::Git),
+        @r"
+    line 1
+    <<<<<<< Conflict 1 of 1
+    +++++++ Contents of side #1
+    line 2 a
+    ------- Contents of base #1
+    line 2 base
+    +++++++ Contents of side #2
+    line 2 b
+    ------- Contents of base #2
+    line 2 base
+    +++++++ Contents of side #3
+    line 2 c
+    >>>>>>> Conflict 1 of 1 ends
+    line 3
+    "
+    );
+
+    let all_styles = [
+        ConflictMarkerStyle::Diff,
+        ConflictMarkerStyle::Snapshot,
+        ConflictMarkerStyle::Git,
+    ];
+    let resolved_id = testutils::write_file(store, path, "line 1\nline 2 resolved\nline 3\n");
+    for materialize_style in all_styles {
+        let materialized = materialize_conflict_string(store, path, &conflict, materialize_style);
+        for parse_style in all_styles {
+            let parse = |content: &[u8]| {
+                update_from_content(
+                    &conflict,
+                    store,
+                    path,
+                    content,
+                    parse_style,
+                    MIN_CONFLICT_MARKER_LEN,
+                )
+                .block_on()
+                .unwrap()
+            };
+            assert_eq!(
+                parse(materialized.as_bytes()),
+                conflict,
+                "parse {materialize_style:?} conflict markers with {parse_style:?}"
+            );
+            // Resolving the conflict by editing the file is detected in every
+            // style
+            assert_eq!(
+                parse(b"line 1\nline 2 resolved\nline 3\n"),
+                Merge::normal(resolved_id.clone()),
+                "resolve {materialize_style:?} conflict with {parse_style:?}"
+            );
+        }
+    }
+}
+
 #[test]
 fn test_materialize_conflict_no_newlines_at_eof() {
     let test_repo = TestRepo::init();
@@ -2443,6 +2528,49 @@ fn test_update_from_content_malformed_conflict() {
     assert_eq!(second_snapshot, new_conflict);
 }
 
+#[test]
+fn test_binary_conflict_sidecars() {
+    assert!(!is_binary_conflict(&Merge::from_vec(vec![
+        "a\n", "b\n", "c\n"
+    ])));
+    assert!(is_binary_conflict(&Merge::from_vec(vec![
+        "a\n", "", "\0c\n"
+    ])));
+
+    assert_eq!(
+        binary_conflict_sidecar_names("logo.png", 2),
+        [
+            "logo.png.jj-side-0",
+            "logo.png.jj-side-1",
+            "logo.png.jj-base"
+        ]
+    );
+    assert_eq!(
+        binary_conflict_sidecar_names("a", 3),
+        [
+            "a.jj-side-0",
+            "a.jj-side-1",
+            "a.jj-side-2",
+            "a.jj-base-0",
+            "a.jj-base-1"
+        ]
+    );
+
+    assert_eq!(
+        binary_conflict_sidecar_owner("logo.png.jj-side-1"),
+        Some("logo.png")
+    );
+    assert_eq!(
+        binary_conflict_sidecar_owner("logo.png.jj-base"),
+        Some("logo.png")
+    );
+    assert_eq!(binary_conflict_sidecar_owner("a.jj-base-10"), Some("a"));
+    assert_eq!(binary_conflict_sidecar_owner("logo.png"), None);
+    assert_eq!(binary_conflict_sidecar_owner("a.jj-side-"), None);
+    assert_eq!(binary_conflict_sidecar_owner("a.jj-side-x"), None);
+    assert_eq!(binary_conflict_sidecar_owner(".jj-base"), None);
+}
+
 fn materialize_conflict_string(
     store: &Store,
     path: &RepoPath,
@@ -2455,3 +2583,38 @@ fn materialize_conflict_string(
     String::from_utf8(materialize_merge_result_to_bytes(&contents, conflict_marker_style).into())
         .unwrap()
 }
+
+#[test]
+fn test_materialized_diff_stream_concurrency() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+    let store = repo.store();
+    let test_backend: &TestBackend = store.backend_impl().downcast_ref().unwrap();
+    test_backend.set_yield_on_file_read(true);
+
+    let paths = (0..10)
+        .map(|i| RepoPathBuf::from_internal_string(format!("file{i}")))
+        .collect_vec();
+    let path_contents = paths
+        .iter()
+        .map(|path| (path.as_ref(), "content\n"))
+        .collect_vec();
+    let left_tree = create_tree(repo, &[]);
+    let right_tree = create_tree(repo, &path_contents);
+
+    // Files are read ahead of the consumer, but no more than the concurrency.
+    let max_reads_in_flight = |concurrency| {
+        let copy_records = CopyRecords::default();
+        let tree_diff =
+            left_tree.diff_stream_with_copies(&right_tree, &EverythingMatcher, &copy_records);
+        let entries: Vec<_> =
+            materialized_diff_stream_with_concurrency(store, tree_diff, concurrency)
+                .collect()
+                .block_on();
+        assert_eq!(entries.len(), paths.len());
+        assert!(entries.iter().all(|entry| entry.values.is_ok()));
+        test_backend.max_file_reads_in_flight()
+    };
+    assert_eq!(max_reads_in_flight(1), 1);
+    assert_eq!(max_reads_in_flight(4), 4);
+}
@@ -3520,6 +3520,53 @@ fn test_push_updates_success(subprocess: bool) {
     assert_eq!(new_target.target().id(), new_oid);
 }
 
+#[test]
+fn test_push_updates_sends_only_new_objects() {
+    let settings = testutils::user_settings();
+    let temp_dir = testutils::new_temp_dir();
+    let setup = set_up_push_repos(&settings, &temp_dir);
+    let git_settings = get_git_settings(true);
+    let source_repo = testutils::git::open(&setup.source_repo_dir);
+
+    // Small pushes are unpacked as loose objects by the remote.
+    let count_loose_objects = || {
+        let objects_dir = setup.source_repo_dir.join("objects");
+        objects_dir
+            .read_dir()
+            .unwrap()
+            .map(|entry| entry.unwrap())
+            .filter(|entry| entry.file_name().len() == 2)
+            .map(|entry| entry.path().read_dir().unwrap().count())
+            .sum::<usize>()
+    };
+
+    // Move main forward in the remote. The remote no longer advertises a ref
+    // pointing to a commit we know about.
+    empty_git_commit(
+        &source_repo,
+        "refs/heads/main",
+        &[git_id(&setup.main_commit)],
+    );
+    let old_count = count_loose_objects();
+
+    let result = git::push_updates(
+        setup.jj_repo.as_ref(),
+        &git_settings,
+        "origin",
+        &[GitRefUpdate {
+            qualified_name: "refs/heads/feature".to_string(),
+            expected_current_target: None,
+            new_target: Some(setup.child_of_main_commit.id().clone()),
+        }],
+        git::RemoteCallbacks::default(),
+    );
+    assert_matches!(result, Ok(()));
+
+    // Only the new commit, its tree, and its file should be sent. The ancestors
+    // of main are already in the remote.
+    assert_eq!(count_loose_objects() - old_count, 3);
+}
+
 #[test_case(false; "use git2 for remote calls")]
 #[test_case(true; "spawn a git subprocess for remote calls")]
 fn test_push_updates_no_such_remote(subprocess: bool) {
@@ -12,11 +12,15 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::BTreeMap;
 use std::collections::HashSet;
 use std::fs;
 use std::sync::Arc;
+use std::time::Duration;
+use std::time::SystemTime;
 
 use assert_matches::assert_matches;
+use itertools::Itertools as _;
 use jj_lib::backend::ChangeId;
 use jj_lib::backend::CommitId;
 use jj_lib::commit::Commit;
@@ -638,6 +642,155 @@ fn test_reindex_corrupt_segment_files() {
     assert!(repo.index().has_id(commit_a.id()));
 }
 
+#[test]
+fn test_reindex_corrupt_segment_positions() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init();
+    let test_env = &test_repo.env;
+    let repo = &test_repo.repo;
+
+    let mut tx = repo.start_transaction();
+    let commit_a = write_random_commit(tx.repo_mut());
+    let repo = tx.commit("test").unwrap();
+    assert!(repo.index().has_id(commit_a.id()));
+
+    // Corrupt the parent position of the first entry, keeping the file length
+    let segments_dir = test_repo.repo_path().join("index").join("segments");
+    for entry in segments_dir.read_dir().unwrap() {
+        let path = entry.unwrap().path();
+        let mut data = fs::read(&path).unwrap();
+        // u32: file format version
+        // u32: parent segment file name length
+        // <length number of bytes>: parent segment file name
+        // u32 * 4: numbers of entries
+        // u32: generation number of the first entry
+        let parent_filename_len = u32::from_le_bytes(data[4..8].try_into().unwrap()) as usize;
+        let offset = 8 + parent_filename_len + 16 + 4;
+        data[offset..offset + 4].copy_from_slice(&0x7fff_fff0_u32.to_le_bytes());
+        fs::write(&path, data).unwrap();
+    }
+
+    // The corruption should be detected on load
+    let default_index_store: &DefaultIndexStore =
+        repo.index_store().as_any().downcast_ref().unwrap();
+    assert_matches!(
+        default_index_store.verify_index_at_operation(repo.operation(), repo.store()),
+        Err(DefaultIndexStoreError::LoadIndex(_))
+    );
+
+    // The index should be rebuilt automatically
+    let repo = test_env.load_repo_at_head(&settings, test_repo.repo_path());
+    assert!(repo.index().has_id(commit_a.id()));
+    let default_index_store: &DefaultIndexStore =
+        repo.index_store().as_any().downcast_ref().unwrap();
+    assert!(default_index_store
+        .verify_index_at_operation(repo.operation(), repo.store())
+        .is_ok());
+}
+
+#[test]
+fn test_compact_index() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init();
+    let test_env = &test_repo.env;
+    let repo = &test_repo.repo;
+    let repo = create_n_commits(repo, 30);
+    let repo = create_n_commits(&repo, 15);
+    let repo = create_n_commits(&repo, 7);
+    assert_eq!(commits_by_level(&repo), vec![31, 15, 7]);
+
+    let entries_by_pos = |index: &CompositeIndex, heads: &HashSet<CommitId>| {
+        let mut pending = heads
+            .iter()
+            .map(|id| index.entry_by_id(id).unwrap())
+            .collect_vec();
+        let mut entries = BTreeMap::new();
+        while let Some(entry) = pending.pop() {
+            if entries.contains_key(&entry.position()) {
+                continue;
+            }
+            pending.extend(entry.parents());
+            let value = (
+                entry.commit_id(),
+                entry.change_id(),
+                entry.generation_number(),
+            );
+            entries.insert(entry.position(), value);
+        }
+        entries.into_values().collect_vec()
+    };
+    let heads = repo.view().heads().clone();
+    let entries = entries_by_pos(as_readonly_composite(&repo), &heads);
+    assert_eq!(
+        entries.len() as u32,
+        as_readonly_composite(&repo).num_commits()
+    );
+
+    let default_index_store: &DefaultIndexStore =
+        repo.index_store().as_any().downcast_ref().unwrap();
+    let stats = default_index_store.stats().unwrap();
+    let index = default_index_store
+        .compact_index_at_operation(repo.operation(), repo.store(), SystemTime::now())
+        .unwrap();
+    assert_eq!(index.as_composite().stats().levels.len(), 1);
+    // The head segment replaced by the compacted one should be kept for
+    // concurrent readers. The other segments are still referenced by the
+    // previous operations.
+    assert_eq!(
+        default_index_store.stats().unwrap().num_segment_files,
+        stats.num_segment_files + 1
+    );
+
+    // The compacted index should be loaded, and the positions, generation
+    // numbers, and ids should be preserved.
+    let repo = test_env.load_repo_at_head(&settings, test_repo.repo_path());
+    assert_eq!(commits_by_level(&repo), vec![53]);
+    let index = as_readonly_composite(&repo);
+    assert_eq!(entries_by_pos(index, &heads), entries);
+    for (commit_id, change_id, _) in &entries {
+        let prefix = HexPrefix::new(&commit_id.hex()[..12]).unwrap();
+        assert_eq!(
+            index.resolve_commit_id_prefix(&prefix),
+            PrefixResolution::SingleMatch(commit_id.clone())
+        );
+        assert!(repo
+            .resolve_change_id(change_id)
+            .is_some_and(|ids| ids.contains(commit_id)));
+    }
+
+    // Already compacted index isn't rewritten
+    let num_segment_files = default_index_store.stats().unwrap().num_segment_files;
+    default_index_store
+        .compact_index_at_operation(repo.operation(), repo.store(), SystemTime::now())
+        .unwrap();
+    assert_eq!(
+        default_index_store.stats().unwrap().num_segment_files,
+        num_segment_files
+    );
+
+    // The segment replaced by the previous compaction is removed once it's
+    // older than the threshold, but the newly replaced segment is kept.
+    let repo = create_n_commits(&repo, 1);
+    let num_segment_files = default_index_store.stats().unwrap().num_segment_files;
+    let keep_newer = SystemTime::now() - Duration::from_secs(3600);
+    default_index_store
+        .compact_index_at_operation(repo.operation(), repo.store(), keep_newer)
+        .unwrap();
+    assert_eq!(
+        default_index_store.stats().unwrap().num_segment_files,
+        num_segment_files + 1
+    );
+    let repo = create_n_commits(&repo, 1);
+    let num_segment_files = default_index_store.stats().unwrap().num_segment_files;
+    default_index_store
+        .compact_index_at_operation(repo.operation(), repo.store(), SystemTime::now())
+        .unwrap();
+    assert_eq!(
+        default_index_store.stats().unwrap().num_segment_files,
+        num_segment_files - 1
+    );
+}
+
 #[test]
 fn test_reindex_from_merged_operation() {
     let test_repo = TestRepo::init();
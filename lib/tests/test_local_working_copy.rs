@@ -19,6 +19,9 @@ use std::os::unix::net::UnixListener;
 use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::SystemTime;
 
 use assert_matches::assert_matches;
 use indoc::indoc;
@@ -26,11 +29,15 @@ use itertools::Itertools;
 use jj_lib::backend::MergedTreeId;
 use jj_lib::backend::TreeId;
 use jj_lib::backend::TreeValue;
+use jj_lib::eol::EolConversionMode;
 use jj_lib::file_util::check_symlink_support;
 use jj_lib::file_util::try_symlink;
 use jj_lib::fsmonitor::FsmonitorSettings;
+#[cfg(feature = "watchman")]
+use jj_lib::fsmonitor::WatchmanConfig;
 use jj_lib::gitignore::GitIgnoreFile;
 use jj_lib::local_working_copy::LocalWorkingCopy;
+use jj_lib::matchers::FilesMatcher;
 use jj_lib::merge::Merge;
 use jj_lib::merge::MergedTreeValue;
 use jj_lib::merged_tree::MergedTree;
@@ -45,8 +52,13 @@ use jj_lib::repo_path::RepoPathComponent;
 use jj_lib::secret_backend::SecretBackend;
 use jj_lib::working_copy::CheckoutError;
 use jj_lib::working_copy::CheckoutOptions;
+use jj_lib::working_copy::CheckoutSkipReason;
 use jj_lib::working_copy::CheckoutStats;
+use jj_lib::working_copy::CloneMode;
+use jj_lib::working_copy::ExecutableBitMode;
+use jj_lib::working_copy::IgnoredPathKind;
 use jj_lib::working_copy::SnapshotOptions;
+use jj_lib::working_copy::SparsePattern;
 use jj_lib::working_copy::UntrackedReason;
 use jj_lib::working_copy::WorkingCopy;
 use jj_lib::workspace::default_working_copy_factories;
@@ -110,7 +122,10 @@ fn test_root() {
     let mut test_workspace = TestWorkspace::init();
 
     let wc = test_workspace.workspace.working_copy();
-    assert_eq!(wc.sparse_patterns().unwrap(), vec![RepoPathBuf::root()]);
+    assert_eq!(
+        wc.sparse_patterns().unwrap(),
+        vec![SparsePattern::Include(RepoPathBuf::root())]
+    );
     let new_tree = test_workspace.snapshot().unwrap();
     let repo = &test_workspace.repo;
     let wc_commit_id = repo
@@ -647,7 +662,23 @@ fn test_conflicting_changes_on_disk() {
             updated_files: 0,
             added_files: 3,
             removed_files: 0,
-            skipped_files: 3
+            skipped_files: 3,
+            skipped_paths: vec![
+                (
+                    dir_file_path.to_owned(),
+                    CheckoutSkipReason::UntrackedPathExists
+                ),
+                (
+                    file_dir_path.join(RepoPathComponent::new("file")),
+                    CheckoutSkipReason::ParentNotDirectory
+                ),
+                (
+                    file_file_path.to_owned(),
+                    CheckoutSkipReason::UntrackedPathExists
+                ),
+            ],
+            fake_symlinks: 0,
+            bytes_written: 0,
         }
     );
 
@@ -670,6 +701,250 @@ fn test_conflicting_changes_on_disk() {
     );
 }
 
+#[test]
+fn test_checkout_progress() {
+    let test_workspace = TestWorkspace::init();
+    let repo = &test_workspace.repo;
+    let mut ws = test_workspace.workspace;
+
+    let tree = create_tree(
+        repo,
+        &[
+            (RepoPath::from_internal_string("a"), "a"),
+            (RepoPath::from_internal_string("b"), "b"),
+            (RepoPath::from_internal_string("dir/c"), "c"),
+        ],
+    );
+    let commit = commit_with_tree(repo.store(), tree.id());
+
+    let updates = Mutex::new(vec![]);
+    let progress = |processed: usize| {
+        updates.lock().unwrap().push(processed);
+    };
+    let options = CheckoutOptions {
+        progress: Some(&progress),
+        ..CheckoutOptions::empty_for_test()
+    };
+    let stats = ws
+        .check_out(repo.op_id().clone(), None, &commit, &options)
+        .unwrap();
+    assert_eq!(stats.added_files, 3);
+    assert_eq!(updates.into_inner().unwrap(), vec![1, 2, 3]);
+}
+
+#[test]
+fn test_checkout_parallel_matches_serial() {
+    // Tests that the files are written in parallel with the same results as
+    // when they are written serially.
+    let create_tree_for_round = |repo: &Arc<ReadonlyRepo>, round: usize| {
+        let store = repo.store();
+        let mut tree_builder = store.tree_builder(store.empty_tree_id().clone());
+        for dir in 0..10 {
+            for file in 0..300 {
+                // Remove every fifth file and modify every other file in the
+                // second round
+                if round > 0 && file % 5 == 0 {
+                    continue;
+                }
+                let contents_round = if file % 2 == 0 { round } else { 0 };
+                let path = format!("dir{dir}/sub{}/file{file}", file % 3);
+                let path = RepoPath::from_internal_string(&path);
+                let contents = format!("contents {contents_round} {dir} {file}\n");
+                if file % 7 == 0 {
+                    testutils::write_executable_file(&mut tree_builder, path, &contents);
+                } else if file % 11 == 0 {
+                    testutils::write_symlink(&mut tree_builder, path, &contents);
+                } else {
+                    testutils::write_normal_file(&mut tree_builder, path, &contents);
+                }
+            }
+        }
+        // Add files in new directories in the second round
+        if round > 0 {
+            for file in 0..100 {
+                let path = format!("new/sub{}/file{file}", file % 4);
+                testutils::write_normal_file(
+                    &mut tree_builder,
+                    RepoPath::from_internal_string(&path),
+                    &format!("new {file}\n"),
+                );
+            }
+        }
+        let id = tree_builder.write_tree().unwrap();
+        let tree = MergedTree::resolved(store.get_tree(RepoPathBuf::root(), &id).unwrap());
+        commit_with_tree(store, tree.id())
+    };
+    let check_out_with_threads =
+        |test_workspace: &mut TestWorkspace, round: usize, num_threads: usize| {
+            let repo = test_workspace.repo.clone();
+            let commit = create_tree_for_round(&repo, round);
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(num_threads)
+                .build()
+                .unwrap();
+            pool.install(|| {
+                test_workspace
+                    .workspace
+                    .check_out(
+                        repo.op_id().clone(),
+                        None,
+                        &commit,
+                        &CheckoutOptions::empty_for_test(),
+                    )
+                    .unwrap()
+            })
+        };
+    // The mtimes can differ between the working copies.
+    let file_states = |test_workspace: &TestWorkspace| {
+        let wc: &LocalWorkingCopy = test_workspace
+            .workspace
+            .working_copy()
+            .as_any()
+            .downcast_ref()
+            .unwrap();
+        wc.file_states()
+            .unwrap()
+            .iter()
+            .map(|(path, state)| {
+                let size = state.size;
+                let is_fake_symlink = state.is_fake_symlink;
+                (path.to_owned(), state.file_type, size, is_fake_symlink)
+            })
+            .collect_vec()
+    };
+
+    let mut serial_workspace = TestWorkspace::init();
+    let mut parallel_workspace = TestWorkspace::init();
+    for round in 0..2 {
+        let serial_stats = check_out_with_threads(&mut serial_workspace, round, 1);
+        let parallel_stats = check_out_with_threads(&mut parallel_workspace, round, 16);
+        assert_eq!(parallel_stats, serial_stats);
+
+        let serial_file_states = file_states(&serial_workspace);
+        assert_eq!(
+            serial_file_states.len(),
+            if round == 0 { 3000 } else { 2500 }
+        );
+        assert_eq!(file_states(&parallel_workspace), serial_file_states);
+
+        for (path, _, _, _) in &serial_file_states {
+            let read = |test_workspace: &TestWorkspace| {
+                let disk_path =
+                    path.to_fs_path_unchecked(test_workspace.workspace.workspace_root());
+                let metadata = disk_path.symlink_metadata().unwrap();
+                let contents = if metadata.is_symlink() {
+                    std::fs::read_link(&disk_path)
+                        .unwrap()
+                        .into_os_string()
+                        .into_encoded_bytes()
+                } else {
+                    std::fs::read(&disk_path).unwrap()
+                };
+                (metadata.file_type().is_symlink(), contents)
+            };
+            assert_eq!(
+                read(&parallel_workspace),
+                read(&serial_workspace),
+                "{path:?}"
+            );
+        }
+    }
+    // The removed files are gone from both working copies.
+    assert!(!serial_workspace
+        .workspace
+        .workspace_root()
+        .join("dir0/sub0/file0")
+        .exists());
+    assert!(!parallel_workspace
+        .workspace
+        .workspace_root()
+        .join("dir0/sub0/file0")
+        .exists());
+}
+
+#[test_case(CloneMode::Copy; "copy")]
+#[test_case(CloneMode::Reflink; "reflink")]
+#[test_case(CloneMode::HardlinkFromCache; "hardlink from cache")]
+fn test_checkout_clone_mode(clone_mode: CloneMode) {
+    let mut test_workspace = TestWorkspace::init();
+    let repo = &test_workspace.repo.clone();
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+
+    let file1_path = RepoPath::from_internal_string("file1");
+    let file2_path = RepoPath::from_internal_string("file2");
+    let tree1 = create_tree(repo, &[(file1_path, "contents\n")]);
+    let tree2 = create_tree(
+        repo,
+        &[(file1_path, "contents\n"), (file2_path, "contents\n")],
+    );
+    let commit1 = commit_with_tree(repo.store(), tree1.id());
+    let commit2 = commit_with_tree(repo.store(), tree2.id());
+
+    let options = CheckoutOptions {
+        clone_mode,
+        ..CheckoutOptions::empty_for_test()
+    };
+    let ws = &mut test_workspace.workspace;
+    let stats1 = ws
+        .check_out(repo.op_id().clone(), None, &commit1, &options)
+        .unwrap();
+    let stats2 = ws
+        .check_out(repo.op_id().clone(), None, &commit2, &options)
+        .unwrap();
+    assert_eq!(stats2.added_files, 1);
+    match clone_mode {
+        CloneMode::Copy => {
+            assert_eq!(stats1.bytes_written, 9);
+            assert_eq!(stats2.bytes_written, 9);
+        }
+        CloneMode::Reflink => {
+            // The file is only cached if the file system supports cloning, so
+            // the second file is written again on file systems such as tmpfs.
+            assert_eq!(stats1.bytes_written, 9);
+            assert!([0, 9].contains(&stats2.bytes_written));
+        }
+        CloneMode::HardlinkFromCache => {
+            assert_eq!(stats1.bytes_written, 9);
+            assert_eq!(stats2.bytes_written, if cfg!(unix) { 0 } else { 9 });
+        }
+    }
+
+    for path in [file1_path, file2_path] {
+        let disk_path = path.to_fs_path_unchecked(&workspace_root);
+        assert_eq!(std::fs::read_to_string(&disk_path).unwrap(), "contents\n");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt as _;
+            let metadata = disk_path.metadata().unwrap();
+            if clone_mode == CloneMode::HardlinkFromCache {
+                assert_eq!(metadata.nlink(), 3);
+                assert_eq!(metadata.mode() & 0o777, 0o444);
+            } else {
+                assert_eq!(metadata.nlink(), 1);
+                assert_eq!(metadata.mode() & 0o777, 0o644);
+            }
+        }
+    }
+
+    // The checked-out files are clean
+    let new_tree = test_workspace.snapshot().unwrap();
+    assert_eq!(new_tree.id(), tree2.id());
+
+    // The cache entries of the removed files are pruned
+    let cache_dir = workspace_root.join(".jj/working_copy/file_cache");
+    if clone_mode == CloneMode::HardlinkFromCache {
+        assert!(cache_dir.read_dir().unwrap().next().is_some());
+    }
+    let commit3 = commit_with_tree(repo.store(), repo.store().empty_merged_tree_id());
+    test_workspace
+        .workspace
+        .check_out(repo.op_id().clone(), None, &commit3, &options)
+        .unwrap();
+    assert!(cache_dir
+        .read_dir()
+        .map_or(true, |mut entries| entries.next().is_none()));
+}
+
 #[test]
 fn test_reset() {
     let mut test_workspace = TestWorkspace::init();
@@ -875,7 +1150,10 @@ fn test_materialize_snapshot_conflicted_files() {
             updated_files: 0,
             added_files: 2,
             removed_files: 0,
-            skipped_files: 0
+            skipped_files: 0,
+            skipped_paths: vec![],
+            fake_symlinks: 0,
+            bytes_written: 252,
         }
     );
 
@@ -983,6 +1261,161 @@ fn test_snapshot_racy_timestamps() {
     }
 }
 
+#[test]
+fn test_snapshot_racy_clean_files_not_reread() {
+    // Tests that files which were racy when the working copy state was saved,
+    // but turned out to be unchanged, aren't read again by every snapshot.
+    let mut test_workspace = TestWorkspace::init();
+    let repo = &test_workspace.repo;
+    let op_id = repo.op_id().clone();
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+    let tree_state_path = workspace_root.join(".jj/working_copy/tree_state");
+
+    let mtime = SystemTime::now() - Duration::from_secs(3600);
+    let set_mtime = |path: &Path| {
+        let file = std::fs::File::options().write(true).open(path).unwrap();
+        file.set_modified(mtime).unwrap();
+    };
+    let paths = (0..100)
+        .map(|i| workspace_root.join(format!("file{i}")))
+        .collect_vec();
+    for path in &paths {
+        std::fs::write(path, "contents").unwrap();
+        set_mtime(path);
+    }
+
+    let mut snapshot = || {
+        let mut locked_ws = test_workspace
+            .workspace
+            .start_working_copy_mutation()
+            .unwrap();
+        let (_tree_id, stats) = locked_ws
+            .locked_wc()
+            .snapshot(&SnapshotOptions::empty_for_test())
+            .unwrap();
+        locked_ws.finish(op_id.clone()).unwrap();
+        stats.num_read_files
+    };
+    assert_eq!(snapshot(), 100);
+    assert_eq!(snapshot(), 0);
+
+    // Pretend that the files were written in the same millisecond as the state
+    // file, so they have to be read again once.
+    set_mtime(&tree_state_path);
+    assert_eq!(snapshot(), 100);
+    assert_eq!(snapshot(), 0);
+}
+
+#[cfg(unix)]
+#[test_case(ExecutableBitMode::PreserveRecorded, false; "preserve recorded")]
+#[test_case(ExecutableBitMode::HonorFilesystem, true; "honor filesystem")]
+fn test_snapshot_executable_bit_mode(executable_bit_mode: ExecutableBitMode, honored: bool) {
+    let mut test_workspace = TestWorkspace::init();
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+    let exec_path = RepoPath::from_internal_string("exec");
+    let new_path = RepoPath::from_internal_string("new");
+
+    let write_file = |path: &RepoPath, contents: &str, executable: bool| {
+        let disk_path = path.to_fs_path_unchecked(&workspace_root);
+        std::fs::write(&disk_path, contents).unwrap();
+        let mode = if executable { 0o755 } else { 0o644 };
+        std::fs::set_permissions(&disk_path, std::fs::Permissions::from_mode(mode)).unwrap();
+    };
+    let is_executable =
+        |tree: &MergedTree, path: &RepoPath| match tree.path_value(path).unwrap().into_resolved() {
+            Ok(Some(TreeValue::File { executable, .. })) => executable,
+            value => panic!("unexpected value: {value:?}"),
+        };
+
+    // Record the file as executable
+    write_file(exec_path, "contents", true);
+    let options = SnapshotOptions {
+        executable_bit_mode: ExecutableBitMode::HonorFilesystem,
+        ..SnapshotOptions::empty_for_test()
+    };
+    let (tree, _stats) = test_workspace.snapshot_with_options(&options).unwrap();
+    assert!(is_executable(&tree, exec_path));
+
+    // Modify the file and clear its executable bit on disk, and add a new
+    // executable file
+    write_file(exec_path, "modified", false);
+    write_file(new_path, "new", true);
+    let options = SnapshotOptions {
+        executable_bit_mode,
+        ..SnapshotOptions::empty_for_test()
+    };
+    let (tree, _stats) = test_workspace.snapshot_with_options(&options).unwrap();
+    assert_eq!(is_executable(&tree, exec_path), !honored);
+    assert_eq!(is_executable(&tree, new_path), honored);
+
+    // Touching the file again doesn't change the recorded bit
+    write_file(exec_path, "modified again", false);
+    let (tree, _stats) = test_workspace.snapshot_with_options(&options).unwrap();
+    assert_eq!(is_executable(&tree, exec_path), !honored);
+}
+
+#[test]
+fn test_snapshot_parallel_matches_serial() {
+    // Tests that the snapshot is deterministic regardless of how the
+    // directories and files are scanned in parallel.
+    let snapshot_with_threads = |test_workspace: &mut TestWorkspace, num_threads: usize| {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .build()
+            .unwrap();
+        pool.install(|| test_workspace.snapshot().unwrap())
+    };
+    let write_files = |workspace_root: &Path, round: usize| {
+        for dir in 0..10 {
+            for file in 0..200 {
+                // Modify every other file in subsequent rounds
+                if round > 0 && file % 2 == 1 {
+                    continue;
+                }
+                let path = format!("dir{dir}/sub{}/file{file}", file % 3);
+                testutils::write_working_copy_file(
+                    workspace_root,
+                    RepoPath::from_internal_string(&path),
+                    &format!("contents {round} {dir} {file}\n"),
+                );
+            }
+        }
+    };
+
+    let mut serial_workspace = TestWorkspace::init();
+    let mut parallel_workspace = TestWorkspace::init();
+    for round in 0..3 {
+        for test_workspace in [&serial_workspace, &parallel_workspace] {
+            write_files(test_workspace.workspace.workspace_root(), round);
+        }
+        let serial_tree = snapshot_with_threads(&mut serial_workspace, 1);
+        let parallel_tree = snapshot_with_threads(&mut parallel_workspace, 16);
+        assert_eq!(serial_tree.entries().count(), 2000);
+        assert_eq!(tree_entries(&parallel_tree), tree_entries(&serial_tree));
+
+        let serial_wc: &LocalWorkingCopy = serial_workspace
+            .workspace
+            .working_copy()
+            .as_any()
+            .downcast_ref()
+            .unwrap();
+        let parallel_wc: &LocalWorkingCopy = parallel_workspace
+            .workspace
+            .working_copy()
+            .as_any()
+            .downcast_ref()
+            .unwrap();
+        assert_eq!(
+            parallel_wc.file_states().unwrap().paths().collect_vec(),
+            serial_wc.file_states().unwrap().paths().collect_vec()
+        );
+
+        // Snapshotting again without changes should produce the same tree
+        let parallel_tree_again = snapshot_with_threads(&mut parallel_workspace, 16);
+        assert_eq!(parallel_tree_again.id(), parallel_tree.id());
+    }
+}
+
 #[cfg(unix)]
 #[test]
 fn test_snapshot_special_file() {
@@ -1149,6 +1582,174 @@ fn test_gitignores_in_ignored_dir() {
     assert_eq!(tree_entries(&new_tree), tree_entries(&tree2));
 }
 
+#[test]
+fn test_gitignores_reported_in_stats() {
+    // Tests that ignored files and directories are reported by snapshot, but
+    // directories containing tracked files aren't.
+
+    let mut test_workspace = TestWorkspace::init();
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+
+    let gitignore_path = RepoPath::from_internal_string(".gitignore");
+    let tracked_path = RepoPath::from_internal_string("partially-ignored/tracked");
+    let tree = create_tree(
+        &test_workspace.repo,
+        &[
+            (
+                gitignore_path,
+                "*.ignored\n/ignored/\n/partially-ignored/\n",
+            ),
+            (tracked_path, "contents"),
+        ],
+    );
+    let commit = commit_with_tree(test_workspace.repo.store(), tree.id());
+    let op_id = test_workspace.repo.op_id().clone();
+    test_workspace
+        .workspace
+        .check_out(op_id, None, &commit, &CheckoutOptions::empty_for_test())
+        .unwrap();
+
+    for path in [
+        "file.ignored",
+        "dir/file.ignored",
+        "dir/file",
+        "ignored/file",
+        "partially-ignored/file",
+    ] {
+        let path = RepoPath::from_internal_string(path);
+        testutils::write_working_copy_file(&workspace_root, path, "contents");
+    }
+
+    // Ignored paths aren't reported unless requested
+    let (_tree, stats) = test_workspace
+        .snapshot_with_options(&SnapshotOptions::empty_for_test())
+        .unwrap();
+    assert!(stats.ignored_paths.is_empty());
+
+    let (_tree, stats) = test_workspace
+        .snapshot_with_options(&SnapshotOptions {
+            report_ignored_paths: true,
+            ..SnapshotOptions::empty_for_test()
+        })
+        .unwrap();
+    assert_eq!(
+        stats
+            .ignored_paths
+            .iter()
+            .map(|(path, kind)| (path.as_internal_file_string(), *kind))
+            .collect_vec(),
+        [
+            ("dir/file.ignored", IgnoredPathKind::File),
+            ("file.ignored", IgnoredPathKind::File),
+            ("ignored", IgnoredPathKind::Directory),
+        ]
+    );
+}
+
+#[test]
+fn test_gitignores_force_tracking() {
+    // Tests that ignored files and files in ignored directories can be tracked
+    // explicitly.
+
+    let mut test_workspace = TestWorkspace::init();
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+
+    let gitignore_path = RepoPath::from_internal_string(".gitignore");
+    let ignored_path = RepoPath::from_internal_string("file.ignored");
+    let other_ignored_path = RepoPath::from_internal_string("other.ignored");
+    let in_ignored_dir_path = RepoPath::from_internal_string("ignored/file");
+    testutils::write_working_copy_file(&workspace_root, gitignore_path, "*.ignored\n/ignored/\n");
+    for path in [ignored_path, other_ignored_path, in_ignored_dir_path] {
+        testutils::write_working_copy_file(&workspace_root, path, "contents");
+    }
+
+    let force_tracking_matcher =
+        FilesMatcher::new([ignored_path.to_owned(), in_ignored_dir_path.to_owned()]);
+    let (tree, _stats) = test_workspace
+        .snapshot_with_options(&SnapshotOptions {
+            force_tracking_matcher: &force_tracking_matcher,
+            ..SnapshotOptions::empty_for_test()
+        })
+        .unwrap();
+    assert_eq!(
+        tree.entries().map(|(path, _)| path).collect_vec(),
+        [
+            gitignore_path.to_owned(),
+            ignored_path.to_owned(),
+            in_ignored_dir_path.to_owned(),
+        ]
+    );
+
+    // The files stay tracked in later snapshots
+    let (tree, _stats) = test_workspace
+        .snapshot_with_options(&SnapshotOptions::empty_for_test())
+        .unwrap();
+    assert_eq!(
+        tree.entries().map(|(path, _)| path).collect_vec(),
+        [
+            gitignore_path.to_owned(),
+            ignored_path.to_owned(),
+            in_ignored_dir_path.to_owned(),
+        ]
+    );
+}
+
+#[test]
+fn test_gitignores_reports_tracked_files_once() {
+    // Tests that tracked files matching ignore patterns are reported only when
+    // a .gitignore file has changed.
+
+    let mut test_workspace = TestWorkspace::init();
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+
+    let gitignore_path = RepoPath::from_internal_string(".gitignore");
+    let build_path = RepoPath::from_internal_string("build/out");
+    let log_path = RepoPath::from_internal_string("dir/file.log");
+    let kept_path = RepoPath::from_internal_string("kept");
+    for path in [build_path, log_path, kept_path] {
+        testutils::write_working_copy_file(&workspace_root, path, "contents");
+    }
+    let mut snapshot_ignored_tracked_paths = || {
+        let (_tree, stats) = test_workspace
+            .snapshot_with_options(&SnapshotOptions::empty_for_test())
+            .unwrap();
+        stats.ignored_tracked_paths
+    };
+    assert_eq!(snapshot_ignored_tracked_paths(), [] as [RepoPathBuf; 0]);
+
+    // Adding a .gitignore reports the tracked files matching it
+    testutils::write_working_copy_file(&workspace_root, gitignore_path, "build/\n*.log\n");
+    assert_eq!(
+        snapshot_ignored_tracked_paths(),
+        [build_path.to_owned(), log_path.to_owned()]
+    );
+    // The files stay tracked, but aren't reported again
+    assert_eq!(snapshot_ignored_tracked_paths(), [] as [RepoPathBuf; 0]);
+
+    // Modifying the .gitignore reports the files again
+    testutils::write_working_copy_file(&workspace_root, gitignore_path, "build/\n*.log\nkept\n");
+    assert_eq!(
+        snapshot_ignored_tracked_paths(),
+        [
+            build_path.to_owned(),
+            log_path.to_owned(),
+            kept_path.to_owned()
+        ]
+    );
+
+    // A .gitignore which ignores itself stays untracked, so it isn't
+    // considered new by every snapshot
+    let dir_gitignore_path = RepoPath::from_internal_string("dir/.gitignore");
+    testutils::write_working_copy_file(
+        &workspace_root,
+        dir_gitignore_path,
+        ".gitignore
+",
+    );
+    assert_eq!(snapshot_ignored_tracked_paths(), [] as [RepoPathBuf; 0]);
+    assert_eq!(snapshot_ignored_tracked_paths(), [] as [RepoPathBuf; 0]);
+}
+
 #[test]
 fn test_gitignores_checkout_never_overwrites_ignored() {
     // Tests that a .gitignore'd file doesn't get overwritten if check out a commit
@@ -1709,6 +2310,147 @@ fn test_check_out_file_removal_over_existing_directory_symlink() {
     assert!(victim_file_path.exists());
 }
 
+#[test]
+fn test_eol_conversion() {
+    let mut test_workspace = TestWorkspace::init();
+    let repo = test_workspace.repo.clone();
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+
+    let text_path = RepoPath::from_internal_string("text");
+    let binary_path = RepoPath::from_internal_string("binary");
+    let mixed_path = RepoPath::from_internal_string("mixed");
+    let new_path = RepoPath::from_internal_string("new");
+    let tree = create_tree(
+        &repo,
+        &[
+            (text_path, "a\nb\n"),
+            (binary_path, "a\0\nb\n"),
+            (mixed_path, "a\r\nb\n"),
+        ],
+    );
+    let commit = commit_with_tree(repo.store(), tree.id());
+    let read_file = |path: &RepoPath| std::fs::read(path.to_fs_path_unchecked(&workspace_root));
+
+    // LF is converted to CRLF on checkout, except for binary files and files
+    // with mixed line endings
+    let checkout_options = CheckoutOptions {
+        eol_conversion_mode: EolConversionMode::InputOutput,
+        ..CheckoutOptions::empty_for_test()
+    };
+    let ws = &mut test_workspace.workspace;
+    ws.check_out(repo.op_id().clone(), None, &commit, &checkout_options)
+        .unwrap();
+    assert_eq!(read_file(text_path).unwrap(), b"a\r\nb\r\n");
+    assert_eq!(read_file(binary_path).unwrap(), b"a\0\nb\n");
+    assert_eq!(read_file(mixed_path).unwrap(), b"a\r\nb\n");
+
+    // The file state records the size of the converted file
+    let wc: &LocalWorkingCopy = ws.working_copy().as_any().downcast_ref().unwrap();
+    assert_eq!(wc.file_states().unwrap().get(text_path).unwrap().size, 6);
+
+    // CRLF is converted back to LF when snapshotting
+    let snapshot_options = SnapshotOptions {
+        eol_conversion_mode: EolConversionMode::InputOutput,
+        ..SnapshotOptions::empty_for_test()
+    };
+    std::fs::write(
+        text_path.to_fs_path_unchecked(&workspace_root),
+        "a\r\nb\r\n",
+    )
+    .unwrap();
+    let (new_tree, _stats) = test_workspace
+        .snapshot_with_options(&snapshot_options)
+        .unwrap();
+    assert_eq!(new_tree.id(), tree.id());
+
+    std::fs::write(
+        text_path.to_fs_path_unchecked(&workspace_root),
+        "a\r\nb\r\nc\r\n",
+    )
+    .unwrap();
+    std::fs::write(new_path.to_fs_path_unchecked(&workspace_root), "new\r\n").unwrap();
+    std::fs::write(mixed_path.to_fs_path_unchecked(&workspace_root), "x\r\ny\n").unwrap();
+    let (new_tree, stats) = test_workspace
+        .snapshot_with_options(&SnapshotOptions {
+            eol_conversion_mode: EolConversionMode::Input,
+            ..SnapshotOptions::empty_for_test()
+        })
+        .unwrap();
+    let expected_tree = create_tree(
+        &repo,
+        &[
+            (text_path, "a\nb\nc\n"),
+            (binary_path, "a\0\nb\n"),
+            (mixed_path, "x\r\ny\n"),
+            (new_path, "new\n"),
+        ],
+    );
+    assert_eq!(tree_entries(&new_tree), tree_entries(&expected_tree));
+    assert_eq!(stats.mixed_eol_paths, vec![mixed_path.to_owned()]);
+    // The files on disk are left alone
+    assert_eq!(read_file(new_path).unwrap(), b"new\r\n");
+}
+
+#[test]
+fn test_check_out_fake_symlink() {
+    let mut test_workspace = TestWorkspace::init();
+    let repo = test_workspace.repo.clone();
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+
+    let file_path = RepoPath::from_internal_string("file");
+    let symlink_path = RepoPath::from_internal_string("symlink");
+    let create_tree = |symlink_target: &str| {
+        let store = repo.store();
+        let mut tree_builder = store.tree_builder(store.empty_tree_id().clone());
+        testutils::write_normal_file(&mut tree_builder, file_path, "contents");
+        testutils::write_symlink(&mut tree_builder, symlink_path, symlink_target);
+        let id = tree_builder.write_tree().unwrap();
+        MergedTree::resolved(store.get_tree(RepoPathBuf::root(), &id).unwrap())
+    };
+    let tree = create_tree("file");
+    let commit = commit_with_tree(repo.store(), tree.id());
+
+    // Symlinks are written as regular files containing the target if they can't
+    // be created
+    let options = CheckoutOptions {
+        create_symlinks: false,
+        ..CheckoutOptions::empty_for_test()
+    };
+    let ws = &mut test_workspace.workspace;
+    let stats = ws
+        .check_out(repo.op_id().clone(), None, &commit, &options)
+        .unwrap();
+    assert_eq!(stats.fake_symlinks, 1);
+    let symlink_disk_path = symlink_path.to_fs_path_unchecked(&workspace_root);
+    let metadata = symlink_disk_path.symlink_metadata().unwrap();
+    assert!(metadata.is_file());
+    assert_eq!(std::fs::read_to_string(&symlink_disk_path).unwrap(), "file");
+
+    // The file isn't considered modified even if its stat info changes
+    std::fs::write(&symlink_disk_path, "file").unwrap();
+    let new_tree = test_workspace.snapshot().unwrap();
+    assert_eq!(new_tree.id(), tree.id());
+
+    // Changing the contents changes the symlink target
+    std::fs::write(&symlink_disk_path, "other").unwrap();
+    let new_tree = test_workspace.snapshot().unwrap();
+    assert_eq!(tree_entries(&new_tree), tree_entries(&create_tree("other")));
+
+    // The symlink isn't reported again when it's rewritten
+    let tree = create_tree("file2");
+    let commit = commit_with_tree(repo.store(), tree.id());
+    let stats = test_workspace
+        .workspace
+        .check_out(repo.op_id().clone(), None, &commit, &options)
+        .unwrap();
+    assert_eq!(stats.updated_files, 1);
+    assert_eq!(stats.fake_symlinks, 0);
+    assert_eq!(
+        std::fs::read_to_string(&symlink_disk_path).unwrap(),
+        "file2"
+    );
+}
+
 #[test_case("../pwned"; "escape from root")]
 #[test_case("sub/../../pwned"; "escape from sub dir")]
 fn test_check_out_malformed_file_path(file_path_str: &str) {
@@ -2061,7 +2803,7 @@ fn test_fsmonitor() {
     let ws = &mut test_workspace.workspace;
     assert_eq!(
         ws.working_copy().sparse_patterns().unwrap(),
-        vec![RepoPathBuf::root()]
+        vec![SparsePattern::Include(RepoPathBuf::root())]
     );
 
     let foo_path = RepoPath::from_internal_string("foo");
@@ -2149,6 +2891,113 @@ fn test_fsmonitor() {
     }
 }
 
+#[test]
+fn test_fsmonitor_changed_gitignore() {
+    let mut test_workspace = TestWorkspace::init();
+    let repo = &test_workspace.repo;
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+    let ws = &mut test_workspace.workspace;
+
+    let nested_path = RepoPath::from_internal_string("path/to/nested");
+    let ignored_path = RepoPath::from_internal_string("path/to/ignored");
+    let gitignore_path = RepoPath::from_internal_string("path/.gitignore");
+    testutils::write_working_copy_file(&workspace_root, nested_path, "nested\n");
+    testutils::write_working_copy_file(&workspace_root, ignored_path, "ignored\n");
+    testutils::write_working_copy_file(&workspace_root, gitignore_path, "to/ignored\n");
+
+    let snapshot = |locked_ws: &mut LockedWorkspace, paths: &[&RepoPath]| {
+        let fs_paths = paths
+            .iter()
+            .map(|p| p.to_fs_path_unchecked(Path::new("")))
+            .collect();
+        let (tree_id, _stats) = locked_ws
+            .locked_wc()
+            .snapshot(&SnapshotOptions {
+                fsmonitor_settings: FsmonitorSettings::Test {
+                    changed_files: fs_paths,
+                },
+                ..SnapshotOptions::empty_for_test()
+            })
+            .unwrap();
+        repo.store().get_root_tree(&tree_id).unwrap()
+    };
+
+    {
+        let mut locked_ws = ws.start_working_copy_mutation().unwrap();
+        let tree = snapshot(&mut locked_ws, &[nested_path, gitignore_path]);
+        assert!(tree.path_value(nested_path).unwrap().is_present());
+        assert!(tree.path_value(ignored_path).unwrap().is_absent());
+        locked_ws.finish(repo.op_id().clone()).unwrap();
+    }
+
+    // The ignored file isn't reported by the monitor, but it's no longer
+    // ignored after the .gitignore change
+    {
+        testutils::write_working_copy_file(&workspace_root, gitignore_path, "");
+        let mut locked_ws = ws.start_working_copy_mutation().unwrap();
+        let tree = snapshot(&mut locked_ws, &[gitignore_path]);
+        assert!(tree.path_value(ignored_path).unwrap().is_present());
+        locked_ws.finish(repo.op_id().clone()).unwrap();
+    }
+}
+
+#[cfg(feature = "watchman")]
+#[test]
+fn test_fsmonitor_watchman() {
+    if std::process::Command::new("watchman")
+        .arg("--version")
+        .output()
+        .is_err()
+    {
+        eprintln!("Skipping test because watchman is not installed on the system");
+        return;
+    }
+
+    let mut test_workspace = TestWorkspace::init();
+    let repo = &test_workspace.repo;
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+    let ws = &mut test_workspace.workspace;
+
+    let foo_path = RepoPath::from_internal_string("foo");
+    let bar_path = RepoPath::from_internal_string("bar");
+    let ignored_path = RepoPath::from_internal_string("ignored");
+    let gitignore_path = RepoPath::from_internal_string(".gitignore");
+
+    let snapshot = |mut locked_ws: LockedWorkspace| {
+        let (tree_id, _stats) = locked_ws
+            .locked_wc()
+            .snapshot(&SnapshotOptions {
+                fsmonitor_settings: FsmonitorSettings::Watchman(WatchmanConfig {
+                    register_trigger: false,
+                }),
+                ..SnapshotOptions::empty_for_test()
+            })
+            .unwrap();
+        locked_ws.finish(repo.op_id().clone()).unwrap();
+        repo.store().get_root_tree(&tree_id).unwrap()
+    };
+
+    // The first query returns a fresh instance, so the working copy is scanned
+    testutils::write_working_copy_file(&workspace_root, foo_path, "foo\n");
+    testutils::write_working_copy_file(&workspace_root, ignored_path, "ignored\n");
+    testutils::write_working_copy_file(&workspace_root, gitignore_path, "ignored\n");
+    let tree = snapshot(ws.start_working_copy_mutation().unwrap());
+    assert!(tree.path_value(foo_path).unwrap().is_present());
+    assert!(tree.path_value(ignored_path).unwrap().is_absent());
+
+    // Added and deleted files are picked up from the query result
+    std::fs::remove_file(foo_path.to_fs_path_unchecked(&workspace_root)).unwrap();
+    testutils::write_working_copy_file(&workspace_root, bar_path, "bar\n");
+    let tree = snapshot(ws.start_working_copy_mutation().unwrap());
+    assert!(tree.path_value(foo_path).unwrap().is_absent());
+    assert!(tree.path_value(bar_path).unwrap().is_present());
+
+    // Files which are no longer ignored are picked up
+    testutils::write_working_copy_file(&workspace_root, gitignore_path, "");
+    let tree = snapshot(ws.start_working_copy_mutation().unwrap());
+    assert!(tree.path_value(ignored_path).unwrap().is_present());
+}
+
 #[test]
 fn test_snapshot_max_new_file_size() {
     let mut test_workspace = TestWorkspace::init();
@@ -18,17 +18,20 @@ use jj_lib::local_working_copy::LocalWorkingCopy;
 use jj_lib::matchers::EverythingMatcher;
 use jj_lib::repo::Repo;
 use jj_lib::repo_path::RepoPath;
-use jj_lib::repo_path::RepoPathBuf;
 use jj_lib::working_copy::CheckoutOptions;
 use jj_lib::working_copy::CheckoutStats;
+use jj_lib::working_copy::SparsePattern;
 use jj_lib::working_copy::WorkingCopy;
 use pollster::FutureExt as _;
 use testutils::commit_with_tree;
 use testutils::create_tree;
 use testutils::TestWorkspace;
 
-fn to_owned_path_vec(paths: &[&RepoPath]) -> Vec<RepoPathBuf> {
-    paths.iter().map(|&path| path.to_owned()).collect()
+fn to_include_patterns(paths: &[&RepoPath]) -> Vec<SparsePattern> {
+    paths
+        .iter()
+        .map(|&path| SparsePattern::Include(path.to_owned()))
+        .collect()
 }
 
 #[test]
@@ -73,7 +76,7 @@ fn test_sparse_checkout() {
 
     // Set sparse patterns to only dir1/
     let mut locked_ws = ws.start_working_copy_mutation().unwrap();
-    let sparse_patterns = to_owned_path_vec(&[dir1_path]);
+    let sparse_patterns = to_include_patterns(&[dir1_path]);
     let stats = locked_ws
         .locked_wc()
         .set_sparse_patterns(sparse_patterns.clone(), &CheckoutOptions::empty_for_test())
@@ -85,6 +88,9 @@ fn test_sparse_checkout() {
             added_files: 0,
             removed_files: 3,
             skipped_files: 0,
+            skipped_paths: vec![],
+            fake_symlinks: 0,
+            bytes_written: 0,
         }
     );
     assert_eq!(
@@ -133,7 +139,7 @@ fn test_sparse_checkout() {
 
     // Set sparse patterns to file2, dir1/subdir1/ and dir2/
     let mut locked_wc = wc.start_mutation().unwrap();
-    let sparse_patterns = to_owned_path_vec(&[root_file1_path, dir1_subdir1_path, dir2_path]);
+    let sparse_patterns = to_include_patterns(&[root_file1_path, dir1_subdir1_path, dir2_path]);
     let stats = locked_wc
         .set_sparse_patterns(sparse_patterns.clone(), &CheckoutOptions::empty_for_test())
         .unwrap();
@@ -144,6 +150,9 @@ fn test_sparse_checkout() {
             added_files: 2,
             removed_files: 2,
             skipped_files: 0,
+            skipped_paths: vec![],
+            fake_symlinks: 0,
+            bytes_written: 16,
         }
     );
     assert_eq!(locked_wc.sparse_patterns().unwrap(), sparse_patterns);
@@ -212,7 +221,7 @@ fn test_sparse_commit() {
         .workspace
         .start_working_copy_mutation()
         .unwrap();
-    let sparse_patterns = to_owned_path_vec(&[dir1_path]);
+    let sparse_patterns = to_include_patterns(&[dir1_path]);
     locked_ws
         .locked_wc()
         .set_sparse_patterns(sparse_patterns, &CheckoutOptions::empty_for_test())
@@ -253,7 +262,7 @@ fn test_sparse_commit() {
         .workspace
         .start_working_copy_mutation()
         .unwrap();
-    let sparse_patterns = to_owned_path_vec(&[dir1_path, dir2_path]);
+    let sparse_patterns = to_include_patterns(&[dir1_path, dir2_path]);
     locked_ws
         .locked_wc()
         .set_sparse_patterns(sparse_patterns, &CheckoutOptions::empty_for_test())
@@ -288,7 +297,7 @@ fn test_sparse_commit_gitignore() {
         .workspace
         .start_working_copy_mutation()
         .unwrap();
-    let sparse_patterns = to_owned_path_vec(&[dir1_path]);
+    let sparse_patterns = to_include_patterns(&[dir1_path]);
     locked_ws
         .locked_wc()
         .set_sparse_patterns(sparse_patterns, &CheckoutOptions::empty_for_test())
@@ -316,3 +325,129 @@ fn test_sparse_commit_gitignore() {
     assert_eq!(entries.len(), 1);
     assert_eq!(entries[0].0.as_ref(), dir1_file2_path);
 }
+
+#[test]
+fn test_sparse_checkout_exclude() {
+    let mut test_workspace = TestWorkspace::init();
+    let repo = &test_workspace.repo;
+    let working_copy_path = test_workspace.workspace.workspace_root().to_owned();
+
+    let root_file1_path = RepoPath::from_internal_string("file1");
+    let dir1_path = RepoPath::from_internal_string("dir1");
+    let dir1_file1_path = RepoPath::from_internal_string("dir1/file1");
+    let dir1_subdir1_path = RepoPath::from_internal_string("dir1/subdir1");
+    let dir1_subdir1_file1_path = RepoPath::from_internal_string("dir1/subdir1/file1");
+    let dir1_subdir1_file2_path = RepoPath::from_internal_string("dir1/subdir1/file2");
+
+    let tree = create_tree(
+        repo,
+        &[
+            (root_file1_path, "contents"),
+            (dir1_file1_path, "contents"),
+            (dir1_subdir1_file1_path, "contents"),
+        ],
+    );
+    let commit = commit_with_tree(repo.store(), tree.id());
+
+    test_workspace
+        .workspace
+        .check_out(
+            repo.op_id().clone(),
+            None,
+            &commit,
+            &CheckoutOptions::empty_for_test(),
+        )
+        .unwrap();
+    let ws = &mut test_workspace.workspace;
+
+    // Check out dir1/ except for dir1/subdir1/
+    let mut locked_ws = ws.start_working_copy_mutation().unwrap();
+    let sparse_patterns = vec![
+        SparsePattern::Include(dir1_path.to_owned()),
+        SparsePattern::Exclude(dir1_subdir1_path.to_owned()),
+    ];
+    let stats = locked_ws
+        .locked_wc()
+        .set_sparse_patterns(sparse_patterns.clone(), &CheckoutOptions::empty_for_test())
+        .unwrap();
+    assert_eq!(
+        stats,
+        CheckoutStats {
+            updated_files: 0,
+            added_files: 0,
+            removed_files: 2,
+            skipped_files: 0,
+            skipped_paths: vec![],
+            fake_symlinks: 0,
+            bytes_written: 0,
+        }
+    );
+    assert!(!root_file1_path
+        .to_fs_path_unchecked(&working_copy_path)
+        .exists());
+    assert!(dir1_file1_path
+        .to_fs_path_unchecked(&working_copy_path)
+        .exists());
+    assert!(!dir1_subdir1_file1_path
+        .to_fs_path_unchecked(&working_copy_path)
+        .exists());
+    locked_ws.finish(repo.op_id().clone()).unwrap();
+
+    // The patterns, including the exclusion, are persisted
+    let wc: &LocalWorkingCopy = ws.working_copy().as_any().downcast_ref().unwrap();
+    let wc = LocalWorkingCopy::load(
+        repo.store().clone(),
+        ws.workspace_root().to_path_buf(),
+        wc.state_path().to_path_buf(),
+    );
+    assert_eq!(wc.sparse_patterns().unwrap(), sparse_patterns);
+    assert_eq!(
+        wc.file_states().unwrap().paths().collect_vec(),
+        vec![dir1_file1_path]
+    );
+
+    // Untracked files in the excluded directory are not snapshotted
+    std::fs::create_dir_all(dir1_subdir1_path.to_fs_path_unchecked(&working_copy_path)).unwrap();
+    std::fs::write(
+        dir1_subdir1_file2_path.to_fs_path_unchecked(&working_copy_path),
+        "contents",
+    )
+    .unwrap();
+    let modified_tree = test_workspace.snapshot().unwrap();
+    assert_eq!(modified_tree.id(), commit.tree_id().clone());
+
+    // Including the directory again restores its files and leaves the
+    // untracked file alone
+    let mut locked_ws = test_workspace
+        .workspace
+        .start_working_copy_mutation()
+        .unwrap();
+    let stats = locked_ws
+        .locked_wc()
+        .set_sparse_patterns(
+            to_include_patterns(&[dir1_path]),
+            &CheckoutOptions::empty_for_test(),
+        )
+        .unwrap();
+    assert_eq!(
+        stats,
+        CheckoutStats {
+            updated_files: 0,
+            added_files: 1,
+            removed_files: 0,
+            skipped_files: 0,
+            skipped_paths: vec![],
+            fake_symlinks: 0,
+            bytes_written: 8,
+        }
+    );
+    assert!(dir1_subdir1_file1_path
+        .to_fs_path_unchecked(&working_copy_path)
+        .exists());
+    assert!(dir1_subdir1_file2_path
+        .to_fs_path_unchecked(&working_copy_path)
+        .exists());
+    locked_ws
+        .finish(test_workspace.repo.op_id().clone())
+        .unwrap();
+}
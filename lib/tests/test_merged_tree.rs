@@ -1038,6 +1038,65 @@ fn test_diff_conflicted() {
     diff_stream_equals_iter(&right_merged, &left_merged, &EverythingMatcher);
 }
 
+/// Diff of a generated deep tree should be the same regardless of the number
+/// of concurrent tree reads.
+#[test]
+fn test_diff_deep_tree_concurrency() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    // Directories nested 4 levels deep with a fan-out of 3, each with 3 files
+    let dirs = (0..4).fold(vec![String::new()], |parents, _| {
+        parents
+            .iter()
+            .flat_map(|parent| (0..3).map(move |i| format!("{parent}dir{i}/")))
+            .collect()
+    });
+    let paths = dirs
+        .iter()
+        .flat_map(|dir| {
+            (0..3).map(move |i| RepoPathBuf::from_internal_string(format!("{dir}file{i}")))
+        })
+        .collect_vec();
+    let left_contents = paths
+        .iter()
+        .map(|path| (path.as_ref(), "left"))
+        .collect_vec();
+    // Modify every other file, and replace a directory by a file
+    let replaced_dir = RepoPath::from_internal_string("dir1/dir2");
+    let right_contents = paths
+        .iter()
+        .enumerate()
+        .filter(|(_, path)| !path.starts_with(replaced_dir))
+        .map(|(i, path)| (path.as_ref(), if i % 2 == 0 { "right" } else { "left" }))
+        .chain([(replaced_dir, "file")])
+        .collect_vec();
+    let left_merged = MergedTree::resolved(create_single_tree(repo, &left_contents));
+    let right_merged = MergedTree::resolved(create_single_tree(repo, &right_contents));
+
+    let prefix_matcher = PrefixMatcher::new([RepoPath::from_internal_string("dir1")]);
+    let matchers: [&dyn Matcher; 2] = [&EverythingMatcher, &prefix_matcher];
+    for matcher in matchers {
+        for (trees1, trees2) in [
+            (left_merged.as_merge(), right_merged.as_merge()),
+            (right_merged.as_merge(), left_merged.as_merge()),
+        ] {
+            let iter_diff = TreeDiffIterator::new(trees1, trees2, matcher)
+                .map(diff_entry_tuple)
+                .collect_vec();
+            assert!(!iter_diff.is_empty());
+            for max_concurrent_reads in [1, 2, 100] {
+                let stream_diff: Vec<_> =
+                    TreeDiffStreamImpl::new(trees1, trees2, matcher, max_concurrent_reads)
+                        .map(diff_entry_tuple)
+                        .collect()
+                        .block_on();
+                assert_eq!(stream_diff, iter_diff);
+            }
+        }
+    }
+}
+
 #[test]
 fn test_diff_dir_file() {
     let test_repo = TestRepo::init();
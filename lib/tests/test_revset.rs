@@ -24,6 +24,7 @@ use jj_lib::backend::MillisSinceEpoch;
 use jj_lib::backend::Signature;
 use jj_lib::backend::Timestamp;
 use jj_lib::commit::Commit;
+use jj_lib::fileset::FilesetAliasesMap;
 use jj_lib::fileset::FilesetExpression;
 use jj_lib::git;
 use jj_lib::graph::reverse_graph;
@@ -919,8 +920,10 @@ fn resolve_commit_ids_in_workspace(
         cwd: cwd.unwrap_or_else(|| workspace.workspace_root()).to_owned(),
         base: workspace.workspace_root().to_owned(),
     };
+    let fileset_aliases_map = FilesetAliasesMap::new();
     let workspace_ctx = RevsetWorkspaceContext {
         path_converter: &path_converter,
+        fileset_aliases_map: &fileset_aliases_map,
         workspace_id: workspace.workspace_id(),
     };
     let aliases_map = RevsetAliasesMap::default();
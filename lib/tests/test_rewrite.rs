@@ -24,12 +24,16 @@ use jj_lib::op_store::RemoteRefState;
 use jj_lib::op_store::WorkspaceId;
 use jj_lib::repo::Repo;
 use jj_lib::repo_path::RepoPath;
+use jj_lib::rewrite::merge_commit_trees;
+use jj_lib::rewrite::merge_commit_trees_following_renames;
 use jj_lib::rewrite::rebase_commit_with_options;
 use jj_lib::rewrite::restore_tree;
 use jj_lib::rewrite::CommitRewriter;
 use jj_lib::rewrite::EmptyBehaviour;
+use jj_lib::rewrite::FollowedRename;
 use jj_lib::rewrite::RebaseOptions;
 use jj_lib::rewrite::RewriteRefsOptions;
+use maplit::btreemap;
 use maplit::hashmap;
 use maplit::hashset;
 use test_case::test_case;
@@ -41,6 +45,7 @@ use testutils::rebase_descendants_with_options_return_map;
 use testutils::write_random_commit;
 use testutils::CommitGraphBuilder;
 use testutils::TestRepo;
+use testutils::TestRepoBackend;
 
 #[test]
 fn test_restore_tree() {
@@ -76,6 +81,54 @@ fn test_restore_tree() {
     assert_eq!(restored, expected.id());
 }
 
+#[test]
+fn test_merge_commit_trees_following_renames() {
+    let test_repo = TestRepo::init_with_backend(TestRepoBackend::Git);
+    let repo = &test_repo.repo;
+
+    let old_path = RepoPath::from_internal_string("old");
+    let new_path = RepoPath::from_internal_string("new");
+    let mut tx = repo.start_transaction();
+    let mut create_commit = |parent: &Commit, tree: &MergedTree| {
+        tx.repo_mut()
+            .new_commit(vec![parent.id().clone()], tree.id())
+            .write()
+            .unwrap()
+    };
+    let base_tree = create_tree(repo, &[(old_path, "1\n2\n3\n")]);
+    let base = create_commit(&repo.store().root_commit(), &base_tree);
+    let renamed_tree = create_tree(repo, &[(new_path, "1\n2\n3\n")]);
+    let renamed = create_commit(&base, &renamed_tree);
+    let modified_tree = create_tree(repo, &[(old_path, "1\ntwo\n3\n")]);
+    let modified = create_commit(&base, &modified_tree);
+    let repo = tx.commit("test").unwrap();
+
+    // Plain merges still produce a modify/delete conflict
+    let commits = [renamed, modified];
+    let tree = merge_commit_trees(repo.as_ref(), &commits).unwrap();
+    assert!(!tree.path_value(old_path).unwrap().is_resolved());
+
+    // The modification is moved to the renamed path
+    let (tree, followed_renames) =
+        merge_commit_trees_following_renames(repo.as_ref(), &commits).unwrap();
+    let expected_tree = create_tree(&repo, &[(new_path, "1\ntwo\n3\n")]);
+    assert_eq!(tree.id(), expected_tree.id());
+    assert_eq!(
+        followed_renames,
+        vec![FollowedRename {
+            source: old_path.to_owned(),
+            target: new_path.to_owned(),
+            resolved: true,
+        }]
+    );
+
+    // Merges of a single commit don't look for renames
+    let (tree, followed_renames) =
+        merge_commit_trees_following_renames(repo.as_ref(), &commits[1..]).unwrap();
+    assert_eq!(tree.id(), modified_tree.id());
+    assert_eq!(followed_renames, vec![]);
+}
+
 #[test]
 fn test_rebase_descendants_sideways() {
     let test_repo = TestRepo::init();
@@ -838,6 +891,134 @@ fn test_rebase_descendants_repeated() {
     );
 }
 
+#[test]
+fn test_rebase_descendants_multiple_in_chain() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    // Commits B and D were rewritten in the same transaction. Each descendant
+    // should be rebased only once, by a single rebase of the whole chain. D2
+    // was written on top of the old C, so it gets rebased too.
+    //
+    // E
+    // D D2
+    // |/
+    // C
+    // B B2
+    // |/
+    // A
+    let mut tx = repo.start_transaction();
+    let mut graph_builder = CommitGraphBuilder::new(tx.repo_mut());
+    let commit_a = graph_builder.initial_commit();
+    let commit_b = graph_builder.commit_with_parents(&[&commit_a]);
+    let commit_c = graph_builder.commit_with_parents(&[&commit_b]);
+    let commit_d = graph_builder.commit_with_parents(&[&commit_c]);
+    let commit_e = graph_builder.commit_with_parents(&[&commit_d]);
+
+    let commit_b2 = tx
+        .repo_mut()
+        .rewrite_commit(&commit_b)
+        .set_description("b2")
+        .write()
+        .unwrap();
+    let commit_d2 = tx
+        .repo_mut()
+        .rewrite_commit(&commit_d)
+        .set_description("d2")
+        .write()
+        .unwrap();
+    let rebase_map =
+        rebase_descendants_with_options_return_map(tx.repo_mut(), &RebaseOptions::default());
+    assert_eq!(rebase_map.len(), 3);
+    let new_commit_c =
+        assert_rebased_onto(tx.repo_mut(), &rebase_map, &commit_c, &[commit_b2.id()]);
+    let new_commit_d2 =
+        assert_rebased_onto(tx.repo_mut(), &rebase_map, &commit_d2, &[new_commit_c.id()]);
+    assert_eq!(new_commit_d2.description(), "d2");
+    let new_commit_e =
+        assert_rebased_onto(tx.repo_mut(), &rebase_map, &commit_e, &[new_commit_d2.id()]);
+
+    assert_eq!(
+        *tx.repo().view().heads(),
+        hashset! {
+            new_commit_e.id().clone(),
+        }
+    );
+}
+
+#[test]
+fn test_rebase_descendants_deferred() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    // B is rewritten twice while rebasing is deferred. C keeps the old B as
+    // parent until the rewrites are resolved.
+    //
+    // C
+    // B B2 B3
+    // |/
+    // A
+    let mut tx = repo.start_transaction();
+    let mut graph_builder = CommitGraphBuilder::new(tx.repo_mut());
+    let commit_a = graph_builder.initial_commit();
+    let commit_b = graph_builder.commit_with_parents(&[&commit_a]);
+    let commit_c = graph_builder.commit_with_parents(&[&commit_b]);
+    let repo = tx.commit("test").unwrap();
+
+    let mut tx = repo.start_transaction();
+    tx.repo_mut().set_defer_descendant_rebase(true);
+    let commit_b2 = tx
+        .repo_mut()
+        .rewrite_commit(&commit_b)
+        .set_description("b2")
+        .write()
+        .unwrap();
+    assert_eq!(tx.repo_mut().rebase_descendants().unwrap(), 0);
+    let repo = tx.commit("test").unwrap();
+    assert_eq!(
+        *repo.view().deferred_rewrites(),
+        btreemap! { commit_b.id().clone() => vec![commit_b2.id().clone()] }
+    );
+    assert_eq!(
+        *repo.view().heads(),
+        hashset! { commit_b2.id().clone(), commit_c.id().clone() }
+    );
+    // The old commit doesn't make the change divergent
+    assert_eq!(
+        repo.resolve_change_id(commit_b.change_id()),
+        Some(vec![commit_b2.id().clone()])
+    );
+
+    // Rewriting the new commit again updates the deferred rewrite
+    let mut tx = repo.start_transaction();
+    tx.repo_mut().set_defer_descendant_rebase(true);
+    let commit_b3 = tx
+        .repo_mut()
+        .rewrite_commit(&commit_b2)
+        .set_description("b3")
+        .write()
+        .unwrap();
+    tx.repo_mut().rebase_descendants().unwrap();
+    let repo = tx.commit("test").unwrap();
+    assert_eq!(
+        *repo.view().deferred_rewrites(),
+        btreemap! { commit_b.id().clone() => vec![commit_b3.id().clone()] }
+    );
+
+    // Rebasing without deferring resolves the deferred rewrites
+    let mut tx = repo.start_transaction();
+    let rebase_map =
+        rebase_descendants_with_options_return_map(tx.repo_mut(), &RebaseOptions::default());
+    assert_eq!(rebase_map.len(), 1);
+    let new_commit_c =
+        assert_rebased_onto(tx.repo_mut(), &rebase_map, &commit_c, &[commit_b3.id()]);
+    assert!(tx.repo().view().deferred_rewrites().is_empty());
+    assert_eq!(
+        *tx.repo().view().heads(),
+        hashset! { new_commit_c.id().clone() }
+    );
+}
+
 #[test]
 fn test_rebase_descendants_contents() {
     let test_repo = TestRepo::init();
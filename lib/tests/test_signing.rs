@@ -1,3 +1,4 @@
+use jj_lib::backend::CommitId;
 use jj_lib::backend::MillisSinceEpoch;
 use jj_lib::backend::Signature;
 use jj_lib::backend::Timestamp;
@@ -8,6 +9,7 @@ use jj_lib::settings::UserSettings;
 use jj_lib::signing::SigStatus;
 use jj_lib::signing::SignBehavior;
 use jj_lib::signing::Signer;
+use jj_lib::signing::SigningBackend as _;
 use jj_lib::signing::Verification;
 use jj_lib::test_signing_backend::TestSigningBackend;
 use test_case::test_case;
@@ -208,3 +210,35 @@ fn drop_behavior(backend: TestRepoBackend) {
     let rewritten_commit = repo.store().get_commit(rewritten.id()).unwrap();
     assert_eq!(rewritten_commit.verification().unwrap(), None);
 }
+
+#[test]
+fn verification_statuses_and_cache() {
+    let signer = Signer::new(Some(Box::new(TestSigningBackend)), vec![]);
+    let sig = TestSigningBackend
+        .sign(b"data", Some("impeccable"))
+        .unwrap();
+
+    let good_id = CommitId::from_hex("01");
+    assert_eq!(
+        signer.verify(&good_id, b"data", &sig).unwrap(),
+        good_verification().unwrap()
+    );
+    // The result is cached per commit, so the signature isn't checked again.
+    assert_eq!(
+        signer.verify(&good_id, b"tampered", &sig).unwrap().status,
+        SigStatus::Good
+    );
+
+    let bad_id = CommitId::from_hex("02");
+    assert_eq!(
+        signer.verify(&bad_id, b"tampered", &sig).unwrap().status,
+        SigStatus::Bad
+    );
+
+    // No backend can read the signature.
+    let unknown_id = CommitId::from_hex("03");
+    assert_eq!(
+        signer.verify(&unknown_id, b"data", b"garbage").unwrap(),
+        Verification::unknown()
+    );
+}
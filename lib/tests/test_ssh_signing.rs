@@ -20,6 +20,7 @@ use std::io::Write;
 use std::os::unix::prelude::PermissionsExt;
 use std::path::Path;
 use std::path::PathBuf;
+use std::process::Command;
 
 use jj_lib::signing::SigStatus;
 use jj_lib::signing::SigningBackend;
@@ -167,3 +168,56 @@ fn ssh_signing_missing_allowed_signers() {
     assert_eq!(check.status, SigStatus::Unknown);
     assert_eq!(check.display.unwrap(), "Signature OK. Unknown principal");
 }
+
+#[test]
+fn ssh_signing_generated_key_pair() {
+    let keys_dir = tempfile::Builder::new()
+        .prefix("jj-test-signing-keys-")
+        .tempdir()
+        .unwrap();
+    let private_key_path = keys_dir.path().join("key");
+    let status = Command::new("ssh-keygen")
+        .args(["-q", "-t", "ed25519", "-N", "", "-C", "", "-f"])
+        .arg(&private_key_path)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let public_key = fs::read_to_string(private_key_path.with_extension("pub")).unwrap();
+    let allowed_signers_path = keys_dir.path().join("allowed_signers");
+    fs::write(
+        &allowed_signers_path,
+        format!("alice@example.com {public_key}"),
+    )
+    .unwrap();
+
+    let backend = SshBackend::new(
+        "ssh-keygen".into(),
+        Some(allowed_signers_path.into_os_string()),
+    );
+    let data = b"tree 1234\n\ncommit message\n";
+
+    let signature = backend
+        .sign(data, Some(private_key_path.to_str().unwrap()))
+        .unwrap();
+    assert!(backend.can_read(&signature));
+
+    let check = backend.verify(data, &signature).unwrap();
+    assert_eq!(check.status, SigStatus::Good);
+    assert_eq!(check.display.unwrap(), "alice@example.com");
+
+    // Tampered commit data
+    let check = backend
+        .verify(b"tree 1234\n\ntampered message\n", &signature)
+        .unwrap();
+    assert_eq!(check.status, SigStatus::Bad);
+    assert_eq!(check.display.unwrap(), "alice@example.com");
+
+    // Signed by a key which isn't in the allowed signers
+    let env = SshEnvironment::new().unwrap();
+    let signature = backend
+        .sign(data, Some(env.private_key_path.to_str().unwrap()))
+        .unwrap();
+    let check = backend.verify(data, &signature).unwrap();
+    assert_eq!(check.status, SigStatus::Unknown);
+    assert_eq!(check.display.unwrap(), "Signature OK. Unknown principal");
+}
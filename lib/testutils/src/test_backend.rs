@@ -24,9 +24,11 @@ use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::sync::MutexGuard;
+use std::task::Poll;
 use std::time::SystemTime;
 
 use async_trait::async_trait;
+use futures::future;
 use futures::stream;
 use futures::stream::BoxStream;
 use jj_lib::backend::make_root_commit;
@@ -66,6 +68,9 @@ pub struct TestBackendData {
     files: HashMap<RepoPathBuf, HashMap<FileId, Vec<u8>>>,
     symlinks: HashMap<RepoPathBuf, HashMap<SymlinkId, String>>,
     conflicts: HashMap<RepoPathBuf, HashMap<ConflictId, Conflict>>,
+    yield_on_file_read: bool,
+    num_file_reads_in_flight: usize,
+    max_file_reads_in_flight: usize,
 }
 
 #[derive(Clone, Default)]
@@ -140,6 +145,18 @@ impl TestBackend {
     pub fn remove_commit_unchecked(&self, id: &CommitId) {
         self.locked_data().commits.remove(id);
     }
+
+    /// Makes `read_file()` yield to the executor once before returning, so
+    /// that concurrent reads can be observed.
+    pub fn set_yield_on_file_read(&self, value: bool) {
+        self.locked_data().yield_on_file_read = value;
+    }
+
+    /// Returns the maximum number of `read_file()` calls that were in flight
+    /// at the same time.
+    pub fn max_file_reads_in_flight(&self) -> usize {
+        self.locked_data().max_file_reads_in_flight
+    }
 }
 
 impl Debug for TestBackend {
@@ -184,8 +201,30 @@ impl Backend for TestBackend {
     }
 
     async fn read_file(&self, path: &RepoPath, id: &FileId) -> BackendResult<Box<dyn Read>> {
-        match self
-            .locked_data()
+        let yield_on_file_read = {
+            let mut data = self.locked_data();
+            data.num_file_reads_in_flight += 1;
+            data.max_file_reads_in_flight = data
+                .max_file_reads_in_flight
+                .max(data.num_file_reads_in_flight);
+            data.yield_on_file_read
+        };
+        if yield_on_file_read {
+            let mut yielded = false;
+            future::poll_fn(|cx| {
+                if yielded {
+                    Poll::Ready(())
+                } else {
+                    yielded = true;
+                    cx.waker().wake_by_ref();
+                    Poll::Pending
+                }
+            })
+            .await;
+        }
+        let mut data = self.locked_data();
+        data.num_file_reads_in_flight -= 1;
+        match data
             .files
             .get(path)
             .and_then(|items| items.get(id))